@@ -0,0 +1,42 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Derive macros of Forest. The generated code refers to items of the `forest-filecoin` crate
+//! through `crate::` paths, so the macros can only be used from within that crate.
+
+mod lotus_json;
+
+use proc_macro::TokenStream;
+
+/// Derives `HasLotusJson` for a struct with named fields, generating its lotus JSON struct, the
+/// conversions, and a snapshot test.
+///
+/// The lotus JSON struct is named after the struct with a `LotusJson` suffix. Its fields are
+/// `PascalCase` and (de)serialized through their own lotus JSON representation. It derives `Eq`, so
+/// the lotus JSON types of the fields must implement it.
+///
+/// # Container attributes
+/// - `#[lotus_json(remote = "path::to::Type")]`: implement `HasLotusJson` for a type defined in
+///   another crate instead of the annotated struct, which must then mirror the fields of the
+///   remote type. May be repeated, e.g. for every version of an actor type.
+/// - `#[lotus_json(rename_all = "camelCase")]`: override the case of the JSON fields.
+/// - `#[lotus_json(snapshot(json = r#"{ ... }"#, value = "expr"))]`: a snapshot of the lotus JSON
+///   and the value it represents. When `value` is omitted, the value is parsed from the JSON, so
+///   the snapshot only checks that the JSON round-trips. At least one snapshot is required.
+///
+/// # Field attributes
+/// - `#[lotus_json(rename = "Name")]`: the name of the JSON field.
+/// - `#[lotus_json(base64)]`: the field holds bytes, serialized as a base64 string.
+/// - `#[lotus_json(cid)]`: the field holds a CID, serialized as `{ "/": "bafy..." }`.
+/// - `#[lotus_json(optional)]`: the field is an [`Option`] that is omitted from the JSON when
+///   unset.
+/// - `#[lotus_json(with = "module")]`: convert the field with `module::into_lotus_json` and
+///   `module::from_lotus_json`. The declared type is the lotus JSON type of the field, which is
+///   serialized as is.
+#[proc_macro_derive(HasLotusJson, attributes(lotus_json))]
+pub fn derive_has_lotus_json(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    lotus_json::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,361 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Expr, Fields, Ident, LitStr, Path, Type, spanned::Spanned as _,
+};
+
+struct Snapshot {
+    json: LitStr,
+    value: Option<Expr>,
+}
+
+#[derive(Default)]
+struct Container {
+    remotes: Vec<Path>,
+    rename_all: Option<LitStr>,
+    snapshots: Vec<Snapshot>,
+}
+
+enum Conversion {
+    /// The field is converted with [`Into`] and serialized as lotus JSON.
+    Into,
+    Base64,
+    Cid,
+    With(Path),
+}
+
+struct Field {
+    ident: Ident,
+    ty: Type,
+    docs: Vec<Attribute>,
+    rename: Option<LitStr>,
+    optional: bool,
+    conversion: Conversion,
+}
+
+fn parse_container(attrs: &[Attribute]) -> syn::Result<Container> {
+    let mut container = Container::default();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("lotus_json"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("remote") {
+                container
+                    .remotes
+                    .push(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("rename_all") {
+                container.rename_all = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("snapshot") {
+                let mut json = None;
+                let mut value = None;
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("json") {
+                        json = Some(meta.value()?.parse::<LitStr>()?);
+                    } else if meta.path.is_ident("value") {
+                        value = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                    } else {
+                        return Err(meta.error("expected `json` or `value`"));
+                    }
+                    Ok(())
+                })?;
+                let json = json.ok_or_else(|| meta.error("missing snapshot `json`"))?;
+                container.snapshots.push(Snapshot { json, value });
+            } else {
+                return Err(meta.error("unknown lotus_json container attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(container)
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<Field> {
+    let ident = field
+        .ident
+        .clone()
+        .ok_or_else(|| syn::Error::new(field.span(), "expected a named field"))?;
+    let mut rename = None;
+    let mut optional = false;
+    let mut conversion = Conversion::Into;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("lotus_json"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                rename = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("optional") {
+                optional = true;
+            } else if meta.path.is_ident("base64") {
+                conversion = Conversion::Base64;
+            } else if meta.path.is_ident("cid") {
+                conversion = Conversion::Cid;
+            } else if meta.path.is_ident("with") {
+                conversion = Conversion::With(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error("unknown lotus_json field attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(Field {
+        ident,
+        ty: field.ty.clone(),
+        docs: field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .cloned()
+            .collect(),
+        rename,
+        optional,
+        conversion,
+    })
+}
+
+/// Converts a `PascalCase` identifier to `snake_case`, keeping acronyms together, e.g.
+/// `EVMConstructorParams` becomes `evm_constructor_params`.
+fn to_snake_case(ident: &Ident) -> String {
+    let chars = ident.to_string().chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev_lower = chars[i - 1].is_lowercase();
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if prev_lower || (chars[i - 1].is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "HasLotusJson can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            input.span(),
+            "HasLotusJson can only be derived for structs with named fields",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "HasLotusJson cannot be derived for generic structs",
+        ));
+    }
+    let container = parse_container(&input.attrs)?;
+    if container.snapshots.is_empty() {
+        return Err(syn::Error::new(
+            input.span(),
+            "at least one `#[lotus_json(snapshot(json = \"...\"))]` is required",
+        ));
+    }
+    let fields = fields
+        .named
+        .iter()
+        .map(parse_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let lotus_json_ident = format_ident!("{ident}LotusJson");
+    let schema_name = LitStr::new(&ident.to_string(), ident.span());
+    let rename_all = container
+        .rename_all
+        .clone()
+        .unwrap_or_else(|| LitStr::new("PascalCase", ident.span()));
+
+    let lotus_json_fields = fields.iter().map(|field| {
+        let Field {
+            ident,
+            ty,
+            docs,
+            rename,
+            optional,
+            conversion,
+        } = field;
+        let rename = rename.as_ref().map(|name| quote!(#[serde(rename = #name)]));
+        let optional =
+            optional.then(|| quote!(#[serde(skip_serializing_if = "Option::is_none", default)]));
+        let (ty, with) = match conversion {
+            Conversion::Base64 => (
+                quote!(::std::vec::Vec<u8>),
+                quote! {
+                    #[schemars(with = "String")]
+                    #[serde(with = "crate::lotus_json::base64_standard")]
+                },
+            ),
+            Conversion::Cid => (
+                quote!(::cid::Cid),
+                quote! {
+                    #[schemars(with = "crate::lotus_json::LotusJson<::cid::Cid>")]
+                    #[serde(with = "crate::lotus_json")]
+                },
+            ),
+            Conversion::Into => {
+                // Use the schema of the lotus JSON type rather than `LotusJson<T>`, which is never
+                // inlined, so that primitive fields keep their inline schemas.
+                let schema_with = LitStr::new(
+                    &format!(
+                        "<{} as crate::lotus_json::HasLotusJson>::LotusJson",
+                        quote!(#ty)
+                    ),
+                    ty.span(),
+                );
+                (
+                    quote!(#ty),
+                    quote! {
+                        #[schemars(with = #schema_with)]
+                        #[serde(with = "crate::lotus_json")]
+                    },
+                )
+            }
+            // The conversion yields the lotus JSON representation.
+            Conversion::With(_) => (quote!(#ty), quote!()),
+        };
+        quote! {
+            #(#docs)*
+            #with
+            #rename
+            #optional
+            pub #ident: #ty
+        }
+    });
+
+    let field_idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let into_fields = fields.iter().map(
+        |Field {
+             ident, conversion, ..
+         }| match conversion {
+            Conversion::With(module) => quote!(#ident: #module::into_lotus_json(#ident)),
+            _ => quote!(#ident: #ident.into()),
+        },
+    );
+    let from_fields = fields.iter().map(
+        |Field {
+             ident, conversion, ..
+         }| match conversion {
+            Conversion::With(module) => quote!(#ident: #module::from_lotus_json(lotus_json.#ident)),
+            _ => quote!(#ident: lotus_json.#ident.into()),
+        },
+    );
+    let snapshots = container.snapshots.iter().map(|Snapshot { json, value }| {
+        let value = match value {
+            Some(value) => quote!(#value),
+            None => quote! {
+                <Self as crate::lotus_json::HasLotusJson>::from_lotus_json(
+                    ::serde_json::from_value(json.clone()).expect("invalid lotus JSON snapshot"),
+                )
+            },
+        };
+        quote! {{
+            let json: ::serde_json::Value =
+                ::serde_json::from_str(#json).expect("invalid JSON snapshot");
+            let value: Self = #value;
+            (json, value)
+        }}
+    });
+    let has_lotus_json = quote! {
+        type LotusJson = #lotus_json_ident;
+
+        #[cfg(test)]
+        fn snapshots() -> ::std::vec::Vec<(::serde_json::Value, Self)> {
+            ::std::vec![#(#snapshots),*]
+        }
+
+        #[allow(clippy::useless_conversion)]
+        fn into_lotus_json(self) -> Self::LotusJson {
+            let Self { #(#field_idents),* } = self;
+            #lotus_json_ident {
+                #(#into_fields),*
+            }
+        }
+
+        #[allow(clippy::useless_conversion)]
+        fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+            Self {
+                #(#from_fields),*
+            }
+        }
+    };
+
+    let domain_types = if container.remotes.is_empty() {
+        vec![quote!(#ident)]
+    } else {
+        container
+            .remotes
+            .iter()
+            .map(|remote| quote!(#remote))
+            .collect()
+    };
+    let impls = domain_types.iter().map(|domain_ty| {
+        quote! {
+            impl crate::lotus_json::HasLotusJson for #domain_ty {
+                #has_lotus_json
+            }
+        }
+    });
+    let tests = domain_types.iter().enumerate().map(|(i, domain_ty)| {
+        let test = format_ident!("snapshots_{i}");
+        quote! {
+            #[test]
+            fn #test() {
+                crate::lotus_json::assert_all_snapshots::<#domain_ty>();
+            }
+        }
+    });
+    let test_mod = format_ident!("{}_lotus_json_tests", to_snake_case(ident));
+
+    // The annotated struct only describes the fields of remote types, make sure it is not
+    // reported as dead code.
+    let remote_fields = (!container.remotes.is_empty()).then(|| {
+        quote! {
+            const _: () = {
+                #[allow(dead_code)]
+                fn fields(value: #ident) -> #ident {
+                    let #ident { #(#field_idents),* } = value;
+                    #ident { #(#field_idents),* }
+                }
+            };
+        }
+    });
+
+    Ok(quote! {
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            Eq,
+            ::serde::Serialize,
+            ::serde::Deserialize,
+            ::schemars::JsonSchema,
+        )]
+        #[serde(rename_all = #rename_all)]
+        #[schemars(rename = #schema_name)]
+        pub struct #lotus_json_ident {
+            #(#lotus_json_fields),*
+        }
+
+        #(#impls)*
+
+        #remote_fields
+
+        #[cfg(test)]
+        mod #test_mod {
+            use super::*;
+
+            #(#tests)*
+        }
+    })
+}
@@ -39,15 +39,22 @@ generate_markdown_section "forest-cli"
 
 generate_markdown_section "forest-cli" "chain"
 generate_markdown_section "forest-cli" "chain block"
+generate_markdown_section "forest-cli" "chain get-block"
+generate_markdown_section "forest-cli" "chain get-message"
+generate_markdown_section "forest-cli" "chain head"
 generate_markdown_section "forest-cli" "chain message"
 generate_markdown_section "forest-cli" "chain read-obj"
 generate_markdown_section "forest-cli" "chain set-head"
 generate_markdown_section "forest-cli" "chain prune"
+generate_markdown_section "forest-cli" "chain checkpoint"
+generate_markdown_section "forest-cli" "chain checkpoint add"
 generate_markdown_section "forest-cli" "chain list"
 
 generate_markdown_section "forest-cli" "auth"
 generate_markdown_section "forest-cli" "auth create-token"
 generate_markdown_section "forest-cli" "auth api-info"
+generate_markdown_section "forest-cli" "auth list"
+generate_markdown_section "forest-cli" "auth revoke"
 
 generate_markdown_section "forest-cli" "net"
 generate_markdown_section "forest-cli" "net peers"
@@ -56,6 +63,7 @@ generate_markdown_section "forest-cli" "net disconnect"
 
 generate_markdown_section "forest-cli" "sync"
 generate_markdown_section "forest-cli" "sync wait"
+generate_markdown_section "forest-cli" "sync status"
 generate_markdown_section "forest-cli" "sync check-bad"
 generate_markdown_section "forest-cli" "sync mark-bad"
 
@@ -67,6 +75,7 @@ generate_markdown_section "forest-cli" "mpool nonce"
 generate_markdown_section "forest-cli" "state"
 generate_markdown_section "forest-cli" "state fetch"
 generate_markdown_section "forest-cli" "state compute"
+generate_markdown_section "forest-cli" "state export-dag"
 
 generate_markdown_section "forest-cli" "config"
 
@@ -74,6 +83,9 @@ generate_markdown_section "forest-cli" "snapshot"
 generate_markdown_section "forest-cli" "snapshot export"
 
 generate_markdown_section "forest-cli" "send"
+generate_markdown_section "forest-cli" "evm"
+generate_markdown_section "forest-cli" "evm deploy"
+generate_markdown_section "forest-cli" "evm invoke"
 generate_markdown_section "forest-cli" "info"
 generate_markdown_section "forest-cli" "shutdown"
 
@@ -101,9 +113,17 @@ generate_markdown_section "forest-tool" "completion"
 
 generate_markdown_section "forest-tool" "benchmark"
 generate_markdown_section "forest-tool" "benchmark car-streaming"
+generate_markdown_section "forest-tool" "benchmark car-reads"
 generate_markdown_section "forest-tool" "benchmark graph-traversal"
 generate_markdown_section "forest-tool" "benchmark forest-encoding"
 generate_markdown_section "forest-tool" "benchmark export"
+generate_markdown_section "forest-tool" "benchmark replay-range"
+generate_markdown_section "forest-tool" "benchmark mpool-select"
+generate_markdown_section "forest-tool" "benchmark execute-tipsets"
+generate_markdown_section "forest-tool" "benchmark state-read"
+
+generate_markdown_section "forest-tool" "state"
+generate_markdown_section "forest-tool" "state provenance"
 
 generate_markdown_section "forest-tool" "state-migration"
 generate_markdown_section "forest-tool" "state-migration actor-bundle"
@@ -146,6 +166,11 @@ generate_markdown_section "forest-tool" "api test"
 
 generate_markdown_section "forest-tool" "net ping"
 
+generate_markdown_section "forest-tool" "light-client"
+
+generate_markdown_section "forest-tool" "hamt dump"
+generate_markdown_section "forest-tool" "amt dump"
+
 generate_markdown_section "forest-tool" "shed"
 generate_markdown_section "forest-tool" "shed summarize-tipsets"
 generate_markdown_section "forest-tool" "shed peer-id-from-key-pair"
@@ -155,6 +180,12 @@ generate_markdown_section "forest-tool" "shed openrpc"
 generate_markdown_section "forest-tool" "index"
 generate_markdown_section "forest-tool" "index backfill"
 
+generate_markdown_section "forest-tool" "test"
+generate_markdown_section "forest-tool" "test roundtrip"
+
+generate_markdown_section "forest-tool" "test-vectors"
+generate_markdown_section "forest-tool" "test-vectors run"
+
 generate_markdown_section "forest-dev" ""
 
 generate_markdown_section "forest-dev" "fetch-test-snapshots"
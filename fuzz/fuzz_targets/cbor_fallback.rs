@@ -0,0 +1,14 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+#![no_main]
+
+use forest::fuzz_private::fuzz_from_slice_with_fallback;
+use libfuzzer_sys::fuzz_target;
+
+// The first byte is the maximum nesting depth.
+fuzz_target!(|data: &[u8]| {
+    if let Some((&max_depth, data)) = data.split_first() {
+        fuzz_from_slice_with_fallback(data, max_depth.into());
+    }
+});
@@ -1,16 +1,23 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::key_management::KeyInfo;
+use crate::key_management::{KeyInfo, KeyStore};
+use crate::lotus_json::lotus_json_with_self;
 use crate::shim::crypto::SignatureType;
 use crate::utils::misc::env::is_env_truthy;
+use anyhow::Context as _;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, decode, encode, errors::Result as JWTResult};
 use rand::Rng;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// constant string that is used to identify the JWT secret key in `KeyStore`
 pub const JWT_IDENTIFIER: &str = "auth-jwt-private";
+/// Prefix of the issued JWT tokens in `KeyStore`, followed by their identifiers
+pub const JWT_TOKEN_PREFIX: &str = "auth-jwt-token-";
+/// constant string that is used to identify the admin token of the node in `KeyStore`
+pub const JWT_ADMIN_TOKEN: &str = "auth-jwt-admin";
 /// Admin permissions
 pub const ADMIN: &[&str] = &["read", "write", "sign", "admin"];
 /// Signing permissions
@@ -28,20 +35,186 @@ struct Claims {
     // Expiration time (as UTC timestamp)
     #[serde(default)]
     exp: Option<usize>,
+    // Token identifier, tokens issued before revocation was supported don't have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jti: Option<String>,
 }
 
-/// Create a new JWT Token
+/// Issued JWT token, as registered in `KeyStore` to be listed and revoked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct TokenInfo {
+    pub id: String,
+    pub allow: Vec<String>,
+    /// Issuance time, as UTC timestamp
+    pub issued_at: i64,
+    /// Expiration time, as UTC timestamp
+    pub expires_at: i64,
+    pub revoked: bool,
+}
+lotus_json_with_self!(TokenInfo);
+
+impl TokenInfo {
+    fn is_expired(&self) -> bool {
+        self.expires_at < Utc::now().timestamp()
+    }
+}
+
+/// Create a new JWT Token. It has no identifier, it isn't registered and can't be revoked.
 pub fn create_token(perms: Vec<String>, key: &[u8], token_exp: Duration) -> JWTResult<String> {
-    let exp_time = Utc::now() + token_exp;
     let payload = Claims {
         allow: perms,
-        exp: Some(exp_time.timestamp() as usize),
+        exp: Some((Utc::now() + token_exp).timestamp() as usize),
+        jti: None,
     };
     encode(&Header::default(), &payload, &EncodingKey::from_secret(key))
 }
 
+fn new_token(
+    perms: Vec<String>,
+    key: &[u8],
+    token_exp: Duration,
+) -> JWTResult<(String, TokenInfo)> {
+    let now = Utc::now();
+    let id = hex::encode(crate::utils::rand::forest_os_rng().r#gen::<[u8; 16]>());
+    let info = TokenInfo {
+        id: id.clone(),
+        allow: perms.clone(),
+        issued_at: now.timestamp(),
+        expires_at: (now + token_exp).timestamp(),
+        revoked: false,
+    };
+    let payload = Claims {
+        allow: perms,
+        exp: Some(info.expires_at as usize),
+        jti: Some(id),
+    };
+    let token = encode(&Header::default(), &payload, &EncodingKey::from_secret(key))?;
+    Ok((token, info))
+}
+
+/// Create a new JWT Token with the secret of the `KeyStore`, and register it there so that it can
+/// be revoked. The registered tokens that expired are removed, unless the expiration is not
+/// validated: expired tokens are then still valid, and revoked ones must stay revoked.
+pub fn issue_token(
+    keystore: &mut KeyStore,
+    perms: Vec<String>,
+    token_exp: Duration,
+) -> anyhow::Result<String> {
+    let ki = keystore.get(JWT_IDENTIFIER)?;
+    let (token, info) = new_token(perms, ki.private_key(), token_exp)?;
+    if !is_env_truthy("FOREST_JWT_DISABLE_EXP_VALIDATION") {
+        for expired in list_tokens(keystore)?
+            .into_iter()
+            .filter(TokenInfo::is_expired)
+        {
+            keystore.remove(&token_key(&expired.id))?;
+        }
+    }
+    put_token_info(keystore, &info)?;
+    Ok(token)
+}
+
+/// Return the admin token persisted in the `KeyStore`, or issue and persist a new one if there is
+/// none or it is no longer valid, e.g. it was revoked.
+pub fn admin_token(keystore: &mut KeyStore, token_exp: Duration) -> anyhow::Result<String> {
+    if let Ok(ki) = keystore.get(JWT_ADMIN_TOKEN)
+        && let Ok(token) = String::from_utf8(ki.private_key().clone())
+        && verify_token_with_keystore(&token, keystore).is_ok()
+    {
+        return Ok(token);
+    }
+    let token = issue_token(
+        keystore,
+        ADMIN.iter().map(ToString::to_string).collect(),
+        token_exp,
+    )?;
+    // Like the JWT secret, the key type is a placeholder.
+    let ki = KeyInfo::new(SignatureType::Bls, token.as_bytes().to_vec());
+    keystore.replace(JWT_ADMIN_TOKEN, ki)?;
+    Ok(token)
+}
+
+/// Return the tokens registered in the `KeyStore`, sorted by issuance time
+pub fn list_tokens(keystore: &KeyStore) -> anyhow::Result<Vec<TokenInfo>> {
+    let mut tokens = keystore
+        .list()
+        .into_iter()
+        .filter_map(|key| {
+            Some(get_token_info(
+                keystore,
+                key.strip_prefix(JWT_TOKEN_PREFIX)?,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    tokens.sort_by_key(|token| (token.issued_at, token.id.clone()));
+    Ok(tokens)
+}
+
+/// Revoke the registered token with the given identifier. Returns `false` if it was already
+/// revoked.
+pub fn revoke_token(keystore: &mut KeyStore, id: &str) -> anyhow::Result<bool> {
+    let mut info = get_token_info(keystore, id)?;
+    if info.revoked {
+        return Ok(false);
+    }
+    info.revoked = true;
+    put_token_info(keystore, &info)?;
+    Ok(true)
+}
+
+/// Return the identifier of a JWT token, without verifying it
+pub fn token_id(token: &str) -> anyhow::Result<String> {
+    use base64::{Engine as _, prelude::BASE64_URL_SAFE_NO_PAD};
+
+    let payload = token
+        .trim_start_matches("Bearer ")
+        .split('.')
+        .nth(1)
+        .context("malformed token")?;
+    let claims: Claims = serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(payload)?)?;
+    claims
+        .jti
+        .context("the token has no identifier, it was issued before tokens could be revoked")
+}
+
+fn token_key(id: &str) -> String {
+    format!("{JWT_TOKEN_PREFIX}{id}")
+}
+
+fn get_token_info(keystore: &KeyStore, id: &str) -> anyhow::Result<TokenInfo> {
+    let ki = keystore
+        .get(&token_key(id))
+        .with_context(|| format!("unknown token {id}"))?;
+    Ok(serde_json::from_slice(ki.private_key())?)
+}
+
+fn put_token_info(keystore: &mut KeyStore, info: &TokenInfo) -> anyhow::Result<()> {
+    // Like the JWT secret, the key type is a placeholder.
+    let ki = KeyInfo::new(SignatureType::Bls, serde_json::to_vec(info)?);
+    keystore.replace(&token_key(&info.id), ki)?;
+    Ok(())
+}
+
+/// Verify JWT Token with the secret of the `KeyStore`, and return the allowed permissions from
+/// token unless it was revoked. A token with an identifier must be registered, tokens without
+/// one, issued before tokens could be revoked or by [`create_token`], can't be revoked.
+pub fn verify_token_with_keystore(token: &str, keystore: &KeyStore) -> anyhow::Result<Vec<String>> {
+    let ki = keystore.get(JWT_IDENTIFIER)?;
+    let claims = decode_claims(token, ki.private_key())?;
+    if let Some(id) = &claims.jti {
+        let info = get_token_info(keystore, id)?;
+        anyhow::ensure!(!info.revoked, "token {id} is revoked");
+    }
+    Ok(claims.allow)
+}
+
 /// Verify JWT Token and return the allowed permissions from token
 pub fn verify_token(token: &str, key: &[u8]) -> JWTResult<Vec<String>> {
+    Ok(decode_claims(token, key)?.allow)
+}
+
+fn decode_claims(token: &str, key: &[u8]) -> JWTResult<Claims> {
     let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::default());
     if is_env_truthy("FOREST_JWT_DISABLE_EXP_VALIDATION") {
         let mut claims = validation.required_spec_claims.clone();
@@ -51,7 +224,7 @@ pub fn verify_token(token: &str, key: &[u8]) -> JWTResult<Vec<String>> {
         validation.validate_exp = false;
     }
     let token = decode::<Claims>(token, &DecodingKey::from_secret(key), &validation)?;
-    Ok(token.claims.allow)
+    Ok(token.claims)
 }
 
 pub fn generate_priv_key() -> KeyInfo {
@@ -71,6 +244,7 @@ mod tests {
         let payload = Claims {
             allow: perms,
             exp: None,
+            jti: None,
         };
         encode(&Header::default(), &payload, &EncodingKey::from_secret(key))
     }
@@ -148,4 +322,100 @@ mod tests {
             std::env::remove_var("FOREST_JWT_DISABLE_EXP_VALIDATION");
         }
     }
+
+    #[test]
+    #[serial]
+    fn issue_and_revoke_token() {
+        let mut keystore = KeyStore::new(crate::key_management::KeyStoreConfig::Memory).unwrap();
+        keystore.put(JWT_IDENTIFIER, generate_priv_key()).unwrap();
+        let perms = READ.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        let token = issue_token(&mut keystore, perms.clone(), Duration::hours(1)).unwrap();
+        let revoked = issue_token(&mut keystore, perms.clone(), Duration::hours(1)).unwrap();
+        // Expired tokens are removed from the registry.
+        issue_token(&mut keystore, perms.clone(), -Duration::hours(1)).unwrap();
+        issue_token(&mut keystore, perms.clone(), Duration::hours(1)).unwrap();
+        let tokens = list_tokens(&keystore).unwrap();
+        assert_eq!(tokens.len(), 3);
+
+        let id = token_id(&revoked).unwrap();
+        assert!(revoke_token(&mut keystore, &id).unwrap());
+        assert!(!revoke_token(&mut keystore, &id).unwrap());
+        assert!(revoke_token(&mut keystore, "unknown").is_err());
+
+        assert_eq!(
+            verify_token_with_keystore(&token, &keystore).unwrap(),
+            perms
+        );
+        assert!(verify_token_with_keystore(&revoked, &keystore).is_err());
+        assert!(
+            list_tokens(&keystore)
+                .unwrap()
+                .iter()
+                .any(|token| token.id == id && token.revoked)
+        );
+
+        // A token with an identifier that is not registered is rejected.
+        let unregistered = token_id(&token).unwrap();
+        keystore.remove(&token_key(&unregistered)).unwrap();
+        assert!(verify_token_with_keystore(&token, &keystore).is_err());
+
+        // Tokens without identifier can't be revoked.
+        let ki = keystore.get(JWT_IDENTIFIER).unwrap();
+        let token = create_token(perms.clone(), ki.private_key(), Duration::hours(1)).unwrap();
+        assert!(token_id(&token).is_err());
+        assert_eq!(
+            verify_token_with_keystore(&token, &keystore).unwrap(),
+            perms
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn revoked_tokens_stay_revoked_without_exp_validation() {
+        let mut keystore = KeyStore::new(crate::key_management::KeyStoreConfig::Memory).unwrap();
+        keystore.put(JWT_IDENTIFIER, generate_priv_key()).unwrap();
+        let perms = READ.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        unsafe {
+            std::env::set_var("FOREST_JWT_DISABLE_EXP_VALIDATION", "1");
+        }
+        let expired = issue_token(&mut keystore, perms.clone(), -Duration::hours(1)).unwrap();
+        let revoked = issue_token(&mut keystore, perms.clone(), -Duration::hours(1)).unwrap();
+        assert!(revoke_token(&mut keystore, &token_id(&revoked).unwrap()).unwrap());
+        // Issuing a token doesn't remove the expired ones, which are still valid.
+        issue_token(&mut keystore, perms.clone(), Duration::hours(1)).unwrap();
+        assert_eq!(list_tokens(&keystore).unwrap().len(), 3);
+        assert_eq!(
+            verify_token_with_keystore(&expired, &keystore).unwrap(),
+            perms
+        );
+        assert!(verify_token_with_keystore(&revoked, &keystore).is_err());
+        unsafe {
+            std::env::remove_var("FOREST_JWT_DISABLE_EXP_VALIDATION");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn admin_token_is_reused() {
+        let mut keystore = KeyStore::new(crate::key_management::KeyStoreConfig::Memory).unwrap();
+        keystore.put(JWT_IDENTIFIER, generate_priv_key()).unwrap();
+
+        let token = admin_token(&mut keystore, Duration::hours(1)).unwrap();
+        assert_eq!(
+            admin_token(&mut keystore, Duration::hours(1)).unwrap(),
+            token
+        );
+        assert_eq!(list_tokens(&keystore).unwrap().len(), 1);
+
+        // A revoked admin token is replaced.
+        assert!(revoke_token(&mut keystore, &token_id(&token).unwrap()).unwrap());
+        let new_token = admin_token(&mut keystore, Duration::hours(1)).unwrap();
+        assert_ne!(new_token, token);
+        assert_eq!(
+            verify_token_with_keystore(&new_token, &keystore).unwrap(),
+            ADMIN
+        );
+    }
 }
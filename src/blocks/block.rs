@@ -13,6 +13,7 @@ pub const BLOCK_MESSAGE_LIMIT: usize = 10000;
 
 /// A complete Filecoin block. This contains the block header as well as all BLS
 /// and SECP messages.
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Block {
     pub header: CachingBlockHeader,
@@ -10,6 +10,7 @@ use super::{Block, CachingBlockHeader, RawBlockHeader, Ticket};
 use crate::{
     chain_sync::TipsetValidator,
     cid_collections::SmallCidNonEmptyVec,
+    db::{BlockKind, with_block_kind},
     networks::{calibnet, mainnet},
     shim::clock::ChainEpoch,
     utils::{cid::CidCborExt, get_size::nunny_vec_heap_size_helper},
@@ -424,6 +425,15 @@ impl std::hash::Hash for FullTipset {
     }
 }
 
+#[cfg(test)]
+impl quickcheck::Arbitrary for FullTipset {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        // TODO(forest): https://github.com/ChainSafe/forest/issues/3570
+        //               Support random generation of tipsets with multiple blocks.
+        FullTipset::from(Block::arbitrary(g))
+    }
+}
+
 // Constructing a FullTipset from a single Block is infallible.
 impl From<Block> for FullTipset {
     fn from(block: Block) -> Self {
@@ -501,10 +511,22 @@ impl FullTipset {
     }
     /// Persists the tipset into the blockstore.
     pub fn persist(&self, db: &impl Blockstore) -> anyhow::Result<()> {
+        self.persist_headers(db)?;
+        with_block_kind(BlockKind::Message, || self.persist_messages(db))
+    }
+    /// Persists the block headers of the tipset into the blockstore.
+    pub fn persist_headers(&self, db: &impl Blockstore) -> anyhow::Result<()> {
         for block in self.blocks() {
             // To persist `TxMeta` that is required for loading tipset messages
             TipsetValidator::validate_msg_root(db, block)?;
             crate::chain::persist_objects(&db, std::iter::once(block.header()))?;
+        }
+        Ok(())
+    }
+    /// Persists the messages of the tipset into the blockstore. Callers are expected to set the
+    /// [`BlockKind::Message`] kind of the blocks.
+    pub fn persist_messages(&self, db: &impl Blockstore) -> anyhow::Result<()> {
+        for block in self.blocks() {
             crate::chain::persist_objects(&db, block.bls_msgs().iter())?;
             crate::chain::persist_objects(&db, block.secp_msgs().iter())?;
         }
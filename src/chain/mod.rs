@@ -1,6 +1,7 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod scheduler;
 mod snapshot_format;
 pub mod store;
 #[cfg(test)]
@@ -98,7 +99,7 @@ pub async fn export_v2<D: Digest, F: Seek + Read>(
         let mut encoder = forest::new_encoder(forest::DEFAULT_FOREST_CAR_COMPRESSION_LEVEL)?;
         snap_meta_block.write(&mut encoder)?;
         anyhow::Ok((
-            vec![snap_meta_block.cid],
+            vec![(snap_meta_block.cid, 0)],
             finalize_frame(forest::DEFAULT_FOREST_CAR_COMPRESSION_LEVEL, &mut encoder)?,
         ))
     }];
@@ -110,7 +111,7 @@ pub async fn export_v2<D: Digest, F: Seek + Read>(
             let mut encoder = forest::new_encoder(forest::DEFAULT_FOREST_CAR_COMPRESSION_LEVEL)?;
             encoder.write_car_block(f3_cid, f3_data_len, &mut f3_data)?;
             anyhow::Ok((
-                vec![f3_cid],
+                vec![(f3_cid, 0)],
                 finalize_frame(forest::DEFAULT_FOREST_CAR_COMPRESSION_LEVEL, &mut encoder)?,
             ))
         });
@@ -0,0 +1,184 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Scheduler of the housekeeping jobs of the node, keyed on the epochs of the chain head rather
+//! than on wall-clock time, so that jobs run at the same points of the chain on every node and can
+//! be tested by feeding epochs.
+//!
+//! Jobs are run inline from the loop of head changes and must return quickly, typically by
+//! triggering a channel of the service doing the actual work.
+
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::HeadChange;
+use crate::shim::clock::ChainEpoch;
+
+type Job = Box<dyn FnMut(ChainEpoch) + Send>;
+
+enum Schedule {
+    /// Every given number of epochs, counted from the first head seen
+    Every {
+        epochs: ChainEpoch,
+        next: Option<ChainEpoch>,
+    },
+    /// Once the head reaches each of the heights, sorted in ascending order
+    AtHeights(Vec<ChainEpoch>),
+}
+
+struct ScheduledJob {
+    name: &'static str,
+    schedule: Schedule,
+    job: Job,
+}
+
+#[derive(Default)]
+pub struct EpochScheduler {
+    jobs: Vec<ScheduledJob>,
+    last_epoch: Option<ChainEpoch>,
+}
+
+impl EpochScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs the job every `epochs` epochs, the first time `epochs` after the first head seen.
+    pub fn every(
+        &mut self,
+        name: &'static str,
+        epochs: ChainEpoch,
+        job: impl FnMut(ChainEpoch) + Send + 'static,
+    ) -> &mut Self {
+        self.jobs.push(ScheduledJob {
+            name,
+            schedule: Schedule::Every {
+                epochs: epochs.max(1),
+                next: None,
+            },
+            job: Box::new(job),
+        });
+        self
+    }
+
+    /// Runs the job once the head reaches each of the heights. Heights the head skipped over, e.g.
+    /// on null rounds, are run on the next head.
+    pub fn at_heights(
+        &mut self,
+        name: &'static str,
+        heights: impl IntoIterator<Item = ChainEpoch>,
+        job: impl FnMut(ChainEpoch) + Send + 'static,
+    ) -> &mut Self {
+        let mut heights = heights.into_iter().collect::<Vec<_>>();
+        heights.sort_unstable();
+        heights.dedup();
+        self.jobs.push(ScheduledJob {
+            name,
+            schedule: Schedule::AtHeights(heights),
+            job: Box::new(job),
+        });
+        self
+    }
+
+    /// Runs the jobs due at the epoch of the new head, and returns their names.
+    pub fn tick(&mut self, epoch: ChainEpoch) -> Vec<&'static str> {
+        let last_epoch = self.last_epoch.replace(epoch);
+        let mut ran = vec![];
+        for ScheduledJob {
+            name,
+            schedule,
+            job,
+        } in &mut self.jobs
+        {
+            let due = match schedule {
+                Schedule::Every { epochs, next } => match next {
+                    Some(next_epoch) if epoch >= *next_epoch => {
+                        *next = Some(epoch + *epochs);
+                        true
+                    }
+                    Some(_) => false,
+                    None => {
+                        *next = Some(epoch + *epochs);
+                        false
+                    }
+                },
+                Schedule::AtHeights(heights) => {
+                    // Heights at or below the first head seen have been reached before the start.
+                    let due = last_epoch.is_some() && heights.first().is_some_and(|h| *h <= epoch);
+                    heights.retain(|h| *h > epoch);
+                    due
+                }
+            };
+            if due {
+                tracing::debug!("Running scheduled job {name} at epoch {epoch}");
+                job(epoch);
+                ran.push(*name);
+            }
+        }
+        ran
+    }
+
+    /// Runs the jobs on the new heads until the publisher of the head changes is closed.
+    pub async fn run(
+        mut self,
+        mut head_changes: broadcast::Receiver<HeadChange>,
+    ) -> anyhow::Result<()> {
+        loop {
+            match head_changes.recv().await {
+                Ok(HeadChange::Apply(ts)) => {
+                    self.tick(ts.epoch());
+                }
+                // Jobs only depend on the latest epoch.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn recorder() -> (
+        Arc<Mutex<Vec<ChainEpoch>>>,
+        impl FnMut(ChainEpoch) + Send + 'static,
+    ) {
+        let runs = Arc::new(Mutex::new(vec![]));
+        let job = {
+            let runs = runs.clone();
+            move |epoch| runs.lock().unwrap().push(epoch)
+        };
+        (runs, job)
+    }
+
+    #[test]
+    fn run_every_epochs() {
+        let (runs, job) = recorder();
+        let mut scheduler = EpochScheduler::new();
+        scheduler.every("job", 10, job);
+        for epoch in 100..=125 {
+            scheduler.tick(epoch);
+        }
+        assert_eq!(*runs.lock().unwrap(), vec![110, 120]);
+        // Skipped epochs postpone the next run from the epoch it ran at.
+        scheduler.tick(135);
+        scheduler.tick(140);
+        scheduler.tick(145);
+        assert_eq!(*runs.lock().unwrap(), vec![110, 120, 135, 145]);
+    }
+
+    #[test]
+    fn run_at_heights() {
+        let (runs, job) = recorder();
+        let mut scheduler = EpochScheduler::new();
+        scheduler.at_heights("job", [130, 100, 110, 120], job);
+        scheduler.tick(100);
+        scheduler.tick(105);
+        assert_eq!(scheduler.tick(112), vec!["job"]);
+        // Both heights were skipped over, which runs the job once.
+        scheduler.tick(131);
+        scheduler.tick(140);
+        assert_eq!(*runs.lock().unwrap(), vec![112, 131]);
+    }
+}
@@ -3,22 +3,32 @@
 
 use super::{
     Error,
-    index::{ChainIndex, ResolveNullTipset},
+    checkpoints::Checkpoints,
+    index::{ChainIndex, ResolveNullTipset, TipsetAtHeight},
     tipset_tracker::TipsetTracker,
 };
-use crate::db::{EthMappingsStore, EthMappingsStoreExt};
+use crate::db::{BlockKind, EthMappingsStore, EthMappingsStoreExt, with_block_kind};
 use crate::interpreter::{BlockMessages, VMTrace};
 use crate::libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
 use crate::message::{ChainMessage, Message as MessageTrait, SignedMessage};
 use crate::networks::{ChainConfig, Height};
-use crate::rpc::eth::{eth_tx_from_signed_eth_message, types::EthHash};
+use crate::rpc::eth::{
+    eth_tx_from_signed_eth_message,
+    types::{EthAddress, EthHash},
+};
 use crate::shim::clock::ChainEpoch;
 use crate::shim::{
-    address::Address, econ::TokenAmount, executor::Receipt, message::Message,
-    state_tree::StateTree, version::NetworkVersion,
+    address::Address,
+    econ::TokenAmount,
+    executor::Receipt,
+    message::Message,
+    state_tree::{ActorID, StateTree},
+    version::NetworkVersion,
 };
 use crate::state_manager::StateOutput;
+use crate::state_manager::reward_metrics::RewardMetrics;
 use crate::utils::db::{BlockstoreExt, CborStoreExt};
+use crate::utils::encoding::blake2b_256;
 use crate::{
     blocks::{CachingBlockHeader, Tipset, TipsetKey, TxMeta},
     db::HeaviestTipsetKeyProvider,
@@ -30,6 +40,7 @@ use cid::Cid;
 use fil_actors_shared::fvm_ipld_amt::Amtv0 as Amt;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CborStore;
+use fvm_ipld_encoding::tuple::*;
 use itertools::Itertools;
 use nonzero_ext::nonzero;
 use parking_lot::{Mutex, RwLock};
@@ -84,6 +95,21 @@ pub struct ChainStore<DB> {
 
     /// Needed by the Ethereum mapping.
     chain_config: Arc<ChainConfig>,
+
+    /// Tipsets the chain must not deviate from
+    checkpoints: RwLock<Checkpoints>,
+
+    /// Headers committed to by the hash chain of a checkpoint
+    checkpointed_headers: Mutex<CheckpointedHeaders>,
+}
+
+/// Block headers that are ancestors of the checkpoint tipset at `epoch`, found by walking the
+/// chain down from it to `lowest`.
+#[derive(Default)]
+struct CheckpointedHeaders {
+    epoch: Option<ChainEpoch>,
+    lowest: Option<Tipset>,
+    cids: HashSet<Cid>,
 }
 
 impl<DB> BitswapStoreRead for ChainStore<DB>
@@ -124,6 +150,7 @@ where
         let (publisher, _) = broadcast::channel(SINK_CAP);
         let chain_index = Arc::new(ChainIndex::new(Arc::clone(&db)));
         let validated_blocks = Mutex::new(HashSet::default());
+        let checkpoints = RwLock::new(Checkpoints::builtin(&chain_config.network));
 
         let cs = Self {
             publisher,
@@ -136,6 +163,8 @@ where
             validated_blocks,
             eth_mappings,
             chain_config,
+            checkpoints,
+            checkpointed_headers: Default::default(),
         };
 
         Ok(cs)
@@ -201,6 +230,426 @@ where
             .map(|(cid, _)| cid))
     }
 
+    /// Writes the message `Cid` to inclusion [`TipsetKey`] mapping for every message in `ts`,
+    /// so that message lookups don't have to scan the chain backwards.
+    pub fn put_message_inclusions(&self, ts: &Tipset) -> Result<(), Error> {
+        let mut indexed = HashSet::default();
+        for bh in ts.block_headers() {
+            let (bls_cids, secp_cids) = read_msg_cids(self.blockstore(), bh)?;
+            for cid in bls_cids.into_iter().chain(secp_cids) {
+                if indexed.insert(cid) {
+                    self.eth_mappings
+                        .write_obj(&message_inclusion_key(&cid), &(ts.key(), ts.epoch()))?;
+                }
+            }
+        }
+        // The messages included at each epoch, by any fork, are recorded to prune their entries
+        let epoch_key = message_inclusions_epoch_key(ts.epoch());
+        let mut epoch_messages: Vec<Cid> =
+            self.eth_mappings.read_obj(&epoch_key)?.unwrap_or_default();
+        for cid in &indexed {
+            if !epoch_messages.contains(cid) {
+                epoch_messages.push(*cid);
+            }
+        }
+        self.eth_mappings.write_obj(&epoch_key, &epoch_messages)?;
+        self.lower_prunable_from(&message_inclusions_from_key(), ts.epoch())?;
+        trace!("Indexed {} messages of tipset {}", indexed.len(), ts.key());
+        Ok(())
+    }
+
+    /// Lowers the epoch the entries recorded by epoch under `from_key` are pruned from to `epoch`.
+    fn lower_prunable_from(&self, from_key: &EthHash, epoch: ChainEpoch) -> Result<(), Error> {
+        if self
+            .eth_mappings
+            .read_obj::<ChainEpoch>(from_key)?
+            .is_none_or(|from| epoch < from)
+        {
+            self.eth_mappings.write_obj(from_key, &epoch)?;
+        }
+        Ok(())
+    }
+
+    /// Drops the message inclusions of the tipsets below epoch `until`, unless the messages were
+    /// included again since. Returns the number of pruned inclusions.
+    pub fn prune_message_inclusions(&self, until: ChainEpoch) -> Result<usize, Error> {
+        let from_key = message_inclusions_from_key();
+        let Some(from) = self.eth_mappings.read_obj::<ChainEpoch>(&from_key)? else {
+            return Ok(0);
+        };
+        let mut pruned = 0;
+        for epoch in from..until {
+            let epoch_key = message_inclusions_epoch_key(epoch);
+            let epoch_messages: Vec<Cid> =
+                self.eth_mappings.read_obj(&epoch_key)?.unwrap_or_default();
+            let mut keys = vec![epoch_key];
+            for cid in epoch_messages {
+                let key = message_inclusion_key(&cid);
+                if let Some((_, included)) = self
+                    .eth_mappings
+                    .read_obj::<(TipsetKey, ChainEpoch)>(&key)?
+                    && included < until
+                {
+                    keys.push(key);
+                    pruned += 1;
+                }
+            }
+            self.eth_mappings.delete(keys)?;
+        }
+        if from < until {
+            self.eth_mappings.write_obj(&from_key, &until)?;
+        }
+        Ok(pruned)
+    }
+
+    /// Reads the [`TipsetKey`] and epoch of the tipset the message was last included in.
+    pub fn get_message_inclusion(
+        &self,
+        msg: &Cid,
+    ) -> Result<Option<(TipsetKey, ChainEpoch)>, Error> {
+        Ok(self.eth_mappings.read_obj(&message_inclusion_key(msg))?)
+    }
+
+    /// Indexes the messages of `ts` by sender and recipient address, see
+    /// [`ChainStore::get_address_messages`]. The tipsets of the chain of `ts` not indexed yet are
+    /// indexed as well, down to the first indexed one, i.e. the tipsets between the indexed range
+    /// and `ts`, and the tipsets a reorg brought into the chain. A tipset below the range is only
+    /// indexed if it is the parent of its first tipset, i.e. when backfilling the index.
+    pub fn put_address_messages(&self, ts: &Tipset) -> Result<(), Error> {
+        let range = match self.get_address_messages_range()? {
+            None => AddressMessagesRange {
+                from: ts.epoch(),
+                from_parents: ts.parents().clone(),
+                to: ts.epoch(),
+            },
+            Some(range) if ts.key() == &range.from_parents => AddressMessagesRange {
+                from: ts.epoch(),
+                from_parents: ts.parents().clone(),
+                ..range
+            },
+            Some(range) if ts.epoch() >= range.from => AddressMessagesRange {
+                to: range.to.max(ts.epoch()),
+                ..range
+            },
+            Some(_) => return Ok(()),
+        };
+        let mut tipsets = vec![];
+        for ts in self.chain_index.chain(ts.clone()) {
+            if ts.epoch() < range.from || self.is_address_messages_indexed(&ts)? {
+                break;
+            }
+            tipsets.push(ts);
+        }
+        for ts in tipsets.iter().rev() {
+            self.index_address_messages(ts)?;
+        }
+        self.eth_mappings
+            .write_obj(&address_messages_range_key(), &range)?;
+        Ok(())
+    }
+
+    fn index_address_messages(&self, ts: &Tipset) -> Result<(), Error> {
+        let epoch = ts.epoch();
+        let addresses_key = address_messages_addresses_key(epoch);
+        let mut addresses: Vec<Address> = self
+            .eth_mappings
+            .read_obj(&addresses_key)?
+            .unwrap_or_default();
+        for (address, messages) in self.messages_by_address(ts)? {
+            let key = address_messages_key(&address, epoch);
+            let mut entries: Vec<AddressMessages> =
+                self.eth_mappings.read_obj(&key)?.unwrap_or_default();
+            // The tipsets of the other forks at this epoch are kept, in case they come back.
+            entries.retain(|entry| &entry.tipset_key != ts.key());
+            entries.push(AddressMessages {
+                tipset_key: ts.key().clone(),
+                messages,
+            });
+            self.eth_mappings.write_obj(&key, &entries)?;
+
+            let epochs_key = address_messages_epochs_key(&address);
+            let mut epochs: Vec<ChainEpoch> =
+                self.eth_mappings.read_obj(&epochs_key)?.unwrap_or_default();
+            if let Err(i) = epochs.binary_search(&epoch) {
+                epochs.insert(i, epoch);
+                self.eth_mappings.write_obj(&epochs_key, &epochs)?;
+            }
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+        self.eth_mappings.write_obj(&addresses_key, &addresses)?;
+        let key = address_messages_tipsets_key(epoch);
+        let mut tipsets: Vec<TipsetKey> = self.eth_mappings.read_obj(&key)?.unwrap_or_default();
+        tipsets.push(ts.key().clone());
+        self.eth_mappings.write_obj(&key, &tipsets)?;
+        Ok(())
+    }
+
+    fn messages_by_address(&self, ts: &Tipset) -> Result<HashMap<Address, Vec<Cid>>, Error> {
+        let mut by_address: HashMap<Address, Vec<Cid>> = HashMap::new();
+        for msg in self.messages_for_tipset(ts)? {
+            let cid = msg.cid();
+            let message = msg.message();
+            by_address.entry(message.from).or_default().push(cid);
+            if message.to != message.from {
+                by_address.entry(message.to).or_default().push(cid);
+            }
+        }
+        Ok(by_address)
+    }
+
+    fn is_address_messages_indexed(&self, ts: &Tipset) -> Result<bool, Error> {
+        let tipsets: Option<Vec<TipsetKey>> = self
+            .eth_mappings
+            .read_obj(&address_messages_tipsets_key(ts.epoch()))?;
+        Ok(tipsets.is_some_and(|tipsets| tipsets.contains(ts.key())))
+    }
+
+    /// Reads the range of epochs whose messages were indexed by address.
+    pub fn get_address_messages_range(&self) -> Result<Option<AddressMessagesRange>, Error> {
+        Ok(self.eth_mappings.read_obj(&address_messages_range_key())?)
+    }
+
+    /// Returns the messages sent or received by `address` in the chain of `ts`, from `ts` down
+    /// to `min_epoch`, most recent first, or `None` if these tipsets were not all indexed. Only the
+    /// epochs the address has messages at are read.
+    pub fn get_address_messages(
+        &self,
+        address: &Address,
+        ts: &Tipset,
+        min_epoch: ChainEpoch,
+    ) -> Result<Option<Vec<Cid>>, Error> {
+        let min_epoch = min_epoch.max(0);
+        match self.get_address_messages_range()? {
+            Some(range) if range.from <= min_epoch && ts.epoch() <= range.to => {}
+            _ => return Ok(None),
+        }
+        // Indexing a tipset indexes its chain down to the first indexed tipset, so the whole
+        // chain of an indexed tipset is indexed down to the start of the range.
+        if !self.is_address_messages_indexed(ts)? {
+            return Ok(None);
+        }
+        let epochs: Vec<ChainEpoch> = self
+            .eth_mappings
+            .read_obj(&address_messages_epochs_key(address))?
+            .unwrap_or_default();
+        let mut messages = vec![];
+        let mut cursor = ts.clone();
+        for &epoch in epochs
+            .iter()
+            .rev()
+            .filter(|&&epoch| min_epoch <= epoch && epoch <= ts.epoch())
+        {
+            let ts = match self.chain_index.tipset_at_height(epoch, cursor.clone())? {
+                TipsetAtHeight::Tipset(ts) => ts,
+                // A null round in this chain, the entries are those of another fork
+                TipsetAtHeight::NullRound { newer, .. } => {
+                    cursor = newer;
+                    continue;
+                }
+            };
+            let entries: Vec<AddressMessages> = self
+                .eth_mappings
+                .read_obj(&address_messages_key(address, epoch))?
+                .unwrap_or_default();
+            if let Some(entry) = entries
+                .into_iter()
+                .find(|entry| &entry.tipset_key == ts.key())
+            {
+                messages.extend(entry.messages);
+            }
+            cursor = ts;
+        }
+        Ok(Some(messages))
+    }
+
+    /// Drops the messages indexed by address below epoch `until` of the chain of `head`, along
+    /// with the ones of the forks at these epochs. Returns the number of pruned epochs.
+    pub fn prune_address_messages(&self, head: &Tipset, until: ChainEpoch) -> Result<usize, Error> {
+        let Some(range) = self.get_address_messages_range()? else {
+            return Ok(0);
+        };
+        if until <= range.from || until > range.to.min(head.epoch()) {
+            return Ok(0);
+        }
+        let first_kept =
+            self.chain_index
+                .tipset_by_height(until, head.clone(), ResolveNullTipset::TakeNewer)?;
+        let mut pruned_addresses = HashSet::default();
+        for epoch in range.from..first_kept.epoch() {
+            let addresses_key = address_messages_addresses_key(epoch);
+            let addresses: Vec<Address> = self
+                .eth_mappings
+                .read_obj(&addresses_key)?
+                .unwrap_or_default();
+            let mut keys = vec![address_messages_tipsets_key(epoch), addresses_key];
+            keys.extend(
+                addresses
+                    .iter()
+                    .map(|address| address_messages_key(address, epoch)),
+            );
+            self.eth_mappings.delete(keys)?;
+            pruned_addresses.extend(addresses);
+        }
+        for address in pruned_addresses {
+            let epochs_key = address_messages_epochs_key(&address);
+            let mut epochs: Vec<ChainEpoch> =
+                self.eth_mappings.read_obj(&epochs_key)?.unwrap_or_default();
+            epochs.retain(|&epoch| epoch >= first_kept.epoch());
+            if epochs.is_empty() {
+                self.eth_mappings.delete(vec![epochs_key])?;
+            } else {
+                self.eth_mappings.write_obj(&epochs_key, &epochs)?;
+            }
+        }
+        self.eth_mappings.write_obj(
+            &address_messages_range_key(),
+            &AddressMessagesRange {
+                from: first_kept.epoch(),
+                from_parents: first_kept.parents().clone(),
+                ..range
+            },
+        )?;
+        Ok((first_kept.epoch() - range.from) as usize)
+    }
+
+    /// Writes the bloom filter of the events of the tipset, see
+    /// [`crate::state_manager::logs_bloom`].
+    pub fn put_logs_bloom(
+        &self,
+        tsk: &TipsetKey,
+        bloom: &ethereum_types::Bloom,
+    ) -> Result<(), Error> {
+        self.eth_mappings
+            .write_bin(&logs_bloom_key(tsk), bloom.as_bytes())?;
+        Ok(())
+    }
+
+    /// Reads the bloom filter of the events of the tipset, if it was computed.
+    pub fn get_logs_bloom(&self, tsk: &TipsetKey) -> Result<Option<ethereum_types::Bloom>, Error> {
+        Ok(self
+            .eth_mappings
+            .read_bin(&logs_bloom_key(tsk))?
+            .and_then(|bytes| <[u8; 256]>::try_from(bytes).ok())
+            .map(ethereum_types::Bloom::from))
+    }
+
+    /// Writes the reward actor metrics of the parent state of the tipset, see
+    /// [`crate::state_manager::reward_metrics`].
+    pub fn put_reward_metrics(
+        &self,
+        tsk: &TipsetKey,
+        metrics: &RewardMetrics,
+    ) -> Result<(), Error> {
+        self.eth_mappings
+            .write_obj(&reward_metrics_key(tsk), metrics)?;
+        Ok(())
+    }
+
+    /// Reads the reward actor metrics of the parent state of the tipset, if they were indexed.
+    pub fn get_reward_metrics(&self, tsk: &TipsetKey) -> Result<Option<RewardMetrics>, Error> {
+        Ok(self.eth_mappings.read_obj(&reward_metrics_key(tsk))?)
+    }
+
+    /// Indexes the delegated Ethereum address of an actor created at `epoch`, `None` if it has
+    /// none, in both directions. A reorg may assign the ID to another actor, the entries of its
+    /// previous address are then removed.
+    pub fn put_actor_eth_address(
+        &self,
+        actor_id: ActorID,
+        eth_address: Option<&EthAddress>,
+        epoch: ChainEpoch,
+    ) -> Result<(), Error> {
+        let previous = self.get_actor_id_eth_address(actor_id)?;
+        if previous.as_ref() == eth_address {
+            return Ok(());
+        }
+        if let Some(previous) = previous {
+            self.remove_actor_eth_address(actor_id, &previous)?;
+        }
+        if let Some(eth_address) = eth_address {
+            self.eth_mappings
+                .write_obj(&eth_address_actor_id_key(eth_address), &actor_id)?;
+            self.eth_mappings.write_bin(
+                &actor_id_eth_address_key(actor_id),
+                eth_address.0.as_bytes(),
+            )?;
+            // The IDs are recorded by epoch to prune their entries
+            let epoch_key = actor_eth_addresses_epoch_key(epoch);
+            let mut actor_ids: Vec<ActorID> =
+                self.eth_mappings.read_obj(&epoch_key)?.unwrap_or_default();
+            if !actor_ids.contains(&actor_id) {
+                actor_ids.push(actor_id);
+                self.eth_mappings.write_obj(&epoch_key, &actor_ids)?;
+            }
+            self.lower_prunable_from(&actor_eth_addresses_from_key(), epoch)?;
+        }
+        Ok(())
+    }
+
+    fn remove_actor_eth_address(
+        &self,
+        actor_id: ActorID,
+        eth_address: &EthAddress,
+    ) -> Result<(), Error> {
+        let mut keys = vec![actor_id_eth_address_key(actor_id)];
+        // The address may have been assigned another ID by a reorg already
+        if self.get_eth_address_actor_id(eth_address)? == Some(actor_id) {
+            keys.push(eth_address_actor_id_key(eth_address));
+        }
+        self.eth_mappings.delete(keys)?;
+        Ok(())
+    }
+
+    /// Drops the delegated Ethereum addresses indexed for the actors created below epoch
+    /// `until`. The lookups of these actors fall back to the state. Returns the number of pruned
+    /// actors.
+    pub fn prune_actor_eth_addresses(&self, until: ChainEpoch) -> Result<usize, Error> {
+        let from_key = actor_eth_addresses_from_key();
+        let Some(from) = self.eth_mappings.read_obj::<ChainEpoch>(&from_key)? else {
+            return Ok(0);
+        };
+        let mut pruned = 0;
+        for epoch in from..until {
+            let epoch_key = actor_eth_addresses_epoch_key(epoch);
+            let actor_ids: Vec<ActorID> =
+                self.eth_mappings.read_obj(&epoch_key)?.unwrap_or_default();
+            for actor_id in actor_ids {
+                if let Some(eth_address) = self.get_actor_id_eth_address(actor_id)? {
+                    self.remove_actor_eth_address(actor_id, &eth_address)?;
+                    pruned += 1;
+                }
+            }
+            self.eth_mappings.delete(vec![epoch_key])?;
+        }
+        if from < until {
+            self.eth_mappings.write_obj(&from_key, &until)?;
+        }
+        Ok(pruned)
+    }
+
+    /// Reads the actor ID the delegated Ethereum address was last seen assigned to. The entry is
+    /// only a hint, the states of other forks may assign the ID to another actor.
+    pub fn get_eth_address_actor_id(
+        &self,
+        eth_address: &EthAddress,
+    ) -> Result<Option<ActorID>, Error> {
+        Ok(self
+            .eth_mappings
+            .read_obj(&eth_address_actor_id_key(eth_address))?)
+    }
+
+    /// Reads the delegated Ethereum address of the actor last seen with the ID. The entry is only
+    /// a hint, see [`ChainStore::get_eth_address_actor_id`].
+    pub fn get_actor_id_eth_address(&self, actor_id: ActorID) -> Result<Option<EthAddress>, Error> {
+        Ok(self
+            .eth_mappings
+            .read_bin(&actor_id_eth_address_key(actor_id))?
+            .map(|bytes| EthAddress::try_from(bytes.as_slice()))
+            .transpose()?)
+    }
+
     /// Expands tipset to tipset with all other headers in the same epoch using
     /// the tipset tracker.
     fn expand_tipset(&self, header: CachingBlockHeader) -> Result<Tipset, Error> {
@@ -250,6 +699,47 @@ where
         &self.chain_config
     }
 
+    /// Returns the checkpoints the chain must not deviate from
+    pub fn checkpoints(&self) -> &RwLock<Checkpoints> {
+        &self.checkpoints
+    }
+
+    /// Returns `true` if the block header is an ancestor of the next checkpoint tipset, which
+    /// commits to it by its hash chain. The signatures of such headers don't need to be verified.
+    /// Returns `false` if the checkpoint tipset is not in the store yet.
+    pub fn is_checkpointed(&self, header: &CachingBlockHeader) -> bool {
+        let Some((epoch, key)) = self
+            .checkpoints
+            .read()
+            .next(header.epoch)
+            .map(|(epoch, key)| (epoch, key.clone()))
+        else {
+            return false;
+        };
+        let mut checkpointed = self.checkpointed_headers.lock();
+        if checkpointed.epoch != Some(epoch) {
+            let Ok(Some(checkpoint)) = self.chain_index.load_tipset(&key) else {
+                return false;
+            };
+            *checkpointed = CheckpointedHeaders {
+                epoch: Some(epoch),
+                cids: checkpoint.key().iter().collect(),
+                lowest: Some(checkpoint),
+            };
+        }
+        // Validation moves forward, so the chain is walked down from the checkpoint only once.
+        while let Some(lowest) = &checkpointed.lowest
+            && lowest.epoch() > header.epoch
+        {
+            let Ok(Some(parent)) = self.chain_index.load_tipset(lowest.parents()) else {
+                break;
+            };
+            checkpointed.cids.extend(parent.key().iter());
+            checkpointed.lowest = Some(parent);
+        }
+        checkpointed.cids.contains(header.cid())
+    }
+
     /// Lotus often treats an empty [`TipsetKey`] as shorthand for "the heaviest tipset".
     /// You may opt-in to that behavior by calling this method with [`None`].
     ///
@@ -441,6 +931,117 @@ where
     }
 }
 
+/// Message inclusions share the Ethereum mapping column with transaction and tipset hashes,
+/// the key is derived from a prefixed message `Cid` so that it never collides with those.
+fn message_inclusion_key(msg: &Cid) -> EthHash {
+    let mut bytes = b"message_inclusion/".to_vec();
+    bytes.extend(msg.to_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Messages of a tipset sent or received by an address, see [`ChainStore::put_address_messages`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+struct AddressMessages {
+    tipset_key: TipsetKey,
+    messages: Vec<Cid>,
+}
+
+/// Range of epochs whose messages were indexed by address, without gaps
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AddressMessagesRange {
+    pub from: ChainEpoch,
+    /// Parents of the first indexed tipset, the next tipset to index when backfilling
+    pub from_parents: TipsetKey,
+    pub to: ChainEpoch,
+}
+
+/// Messages included at an epoch, by the tipsets of any fork, to prune their inclusions
+fn message_inclusions_epoch_key(epoch: ChainEpoch) -> EthHash {
+    let mut bytes = b"message_inclusions_epoch/".to_vec();
+    bytes.extend_from_slice(&epoch.to_be_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Lowest epoch with message inclusions that were not pruned
+fn message_inclusions_from_key() -> EthHash {
+    EthHash::from(blake2b_256(b"message_inclusions_from"))
+}
+
+/// Messages by address share the Ethereum mapping column as well, keyed by a prefixed address
+/// and epoch. The messages of the tipsets of all the indexed forks at the epoch share the key.
+fn address_messages_key(address: &Address, epoch: ChainEpoch) -> EthHash {
+    let mut bytes = b"address_messages/".to_vec();
+    bytes.extend(address.to_bytes());
+    bytes.extend_from_slice(&epoch.to_be_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Epochs at which an address has indexed messages, in any fork, in ascending order
+fn address_messages_epochs_key(address: &Address) -> EthHash {
+    let mut bytes = b"address_messages_epochs/".to_vec();
+    bytes.extend(address.to_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Addresses with indexed messages at an epoch, in any fork, to prune their entries
+fn address_messages_addresses_key(epoch: ChainEpoch) -> EthHash {
+    let mut bytes = b"address_messages_addresses/".to_vec();
+    bytes.extend_from_slice(&epoch.to_be_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Keys of the tipsets whose messages were indexed by address at an epoch
+fn address_messages_tipsets_key(epoch: ChainEpoch) -> EthHash {
+    let mut bytes = b"address_messages_tipsets/".to_vec();
+    bytes.extend_from_slice(&epoch.to_be_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+fn address_messages_range_key() -> EthHash {
+    EthHash::from(blake2b_256(b"address_messages_range"))
+}
+
+/// Logs blooms share the Ethereum mapping column as well, keyed by a prefixed tipset key.
+fn logs_bloom_key(tsk: &TipsetKey) -> EthHash {
+    let mut bytes = b"logs_bloom/".to_vec();
+    bytes.extend_from_slice(&tsk.bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Reward metrics share the Ethereum mapping column as well, keyed by a prefixed tipset key.
+fn reward_metrics_key(tsk: &TipsetKey) -> EthHash {
+    let mut bytes = b"reward_metrics/".to_vec();
+    bytes.extend_from_slice(&tsk.bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Actor IDs of delegated addresses share the Ethereum mapping column as well, keyed by a prefixed
+/// Ethereum address.
+fn eth_address_actor_id_key(eth_address: &EthAddress) -> EthHash {
+    let mut bytes = b"eth_address_actor_id/".to_vec();
+    bytes.extend_from_slice(eth_address.0.as_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Delegated addresses of actor IDs, the reverse of [`eth_address_actor_id_key`].
+fn actor_id_eth_address_key(actor_id: ActorID) -> EthHash {
+    let mut bytes = b"actor_id_eth_address/".to_vec();
+    bytes.extend_from_slice(&actor_id.to_be_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Actors with a delegated address created at an epoch, by any fork, to prune their entries
+fn actor_eth_addresses_epoch_key(epoch: ChainEpoch) -> EthHash {
+    let mut bytes = b"actor_eth_addresses_epoch/".to_vec();
+    bytes.extend_from_slice(&epoch.to_be_bytes());
+    EthHash::from(blake2b_256(&bytes))
+}
+
+/// Lowest epoch with actor delegated addresses that were not pruned
+fn actor_eth_addresses_from_key() -> EthHash {
+    EthHash::from(blake2b_256(b"actor_eth_addresses_from"))
+}
+
 fn filter_lowest_index(values: Vec<(EthHash, Cid, u64, usize)>) -> Vec<(EthHash, Cid, u64)> {
     let map: HashMap<EthHash, (Cid, u64, usize)> = values.into_iter().fold(
         HashMap::default(),
@@ -502,9 +1103,15 @@ where
     DB: Blockstore,
 {
     let msg_cid = &block_header.messages;
-    if let Some(roots) = db.get_cbor::<TxMeta>(msg_cid)? {
-        let bls_cids = read_amt_cids(db, &roots.bls_message_root)?;
-        let secpk_cids = read_amt_cids(db, &roots.secp_message_root)?;
+    let roots = with_block_kind(BlockKind::Message, || db.get_cbor::<TxMeta>(msg_cid))?;
+    if let Some(roots) = roots {
+        let (bls_cids, secpk_cids) =
+            with_block_kind(BlockKind::Message, || -> Result<_, Error> {
+                Ok((
+                    read_amt_cids(db, &roots.bls_message_root)?,
+                    read_amt_cids(db, &roots.secp_message_root)?,
+                ))
+            })?;
         Ok((bls_cids, secpk_cids))
     } else {
         Err(Error::UndefinedKey(format!(
@@ -702,7 +1309,7 @@ where
     DB: Blockstore,
     T: DeserializeOwned,
 {
-    db.get_cbor(key)?
+    with_block_kind(BlockKind::Message, || db.get_cbor(key))?
         .ok_or_else(|| Error::UndefinedKey(key.to_string()))
 }
 
@@ -765,6 +1372,271 @@ mod tests {
         assert!(cs.is_block_validated(&cid));
     }
 
+    #[test]
+    fn message_inclusion_index() {
+        let db = Arc::new(crate::db::MemoryDB::default());
+        let chain_config = Arc::new(ChainConfig::default());
+        let (bls, secp) = crate::test_utils::construct_messages();
+        let messages = crate::chain_sync::TipsetValidator::compute_msg_root(
+            &db,
+            std::slice::from_ref(&bls),
+            std::slice::from_ref(&secp),
+        )
+        .unwrap();
+        let header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(0),
+            epoch: 42,
+            messages,
+            ..Default::default()
+        });
+        let ts = Tipset::from(&header);
+
+        let cs = ChainStore::new(db.clone(), db.clone(), db, chain_config, header).unwrap();
+        assert_eq!(cs.get_message_inclusion(&bls.cid()).unwrap(), None);
+
+        cs.put_message_inclusions(&ts).unwrap();
+        for cid in [bls.cid(), secp.cid()] {
+            assert_eq!(
+                cs.get_message_inclusion(&cid).unwrap(),
+                Some((ts.key().clone(), 42))
+            );
+            // The inclusion entry must not shadow a transaction hash mapping.
+            assert_eq!(cs.get_mapping(&cid.into()).unwrap(), None);
+        }
+
+        assert_eq!(cs.prune_message_inclusions(42).unwrap(), 0);
+        assert_eq!(cs.prune_message_inclusions(43).unwrap(), 2);
+        for cid in [bls.cid(), secp.cid()] {
+            assert_eq!(cs.get_message_inclusion(&cid).unwrap(), None);
+        }
+        assert!(
+            cs.eth_mappings
+                .read_bin(&message_inclusions_epoch_key(42))
+                .unwrap()
+                .is_none()
+        );
+        // Nothing is pruned twice
+        assert_eq!(cs.prune_message_inclusions(43).unwrap(), 0);
+    }
+
+    #[test]
+    fn address_messages_index() {
+        let db = Arc::new(crate::db::MemoryDB::default());
+        let chain_config = Arc::new(ChainConfig::default());
+        let (bls, secp) = crate::test_utils::construct_messages();
+        let messages = crate::chain_sync::TipsetValidator::compute_msg_root(
+            &db,
+            std::slice::from_ref(&bls),
+            std::slice::from_ref(&secp),
+        )
+        .unwrap();
+        let header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(0),
+            epoch: 42,
+            messages,
+            ..Default::default()
+        });
+        let ts = Tipset::from(&header);
+
+        let cs = ChainStore::new(db.clone(), db.clone(), db, chain_config, header).unwrap();
+        assert_eq!(cs.get_address_messages(&bls.from, &ts, 42).unwrap(), None);
+
+        cs.put_address_messages(&ts).unwrap();
+        for address in [bls.from, bls.to] {
+            let indexed = cs.get_address_messages(&address, &ts, 42).unwrap().unwrap();
+            assert_eq!(indexed.len(), 2);
+            assert!(indexed.contains(&bls.cid()) && indexed.contains(&secp.cid()));
+        }
+        assert_eq!(
+            cs.get_address_messages(&Address::new_id(3), &ts, 42)
+                .unwrap(),
+            Some(vec![])
+        );
+        // The epochs below the indexed range are not covered
+        assert_eq!(cs.get_address_messages(&bls.from, &ts, 0).unwrap(), None);
+    }
+
+    /// A block of a message from and to ID addresses
+    fn message_block(from: u64, to: u64) -> (crate::test_utils::BlockSpec, Cid) {
+        let message = Message {
+            from: Address::new_id(from),
+            to: Address::new_id(to),
+            ..Default::default()
+        };
+        let cid = message.cid();
+        let block = crate::test_utils::BlockSpec {
+            bls_messages: vec![message],
+            ..Default::default()
+        };
+        (block, cid)
+    }
+
+    #[tokio::test]
+    async fn address_messages_index_reorg() {
+        let harness = crate::test_utils::ChainHarness::new();
+        let cs = &harness.chain_store;
+        let (block1, msg1) = message_block(2, 1);
+        let (block2, msg2) = message_block(3, 1);
+        let ts1 = harness.produce(0, [block1]).unwrap().into_tipset();
+        let ts2 = harness.produce(0, [block2]).unwrap().into_tipset();
+        cs.put_address_messages(&ts1).unwrap();
+        cs.put_address_messages(&ts2).unwrap();
+        let messages = |address, ts: &Tipset| {
+            cs.get_address_messages(&Address::new_id(address), ts, 1)
+                .unwrap()
+        };
+        assert_eq!(messages(1, &ts2), Some(vec![msg2, msg1]));
+
+        // A fork of two tipsets from `ts1`, only its head is indexed
+        cs.set_heaviest_tipset(ts1.clone()).unwrap();
+        let (fork_block2, fork_msg2) = message_block(4, 1);
+        let (fork_block3, fork_msg3) = message_block(3, 5);
+        let fork2 = harness.produce(0, [fork_block2]).unwrap().into_tipset();
+        let fork3 = harness.produce(0, [fork_block3]).unwrap().into_tipset();
+        assert_eq!(fork2.epoch(), ts2.epoch());
+        cs.put_address_messages(&fork3).unwrap();
+        assert_eq!(messages(1, &fork3), Some(vec![fork_msg2, msg1]));
+        assert_eq!(messages(3, &fork3), Some(vec![fork_msg3]));
+        assert_eq!(messages(5, &fork3), Some(vec![fork_msg3]));
+
+        // The messages of the abandoned fork are kept, in case it comes back
+        cs.put_address_messages(&ts2).unwrap();
+        assert_eq!(messages(1, &ts2), Some(vec![msg2, msg1]));
+        assert_eq!(messages(3, &ts2), Some(vec![msg2]));
+
+        // The tipsets of a fork that was never indexed are not covered
+        cs.set_heaviest_tipset(ts1.clone()).unwrap();
+        let (other_block, _) = message_block(6, 1);
+        let other = harness
+            .produce(
+                0,
+                [crate::test_utils::BlockSpec {
+                    miner: Some(Address::new_id(7)),
+                    ..other_block
+                }],
+            )
+            .unwrap()
+            .into_tipset();
+        assert_eq!(messages(1, &other), None);
+    }
+
+    #[tokio::test]
+    async fn address_messages_index_backfill_and_prune() {
+        let harness = crate::test_utils::ChainHarness::new();
+        let cs = &harness.chain_store;
+        let tipsets = harness
+            .produce_with(3, |_, epoch| vec![message_block(2, epoch as u64 + 10).0])
+            .unwrap()
+            .into_iter()
+            .map(crate::blocks::FullTipset::into_tipset)
+            .collect_vec();
+        let messages = |min_epoch| {
+            cs.get_address_messages(&Address::new_id(2), &tipsets[2], min_epoch)
+                .unwrap()
+                .map(|messages| messages.len())
+        };
+        cs.put_address_messages(&tipsets[2]).unwrap();
+        assert_eq!(messages(3), Some(1));
+        assert_eq!(messages(1), None);
+
+        // Only the parents of the first indexed tipset extend the range
+        cs.put_address_messages(&tipsets[0]).unwrap();
+        assert_eq!(messages(1), None);
+        cs.put_address_messages(&tipsets[1]).unwrap();
+        cs.put_address_messages(&tipsets[0]).unwrap();
+        assert_eq!(messages(1), Some(3));
+        let range = cs.get_address_messages_range().unwrap().unwrap();
+        assert_eq!((range.from, range.to), (1, 3));
+        assert_eq!(&range.from_parents, tipsets[0].parents());
+
+        assert_eq!(cs.prune_address_messages(&tipsets[2], 3).unwrap(), 2);
+        assert_eq!(messages(1), None);
+        assert_eq!(messages(3), Some(1));
+        let pruned = |epoch: ChainEpoch| {
+            cs.eth_mappings
+                .read_bin(&address_messages_key(&Address::new_id(2), epoch))
+                .unwrap()
+                .is_none()
+        };
+        assert!(pruned(1) && pruned(2) && !pruned(3));
+        let epochs: Vec<ChainEpoch> = cs
+            .eth_mappings
+            .read_obj(&address_messages_epochs_key(&Address::new_id(2)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(epochs, vec![3]);
+        // Nothing is pruned twice
+        assert_eq!(cs.prune_address_messages(&tipsets[2], 3).unwrap(), 0);
+    }
+
+    #[test]
+    fn eth_address_actor_id_index() {
+        let db = Arc::new(crate::db::MemoryDB::default());
+        let chain_config = Arc::new(ChainConfig::default());
+        let header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(0),
+            ..Default::default()
+        });
+        let cs = ChainStore::new(db.clone(), db.clone(), db, chain_config, header).unwrap();
+
+        let eth_address = EthAddress(ethereum_types::H160::repeat_byte(0xab));
+        let other_eth_address = EthAddress(ethereum_types::H160::repeat_byte(0xcd));
+        assert_eq!(cs.get_eth_address_actor_id(&eth_address).unwrap(), None);
+        cs.put_actor_eth_address(1234, Some(&eth_address), 10)
+            .unwrap();
+        assert_eq!(
+            cs.get_eth_address_actor_id(&eth_address).unwrap(),
+            Some(1234)
+        );
+        assert_eq!(
+            cs.get_actor_id_eth_address(1234).unwrap(),
+            Some(eth_address)
+        );
+
+        // A reorg assigns the address to the next ID, and the ID to another address
+        cs.put_actor_eth_address(1235, Some(&eth_address), 11)
+            .unwrap();
+        cs.put_actor_eth_address(1234, Some(&other_eth_address), 11)
+            .unwrap();
+        assert_eq!(
+            cs.get_eth_address_actor_id(&eth_address).unwrap(),
+            Some(1235)
+        );
+        assert_eq!(
+            cs.get_eth_address_actor_id(&other_eth_address).unwrap(),
+            Some(1234)
+        );
+        assert_eq!(
+            cs.get_actor_id_eth_address(1234).unwrap(),
+            Some(other_eth_address)
+        );
+
+        // Another reorg assigns the ID to an actor without a delegated address
+        cs.put_actor_eth_address(1234, None, 12).unwrap();
+        assert_eq!(
+            cs.get_eth_address_actor_id(&other_eth_address).unwrap(),
+            None
+        );
+        assert_eq!(cs.get_actor_id_eth_address(1234).unwrap(), None);
+        assert_eq!(
+            cs.get_eth_address_actor_id(&eth_address).unwrap(),
+            Some(1235)
+        );
+
+        cs.put_actor_eth_address(1236, Some(&other_eth_address), 12)
+            .unwrap();
+        assert_eq!(cs.prune_actor_eth_addresses(10).unwrap(), 0);
+        // Only the actors of the pruned epochs are dropped
+        assert_eq!(cs.prune_actor_eth_addresses(12).unwrap(), 1);
+        assert_eq!(cs.get_eth_address_actor_id(&eth_address).unwrap(), None);
+        assert_eq!(cs.get_actor_id_eth_address(1235).unwrap(), None);
+        assert_eq!(
+            cs.get_eth_address_actor_id(&other_eth_address).unwrap(),
+            Some(1236)
+        );
+        assert_eq!(cs.prune_actor_eth_addresses(12).unwrap(), 0);
+    }
+
     #[test]
     fn test_messages_in_tipset_cache() {
         let cache = MsgsInTipsetCache::new(2.try_into().unwrap());
@@ -0,0 +1,237 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Checkpoints are tipsets of the canonical chain the node refuses to deviate from, which
+//! protects it from long-range forks. The built-in checkpoints are the known blocks of mainnet
+//! and calibnet, operators can add their own to a YAML file that maps epochs to tipset keys:
+//!
+//! ```yaml
+//! 3369600:
+//!   - bafy2bzacecvz2ji6npz7kpgddib33uvprficdoabxghftivjcipy62msc3adi
+//! ```
+//!
+//! The tipset of a checkpoint must contain all the blocks of its key, so a checkpoint of a single
+//! block, like the built-in ones, accepts the complete tipset.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cid::Cid;
+use nunny::Vec as NonEmpty;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+use crate::blocks::TipsetKey;
+use crate::networks::NetworkChain;
+use crate::shim::clock::ChainEpoch;
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct CheckpointsFile(
+    #[serde_as(as = "BTreeMap<_, Vec<DisplayFromStr>>")] BTreeMap<ChainEpoch, Vec<Cid>>,
+);
+
+#[derive(Debug, Default)]
+pub struct Checkpoints {
+    builtin: BTreeMap<ChainEpoch, TipsetKey>,
+    operator: BTreeMap<ChainEpoch, TipsetKey>,
+    /// File the operator checkpoints are persisted to
+    file: Option<PathBuf>,
+}
+
+impl Checkpoints {
+    /// The built-in checkpoints of the network, from `build/known_blocks.yaml`.
+    pub fn builtin(network: &NetworkChain) -> Self {
+        // The known blocks are the minimum ticket blocks of their tipsets.
+        #[serde_as]
+        #[derive(Deserialize)]
+        struct KnownBlocks {
+            #[serde_as(as = "BTreeMap<_, DisplayFromStr>")]
+            calibnet: BTreeMap<ChainEpoch, Cid>,
+            #[serde_as(as = "BTreeMap<_, DisplayFromStr>")]
+            mainnet: BTreeMap<ChainEpoch, Cid>,
+        }
+
+        let known_blocks: KnownBlocks =
+            serde_yaml::from_str(include_str!("../../../build/known_blocks.yaml"))
+                .expect("invalid known blocks");
+        let builtin = match network {
+            NetworkChain::Mainnet => known_blocks.mainnet,
+            NetworkChain::Calibnet => known_blocks.calibnet,
+            NetworkChain::Butterflynet | NetworkChain::Devnet(_) => BTreeMap::new(),
+        };
+        Self {
+            builtin: builtin
+                .into_iter()
+                .map(|(epoch, cid)| (epoch, TipsetKey::from(nunny::vec![cid])))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Loads the operator checkpoints from the file, if it exists, and persists the checkpoints
+    /// added later to it.
+    pub fn load_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read checkpoints {}", path.display()))?;
+            let file: CheckpointsFile = serde_yaml::from_str(&content)
+                .with_context(|| format!("invalid checkpoints {}", path.display()))?;
+            self.operator = to_tipset_keys(file)?;
+        }
+        self.file = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Adds an operator checkpoint, and persists it to the checkpoints file if there is one.
+    pub fn add(&mut self, epoch: ChainEpoch, key: TipsetKey) -> anyhow::Result<()> {
+        if let Some(existing) = self.get(epoch)
+            && !is_checkpoint_of(existing, &key)
+        {
+            anyhow::bail!("conflicts with the checkpoint {existing} at epoch {epoch}");
+        }
+        self.operator.insert(epoch, key);
+        if let Some(path) = &self.file {
+            let file = CheckpointsFile(
+                self.operator
+                    .iter()
+                    .map(|(epoch, key)| (*epoch, key.iter().collect()))
+                    .collect(),
+            );
+            std::fs::write(path, serde_yaml::to_string(&file)?)
+                .with_context(|| format!("failed to write checkpoints {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the key of the checkpoint at the epoch, operator checkpoints take precedence.
+    pub fn get(&self, epoch: ChainEpoch) -> Option<&TipsetKey> {
+        self.operator
+            .get(&epoch)
+            .or_else(|| self.builtin.get(&epoch))
+    }
+
+    /// Returns the first checkpoint at or after the epoch.
+    pub fn next(&self, epoch: ChainEpoch) -> Option<(ChainEpoch, &TipsetKey)> {
+        let operator = self.operator.range(epoch..).next();
+        let builtin = self.builtin.range(epoch..).next();
+        match (operator, builtin) {
+            (Some(operator), Some(builtin)) if builtin.0 < operator.0 => Some(builtin),
+            (Some(operator), _) => Some(operator),
+            (None, builtin) => builtin,
+        }
+        .map(|(epoch, key)| (*epoch, key))
+    }
+
+    /// Checks that a tipset, whose parent tipset is at `parent_epoch`, doesn't deviate from the
+    /// checkpoints: it must match the checkpoint at its epoch, and must not skip one.
+    pub fn check(
+        &self,
+        epoch: ChainEpoch,
+        key: &TipsetKey,
+        parent_epoch: ChainEpoch,
+    ) -> Result<(), String> {
+        if let Some(checkpoint) = self.get(epoch)
+            && !is_checkpoint_of(checkpoint, key)
+        {
+            return Err(format!(
+                "tipset {key} deviates from the checkpoint {checkpoint} at epoch {epoch}"
+            ));
+        }
+        if let Some((checkpoint_epoch, checkpoint)) = self.next(parent_epoch.saturating_add(1))
+            && checkpoint_epoch < epoch
+        {
+            return Err(format!(
+                "tipset {key} skips the checkpoint {checkpoint} at epoch {checkpoint_epoch}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if the tipset of the key contains all the blocks of the checkpoint.
+pub fn is_checkpoint_of(checkpoint: &TipsetKey, key: &TipsetKey) -> bool {
+    checkpoint.iter().all(|cid| key.contains(cid))
+}
+
+fn to_tipset_keys(file: CheckpointsFile) -> anyhow::Result<BTreeMap<ChainEpoch, TipsetKey>> {
+    file.0
+        .into_iter()
+        .map(|(epoch, cids)| {
+            let cids = NonEmpty::new(cids)
+                .map_err(|_| anyhow::anyhow!("empty checkpoint at epoch {epoch}"))?;
+            Ok((epoch, TipsetKey::from(cids)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::multihash::prelude::*;
+    use fvm_ipld_encoding::DAG_CBOR;
+
+    fn cid(n: u8) -> Cid {
+        Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&[n]))
+    }
+
+    fn key(cids: &[Cid]) -> TipsetKey {
+        TipsetKey::from(NonEmpty::new(cids.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn builtin_checkpoints() {
+        assert!(
+            Checkpoints::builtin(&NetworkChain::Mainnet)
+                .next(0)
+                .is_some()
+        );
+        assert!(
+            Checkpoints::builtin(&NetworkChain::Calibnet)
+                .next(0)
+                .is_some()
+        );
+        assert!(
+            Checkpoints::builtin(&NetworkChain::Devnet("devnet".into()))
+                .next(0)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn check_checkpoints() {
+        let mut checkpoints = Checkpoints::default();
+        checkpoints.builtin.insert(100, key(&[cid(1)]));
+        checkpoints.add(200, key(&[cid(2), cid(3)])).unwrap();
+
+        // The tipset of a checkpoint may have more blocks.
+        checkpoints.check(100, &key(&[cid(1), cid(4)]), 99).unwrap();
+        checkpoints.check(100, &key(&[cid(4)]), 99).unwrap_err();
+        checkpoints
+            .check(200, &key(&[cid(2), cid(3)]), 100)
+            .unwrap();
+        checkpoints.check(200, &key(&[cid(2)]), 100).unwrap_err();
+        // Tipsets must not skip checkpoints with null rounds.
+        checkpoints.check(150, &key(&[cid(5)]), 100).unwrap();
+        checkpoints.check(201, &key(&[cid(5)]), 150).unwrap_err();
+        checkpoints.check(201, &key(&[cid(5)]), 200).unwrap();
+
+        checkpoints.add(100, key(&[cid(1), cid(4)])).unwrap();
+        checkpoints.add(200, key(&[cid(4)])).unwrap_err();
+    }
+
+    #[test]
+    fn checkpoints_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoints.yaml");
+        let mut checkpoints = Checkpoints::default();
+        checkpoints.load_file(&path).unwrap();
+        checkpoints.add(200, key(&[cid(2), cid(3)])).unwrap();
+
+        let mut loaded = Checkpoints::default();
+        loaded.load_file(&path).unwrap();
+        assert_eq!(loaded.get(200), Some(&key(&[cid(2), cid(3)])));
+    }
+}
@@ -11,11 +11,13 @@ use crate::metrics;
 use crate::shim::clock::ChainEpoch;
 use crate::utils::cache::SizeTrackingLruCache;
 use crate::utils::misc::env::is_env_truthy;
+use crate::utils::misc::memory_budget::size_from_env_or_memory_budget;
 use fvm_ipld_blockstore::Blockstore;
-use itertools::Itertools;
 use nonzero_ext::nonzero;
 use num::Integer;
 
+/// Size of the tipset cache at the reference memory budget, it can be overridden with
+/// `FOREST_TIPSET_CACHE_SIZE`.
 const DEFAULT_TIPSET_CACHE_SIZE: NonZeroUsize = nonzero!(131072_usize);
 
 type TipsetCache = SizeTrackingLruCache<TipsetKey, Tipset>;
@@ -38,10 +40,39 @@ pub enum ResolveNullTipset {
     TakeOlder,
 }
 
+/// The tipset at an epoch of a chain, or the tipsets around the epoch if it is a null round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TipsetAtHeight {
+    Tipset(Tipset),
+    /// The epoch is a null round, `older` and `newer` are the nearest tipsets of the chain
+    /// before and after it.
+    NullRound {
+        older: Tipset,
+        newer: Tipset,
+    },
+}
+
+impl TipsetAtHeight {
+    /// Returns the tipset at the epoch, or the tipset picked by `resolve` for a null round.
+    pub fn resolve(self, resolve: ResolveNullTipset) -> Tipset {
+        match (self, resolve) {
+            (Self::Tipset(tipset), _)
+            | (Self::NullRound { older: tipset, .. }, ResolveNullTipset::TakeOlder)
+            | (Self::NullRound { newer: tipset, .. }, ResolveNullTipset::TakeNewer) => tipset,
+        }
+    }
+}
+
 impl<DB: Blockstore> ChainIndex<DB> {
     pub fn new(db: DB) -> Self {
-        let ts_cache =
-            SizeTrackingLruCache::new_with_metrics("tipset".into(), DEFAULT_TIPSET_CACHE_SIZE);
+        let cache_size = NonZeroUsize::new(size_from_env_or_memory_budget(
+            "FOREST_TIPSET_CACHE_SIZE",
+            DEFAULT_TIPSET_CACHE_SIZE.get(),
+            1024,
+            1 << 20,
+        ))
+        .unwrap_or(DEFAULT_TIPSET_CACHE_SIZE);
+        let ts_cache = SizeTrackingLruCache::new_with_metrics("tipset".into(), cache_size);
         Self { ts_cache, db }
     }
 
@@ -49,6 +80,15 @@ impl<DB: Blockstore> ChainIndex<DB> {
         &self.db
     }
 
+    /// Returns an index over `db` sharing the tipset cache of this index. The tipsets of `db`
+    /// must be those of this index, e.g. `db` wraps the store of this index.
+    pub fn with_db<DB2: Blockstore>(&self, db: DB2) -> ChainIndex<DB2> {
+        ChainIndex {
+            ts_cache: self.ts_cache.clone(),
+            db,
+        }
+    }
+
     /// Loads a tipset from memory given the tipset keys and cache. Semantically
     /// identical to [`Tipset::load`] but the result is cached.
     pub fn load_tipset(&self, tsk: &TipsetKey) -> Result<Option<Tipset>, Error> {
@@ -117,13 +157,24 @@ impl<DB: Blockstore> ChainIndex<DB> {
     /// Pick the nearest older tipset or pick the nearest younger tipset.
     /// Requesting epoch 2 with [`ResolveNullTipset::TakeNewer`] will return
     /// epoch 3. Requesting with [`ResolveNullTipset::TakeOlder`] will return
-    /// epoch 1.
+    /// epoch 1. Use [`ChainIndex::tipset_at_height`] to tell null rounds apart.
     pub fn tipset_by_height(
         &self,
         to: ChainEpoch,
-        mut from: Tipset,
+        from: Tipset,
         resolve: ResolveNullTipset,
     ) -> Result<Tipset, Error> {
+        Ok(self.tipset_at_height(to, from)?.resolve(resolve))
+    }
+
+    /// Finds the tipset at epoch `to` in the chain of ancestors starting at `from`, like
+    /// [`ChainIndex::tipset_by_height`], but reports null rounds explicitly. Fails if the
+    /// ancestors down to the epoch are missing from the store, which is not a null round.
+    pub fn tipset_at_height(
+        &self,
+        to: ChainEpoch,
+        mut from: Tipset,
+    ) -> Result<TipsetAtHeight, Error> {
         use crate::shim::policy::policy_constants::CHAIN_FINALITY;
 
         static CACHE: LazyLock<SizeTrackingLruCache<ChainEpoch, TipsetKey>> = LazyLock::new(|| {
@@ -139,6 +190,21 @@ impl<DB: Blockstore> ChainIndex<DB> {
             epoch - epoch.mod_floor(&CHAIN_FINALITY) + CHAIN_FINALITY
         }
 
+        if to < 0 {
+            return Err(Error::Other(format!(
+                "looking for tipset with negative height: {to}"
+            )));
+        }
+        if to > from.epoch() {
+            return Err(Error::Other(format!(
+                "looking for tipset with height greater than start point, req: {to}, head: {from}",
+                from = from.epoch()
+            )));
+        }
+        if to == from.epoch() {
+            return Ok(TipsetAtHeight::Tipset(from));
+        }
+
         let from_epoch = from.epoch();
 
         let mut checkpoint_from_epoch = to;
@@ -153,16 +219,13 @@ impl<DB: Blockstore> ChainIndex<DB> {
         }
 
         if to == 0 {
-            return Ok(Tipset::from(from.genesis(&self.db)?));
-        }
-        if to > from.epoch() {
-            return Err(Error::Other(format!(
-                "looking for tipset with height greater than start point, req: {to}, head: {from}",
-                from = from.epoch()
+            return Ok(TipsetAtHeight::Tipset(Tipset::from(
+                from.genesis(&self.db)?,
             )));
         }
 
-        for (child, parent) in self.chain(from).tuple_windows() {
+        let mut child = from;
+        loop {
             // use `child.epoch() + CHAIN_FINALITY <= from_epoch`
             // to ensure the cached child is finalized(not on a fork).
             if child.epoch() % CHAIN_FINALITY == 0 && child.epoch() + CHAIN_FINALITY <= from_epoch {
@@ -170,19 +233,23 @@ impl<DB: Blockstore> ChainIndex<DB> {
             }
 
             if to == child.epoch() {
-                return Ok(child);
+                return Ok(TipsetAtHeight::Tipset(child));
             }
+            let Some(parent) = self.load_tipset(child.parents())? else {
+                return Err(Error::NotFound(format!(
+                    "Tipset with epoch={to} (the parents of the tipset at epoch {} are missing)",
+                    child.epoch()
+                )));
+            };
             if to > parent.epoch() {
                 // We're at a point where child.epoch() > x > parent.epoch().
-                match resolve {
-                    ResolveNullTipset::TakeOlder => return Ok(parent),
-                    ResolveNullTipset::TakeNewer => return Ok(child),
-                }
+                return Ok(TipsetAtHeight::NullRound {
+                    older: parent,
+                    newer: child,
+                });
             }
+            child = parent;
         }
-        Err(Error::Other(format!(
-            "Tipset with epoch={to} does not exist"
-        )))
     }
 
     /// Iterate from the given tipset to genesis. Missing tipsets cut the chain
@@ -268,6 +335,17 @@ mod tests {
 
         let index = ChainIndex::new(db);
         // epoch 2 is null. ResolveNullTipset decided whether to return epoch 1 or epoch 3
+        assert_eq!(
+            index.tipset_at_height(2, epoch4.clone()).unwrap(),
+            TipsetAtHeight::NullRound {
+                older: epoch1.clone(),
+                newer: epoch3.clone()
+            }
+        );
+        assert_eq!(
+            index.tipset_at_height(3, epoch4.clone()).unwrap(),
+            TipsetAtHeight::Tipset(epoch3.clone())
+        );
         assert_eq!(
             index
                 .tipset_by_height(2, epoch4.clone(), ResolveNullTipset::TakeOlder)
@@ -283,6 +361,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_missing_tipset() {
+        let db = Arc::new(MemoryDB::default());
+        let genesis = genesis_tipset();
+        let epoch1 = tipset_child(&genesis, 1);
+        let epoch2 = tipset_child(&epoch1, 2);
+        let epoch3 = tipset_child(&epoch2, 3);
+        // The chain is only known down to epoch 2
+        persist_tipset(&epoch2, &db);
+        persist_tipset(&epoch3, &db);
+
+        let index = ChainIndex::new(db);
+        assert_eq!(
+            index.tipset_at_height(3, epoch3.clone()).unwrap(),
+            TipsetAtHeight::Tipset(epoch3.clone())
+        );
+        assert_eq!(
+            index.tipset_at_height(2, epoch3.clone()).unwrap(),
+            TipsetAtHeight::Tipset(epoch2)
+        );
+        assert!(matches!(
+            index.tipset_at_height(1, epoch3.clone()),
+            Err(Error::NotFound(_))
+        ));
+        assert!(index.tipset_at_height(-1, epoch3.clone()).is_err());
+        assert!(index.tipset_at_height(4, epoch3).is_err());
+    }
+
     #[test]
     fn get_different_branches() {
         let db = Arc::new(MemoryDB::default());
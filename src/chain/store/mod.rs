@@ -3,8 +3,10 @@
 
 pub mod base_fee;
 mod chain_store;
+pub mod checkpoints;
 mod errors;
 pub mod index;
 mod tipset_tracker;
+pub mod warm_restart;
 
 pub use self::{base_fee::*, chain_store::*, errors::*};
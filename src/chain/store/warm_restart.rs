@@ -0,0 +1,147 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Hints persisted in the settings store for a restarted node to reach follow mode quickly: the
+//! blocks of the recent tipsets that were fully validated, so that they are not validated again
+//! when peers announce them, and the keys of the recent tipsets, to warm the tipset cache up. The
+//! recently computed tipset states are persisted by the state manager.
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use serde::{Deserialize, Serialize};
+
+use super::ChainStore;
+use crate::blocks::TipsetKey;
+use crate::db::{SettingsStore, SettingsStoreExt as _, setting_keys::WARM_RESTART_HINTS_KEY};
+use crate::shim::clock::ChainEpoch;
+
+/// Number of tipsets below the head the hints cover, half an hour of chain
+const WARM_RESTART_TIPSETS: usize = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarmRestartHints {
+    /// Key of the heaviest tipset when the hints were saved
+    pub head: TipsetKey,
+    pub head_epoch: ChainEpoch,
+    /// Keys of the tipsets of the chain from the head down, most recent first
+    pub recent_tipsets: Vec<TipsetKey>,
+    /// Blocks of the recent tipsets that were fully validated
+    pub validated_blocks: Vec<Cid>,
+}
+
+impl WarmRestartHints {
+    /// Collects the hints of the heaviest tipset of the chain store.
+    pub fn collect<DB: Blockstore>(cs: &ChainStore<DB>) -> Self {
+        let head = cs.heaviest_tipset();
+        let mut recent_tipsets = Vec::with_capacity(WARM_RESTART_TIPSETS);
+        let mut validated_blocks = vec![];
+        for ts in cs
+            .chain_index()
+            .chain(head.clone())
+            .take(WARM_RESTART_TIPSETS)
+        {
+            validated_blocks.extend(
+                ts.cids()
+                    .into_iter()
+                    .filter(|cid| cs.is_block_validated(cid)),
+            );
+            recent_tipsets.push(ts.key().clone());
+        }
+        Self {
+            head: head.key().clone(),
+            head_epoch: head.epoch(),
+            recent_tipsets,
+            validated_blocks,
+        }
+    }
+
+    pub fn load(store: &dyn SettingsStore) -> anyhow::Result<Option<Self>> {
+        store.read_obj(WARM_RESTART_HINTS_KEY)
+    }
+
+    pub fn save(&self, store: &dyn SettingsStore) -> anyhow::Result<()> {
+        store.write_obj(WARM_RESTART_HINTS_KEY, self)
+    }
+
+    /// Applies the hints to the chain store if they were saved on its current chain, i.e. if the
+    /// head they were saved at is the heaviest tipset or one of its ancestors. Returns whether
+    /// they were applied.
+    pub fn apply<DB: Blockstore>(&self, cs: &ChainStore<DB>) -> bool {
+        let head = cs.heaviest_tipset();
+        let on_chain = self.head_epoch <= head.epoch()
+            && cs
+                .chain_index()
+                .chain(head)
+                .find(|ts| ts.epoch() <= self.head_epoch)
+                .is_some_and(|ts| ts.key() == &self.head);
+        if !on_chain {
+            return false;
+        }
+        for cid in &self.validated_blocks {
+            cs.mark_block_as_validated(cid);
+        }
+        for tsk in &self.recent_tipsets {
+            if let Err(e) = cs.chain_index().load_tipset(tsk) {
+                tracing::debug!("Failed to load the recent tipset {tsk}: {e}");
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{CachingBlockHeader, Chain4U, chain4u};
+    use crate::db::MemoryDB;
+    use crate::networks::ChainConfig;
+    use std::sync::Arc;
+
+    #[test]
+    fn save_and_apply_hints() {
+        let db = Arc::new(MemoryDB::default());
+        let c4u = Chain4U::with_blockstore(db.clone());
+        chain4u! {
+            in c4u;
+            [genesis] -> [b_1] -> [b_2_0, b_2_1] -> head @ [b_3]
+        };
+        chain4u! {
+            from [b_2_0] in c4u;
+            fork_head @ [fork]
+        };
+        let chain_store = || {
+            ChainStore::new(
+                db.clone(),
+                db.clone(),
+                db.clone(),
+                Arc::new(ChainConfig::default()),
+                CachingBlockHeader::new(genesis.clone()),
+            )
+            .unwrap()
+        };
+
+        let cs = chain_store();
+        cs.set_heaviest_tipset(head.clone()).unwrap();
+        for cid in [b_2_0.cid(), b_2_1.cid(), b_3.cid()] {
+            cs.mark_block_as_validated(&cid);
+        }
+        let hints = WarmRestartHints::collect(&cs);
+        assert_eq!(hints.recent_tipsets.len(), 4);
+        assert_eq!(hints.validated_blocks.len(), 3);
+        hints.save(db.as_ref()).unwrap();
+
+        // The hints are applied after a restart on the same chain
+        let cs = chain_store();
+        let hints = WarmRestartHints::load(db.as_ref()).unwrap().unwrap();
+        assert!(!cs.is_block_validated(&b_3.cid()));
+        assert!(hints.apply(&cs));
+        assert!(cs.is_block_validated(&b_3.cid()));
+        assert!(cs.is_block_validated(&b_2_1.cid()));
+
+        // and ignored on another chain
+        let cs = chain_store();
+        cs.set_heaviest_tipset(fork_head.clone()).unwrap();
+        assert!(!hints.apply(&cs));
+        assert!(!cs.is_block_validated(&b_2_0.cid()));
+    }
+}
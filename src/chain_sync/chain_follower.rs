@@ -23,15 +23,24 @@ use crate::{
     chain_sync::{
         ForkSyncInfo, ForkSyncStage, SyncStatus, SyncStatusReport, TipsetValidator,
         bad_block_cache::BadBlockCache,
+        chain_muxer::DEFAULT_PERSIST_BATCH_EPOCHS,
+        checkpoint::{SyncCheckpointStore, load_checkpointed_tipsets},
         metrics,
+        sync_status::MAX_RECENT_ERRORS,
         tipset_syncer::{TipsetSyncerError, validate_tipset},
+        validate_header_fast,
+    },
+    db::{
+        BlockKind, BlockstoreWithWriteBuffer, SettingsStore, WriteBufferConfig,
+        disk_space::{self, DiskSpaceStatus},
+        with_block_kind,
     },
     libp2p::{NetworkEvent, PubsubMessage, hello::HelloRequest},
     message_pool::{MessagePool, MpoolRpcProvider},
     networks::calculate_expected_epoch,
     shim::clock::ChainEpoch,
     state_manager::StateManager,
-    utils::misc::env::is_env_truthy,
+    utils::misc::env::{env_or_default, is_env_truthy},
 };
 use ahash::{HashMap, HashSet};
 use chrono::Utc;
@@ -40,10 +49,72 @@ use fvm_ipld_blockstore::Blockstore;
 use itertools::Itertools;
 use libp2p::PeerId;
 use parking_lot::{Mutex, RwLock};
-use std::{sync::Arc, time::Instant};
+use std::{
+    cmp::Reverse,
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::{sync::Notify, task::JoinSet};
 use tracing::{debug, error, info, trace, warn};
 
+/// Number of epochs without head progress, while peers report higher heads, after which the sync
+/// is considered stalled.
+const DEFAULT_HEAD_STALL_EPOCHS: u64 = 10;
+/// Maximum number of peers whose heads are re-requested when the sync is stalled.
+const HEAD_STALL_MAX_PEERS: usize = 5;
+/// Number of epochs behind the expected head of the network under which the node follows the
+/// head rather than catching up with it.
+const FOLLOW_MODE_EPOCHS: ChainEpoch = 10;
+
+/// Detects a chain head that stopped advancing while peers report higher heads.
+struct HeadWatchdog {
+    /// Number of checks without head progress after which the head is stalled
+    stall_epochs: u64,
+    last_head_epoch: ChainEpoch,
+    stalled_epochs: u64,
+}
+
+impl HeadWatchdog {
+    fn new(stall_epochs: u64, head_epoch: ChainEpoch) -> Self {
+        Self {
+            stall_epochs,
+            last_head_epoch: head_epoch,
+            stalled_epochs: 0,
+        }
+    }
+
+    /// Checks the head, once per block delay. When it has not advanced for `stall_epochs` checks,
+    /// returns the peers whose heads are higher, highest first, and starts counting again. The
+    /// head is checked again on the next call if no peer is ahead.
+    fn check(
+        &mut self,
+        head_epoch: ChainEpoch,
+        peer_heads: &HashMap<PeerId, (ChainEpoch, TipsetKey)>,
+    ) -> Option<Vec<(PeerId, ChainEpoch, TipsetKey)>> {
+        if head_epoch > self.last_head_epoch {
+            self.last_head_epoch = head_epoch;
+            self.stalled_epochs = 0;
+            return None;
+        }
+        self.stalled_epochs += 1;
+        if self.stalled_epochs < self.stall_epochs {
+            return None;
+        }
+        let peers_ahead = peer_heads
+            .iter()
+            .filter(|(_, (epoch, _))| *epoch > head_epoch)
+            .map(|(peer, (epoch, key))| (*peer, *epoch, key.clone()))
+            .sorted_by_key(|(_, epoch, _)| Reverse(*epoch))
+            .collect_vec();
+        if peers_ahead.is_empty() {
+            return None;
+        }
+        self.stalled_epochs = 0;
+        Some(peers_ahead)
+    }
+}
+
 pub struct ChainFollower<DB> {
     /// Syncing status of the chain
     pub sync_status: SyncStatus,
@@ -79,6 +150,12 @@ pub struct ChainFollower<DB> {
 
     /// Message pool
     mem_pool: Arc<MessagePool<MpoolRpcProvider<DB>>>,
+
+    /// Persisted progress of the sync, resumed after a restart
+    sync_checkpoint: Option<Arc<SyncCheckpointStore>>,
+
+    /// Number of epochs of downloaded tipsets persisted in a single transaction while catching up
+    persist_batch_epochs: usize,
 }
 
 impl<DB: Blockstore + Sync + Send + 'static> ChainFollower<DB> {
@@ -108,9 +185,25 @@ impl<DB: Blockstore + Sync + Send + 'static> ChainFollower<DB> {
             tipset_receiver,
             stateless_mode,
             mem_pool,
+            sync_checkpoint: None,
+            persist_batch_epochs: DEFAULT_PERSIST_BATCH_EPOCHS,
         }
     }
 
+    /// Persists the progress of the sync in the given settings store, so that after a crash or a
+    /// restart it resumes the range of tipsets it was working on.
+    pub fn with_sync_checkpoint(mut self, store: Arc<dyn SettingsStore + Sync + Send>) -> Self {
+        self.sync_checkpoint = Some(Arc::new(SyncCheckpointStore::new(store)));
+        self
+    }
+
+    /// Sets the number of epochs of downloaded tipsets persisted, and synced to disk, in a single
+    /// transaction while catching up with the network.
+    pub fn with_persist_batch_epochs(mut self, persist_batch_epochs: usize) -> Self {
+        self.persist_batch_epochs = persist_batch_epochs;
+        self
+    }
+
     pub async fn run(self) -> anyhow::Result<()> {
         chain_follower(
             self.state_manager,
@@ -122,6 +215,8 @@ impl<DB: Blockstore + Sync + Send + 'static> ChainFollower<DB> {
             self.sync_status,
             self.genesis,
             self.stateless_mode,
+            self.sync_checkpoint,
+            self.persist_batch_epochs,
         )
         .await
     }
@@ -139,6 +234,8 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
     sync_status: SyncStatus,
     genesis: Tipset,
     stateless_mode: bool,
+    sync_checkpoint: Option<Arc<SyncCheckpointStore>>,
+    persist_batch_epochs: usize,
 ) -> anyhow::Result<()> {
     let state_changed = Arc::new(Notify::new());
     let state_machine = Arc::new(Mutex::new(SyncStateMachine::new(
@@ -146,7 +243,25 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
         bad_block_cache.clone(),
         stateless_mode,
     )));
+    // Resume the range of tipsets the sync was working on before a crash or a restart.
+    if let Some(checkpoint) = sync_checkpoint.as_ref().and_then(|store| store.load()) {
+        let tipsets = load_checkpointed_tipsets(state_manager.chain_store(), &checkpoint);
+        info!(
+            "Resuming sync towards epoch {} from the checkpoint, validated up to epoch {}, {} tipset(s) already downloaded",
+            checkpoint.target_epoch,
+            checkpoint.validated_epoch,
+            tipsets.len()
+        );
+        state_machine
+            .lock()
+            .update(SyncEvent::NewFullTipsets(tipsets));
+        state_changed.notify_one();
+    }
     let tasks: Arc<Mutex<HashSet<SyncTask>>> = Arc::new(Mutex::new(HashSet::default()));
+    // Heaviest tipsets reported by peers in their hello messages.
+    let peer_heads: Arc<Mutex<HashMap<PeerId, (ChainEpoch, TipsetKey)>>> = Default::default();
+    // Most recent errors encountered by the sync tasks, for diagnostics.
+    let recent_errors: Arc<Mutex<VecDeque<String>>> = Default::default();
 
     let mut set = JoinSet::new();
 
@@ -156,10 +271,16 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
         let state_changed = state_changed.clone();
         let state_machine = state_machine.clone();
         let network = network.clone();
+        let mem_pool = mem_pool.clone();
+        let peer_heads = peer_heads.clone();
         async move {
             while let Ok(event) = network_rx.recv_async().await {
                 inc_gossipsub_event_metrics(&event);
 
+                if let NetworkEvent::PeerDisconnected(peer_id) = &event {
+                    peer_heads.lock().remove(peer_id);
+                }
+
                 update_peer_info(
                     &event,
                     &network,
@@ -170,6 +291,10 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
                 let Ok(tipset) = (match event {
                     NetworkEvent::HelloResponseOutbound { request, source } => {
                         let tipset_keys = TipsetKey::from(request.heaviest_tip_set.clone());
+                        peer_heads.lock().insert(
+                            source,
+                            (request.heaviest_tipset_height, tipset_keys.clone()),
+                        );
                         get_full_tipset(
                             &network,
                             state_manager.chain_store(),
@@ -182,6 +307,24 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
                     NetworkEvent::PubsubMessage { message } => match message {
                         PubsubMessage::Block(b) => {
                             let key = TipsetKey::from(nunny::vec![*b.header.cid()]);
+                            // Check the header before fetching the messages of the block.
+                            let header = b.header.clone();
+                            let checked = tokio::task::spawn_blocking({
+                                let state_manager = state_manager.clone();
+                                move || validate_header_fast(&state_manager, &header)
+                            })
+                            .await;
+                            match checked {
+                                Ok(Ok(())) => {}
+                                Ok(Err(e)) => {
+                                    debug!("Received invalid GossipSub block {key}: {e:#}");
+                                    continue;
+                                }
+                                Err(e) => {
+                                    warn!("Checking GossipSub block {key} failed: {e}");
+                                    continue;
+                                }
+                            }
                             get_full_tipset(&network, state_manager.chain_store(), None, &key).await
                         }
                         PubsubMessage::Message(m) => {
@@ -227,12 +370,17 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
         let state_changed = state_changed.clone();
         let tasks = tasks.clone();
         let bad_block_cache = bad_block_cache.clone();
+        let network = network.clone();
+        let recent_errors = recent_errors.clone();
         async move {
             loop {
                 state_changed.notified().await;
 
                 let mut tasks_set = tasks.lock();
                 let (task_vec, current_active_forks) = state_machine.lock().tasks();
+                if let Some(sync_checkpoint) = &sync_checkpoint {
+                    sync_checkpoint.save(&current_active_forks);
+                }
 
                 // Update the sync states
                 {
@@ -240,6 +388,7 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
                     let new_status_report = old_status_report.update(
                         &state_manager,
                         current_active_forks,
+                        recent_errors.lock().iter().cloned().collect(),
                         stateless_mode,
                     );
 
@@ -255,7 +404,11 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
                             state_manager.clone(),
                             stateless_mode,
                             bad_block_cache.clone(),
+                            recent_errors.clone(),
+                            persist_batch_epochs,
                         );
+                        // Sync may be paused to avoid filling the disk.
+                        let action = disk_space::pause_while(DiskSpaceStatus::sync_paused, action);
                         tokio::spawn({
                             let tasks = tasks.clone();
                             let state_machine = state_machine.clone();
@@ -302,9 +455,12 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
                     state_manager.chain_config().block_delay_secs,
                 );
 
-                // Only print 'Catching up to HEAD' if we're more than 10 epochs
-                // behind. Otherwise it can be too spammy.
-                match (expected_head - heaviest_epoch > 10, to_download > 0) {
+                // Only print 'Catching up to HEAD' if we're not following the head. Otherwise it
+                // can be too spammy.
+                match (
+                    expected_head - heaviest_epoch > FOLLOW_MODE_EPOCHS,
+                    to_download > 0,
+                ) {
                     (true, true) => info!(
                         "Catching up to HEAD: {heaviest_epoch}{} -> {expected_head}, downloading {to_download} tipsets"
                         , heaviest_tipset.key()
@@ -322,6 +478,67 @@ pub async fn chain_follower<DB: Blockstore + Sync + Send + 'static>(
         }
     });
 
+    // Watch the chain head. If it stops advancing while peers report higher heads, log diagnostics
+    // and feed the heads of those peers back into the state machine instead of waiting for new
+    // gossip or hello messages.
+    let head_stall_epochs = env_or_default("FOREST_HEAD_STALL_EPOCHS", DEFAULT_HEAD_STALL_EPOCHS);
+    if head_stall_epochs > 0 {
+        set.spawn({
+            let state_manager = state_manager.clone();
+            let state_machine = state_machine.clone();
+            let state_changed = state_changed.clone();
+            async move {
+                let block_delay =
+                    Duration::from_secs(state_manager.chain_config().block_delay_secs.into());
+                let mut watchdog = HeadWatchdog::new(
+                    head_stall_epochs,
+                    state_manager.chain_store().heaviest_tipset().epoch(),
+                );
+                loop {
+                    tokio::time::sleep(block_delay).await;
+                    let head = state_manager.chain_store().heaviest_tipset();
+                    let Some(peers_ahead) = watchdog.check(head.epoch(), &peer_heads.lock())
+                    else {
+                        continue;
+                    };
+                    let highest_peer_epoch = peers_ahead.first().map_or(0, |(_, epoch, _)| *epoch);
+                    metrics::HEAD_STALL_TOTAL.inc();
+
+                    let (active_tasks, _) = state_machine.lock().tasks();
+                    let mpool_size = mem_pool.pending().map(|(msgs, _)| msgs.len()).ok();
+                    warn!(
+                        "Chain head is stuck at epoch {} for {head_stall_epochs} epochs while {} peer(s) report higher heads (highest: {highest_peer_epoch}). Active sync tasks: {}, mpool size: {}, last error: {}. Re-requesting peer heads.",
+                        head.epoch(),
+                        peers_ahead.len(),
+                        active_tasks.len(),
+                        mpool_size.map_or_else(|| "unknown".into(), |it| it.to_string()),
+                        recent_errors.lock().back().map_or("none", String::as_str),
+                    );
+                    for (peer, epoch, key) in peers_ahead.into_iter().take(HEAD_STALL_MAX_PEERS) {
+                        match get_full_tipset(
+                            &network,
+                            state_manager.chain_store(),
+                            Some(peer),
+                            &key,
+                        )
+                        .await
+                        {
+                            Ok(tipset) => {
+                                state_machine
+                                    .lock()
+                                    .update(SyncEvent::NewFullTipsets(vec![tipset]));
+                                state_changed.notify_one();
+                            }
+                            Err(e) => {
+                                debug!("Querying head of peer {peer} at epoch {epoch} failed: {e}")
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     set.join_all().await;
     Ok(())
 }
@@ -452,6 +669,7 @@ async fn get_full_tipset_batch<DB: Blockstore + Sync + Send + 'static>(
     chain_store: &ChainStore<DB>,
     peer_id: Option<PeerId>,
     tipset_keys: &TipsetKey,
+    persist_batch_epochs: usize,
 ) -> anyhow::Result<Vec<FullTipset>> {
     // Attempt to load from the store
     if let Ok(full_tipset) = load_full_tipset(chain_store, tipset_keys) {
@@ -463,13 +681,59 @@ async fn get_full_tipset_batch<DB: Blockstore + Sync + Send + 'static>(
         .await
         .map_err(|e| anyhow::anyhow!(e))?;
 
-    for tipset in tipsets.iter() {
-        tipset.persist(chain_store.blockstore())?;
-    }
+    persist_full_tipsets(chain_store.blockstore(), &tipsets, persist_batch_epochs)?;
 
     Ok(tipsets)
 }
 
+/// Persists the tipsets into the blockstore in transactions of up to `batch_epochs` tipsets,
+/// rather than in several transactions per block. Every transaction is synced to disk.
+fn persist_full_tipsets<DB: Blockstore + Sync + Send + 'static>(
+    db: &Arc<DB>,
+    tipsets: &[FullTipset],
+    batch_epochs: usize,
+) -> anyhow::Result<()> {
+    // Buffers flushed explicitly, one per kind of block as it is set while writing.
+    let config = WriteBufferConfig {
+        max_blocks: usize::MAX,
+        max_bytes: usize::MAX,
+        background_flush: false,
+    };
+    for batch in tipsets.chunks(batch_epochs.max(1)) {
+        let headers = BlockstoreWithWriteBuffer::new(db.clone(), config);
+        let messages = BlockstoreWithWriteBuffer::new(db.clone(), config);
+        for tipset in batch {
+            tipset.persist_headers(&headers)?;
+            tipset.persist_messages(&messages)?;
+        }
+        // The messages are flushed first, so that the headers are never persisted without them
+        // if the node stops in between.
+        with_block_kind(BlockKind::Message, || messages.flush())?;
+        headers.flush()?;
+    }
+    Ok(())
+}
+
+/// Records an error of a sync task, keeping the [`MAX_RECENT_ERRORS`] most recent ones.
+fn record_error(recent_errors: &Mutex<VecDeque<String>>, error: String) {
+    let mut recent_errors = recent_errors.lock();
+    if recent_errors.len() >= MAX_RECENT_ERRORS {
+        recent_errors.pop_front();
+    }
+    recent_errors.push_back(error);
+}
+
+/// Returns whether the epoch is close enough to the expected head of the network for the node to
+/// be following the head.
+fn is_following_head<DB>(state_manager: &StateManager<DB>, epoch: ChainEpoch) -> bool {
+    let expected_head = calculate_expected_epoch(
+        Utc::now().timestamp() as u64,
+        state_manager.chain_store().genesis_block_header().timestamp,
+        state_manager.chain_config().block_delay_secs,
+    );
+    expected_head - epoch <= FOLLOW_MODE_EPOCHS
+}
+
 pub fn load_full_tipset<DB: Blockstore>(
     chain_store: &ChainStore<DB>,
     tipset_keys: &TipsetKey,
@@ -556,6 +820,8 @@ impl<DB: Blockstore> SyncStateMachine<DB> {
     }
 
     // Compute the list of chains from the tipsets map
+    /// Splits the tipsets into chains, ordered by the weight of their heaviest tipset, heaviest
+    /// first.
     fn chains(&self) -> Vec<Vec<FullTipset>> {
         let mut chains = Vec::new();
         let mut remaining_tipsets = self.tipsets.clone();
@@ -617,7 +883,7 @@ impl<DB: Blockstore> SyncStateMachine<DB> {
             &self.cs,
             self.bad_block_cache.as_ref().map(AsRef::as_ref),
             &self.cs.genesis_tipset(),
-            self.cs.chain_config().block_delay_secs,
+            self.cs.chain_config(),
         ) {
             metrics::INVALID_TIPSET_TOTAL.inc();
             trace!("Skipping invalid tipset: {}", why);
@@ -746,6 +1012,8 @@ impl<DB: Blockstore> SyncStateMachine<DB> {
         }
     }
 
+    /// Returns the next tasks of the forks in sync and their progress, ordered by the weight of
+    /// their heaviest tipset, heaviest first.
     pub fn tasks(&self) -> (Vec<SyncTask>, Vec<ForkSyncInfo>) {
         // Get the node's current validated head epoch once, as it's the same for all forks.
         let current_validated_epoch = self.cs.heaviest_tipset().epoch();
@@ -830,6 +1098,8 @@ impl SyncTask {
         state_manager: Arc<StateManager<DB>>,
         stateless_mode: bool,
         bad_block_cache: Option<Arc<BadBlockCache>>,
+        recent_errors: Arc<Mutex<VecDeque<String>>>,
+        persist_batch_epochs: usize,
     ) -> Option<SyncEvent> {
         tracing::trace!("SyncTask::execute {self}");
         match self {
@@ -858,15 +1128,30 @@ impl SyncTask {
                 }
                 Err(e) => {
                     warn!("Error validating tipset: {e}");
+                    record_error(&recent_errors, format!("validating tipset: {e}"));
                     Some(SyncEvent::BadTipset(tipset))
                 }
             },
             SyncTask::FetchTipset(key, epoch) => {
-                match get_full_tipset_batch(&network, state_manager.chain_store(), None, &key).await
+                // Tipsets are persisted, and synced to disk, every epoch when following the head.
+                let persist_batch_epochs = if is_following_head(&state_manager, epoch) {
+                    1
+                } else {
+                    persist_batch_epochs
+                };
+                match get_full_tipset_batch(
+                    &network,
+                    state_manager.chain_store(),
+                    None,
+                    &key,
+                    persist_batch_epochs,
+                )
+                .await
                 {
                     Ok(parents) => Some(SyncEvent::NewFullTipsets(parents)),
                     Err(e) => {
                         tracing::warn!(%key, %epoch, "failed to fetch tipset: {e}");
+                        record_error(&recent_errors, format!("fetching tipset: {e}"));
                         None
                     }
                 }
@@ -1012,6 +1297,118 @@ mod tests {
         assert_eq!(validation_tasks, vec![1, 2, 3, 4, 5]);
     }
 
+    #[test]
+    fn test_resume_from_sync_checkpoint() {
+        let (cs, c4u) = setup();
+        let db = cs.blockstore().clone();
+
+        chain4u! {
+            from [genesis_header] in c4u;
+            [a = dummy_node(&db, 1)] -> [b = dummy_node(&db, 2)] -> [c = dummy_node(&db, 3)]
+        };
+
+        let mut state_machine = SyncStateMachine::new(cs.clone(), Default::default(), false);
+        for block in [a, b, c] {
+            let full_tipset = FullTipset::new(vec![Block {
+                header: block.clone().into(),
+                bls_messages: vec![],
+                secp_messages: vec![],
+            }])
+            .unwrap();
+            state_machine.update(SyncEvent::NewFullTipsets(vec![full_tipset]));
+        }
+        let (tasks, forks) = state_machine.tasks();
+        let checkpoints = SyncCheckpointStore::new(db.clone());
+        checkpoints.save(&forks);
+
+        // After a restart, the range in flight is loaded back from the database.
+        let checkpoint = SyncCheckpointStore::new(db.clone()).load().unwrap();
+        assert_eq!(checkpoint.target_epoch, 3);
+        assert_eq!(checkpoint.range_start_epoch, 1);
+        let tipsets = load_checkpointed_tipsets(&cs, &checkpoint);
+        assert_eq!(
+            tipsets.iter().map(|ts| ts.epoch()).collect_vec(),
+            vec![3, 2, 1]
+        );
+        let mut resumed = SyncStateMachine::new(cs, Default::default(), false);
+        resumed.update(SyncEvent::NewFullTipsets(tipsets));
+        assert_eq!(resumed.tasks().0, tasks);
+
+        // The checkpoint is cleared once there is nothing left to sync.
+        checkpoints.save(&[]);
+        assert!(SyncCheckpointStore::new(db).load().is_none());
+    }
+
+    #[test]
+    fn test_sync_checkpoint_of_heaviest_fork() {
+        let (cs, c4u) = setup();
+        let db = cs.blockstore().clone();
+
+        chain4u! {
+            from [genesis_header] in c4u;
+            [a = dummy_node(&db, 1)] -> [b = dummy_node(&db, 2)]
+        };
+        chain4u! {
+            from [genesis_header] in c4u;
+            [x = HeaderBuilder {
+                weight: BigInt::from(10).into(),
+                ..dummy_node(&db, 1)
+            }]
+        };
+
+        let tipsets = [a, b, x].map(|block| {
+            FullTipset::new(vec![Block {
+                header: block.clone().into(),
+                bls_messages: vec![],
+                secp_messages: vec![],
+            }])
+            .unwrap()
+        });
+        let heaviest = tipsets[2].key().clone();
+
+        // The heavier fork is added last.
+        let mut state_machine = SyncStateMachine::new(cs.clone(), Default::default(), false);
+        for tipset in tipsets {
+            state_machine.update(SyncEvent::NewFullTipsets(vec![tipset]));
+        }
+        let (_, forks) = state_machine.tasks();
+        assert_eq!(forks.len(), 2);
+        let checkpoints = SyncCheckpointStore::new(db.clone());
+        checkpoints.save(&forks);
+
+        let checkpoint = SyncCheckpointStore::new(db).load().unwrap();
+        assert_eq!(checkpoint.target, heaviest);
+        assert_eq!(checkpoint.target_epoch, 1);
+    }
+
+    #[test]
+    fn test_persist_full_tipsets_in_batches() {
+        let (cs, c4u) = setup();
+        let db = cs.blockstore().clone();
+
+        chain4u! {
+            from [genesis_header] in c4u;
+            [a = dummy_node(&db, 1)] -> [b = dummy_node(&db, 2)] -> [c = dummy_node(&db, 3)]
+        };
+
+        let tipsets = [a, b, c].map(|block| {
+            FullTipset::new(vec![Block {
+                header: block.clone().into(),
+                bls_messages: vec![],
+                secp_messages: vec![],
+            }])
+            .unwrap()
+        });
+        let store = Arc::new(MemoryDB::default());
+        persist_full_tipsets(&store, &tipsets, 2).unwrap();
+        for tipset in &tipsets {
+            for block in tipset.blocks() {
+                assert!(store.has(block.header().cid()).unwrap());
+                crate::chain::block_messages(&store, block.header()).unwrap();
+            }
+        }
+    }
+
     #[test]
     fn test_sync_state_machine_chain_fragments() {
         let (cs, c4u) = setup();
@@ -1056,4 +1453,49 @@ mod tests {
         // Both chains should start at the same tipset
         assert_eq!(chains, vec![vec![1, 3], vec![1, 2]]);
     }
+
+    #[test]
+    fn head_watchdog_stall() {
+        let key = TipsetKey::from(nunny::vec![Cid::default()]);
+        let (behind, ahead, highest) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let mut peer_heads = HashMap::from_iter([
+            (behind, (5, key.clone())),
+            (ahead, (12, key.clone())),
+            (highest, (15, key.clone())),
+        ]);
+        let mut watchdog = HeadWatchdog::new(3, 10);
+
+        // The head advances
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        // The head is stuck, but not for long enough
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        // The peers ahead are reported, highest first
+        let peers = watchdog
+            .check(11, &peer_heads)
+            .unwrap()
+            .into_iter()
+            .map(|(peer, epoch, _)| (peer, epoch))
+            .collect_vec();
+        assert_eq!(peers, vec![(highest, 15), (ahead, 12)]);
+
+        // The count starts over after a report
+        peer_heads.retain(|peer, _| *peer == behind);
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        // No peer is ahead, the stall is reported as soon as one is
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        peer_heads.insert(ahead, (12, key.clone()));
+        assert_eq!(watchdog.check(11, &peer_heads).unwrap().len(), 1);
+
+        // Head progress resets the count
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        assert!(watchdog.check(11, &peer_heads).is_none());
+        assert!(watchdog.check(12, &peer_heads).is_none());
+        assert!(watchdog.check(12, &peer_heads).is_none());
+        assert!(watchdog.check(12, &peer_heads).is_none());
+        // The head caught up with the peers
+        assert!(watchdog.check(12, &peer_heads).is_none());
+    }
 }
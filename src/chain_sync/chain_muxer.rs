@@ -4,20 +4,33 @@
 use serde::{Deserialize, Serialize};
 
 pub const DEFAULT_RECENT_STATE_ROOTS: i64 = 2000;
+pub const DEFAULT_PERSIST_BATCH_EPOCHS: usize = 20;
 
 /// Structure that defines syncing configuration options
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
 pub struct SyncConfig {
     /// Number of recent state roots to keep in the database after `sync`
     /// and to include in the exported snapshot.
     pub recent_state_roots: i64,
+    /// Retain all historical state roots and receipts. In archival mode the
+    /// database is never garbage collected, `recent_state_roots` only applies
+    /// to exported snapshots.
+    pub archive: bool,
+    /// Number of epochs of downloaded tipsets persisted in a single transaction while catching
+    /// up with the network. Every transaction is synced to disk, so this is also the fsync
+    /// cadence. Tipsets are persisted every epoch when following the head of the network.
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub persist_batch_epochs: usize,
 }
 
 impl Default for SyncConfig {
     fn default() -> Self {
         Self {
             recent_state_roots: DEFAULT_RECENT_STATE_ROOTS,
+            archive: false,
+            persist_batch_epochs: DEFAULT_PERSIST_BATCH_EPOCHS,
         }
     }
 }
@@ -0,0 +1,102 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Checkpoint of the progress of the chain sync, persisted in the settings store so that after a
+//! crash or a restart the sync resumes the range of tipsets it was working on, from the tipsets
+//! already downloaded to the database, instead of waiting for the heads of the peers and
+//! downloading the range again.
+
+use std::sync::Arc;
+
+use fvm_ipld_blockstore::Blockstore;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::{ForkSyncInfo, load_full_tipset};
+use crate::blocks::{FullTipset, TipsetKey};
+use crate::chain::ChainStore;
+use crate::db::{SettingsStore, SettingsStoreExt as _, setting_keys::SYNC_CHECKPOINT_KEY};
+use crate::shim::clock::ChainEpoch;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct SyncCheckpoint {
+    /// Key of the tipset the sync is heading to
+    pub target: TipsetKey,
+    pub target_epoch: ChainEpoch,
+    /// Epoch of the last validated tipset
+    pub validated_epoch: ChainEpoch,
+    /// Lowest epoch of the downloaded range in flight, whose parents are fetched or validated next
+    pub range_start_epoch: ChainEpoch,
+}
+
+impl From<&ForkSyncInfo> for SyncCheckpoint {
+    fn from(fork: &ForkSyncInfo) -> Self {
+        Self {
+            target: fork.target_tipset_key.clone(),
+            target_epoch: fork.target_epoch,
+            validated_epoch: fork.validated_chain_head_epoch,
+            range_start_epoch: fork.target_sync_epoch_start,
+        }
+    }
+}
+
+pub(super) struct SyncCheckpointStore {
+    store: Arc<dyn SettingsStore + Sync + Send>,
+    /// Last saved checkpoint, to only write the changes
+    last: Mutex<Option<SyncCheckpoint>>,
+}
+
+impl SyncCheckpointStore {
+    pub fn new(store: Arc<dyn SettingsStore + Sync + Send>) -> Self {
+        Self {
+            store,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn load(&self) -> Option<SyncCheckpoint> {
+        let checkpoint = self
+            .store
+            .read_obj::<Option<SyncCheckpoint>>(SYNC_CHECKPOINT_KEY)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Ignoring the sync checkpoint: {e}");
+                None
+            })
+            .flatten();
+        self.last.lock().clone_from(&checkpoint);
+        checkpoint
+    }
+
+    /// Saves the checkpoint of the heaviest fork in sync, or clears it when there is none. The
+    /// forks are ordered heaviest first, as returned by `SyncStateMachine::tasks`.
+    pub fn save(&self, forks: &[ForkSyncInfo]) {
+        let checkpoint = forks.first().map(SyncCheckpoint::from);
+        let mut last = self.last.lock();
+        if *last == checkpoint {
+            return;
+        }
+        match self.store.write_obj(SYNC_CHECKPOINT_KEY, &checkpoint) {
+            Ok(()) => *last = checkpoint,
+            Err(e) => tracing::warn!("Failed to persist the sync checkpoint: {e}"),
+        }
+    }
+}
+
+/// Loads the downloaded tipsets of the range of the checkpoint, from its target down to its start,
+/// the head of the node, or the first tipset missing from the database.
+pub(super) fn load_checkpointed_tipsets<DB: Blockstore>(
+    cs: &ChainStore<DB>,
+    checkpoint: &SyncCheckpoint,
+) -> Vec<FullTipset> {
+    let head_epoch = cs.heaviest_tipset().epoch();
+    let mut tipsets = vec![];
+    let mut key = checkpoint.target.clone();
+    while let Ok(tipset) = load_full_tipset(cs, &key) {
+        if tipset.epoch() <= head_epoch || tipset.epoch() < checkpoint.range_start_epoch {
+            break;
+        }
+        key = tipset.parents().clone();
+        tipsets.push(tipset);
+    }
+    tipsets
+}
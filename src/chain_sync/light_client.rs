@@ -0,0 +1,362 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Light client verification of the chain. The headers of new tipsets are checked against their
+//! parent tipset, i.e. the block signatures, the tickets, the election proofs and the win counts,
+//! the beacon entries, the timestamps and the parent weight, without executing any message. The
+//! winning `PoSt` proofs are not checked. The bits of state this requires, the worker keys and
+//! the power of the miners, are read from the state roots committed to by the verified headers,
+//! so they can be served by an untrusted node, see [`RemoteBlockstore`].
+//!
+//! Tipsets of the verified chain are final once they are the chain finality below the verified
+//! head, after which the chain never reorgs below them. F3 finality certificates are not used,
+//! their aggregated signatures can't be checked without tracking the F3 power table.
+
+use std::sync::Arc;
+
+use ahash::HashMap;
+use anyhow::{Context as _, ensure};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use nonzero_ext::nonzero;
+
+use crate::beacon::BeaconSchedule;
+use crate::blocks::{Tipset, TipsetKey};
+use crate::chain::{ChainStore, index::ChainIndex};
+use crate::fil_cns::{self, validate_ticket_election, validate_winner_election};
+use crate::interpreter::resolve_to_key_addr;
+use crate::networks::ChainConfig;
+use crate::rpc::{self, RpcMethodExt as _, chain::ChainReadObj};
+use crate::shim::actors::miner;
+use crate::shim::address::Address;
+use crate::shim::state_tree::StateTree;
+use crate::utils::cache::SizeTrackingLruCache;
+use crate::utils::get_size::CidWrapper;
+use crate::utils::multihash::prelude::*;
+
+/// Blockstore reading the blocks from a node over RPC. The blocks are checked against their CIDs,
+/// so the node doesn't need to be trusted, and cached in memory only. Blocks are read
+/// synchronously, so the store must be used from a blocking task.
+pub struct RemoteBlockstore {
+    client: Arc<rpc::Client>,
+    handle: tokio::runtime::Handle,
+    cache: SizeTrackingLruCache<CidWrapper, Vec<u8>>,
+}
+
+impl RemoteBlockstore {
+    pub fn new(client: Arc<rpc::Client>) -> Self {
+        Self {
+            client,
+            handle: tokio::runtime::Handle::current(),
+            cache: SizeTrackingLruCache::new_without_metrics_registry(
+                "light_client_blocks".into(),
+                nonzero!(65536usize),
+            ),
+        }
+    }
+}
+
+impl Blockstore for RemoteBlockstore {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(block) = self.cache.get_cloned(&CidWrapper::from(*k)) {
+            return Ok(Some(block));
+        }
+        let block = self
+            .handle
+            .block_on(ChainReadObj::call(&self.client, (*k,)))
+            .with_context(|| format!("failed to read block {k}"))?;
+        let hash = MultihashCode::try_from(k.hash().code())?.digest(&block);
+        ensure!(hash == *k.hash(), "block {k} doesn't match its CID");
+        self.cache.push((*k).into(), block.clone());
+        Ok(Some(block))
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.cache.push((*k).into(), block.to_vec());
+        Ok(())
+    }
+}
+
+/// Verifies the headers of tipsets against their parent tipset.
+pub struct HeaderVerifier<DB> {
+    chain_index: Arc<ChainIndex<Arc<DB>>>,
+    chain_config: Arc<ChainConfig>,
+    beacon: Arc<BeaconSchedule>,
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> HeaderVerifier<DB> {
+    pub fn new(db: Arc<DB>, chain_config: Arc<ChainConfig>, genesis_timestamp: u64) -> Self {
+        Self {
+            chain_index: Arc::new(ChainIndex::new(db)),
+            beacon: Arc::new(chain_config.get_beacon_schedule(genesis_timestamp)),
+            chain_config,
+        }
+    }
+
+    pub fn db(&self) -> &Arc<DB> {
+        self.chain_index.db()
+    }
+
+    /// Verifies the headers of the tipset, whose parent tipset has been verified.
+    pub fn verify(&self, parent: &Tipset, tipset: &Tipset) -> anyhow::Result<()> {
+        ensure!(
+            tipset.parents() == parent.key(),
+            "tipset {} doesn't build on {}",
+            tipset.key(),
+            parent.key()
+        );
+        ensure!(
+            tipset.epoch() > parent.epoch(),
+            "tipset epoch {} isn't greater than the parent epoch {}",
+            tipset.epoch(),
+            parent.epoch()
+        );
+        let weight = fil_cns::weight(self.db(), parent)?;
+        ensure!(
+            tipset.weight() == &weight,
+            "parent weight doesn't match: {} (header), {weight} (computed)",
+            tipset.weight()
+        );
+        let target_timestamp = parent.min_timestamp()
+            + self.chain_config.block_delay_secs as u64 * (tipset.epoch() - parent.epoch()) as u64;
+        let (_, lookback_state) = ChainStore::get_lookback_tipset_for_round(
+            &self.chain_index,
+            &self.chain_config,
+            parent,
+            tipset.epoch(),
+        )?;
+        let prev_beacon = self.chain_index.latest_beacon_entry(parent.clone())?;
+        let network_version = self.chain_config.network_version(tipset.epoch());
+        for header in tipset.block_headers() {
+            let cid = header.cid();
+            ensure!(
+                header.timestamp == target_timestamp,
+                "block {cid} has the wrong timestamp: {} != {target_timestamp}",
+                header.timestamp
+            );
+            let work_addr = miner_work_addr(self.db(), &lookback_state, &header.miner_address)
+                .with_context(|| format!("failed to load the worker of block {cid}"))?;
+            header
+                .verify_signature_against(&work_addr)
+                .with_context(|| format!("invalid signature of block {cid}"))?;
+            validate_ticket_election(header, parent, &prev_beacon, &work_addr, &self.chain_config)
+                .with_context(|| format!("invalid ticket of block {cid}"))?;
+            validate_winner_election(
+                self.db(),
+                &self.chain_config,
+                header,
+                parent,
+                &lookback_state,
+                &prev_beacon,
+                &work_addr,
+            )
+            .with_context(|| format!("invalid election proof of block {cid}"))?;
+            header
+                .validate_block_drand(network_version, &self.beacon, parent.epoch(), &prev_beacon)
+                .with_context(|| format!("invalid beacon entries of block {cid}"))?;
+        }
+        Ok(())
+    }
+}
+
+fn miner_work_addr<DB: Blockstore>(
+    db: &Arc<DB>,
+    state_root: &Cid,
+    miner: &Address,
+) -> anyhow::Result<Address> {
+    let state_tree = StateTree::new_from_root(db.clone(), state_root)?;
+    let state: miner::State = state_tree.get_actor_state_from_address(miner)?;
+    let info = state.info(db)?;
+    resolve_to_key_addr(&state_tree, db, &info.worker())
+}
+
+/// Chain of verified tipsets, growing from a trusted anchor tipset.
+pub struct LightClient<DB> {
+    verifier: HeaderVerifier<DB>,
+    /// Verified tipsets not older than the finalized tipset, by key
+    verified: HashMap<TipsetKey, Tipset>,
+    head: Tipset,
+    /// Verified tipset the chain finality below the head, or the anchor
+    finalized: Tipset,
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> LightClient<DB> {
+    /// The anchor must be obtained from a trusted source, e.g. a checkpoint.
+    pub fn new(verifier: HeaderVerifier<DB>, anchor: Tipset) -> Self {
+        Self {
+            verifier,
+            verified: HashMap::from_iter([(anchor.key().clone(), anchor.clone())]),
+            head: anchor.clone(),
+            finalized: anchor,
+        }
+    }
+
+    pub fn head(&self) -> &Tipset {
+        &self.head
+    }
+
+    pub fn finalized(&self) -> &Tipset {
+        &self.finalized
+    }
+
+    /// Verifies the tipsets from the last verified ancestor of the tipset up to it, whose headers
+    /// are read from the store, and returns `true` if it became the head, i.e. it's heavier.
+    pub fn apply(&mut self, tipset: Tipset) -> anyhow::Result<bool> {
+        let mut pending = vec![];
+        let mut current = tipset;
+        let parent = loop {
+            if let Some(verified) = self.verified.get(current.key()) {
+                break verified.clone();
+            }
+            // Bounds the walk on chains which don't descend from the verified chain.
+            ensure!(
+                current.epoch() > self.finalized.epoch(),
+                "tipset {} doesn't descend from the finalized tipset {}",
+                current.key(),
+                self.finalized.key()
+            );
+            let parent = Tipset::load_required(self.verifier.db(), current.parents())?;
+            pending.push(std::mem::replace(&mut current, parent));
+        };
+        ensure!(
+            parent.epoch() >= self.finalized.epoch(),
+            "tipset {} forks below the finalized tipset {}",
+            current.key(),
+            self.finalized.key()
+        );
+        let mut parent = parent;
+        for tipset in pending.into_iter().rev() {
+            self.verifier.verify(&parent, &tipset)?;
+            self.verified.insert(tipset.key().clone(), tipset.clone());
+            parent = tipset;
+        }
+        if parent.weight() <= self.head.weight() {
+            return Ok(false);
+        }
+        self.head = parent;
+        self.finalize();
+        Ok(true)
+    }
+
+    /// Finalizes the verified tipset the chain finality below the head. Forks which don't
+    /// descend from it are abandoned.
+    fn finalize(&mut self) {
+        let finality = self.verifier.chain_config.policy.chain_finality;
+        let mut finalized = &self.head;
+        while finalized.epoch() > self.head.epoch() - finality {
+            match self.verified.get(finalized.parents()) {
+                Some(parent) => finalized = parent,
+                None => break,
+            }
+        }
+        if finalized.epoch() <= self.finalized.epoch() {
+            return;
+        }
+        self.finalized = finalized.clone();
+        let abandoned = self
+            .verified
+            .values()
+            .filter(|ts| !self.is_ancestor(&self.finalized, ts))
+            .map(|ts| ts.key().clone())
+            .collect::<Vec<_>>();
+        for key in abandoned {
+            self.verified.remove(&key);
+        }
+    }
+
+    /// Returns `true` if the tipset descends from, or is, the ancestor, among the verified
+    /// tipsets.
+    fn is_ancestor(&self, ancestor: &Tipset, tipset: &Tipset) -> bool {
+        let mut current = tipset;
+        while current.epoch() > ancestor.epoch() {
+            match self.verified.get(current.parents()) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        current.key() == ancestor.key()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{CachingBlockHeader, RawBlockHeader};
+    use crate::db::MemoryDB;
+    use crate::shim::clock::ChainEpoch;
+    use crate::utils::db::CborStoreExt as _;
+
+    fn tipset_child(db: &MemoryDB, parent: &Tipset, epoch: ChainEpoch) -> Tipset {
+        let tipset = Tipset::from(CachingBlockHeader::new(RawBlockHeader {
+            parents: parent.key().clone(),
+            epoch,
+            timestamp: parent.min_timestamp() + epoch as u64,
+            ..Default::default()
+        }));
+        for block in tipset.block_headers() {
+            db.put_cbor_default(block).unwrap();
+        }
+        tipset
+    }
+
+    fn light_client(db: &Arc<MemoryDB>, anchor: Tipset) -> LightClient<MemoryDB> {
+        let verifier = HeaderVerifier::new(db.clone(), Arc::new(ChainConfig::default()), 0);
+        LightClient::new(verifier, anchor)
+    }
+
+    #[test]
+    fn reject_unlinked_tipsets() {
+        let db = Arc::new(MemoryDB::default());
+        let genesis = Tipset::from(CachingBlockHeader::default());
+        let client = light_client(&db, genesis.clone());
+        let child = tipset_child(&db, &genesis, 1);
+        let grandchild = tipset_child(&db, &child, 2);
+        assert!(client.verifier.verify(&genesis, &grandchild).is_err());
+        let stale = tipset_child(&db, &child, 1);
+        assert!(client.verifier.verify(&child, &stale).is_err());
+    }
+
+    #[test]
+    fn reject_forks_below_finalized() {
+        let db = Arc::new(MemoryDB::default());
+        let genesis = Tipset::from(CachingBlockHeader::default());
+        db.put_cbor_default(genesis.block_headers().first())
+            .unwrap();
+        let anchor = tipset_child(&db, &genesis, 10);
+        let fork = tipset_child(&db, &genesis, 5);
+        let mut client = light_client(&db, anchor.clone());
+        assert!(client.apply(fork).is_err());
+        assert!(!client.apply(anchor.clone()).unwrap());
+        assert_eq!(client.head(), &anchor);
+    }
+
+    #[test]
+    fn finality_follows_the_head() {
+        let db = Arc::new(MemoryDB::default());
+        let genesis = Tipset::from(CachingBlockHeader::default());
+        let mut client = light_client(&db, genesis.clone());
+        let finality = client.verifier.chain_config.policy.chain_finality;
+        let fork = tipset_child(&db, &genesis, 1);
+        client.verified.insert(fork.key().clone(), fork);
+        let mut head = genesis;
+        for epoch in 1..=finality + 10 {
+            // Null round
+            if epoch == 5 {
+                continue;
+            }
+            head = tipset_child(&db, &head, epoch);
+            client.verified.insert(head.key().clone(), head.clone());
+            if epoch == 3 {
+                // Fork above the finalized tipset, but not descending from it
+                let fork = tipset_child(&db, &head, 20);
+                client.verified.insert(fork.key().clone(), fork);
+            }
+        }
+        client.head = head;
+        client.finalize();
+        assert_eq!(client.finalized().epoch(), 10);
+        assert!(client.is_ancestor(client.finalized(), client.head()));
+        // The forks are abandoned, the tipsets before the finalized tipset are pruned
+        assert_eq!(client.verified.len(), finality as usize + 1);
+    }
+}
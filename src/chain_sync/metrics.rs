@@ -44,6 +44,15 @@ pub static INVALID_TIPSET_TOTAL: LazyLock<Counter> = LazyLock::new(|| {
     );
     metric
 });
+pub static HEAD_STALL_TOTAL: LazyLock<Counter> = LazyLock::new(|| {
+    let metric = Counter::default();
+    crate::metrics::default_registry().register(
+        "head_stall_total",
+        "Total number of times the chain head stopped advancing while peers reported higher heads",
+        metric.clone(),
+    );
+    metric
+});
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, derive_more::Constructor)]
 pub struct Libp2pMessageKindLabel(&'static str);
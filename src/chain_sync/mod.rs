@@ -4,7 +4,9 @@
 mod bad_block_cache;
 mod chain_follower;
 pub mod chain_muxer;
+mod checkpoint;
 pub mod consensus;
+pub mod light_client;
 pub mod metrics;
 pub mod network_context;
 mod sync_status;
@@ -17,5 +19,5 @@ pub use self::{
     chain_muxer::SyncConfig,
     consensus::collect_errs,
     sync_status::{ForkSyncInfo, ForkSyncStage, NodeSyncStatus, SyncStatus, SyncStatusReport},
-    validation::{TipsetValidationError, TipsetValidator},
+    validation::{TipsetValidationError, TipsetValidator, is_header_checked, validate_header_fast},
 };
@@ -15,6 +15,8 @@ use tracing::log;
 
 // Node considered synced if the head is within this threshold.
 const SYNCED_EPOCH_THRESHOLD: u64 = 10;
+/// Maximum number of recent errors of the sync tasks kept in the report.
+pub(crate) const MAX_RECENT_ERRORS: usize = 10;
 
 /// Represents the overall synchronization status of the Forest node.
 #[derive(
@@ -126,6 +128,8 @@ pub struct SyncStatusReport {
     pub(crate) node_start_time: DateTime<Utc>,
     /// Last time this status report was generated.
     pub(crate) last_updated: DateTime<Utc>,
+    /// Most recent errors of the sync tasks, oldest first.
+    pub(crate) recent_errors: Vec<String>,
 }
 
 lotus_json_with_self!(SyncStatusReport);
@@ -144,6 +148,7 @@ impl SyncStatusReport {
         &self,
         state_manager: &StateManager<DB>,
         active_forks: Vec<ForkSyncInfo>,
+        recent_errors: Vec<String>,
         stateless_mode: bool,
     ) -> Self {
         let heaviest = state_manager.chain_store().heaviest_tipset();
@@ -188,6 +193,7 @@ impl SyncStatusReport {
             status,
             active_forks,
             last_updated,
+            recent_errors,
         }
     }
 
@@ -4,8 +4,8 @@
 use std::sync::Arc;
 
 use crate::chain_sync::BadBlockCache;
-use crate::networks::Height;
-use crate::shim::clock::ALLOWABLE_CLOCK_DRIFT;
+use crate::networks::{ChainConfig, Height};
+use crate::shim::clock::ChainEpoch;
 use crate::shim::crypto::SignatureType;
 use crate::shim::{
     address::Address, crypto::verify_bls_aggregate, econ::BLOCK_GAS_LIMIT,
@@ -22,8 +22,8 @@ use crate::{
     metrics::HistogramTimerExt,
 };
 use crate::{
-    eth::is_valid_eth_tx_for_sending,
-    message::{Message as MessageTrait, valid_for_block_inclusion},
+    eth::{EthChainId, is_valid_eth_tx_for_sending},
+    message::{Message as MessageTrait, SignedMessage, valid_for_block_inclusion},
 };
 use ahash::HashMap;
 use cid::Cid;
@@ -32,11 +32,16 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::to_vec;
 use itertools::Itertools;
 use nunny::Vec as NonEmpty;
+use rayon::prelude::*;
 use thiserror::Error;
 use tokio::task::JoinSet;
 use tracing::{trace, warn};
 
-use crate::chain_sync::{consensus::collect_errs, metrics, validation::TipsetValidator};
+use crate::chain_sync::{
+    consensus::collect_errs,
+    metrics,
+    validation::{TipsetValidator, is_header_checked},
+};
 
 #[derive(Debug, Error)]
 pub enum TipsetSyncerError {
@@ -112,10 +117,19 @@ pub async fn validate_tipset<DB: Blockstore + Send + Sync + 'static>(
     let epoch = full_tipset.epoch();
     let full_tipset_key = full_tipset.key().clone();
     trace!("Tipset keys: {full_tipset_key}");
-    let blocks = full_tipset.into_blocks();
+    let blocks = full_tipset
+        .into_blocks()
+        .into_iter()
+        .map(Arc::new)
+        .collect_vec();
+    if let Err((cid, why)) = verify_message_signatures(state_manager, &blocks).await {
+        on_invalid_block(bad_block_cache.as_deref(), epoch, cid, &why);
+        return Err(why);
+    }
+
     let mut validations = JoinSet::new();
     for b in blocks {
-        validations.spawn(validate_block(state_manager.clone(), Arc::new(b)));
+        validations.spawn(validate_block(state_manager.clone(), b));
     }
 
     while let Some(result) = validations.join_next().await {
@@ -126,18 +140,7 @@ pub async fn validate_tipset<DB: Blockstore + Send + Sync + 'static>(
                     .add_to_tipset_tracker(block.header());
             }
             Err((cid, why)) => {
-                warn!("Validating block [CID = {cid}] in EPOCH = {epoch} failed: {why}");
-                match &why {
-                    TipsetSyncerError::TimeTravellingBlock(_, _) => {
-                        // Do not mark a block as bad for temporary errors.
-                        // See <https://github.com/filecoin-project/lotus/blob/v1.34.1/chain/sync.go#L602> in Lotus
-                    }
-                    _ => {
-                        if let Some(bad_block_cache) = bad_block_cache {
-                            bad_block_cache.push(cid);
-                        }
-                    }
-                };
+                on_invalid_block(bad_block_cache.as_deref(), epoch, cid, &why);
                 return Err(why);
             }
         }
@@ -146,6 +149,145 @@ pub async fn validate_tipset<DB: Blockstore + Send + Sync + 'static>(
     Ok(())
 }
 
+/// Logs a block that failed validation, and adds it to the bad block cache unless the failure
+/// is temporary.
+fn on_invalid_block(
+    bad_block_cache: Option<&BadBlockCache>,
+    epoch: ChainEpoch,
+    cid: Cid,
+    why: &TipsetSyncerError,
+) {
+    warn!("Validating block [CID = {cid}] in EPOCH = {epoch} failed: {why}");
+    match why {
+        TipsetSyncerError::TimeTravellingBlock(_, _) => {
+            // Do not mark a block as bad for temporary errors.
+            // See <https://github.com/filecoin-project/lotus/blob/v1.34.1/chain/sync.go#L602> in Lotus
+        }
+        _ => {
+            if let Some(bad_block_cache) = bad_block_cache {
+                bad_block_cache.push(cid);
+            }
+        }
+    }
+}
+
+/// Verifies the message signatures of the blocks of a tipset that have not been validated yet,
+/// which is one of the largest costs of the validation. The BLS aggregate signatures of the
+/// blocks are verified in parallel, and so are the signatures of the secp messages, once per
+/// message even if several blocks include it.
+///
+/// Returns the CID of an invalid block and the error if a signature is invalid.
+async fn verify_message_signatures<DB: Blockstore + Send + Sync + 'static>(
+    state_manager: &Arc<StateManager<DB>>,
+    blocks: &[Arc<Block>],
+) -> Result<(), (Cid, TipsetSyncerError)> {
+    let chain_store = state_manager.chain_store();
+    // The messages of checkpointed blocks are committed to by the checkpoint
+    let blocks = blocks
+        .iter()
+        .filter(|block| {
+            !chain_store.is_block_validated(block.cid())
+                && !chain_store.is_checkpointed(block.header())
+        })
+        .cloned()
+        .collect_vec();
+    let Some(first) = blocks.first() else {
+        return Ok(());
+    };
+    let first_cid = *first.cid();
+    // The blocks of a tipset share their parents
+    let base_tipset = chain_store
+        .chain_index()
+        .load_required_tipset(&first.header().parents)
+        .map_err(|why| (first_cid, TipsetSyncerError::TipsetParentNotFound(why)))?;
+
+    // Resolve the key addresses of the secp senders, once per sender
+    let mut key_addrs: HashMap<Address, Address> = HashMap::default();
+    let mut secp_msgs = vec![];
+    for (block_cid, msg) in blocks
+        .iter()
+        .flat_map(|block| block.secp_msgs().iter().map(move |msg| (*block.cid(), msg)))
+        .unique_by(|(_, msg)| msg.cid())
+    {
+        let key_addr = match key_addrs.get(&msg.from()) {
+            Some(key_addr) => *key_addr,
+            None => {
+                let key_addr = state_manager
+                    .resolve_to_key_addr(&msg.from(), &base_tipset)
+                    .await
+                    .map_err(|e| {
+                        (
+                            block_cid,
+                            TipsetSyncerError::ResolvingAddressFromMessage(e.to_string()),
+                        )
+                    })?;
+                key_addrs.insert(msg.from(), key_addr);
+                key_addr
+            }
+        };
+        secp_msgs.push((block_cid, msg.clone(), key_addr));
+    }
+
+    let db = state_manager.blockstore_owned();
+    let eth_chain_id = state_manager.chain_config().eth_chain_id;
+    let parent_state = *base_tipset.parent_state();
+    tokio::task::spawn_blocking(move || {
+        blocks.par_iter().try_for_each(|block| {
+            verify_bls_aggregate_signature(&db, block, parent_state).map_err(|e| (*block.cid(), e))
+        })?;
+        verify_secp_signatures(eth_chain_id, &secp_msgs)
+    })
+    .await
+    .map_err(|e| (first_cid, e.into()))?
+}
+
+/// Verifies the BLS aggregate signature of a block against its BLS messages.
+fn verify_bls_aggregate_signature<DB: Blockstore + Send + Sync + 'static>(
+    db: &Arc<DB>,
+    block: &Block,
+    parent_state: Cid,
+) -> Result<(), TipsetSyncerError> {
+    let Some(sig) = &block.header().bls_aggregate else {
+        return Err(TipsetSyncerError::BlockWithoutBlsAggregate);
+    };
+    let mut pub_keys = Vec::with_capacity(block.bls_msgs().len());
+    let mut cids = Vec::with_capacity(block.bls_msgs().len());
+    for m in block.bls_msgs() {
+        pub_keys.push(StateManager::get_bls_public_key(db, &m.from, parent_state)?);
+        cids.push(m.cid().to_bytes());
+    }
+
+    if !verify_bls_aggregate(
+        &cids.iter().map(|x| x.as_slice()).collect_vec(),
+        &pub_keys,
+        sig,
+    ) {
+        return Err(TipsetSyncerError::BlsAggregateSignatureInvalid(
+            format!("{sig:?}"),
+            format!("{cids:?}"),
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies the signatures of secp messages, given with the CID of a block that includes them
+/// and the key address of their sender, in parallel.
+fn verify_secp_signatures(
+    eth_chain_id: EthChainId,
+    msgs: &[(Cid, SignedMessage, Address)],
+) -> Result<(), (Cid, TipsetSyncerError)> {
+    msgs.par_iter().try_for_each(|(block_cid, msg, key_addr)| {
+        msg.signature
+            .authenticate_msg(eth_chain_id, msg, key_addr)
+            .map_err(|e| {
+                (
+                    *block_cid,
+                    TipsetSyncerError::MessageSignatureInvalid(format!("{}: {e}", msg.cid())),
+                )
+            })
+    })
+}
+
 /// Validate the block according to the rules specific to the consensus being
 /// used, and the common rules that pertain to the assumptions of the
 /// `ChainSync` protocol.
@@ -191,7 +333,7 @@ async fn validate_block<DB: Blockstore + Sync + Send + 'static>(
 
     // Check to ensure all optional values exist
     block_sanity_checks(header).map_err(|e| (*block_cid, e))?;
-    block_timestamp_checks(header).map_err(|e| (*block_cid, e))?;
+    block_timestamp_checks(header, state_manager.chain_config()).map_err(|e| (*block_cid, e))?;
 
     let base_tipset = chain_store
         .chain_index()
@@ -298,36 +440,43 @@ async fn validate_block<DB: Blockstore + Sync + Send + 'static>(
         }
     });
 
-    // Block signature check
-    validations.spawn_blocking({
-        let block = block.clone();
-        move || {
-            block.header().verify_signature_against(&work_addr)?;
-            Ok(())
-        }
-    });
+    // The signature and the election of a checkpointed block are committed to by the checkpoint
+    let is_checkpointed = chain_store.is_checkpointed(header);
 
-    validations.spawn({
-        let block = block.clone();
-        async move {
-            consensus
-                .validate_block(state_manager, block)
-                .map_err(|errs| {
-                    // NOTE: Concatenating errors here means the wrapper type of error
-                    // never surfaces, yet we always pay the cost of the generic argument.
-                    // But there's no reason `validate_block` couldn't return a list of all
-                    // errors instead of a single one that has all the error messages,
-                    // removing the caller's ability to distinguish between them.
-
-                    TipsetSyncerError::concat(
-                        errs.into_iter_ne()
-                            .map(TipsetSyncerError::ConsensusError)
-                            .collect_vec(),
-                    )
-                })
-                .await
-        }
-    });
+    // Block signature check, unless the header was checked when it was gossiped
+    if !is_header_checked(block_cid) && !is_checkpointed {
+        validations.spawn_blocking({
+            let block = block.clone();
+            move || {
+                block.header().verify_signature_against(&work_addr)?;
+                Ok(())
+            }
+        });
+    }
+
+    if !is_checkpointed {
+        validations.spawn({
+            let block = block.clone();
+            async move {
+                consensus
+                    .validate_block(state_manager, block)
+                    .map_err(|errs| {
+                        // NOTE: Concatenating errors here means the wrapper type of error
+                        // never surfaces, yet we always pay the cost of the generic argument.
+                        // But there's no reason `validate_block` couldn't return a list of all
+                        // errors instead of a single one that has all the error messages,
+                        // removing the caller's ability to distinguish between them.
+
+                        TipsetSyncerError::concat(
+                            errs.into_iter_ne()
+                                .map(TipsetSyncerError::ConsensusError)
+                                .collect_vec(),
+                        )
+                    })
+                    .await
+            }
+        });
+    }
 
     // Collect the errors from the async validations
     if let Err(errs) = collect_errs(validations).await {
@@ -342,11 +491,12 @@ async fn validate_block<DB: Blockstore + Sync + Send + 'static>(
 /// Validate messages in a full block, relative to the parent tipset.
 ///
 /// This includes:
-/// * signature checks
 /// * gas limits, and prices
 /// * account nonce values
 /// * the message root in the header
 ///
+/// The message signatures are verified for the whole tipset by [`verify_message_signatures`].
+///
 /// NB: This loads/computes the state resulting from the execution of the parent
 /// tipset.
 async fn check_block_messages<DB: Blockstore + Send + Sync + 'static>(
@@ -359,32 +509,6 @@ async fn check_block_messages<DB: Blockstore + Send + Sync + 'static>(
         .network_version(block.header.epoch);
     let eth_chain_id = state_manager.chain_config().eth_chain_id;
 
-    if let Some(sig) = &block.header().bls_aggregate {
-        // Do the initial loop here
-        // check block message and signatures in them
-        let mut pub_keys = Vec::with_capacity(block.bls_msgs().len());
-        let mut cids = Vec::with_capacity(block.bls_msgs().len());
-        let db = state_manager.blockstore();
-        for m in block.bls_msgs() {
-            let pk = StateManager::get_bls_public_key(db, &m.from, *base_tipset.parent_state())?;
-            pub_keys.push(pk);
-            cids.push(m.cid().to_bytes());
-        }
-
-        if !verify_bls_aggregate(
-            &cids.iter().map(|x| x.as_slice()).collect_vec(),
-            &pub_keys,
-            sig,
-        ) {
-            return Err(TipsetSyncerError::BlsAggregateSignatureInvalid(
-                format!("{sig:?}"),
-                format!("{cids:?}"),
-            ));
-        }
-    } else {
-        return Err(TipsetSyncerError::BlockWithoutBlsAggregate);
-    }
-
     let price_list = price_list_by_network_version(network_version);
     let mut sum_gas_limit = 0;
 
@@ -469,15 +593,6 @@ async fn check_block_messages<DB: Blockstore + Send + Sync + 'static>(
                 "block had an invalid secp message at index {i}: {e}"
             ))
         })?;
-        // Resolve key address for signature verification
-        let key_addr = state_manager
-            .resolve_to_key_addr(&msg.from(), &base_tipset)
-            .await
-            .map_err(|e| TipsetSyncerError::ResolvingAddressFromMessage(e.to_string()))?;
-        // SecP256K1 Signature validation
-        msg.signature
-            .authenticate_msg(eth_chain_id, msg, &key_addr)
-            .map_err(|e| TipsetSyncerError::MessageSignatureInvalid(e.to_string()))?;
     }
 
     // Validate message root from header matches message root
@@ -511,9 +626,12 @@ fn block_sanity_checks(header: &CachingBlockHeader) -> Result<(), TipsetSyncerEr
 }
 
 /// Check the clock drift.
-fn block_timestamp_checks(header: &CachingBlockHeader) -> Result<(), TipsetSyncerError> {
+fn block_timestamp_checks(
+    header: &CachingBlockHeader,
+    chain_config: &ChainConfig,
+) -> Result<(), TipsetSyncerError> {
     let time_now = chrono::Utc::now().timestamp() as u64;
-    if header.timestamp > time_now.saturating_add(ALLOWABLE_CLOCK_DRIFT) {
+    if header.timestamp > time_now.saturating_add(chain_config.allowable_clock_drift_secs) {
         return Err(TipsetSyncerError::TimeTravellingBlock(
             time_now,
             header.timestamp,
@@ -526,3 +644,52 @@ fn block_timestamp_checks(header: &CachingBlockHeader) -> Result<(), TipsetSynce
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_management::{generate_key, sign};
+    use crate::utils::cid::CidCborExt as _;
+
+    #[test]
+    fn verify_secp_signatures_batch() {
+        let key = generate_key(SignatureType::Secp256k1).unwrap();
+        let signed = |sequence| {
+            let message = Message {
+                from: key.address,
+                sequence,
+                ..Default::default()
+            };
+            let sig = sign(
+                SignatureType::Secp256k1,
+                key.key_info.private_key(),
+                &message.cid().to_bytes(),
+            )
+            .unwrap();
+            SignedMessage::new_unchecked(message, sig)
+        };
+        let block_cid = Cid::default();
+        let mut msgs = (0..16)
+            .map(|sequence| (block_cid, signed(sequence), key.address))
+            .collect_vec();
+        assert!(verify_secp_signatures(0, &msgs).is_ok());
+
+        // A message signed for another one, in another block
+        let bad_block_cid = Cid::from_cbor_blake2b256(&"bad block").unwrap();
+        let forged = SignedMessage::new_unchecked(
+            Message {
+                from: key.address,
+                sequence: 100,
+                ..Default::default()
+            },
+            signed(0).signature,
+        );
+        msgs.insert(7, (bad_block_cid, forged, key.address));
+        let (cid, err) = verify_secp_signatures(0, &msgs).unwrap_err();
+        assert_eq!(cid, bad_block_cid);
+        assert!(
+            matches!(err, TipsetSyncerError::MessageSignatureInvalid(_)),
+            "{err}"
+        );
+    }
+}
@@ -1,22 +1,74 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::sync::LazyLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::blocks::{Block, FullTipset, Tipset, TxMeta};
+use crate::blocks::{Block, CachingBlockHeader, FullTipset, Tipset, TxMeta};
 use crate::chain::ChainStore;
+use crate::db::{BlockKind, with_block_kind};
+use crate::fil_cns::validate_ticket_election;
 use crate::message::SignedMessage;
+use crate::networks::ChainConfig;
 use crate::shim::message::Message;
-use crate::utils::{cid::CidCborExt, db::CborStoreExt};
+use crate::state_manager::StateManager;
+use crate::utils::{
+    cache::SizeTrackingLruCache, cid::CidCborExt, db::CborStoreExt, get_size::CidWrapper,
+};
 use cid::Cid;
 use fil_actors_shared::fvm_ipld_amt::{Amtv0 as Amt, Error as IpldAmtError};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::Error as EncodingError;
+use nonzero_ext::nonzero;
 use thiserror::Error;
 
 use crate::chain_sync::bad_block_cache::BadBlockCache;
 
-const MAX_HEIGHT_DRIFT: u64 = 5;
+/// CIDs of the block headers whose signature and ticket are valid. A header that arrives from
+/// several peers, or is delivered again, is only checked once.
+static CHECKED_HEADERS: LazyLock<SizeTrackingLruCache<CidWrapper, ()>> = LazyLock::new(|| {
+    SizeTrackingLruCache::new_with_metrics("checked_block_headers".into(), nonzero!(4096usize))
+});
+
+/// Returns `true` if the signature and the ticket of the block header have been checked by
+/// [`validate_header_fast`].
+pub fn is_header_checked(cid: &Cid) -> bool {
+    CHECKED_HEADERS.contains(&CidWrapper::from(*cid))
+}
+
+/// Checks the signature and the ticket of a block header, typically received via gossip,
+/// before its messages are fetched. The checks are skipped for headers that passed them before,
+/// and for headers whose parent tipset is unknown, which are checked by the full validation.
+pub fn validate_header_fast<DB: Blockstore + Send + Sync + 'static>(
+    state_manager: &StateManager<DB>,
+    header: &CachingBlockHeader,
+) -> anyhow::Result<()> {
+    if is_header_checked(header.cid()) {
+        return Ok(());
+    }
+    let chain_index = state_manager.chain_store().chain_index();
+    let Some(base_tipset) = chain_index.load_tipset(&header.parents)? else {
+        return Ok(());
+    };
+    let (_, lookback_state) = ChainStore::get_lookback_tipset_for_round(
+        chain_index,
+        state_manager.chain_config(),
+        &base_tipset,
+        header.epoch,
+    )?;
+    let work_addr = state_manager.get_miner_work_addr(lookback_state, &header.miner_address)?;
+    header.verify_signature_against(&work_addr)?;
+    let prev_beacon = chain_index.latest_beacon_entry(base_tipset.clone())?;
+    validate_ticket_election(
+        header,
+        &base_tipset,
+        &prev_beacon,
+        &work_addr,
+        state_manager.chain_config(),
+    )?;
+    CHECKED_HEADERS.push((*header.cid()).into(), ());
+    Ok(())
+}
 
 #[derive(Debug, Error)]
 pub enum TipsetValidationError {
@@ -24,6 +76,8 @@ pub enum TipsetValidationError {
     NoBlocks,
     #[error("Tipset has an epoch that is too large")]
     EpochTooLarge,
+    #[error("Checkpoint mismatch: {0}")]
+    Checkpoint(String),
     #[error("Tipset has an insufficient weight")]
     InsufficientWeight,
     #[error("Tipset block = [CID = {0}] is invalid")]
@@ -58,7 +112,7 @@ impl TipsetValidator<'_> {
         chainstore: &ChainStore<DB>,
         bad_block_cache: Option<&BadBlockCache>,
         genesis_tipset: &Tipset,
-        block_delay: u32,
+        chain_config: &ChainConfig,
     ) -> Result<(), TipsetValidationError> {
         // No empty blocks
         if self.0.blocks().is_empty() {
@@ -66,7 +120,10 @@ impl TipsetValidator<'_> {
         }
 
         // Tipset epoch must not be behind current max
-        self.validate_epoch(genesis_tipset, block_delay)?;
+        self.validate_epoch(genesis_tipset, chain_config)?;
+
+        // Tipset must not deviate from the checkpoints
+        self.validate_checkpoints(chainstore)?;
 
         // Validate each block in the tipset by:
         // 1. Calculating the message root using all of the messages to ensure it
@@ -87,14 +144,15 @@ impl TipsetValidator<'_> {
     pub fn validate_epoch(
         &self,
         genesis_tipset: &Tipset,
-        block_delay: u32,
+        chain_config: &ChainConfig,
     ) -> Result<(), TipsetValidationError> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        let max_epoch =
-            ((now - genesis_tipset.min_timestamp()) / block_delay as u64) + MAX_HEIGHT_DRIFT;
+        let max_epoch = ((now - genesis_tipset.min_timestamp())
+            / chain_config.block_delay_secs as u64)
+            + chain_config.max_height_drift_epochs;
         let too_far_ahead_in_time = self.0.epoch() as u64 > max_epoch;
         if too_far_ahead_in_time {
             Err(TipsetValidationError::EpochTooLarge)
@@ -103,6 +161,24 @@ impl TipsetValidator<'_> {
         }
     }
 
+    /// Checks the tipset against the checkpoint at its epoch, and, if its parent tipset is known,
+    /// that it doesn't skip a checkpoint with null rounds.
+    pub fn validate_checkpoints<DB: Blockstore>(
+        &self,
+        chainstore: &ChainStore<DB>,
+    ) -> Result<(), TipsetValidationError> {
+        let epoch = self.0.epoch();
+        let parent_epoch = match chainstore.chain_index().load_tipset(self.0.parents()) {
+            Ok(Some(parent)) => parent.epoch(),
+            _ => epoch.saturating_sub(1),
+        };
+        chainstore
+            .checkpoints()
+            .read()
+            .check(epoch, self.0.key(), parent_epoch)
+            .map_err(TipsetValidationError::Checkpoint)
+    }
+
     pub fn validate_msg_root<DB: Blockstore>(
         blockstore: &DB,
         block: &Block,
@@ -130,34 +206,45 @@ impl TipsetValidator<'_> {
             .map(Cid::from_cbor_blake2b256)
             .collect::<Result<Vec<Cid>, fvm_ipld_encoding::Error>>()?;
 
-        // Generate Amt and batch set message values
-        let bls_message_root = Amt::new_from_iter(blockstore, bls_cids)?;
-        let secp_message_root = Amt::new_from_iter(blockstore, secp_cids)?;
-        let meta = TxMeta {
-            bls_message_root,
-            secp_message_root,
-        };
+        with_block_kind(
+            BlockKind::Message,
+            || -> Result<Cid, TipsetValidationError> {
+                // Generate Amt and batch set message values
+                let bls_message_root = Amt::new_from_iter(blockstore, bls_cids)?;
+                let secp_message_root = Amt::new_from_iter(blockstore, secp_cids)?;
+                let meta = TxMeta {
+                    bls_message_root,
+                    secp_message_root,
+                };
 
-        // Store message roots and receive meta_root CID
-        blockstore
-            .put_cbor_default(&meta)
-            .map_err(|e| TipsetValidationError::Blockstore(e.to_string()))
+                // Store message roots and receive meta_root CID
+                blockstore
+                    .put_cbor_default(&meta)
+                    .map_err(|e| TipsetValidationError::Blockstore(e.to_string()))
+            },
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
+    use std::sync::Arc;
 
+    use crate::blocks::{CachingBlockHeader, RawBlockHeader, TipsetKey};
+    use crate::chain::ChainStore;
     use crate::db::MemoryDB;
     use crate::message::SignedMessage;
+    use crate::networks::ChainConfig;
+    use crate::shim::address::Address;
     use crate::shim::message::Message;
+    use crate::state_manager::StateManager;
     use crate::test_utils::construct_messages;
     use crate::utils::encoding::from_slice_with_fallback;
     use base64::{Engine, prelude::BASE64_STANDARD};
     use cid::Cid;
 
-    use super::TipsetValidator;
+    use super::{TipsetValidator, is_header_checked, validate_header_fast};
 
     #[test]
     fn compute_msg_meta_given_msgs_test() {
@@ -189,4 +276,35 @@ mod tests {
             "bafy2bzacecmda75ovposbdateg7eyhwij65zklgyijgcjwynlklmqazpwlhba"
         );
     }
+
+    #[test]
+    fn validate_header_fast_with_unknown_parents() {
+        let db = Arc::new(MemoryDB::default());
+        let genesis_header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(0),
+            timestamp: 7777,
+            ..Default::default()
+        });
+        let chain_store = Arc::new(
+            ChainStore::new(
+                db.clone(),
+                db.clone(),
+                db,
+                Arc::new(ChainConfig::default()),
+                genesis_header,
+            )
+            .unwrap(),
+        );
+        let state_manager = StateManager::new(chain_store).unwrap();
+
+        // The header is left to the full validation, and is not cached.
+        let header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(1000),
+            epoch: 10,
+            parents: TipsetKey::from(nunny::vec![Cid::default()]),
+            ..Default::default()
+        });
+        assert!(validate_header_fast(&state_manager, &header).is_ok());
+        assert!(!is_header_checked(header.cid()));
+    }
 }
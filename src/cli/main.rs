@@ -28,7 +28,10 @@ where
 
     let client = rpc::Client::default_or_from_env(token.as_deref())?;
 
-    let (_bg_tasks, _guards) = logger::setup_logger(&crate::cli_shared::cli::CliOpts::default());
+    let (_bg_tasks, _guards) = logger::setup_logger(
+        &crate::cli_shared::cli::CliOpts::default(),
+        &Default::default(),
+    );
 
     cmd.run(client).await
 }
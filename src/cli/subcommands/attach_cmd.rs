@@ -0,0 +1,270 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::io::{BufRead as _, Write as _};
+use std::str::FromStr as _;
+use std::sync::Arc;
+
+use crate::cli::humantoken::{self, TokenAmountPretty as _};
+use crate::lotus_json::HasLotusJson as _;
+use crate::rpc::{self, ApiPaths, prelude::*};
+use crate::shim::address::{Address, StrictAddress};
+use crate::shim::message::{METHOD_SEND, Message};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use tokio::runtime::Handle;
+
+const HELP: &str = "\
+The console evaluates Rhai statements (https://rhai.rs), variables are kept between statements.
+
+Built-in functions:
+  head()                        The current chain head
+  wallets()                     The addresses in the node wallet
+  balance(address)              The balance of an address
+  send(to, amount)              Send funds from the default address of the node wallet, e.g.
+                                send(\"f01234\", \"1.5 FIL\"), and return the message CID
+  send(to, amount, from)        Send funds from an address of the node wallet
+  call(method)                  Call a JSON-RPC method, e.g. call(\"Filecoin.ChainHead\")
+  call(method, params)          Call a JSON-RPC method with parameters, e.g.
+                                call(\"Filecoin.ChainGetTipSetByHeight\", [100, ()])
+
+Console commands:
+  help                          Print this message
+  exit                          Leave the console";
+
+type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Attach an interactive console to the running node
+#[derive(Debug, clap::Args)]
+pub struct AttachCommand {
+    /// Execute a single statement and exit
+    #[arg(long)]
+    exec: Option<String>,
+}
+
+impl AttachCommand {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        if self.exec.is_none() {
+            println!("Attached to {}", client.base_url());
+        }
+        let runtime = Handle::current();
+        // The engine calls the node synchronously, so it runs off the async runtime.
+        tokio::task::spawn_blocking(move || {
+            let engine = engine(Arc::new(client), runtime);
+            let mut scope = Scope::new();
+            match self.exec {
+                Some(statement) => eval(&engine, &mut scope, &statement),
+                None => repl(&engine, &mut scope),
+            }
+        })
+        .await?
+    }
+}
+
+fn repl(engine: &Engine, scope: &mut Scope) -> anyhow::Result<()> {
+    println!("Type `help` for the list of built-in functions");
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            // EOF, e.g. Ctrl-D
+            println!();
+            return Ok(());
+        };
+        match line.trim() {
+            "" => {}
+            "help" => println!("{HELP}"),
+            "exit" | "quit" => return Ok(()),
+            statement => {
+                if let Err(e) = eval(engine, scope, statement) {
+                    eprintln!("Error: {e:#}");
+                }
+            }
+        }
+    }
+}
+
+/// Evaluates a statement and prints its value, if any.
+fn eval(engine: &Engine, scope: &mut Scope, statement: &str) -> anyhow::Result<()> {
+    let value = engine
+        .eval_with_scope::<Dynamic>(scope, statement)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    if let Some(value) = format_value(value) {
+        println!("{value}");
+    }
+    Ok(())
+}
+
+/// Formats a value as pretty JSON, strings are printed as is.
+fn format_value(value: Dynamic) -> Option<String> {
+    if value.is_unit() {
+        None
+    } else if value.is_string() {
+        Some(value.to_string())
+    } else {
+        Some(
+            rhai::serde::from_dynamic::<serde_json::Value>(&value)
+                .ok()
+                .and_then(|json| serde_json::to_string_pretty(&json).ok())
+                .unwrap_or_else(|| value.to_string()),
+        )
+    }
+}
+
+/// Returns the scripting engine, with the built-in functions calling the node.
+fn engine(client: Arc<rpc::Client>, runtime: Handle) -> Engine {
+    let mut engine = Engine::new();
+    let node = Node { client, runtime };
+    engine
+        .register_fn("head", {
+            let node = node.clone();
+            move || -> RhaiResult<Dynamic> {
+                let head = node.block_on(ChainHead::call(&node.client, ()))?;
+                rhai::serde::to_dynamic(head.into_lotus_json())
+            }
+        })
+        .register_fn("wallets", {
+            let node = node.clone();
+            move || -> RhaiResult<rhai::Array> {
+                let addresses = node.block_on(WalletList::call(&node.client, ()))?;
+                Ok(addresses
+                    .into_iter()
+                    .map(|address| address.to_string().into())
+                    .collect())
+            }
+        })
+        .register_fn("balance", {
+            let node = node.clone();
+            move |address: &str| -> RhaiResult<String> {
+                let address = parse_address(address)?;
+                let balance = node.block_on(WalletBalance::call(&node.client, (address,)))?;
+                Ok(balance.pretty().to_string())
+            }
+        })
+        .register_fn("send", {
+            let node = node.clone();
+            move |to: &str, amount: &str| node.send(to, amount, None)
+        })
+        .register_fn("send", {
+            let node = node.clone();
+            move |to: &str, amount: &str, from: &str| node.send(to, amount, Some(from))
+        })
+        .register_fn("call", {
+            let node = node.clone();
+            move |method_name: &str| node.call(method_name, serde_json::Value::Null)
+        })
+        .register_fn("call", {
+            let node = node.clone();
+            move |method_name: &str, params: Dynamic| -> RhaiResult<Dynamic> {
+                let params = rhai::serde::from_dynamic::<serde_json::Value>(&params)?;
+                node.call(method_name, params)
+            }
+        });
+    engine
+}
+
+fn parse_address(address: &str) -> RhaiResult<Address> {
+    StrictAddress::from_str(address)
+        .map(Into::into)
+        .map_err(|e| format!("invalid address `{address}`: {e}").into())
+}
+
+/// Handle on the node the built-in functions call.
+#[derive(Clone)]
+struct Node {
+    client: Arc<rpc::Client>,
+    runtime: Handle,
+}
+
+impl Node {
+    fn block_on<T, E: std::fmt::Display>(
+        &self,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> RhaiResult<T> {
+        self.runtime
+            .block_on(future)
+            .map_err(|e| e.to_string().into())
+    }
+
+    fn send(&self, to: &str, amount: &str, from: Option<&str>) -> RhaiResult<String> {
+        let to = parse_address(to)?;
+        let amount = humantoken::parse(amount).map_err(|e| format!("invalid amount: {e}"))?;
+        let from = match from {
+            Some(from) => parse_address(from)?,
+            None => self
+                .block_on(WalletDefaultAddress::call(&self.client, ()))?
+                .ok_or("no default wallet address selected")?,
+        };
+        let message = Message {
+            from,
+            to,
+            value: amount,
+            method_num: METHOD_SEND,
+            ..Default::default()
+        };
+        let signed = self.block_on(MpoolPushMessage::call(&self.client, (message, None)))?;
+        Ok(signed.cid().to_string())
+    }
+
+    fn call(&self, method_name: &str, params: serde_json::Value) -> RhaiResult<Dynamic> {
+        if !matches!(
+            params,
+            serde_json::Value::Null | serde_json::Value::Array(_) | serde_json::Value::Object(_)
+        ) {
+            return Err("parameters must be an array or an object map".into());
+        }
+        let request = rpc::Request::<serde_json::Value> {
+            method_name: method_name.to_owned().into(),
+            params,
+            result_type: Default::default(),
+            api_paths: ApiPaths::V1.into(),
+            timeout: *rpc::DEFAULT_REQUEST_TIMEOUT,
+        };
+        let result = self.block_on(self.client.call(request))?;
+        rhai::serde::to_dynamic(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn evaluate_statements() {
+        let client = rpc::Client::from_url("http://127.0.0.1:1/".parse().unwrap());
+        let engine = engine(Arc::new(client), Handle::current());
+        let mut scope = Scope::new();
+
+        engine
+            .eval_with_scope::<()>(&mut scope, "let epoch = 100;")
+            .unwrap();
+        let params = engine
+            .eval_with_scope::<Dynamic>(&mut scope, "[epoch, ()]")
+            .unwrap();
+        assert_eq!(
+            rhai::serde::from_dynamic::<serde_json::Value>(&params).unwrap(),
+            serde_json::json!([100, null])
+        );
+        // Invalid arguments are reported before calling the node
+        assert!(
+            engine
+                .eval_with_scope::<Dynamic>(&mut scope, "balance(\"not an address\")")
+                .unwrap_err()
+                .to_string()
+                .contains("invalid address")
+        );
+        assert!(
+            engine
+                .eval_with_scope::<Dynamic>(&mut scope, "call(\"Filecoin.ChainHead\", 42)")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn format_values() {
+        assert_eq!(format_value(Dynamic::UNIT), None);
+        assert_eq!(format_value("bafy".into()), Some("bafy".into()));
+        let map = rhai::serde::to_dynamic(serde_json::json!({"Height": 100})).unwrap();
+        assert_eq!(format_value(map), Some("{\n  \"Height\": 100\n}".into()));
+    }
+}
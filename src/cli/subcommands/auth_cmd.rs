@@ -1,8 +1,9 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use crate::auth::token_id;
 use crate::rpc::{self, auth::AuthNewParams, prelude::*};
-use chrono::Duration;
+use chrono::{DateTime, Duration};
 use clap::Subcommand;
 
 use super::print_rpc_res_bytes;
@@ -27,6 +28,13 @@ pub enum AuthCommands {
         #[arg(long, default_value = "2 months")]
         expire_in: humantime::Duration,
     },
+    /// List the tokens issued by the node that did not expire
+    List,
+    /// Revoke a token, so that it's rejected by the node without regenerating the JWT secret
+    Revoke {
+        /// The token, or its identifier as listed by `forest-cli auth list`
+        token: String,
+    },
 }
 
 impl AuthCommands {
@@ -46,10 +54,46 @@ impl AuthCommands {
                 let token = String::from_utf8(
                     AuthNew::call(&client, AuthNewParams { perms, token_exp }.into()).await?,
                 )?;
-                let addr = multiaddr::from_url(client.base_url().as_str())?;
+                let addr = match client.socket() {
+                    Some(socket) => format!("/unix{}", socket.display()),
+                    None => multiaddr::from_url(client.base_url().as_str())?.to_string(),
+                };
                 println!("FULLNODE_API_INFO=\"{token}:{addr}\"");
                 Ok(())
             }
+            Self::List => {
+                let tokens = ForestAuthList::call(&client, ()).await?;
+                let format_timestamp = |timestamp| {
+                    DateTime::from_timestamp(timestamp, 0)
+                        .map(|time| time.to_rfc3339())
+                        .unwrap_or_default()
+                };
+                for token in tokens {
+                    println!(
+                        "{}\t{}\tissued {}\texpires {}{}",
+                        token.id,
+                        token.allow.join(","),
+                        format_timestamp(token.issued_at),
+                        format_timestamp(token.expires_at),
+                        if token.revoked { "\trevoked" } else { "" }
+                    );
+                }
+                Ok(())
+            }
+            Self::Revoke { token } => {
+                // Tokens have 3 dot-separated parts, identifiers are hexadecimal.
+                let id = if token.contains('.') {
+                    token_id(&token)?
+                } else {
+                    token
+                };
+                if ForestAuthRevoke::call(&client, (id.clone(),)).await? {
+                    println!("Revoked token {id}");
+                } else {
+                    println!("Token {id} was already revoked");
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -1,20 +1,27 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod checkpoint;
+use checkpoint::ChainCheckpointCommands;
+
 mod list;
 use list::ChainListCommand;
 
+mod pretty;
+
 mod prune;
 use prune::ChainPruneCommands;
 
-use super::print_pretty_lotus_json;
+use super::{OutputFormat, print_pretty_lotus_json};
 use crate::blocks::{Tipset, TipsetKey};
 use crate::lotus_json::HasLotusJson;
 use crate::message::ChainMessage;
-use crate::rpc::{self, prelude::*};
-use anyhow::{bail, ensure};
+use crate::rpc::chain::{ApiHeadChange, CHAIN_NOTIFY};
+use crate::rpc::{self, ApiPaths, prelude::*};
+use anyhow::{Context as _, bail, ensure};
 use cid::Cid;
 use clap::Subcommand;
+use futures::StreamExt as _;
 use nunny::Vec as NonEmpty;
 
 #[derive(Debug, Clone, clap::ValueEnum)]
@@ -31,6 +38,22 @@ pub enum ChainCommands {
         cid: Cid,
     },
 
+    /// Prints out the block specified by the given CID and the counts of its messages
+    GetBlock {
+        cid: Cid,
+        /// Format of the output
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+
+    /// Prints out the message specified by the given CID
+    GetMessage {
+        cid: Cid,
+        /// Format of the output
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+
     /// Prints out the genesis tipset
     Genesis,
 
@@ -41,8 +64,11 @@ pub enum ChainCommands {
         #[arg(short = 'n', long, default_value = "1")]
         tipsets: u64,
         /// Format of the output. `json` or `text`.
-        #[arg(long, default_value = "text")]
+        #[arg(long, visible_alias = "output", default_value = "text")]
         format: Format,
+        /// Keep printing the new heads as they are applied or reverted, until interrupted
+        #[arg(long, conflicts_with = "tipsets")]
+        follow: bool,
     },
 
     /// Reads and prints out a message referenced by the specified CID from the
@@ -59,8 +85,9 @@ pub enum ChainCommands {
         cid: Cid,
     },
 
-    /// Manually set the head to the given tipset. This invalidates blocks
-    /// between the desired head and the new head
+    /// Manually set the head to the given tipset. When switching to another fork, this marks the
+    /// blocks abandoned between the current head and the new head as bad, so that they are not
+    /// followed again
     SetHead {
         /// Construct the new head tipset from these CIDs
         #[arg(num_args = 1.., required = true)]
@@ -75,6 +102,8 @@ pub enum ChainCommands {
     },
     #[command(subcommand)]
     Prune(ChainPruneCommands),
+    #[command(subcommand)]
+    Checkpoint(ChainCheckpointCommands),
     List(ChainListCommand),
 }
 
@@ -84,8 +113,36 @@ impl ChainCommands {
             Self::Block { cid } => {
                 print_pretty_lotus_json(ChainGetBlock::call(&client, (cid,)).await?)
             }
+            Self::GetBlock { cid, output } => {
+                let block = ChainGetBlock::call(&client, (cid,)).await?;
+                match output {
+                    OutputFormat::Text => {
+                        let messages = ChainGetBlockMessages::call(&client, (cid,)).await?;
+                        println!("{}", pretty::format_block(&block, &messages)?);
+                        Ok(())
+                    }
+                    OutputFormat::Json => print_pretty_lotus_json(block),
+                }
+            }
+            Self::GetMessage { cid, output } => {
+                let message = ChainGetMessage::call(&client, (cid,)).await?;
+                match output {
+                    OutputFormat::Text => {
+                        println!("{}", pretty::format_message(&message));
+                        Ok(())
+                    }
+                    OutputFormat::Json => print_pretty_lotus_json(message),
+                }
+            }
             Self::Genesis => print_pretty_lotus_json(ChainGetGenesis::call(&client, ()).await?),
-            Self::Head { tipsets, format } => print_chain_head(&client, tipsets, format).await,
+            Self::Head {
+                format,
+                follow: true,
+                ..
+            } => follow_chain_head(&client, format).await,
+            Self::Head {
+                tipsets, format, ..
+            } => print_chain_head(&client, tipsets, format).await,
             Self::Message { cid } => {
                 let bytes = ChainReadObj::call(&client, (cid,)).await?;
                 match fvm_ipld_encoding::from_slice::<ChainMessage>(&bytes)? {
@@ -132,6 +189,7 @@ impl ChainCommands {
                 Ok(())
             }
             Self::Prune(cmd) => cmd.run(client).await,
+            Self::Checkpoint(cmd) => cmd.run(client).await,
             Self::List(cmd) => cmd.run(client).await,
         }
     }
@@ -210,3 +268,28 @@ async fn print_chain_head(client: &rpc::Client, n: u64, format: Format) -> anyho
     }
     Ok(())
 }
+
+/// Prints the head changes streamed by `Filecoin.ChainNotify` until interrupted or the node closes
+/// the channel. The first change is the current head.
+async fn follow_chain_head(client: &rpc::Client, format: Format) -> anyhow::Result<()> {
+    let mut changes = client
+        .subscribe_channel::<Vec<ApiHeadChange>>(CHAIN_NOTIFY, ApiPaths::V1)
+        .await?;
+    loop {
+        tokio::select! {
+            next = changes.next() => {
+                let Some(next) = next else {
+                    bail!("the node closed the chain notification channel");
+                };
+                for change in next.context("invalid chain notification")? {
+                    let line = match format {
+                        Format::Json => change.into_lotus_json_string()?,
+                        Format::Text => pretty::format_head_change(&change.change, &change.tipset)?,
+                    };
+                    println!("{line}");
+                }
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
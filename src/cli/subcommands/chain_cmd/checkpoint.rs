@@ -0,0 +1,45 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::tipset_by_epoch_or_offset;
+use crate::blocks::TipsetKey;
+use crate::rpc::{self, RpcMethodExt, chain::ForestChainCheckpointAdd};
+use cid::Cid;
+use clap::Subcommand;
+use nunny::Vec as NonEmpty;
+
+/// Manage the checkpoints the node refuses to deviate from when syncing
+#[derive(Debug, Subcommand)]
+pub enum ChainCheckpointCommands {
+    /// Add a tipset of the current chain to the checkpoints, which are persisted to the
+    /// checkpoints file
+    Add {
+        /// The CIDs of the tipset
+        #[arg(num_args = 1.., required = true)]
+        cids: Vec<Cid>,
+        /// Use the tipset from this epoch of the current chain.
+        /// Negative numbers specify decrements from the current head.
+        #[arg(long, conflicts_with = "cids", allow_hyphen_values = true)]
+        epoch: Option<i64>,
+    },
+}
+
+impl ChainCheckpointCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Add {
+                epoch: Some(epoch), ..
+            } => {
+                let tipset = tipset_by_epoch_or_offset(&client, epoch).await?;
+                ForestChainCheckpointAdd::call(&client, (tipset.key().clone(),)).await?;
+            }
+            Self::Add { cids, epoch: None } => {
+                let tsk =
+                    TipsetKey::from(NonEmpty::new(cids).expect("empty vec disallowed by clap"));
+                ForestChainCheckpointAdd::call(&client, (tsk,)).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
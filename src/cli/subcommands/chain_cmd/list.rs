@@ -95,7 +95,7 @@ impl ChainListCommand {
                     let gas_used: u64 = {
                         let receipts = ChainGetParentReceipts::call(
                             &client,
-                            (*child_ts.block_headers().first().cid(),),
+                            (*child_ts.block_headers().first().cid(), None, None),
                         )
                         .await?;
                         receipts.iter().map(|r| r.gas_used).sum()
@@ -0,0 +1,102 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Human-friendly formatting of tipsets, blocks and messages.
+
+use std::fmt::Write as _;
+
+use anyhow::Context as _;
+use itertools::Itertools as _;
+
+use crate::blocks::{CachingBlockHeader, Tipset};
+use crate::cli::humantoken::TokenAmountPretty as _;
+use crate::rpc::chain::{BlockMessages, FlattenedApiMessage};
+
+fn format_timestamp(timestamp: u64) -> anyhow::Result<String> {
+    Ok(chrono::DateTime::from_timestamp(timestamp as _, 0)
+        .context("invalid timestamp")?
+        .to_rfc3339())
+}
+
+/// Formats a head change of the chain, one line per block.
+pub(super) fn format_head_change(change: &str, tipset: &Tipset) -> anyhow::Result<String> {
+    let mut s = format!(
+        "{change} epoch {} at {}, weight {}, {} block(s):",
+        tipset.epoch(),
+        format_timestamp(tipset.min_timestamp())?,
+        tipset.weight(),
+        tipset.len()
+    );
+    for header in tipset.block_headers() {
+        write!(s, "\n  {} by {}", header.cid(), header.miner_address)?;
+    }
+    Ok(s)
+}
+
+pub(super) fn format_block(
+    header: &CachingBlockHeader,
+    messages: &BlockMessages,
+) -> anyhow::Result<String> {
+    Ok([
+        format!("Cid:              {}", header.cid()),
+        format!("Epoch:            {}", header.epoch),
+        format!("Timestamp:        {}", format_timestamp(header.timestamp)?),
+        format!("Miner:            {}", header.miner_address),
+        format!("Parents:          {}", header.parents.iter().join(", ")),
+        format!("Parent weight:    {}", header.weight),
+        format!("Parent state:     {}", header.state_root),
+        format!("Parent receipts:  {}", header.message_receipts),
+        format!("Parent base fee:  {}", header.parent_base_fee.pretty()),
+        format!(
+            "Messages:         {} BLS, {} secp256k1",
+            messages.bls_msg.len(),
+            messages.secp_msg.len()
+        ),
+    ]
+    .join("\n"))
+}
+
+pub(super) fn format_message(FlattenedApiMessage { message, cid }: &FlattenedApiMessage) -> String {
+    [
+        format!("Cid:          {cid}"),
+        format!("From:         {}", message.from),
+        format!("To:           {}", message.to),
+        format!("Nonce:        {}", message.sequence),
+        format!("Value:        {}", message.value.pretty()),
+        format!("Method:       {}", message.method_num),
+        format!("Gas limit:    {}", message.gas_limit),
+        format!("Gas fee cap:  {}", message.gas_fee_cap.pretty()),
+        format!("Gas premium:  {}", message.gas_premium.pretty()),
+        format!("Params:       {} bytes", message.params.len()),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shim::message::Message;
+
+    #[test]
+    fn head_change_lines() {
+        let tipset = Tipset::from(CachingBlockHeader::default());
+        let formatted = format_head_change("apply", &tipset).unwrap();
+        let lines = formatted.lines().collect_vec();
+        assert_eq!(
+            lines[0],
+            "apply epoch 0 at 1970-01-01T00:00:00+00:00, weight 0, 1 block(s):"
+        );
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn message_fields() {
+        let message = Message::default();
+        let formatted = format_message(&FlattenedApiMessage {
+            cid: message.cid(),
+            message,
+        });
+        assert_eq!(formatted.lines().count(), 10);
+        assert!(formatted.contains("Params:       0 bytes"));
+    }
+}
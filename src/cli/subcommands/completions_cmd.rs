@@ -0,0 +1,23 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::cli::subcommands::Cli;
+use crate::rpc;
+use clap::CommandFactory as _;
+use clap_complete::aot::{Shell, generate};
+
+#[derive(Debug, clap::Args)]
+pub struct CompletionsCommand {
+    /// The shell to generate completions for
+    #[arg(default_value = "bash")]
+    shell: Shell,
+}
+
+impl CompletionsCommand {
+    pub async fn run(self, _client: rpc::Client) -> anyhow::Result<()> {
+        let mut cmd = Cli::command();
+        let bin_name = cmd.get_bin_name().unwrap_or("forest-cli").to_string();
+        generate(self.shell, &mut cmd, bin_name, &mut std::io::stdout());
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cli::humantoken;
+use crate::eth::{EAMMethod, EVMMethod};
+use crate::rpc::eth::types::{EthAddress, EthBytes, EthCallMessage};
+use crate::rpc::types::MessageLookup;
+use crate::rpc::{self, LOOKBACK_NO_LIMIT, prelude::*};
+use crate::shim::actors::eam;
+use crate::shim::address::{Address, Protocol, StrictAddress};
+use crate::shim::econ::TokenAmount;
+use crate::shim::message::Message;
+use crate::utils::encoding::from_slice_with_fallback;
+use anyhow::{Context as _, ensure};
+use clap::Subcommand;
+use fvm_ipld_encoding::RawBytes;
+use num::Zero as _;
+
+#[derive(Debug, Subcommand)]
+pub enum EvmCommands {
+    /// Deploy an EVM contract, and print its addresses
+    Deploy {
+        /// Init code of the contract, hex encoded, or the path of a file of hex encoded init
+        /// code, like the `.bin` output of `solc`
+        bytecode: String,
+        #[command(flatten)]
+        send: EvmSendArgs,
+    },
+    /// Invoke an EVM contract, and print its return data
+    Invoke {
+        /// Address of the contract, either an Ethereum or a Filecoin address
+        address: String,
+        /// Call data, hex encoded
+        #[arg(default_value = "")]
+        calldata: String,
+        #[command(flatten)]
+        send: EvmSendArgs,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+pub struct EvmSendArgs {
+    /// Address to send the message from (the default wallet address if omitted)
+    #[arg(long)]
+    from: Option<StrictAddress>,
+    /// The amount to send along the message, e.g. `1.5FIL` or `100 nanoFIL`
+    #[arg(long, value_parser = humantoken::parse)]
+    value: Option<TokenAmount>,
+    /// Gas limit (estimated if omitted)
+    #[arg(long)]
+    gas_limit: Option<u64>,
+}
+
+impl EvmCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Deploy { bytecode, send } => {
+                let initcode = match Path::new(&bytecode).is_file() {
+                    true => std::fs::read_to_string(&bytecode)
+                        .with_context(|| format!("failed to read {bytecode}"))?,
+                    false => bytecode,
+                };
+                let initcode = decode_hex(&initcode).context("invalid init code")?;
+                ensure!(!initcode.is_empty(), "the init code is empty");
+
+                let from = send.from(&client).await?;
+                // The contract address is derived from the address and the nonce of the sender.
+                let nonce = MpoolGetNonce::call(&client, (from,)).await?;
+                let params = eam::CreateParams { initcode, nonce };
+                let message = Message {
+                    from,
+                    to: Address::ETHEREUM_ACCOUNT_MANAGER_ACTOR,
+                    method_num: EAMMethod::Create as u64,
+                    params: fvm_ipld_encoding::to_vec(&params)?.into(),
+                    ..send.message()
+                };
+                let lookup = push_and_wait(&client, message).await?;
+                let ret: eam::CreateReturn =
+                    from_slice_with_fallback(lookup.receipt.return_data().bytes())
+                        .context("failed to decode the return of the EAM")?;
+                let eth_address = EthAddress(ret.eth_address.0.into());
+                println!("Actor ID: {}", ret.actor_id);
+                println!("ID address: {}", Address::new_id(ret.actor_id));
+                if let Some(robust_address) = ret.robust_address {
+                    println!("Robust address: {}", Address::from(&robust_address));
+                }
+                println!("Eth address: {:#x}", eth_address.0);
+                println!("f4 address: {}", eth_address.to_filecoin_address()?);
+                Ok(())
+            }
+            Self::Invoke {
+                address,
+                calldata,
+                send,
+            } => {
+                let to = match address.parse::<EthAddress>() {
+                    Ok(eth_address) => eth_address.to_filecoin_address()?,
+                    Err(_) => address
+                        .parse::<StrictAddress>()
+                        .with_context(|| format!("invalid contract address {address}"))?
+                        .into(),
+                };
+                let calldata = decode_hex(&calldata).context("invalid call data")?;
+
+                let from = send.from(&client).await?;
+                let message = Message {
+                    from,
+                    to,
+                    method_num: EVMMethod::InvokeContract as u64,
+                    params: EthCallMessage::convert_data_to_message_params(EthBytes(calldata))?,
+                    ..send.message()
+                };
+                let lookup = push_and_wait(&client, message).await?;
+                let ret = lookup.receipt.return_data();
+                let ret: RawBytes = match ret.is_empty() {
+                    true => ret,
+                    false => from_slice_with_fallback(ret.bytes())
+                        .context("failed to decode the return of the contract")?,
+                };
+                println!("0x{}", hex::encode(ret.bytes()));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl EvmSendArgs {
+    /// The sender, which can't be an Ethereum account: the node signs messages, not Ethereum
+    /// transactions.
+    async fn from(&self, client: &rpc::Client) -> anyhow::Result<Address> {
+        let from = match self.from {
+            Some(from) => from.into(),
+            None => WalletDefaultAddress::call(client, ())
+                .await?
+                .context("No default wallet address selected. Please set a default address.")?,
+        };
+        ensure!(
+            from.protocol() != Protocol::Delegated,
+            "{from} is an Ethereum account, send an Ethereum transaction with `eth_sendRawTransaction` instead"
+        );
+        Ok(from)
+    }
+
+    /// A message of the value and gas limit of the arguments, the other gas fields are
+    /// estimated.
+    fn message(&self) -> Message {
+        Message {
+            value: self.value.clone().unwrap_or_else(TokenAmount::zero),
+            gas_limit: self.gas_limit.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Pushes a message with the wallet of the node, which assigns its nonce, estimates its gas and
+/// signs it, and waits for its execution. Fails if the message didn't execute successfully.
+async fn push_and_wait(client: &rpc::Client, message: Message) -> anyhow::Result<MessageLookup> {
+    let cid = MpoolPushMessage::call(client, (message, None)).await?.cid();
+    println!("Message CID: {cid}");
+    println!("Waiting for the message to be executed...");
+    let lookup = client
+        .call(StateWaitMsg::request((cid, 1, LOOKBACK_NO_LIMIT, true))?.with_timeout(Duration::MAX))
+        .await?;
+    println!("Gas used: {}", lookup.receipt.gas_used());
+    ensure!(
+        lookup.receipt.exit_code().is_success(),
+        "the message failed with exit code {}",
+        lookup.receipt.exit_code()
+    );
+    Ok(lookup)
+}
+
+/// Decodes hex encoded bytes, with or without the `0x` prefix.
+fn decode_hex(s: &str) -> anyhow::Result<Vec<u8>> {
+    let s = s.trim();
+    Ok(hex::decode(s.strip_prefix("0x").unwrap_or(s))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("0x6080").unwrap(), vec![0x60, 0x80]);
+        assert_eq!(decode_hex("6080\n").unwrap(), vec![0x60, 0x80]);
+        assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+        assert!(decode_hex("0x608").is_err());
+    }
+
+    #[tokio::test]
+    async fn ethereum_account_sender() {
+        let client = rpc::Client::from_url("http://127.0.0.1:1/".parse().unwrap());
+        let send = EvmSendArgs {
+            from: Some(StrictAddress(
+                EthAddress::default().to_filecoin_address().unwrap(),
+            )),
+            value: None,
+            gas_limit: None,
+        };
+        // Rejected before calling the node
+        assert!(
+            send.from(&client)
+                .await
+                .unwrap_err()
+                .to_string()
+                .contains("is an Ethereum account")
+        );
+    }
+}
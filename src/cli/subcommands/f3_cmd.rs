@@ -10,6 +10,7 @@ use std::{
     time::{Duration, Instant},
 };
 
+use super::OutputFormat;
 use crate::{
     blocks::{Tipset, TipsetKey},
     lotus_json::HasLotusJson as _,
@@ -25,7 +26,7 @@ use crate::{
 };
 use ahash::HashSet;
 use cid::Cid;
-use clap::{Subcommand, ValueEnum};
+use clap::Subcommand;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools as _;
 use serde::{Deserialize, Serialize};
@@ -67,25 +68,14 @@ static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| {
     tera
 });
 
-/// Output format
-#[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum F3OutputFormat {
-    /// Text
-    #[default]
-    Text,
-    /// JSON
-    Json,
-}
-
 /// Manages Filecoin Fast Finality (F3) interactions
 #[derive(Debug, Subcommand)]
 pub enum F3Commands {
     /// Gets the current manifest used by F3
     Manifest {
         /// The output format.
-        #[arg(long, value_enum, default_value_t = F3OutputFormat::Text)]
-        output: F3OutputFormat,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
     },
     /// Checks the F3 status.
     Status,
@@ -115,10 +105,10 @@ impl F3Commands {
             Self::Manifest { output } => {
                 let manifest = client.call(F3GetManifest::request(())?).await?;
                 match output {
-                    F3OutputFormat::Text => {
+                    OutputFormat::Text => {
                         println!("{}", render_manifest_template(&manifest)?);
                     }
-                    F3OutputFormat::Json => {
+                    OutputFormat::Json => {
                         println!("{}", serde_json::to_string_pretty(&manifest)?);
                     }
                 }
@@ -236,8 +226,8 @@ pub enum F3CertsCommands {
     Get {
         instance: Option<u64>,
         /// The output format.
-        #[arg(long, value_enum, default_value_t = F3OutputFormat::Text)]
-        output: F3OutputFormat,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
     },
     /// Lists a range of F3 finality certificates.
     List {
@@ -245,8 +235,8 @@ pub enum F3CertsCommands {
         /// `<from>..<to>`. Either `<from>` or `<to>` may be omitted, but not both.
         range: Option<String>,
         /// The output format.
-        #[arg(long, value_enum, default_value_t = F3OutputFormat::Text)]
-        output: F3OutputFormat,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// The maximum number of instances. A value less than 0 indicates no limit.
         #[arg(long, default_value_t = 10)]
         limit: i64,
@@ -266,10 +256,10 @@ impl F3CertsCommands {
                     client.call(F3GetLatestCertificate::request(())?).await?
                 };
                 match output {
-                    F3OutputFormat::Text => {
+                    OutputFormat::Text => {
                         println!("{}", render_certificate_template(&cert)?);
                     }
-                    F3OutputFormat::Json => {
+                    OutputFormat::Json => {
                         println!("{}", serde_json::to_string_pretty(&cert)?);
                     }
                 }
@@ -308,10 +298,10 @@ impl F3CertsCommands {
                 for i in range {
                     let cert = F3GetCertificate::call(&client, (i,)).await?;
                     match output {
-                        F3OutputFormat::Text => {
+                        OutputFormat::Text => {
                             println!("{}", render_certificate_template(&cert)?);
                         }
-                        F3OutputFormat::Json => {
+                        OutputFormat::Json => {
                             println!("{}", serde_json::to_string_pretty(&cert)?);
                         }
                     }
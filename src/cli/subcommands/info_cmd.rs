@@ -3,6 +3,7 @@
 
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use super::OutputFormat;
 use crate::blocks::Tipset;
 use crate::cli::humantoken::TokenAmountPretty;
 use crate::rpc::{self, prelude::*};
@@ -12,13 +13,19 @@ use crate::shim::econ::TokenAmount;
 use chrono::{DateTime, Utc};
 use clap::Subcommand;
 use humantime::format_duration;
+use serde::Serialize;
 
 #[derive(Debug, Subcommand)]
 pub enum InfoCommand {
-    Show,
+    Show {
+        /// The output format
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
 pub struct NodeStatusInfo {
     /// How far behind the node is with respect to syncing to head in seconds
     pub lag: i64,
@@ -30,16 +37,19 @@ pub struct NodeStatusInfo {
     /// epoch the node is currently at
     pub epoch: ChainEpoch,
     /// Base fee is the set price per unit of gas (measured in attoFIL/gas unit) to be burned (sent to an unrecoverable address) for every message execution
+    #[serde(with = "crate::lotus_json")]
     pub base_fee: TokenAmount,
     pub sync_status: SyncStatus,
     /// Start time of the node
     pub start_time: DateTime<Utc>,
     pub network: String,
+    #[serde(with = "crate::lotus_json")]
     pub default_wallet_address: Option<Address>,
+    #[serde(with = "crate::lotus_json")]
     pub default_wallet_address_balance: Option<TokenAmount>,
 }
 
-#[derive(Debug, strum::Display, PartialEq)]
+#[derive(Debug, strum::Display, PartialEq, Serialize)]
 pub enum SyncStatus {
     Ok,
     Slow,
@@ -145,6 +155,7 @@ impl NodeStatusInfo {
 
 impl InfoCommand {
     pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        let Self::Show { output } = self;
         let (node_status, head, network, start_time, default_wallet_address) = tokio::try_join!(
             NodeStatus::call(&client, ()),
             ChainHead::call(&client, ()),
@@ -174,7 +185,10 @@ impl InfoCommand {
             default_wallet_address_balance,
         );
 
-        println!("{}", node_status_info.format(Utc::now()));
+        match output {
+            OutputFormat::Text => println!("{}", node_status_info.format(Utc::now())),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&node_status_info)?),
+        }
 
         Ok(())
     }
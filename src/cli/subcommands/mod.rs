@@ -6,14 +6,18 @@
 // check out the original commit history here:
 // https://github.com/ChainSafe/forest/commits/main/forest/src/cli/mod.rs
 
+mod attach_cmd;
 mod auth_cmd;
 mod chain_cmd;
+mod completions_cmd;
 mod config_cmd;
+mod evm_cmd;
 mod f3_cmd;
 mod healthcheck_cmd;
 mod info_cmd;
 mod mpool_cmd;
 mod net_cmd;
+mod send_cmd;
 mod shutdown_cmd;
 mod snapshot_cmd;
 mod state_cmd;
@@ -21,10 +25,12 @@ mod sync_cmd;
 mod wait_api_cmd;
 
 pub(super) use self::{
-    auth_cmd::AuthCommands, chain_cmd::ChainCommands, config_cmd::ConfigCommands,
+    attach_cmd::AttachCommand, auth_cmd::AuthCommands, chain_cmd::ChainCommands,
+    completions_cmd::CompletionsCommand, config_cmd::ConfigCommands, evm_cmd::EvmCommands,
     f3_cmd::F3Commands, healthcheck_cmd::HealthcheckCommand, mpool_cmd::MpoolCommands,
-    net_cmd::NetCommands, shutdown_cmd::ShutdownCommand, snapshot_cmd::SnapshotCommands,
-    state_cmd::StateCommands, sync_cmd::SyncCommands, wait_api_cmd::WaitApiCommand,
+    net_cmd::NetCommands, send_cmd::SendCommand, shutdown_cmd::ShutdownCommand,
+    snapshot_cmd::SnapshotCommands, state_cmd::StateCommands, sync_cmd::SyncCommands,
+    wait_api_cmd::WaitApiCommand,
 };
 use crate::cli::subcommands::info_cmd::InfoCommand;
 pub(crate) use crate::cli_shared::cli::Config;
@@ -72,6 +78,13 @@ pub enum Subcommand {
     #[command(subcommand)]
     Mpool(MpoolCommands),
 
+    /// Send funds from a wallet of the node
+    Send(SendCommand),
+
+    /// Deploy and invoke FEVM contracts
+    #[command(subcommand)]
+    Evm(EvmCommands),
+
     /// Interact with and query Filecoin chain state
     #[command(subcommand)]
     State(StateCommands),
@@ -101,6 +114,12 @@ pub enum Subcommand {
 
     /// Wait for lotus API to come online
     WaitApi(WaitApiCommand),
+
+    /// Attach an interactive console to the running node
+    Attach(AttachCommand),
+
+    /// Generate shell completions for `forest-cli`
+    Completions(CompletionsCommand),
 }
 
 impl Subcommand {
@@ -109,6 +128,16 @@ impl Subcommand {
     }
 }
 
+/// Output format of a subcommand
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON
+    Json,
+}
+
 /// Print an error message and exit the program with an error code
 /// Used for handling high level errors such as invalid parameters
 pub fn cli_error_and_die(msg: impl AsRef<str>, code: i32) -> ! {
@@ -2,10 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::libp2p::{Multiaddr, Protocol};
-use crate::rpc::{self, net::AddrInfo, prelude::*};
+use crate::rpc::{
+    self,
+    net::{AddrInfo, BandwidthStats},
+    prelude::*,
+};
 use ahash::{HashMap, HashSet};
 use cid::multibase;
 use clap::Subcommand;
+use human_bytes::human_bytes;
 use itertools::Itertools;
 
 use crate::cli::subcommands::cli_error_and_die;
@@ -34,6 +39,15 @@ pub enum NetCommands {
     },
     /// Print information about reachability from the internet
     Reachability,
+    /// Print bandwidth usage, in total or by peer or protocol
+    Bandwidth {
+        /// Print bandwidth usage by peer
+        #[arg(long, conflicts_with = "by_protocol")]
+        by_peer: bool,
+        /// Print bandwidth usage by protocol
+        #[arg(long)]
+        by_protocol: bool,
+    },
 }
 
 impl NetCommands {
@@ -169,6 +183,50 @@ impl NetCommands {
                 }
                 Ok(())
             }
+            Self::Bandwidth {
+                by_peer,
+                by_protocol,
+            } => {
+                let mut rows: Vec<(String, BandwidthStats)> = if by_peer {
+                    NetBandwidthStatsByPeer::call(&client, ())
+                        .await?
+                        .into_iter()
+                        .collect()
+                } else if by_protocol {
+                    NetBandwidthStatsByProtocol::call(&client, ())
+                        .await?
+                        .into_iter()
+                        .collect()
+                } else {
+                    vec![("Total".into(), NetBandwidthStats::call(&client, ()).await?)]
+                };
+                // Heaviest first
+                rows.sort_by_key(|(_, stats)| {
+                    std::cmp::Reverse(stats.total_in.saturating_add(stats.total_out))
+                });
+                let width = rows
+                    .iter()
+                    .map(|(segment, _)| segment.len())
+                    .chain(["Segment".len()])
+                    .max()
+                    .unwrap_or_default();
+                println!(
+                    "{:<width$}  {:>10}  {:>10}  {:>12}  {:>12}",
+                    "Segment", "TotalIn", "TotalOut", "RateIn", "RateOut"
+                );
+                let rate = |bytes_per_sec: f64| format!("{}/s", human_bytes(bytes_per_sec));
+                for (segment, stats) in rows {
+                    println!(
+                        "{:<width$}  {:>10}  {:>10}  {:>12}  {:>12}",
+                        segment,
+                        human_bytes(stats.total_in as f64),
+                        human_bytes(stats.total_out as f64),
+                        rate(stats.rate_in),
+                        rate(stats.rate_out),
+                    );
+                }
+                Ok(())
+            }
         }
     }
 }
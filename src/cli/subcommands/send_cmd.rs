@@ -0,0 +1,117 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::cli::humantoken;
+use crate::rpc::{self, prelude::*, types::ApiTipsetKey};
+use crate::shim::address::{Address, StrictAddress};
+use crate::shim::econ::TokenAmount;
+use crate::shim::message::{METHOD_SEND, Message};
+use anyhow::{Context as _, ensure};
+use num::{BigInt, Zero as _};
+
+/// Send funds from a wallet of the node
+#[derive(Debug, clap::Args)]
+pub struct SendCommand {
+    /// The address to send funds to
+    target_address: StrictAddress,
+    /// The amount to send, e.g. `1.5FIL` or `100 nanoFIL`
+    #[arg(value_parser = humantoken::parse)]
+    amount: TokenAmount,
+    /// Address to send funds from (the default wallet address if omitted)
+    #[arg(long)]
+    from: Option<StrictAddress>,
+    /// Maximum fee per gas unit (estimated if omitted)
+    #[arg(long, value_parser = humantoken::parse)]
+    gas_feecap: Option<TokenAmount>,
+    /// Gas premium per gas unit (estimated if omitted)
+    #[arg(long, value_parser = humantoken::parse)]
+    gas_premium: Option<TokenAmount>,
+    /// Number of epochs within which the message should be included, the gas premium is then
+    /// estimated from the premiums included in the recent tipsets
+    #[arg(long)]
+    inclusion_epochs: Option<u64>,
+    /// Gas limit (estimated if omitted)
+    #[arg(long)]
+    gas_limit: Option<u64>,
+    /// Message nonce (the next nonce of the sender if omitted)
+    #[arg(long)]
+    nonce: Option<u64>,
+    /// Print the expected receipt without pushing the message
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl SendCommand {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        let from: Address = match self.from {
+            Some(from) => from.into(),
+            None => WalletDefaultAddress::call(&client, ())
+                .await?
+                .context("No default wallet address selected. Please set a default address.")?,
+        };
+
+        let message = Message {
+            from,
+            to: self.target_address.into(),
+            value: self.amount,
+            method_num: METHOD_SEND,
+            gas_limit: self.gas_limit.unwrap_or_default(),
+            gas_fee_cap: self.gas_feecap.unwrap_or_else(TokenAmount::zero),
+            gas_premium: self.gas_premium.unwrap_or_else(TokenAmount::zero),
+            ..Default::default()
+        };
+        let message = if let Some(inclusion_epochs) = self.inclusion_epochs
+            && message.gas_premium.is_zero()
+        {
+            let premium =
+                ForestGasEstimateGasPremium::call(&client, (inclusion_epochs, ApiTipsetKey(None)))
+                    .await?;
+            Message {
+                gas_premium: TokenAmount::from_atto(
+                    premium
+                        .parse::<BigInt>()
+                        .context("invalid estimated gas premium")?,
+                ),
+                ..message
+            }
+        } else {
+            message
+        };
+        // Only the fields left unset are estimated.
+        let mut message = GasEstimateMessageGas::call(&client, (message, None, ApiTipsetKey(None)))
+            .await?
+            .message;
+        ensure!(
+            message.gas_premium <= message.gas_fee_cap,
+            "gas premium ({}) is greater than gas fee cap ({})",
+            message.gas_premium,
+            message.gas_fee_cap
+        );
+        message.sequence = match self.nonce {
+            Some(nonce) => nonce,
+            None => MpoolGetNonce::call(&client, (from,)).await?,
+        };
+
+        if self.dry_run {
+            let result = StateCall::call(&client, (message.clone(), ApiTipsetKey(None))).await?;
+            let receipt = result
+                .msg_rct
+                .context("StateCall did not return a receipt")?;
+            println!("Nonce: {}", message.sequence);
+            println!("Gas limit: {}", message.gas_limit);
+            println!("Gas fee cap: {}", message.gas_fee_cap);
+            println!("Gas premium: {}", message.gas_premium);
+            println!("Exit code: {}", receipt.exit_code());
+            println!("Gas used: {}", receipt.gas_used());
+            if !result.error.is_empty() {
+                println!("Error: {}", result.error);
+            }
+            return Ok(());
+        }
+
+        let signed = WalletSignMessage::call(&client, (from, message)).await?;
+        let cid = MpoolPush::call(&client, (signed,)).await?;
+        println!("{cid}");
+        Ok(())
+    }
+}
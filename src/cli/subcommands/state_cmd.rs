@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::lotus_json::HasLotusJson;
-use crate::rpc::state::{ForestComputeStateOutput, ForestStateCompute};
+use crate::rpc::state::{ForestComputeStateOutput, ForestStateCompute, ForestStateExportDagParams};
 use crate::rpc::{self, prelude::*};
 use crate::shim::address::StrictAddress;
 use crate::shim::clock::ChainEpoch;
+use anyhow::Context as _;
 use cid::Cid;
 use clap::Subcommand;
 use std::num::NonZeroUsize;
@@ -43,6 +44,31 @@ pub enum StateCommands {
         /// Actor address to read the state of
         actor_address: StrictAddress,
     },
+    /// Export the sub-DAG of a root CID, or of the state of an actor, matched by an IPLD selector
+    /// or by a path and a depth, to a CAR file
+    ExportDag {
+        /// Root CID of the DAG
+        #[arg(required_unless_present = "actor")]
+        root: Option<Cid>,
+        /// Export the state of the actor at the chain head instead of a root CID
+        #[arg(long, conflicts_with = "root")]
+        actor: Option<StrictAddress>,
+        /// IPLD selector in its JSON representation
+        #[arg(long, conflicts_with_all = ["path", "depth"])]
+        selector: Option<String>,
+        /// `/`-separated fields or list indices leading to the exported sub-DAG
+        #[arg(long)]
+        path: Option<String>,
+        /// Number of IPLD nodes the sub-DAG is explored below the path, all if omitted
+        #[arg(long)]
+        depth: Option<u64>,
+        /// The `.car` file path to export the blocks to
+        #[arg(short, long)]
+        output_path: PathBuf,
+        /// Write a CARv2 file with an index of the blocks, e.g. for IPFS tooling
+        #[arg(long)]
+        car_v2: bool,
+    },
     /// Returns the built-in actor bundle CIDs for the current network
     ActorCids {
         /// Format output
@@ -95,6 +121,43 @@ impl StateCommands {
                     .await?;
                 println!("{}", ret.state.into_lotus_json_string_pretty()?);
             }
+            Self::ExportDag {
+                root,
+                actor,
+                selector,
+                path,
+                depth,
+                output_path,
+                car_v2,
+            } => {
+                let root = match (root, actor) {
+                    (Some(root), _) => root,
+                    (None, Some(actor)) => {
+                        let tipset = ChainHead::call(&client, ()).await?;
+                        StateGetActor::call(&client, (actor.into(), tipset.key().into()))
+                            .await?
+                            .with_context(|| format!("actor {actor} not found"))?
+                            .state
+                    }
+                    (None, None) => anyhow::bail!("either a root CID or an actor is required"),
+                };
+                let selector = selector
+                    .map(|selector| serde_json::from_str(&selector))
+                    .transpose()
+                    .context("invalid IPLD selector")?;
+                let params = ForestStateExportDagParams {
+                    root,
+                    selector,
+                    path,
+                    depth,
+                    output_path: std::path::absolute(&output_path)?,
+                    car_v2,
+                };
+                let exported = client
+                    .call(ForestStateExportDag::request((params,))?.with_timeout(Duration::MAX))
+                    .await?;
+                println!("Exported {exported} blocks to {}", output_path.display());
+            }
             Self::ActorCids { format } => {
                 let info = client.call(StateActorInfo::request(())?).await?;
 
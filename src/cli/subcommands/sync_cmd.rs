@@ -1,10 +1,12 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::OutputFormat;
 use crate::blocks::TipsetKey;
 use crate::chain_sync::{ForkSyncInfo, NodeSyncStatus, SyncStatusReport};
 use crate::rpc::sync::{SnapshotProgressState, SyncStatus};
 use crate::rpc::{self, prelude::*};
+use crate::shim::clock::ChainEpoch;
 use anyhow::Context;
 use cid::Cid;
 use clap::Subcommand;
@@ -24,7 +26,14 @@ pub enum SyncCommands {
         watch: bool,
     },
     /// Check sync status
-    Status,
+    Status {
+        /// The output format
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+        /// Show a live view of the sync, refreshed every second, until interrupted
+        #[arg(long, conflicts_with = "output")]
+        watch: bool,
+    },
     /// Check if a given block is marked bad, and for what reason
     CheckBad {
         #[arg(short)]
@@ -72,8 +81,13 @@ impl SyncCommands {
                 Ok(())
             }
 
-            Self::Status => {
+            Self::Status { watch: true, .. } => watch_sync_status(&client).await,
+            Self::Status { output, .. } => {
                 let sync_status = client.call(SyncStatus::request(())?).await?;
+                if output == OutputFormat::Json {
+                    println!("{}", serde_json::to_string_pretty(&sync_status)?);
+                    return Ok(());
+                }
                 if sync_status.status == NodeSyncStatus::Initializing {
                     // If a snapshot is required and not yet complete, return here
                     if !check_snapshot_progress(&client, false)
@@ -157,6 +171,125 @@ fn print_sync_report_details(report: &SyncStatusReport) -> anyhow::Result<usize>
     Ok(lines_printed_count)
 }
 
+/// Shows a live view of the sync status in the alternate screen of the terminal, until
+/// interrupted.
+async fn watch_sync_status(client: &rpc::Client) -> anyhow::Result<()> {
+    let mut stdout = stdout();
+    write!(
+        stdout,
+        "{}{}",
+        anes::SwitchBufferToAlternate,
+        anes::HideCursor
+    )?;
+    let result = tokio::select! {
+        result = refresh_sync_status(client, &mut stdout) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+    write!(stdout, "{}{}", anes::ShowCursor, anes::SwitchBufferToNormal)?;
+    stdout.flush()?;
+    result
+}
+
+async fn refresh_sync_status(
+    client: &rpc::Client,
+    stdout: &mut std::io::Stdout,
+) -> anyhow::Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    let mut previous: Option<(std::time::Instant, ChainEpoch)> = None;
+    loop {
+        interval.tick().await;
+        let report = SyncStatus::call(client, ())
+            .await
+            .context("Failed to get sync status")?;
+        let peers = NetPeers::call(client, ())
+            .await
+            .map(|peers| peers.len())
+            .ok();
+        let now = std::time::Instant::now();
+        let epochs_per_sec = previous.map(|(time, epoch)| {
+            (report.current_head_epoch - epoch) as f64 / now.duration_since(time).as_secs_f64()
+        });
+        previous = Some((now, report.current_head_epoch));
+
+        write!(
+            stdout,
+            "{}{}",
+            anes::ClearBuffer::All,
+            anes::MoveCursorTo(1, 1)
+        )?;
+        for line in sync_status_view(&report, peers, epochs_per_sec) {
+            writeln!(stdout, "{line}")?;
+        }
+        stdout.flush()?;
+    }
+}
+
+/// Lines of the live view of the sync status.
+fn sync_status_view(
+    report: &SyncStatusReport,
+    peers: Option<usize>,
+    epochs_per_sec: Option<f64>,
+) -> Vec<String> {
+    let unknown = || "unknown".to_string();
+    let mut lines = vec![
+        format!(
+            "Status: {} ({} epochs behind)",
+            report.status, report.epochs_behind
+        ),
+        format!(
+            "Node Head: Epoch {} ({})",
+            report.current_head_epoch,
+            report
+                .current_head_key
+                .as_ref()
+                .map(tipset_key_to_string)
+                .unwrap_or_else(|| "[unknown]".to_string())
+        ),
+        format!("Network Head: Epoch {}", report.network_head_epoch),
+        format!(
+            "Speed: {} epochs/s",
+            epochs_per_sec.map_or_else(unknown, |it| format!("{it:.2}"))
+        ),
+        format!("Peers: {}", peers.map_or_else(unknown, |it| it.to_string())),
+        format!("Last Update: {}", report.last_updated.to_rfc3339()),
+        String::new(),
+    ];
+    if report.active_forks.is_empty() {
+        lines.push("Active Sync Tasks: None".to_string());
+    } else {
+        lines.push("Active Sync Tasks:".to_string());
+        let mut sorted_forks = report.active_forks.clone();
+        sorted_forks.sort_by_key(|f| std::cmp::Reverse(f.target_epoch));
+        lines.extend(sorted_forks.iter().map(|fork| {
+            format!(
+                "  - {:<18} Epoch {} -> {} ({} epochs), Target: {}",
+                fork.stage.to_string(),
+                fork.target_sync_epoch_start,
+                fork.target_epoch,
+                fork.target_epoch
+                    .saturating_sub(fork.target_sync_epoch_start),
+                tipset_key_to_string(&fork.target_tipset_key),
+            )
+        }));
+    }
+    lines.push(String::new());
+    if report.recent_errors.is_empty() {
+        lines.push("Recent Errors: None".to_string());
+    } else {
+        lines.push("Recent Errors:".to_string());
+        lines.extend(
+            report
+                .recent_errors
+                .iter()
+                .rev()
+                .map(|error| format!("  - {error}")),
+        );
+    }
+    lines.push(String::new());
+    lines.push("Press Ctrl-C to exit".to_string());
+    lines
+}
+
 /// Prints fork sync info and returns the number of lines printed (expected to be 1).
 fn print_fork_sync_info(fork: &ForkSyncInfo) -> anyhow::Result<usize> {
     let total_epochs_for_this_fork = fork
@@ -33,6 +33,20 @@ impl Default for BufferSize {
     }
 }
 
+/// TLS termination of the RPC and metrics endpoints
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+pub struct TlsConfig {
+    /// PEM file of the certificate chain
+    pub cert_path: PathBuf,
+    /// PEM file of the private key
+    pub key_path: PathBuf,
+    /// PEM file of the certificate authorities of the clients. If set, clients must present a
+    /// certificate issued by one of them.
+    #[serde(default)]
+    pub client_ca_path: Option<PathBuf>,
+}
+
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default)]
@@ -40,6 +54,11 @@ impl Default for BufferSize {
 pub struct Client {
     pub data_dir: PathBuf,
     pub genesis_file: Option<PathBuf>,
+    /// TOML file with a custom chain configuration, used instead of the built-in one
+    pub chain_config: Option<PathBuf>,
+    /// YAML file of operator checkpoints, `checkpoints.yaml` in the chain data directory by
+    /// default. Sync refuses to deviate from them, as from the built-in ones.
+    pub checkpoints_file: Option<PathBuf>,
     pub enable_rpc: bool,
     pub enable_metrics_endpoint: bool,
     pub enable_health_check: bool,
@@ -61,10 +80,32 @@ pub struct Client {
     pub metrics_address: SocketAddr,
     /// RPC bind, e.g. 127.0.0.1:1234
     pub rpc_address: SocketAddr,
+    /// Serve the RPC and metrics endpoints over TLS
+    pub tls: Option<TlsConfig>,
+    /// Path of a unix socket to serve RPC on as well, e.g. `/run/forest/rpc.sock`. Access is
+    /// restricted to the user and the group of the node.
+    pub rpc_socket: Option<PathBuf>,
+    /// Origins of the browser-based applications allowed to call the RPC, through CORS and
+    /// WebSocket, e.g. `http://localhost:3000`. Use `*` to allow any origin. Browsers are denied
+    /// by default.
+    pub rpc_allowed_origins: Vec<String>,
+    /// Size limits of the RPC requests and responses, and slow-call logging
+    pub rpc_limits: crate::rpc::RpcLimitsConfig,
     /// Path to a list of RPC methods to allow/disallow.
     pub rpc_filter_list: Option<PathBuf>,
+    /// URL of an upstream Lotus or Forest node, e.g. `http://127.0.0.1:1234/`. RPC methods
+    /// that are not supported locally are proxied to this node.
+    pub rpc_upstream: Option<String>,
+    /// RPC methods that are always proxied to `rpc_upstream`, even if they are supported locally.
+    pub rpc_upstream_methods: Vec<String>,
     /// Healthcheck bind, e.g. 127.0.0.1:2346
     pub healthcheck_address: SocketAddr,
+    /// Address of the read-only gateway serving raw blocks by CID on `/ipfs/<cid>?format=raw`,
+    /// e.g. `127.0.0.1:8080`. The gateway is disabled by default.
+    pub gateway_address: Option<SocketAddr>,
+    /// Address of the read-only GraphQL API over the chain data, e.g. `127.0.0.1:8081`. Only
+    /// served when Forest is built with the `graphql` feature, disabled by default.
+    pub graphql_address: Option<SocketAddr>,
     /// Load actors from the bundle file (possibly generating it if it doesn't exist)
     pub load_actors: bool,
 }
@@ -75,6 +116,8 @@ impl Default for Client {
         Self {
             data_dir: dir.data_dir().to_path_buf(),
             genesis_file: None,
+            chain_config: None,
+            checkpoints_file: None,
             enable_rpc: true,
             enable_metrics_endpoint: true,
             enable_health_check: true,
@@ -88,11 +131,19 @@ impl Default for Client {
             encrypt_keystore: true,
             metrics_address: FromStr::from_str("0.0.0.0:6116").unwrap(),
             rpc_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), crate::rpc::DEFAULT_PORT),
+            tls: None,
+            rpc_socket: None,
+            rpc_allowed_origins: vec![],
+            rpc_limits: Default::default(),
             rpc_filter_list: None,
+            rpc_upstream: None,
+            rpc_upstream_methods: vec![],
             healthcheck_address: SocketAddr::new(
                 IpAddr::V4(Ipv4Addr::LOCALHOST),
                 crate::health::DEFAULT_HEALTHCHECK_PORT,
             ),
+            gateway_address: None,
+            graphql_address: None,
             load_actors: true,
         }
     }
@@ -2,13 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::client::Client;
+use crate::db::WriteBufferConfig;
 use crate::db::db_engine::DbConfig;
 use crate::libp2p::Libp2pConfig;
 use crate::shim::clock::ChainEpoch;
 use crate::shim::econ::TokenAmount;
 use crate::utils::misc::env::is_env_set_and_truthy;
 use crate::{chain_sync::SyncConfig, networks::NetworkChain};
+use libp2p::Multiaddr;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 const FOREST_CHAIN_INDEXER_ENABLED: &str = "FOREST_CHAIN_INDEXER_ENABLED";
@@ -43,10 +46,21 @@ impl Default for DaemonConfig {
 /// Structure that defines events configuration
 #[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
 pub struct EventsConfig {
     #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
     pub max_filter_results: usize,
     pub max_filter_height_range: ChainEpoch,
+    /// Maximum number of epochs missed while the node was down whose changes are returned to
+    /// the filters installed before the restart
+    pub max_filter_replay_epochs: ChainEpoch,
+    /// Number of seconds after which a filter that is not polled is uninstalled
+    pub filter_ttl_secs: u64,
+    /// Schemas the values of the actor events returned by `Forest.GetActorEventsDecoded` are
+    /// decoded with, defined in `[[events.schemas]]` sections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(test, arbitrary(gen(|_g| vec![])))]
+    pub schemas: Vec<crate::rpc::misc::EventSchema>,
 }
 
 impl Default for EventsConfig {
@@ -54,6 +68,9 @@ impl Default for EventsConfig {
         Self {
             max_filter_results: 10000,
             max_filter_height_range: 2880,
+            max_filter_replay_epochs: 2880,
+            filter_ttl_secs: 24 * 60 * 60,
+            schemas: vec![],
         }
     }
 }
@@ -109,6 +126,34 @@ impl Default for FeeConfig {
     }
 }
 
+/// Structure that defines a named network profile, selected with `--chain <name>`. Unset fields
+/// keep the values from the rest of the configuration.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Debug, Clone)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct NetworkProfile {
+    /// Data directory of the profile
+    pub data_dir: Option<PathBuf>,
+    /// Genesis CAR file, required for private networks
+    pub genesis_file: Option<PathBuf>,
+    /// TOML file with the chain configuration of a private network, e.g. its height schedule
+    pub chain_config: Option<PathBuf>,
+    #[cfg_attr(test, arbitrary(gen(
+        |g: &mut quickcheck::Gen| {
+            let addr = std::net::Ipv4Addr::arbitrary(&mut *g);
+            let n = u8::arbitrary(g) as usize;
+            vec![addr.into(); n]
+        }
+    )))]
+    pub bootstrap_peers: Vec<Multiaddr>,
+    /// Use only the bootstrap peers of the profile, even if there are none, see
+    /// [`Libp2pConfig::override_bootstrap_peers`]
+    pub override_bootstrap_peers: bool,
+    /// Network of the profile. Defaults to the built-in network of the same name, or to a devnet
+    /// for any other name.
+    pub network: Option<NetworkChain>,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Default, Debug, Clone)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[serde(default)]
@@ -116,6 +161,9 @@ pub struct Config {
     pub chain: NetworkChain,
     pub client: Client,
     pub parity_db: crate::db::parity_db_config::ParityDbConfig,
+    pub disk_space: crate::db::disk_space::DiskSpaceConfig,
+    pub block_probability: crate::message_pool::BlockProbabilityConfig,
+    pub write_buffer: WriteBufferConfig,
     pub network: Libp2pConfig,
     pub sync: SyncConfig,
     pub daemon: DaemonConfig,
@@ -123,6 +171,11 @@ pub struct Config {
     pub fevm: FevmConfig,
     pub fee: FeeConfig,
     pub chain_indexer: ChainIndexerConfig,
+    pub balance_watcher: crate::state_manager::balance_watcher::BalanceWatcherConfig,
+    pub event_sink: crate::state_manager::event_sink::EventSinkConfig,
+    pub log: crate::cli_shared::logger::LogConfig,
+    /// Network profiles, defined in `[chains.<name>]` sections
+    pub chains: BTreeMap<String, NetworkProfile>,
 }
 
 impl Config {
@@ -133,6 +186,31 @@ impl Config {
     pub fn chain(&self) -> &NetworkChain {
         &self.chain
     }
+
+    /// Applies the network profile named after the selected chain, if any.
+    pub fn apply_chain_profile(&mut self) {
+        let Some(profile) = self.chains.get(&self.chain.to_string()).cloned() else {
+            return;
+        };
+        if let Some(network) = profile.network {
+            self.chain = network;
+        }
+        if let Some(data_dir) = profile.data_dir {
+            self.client.data_dir = data_dir;
+        }
+        if profile.genesis_file.is_some() {
+            self.client.genesis_file = profile.genesis_file;
+        }
+        if profile.chain_config.is_some() {
+            self.client.chain_config = profile.chain_config;
+        }
+        if profile.override_bootstrap_peers {
+            self.network.bootstrap_peers = profile.bootstrap_peers;
+            self.network.override_bootstrap_peers = true;
+        } else if !profile.bootstrap_peers.is_empty() {
+            self.network.bootstrap_peers = profile.bootstrap_peers;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -154,4 +232,23 @@ mod test {
             '['
         )
     }
+
+    #[test]
+    fn chain_profile_overrides_bootstrap_peers() {
+        let mut config = Config {
+            chain: NetworkChain::Calibnet,
+            ..Default::default()
+        };
+        config.network.bootstrap_peers = vec!["/ip4/10.0.0.1/tcp/1347".parse().unwrap()];
+        config.chains.insert(
+            "calibnet".into(),
+            NetworkProfile {
+                override_bootstrap_peers: true,
+                ..Default::default()
+            },
+        );
+        config.apply_chain_profile();
+        assert!(config.network.bootstrap_peers.is_empty());
+        assert!(config.network.override_bootstrap_peers);
+    }
 }
@@ -12,6 +12,7 @@ use std::{
 
 use crate::networks::NetworkChain;
 use crate::utils::misc::LoggingColor;
+use crate::utils::misc::memory_budget::{parse_memory_size, set_memory_budget};
 use crate::{cli_shared::read_config, daemon::db_util::ImportMode};
 use ahash::HashSet;
 use clap::Parser;
@@ -61,12 +62,21 @@ pub struct CliOpts {
     /// Path to a list of RPC methods to allow/disallow.
     #[arg(long)]
     pub rpc_filter_list: Option<PathBuf>,
+    /// URL of an upstream Lotus or Forest node that unsupported RPC methods are proxied to.
+    #[arg(long)]
+    pub rpc_upstream: Option<String>,
     /// Disable healthcheck endpoints
     #[arg(long)]
     pub no_healthcheck: bool,
     /// Address used for healthcheck server. By defaults binds on localhost on port 2346.
     #[arg(long)]
     pub healthcheck_address: Option<SocketAddr>,
+    /// Address of the read-only gateway serving raw blocks by CID, e.g. `127.0.0.1:8080`. Disabled by default.
+    #[arg(long)]
+    pub gateway_address: Option<SocketAddr>,
+    /// Address of the read-only GraphQL API, e.g. `127.0.0.1:8081`. Requires the `graphql` feature. Disabled by default.
+    #[arg(long)]
+    pub graphql_address: Option<SocketAddr>,
     /// P2P listen addresses, e.g., `--p2p-listen-address /ip4/0.0.0.0/tcp/12345 --p2p-listen-address /ip4/0.0.0.0/tcp/12346`
     #[arg(long)]
     pub p2p_listen_address: Option<Vec<Multiaddr>>,
@@ -111,7 +121,8 @@ pub struct CliOpts {
     /// Encrypt the key-store (default: true)
     #[arg(long)]
     pub encrypt_keystore: Option<bool>,
-    /// Choose network chain to sync to
+    /// Choose network chain to sync to, or a network profile defined in a `[chains.<name>]`
+    /// config section
     #[arg(long)]
     pub chain: Option<NetworkChain>,
     /// Automatically download a chain specific snapshot to sync with the
@@ -130,7 +141,8 @@ pub struct CliOpts {
     /// Endpoint of `grafana loki`
     #[arg(long, default_value = "http://127.0.0.1:3100")]
     pub loki_endpoint: String,
-    /// Specify a directory into which rolling log files should be appended
+    /// Specify a directory into which rolling log files should be appended, overrides `log.dir` of
+    /// the configuration
     #[arg(long)]
     pub log_dir: Option<PathBuf>,
     /// Exit after basic daemon initialization
@@ -142,6 +154,10 @@ pub struct CliOpts {
     /// Disable the automatic database garbage collection.
     #[arg(long)]
     pub no_gc: bool,
+    /// Run in archival mode, retaining all historical state roots and receipts.
+    /// Database garbage collection is disabled in this mode.
+    #[arg(long)]
+    pub archive: bool,
     /// In stateless mode, forest connects to the P2P network but does not sync to HEAD.
     #[arg(long)]
     pub stateless: bool,
@@ -151,10 +167,18 @@ pub struct CliOpts {
     /// Skip loading actors from the actors bundle.
     #[arg(long)]
     pub skip_load_actors: bool,
+    /// Memory budget the caches and buffers are sized from, in bytes or with a binary unit, e.g.
+    /// `8GiB`. The default sizes are those of an 8 GiB budget.
+    #[arg(long, value_parser = parse_memory_size)]
+    pub memory_budget: Option<u64>,
 }
 
 impl CliOpts {
     pub fn to_config(&self) -> Result<(Config, Option<ConfigPath>), anyhow::Error> {
+        // The defaults of the configuration are sized from the memory budget.
+        if let Some(memory_budget) = self.memory_budget {
+            set_memory_budget(memory_budget);
+        }
         let (path, mut cfg) = read_config(self.config.as_ref(), self.chain.clone())?;
 
         if let Some(genesis_file) = &self.genesis {
@@ -163,6 +187,9 @@ impl CliOpts {
         if self.rpc.unwrap_or(cfg.client.enable_rpc) {
             cfg.client.enable_rpc = true;
             cfg.client.rpc_filter_list = self.rpc_filter_list.clone();
+            if let Some(rpc_upstream) = &self.rpc_upstream {
+                cfg.client.rpc_upstream = Some(rpc_upstream.clone());
+            }
             if let Some(rpc_address) = self.rpc_address {
                 cfg.client.rpc_address = rpc_address;
             }
@@ -179,6 +206,14 @@ impl CliOpts {
             }
         }
 
+        if let Some(gateway_address) = self.gateway_address {
+            cfg.client.gateway_address = Some(gateway_address);
+        }
+
+        if let Some(graphql_address) = self.graphql_address {
+            cfg.client.graphql_address = Some(graphql_address);
+        }
+
         if self.no_metrics {
             cfg.client.enable_metrics_endpoint = false;
         } else {
@@ -216,6 +251,10 @@ impl CliOpts {
 
         cfg.client.load_actors = !self.skip_load_actors;
 
+        if self.archive {
+            cfg.sync.archive = true;
+        }
+
         Ok((cfg, path))
     }
 }
@@ -252,15 +291,20 @@ pub fn find_config_path(config: Option<&PathBuf>) -> Option<ConfigPath> {
     if let Ok(s) = std::env::var("FOREST_CONFIG_PATH") {
         return Some(ConfigPath::Env(PathBuf::from(s)));
     }
-    if let Some(dir) = ProjectDirs::from("com", "ChainSafe", "Forest") {
-        let path = dir.config_dir().join("config.toml");
-        if path.exists() {
-            return Some(ConfigPath::Project(path));
-        }
+    if let Some(path) = project_config_path()
+        && path.exists()
+    {
+        return Some(ConfigPath::Project(path));
     }
     None
 }
 
+/// Returns the path of the configuration file in the project configuration directory of the
+/// user, whether it exists or not.
+pub fn project_config_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "ChainSafe", "Forest").map(|dir| dir.config_dir().join("config.toml"))
+}
+
 fn find_unknown_keys<'a>(
     tables: Vec<&'a str>,
     x: &'a toml::Value,
@@ -1,6 +1,9 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod rolling;
+pub use rolling::{LogConfig, LogRotation};
+
 use std::pin::Pin;
 
 use futures::Future;
@@ -18,7 +21,7 @@ pub struct Guards {
 }
 
 #[allow(unused_mut)]
-pub fn setup_logger(opts: &CliOpts) -> (Vec<BackgroundTask>, Guards) {
+pub fn setup_logger(opts: &CliOpts, config: &LogConfig) -> (Vec<BackgroundTask>, Guards) {
     let mut background_tasks: Vec<BackgroundTask> = vec![];
     let mut guards = Guards::default();
     let mut layers: Vec<Box<dyn tracing_subscriber::layer::Layer<Registry> + Send + Sync>> =
@@ -30,14 +33,16 @@ pub fn setup_logger(opts: &CliOpts) -> (Vec<BackgroundTask>, Guards) {
         )];
 
     // file logger
-    if let Some(log_dir) = &opts.log_dir {
-        let file_appender = tracing_appender::rolling::hourly(log_dir, "forest.log");
-        layers.push(Box::new(
-            tracing_subscriber::fmt::Layer::new()
-                .with_ansi(false)
-                .with_writer(file_appender)
-                .with_filter(get_env_filter(default_env_filter())),
-        ));
+    if let Some(log_dir) = opts.log_dir.as_ref().or(config.dir.as_ref()) {
+        match rolling::RollingFile::new(log_dir, config.clone()) {
+            Ok(file_appender) => layers.push(Box::new(
+                tracing_subscriber::fmt::Layer::new()
+                    .with_ansi(false)
+                    .with_writer(std::sync::Mutex::new(file_appender))
+                    .with_filter(get_env_filter(default_env_filter())),
+            )),
+            Err(e) => eprintln!("Failed to open the log file in {}: {e}", log_dir.display()),
+        }
     }
 
     if opts.tokio_console {
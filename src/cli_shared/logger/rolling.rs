@@ -0,0 +1,243 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Rotating log file appender. Logs are appended to `forest.log` in the log directory, which is
+//! rotated on a period and on a size, i.e. renamed after the time of the rotation, optionally
+//! compressed, and the oldest rotated files beyond the retention count are deleted. Unlike an
+//! external `logrotate`, files are never truncated under the daemon.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Timelike as _, Utc};
+use serde::{Deserialize, Serialize};
+
+const LOG_FILE_NAME: &str = "forest.log";
+
+/// Period on which the log file is rotated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Minutely,
+    #[default]
+    Hourly,
+    Daily,
+    /// Only rotated on size
+    Never,
+}
+
+impl LogRotation {
+    /// Returns the start of the period of the time, `None` if never rotated on time.
+    fn period_start(self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let time = time.with_second(0)?.with_nanosecond(0)?;
+        match self {
+            Self::Minutely => Some(time),
+            Self::Hourly => time.with_minute(0),
+            Self::Daily => time.with_minute(0)?.with_hour(0),
+            Self::Never => None,
+        }
+    }
+}
+
+/// Log file configuration exposed in Forest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct LogConfig {
+    /// Directory the log files are written to, overridden by `--log-dir`. Logs are only written to
+    /// the standard output when unset.
+    pub dir: Option<PathBuf>,
+    /// Period on which the log file is rotated
+    pub rotation: LogRotation,
+    /// Size of the log file, in bytes, above which it is rotated, use 0 to only rotate on the
+    /// period
+    pub max_file_size_bytes: u64,
+    /// Number of rotated log files kept, use 0 to keep them all
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub max_files: usize,
+    /// Compress the rotated log files with gzip
+    pub compress: bool,
+}
+
+pub struct RollingFile {
+    dir: PathBuf,
+    config: LogConfig,
+    file: File,
+    size: u64,
+    period_start: Option<DateTime<Utc>>,
+}
+
+impl RollingFile {
+    pub fn new(dir: impl Into<PathBuf>, config: LogConfig) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let file = open_log_file(&dir)?;
+        Ok(Self {
+            size: file.metadata()?.len(),
+            period_start: config.rotation.period_start(Utc::now()),
+            dir,
+            config,
+            file,
+        })
+    }
+
+    fn should_rotate(&self, now: DateTime<Utc>) -> bool {
+        (self.config.max_file_size_bytes > 0 && self.size >= self.config.max_file_size_bytes)
+            || self.config.rotation.period_start(now) != self.period_start
+    }
+
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        self.file.flush()?;
+        let stamp = now.format("%Y-%m-%dT%H-%M-%S");
+        let taken = |name: &str| {
+            self.dir.join(name).exists() || self.dir.join(format!("{name}.gz")).exists()
+        };
+        let mut name = format!("{LOG_FILE_NAME}.{stamp}");
+        let mut n = 1;
+        while taken(&name) {
+            name = format!("{LOG_FILE_NAME}.{stamp}.{n}");
+            n += 1;
+        }
+        let rotated = self.dir.join(name);
+        fs::rename(self.dir.join(LOG_FILE_NAME), &rotated)?;
+        self.file = open_log_file(&self.dir)?;
+        self.size = 0;
+        self.period_start = self.config.rotation.period_start(now);
+        // Compressing can take a while, logging isn't blocked on it.
+        let dir = self.dir.clone();
+        let config = self.config.clone();
+        std::thread::spawn(move || {
+            if config.compress
+                && let Err(e) = compress(&rotated)
+            {
+                eprintln!("Failed to compress log file {}: {e}", rotated.display());
+            }
+            if let Err(e) = remove_old_files(&dir, config.max_files) {
+                eprintln!("Failed to remove old log files: {e}");
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let now = Utc::now();
+        if self.should_rotate(now)
+            && let Err(e) = self.rotate(now)
+        {
+            // Logs are kept in the current file rather than lost.
+            eprintln!("Failed to rotate the log file: {e}");
+            self.period_start = self.config.rotation.period_start(now);
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_log_file(dir: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(LOG_FILE_NAME))
+}
+
+fn compress(path: &Path) -> io::Result<()> {
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(".gz");
+    let mut encoder =
+        flate2::write::GzEncoder::new(File::create(&compressed)?, flate2::Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)
+}
+
+/// Removes the oldest rotated log files, keeping `max_files` of them.
+fn remove_old_files(dir: &Path, max_files: usize) -> io::Result<()> {
+    if max_files == 0 {
+        return Ok(());
+    }
+    let prefix = format!("{LOG_FILE_NAME}.");
+    let mut rotated = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .collect::<Vec<_>>();
+    // The names start with the time of the rotation, so the oldest sort first.
+    rotated.sort();
+    for path in rotated.iter().rev().skip(max_files) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_on_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogConfig {
+            rotation: LogRotation::Never,
+            max_file_size_bytes: 10,
+            ..Default::default()
+        };
+        let mut file = RollingFile::new(dir.path(), config).unwrap();
+        writeln!(file, "first line").unwrap();
+        assert!(file.should_rotate(Utc::now()));
+        writeln!(file, "second line").unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.path().join(LOG_FILE_NAME)).unwrap(),
+            "second line\n"
+        );
+    }
+
+    #[test]
+    fn retain_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = RollingFile::new(dir.path(), LogConfig::default()).unwrap();
+        for i in 0..4 {
+            writeln!(file, "line {i}").unwrap();
+            file.rotate(Utc::now()).unwrap();
+        }
+        remove_old_files(dir.path(), 2).unwrap();
+        // The current file and the two newest rotated ones
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn rotation_periods() {
+        let time = DateTime::parse_from_rfc3339("2026-10-16T12:34:56Z")
+            .unwrap()
+            .to_utc();
+        let hour = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        assert_eq!(LogRotation::Hourly.period_start(time), Some(hour));
+        assert_eq!(LogRotation::Daily.period_start(time), hour.with_hour(0));
+        assert_eq!(LogRotation::Never.period_start(time), None);
+    }
+
+    #[test]
+    fn compress_rotated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("forest.log.1");
+        fs::write(&path, "some logs").unwrap();
+        compress(&path).unwrap();
+        assert!(!path.exists());
+        assert!(dir.path().join("forest.log.1.gz").exists());
+    }
+}
@@ -37,6 +37,7 @@ pub fn read_config(
     if let Some(chain) = chain_opt {
         config.chain = chain;
     }
+    config.apply_chain_profile();
     Ok((path, config))
 }
 
@@ -82,6 +83,47 @@ mod tests {
         assert_eq!(config.chain(), &NetworkChain::Mainnet);
         assert_eq!(config, default_config);
     }
+
+    #[test]
+    fn read_config_with_chain_profile() {
+        let temp_dir = tempfile::tempdir().expect("couldn't create temp dir");
+        let config_file = temp_dir.path().join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+            [chains.calibnet]
+            data_dir = "/data/calibnet"
+
+            [chains.mynet]
+            genesis_file = "/data/mynet/genesis.car"
+            chain_config = "/data/mynet/chain.toml"
+            bootstrap_peers = ["/ip4/10.0.0.1/tcp/1347"]
+            "#,
+        )
+        .unwrap();
+
+        let (_, config) = read_config(Some(&config_file), Some(NetworkChain::Calibnet)).unwrap();
+        assert_eq!(config.chain(), &NetworkChain::Calibnet);
+        assert_eq!(config.client.data_dir, PathBuf::from("/data/calibnet"));
+        assert!(config.client.chain_config.is_none());
+
+        let (_, config) = read_config(Some(&config_file), Some("mynet".parse().unwrap())).unwrap();
+        assert_eq!(config.chain(), &NetworkChain::Devnet("mynet".into()));
+        assert_eq!(
+            config.client.genesis_file,
+            Some(PathBuf::from("/data/mynet/genesis.car"))
+        );
+        assert_eq!(
+            config.client.chain_config,
+            Some(PathBuf::from("/data/mynet/chain.toml"))
+        );
+        assert_eq!(config.network.bootstrap_peers.len(), 1);
+
+        // No profile for mainnet, the configuration is left as is.
+        let (_, config) = read_config(Some(&config_file), None).unwrap();
+        assert_eq!(config.chain(), &NetworkChain::Mainnet);
+        assert_eq!(config.client, Config::default().client);
+    }
 }
 
 pub mod snapshot;
@@ -4,13 +4,14 @@
 use crate::db::PersistentStore;
 use crate::utils::net::http_get;
 use crate::{
-    networks::{ACTOR_BUNDLES, ActorBundleInfo, NetworkChain},
+    networks::{ACTOR_BUNDLES, ActorBundleInfo, ChainConfig, NetworkChain},
     utils::db::car_stream::{CarBlock, CarStream},
 };
 use ahash::HashSet;
 use cid::Cid;
 use directories::ProjectDirs;
 use futures::{TryStreamExt, stream::FuturesUnordered};
+use fvm_ipld_blockstore::Blockstore;
 use std::mem::discriminant;
 use std::path::PathBuf;
 use std::sync::LazyLock;
@@ -38,6 +39,23 @@ pub async fn load_actor_bundles(
     Ok(())
 }
 
+/// Ensures the actor bundles of all the network upgrades are in the blockstore. Custom chain
+/// configurations may reference bundles that Forest does not know about.
+pub fn ensure_actor_bundles_loaded(
+    db: &impl Blockstore,
+    chain_config: &ChainConfig,
+) -> anyhow::Result<()> {
+    for (height, info) in chain_config.height_infos.iter() {
+        if let Some(bundle) = &info.bundle {
+            anyhow::ensure!(
+                db.has(bundle)?,
+                "actor bundle {bundle} of the {height} upgrade is missing, set FOREST_ACTOR_BUNDLE_PATH to a bundle that contains it"
+            );
+        }
+    }
+    Ok(())
+}
+
 pub async fn load_actor_bundles_from_path(
     db: &impl PersistentStore,
     network: &NetworkChain,
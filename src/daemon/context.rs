@@ -1,12 +1,12 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::auth::{ADMIN, create_token, generate_priv_key};
+use crate::auth::{admin_token, generate_priv_key};
 use crate::chain::ChainStore;
 use crate::cli_shared::chain_path;
 use crate::cli_shared::cli::CliOpts;
 use crate::daemon::asyncify;
-use crate::daemon::bundle::load_actor_bundles;
+use crate::daemon::bundle::{ensure_actor_bundles_loaded, load_actor_bundles};
 use crate::daemon::db_util::load_all_forest_cars_with_cleanup;
 use crate::db::car::ManyCar;
 use crate::db::db_engine::{db_root, open_db};
@@ -14,7 +14,7 @@ use crate::db::parity_db::ParityDb;
 use crate::db::{CAR_DB_DIR_NAME, DummyStore, EthMappingsStore};
 use crate::genesis::read_genesis_header;
 use crate::libp2p::{Keypair, PeerId};
-use crate::networks::ChainConfig;
+use crate::networks::{ChainConfig, CustomChainConfig};
 use crate::rpc::sync::SnapshotProgressTracker;
 use crate::shim::address::CurrentNetwork;
 use crate::state_manager::StateManager;
@@ -27,7 +27,7 @@ use dialoguer::console::Term;
 use fvm_shared4::address::Network;
 use parking_lot::RwLock;
 use std::cell::RefCell;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{info, warn};
 
@@ -44,10 +44,22 @@ pub struct AppContext {
 
 impl AppContext {
     pub async fn init(opts: &CliOpts, cfg: &Config) -> anyhow::Result<AppContext> {
-        let chain_cfg = get_chain_config_and_set_network(cfg);
+        let custom_chain_cfg = cfg
+            .client
+            .chain_config
+            .as_deref()
+            .map(CustomChainConfig::from_file)
+            .transpose()?;
+        let chain_cfg = get_chain_config_and_set_network(cfg, custom_chain_cfg.as_ref())?;
         let (net_keypair, p2p_peer_id) = get_or_create_p2p_keypair_and_peer_id(cfg)?;
         let (db, db_meta_data) = setup_db(opts, cfg).await?;
-        let state_manager = create_state_manager(cfg, &db, &chain_cfg).await?;
+        if custom_chain_cfg.is_some() && cfg.client.load_actors && !opts.stateless {
+            ensure_actor_bundles_loaded(&db, &chain_cfg)?;
+        }
+        let genesis_file = cfg.client.genesis_file.as_deref().or(custom_chain_cfg
+            .as_ref()
+            .and_then(|custom| custom.genesis_file.as_deref()));
+        let state_manager = create_state_manager(cfg, &db, &chain_cfg, genesis_file).await?;
         let (keystore, admin_jwt) = load_or_create_keystore_and_configure_jwt(opts, cfg).await?;
         let snapshot_progress_tracker = SnapshotProgressTracker::default();
         Ok(Self {
@@ -71,17 +83,27 @@ impl AppContext {
     }
 }
 
-fn get_chain_config_and_set_network(config: &Config) -> Arc<ChainConfig> {
-    let chain_config = ChainConfig::from_chain(config.chain());
+fn get_chain_config_and_set_network(
+    config: &Config,
+    custom_chain_config: Option<&CustomChainConfig>,
+) -> anyhow::Result<Arc<ChainConfig>> {
+    let chain_config = match custom_chain_config {
+        Some(custom) => {
+            info!("Using custom chain configuration for {}", config.chain());
+            custom.to_chain_config(config.chain())?
+        }
+        None => ChainConfig::from_chain(config.chain()),
+    };
     if chain_config.is_testnet() {
         CurrentNetwork::set_global(Network::Testnet);
     }
-    Arc::new(ChainConfig {
+    Ok(Arc::new(ChainConfig {
         enable_indexer: config.chain_indexer.enable_indexer,
         enable_receipt_event_caching: config.client.enable_rpc,
         default_max_fee: config.fee.max_fee.clone(),
+        write_buffer: config.write_buffer,
         ..chain_config
-    })
+    }))
 }
 
 fn get_or_create_p2p_keypair_and_peer_id(config: &Config) -> anyhow::Result<(Keypair, PeerId)> {
@@ -165,7 +187,7 @@ async fn load_or_create_keystore_and_configure_jwt(
     if keystore.get(JWT_IDENTIFIER).is_err() {
         keystore.put(JWT_IDENTIFIER, generate_priv_key())?;
     }
-    let admin_jwt = handle_admin_token(opts, config, &keystore)?;
+    let admin_jwt = handle_admin_token(opts, config, &mut keystore)?;
     let keystore = Arc::new(RwLock::new(keystore));
     Ok((keystore, admin_jwt))
 }
@@ -225,12 +247,13 @@ async fn create_state_manager(
     config: &Config,
     db: &Arc<DbType>,
     chain_config: &Arc<ChainConfig>,
+    genesis_file: Option<&Path>,
 ) -> anyhow::Result<Arc<StateManager<DbType>>> {
     // Read Genesis file
     // * When snapshot command implemented, this genesis does not need to be
     //   initialized
     let genesis_header = read_genesis_header(
-        config.client.genesis_file.as_deref(),
+        genesis_file,
         chain_config.genesis_bytes(db).await?.as_deref(),
         db,
     )
@@ -249,9 +272,22 @@ async fn create_state_manager(
         chain_config.clone(),
         genesis_header.clone(),
     )?);
+    let checkpoints_file = config
+        .client
+        .checkpoints_file
+        .clone()
+        .unwrap_or_else(|| chain_path(config).join("checkpoints.yaml"));
+    chain_store
+        .checkpoints()
+        .write()
+        .load_file(&checkpoints_file)?;
 
     // Initialize StateManager
-    let state_manager = Arc::new(StateManager::new(Arc::clone(&chain_store))?);
+    let state_manager = Arc::new(
+        StateManager::new(Arc::clone(&chain_store))?
+            .with_persisted_tipset_states(db.clone())
+            .with_state_provenance(db.clone()),
+    );
 
     Ok(state_manager)
 }
@@ -322,18 +358,15 @@ fn create_password(prompt: &str) -> dialoguer::Result<String> {
 fn handle_admin_token(
     opts: &CliOpts,
     config: &Config,
-    keystore: &KeyStore,
+    keystore: &mut KeyStore,
 ) -> anyhow::Result<String> {
-    let ki = keystore.get(JWT_IDENTIFIER)?;
     // Lotus admin tokens do not expire but Forest requires all JWT tokens to
     // have an expiration date. So we set the expiration date to 100 years in
     // the future to match user-visible behavior of Lotus.
     let token_exp = chrono::Duration::days(365 * 100);
-    let token = create_token(
-        ADMIN.iter().map(ToString::to_string).collect(),
-        ki.private_key(),
-        token_exp,
-    )?;
+    // The admin token is persisted in the keystore and reused across restarts, for a new
+    // token not to be registered on every start.
+    let token = admin_token(keystore, token_exp)?;
     let default_token_path = config.client.default_rpc_token_path();
     if let Err(e) =
         crate::utils::io::write_new_sensitive_file(token.as_bytes(), &default_token_path)
@@ -5,7 +5,7 @@ use crate::blocks::Tipset;
 use crate::db::car::forest::{
     FOREST_CAR_FILE_EXTENSION, TEMP_FOREST_CAR_FILE_EXTENSION, new_forest_car_temp_path_in,
 };
-use crate::db::car::{ForestCar, ManyCar};
+use crate::db::car::{BLOOM_FILE_EXTENSION, ForestCar, ManyCar};
 use crate::interpreter::VMTrace;
 use crate::networks::ChainConfig;
 use crate::rpc::sync::SnapshotProgressTracker;
@@ -14,7 +14,7 @@ use crate::state_manager::{NO_CALLBACK, StateManager};
 use crate::utils::db::car_stream::CarStream;
 use crate::utils::io::EitherMmapOrRandomAccessFile;
 use crate::utils::net::{DownloadFileOption, download_to};
-use anyhow::{Context, bail};
+use anyhow::{Context, bail, ensure};
 use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -59,6 +59,7 @@ fn load_all_forest_cars_internal<T>(
     if !forest_car_db_dir.is_dir() {
         fs::create_dir_all(forest_car_db_dir)?;
     }
+    let mut forest_cars = vec![];
     for file in WalkDir::new(forest_car_db_dir)
         .max_depth(1)
         .into_iter()
@@ -74,10 +75,20 @@ fn load_all_forest_cars_internal<T>(
     {
         if let Some(filename) = file.file_name().and_then(OsStr::to_str) {
             if filename.ends_with(FOREST_CAR_FILE_EXTENSION) {
-                let car = ForestCar::try_from(file.as_path())
-                    .with_context(|| format!("Error loading car DB at {}", file.display()))?;
-                store.read_only(car.into())?;
-                debug!("Loaded car DB at {}", file.display());
+                forest_cars.push(file);
+            } else if cleanup
+                && let Some(car_filename) = filename
+                    .strip_suffix(BLOOM_FILE_EXTENSION)
+                    .and_then(|it| it.strip_suffix('.'))
+                && !file.with_file_name(car_filename).exists()
+            {
+                // Bloom filters of the car DB files that were removed
+                if let Err(e) = std::fs::remove_file(&file) {
+                    warn!(
+                        "Failed to delete stale bloom filter at {}: {e}",
+                        file.display()
+                    );
+                }
             } else if cleanup && filename.ends_with(TEMP_FOREST_CAR_FILE_EXTENSION) {
                 // Only delete files that appear to be incomplete car DB files
                 match std::fs::remove_file(&file) {
@@ -92,6 +103,11 @@ fn load_all_forest_cars_internal<T>(
         }
     }
 
+    debug!("Loading car DBs {forest_cars:?}");
+    store
+        .read_only_files(forest_cars.into_iter())
+        .context("Error loading car DBs")?;
+
     tracing::info!("Loaded {} CARs", store.len());
 
     Ok(())
@@ -125,6 +141,22 @@ pub enum ImportMode {
     Hardlink,
 }
 
+/// What becomes of the original snapshot file once the imported snapshot has been verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OriginalSnapshot {
+    /// The original file is left untouched, or there is none.
+    Kept,
+    /// The original file has been moved into the database directory.
+    Moved,
+    /// The original file has been transcoded and is removed once the import is verified.
+    Remove,
+}
+
+const STAGE_DOWNLOADING: &str = "[1/4] Downloading";
+const STAGE_DECOMPRESSING: &str = "[2/4] Decompressing and re-encoding into .forest.car.zst";
+const STAGE_INDEXING: &str = "[3/4] Indexing";
+const STAGE_VERIFYING: &str = "[4/4] Verifying head links";
+
 /// This function validates and stores the CAR binary from `from_path`(either local path or URL) into the `{DB_ROOT}/car_db/`
 /// (automatically trans-code into `.forest.car.zst` format when needed), and returns its final file path and the heaviest tipset.
 pub async fn import_chain_as_forest_car(
@@ -149,28 +181,34 @@ pub async fn import_chain_as_forest_car(
         let forest_car_db_path = forest_car_db_path.clone();
         async move {
             let downloaded_car_temp_path = new_forest_car_temp_path_in(forest_car_db_dir)?;
-            if let Ok(url) = Url::parse(&from_path.display().to_string()) {
+            let original = if let Ok(url) = Url::parse(&from_path.display().to_string()) {
                 download_to(
                     &url,
                     &downloaded_car_temp_path,
                     DownloadFileOption::Resumable,
-                    snapshot_progress_tracker.create_callback(),
+                    snapshot_progress_tracker.create_callback(STAGE_DOWNLOADING),
                 )
                 .await?;
-
-                snapshot_progress_tracker.completed();
+                OriginalSnapshot::Kept
             } else {
                 snapshot_progress_tracker.not_required();
                 if ForestCar::is_valid(&EitherMmapOrRandomAccessFile::open(from_path)?) {
                     move_or_copy_file(from_path, &downloaded_car_temp_path, mode)?;
+                    if mode == ImportMode::Move {
+                        OriginalSnapshot::Moved
+                    } else {
+                        OriginalSnapshot::Kept
+                    }
                 } else {
                     // For a local snapshot, we transcode directly instead of copying & transcoding.
                     transcode_into_forest_car(from_path, &downloaded_car_temp_path).await?;
                     if mode == ImportMode::Move {
-                        std::fs::remove_file(from_path).context("Error removing original file")?;
+                        OriginalSnapshot::Remove
+                    } else {
+                        OriginalSnapshot::Kept
                     }
                 }
-            }
+            };
 
             if ForestCar::is_valid(&EitherMmapOrRandomAccessFile::open(
                 &downloaded_car_temp_path,
@@ -179,39 +217,41 @@ pub async fn import_chain_as_forest_car(
             } else {
                 // Use another temp file to make sure all final `.forest.car.zst` files are complete and valid.
                 let forest_car_db_temp_path = new_forest_car_temp_path_in(forest_car_db_dir)?;
+                snapshot_progress_tracker.set_stage(STAGE_DECOMPRESSING);
                 transcode_into_forest_car(&downloaded_car_temp_path, &forest_car_db_temp_path)
                     .await?;
                 forest_car_db_temp_path.persist(&forest_car_db_path)?;
             }
-            anyhow::Ok(())
+            anyhow::Ok(original)
         }
     };
 
-    match import_mode {
+    let original = match import_mode {
         ImportMode::Auto => {
             if Url::parse(&from_path.display().to_string()).is_ok() {
                 // Fallback to move if from_path is url
-                move_or_copy(ImportMode::Move).await?;
+                move_or_copy(ImportMode::Move).await?
             } else if ForestCar::is_valid(&EitherMmapOrRandomAccessFile::open(from_path)?) {
                 tracing::info!(
                     "Hardlinking {} to {}",
                     from_path.display(),
                     forest_car_db_path.display()
                 );
+                snapshot_progress_tracker.not_required();
                 if std::fs::hard_link(from_path, &forest_car_db_path).is_err() {
                     tracing::warn!("Error creating hardlink, fallback to copy");
-                    move_or_copy(ImportMode::Copy).await?;
+                    move_or_copy(ImportMode::Copy).await?
+                } else {
+                    OriginalSnapshot::Kept
                 }
             } else {
                 tracing::warn!(
                     "Snapshot file is not a valid forest.car.zst file, fallback to copy"
                 );
-                move_or_copy(ImportMode::Copy).await?;
+                move_or_copy(ImportMode::Copy).await?
             }
         }
-        ImportMode::Copy | ImportMode::Move => {
-            move_or_copy(import_mode).await?;
-        }
+        ImportMode::Copy | ImportMode::Move => move_or_copy(import_mode).await?,
         ImportMode::Symlink => {
             let from_path = std::path::absolute(from_path)?;
             if ForestCar::is_valid(&EitherMmapOrRandomAccessFile::open(&from_path)?) {
@@ -220,8 +260,10 @@ pub async fn import_chain_as_forest_car(
                     from_path.display(),
                     forest_car_db_path.display()
                 );
+                snapshot_progress_tracker.not_required();
                 std::os::unix::fs::symlink(from_path, &forest_car_db_path)
                     .context("Error creating symlink")?;
+                OriginalSnapshot::Kept
             } else {
                 bail!("Snapshot file must be a valid forest.car.zst file");
             }
@@ -233,14 +275,17 @@ pub async fn import_chain_as_forest_car(
                     from_path.display(),
                     forest_car_db_path.display()
                 );
+                snapshot_progress_tracker.not_required();
                 std::fs::hard_link(from_path, &forest_car_db_path)
                     .context("Error creating hardlink")?;
+                OriginalSnapshot::Kept
             } else {
                 bail!("Snapshot file must be a valid forest.car.zst file");
             }
         }
     };
 
+    snapshot_progress_tracker.set_stage(STAGE_INDEXING);
     let forest_car = ForestCar::try_from(forest_car_db_path.as_path())?;
 
     if let Some(f3_cid) = forest_car.metadata().as_ref().and_then(|m| m.f3_data) {
@@ -277,6 +322,30 @@ pub async fn import_chain_as_forest_car(
     }
 
     let ts = forest_car.heaviest_tipset()?;
+
+    snapshot_progress_tracker.set_stage(STAGE_VERIFYING);
+    if let Err(e) = verify_snapshot_genesis(&forest_car, &ts, chain_config) {
+        drop(forest_car);
+        // Do not leave an unusable snapshot behind, it would be loaded on the next start. A moved
+        // snapshot belongs to the user and is moved back instead.
+        let cleanup = if original == OriginalSnapshot::Moved {
+            move_or_copy_file(&forest_car_db_path, from_path, ImportMode::Move)
+        } else {
+            std::fs::remove_file(&forest_car_db_path).context("Error removing snapshot")
+        };
+        if let Err(cleanup_err) = cleanup {
+            warn!(
+                "Failed to clean up {}: {cleanup_err:#}",
+                forest_car_db_path.display()
+            );
+        }
+        return Err(e);
+    }
+    if original == OriginalSnapshot::Remove {
+        std::fs::remove_file(from_path).context("Error removing original file")?;
+    }
+    snapshot_progress_tracker.completed();
+
     info!(
         "Imported snapshot in: {}s, heaviest tipset epoch: {}, key: {}",
         stopwatch.elapsed().as_secs(),
@@ -287,6 +356,28 @@ pub async fn import_chain_as_forest_car(
     Ok((forest_car_db_path, ts))
 }
 
+/// Verifies that the head of the snapshot links back to the genesis block of the current network,
+/// using the known checkpoints to short-circuit the search.
+fn verify_snapshot_genesis(
+    store: &impl fvm_ipld_blockstore::Blockstore,
+    head: &Tipset,
+    chain_config: &ChainConfig,
+) -> anyhow::Result<()> {
+    // Devnets do not have a well-known genesis.
+    let Some(expected_genesis_cid) = &chain_config.genesis_cid else {
+        return Ok(());
+    };
+    let genesis = head
+        .genesis(store)
+        .context("failed to link the snapshot head to a genesis block")?;
+    ensure!(
+        &genesis.cid().to_string() == expected_genesis_cid,
+        "snapshot genesis {} does not match the network genesis {expected_genesis_cid}",
+        genesis.cid()
+    );
+    Ok(())
+}
+
 fn move_or_copy_file(from: &Path, to: &Path, import_mode: ImportMode) -> anyhow::Result<()> {
     match import_mode {
         ImportMode::Move => {
@@ -350,6 +441,8 @@ where
     );
     tracing::trace!("Indexing tipset @{}: {}", epoch, &tsk);
     state_manager.chain_store().put_tipset_key(&tsk)?;
+    state_manager.chain_store().put_message_inclusions(ts)?;
+    state_manager.chain_store().put_address_messages(ts)?;
 
     Ok(())
 }
@@ -373,7 +466,8 @@ impl std::fmt::Display for RangeSpec {
 ///
 /// Similarly, to support the Ethereum RPC API, another column has been introduced to map:
 /// - [`struct@EthHash`] -> [`TipsetKey`],
-/// - [`struct@EthHash`] -> Delegated message [`Cid`].
+/// - [`struct@EthHash`] -> Delegated message [`Cid`],
+/// - Message [`Cid`] -> Inclusion [`TipsetKey`].
 ///
 /// This function traverses the chain store and populates these columns accordingly.
 pub async fn backfill_db<DB>(
@@ -518,6 +612,59 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn import_snapshot_genesis_mismatch() {
+        for import_mode in [ImportMode::Auto, ImportMode::Copy, ImportMode::Move] {
+            import_snapshot_with_wrong_genesis("test-snapshots/chain4.car", import_mode)
+                .await
+                .unwrap();
+        }
+        for import_mode in [
+            ImportMode::Auto,
+            ImportMode::Copy,
+            ImportMode::Move,
+            ImportMode::Symlink,
+            ImportMode::Hardlink,
+        ] {
+            import_snapshot_with_wrong_genesis("test-snapshots/chain4.forest.car.zst", import_mode)
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Imports a snapshot whose genesis does not match the network, the import must fail without
+    /// leaving a snapshot in the database directory nor touching the original file.
+    async fn import_snapshot_with_wrong_genesis(
+        file_path: &str,
+        import_mode: ImportMode,
+    ) -> anyhow::Result<()> {
+        let temp_file = tempfile::Builder::new().tempfile()?;
+        fs::copy(Path::new(file_path), temp_file.path())?;
+        let original = fs::read(temp_file.path())?;
+
+        let temp_db_dir = tempfile::Builder::new().tempdir()?;
+        let chain_config = ChainConfig {
+            genesis_cid: Some(crate::networks::mainnet::GENESIS_CID.to_string()),
+            ..ChainConfig::devnet()
+        };
+
+        import_chain_as_forest_car(
+            temp_file.path(),
+            temp_db_dir.path(),
+            import_mode,
+            "http://127.0.0.1:2345/rpc/v1".parse().unwrap(),
+            Path::new("test"),
+            &chain_config,
+            &SnapshotProgressTracker::default(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(fs::read(temp_file.path())?, original);
+        assert!(fs::read_dir(temp_db_dir.path())?.next().is_none());
+        Ok(())
+    }
+
     async fn import_snapshot_from_file(
         file_path: &str,
         import_mode: ImportMode,
@@ -528,6 +675,7 @@ mod test {
         let file_path = temp_file.path();
 
         let temp_db_dir = tempfile::Builder::new().tempdir()?;
+        let snapshot_progress_tracker = SnapshotProgressTracker::default();
 
         let (path, ts) = import_chain_as_forest_car(
             file_path,
@@ -536,9 +684,11 @@ mod test {
             "http://127.0.0.1:2345/rpc/v1".parse().unwrap(),
             Path::new("test"),
             &ChainConfig::devnet(),
-            &SnapshotProgressTracker::default(),
+            &snapshot_progress_tracker,
         )
         .await?;
+        // Local snapshots do not need to be downloaded.
+        assert!(snapshot_progress_tracker.state().is_not_required());
         match import_mode {
             ImportMode::Symlink => {
                 assert_eq!(
@@ -9,6 +9,8 @@ pub mod main;
 use crate::blocks::Tipset;
 use crate::chain::HeadChange;
 use crate::chain::index::ResolveNullTipset;
+use crate::chain::scheduler::EpochScheduler;
+use crate::chain::warm_restart::WarmRestartHints;
 use crate::chain_sync::network_context::SyncNetworkContext;
 use crate::chain_sync::{ChainFollower, SyncStatus};
 use crate::cli_shared::snapshot;
@@ -21,16 +23,20 @@ use crate::daemon::{
     db_util::import_chain_as_forest_car,
 };
 use crate::db::gc::SnapshotGarbageCollector;
-use crate::db::ttl::EthMappingCollector;
-use crate::libp2p::{Libp2pService, PeerManager};
+use crate::db::ttl::{EthMappingCollector, ReceiptCollector};
+use crate::libp2p::{Libp2pService, NetworkMessage, PeerManager};
 use crate::message_pool::{MessagePool, MpoolConfig, MpoolRpcProvider};
 use crate::networks::{self, ChainConfig};
 use crate::rpc::RPCState;
 use crate::rpc::eth::filter::EthEventHandler;
-use crate::rpc::start_rpc;
+use crate::rpc::{RpcServerConfig, start_rpc};
+use crate::shim::address::{Address, StrictAddress};
 use crate::shim::clock::ChainEpoch;
 use crate::shim::state_tree::StateTree;
 use crate::shim::version::NetworkVersion;
+use crate::state_manager::actor_code;
+use crate::state_manager::balance_watcher::BalanceWatcher;
+use crate::state_manager::event_sink::EventSink;
 use crate::utils;
 use crate::utils::misc::env::is_env_truthy;
 use crate::utils::{proofs_api::ensure_proof_params_downloaded, version::FOREST_VERSION_STRING};
@@ -47,7 +53,7 @@ use tokio::{
         ctrl_c,
         unix::{SignalKind, signal},
     },
-    sync::mpsc,
+    sync::{broadcast::error::RecvError, mpsc},
     task::JoinSet,
 };
 use tracing::{debug, info, warn};
@@ -199,9 +205,16 @@ async fn maybe_start_metrics_service(
         let prometheus_listener = TcpListener::bind(config.client.metrics_address)
             .await
             .with_context(|| format!("could not bind to {}", config.client.metrics_address))?;
+        let tls = config
+            .client
+            .tls
+            .as_ref()
+            .map(crate::utils::net::tls_acceptor)
+            .transpose()?;
         info!(
-            "Prometheus server started at {}",
-            config.client.metrics_address
+            "Prometheus server started at {}{}",
+            config.client.metrics_address,
+            if tls.is_some() { " over TLS" } else { "" }
         );
         let db_directory = crate::db::db_engine::db_root(&chain_path(config))?;
         let db = ctx.db.writer().clone();
@@ -239,6 +252,7 @@ async fn maybe_start_metrics_service(
             async {
                 crate::metrics::init_prometheus(
                     prometheus_listener,
+                    tls,
                     db_directory,
                     db,
                     chain_config,
@@ -270,7 +284,7 @@ async fn create_p2p_service(
     ctx: &AppContext,
 ) -> anyhow::Result<Libp2pService<DbType>> {
     // if bootstrap peers are not set, set them
-    if config.network.bootstrap_peers.is_empty() {
+    if config.network.bootstrap_peers.is_empty() && !config.network.override_bootstrap_peers {
         config.network.bootstrap_peers = ctx.state_manager.chain_config().bootstrap_peers.clone();
     }
 
@@ -284,6 +298,7 @@ async fn create_p2p_service(
         ctx.net_keypair.clone(),
         config.chain.genesis_name(),
         *ctx.state_manager.chain_store().genesis_block_header().cid(),
+        chain_path(config),
     )
     .await?;
     Ok(p2p_service)
@@ -292,14 +307,17 @@ async fn create_p2p_service(
 fn create_mpool(
     services: &mut JoinSet<anyhow::Result<()>>,
     p2p_service: &Libp2pService<DbType>,
+    config: &Config,
     ctx: &AppContext,
 ) -> anyhow::Result<Arc<MessagePool<MpoolRpcProvider<DbType>>>> {
     let publisher = ctx.state_manager.chain_store().publisher();
     let provider = MpoolRpcProvider::new(publisher.clone(), ctx.state_manager.clone());
+    let mut mpool_config = MpoolConfig::load_config(ctx.db.writer().as_ref())?;
+    mpool_config.block_probability = config.block_probability.clone();
     Ok(MessagePool::new(
         provider,
         p2p_service.network_sender().clone(),
-        MpoolConfig::load_config(ctx.db.writer().as_ref())?,
+        mpool_config,
         ctx.state_manager.chain_config().clone(),
         services,
     )
@@ -308,6 +326,7 @@ fn create_mpool(
 
 fn create_chain_follower(
     opts: &CliOpts,
+    config: &Config,
     p2p_service: &Libp2pService<DbType>,
     mpool: Arc<MessagePool<MpoolRpcProvider<DbType>>>,
     ctx: &AppContext,
@@ -322,7 +341,9 @@ fn create_chain_follower(
         p2p_service.network_receiver(),
         opts.stateless,
         mpool,
-    );
+    )
+    .with_sync_checkpoint(ctx.db.clone())
+    .with_persist_batch_epochs(config.sync.persist_batch_epochs);
     Ok(chain_follower)
 }
 
@@ -366,6 +387,61 @@ async fn maybe_start_health_check_service(
     Ok(())
 }
 
+async fn maybe_start_gateway_service(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    config: &Config,
+    ctx: &AppContext,
+) -> anyhow::Result<()> {
+    if let Some(gateway_address) = config.client.gateway_address {
+        info!("Gateway endpoint will listen at {gateway_address}");
+        let listener = TcpListener::bind(gateway_address)
+            .await
+            .with_context(|| format!("could not bind to {gateway_address}"))?;
+        let db = ctx.db.clone();
+        services.spawn(async move {
+            crate::gateway::init_gateway_server(db, listener)
+                .await
+                .context("Failed to initiate gateway server")
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "graphql")]
+async fn maybe_start_graphql_service(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    config: &Config,
+    ctx: &AppContext,
+) -> anyhow::Result<()> {
+    if let Some(graphql_address) = config.client.graphql_address {
+        info!("GraphQL endpoint will listen at {graphql_address}");
+        let listener = TcpListener::bind(graphql_address)
+            .await
+            .with_context(|| format!("could not bind to {graphql_address}"))?;
+        let state_manager = ctx.state_manager.clone();
+        services.spawn(async move {
+            crate::graphql::init_graphql_server(state_manager, listener)
+                .await
+                .context("Failed to initiate GraphQL server")
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "graphql"))]
+async fn maybe_start_graphql_service(
+    _services: &mut JoinSet<anyhow::Result<()>>,
+    config: &Config,
+    _ctx: &AppContext,
+) -> anyhow::Result<()> {
+    if config.client.graphql_address.is_some() {
+        warn!(
+            "The GraphQL endpoint is configured, but Forest was built without the `graphql` feature"
+        );
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn maybe_start_rpc_service(
     services: &mut JoinSet<anyhow::Result<()>>,
@@ -384,9 +460,46 @@ fn maybe_start_rpc_service(
             .rpc_filter_list
             .as_ref()
             .map(|path| crate::rpc::FilterList::new_from_file(path))
+            .transpose()?
+            .unwrap_or_default();
+        let upstream = config
+            .client
+            .rpc_upstream
+            .as_deref()
+            .map(|url| {
+                anyhow::Ok(crate::rpc::RpcUpstream::new(
+                    url.parse()
+                        .with_context(|| format!("invalid RPC upstream URL: {url}"))?,
+                    config.client.rpc_upstream_methods.iter().cloned(),
+                ))
+            })
+            .transpose()?;
+        let origin_policy = crate::rpc::OriginPolicy::new(&config.client.rpc_allowed_origins)?;
+        let limits = config.client.rpc_limits.clone();
+        let tls = config
+            .client
+            .tls
+            .as_ref()
+            .map(crate::utils::net::tls_acceptor)
             .transpose()?;
-        info!("JSON-RPC endpoint will listen at {rpc_address}");
-        let eth_event_handler = Arc::new(EthEventHandler::from_config(&config.events));
+        info!(
+            "JSON-RPC endpoint will listen at {rpc_address}{}",
+            if tls.is_some() { " over TLS" } else { "" }
+        );
+        let rpc_socket = config
+            .client
+            .rpc_socket
+            .as_deref()
+            .map(|path| {
+                info!("JSON-RPC endpoint will listen at {}", path.display());
+                crate::rpc::bind_rpc_socket(path)
+            })
+            .transpose()?;
+        let eth_event_handler = Arc::new(
+            EthEventHandler::from_config(&config.events)
+                .with_persisted_filters(ctx.db.clone(), ctx.state_manager.chain_store()),
+        );
+        services.spawn(eth_event_handler.clone().maintain_filters());
         if is_env_truthy("FOREST_JWT_DISABLE_EXP_VALIDATION") {
             warn!(
                 "JWT expiration validation is disabled; this significantly weakens security and should only be used in tightly controlled environments"
@@ -425,7 +538,14 @@ fn maybe_start_rpc_service(
                     },
                     rpc_listener,
                     rpc_stop_handle,
-                    filter_list,
+                    RpcServerConfig {
+                        tls,
+                        socket: rpc_socket,
+                        filter_list,
+                        upstream,
+                        origin_policy,
+                        limits,
+                    },
                 )
                 .await
             }
@@ -500,6 +620,7 @@ fn maybe_start_indexer_service(
     {
         let mut receiver = ctx.state_manager.chain_store().publisher().subscribe();
         let chain_store = ctx.state_manager.chain_store().clone();
+        let retention_epochs = config.chain_indexer.gc_retention_epochs;
         services.spawn(async move {
             tracing::info!("Starting indexer service");
 
@@ -511,6 +632,14 @@ fn maybe_start_indexer_service(
                 tracing::debug!("Indexing tipset {}", ts.key());
 
                 chain_store.put_tipset_key(ts.key())?;
+                chain_store.put_message_inclusions(&ts)?;
+                chain_store.put_address_messages(&ts)?;
+                if let Some(retention_epochs) = retention_epochs {
+                    let until = ts.epoch() - ChainEpoch::from(retention_epochs);
+                    chain_store.prune_message_inclusions(until)?;
+                    chain_store.prune_actor_eth_addresses(until)?;
+                    chain_store.prune_address_messages(&ts, until)?;
+                }
 
                 let delegated_messages =
                     chain_store.headers_delegated_messages(ts.block_headers().iter())?;
@@ -536,6 +665,156 @@ fn maybe_start_indexer_service(
     }
 }
 
+/// Fetches the blocks quarantined by the database again from the peers, via bitswap, see
+/// [`ParityDb::subscribe_quarantined_blocks`](crate::db::parity_db::ParityDb::subscribe_quarantined_blocks).
+fn start_quarantined_blocks_refetch(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    p2p_service: &Libp2pService<DbType>,
+    ctx: &AppContext,
+) {
+    let mut receiver = ctx.db.writer().subscribe_quarantined_blocks();
+    let network_send = p2p_service.network_sender();
+    services.spawn(async move {
+        loop {
+            let cid = match receiver.recv().await {
+                Ok(cid) => cid,
+                Err(RecvError::Lagged(n)) => {
+                    warn!("Missed {n} quarantined blocks, they are fetched again when needed");
+                    continue;
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            };
+            // The block is written to the database once it is received.
+            let (tx, _rx) = flume::bounded(1);
+            network_send
+                .send_async(NetworkMessage::BitswapRequest {
+                    cid,
+                    response_channel: tx,
+                })
+                .await?;
+        }
+    });
+}
+
+/// Prunes the receipts and events older than their retention, see [`ReceiptCollector`].
+fn maybe_start_receipt_collector(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    opts: &CliOpts,
+    config: &Config,
+    ctx: &AppContext,
+) {
+    if opts.stateless {
+        return;
+    }
+    let mut collector = ReceiptCollector::new(
+        ctx.state_manager.chain_store().clone(),
+        ctx.db.writer().clone(),
+        &config.parity_db,
+        config.sync.archive,
+    );
+    if collector.is_enabled() {
+        services.spawn(async move {
+            tracing::info!("Starting collector for receipts and events");
+            collector.run().await
+        });
+    } else if config.sync.archive {
+        tracing::info!("Collector for receipts and events is disabled in archive mode");
+    }
+}
+
+/// Logs the migrations of the built-in actors, and the code changes of the actors listed in
+/// `FOREST_WATCHED_ACTORS`, as they are observed in new heads.
+fn maybe_start_actor_code_watcher(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    opts: &CliOpts,
+    ctx: &AppContext,
+) -> anyhow::Result<()> {
+    if opts.stateless {
+        return Ok(());
+    }
+    let watched = match std::env::var("FOREST_WATCHED_ACTORS") {
+        Ok(addresses) => addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                address
+                    .parse::<StrictAddress>()
+                    .map(Address::from)
+                    .with_context(|| format!("invalid address {address} in FOREST_WATCHED_ACTORS"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?,
+        Err(_) => vec![],
+    };
+    let mut receiver = ctx.state_manager.chain_store().publisher().subscribe();
+    let db = ctx.db.clone();
+    services.spawn(async move {
+        loop {
+            let ts = match receiver.recv().await {
+                Ok(HeadChange::Apply(ts)) => ts,
+                // Missed heads are not logged, this is only best-effort monitoring.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            };
+            match actor_code::tipset_changes(&db, &ts, &watched) {
+                Ok(changes) => {
+                    for change in changes {
+                        info!("Observed actor code change: {change}");
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to get the actor code changes of tipset {}: {e}",
+                        ts.key()
+                    );
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Exports the balances and nonces of the addresses of the balance watcher configuration as
+/// metrics, and alerts their low balances.
+fn maybe_start_balance_watcher(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    opts: &CliOpts,
+    config: &Config,
+    ctx: &AppContext,
+) -> anyhow::Result<()> {
+    if opts.stateless {
+        return Ok(());
+    }
+    if let Some(watcher) = BalanceWatcher::new(&config.balance_watcher)? {
+        let state_manager = ctx.state_manager.clone();
+        services.spawn(async move {
+            watcher.run(state_manager).await;
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+/// Posts the chain events to the endpoints of the event sink configuration.
+fn maybe_start_event_sink(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    opts: &CliOpts,
+    config: &Config,
+    ctx: &AppContext,
+) -> anyhow::Result<()> {
+    if opts.stateless {
+        return Ok(());
+    }
+    if let Some(sink) = EventSink::new(&config.event_sink)? {
+        let state_manager = ctx.state_manager.clone();
+        services.spawn(async move {
+            sink.run(state_manager).await;
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
 /// Starts daemon process
 pub(super) async fn start(
     start_time: chrono::DateTime<chrono::Utc>,
@@ -558,12 +837,12 @@ pub(super) async fn start(
             async move { snap_gc.event_loop().await }
         });
     }
-    // GC shouldn't run periodically if the node is stateless or if the user has disabled it.
-    if !opts.no_gc && !opts.stateless {
-        tokio::task::spawn({
-            let snap_gc = snap_gc.clone();
-            async move { snap_gc.scheduler_loop().await }
-        });
+    // The disk space monitor outlives the restarts of the services by the GC.
+    if config.disk_space.check_interval_secs > 0 {
+        tokio::task::spawn(crate::db::disk_space::run_monitor(
+            chain_path(&config),
+            config.disk_space.clone(),
+        ));
     }
     loop {
         let (rpc_stop_handle, rpc_server_handle) = jsonrpsee::server::stop_channel();
@@ -575,10 +854,14 @@ pub(super) async fn start(
                 }
                 snap_gc.cleanup_before_reboot().await;
             }
-            result = start_services(start_time, &opts, config.clone(), shutdown_send.clone(), rpc_stop_handle, |ctx, sync_status| {
+            result = start_services(start_time, &opts, config.clone(), shutdown_send.clone(), rpc_stop_handle, |ctx, sync_status, scheduler| {
                 snap_gc.set_db(ctx.db.clone());
                 snap_gc.set_sync_status(sync_status);
                 snap_gc.set_car_db_head_epoch(ctx.db.heaviest_tipset().map(|ts|ts.epoch()).unwrap_or_default());
+                // GC shouldn't run periodically if the node is stateless or if the user has disabled it.
+                if !opts.no_gc && !opts.stateless {
+                    snap_gc.schedule(scheduler);
+                }
             }) => {
                 break result
             }
@@ -592,7 +875,7 @@ pub(super) async fn start_services(
     mut config: Config,
     shutdown_send: mpsc::Sender<()>,
     rpc_stop_handle: jsonrpsee::server::StopHandle,
-    on_app_context_and_db_initialized: impl FnOnce(&AppContext, SyncStatus),
+    on_app_context_and_db_initialized: impl FnOnce(&AppContext, SyncStatus, &mut EpochScheduler),
 ) -> anyhow::Result<()> {
     // Cleanup the collector prometheus metrics registry on start
     crate::metrics::reset_collector_registry();
@@ -611,8 +894,9 @@ pub(super) async fn start_services(
         tracing::warn!("error in maybe_rewind_heaviest_tipset: {e}");
     }
     let p2p_service = create_p2p_service(&mut services, &mut config, &ctx).await?;
-    let mpool = create_mpool(&mut services, &p2p_service, &ctx)?;
-    let chain_follower = create_chain_follower(opts, &p2p_service, mpool.clone(), &ctx)?;
+    start_quarantined_blocks_refetch(&mut services, &p2p_service, &ctx);
+    let mpool = create_mpool(&mut services, &p2p_service, &config, &ctx)?;
+    let chain_follower = create_chain_follower(opts, &config, &p2p_service, mpool.clone(), &ctx)?;
 
     maybe_start_rpc_service(
         &mut services,
@@ -631,17 +915,29 @@ pub(super) async fn start_services(
         services.shutdown().await;
         return Ok(());
     }
-    on_app_context_and_db_initialized(&ctx, chain_follower.sync_status.clone());
+    let mut scheduler = EpochScheduler::new();
+    on_app_context_and_db_initialized(&ctx, chain_follower.sync_status.clone(), &mut scheduler);
     warmup_in_background(&ctx);
     ctx.state_manager.populate_cache();
+    apply_warm_restart_hints(&ctx);
     maybe_start_metrics_service(&mut services, &config, &ctx).await?;
     maybe_start_f3_service(opts, &config, &ctx)?;
     maybe_start_health_check_service(&mut services, &config, &p2p_service, &chain_follower, &ctx)
         .await?;
+    maybe_start_gateway_service(&mut services, &config, &ctx).await?;
+    maybe_start_graphql_service(&mut services, &config, &ctx).await?;
     maybe_start_indexer_service(&mut services, opts, &config, &ctx);
+    maybe_start_receipt_collector(&mut services, opts, &config, &ctx);
+    maybe_start_actor_code_watcher(&mut services, opts, &ctx)?;
+    maybe_start_balance_watcher(&mut services, opts, &config, &ctx)?;
+    maybe_start_event_sink(&mut services, opts, &config, &ctx)?;
     if !opts.stateless {
         ensure_proof_params_downloaded().await?;
     }
+    mpool.schedule_republish(&mut scheduler);
+    p2p_service.schedule_peerstore_save(&mut scheduler);
+    schedule_warm_restart_hints_save(&ctx, &mut scheduler);
+    services.spawn(scheduler.run(ctx.state_manager.chain_store().publisher().subscribe()));
     services.spawn(p2p_service.run());
     start_chain_follower_service(&mut services, chain_follower);
     // blocking until any of the services returns an error,
@@ -651,6 +947,43 @@ pub(super) async fn start_services(
         .map(|_| {})
 }
 
+/// Marks the recently validated blocks as validated again and warms the tipset cache up, from the
+/// hints saved before the node stopped.
+fn apply_warm_restart_hints(ctx: &AppContext) {
+    match WarmRestartHints::load(ctx.db.as_ref()) {
+        Ok(Some(hints)) => {
+            if hints.apply(ctx.chain_store()) {
+                tracing::info!(
+                    "Applied the warm restart hints saved at epoch {}",
+                    hints.head_epoch
+                );
+            } else {
+                tracing::info!(
+                    "Ignoring the warm restart hints saved at epoch {}, off the current chain",
+                    hints.head_epoch
+                );
+            }
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Ignoring the warm restart hints: {e}"),
+    }
+}
+
+/// Saves the warm restart hints on every new head, so that they are at most an epoch behind
+/// whenever the node stops, be it gracefully or not.
+fn schedule_warm_restart_hints_save(ctx: &AppContext, scheduler: &mut EpochScheduler) {
+    let cs = ctx.chain_store().clone();
+    let db = ctx.db.clone();
+    scheduler.every("warm restart hints save", 1, move |_| {
+        let (cs, db) = (cs.clone(), db.clone());
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = WarmRestartHints::collect(&cs).save(db.as_ref()) {
+                tracing::warn!("Failed to save the warm restart hints: {e}");
+            }
+        });
+    });
+}
+
 fn warmup_in_background(ctx: &AppContext) {
     // Populate `tipset_by_height` cache
     let cs = ctx.chain_store().clone();
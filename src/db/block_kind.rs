@@ -0,0 +1,96 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Kinds of the IPLD blocks written to the blockstore. The [`Blockstore`](fvm_ipld_blockstore::Blockstore)
+//! interface only carries the CIDs and the data of the blocks, so the writers that know what they
+//! store, like the state manager for receipts and events, tag their writes with
+//! [`with_block_kind`]. Stores that don't distinguish block kinds ignore the tag, while
+//! [`ParityDb`](super::parity_db::ParityDb) writes the tagged blocks to dedicated columns, which
+//! can be pruned independently of the state. Readers may tag their reads the same way, for
+//! [`ParityDb`](super::parity_db::ParityDb) to look the blocks up in the column of their kind
+//! first.
+//!
+//! The blocks of the receipts and events are referenced once per tipset, for the tipsets to be
+//! pruned independently of each other. The tipsets whose receipts were written are marked with a
+//! [`receipts_marker`] block, and their receipts and events are written untagged when the tipsets
+//! are computed again.
+
+use std::cell::Cell;
+
+use cid::Cid;
+
+use crate::blocks::TipsetKey;
+use crate::utils::multihash::prelude::*;
+use fvm_ipld_encoding::DAG_CBOR;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    /// Messages, and the `AMTs` and `TxMeta` that link them to the block headers
+    Message,
+    /// Receipts `AMTs` of the executed tipsets
+    Receipt,
+    /// Events `AMTs` of the executed messages
+    Event,
+}
+
+thread_local! {
+    static BLOCK_KIND: Cell<Option<BlockKind>> = const { Cell::new(None) };
+}
+
+/// Runs `f`, tagging the blocks it writes or reads on the current thread with `kind`.
+pub fn with_block_kind<T>(kind: BlockKind, f: impl FnOnce() -> T) -> T {
+    with_optional_block_kind(Some(kind), f)
+}
+
+/// Runs `f`, tagging the blocks it writes or reads on the current thread with `kind`, or leaving
+/// them untagged if `kind` is unset.
+pub fn with_optional_block_kind<T>(kind: Option<BlockKind>, f: impl FnOnce() -> T) -> T {
+    // Restores the previous kind, even if `f` panics.
+    struct Restore(Option<BlockKind>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            BLOCK_KIND.set(self.0);
+        }
+    }
+
+    let _restore = Restore(BLOCK_KIND.replace(kind));
+    f()
+}
+
+/// Returns the kind of the blocks written on the current thread, if any.
+pub fn current_block_kind() -> Option<BlockKind> {
+    BLOCK_KIND.get()
+}
+
+/// Returns the CID and the data of the block marking that the receipts and events of the tipset
+/// were written. It is written along with them as a [`BlockKind::Receipt`] block, and pruned
+/// with them.
+pub fn receipts_marker(tipset_key: &TipsetKey) -> anyhow::Result<(Cid, Vec<u8>)> {
+    let data = fvm_ipld_encoding::to_vec(&("receipts", tipset_key))?;
+    Ok((
+        Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&data)),
+        data,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_block_kinds() {
+        assert_eq!(current_block_kind(), None);
+        with_block_kind(BlockKind::Receipt, || {
+            assert_eq!(current_block_kind(), Some(BlockKind::Receipt));
+            with_block_kind(BlockKind::Event, || {
+                assert_eq!(current_block_kind(), Some(BlockKind::Event));
+            });
+            assert_eq!(current_block_kind(), Some(BlockKind::Receipt));
+        });
+        assert_eq!(current_block_kind(), None);
+        with_block_kind(BlockKind::Receipt, || {
+            with_optional_block_kind(None, || assert_eq!(current_block_kind(), None));
+        });
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+
+/// A blockstore keeping its writes in memory, on top of a store that is only read from. The
+/// writes are discarded when it is dropped, it is used to compute states that must not be
+/// persisted, e.g. a state with overridden actors.
+pub struct BlockstoreWithMemoryOverlay<DB> {
+    inner: DB,
+    overlay: MemoryBlockstore,
+}
+
+impl<DB> BlockstoreWithMemoryOverlay<DB> {
+    pub fn new(inner: DB) -> Self {
+        Self {
+            inner,
+            overlay: MemoryBlockstore::default(),
+        }
+    }
+}
+
+impl<DB: Blockstore> Blockstore for BlockstoreWithMemoryOverlay<DB> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.overlay.get(k)? {
+            Some(v) => Ok(Some(v)),
+            None => self.inner.get(k),
+        }
+    }
+
+    fn has(&self, k: &Cid) -> anyhow::Result<bool> {
+        Ok(self.overlay.has(k)? || self.inner.has(k)?)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.overlay.put_keyed(k, block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::utils::db::CborStoreExt as _;
+    use std::sync::Arc;
+
+    #[test]
+    fn writes_stay_in_memory() {
+        let db = Arc::new(MemoryDB::default());
+        let persisted = db.put_cbor_default(&"persisted").unwrap();
+
+        let overlay = BlockstoreWithMemoryOverlay::new(db.clone());
+        let written = overlay.put_cbor_default(&"written").unwrap();
+        assert!(overlay.has(&persisted).unwrap());
+        assert!(overlay.has(&written).unwrap());
+        assert!(!db.has(&written).unwrap());
+
+        drop(overlay);
+        assert!(!db.has(&written).unwrap());
+    }
+}
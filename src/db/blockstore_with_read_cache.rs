@@ -3,6 +3,10 @@
 
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
+use parking_lot::Mutex;
+use std::borrow::Cow;
+use std::hash::BuildHasher as _;
+use std::num::NonZeroUsize;
 use std::sync::{
     Arc,
     atomic::{self, AtomicUsize},
@@ -14,6 +18,9 @@ pub trait BlockstoreReadCache {
     fn get(&self, k: &Cid) -> Option<Vec<u8>>;
 
     fn put(&self, k: Cid, block: Vec<u8>);
+
+    /// Removes a block, e.g. once it is deleted from the backing store.
+    fn remove(&self, k: &Cid);
 }
 
 pub type LruBlockstoreReadCache = SizeTrackingLruCache<get_size::CidWrapper, Vec<u8>>;
@@ -26,6 +33,130 @@ impl BlockstoreReadCache for SizeTrackingLruCache<get_size::CidWrapper, Vec<u8>>
     fn put(&self, k: Cid, block: Vec<u8>) {
         self.push(k.into(), block);
     }
+
+    fn remove(&self, k: &Cid) {
+        self.cache().write().remove(&get_size::CidWrapper(*k));
+    }
+}
+
+/// LRU cache with a `TinyLFU` admission policy. When the cache is full, a new block is only
+/// admitted if it has been requested more often than the block it would evict, which keeps
+/// frequently read blocks, e.g. the HAMT nodes read by every message execution, from being
+/// flushed out by one-off reads.
+pub struct TinyLfuBlockstoreReadCache {
+    cache: LruBlockstoreReadCache,
+    sketch: Mutex<FrequencySketch>,
+}
+
+impl TinyLfuBlockstoreReadCache {
+    pub fn new_with_metrics(cache_name: Cow<'static, str>, capacity: NonZeroUsize) -> Self {
+        Self {
+            cache: LruBlockstoreReadCache::new_with_metrics(cache_name, capacity),
+            sketch: Mutex::new(FrequencySketch::new(capacity)),
+        }
+    }
+
+    pub fn new_without_metrics_registry(
+        cache_name: Cow<'static, str>,
+        capacity: NonZeroUsize,
+    ) -> Self {
+        Self {
+            cache: LruBlockstoreReadCache::new_without_metrics_registry(cache_name, capacity),
+            sketch: Mutex::new(FrequencySketch::new(capacity)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn evictions(&self) -> u64 {
+        self.cache.evictions()
+    }
+}
+
+impl BlockstoreReadCache for TinyLfuBlockstoreReadCache {
+    fn get(&self, k: &Cid) -> Option<Vec<u8>> {
+        self.sketch.lock().increment(k);
+        self.cache.get(k)
+    }
+
+    fn put(&self, k: Cid, block: Vec<u8>) {
+        let key = get_size::CidWrapper(k);
+        {
+            let cache = self.cache.cache().read();
+            if !cache.contains_key(&key)
+                && cache.len() >= cache.capacity()
+                && let Some((victim, _)) = cache.iter().next()
+            {
+                let sketch = self.sketch.lock();
+                if sketch.frequency(&k) <= sketch.frequency(&victim.0) {
+                    return;
+                }
+            }
+        }
+        // Pushed through the cache so that the eviction is counted
+        self.cache.push(key, block);
+    }
+
+    fn remove(&self, k: &Cid) {
+        BlockstoreReadCache::remove(&self.cache, k);
+    }
+}
+
+/// Count-min sketch with 4-bit counters that estimates how often keys are requested. Counters are
+/// halved periodically so that the estimates favor recent requests.
+struct FrequencySketch {
+    counters: Vec<u8>,
+    mask: usize,
+    additions: usize,
+    sample_size: usize,
+    hasher: ahash::RandomState,
+}
+
+impl FrequencySketch {
+    const DEPTH: usize = 4;
+    const MAX_COUNT: u8 = 15;
+
+    fn new(capacity: NonZeroUsize) -> Self {
+        let width = capacity.get().next_power_of_two().max(1024);
+        Self {
+            counters: vec![0; width],
+            mask: width - 1,
+            additions: 0,
+            sample_size: capacity.get().saturating_mul(10),
+            hasher: ahash::RandomState::new(),
+        }
+    }
+
+    fn indexes(&self, k: &Cid) -> [usize; Self::DEPTH] {
+        let hash = self.hasher.hash_one(k);
+        let (h1, h2) = (hash as usize, (hash >> 32) as usize | 1);
+        std::array::from_fn(|i| h1.wrapping_add(i.wrapping_mul(h2)) & self.mask)
+    }
+
+    fn frequency(&self, k: &Cid) -> u8 {
+        self.indexes(k)
+            .into_iter()
+            .filter_map(|i| self.counters.get(i).copied())
+            .min()
+            .unwrap_or_default()
+    }
+
+    fn increment(&mut self, k: &Cid) {
+        for i in self.indexes(k) {
+            if let Some(counter) = self.counters.get_mut(i) {
+                *counter = counter.saturating_add(1).min(Self::MAX_COUNT);
+            }
+        }
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            for counter in self.counters.iter_mut() {
+                *counter /= 2;
+            }
+            self.additions /= 2;
+        }
+    }
 }
 
 impl<T: BlockstoreReadCache> BlockstoreReadCache for Arc<T> {
@@ -36,6 +167,10 @@ impl<T: BlockstoreReadCache> BlockstoreReadCache for Arc<T> {
     fn put(&self, k: Cid, block: Vec<u8>) {
         self.as_ref().put(k, block)
     }
+
+    fn remove(&self, k: &Cid) {
+        self.as_ref().remove(k)
+    }
 }
 
 pub trait BlockstoreReadCacheStats {
@@ -113,6 +248,15 @@ impl<DB: Blockstore, CACHE: BlockstoreReadCache, STATS: BlockstoreReadCacheStats
     fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
         self.inner.put_keyed(k, block)
     }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        self.inner.put_many_keyed(blocks)
+    }
 }
 
 #[cfg(test)]
@@ -179,4 +323,49 @@ mod tests {
         assert_eq!(db.stats().unwrap().hit(), 5);
         assert_eq!(db.stats().unwrap().miss(), 5);
     }
+
+    #[test]
+    fn test_tiny_lfu_admission() {
+        let records = (0..3u8)
+            .map(|i| {
+                let record = [i; 32];
+                let key = Cid::new_v1(DAG_CBOR, Blake2b256.digest(record.as_slice()));
+                (key, record.to_vec())
+            })
+            .collect::<Vec<_>>();
+        let cache = TinyLfuBlockstoreReadCache::new_without_metrics_registry(
+            "test_tiny_lfu_admission".into(),
+            2.try_into().unwrap(),
+        );
+        let [(k0, v0), (k1, v1), (k2, v2)] = records.try_into().unwrap();
+
+        // Hot blocks
+        for _ in 0..3 {
+            assert!(cache.get(&k0).is_none());
+            assert!(cache.get(&k1).is_none());
+        }
+        cache.put(k0, v0.clone());
+        cache.put(k1, v1.clone());
+        assert_eq!(cache.len(), 2);
+
+        // A one-off read does not evict hot blocks.
+        assert!(cache.get(&k2).is_none());
+        cache.put(k2, v2.clone());
+        assert!(cache.get(&k2).is_none());
+        assert_eq!(cache.get(&k0), Some(v0));
+        assert_eq!(cache.get(&k1), Some(v1));
+
+        // Until it becomes more frequent than the least recently used block.
+        for _ in 0..5 {
+            cache.get(&k2);
+        }
+        cache.put(k2, v2.clone());
+        assert_eq!(cache.get(&k2), Some(v2));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.evictions(), 1);
+
+        cache.remove(&k2);
+        assert!(cache.get(&k2).is_none());
+        assert_eq!(cache.len(), 1);
+    }
 }
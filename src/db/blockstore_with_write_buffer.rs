@@ -1,68 +1,204 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use ahash::{HashMap, HashMapExt};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use ahash::HashMap;
+use anyhow::Context as _;
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
-use itertools::Itertools;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use super::metrics;
+use crate::utils::misc::memory_budget::scale_to_memory_budget;
+
+/// Flushing policy of [`BlockstoreWithWriteBuffer`]. The buffer is flushed as soon as either
+/// limit is reached.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct WriteBufferConfig {
+    /// Maximum number of buffered blocks, use 0 to write every block through
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub max_blocks: usize,
+    /// Maximum total size of the buffered blocks, in bytes
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub max_bytes: usize,
+    /// Flush in a background thread. Only one flush is in flight at a time, writers wait for it
+    /// to complete before the next one starts.
+    pub background_flush: bool,
+}
+
+impl Default for WriteBufferConfig {
+    /// Sized from the memory budget, 10000 blocks and 64 MiB at the reference budget.
+    fn default() -> Self {
+        Self {
+            max_blocks: scale_to_memory_budget(10000, 1000, 200_000),
+            max_bytes: scale_to_memory_budget(64 << 20, 8 << 20, 1 << 30),
+            background_flush: true,
+        }
+    }
+}
 
-pub struct BlockstoreWithWriteBuffer<DB: Blockstore> {
-    inner: DB,
-    buffer: RwLock<HashMap<Cid, Vec<u8>>>,
-    buffer_capacity: usize,
+#[derive(Default)]
+struct Buffer {
+    blocks: HashMap<Cid, Vec<u8>>,
+    bytes: usize,
 }
 
-impl<DB: Blockstore> Blockstore for BlockstoreWithWriteBuffer<DB> {
+impl Buffer {
+    fn insert(&mut self, k: Cid, block: &[u8]) {
+        if let Some(old) = self.blocks.insert(k, block.to_vec()) {
+            self.bytes -= old.len();
+        }
+        self.bytes += block.len();
+    }
+
+    fn is_full(&self, config: &WriteBufferConfig) -> bool {
+        self.blocks.len() >= config.max_blocks || self.bytes >= config.max_bytes
+    }
+}
+
+pub struct BlockstoreWithWriteBuffer<DB: Blockstore + Send + Sync + 'static> {
+    inner: Arc<DB>,
+    buffer: RwLock<Buffer>,
+    /// Blocks that are being written to `inner` by the background flush.
+    flushing: Arc<RwLock<HashMap<Cid, Vec<u8>>>>,
+    in_flight: Mutex<Option<JoinHandle<anyhow::Result<()>>>>,
+    config: WriteBufferConfig,
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> Blockstore for BlockstoreWithWriteBuffer<DB> {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
-        if let Some(v) = self.buffer.read().get(k) {
+        if let Some(v) = self.buffer.read().blocks.get(k) {
+            return Ok(Some(v.clone()));
+        }
+        if let Some(v) = self.flushing.read().get(k) {
             return Ok(Some(v.clone()));
         }
         self.inner.get(k)
     }
 
     fn has(&self, k: &Cid) -> anyhow::Result<bool> {
-        Ok(self.buffer.read().contains_key(k) || self.inner.has(k)?)
+        Ok(self.buffer.read().blocks.contains_key(k)
+            || self.flushing.read().contains_key(k)
+            || self.inner.has(k)?)
     }
 
     fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
-        {
+        let is_full = {
             let mut buffer = self.buffer.write();
-            buffer.insert(*k, block.to_vec());
+            buffer.insert(*k, block);
+            buffer.is_full(&self.config)
+        };
+        if is_full { self.flush_buffer() } else { Ok(()) }
+    }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        let mut blocks = blocks.into_iter().peekable();
+        while blocks.peek().is_some() {
+            // Fill the buffer under a single lock, flushing whenever it is full.
+            let is_full = {
+                let mut buffer = self.buffer.write();
+                blocks.by_ref().any(|(k, block)| {
+                    buffer.insert(k, block.as_ref());
+                    buffer.is_full(&self.config)
+                })
+            };
+            if is_full {
+                self.flush_buffer()?;
+            }
         }
-        self.flush_buffer_if_needed()
+        Ok(())
     }
 }
 
-impl<DB: Blockstore> BlockstoreWithWriteBuffer<DB> {
-    pub fn new_with_capacity(inner: DB, buffer_capacity: usize) -> Self {
+impl<DB: Blockstore + Send + Sync + 'static> BlockstoreWithWriteBuffer<DB> {
+    pub fn new(inner: DB, config: WriteBufferConfig) -> Self {
         Self {
-            inner,
-            buffer_capacity,
-            buffer: RwLock::new(HashMap::with_capacity(buffer_capacity)),
+            inner: Arc::new(inner),
+            buffer: Default::default(),
+            flushing: Default::default(),
+            in_flight: Default::default(),
+            config,
         }
     }
 
+    /// Writes all the buffered blocks to the inner store and waits for the write to complete.
+    pub fn flush(&self) -> anyhow::Result<()> {
+        self.flush_buffer()?;
+        Self::wait_for_flush(&mut self.in_flight.lock())
+    }
+
     fn flush_buffer(&self) -> anyhow::Result<()> {
-        let records = {
-            let mut buffer = self.buffer.write();
-            buffer.drain().collect_vec()
-        };
-        self.inner.put_many_keyed(records)
+        // Holding the lock serializes flushes, so at most one buffer is in flight.
+        let mut in_flight = self.in_flight.lock();
+        Self::wait_for_flush(&mut in_flight)?;
+        if self.config.background_flush {
+            let bytes = {
+                let mut buffer = self.buffer.write();
+                // Blocks are moved while holding the buffer lock so that they are always visible
+                // to readers.
+                *self.flushing.write() = std::mem::take(&mut buffer.blocks);
+                std::mem::take(&mut buffer.bytes)
+            };
+            let inner = self.inner.clone();
+            let flushing = self.flushing.clone();
+            *in_flight = Some(std::thread::spawn(move || {
+                let result = write_blocks(&*inner, &flushing.read(), bytes);
+                flushing.write().clear();
+                result
+            }));
+            Ok(())
+        } else {
+            let Buffer { blocks, bytes } = std::mem::take(&mut *self.buffer.write());
+            write_blocks(&*self.inner, &blocks, bytes)
+        }
     }
 
-    fn flush_buffer_if_needed(&self) -> anyhow::Result<()> {
-        if self.buffer.read().len() >= self.buffer_capacity {
-            self.flush_buffer()
+    fn wait_for_flush(
+        in_flight: &mut Option<JoinHandle<anyhow::Result<()>>>,
+    ) -> anyhow::Result<()> {
+        if let Some(handle) = in_flight.take() {
+            let start = Instant::now();
+            let result = handle
+                .join()
+                .ok()
+                .context("write buffer flush thread panicked")?;
+            metrics::WRITE_BUFFER_FLUSH_WAIT_TIME.observe(start.elapsed().as_secs_f64());
+            result
         } else {
             Ok(())
         }
     }
 }
 
-impl<DB: Blockstore> Drop for BlockstoreWithWriteBuffer<DB> {
+fn write_blocks(
+    db: &impl Blockstore,
+    blocks: &HashMap<Cid, Vec<u8>>,
+    bytes: usize,
+) -> anyhow::Result<()> {
+    if blocks.is_empty() {
+        return Ok(());
+    }
+    let start = Instant::now();
+    db.put_many_keyed(blocks.iter().map(|(k, v)| (*k, v)))?;
+    metrics::WRITE_BUFFER_FLUSH_TIME.observe(start.elapsed().as_secs_f64());
+    metrics::WRITE_BUFFER_FLUSHED_BYTES.inc_by(bytes as u64);
+    Ok(())
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> Drop for BlockstoreWithWriteBuffer<DB> {
     fn drop(&mut self) {
-        if let Err(e) = self.flush_buffer() {
+        if let Err(e) = self.flush() {
             tracing::warn!("{e}");
         }
     }
@@ -76,34 +212,53 @@ mod tests {
     use multihash_codetable::Code::Blake2b256;
     use multihash_codetable::MultihashDigest as _;
     use rand::Rng as _;
-    use std::sync::Arc;
 
-    #[test]
-    fn test_buffer_flush() {
-        const BUFFER_SIZE: usize = 10;
-        const N_RECORDS: usize = 15;
-        let mem_db = Arc::new(MemoryDB::default());
-        let buf_db = BlockstoreWithWriteBuffer::new_with_capacity(mem_db.clone(), BUFFER_SIZE);
-        let mut records = Vec::with_capacity(N_RECORDS);
-        for _ in 0..N_RECORDS {
-            let mut record = [0; 1024];
-            forest_rng().fill(&mut record);
-            let key = Cid::new_v1(DAG_CBOR, Blake2b256.digest(record.as_slice()));
-            records.push((key, record));
-        }
-
-        buf_db.put_many_keyed(records.clone()).unwrap();
+    fn random_records(n: usize) -> Vec<(Cid, [u8; 1024])> {
+        (0..n)
+            .map(|_| {
+                let mut record = [0; 1024];
+                forest_rng().fill(&mut record);
+                let key = Cid::new_v1(DAG_CBOR, Blake2b256.digest(record.as_slice()));
+                (key, record)
+            })
+            .collect()
+    }
 
+    fn assert_flushed_prefix(
+        buf_db: &impl Blockstore,
+        mem_db: &MemoryDB,
+        records: &[(Cid, [u8; 1024])],
+        flushed: usize,
+    ) {
         for (i, (k, v)) in records.iter().enumerate() {
             assert!(buf_db.has(k).unwrap());
             assert_eq!(buf_db.get(k).unwrap().unwrap().as_slice(), v);
-            if i < BUFFER_SIZE {
+            if i < flushed {
                 assert!(mem_db.has(k).unwrap());
                 assert_eq!(mem_db.get(k).unwrap().unwrap().as_slice(), v);
             } else {
                 assert!(!mem_db.has(k).unwrap());
             }
         }
+    }
+
+    #[test]
+    fn test_buffer_flush() {
+        const BUFFER_SIZE: usize = 10;
+        const N_RECORDS: usize = 15;
+        let mem_db = Arc::new(MemoryDB::default());
+        let buf_db = BlockstoreWithWriteBuffer::new(
+            mem_db.clone(),
+            WriteBufferConfig {
+                max_blocks: BUFFER_SIZE,
+                background_flush: false,
+                ..Default::default()
+            },
+        );
+        let records = random_records(N_RECORDS);
+
+        buf_db.put_many_keyed(records.clone()).unwrap();
+        assert_flushed_prefix(&buf_db, &mem_db, &records, BUFFER_SIZE);
 
         drop(buf_db);
 
@@ -112,4 +267,47 @@ mod tests {
             assert_eq!(mem_db.get(k).unwrap().unwrap().as_slice(), v);
         }
     }
+
+    #[test]
+    fn test_buffer_flush_by_size() {
+        let mem_db = Arc::new(MemoryDB::default());
+        let buf_db = BlockstoreWithWriteBuffer::new(
+            mem_db.clone(),
+            WriteBufferConfig {
+                max_blocks: usize::MAX,
+                max_bytes: 4 * 1024,
+                background_flush: false,
+            },
+        );
+        let records = random_records(6);
+
+        buf_db.put_many_keyed(records.clone()).unwrap();
+        assert_flushed_prefix(&buf_db, &mem_db, &records, 4);
+    }
+
+    #[test]
+    fn test_background_flush() {
+        let mem_db = Arc::new(MemoryDB::default());
+        let buf_db = BlockstoreWithWriteBuffer::new(
+            mem_db.clone(),
+            WriteBufferConfig {
+                max_blocks: 10,
+                background_flush: true,
+                ..Default::default()
+            },
+        );
+        let records = random_records(25);
+
+        buf_db.put_many_keyed(records.clone()).unwrap();
+        // Blocks are readable whether they are buffered, in flight or flushed.
+        for (k, v) in records.iter() {
+            assert!(buf_db.has(k).unwrap());
+            assert_eq!(buf_db.get(k).unwrap().unwrap().as_slice(), v);
+        }
+
+        buf_db.flush().unwrap();
+        for (k, v) in records.iter() {
+            assert_eq!(mem_db.get(k).unwrap().unwrap().as_slice(), v);
+        }
+    }
 }
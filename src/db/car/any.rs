@@ -6,7 +6,8 @@
 //! header and the first key-value block, and picks the appropriate block store
 //! (either [`super::ForestCar`] or [`super::PlainCar`]).
 //!
-//! CARv2 is not supported yet.
+//! CARv2 files are read as [`super::PlainCar`]s, with their embedded index if
+//! any, see [`crate::utils::db::car_v2`].
 
 use super::{CacheKey, RandomAccessFileReader, ZstdFrameCache};
 use crate::blocks::{Tipset, TipsetKey};
@@ -15,7 +16,7 @@ use crate::utils::io::EitherMmapOrRandomAccessFile;
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use itertools::Either;
-use positioned_io::ReadAt;
+use positioned_io::{ReadAt, Size as _};
 use std::borrow::Cow;
 use std::io::{Error, ErrorKind, Read, Result};
 use std::path::{Path, PathBuf};
@@ -34,18 +35,35 @@ impl<ReaderT: RandomAccessFileReader> AnyCar<ReaderT> {
     /// `.forest.car.zst`. This call may block for an indeterminate amount of
     /// time while data is decoded and indexed.
     pub fn new(reader: ReaderT) -> Result<Self> {
+        Self::new_with_progress(reader, &|_| {})
+    }
+
+    /// Same as [`AnyCar::new`], calling `progress` with the number of bytes of the archive
+    /// decoded and indexed as opening goes.
+    pub fn new_with_progress(reader: ReaderT, progress: &(dyn Fn(u64) + Sync)) -> Result<Self> {
         if super::ForestCar::is_valid(&reader) {
-            return Ok(AnyCar::Forest(super::ForestCar::new(reader)?));
+            // The index of `.forest.car.zst` archives is embedded, there is nothing to index.
+            let size = reader.size()?.unwrap_or_default();
+            let forest_car = super::ForestCar::new(reader)?;
+            progress(size);
+            return Ok(AnyCar::Forest(forest_car));
         }
 
-        // Maybe use a tempfile for this in the future.
-        if let Ok(decompressed) = zstd::stream::decode_all(positioned_io::Cursor::new(&reader))
+        // Maybe use a tempfile for this in the future. Checking the magic number first keeps the
+        // bytes read from uncompressed archives out of the progress.
+        let mut magic = [0; 4];
+        if reader.read_exact_at(0, &mut magic).is_ok()
+            && magic == ZSTD_MAGIC
+            && let Ok(decompressed) = zstd::stream::decode_all(ProgressRead {
+                inner: positioned_io::Cursor::new(&reader),
+                progress,
+            })
             && let Ok(mem_car) = super::PlainCar::new(decompressed)
         {
             return Ok(AnyCar::Memory(mem_car));
         }
 
-        if let Ok(plain_car) = super::PlainCar::new(reader) {
+        if let Ok(plain_car) = super::PlainCar::new_with_progress(reader, progress) {
             return Ok(AnyCar::Plain(plain_car));
         }
         Err(Error::new(
@@ -149,7 +167,7 @@ impl TryFrom<&PathBuf> for AnyCar<EitherMmapOrRandomAccessFile> {
 
 impl<ReaderT> Blockstore for AnyCar<ReaderT>
 where
-    ReaderT: ReadAt,
+    ReaderT: ReadAt + 'static,
 {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
         match self {
@@ -166,6 +184,36 @@ where
             AnyCar::Memory(mem) => mem.put_keyed(k, block),
         }
     }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        match self {
+            AnyCar::Forest(forest) => forest.put_many_keyed(blocks),
+            AnyCar::Plain(plain) => plain.put_many_keyed(blocks),
+            AnyCar::Memory(mem) => mem.put_many_keyed(blocks),
+        }
+    }
+}
+
+/// Magic number of `zstd` frames.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Reader calling `progress` with the number of bytes read.
+struct ProgressRead<'a, R> {
+    inner: R,
+    progress: &'a (dyn Fn(u64) + Sync),
+}
+
+impl<R: Read> Read for ProgressRead<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        (self.progress)(n as u64);
+        Ok(n)
+    }
 }
 
 #[cfg(test)]
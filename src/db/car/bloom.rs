@@ -0,0 +1,268 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Bloom filters of the blocks of read-only CAR stores, which let [`super::ManyCar`] skip the
+//! index lookups of the stores that cannot contain a block. Missing blocks otherwise cost a
+//! lookup in every store, which adds up when dozens of diff snapshots are mounted.
+//!
+//! The filters are keyed on [`cid_hash`], the hash stored in the embedded index of the
+//! `.forest.car.zst` format, so that they are built from the index without decoding any frame.
+//! They are persisted next to the CAR files, in a file with the [`BLOOM_FILE_EXTENSION`]
+//! extension, and rebuilt when the CAR file changes.
+
+use std::io::{self, Read as _, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use byteorder::{LittleEndian, ReadBytesExt as _, WriteBytesExt as _};
+use tracing::{debug, warn};
+
+use super::forest::cid_hash;
+use super::{AnyCar, RandomAccessFileReader};
+
+pub const BLOOM_FILE_EXTENSION: &str = "bloom";
+
+const MAGIC: [u8; 4] = *b"FCBF";
+const VERSION: u8 = 1;
+/// Around 1% of false positives with [`NUM_HASHES`] hashes.
+const BITS_PER_ENTRY: u64 = 10;
+const NUM_HASHES: u32 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CidBloomFilter {
+    words: Vec<u64>,
+    num_hashes: u32,
+}
+
+impl CidBloomFilter {
+    /// Creates an empty filter sized for `num_entries` entries.
+    pub fn with_capacity(num_entries: u64) -> Self {
+        let num_bits = (num_entries * BITS_PER_ENTRY).max(u64::BITS as u64);
+        Self {
+            words: vec![0; num_bits.div_ceil(u64::BITS as u64) as usize],
+            num_hashes: NUM_HASHES,
+        }
+    }
+
+    /// Builds the filter of the blocks of a CAR store.
+    pub fn from_car<ReaderT: RandomAccessFileReader>(car: &AnyCar<ReaderT>) -> io::Result<Self> {
+        match car {
+            AnyCar::Forest(forest) => {
+                let mut filter = Self::with_capacity(forest.index_slots()?);
+                for hash in forest.index_hashes()? {
+                    filter.insert_hash(hash?);
+                }
+                Ok(filter)
+            }
+            AnyCar::Plain(plain) => Ok(Self::from_hashes(plain.index_hashes())),
+            AnyCar::Memory(plain) => Ok(Self::from_hashes(plain.index_hashes())),
+        }
+    }
+
+    fn from_hashes(hashes: Vec<u64>) -> Self {
+        let mut filter = Self::with_capacity(hashes.len() as u64);
+        for hash in hashes {
+            filter.insert_hash(hash);
+        }
+        filter
+    }
+
+    /// Loads the filter persisted next to the CAR file, or builds it from the store and persists
+    /// it if it is missing or outdated. Failing to persist the filter is not an error, e.g. the
+    /// CAR file may be in a read-only directory.
+    pub fn load_or_build<ReaderT: RandomAccessFileReader>(
+        car_path: &Path,
+        car: &AnyCar<ReaderT>,
+    ) -> io::Result<Self> {
+        let bloom_path = bloom_path(car_path);
+        let stamp = CarStamp::of(car_path)?;
+        match Self::load(&bloom_path, &stamp) {
+            Ok(Some(filter)) => return Ok(filter),
+            Ok(None) => {}
+            Err(e) => debug!("Ignoring the bloom filter {}: {e}", bloom_path.display()),
+        }
+        let filter = Self::from_car(car)?;
+        if let Err(e) = filter.save(&bloom_path, &stamp) {
+            warn!(
+                "Failed to persist the bloom filter {}: {e}",
+                bloom_path.display()
+            );
+        }
+        Ok(filter)
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        for bit in self.bits(hash) {
+            if let Some(word) = self.words.get_mut(bit / 64) {
+                *word |= 1 << (bit % 64);
+            }
+        }
+    }
+
+    /// Returns `false` if the block is definitely not in the store. Takes the [`cid_hash`] of the
+    /// block, so that it is computed once for all the stores.
+    pub fn might_contain(&self, hash: u64) -> bool {
+        self.bits(hash).all(|bit| {
+            self.words
+                .get(bit / 64)
+                // A filter can't rule out the blocks it has no bits for
+                .is_none_or(|word| word & (1 << (bit % 64)) != 0)
+        })
+    }
+
+    /// Positions of the bits of a hash, derived with double hashing.
+    fn bits(&self, hash: u64) -> impl Iterator<Item = usize> + use<> {
+        let num_bits = (self.words.len() * 64) as u128;
+        let step = splitmix64(hash) | 1;
+        (0..self.num_hashes as u64).map(move |i| {
+            let combined = hash.wrapping_add(i.wrapping_mul(step));
+            // Maps the hash to `0..num_bits` without a division.
+            ((combined as u128 * num_bits) >> 64) as usize
+        })
+    }
+
+    fn load(path: &Path, stamp: &CarStamp) -> io::Result<Option<Self>> {
+        let mut reader = match std::fs::File::open(path) {
+            Ok(file) => io::BufReader::new(file),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC || reader.read_u8()? != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unsupported bloom filter format",
+            ));
+        }
+        if CarStamp::read_from(&mut reader)? != *stamp {
+            debug!("Bloom filter {} is outdated", path.display());
+            return Ok(None);
+        }
+        let num_hashes = reader.read_u32::<LittleEndian>()?;
+        let num_words = reader.read_u64::<LittleEndian>()?;
+        if num_words == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "empty bloom filter",
+            ));
+        }
+        let mut words = vec![0; usize::try_from(num_words).map_err(io::Error::other)?];
+        reader.read_u64_into::<LittleEndian>(&mut words)?;
+        Ok(Some(Self { words, num_hashes }))
+    }
+
+    fn save(&self, path: &Path, stamp: &CarStamp) -> io::Result<()> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        {
+            let mut writer = io::BufWriter::new(file.as_file_mut());
+            writer.write_all(&MAGIC)?;
+            writer.write_u8(VERSION)?;
+            stamp.write_to(&mut writer)?;
+            writer.write_u32::<LittleEndian>(self.num_hashes)?;
+            writer.write_u64::<LittleEndian>(self.words.len() as u64)?;
+            for word in &self.words {
+                writer.write_u64::<LittleEndian>(*word)?;
+            }
+            writer.flush()?;
+        }
+        file.persist(path)?;
+        Ok(())
+    }
+}
+
+/// Path of the bloom filter persisted next to a CAR file.
+pub fn bloom_path(car_path: &Path) -> PathBuf {
+    let mut path = car_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(BLOOM_FILE_EXTENSION);
+    path.into()
+}
+
+/// Size and modification time of a CAR file, to detect outdated filters.
+#[derive(Debug, PartialEq, Eq)]
+struct CarStamp {
+    len: u64,
+    modified_nanos: u64,
+}
+
+impl CarStamp {
+    fn of(car_path: &Path) -> io::Result<Self> {
+        let metadata = std::fs::metadata(car_path)?;
+        let modified_nanos = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|it| it.as_nanos() as u64)
+            .unwrap_or_default();
+        Ok(Self {
+            len: metadata.len(),
+            modified_nanos,
+        })
+    }
+
+    fn read_from(mut reader: impl io::Read) -> io::Result<Self> {
+        Ok(Self {
+            len: reader.read_u64::<LittleEndian>()?,
+            modified_nanos: reader.read_u64::<LittleEndian>()?,
+        })
+    }
+
+    fn write_to(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_u64::<LittleEndian>(self.len)?;
+        writer.write_u64::<LittleEndian>(self.modified_nanos)
+    }
+}
+
+/// See <https://prng.di.unimi.it/splitmix64.c>
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::calibnet;
+
+    #[test]
+    fn no_false_negatives() {
+        let car = AnyCar::try_from(calibnet::DEFAULT_GENESIS).unwrap();
+        let filter = CidBloomFilter::from_car(&car).unwrap();
+        let cids = match &car {
+            AnyCar::Plain(plain) => plain.cids(),
+            AnyCar::Memory(plain) => plain.cids(),
+            AnyCar::Forest(_) => unreachable!("genesis is not a forest CAR"),
+        };
+        for cid in cids {
+            assert!(filter.might_contain(cid_hash(&cid)));
+        }
+    }
+
+    #[test]
+    fn persist_next_to_car() {
+        let dir = tempfile::tempdir().unwrap();
+        let car_path = dir.path().join("genesis.car");
+        std::fs::write(&car_path, calibnet::DEFAULT_GENESIS).unwrap();
+        let car =
+            AnyCar::new(crate::utils::io::EitherMmapOrRandomAccessFile::open(&car_path).unwrap())
+                .unwrap();
+        let filter = CidBloomFilter::load_or_build(&car_path, &car).unwrap();
+        let stamp = CarStamp::of(&car_path).unwrap();
+        assert_eq!(
+            CidBloomFilter::load(&bloom_path(&car_path), &stamp).unwrap(),
+            Some(filter)
+        );
+        // A filter of another version of the CAR file is not used.
+        let outdated = CarStamp {
+            len: stamp.len + 1,
+            ..stamp
+        };
+        assert_eq!(
+            CidBloomFilter::load(&bloom_path(&car_path), &outdated).unwrap(),
+            None
+        );
+    }
+}
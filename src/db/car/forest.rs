@@ -32,7 +32,10 @@
 //! Looking up a block uses an [`index::Reader`] to find
 //! the right z-frame. The frame is then decoded and each block is linearly
 //! scanned until a match is found. Decoded (and scanned) z-frames are stored in
-//! a lru-cache for faster repeat retrievals.
+//! a lru-cache for faster repeat retrievals. Indices written with
+//! `FOREST_CAR_INDEX_BLOCK_OFFSETS` set also hold the offset of each block in
+//! its decoded z-frame, so that streaming a block with
+//! [`ForestCar::get_reader`] decodes the frame up to the end of the block only.
 //!
 //! `forest.car.zst` files are backward compatible with Lotus (and all other
 //! tools that consume compressed CAR files). All Forest-specifc information is
@@ -55,13 +58,15 @@ use crate::utils::db::car_stream::{CarBlock, CarV1Header, uvi_bytes};
 use crate::utils::encoding::from_slice_with_fallback;
 use crate::utils::get_size::CidWrapper;
 use crate::utils::io::EitherMmapOrRandomAccessFile;
+use crate::utils::misc::env::is_env_truthy;
+use anyhow::Context as _;
 use byteorder::LittleEndian;
 use bytes::{BufMut as _, Bytes, BytesMut, buf::Writer};
 use cid::Cid;
 use futures::{Stream, TryStreamExt as _};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CborStore as _;
-use integer_encoding::VarIntReader;
+use integer_encoding::{VarInt as _, VarIntReader};
 use nunny::Vec as NonEmpty;
 use positioned_io::{Cursor, ReadAt, ReadBytesAtExt, SizeCursor};
 use std::io::{Seek, SeekFrom};
@@ -80,6 +85,8 @@ pub mod index;
 #[cfg(not(feature = "benchmark-private"))]
 mod index;
 
+pub use index::cid_hash;
+
 pub const FOREST_CAR_FILE_EXTENSION: &str = ".forest.car.zst";
 pub const TEMP_FOREST_CAR_FILE_EXTENSION: &str = ".forest.car.zst.tmp";
 /// <https://github.com/facebook/zstd/blob/dev/doc/zstd_compression_format.md#skippable-frames>
@@ -88,8 +95,9 @@ pub const DEFAULT_FOREST_CAR_FRAME_SIZE: usize = 8000_usize.next_power_of_two();
 pub const DEFAULT_FOREST_CAR_COMPRESSION_LEVEL: u16 = zstd::DEFAULT_COMPRESSION_LEVEL as _;
 const ZSTD_SKIP_FRAME_LEN: u64 = 8;
 
-/// `zstd` frame of Forest CAR
-pub type ForestCarFrame = (Vec<Cid>, Bytes);
+/// `zstd` frame of Forest CAR, with the CIDs of its blocks and their offsets in
+/// the decompressed frame
+pub type ForestCarFrame = (Vec<(Cid, u64)>, Bytes);
 
 pub struct ForestCar<ReaderT> {
     // Multiple `ForestCar` structures may share the same cache. The cache key is used to identify
@@ -187,6 +195,16 @@ impl<ReaderT: super::RandomAccessFileReader> ForestCar<ReaderT> {
         Tipset::load_required(self, &self.heaviest_tipset_key())
     }
 
+    /// Number of slots of the embedded index, an upper bound of the number of blocks.
+    pub fn index_slots(&self) -> io::Result<u64> {
+        self.indexed.num_slots()
+    }
+
+    /// Iterates over the hashes of the blocks in the embedded index, see [`cid_hash`].
+    pub fn index_hashes(&self) -> io::Result<impl Iterator<Item = io::Result<u64>> + '_> {
+        self.indexed.hashes()
+    }
+
     pub fn into_dyn(self) -> ForestCar<Box<dyn super::RandomAccessFileReader>> {
         ForestCar {
             cache_key: self.cache_key,
@@ -215,21 +233,11 @@ impl<ReaderT: super::RandomAccessFileReader> ForestCar<ReaderT> {
 
     /// Gets a reader of the block data by its `Cid`
     pub fn get_reader(&self, k: Cid) -> anyhow::Result<Option<impl Read>> {
-        for position in self.indexed.get(k)? {
+        for location in self.indexed.get(k)? {
             // escape the positioned_io::Slice
             let entire_file = self.indexed.reader().get_ref();
-            // `position` is the frame start offset.
-            let cursor = Cursor::new_pos(entire_file, position);
-            let mut decoder = zstd::Decoder::new(cursor)?.single_frame();
-            while let Ok(car_block_len) = decoder.read_varint::<usize>() {
-                let cid = Cid::read_bytes(&mut decoder)?;
-                let data_len = car_block_len.saturating_sub(cid.encoded_len()) as u64;
-                if cid == k {
-                    // return the reader instead of decoding the entire data block into memory
-                    return Ok(Some(decoder.take(data_len)));
-                }
-                // Discard data bytes
-                io::copy(&mut decoder.by_ref().take(data_len), &mut io::sink())?;
+            if let Some(reader) = read_block_in_frame(entire_file, location, k)? {
+                return Ok(Some(reader));
             }
         }
         Ok(None)
@@ -245,12 +253,13 @@ impl TryFrom<&Path> for ForestCar<EitherMmapOrRandomAccessFile> {
 
 impl<ReaderT> Blockstore for ForestCar<ReaderT>
 where
-    ReaderT: ReadAt,
+    ReaderT: ReadAt + 'static,
 {
     #[tracing::instrument(level = "trace", skip(self))]
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
         let indexed = &self.indexed;
-        for position in indexed.get(*k)?.into_iter() {
+        for location in indexed.get(*k)?.into_iter() {
+            let position = location.frame_offset;
             let cache_query = self.frame_cache.get(position, self.cache_key, *k);
             match cache_query {
                 // Frame cache hit, found value.
@@ -260,8 +269,16 @@ where
                 None => {
                     // Decode entire frame into memory, "position" arg is the frame start offset.
                     let entire_file = indexed.reader().get_ref(); // escape the positioned_io::Slice
-                    let cursor = Cursor::new_pos(entire_file, position);
-                    let mut zstd_frame = decode_zstd_single_frame(cursor)?;
+                    let mut zstd_frame = match super::zero_copy_bytes(entire_file) {
+                        // Decode the frame in place, without buffering the compressed bytes.
+                        Some(bytes) => decode_zstd_single_frame_in_place(
+                            usize::try_from(position)
+                                .ok()
+                                .and_then(|position| bytes.get(position..))
+                                .context("frame is out of the bounds of the CAR file")?,
+                        )?,
+                        None => decode_zstd_single_frame(Cursor::new_pos(entire_file, position))?,
+                    };
                     // Parse all key-value pairs and insert them into a map
                     let mut block_map = hashbrown::HashMap::new();
                     while let Some(block_frame) = uvi_bytes().decode_eof(&mut zstd_frame)? {
@@ -287,6 +304,37 @@ where
     }
 }
 
+/// Reads the data of the block `k` in the frame at `location`. The frame is
+/// decoded up to the block if its offset is known, otherwise the blocks of the
+/// frame are scanned until a match is found. Returns `None` when hashes collide.
+fn read_block_in_frame<ReaderT: ReadAt>(
+    entire_file: &ReaderT,
+    location: index::BlockLocation,
+    k: Cid,
+) -> io::Result<Option<impl Read>> {
+    let cursor = Cursor::new_pos(entire_file, location.frame_offset);
+    let mut decoder = zstd::Decoder::new(cursor)?.single_frame();
+    if let Some(data_offset) = location.data_offset {
+        // Discard the preceding blocks without parsing them
+        io::copy(&mut decoder.by_ref().take(data_offset), &mut io::sink())?;
+    }
+    while let Ok(car_block_len) = decoder.read_varint::<usize>() {
+        let cid = Cid::read_bytes(&mut decoder).map_err(invalid_data)?;
+        let data_len = car_block_len.saturating_sub(cid.encoded_len()) as u64;
+        if cid == k {
+            // return the reader instead of decoding the entire data block into memory
+            return Ok(Some(decoder.take(data_len)));
+        }
+        if location.data_offset.is_some() {
+            // The block at the offset is another block with the same hash
+            break;
+        }
+        // Discard data bytes
+        io::copy(&mut decoder.by_ref().take(data_len), &mut io::sink())?;
+    }
+    Ok(None)
+}
+
 fn decode_zstd_single_frame<ReaderT: Read>(reader: ReaderT) -> io::Result<BytesMut> {
     let mut zstd_frame = vec![];
     zstd::Decoder::new(reader)?
@@ -295,6 +343,17 @@ fn decode_zstd_single_frame<ReaderT: Read>(reader: ReaderT) -> io::Result<BytesM
     Ok(zstd_frame.into_iter().collect())
 }
 
+/// Decompresses the frame straight from `bytes` into the returned buffer, neither the compressed
+/// nor the decompressed bytes are copied.
+fn decode_zstd_single_frame_in_place(bytes: &[u8]) -> io::Result<BytesMut> {
+    let mut zstd_frame = BytesMut::new().writer();
+    io::copy(
+        &mut zstd::Decoder::with_buffer(bytes)?.single_frame(),
+        &mut zstd_frame,
+    )?;
+    Ok(zstd_frame.into_inner())
+}
+
 pub struct Encoder {}
 
 impl Encoder {
@@ -325,13 +384,25 @@ impl Encoder {
         // Write seekable zstd and collect a mapping of CIDs to frame_offset+data_offset.
         let mut builder = index::Builder::new();
         while let Some((cids, zstd_frame)) = stream.try_next().await? {
-            builder.extend(cids.into_iter().map(|cid| (cid, offset as u64)));
+            builder.extend(cids.into_iter().map(|(cid, data_offset)| {
+                (
+                    cid,
+                    index::BlockLocation {
+                        frame_offset: offset as u64,
+                        data_offset: Some(data_offset),
+                    },
+                )
+            }));
             sink.write_all(&zstd_frame).await?;
             offset += zstd_frame.len()
         }
 
         // Create index
-        let writer = builder.into_writer();
+        let writer = if is_env_truthy("FOREST_CAR_INDEX_BLOCK_OFFSETS") {
+            builder.into_writer_with_block_offsets()
+        } else {
+            builder.into_writer()
+        };
         write_skip_frame_header_async(&mut sink, writer.written_len().try_into().unwrap()).await?;
         writer.write_into(&mut sink).await?;
 
@@ -363,6 +434,8 @@ impl Encoder {
     ) -> impl Stream<Item = anyhow::Result<ForestCarFrame>> {
         let mut encoder_store = new_encoder(zstd_compression_level);
         let mut frame_cids = vec![];
+        // Uncompressed length of the blocks of the current frame
+        let mut frame_len = 0;
 
         let mut stream = Box::pin(stream.into_stream());
         futures::stream::poll_fn(move |cx| {
@@ -380,6 +453,7 @@ impl Encoder {
                 // Emit frame if compressed_len > zstd_frame_size_tripwire
                 if compressed_len(encoder) > zstd_frame_size_tripwire {
                     let cids = std::mem::take(&mut frame_cids);
+                    frame_len = 0;
                     let frame = finalize_frame(zstd_compression_level, encoder)?;
                     return Poll::Ready(Some(Ok((cids, frame))));
                 }
@@ -406,7 +480,9 @@ impl Encoder {
                     }
                     // Got element, add to encoder and emit block position
                     Some(Ok(block)) => {
-                        frame_cids.push(block.cid);
+                        frame_cids.push((block.cid, frame_len));
+                        let block_len = block.cid.encoded_len() + block.data.len();
+                        frame_len += (block_len.required_space() + block_len) as u64;
                         block.write(encoder)?;
                         encoder.flush()?;
                     }
@@ -524,6 +600,20 @@ mod tests {
         }
     }
 
+    #[quickcheck]
+    fn forest_car_zero_copy(blocks: nunny::Vec<CarBlock>) -> anyhow::Result<()> {
+        let roots = nonempty!(blocks.first().cid);
+        let tmp = tempfile::Builder::new().tempfile()?.into_temp_path();
+        std::fs::write(&tmp, mk_encoded_car(1024 * 4, 3, roots, blocks.clone()))?;
+        let forest_car =
+            ForestCar::new(crate::utils::io::Mmap::map_path(&tmp)?.with_zero_copy(true))?;
+        assert!(super::super::zero_copy_bytes(forest_car.indexed.reader().get_ref()).is_some());
+        for block in blocks {
+            assert_eq!(forest_car.get(&block.cid)?, Some(block.data));
+        }
+        Ok(())
+    }
+
     #[quickcheck]
     fn forest_car_create_options(
         blocks: nunny::Vec<CarBlock>,
@@ -48,11 +48,17 @@
 //!   [`hash::ideal_slot_ix`].
 //!   This is calculated at construction time.
 //!
+//! Each occupied slot holds the hash, the offset of the zstd frame of the block
+//! in the file and, in the opt-in [`Version::V2`], the offset of the block in
+//! the decompressed frame, so that streaming a block decompresses the frame up
+//! to the block only rather than scanning it. [`Version::V1`] is written by
+//! default, as older versions of Forest can't read [`Version::V2`] indices.
+//!
 //! So the layout on disk is as follows:
 //!
 //! ```text
 //! ┌──────────────┐
-//! │Version::V1   │
+//! │Version       │
 //! ├──────────────┤
 //! │Header        │ <- Contains the "intial width", required to perform lookups
 //! ├──────────────┤
@@ -94,6 +100,7 @@ pub mod hash;
 pub struct Reader<R> {
     inner: R,
     table_offset: u64,
+    version: Version,
     #[cfg(feature = "benchmark-private")]
     pub header: V1Header,
     #[cfg(not(feature = "benchmark-private"))]
@@ -106,57 +113,57 @@ where
 {
     pub fn new(reader: R) -> io::Result<Self> {
         let mut reader = positioned_io::Cursor::new(reader);
-        let Version::V1 = Version::read_from(&mut reader)? else {
+        let version = Version::read_from(&mut reader)?;
+        if !matches!(version, Version::V1 | Version::V2) {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "unsupported embedded index version",
             ));
-        };
+        }
         let header = V1Header::read_from(&mut reader)?;
         Ok(Self {
             table_offset: reader.position(),
             inner: reader.into_inner(),
+            version,
             header,
         })
     }
 
-    /// Look up possible frame offsets for a [`Cid`].
-    /// Returns `Ok([])` if no offsets are found, or [`Err(_)`] if the underlying
+    /// Look up possible locations for a [`Cid`].
+    /// Returns `Ok([])` if no locations are found, or [`Err(_)`] if the underlying
     /// IO fails.
     ///
     /// Does not allocate unless 2 or more CIDs have collided, see [module documentation](mod@self).
     ///
-    /// You MUST check the actual CID at the offset to see if it matches.
-    pub fn get(&self, key: Cid) -> io::Result<SmallVec<[u64; 1]>> {
+    /// You MUST check the actual CID at the location to see if it matches.
+    pub fn get(&self, key: Cid) -> io::Result<SmallVec<[BlockLocation; 1]>> {
         self.get_by_hash(hash::summary(&key))
     }
 
     /// Jump to slot offset and scan downstream. All key-value pairs with a
     /// matching key are guaranteed to appear before we encounter an empty slot.
     #[cfg_vis(feature = "benchmark-private", pub)]
-    fn get_by_hash(&self, needle: NonMaximalU64) -> io::Result<SmallVec<[u64; 1]>> {
+    fn get_by_hash(&self, needle: NonMaximalU64) -> io::Result<SmallVec<[BlockLocation; 1]>> {
         let Some(initial_buckets) =
             NonZeroUsize::new(self.header.initial_buckets.try_into().unwrap())
         else {
             return Ok(smallvec![]); // empty table
         };
-        let offset_in_table =
-            u64::try_from(hash::ideal_slot_ix(needle, initial_buckets)).unwrap() * RawSlot::LEN;
+        let offset_in_table = u64::try_from(hash::ideal_slot_ix(needle, initial_buckets)).unwrap()
+            * slot_len(self.version);
         let mut haystack =
             positioned_io::Cursor::new_pos(&self.inner, self.table_offset + offset_in_table);
 
         let mut limit = self.header.longest_distance;
-        while let Slot::Occupied(OccupiedSlot { hash, frame_offset }) =
-            Slot::read_from(&mut haystack)?
-        {
-            if hash == needle {
-                let mut found = smallvec![frame_offset];
+        while let Slot::Occupied(occupied) = read_slot(self.version, &mut haystack)? {
+            if occupied.hash == needle {
+                let mut found = smallvec![occupied.location()];
                 // The entries are sorted. Once we've found a matching key, all
                 // duplicate hash keys will be right next to it.
                 loop {
-                    match Slot::read_from(&mut haystack)? {
+                    match read_slot(self.version, &mut haystack)? {
                         Slot::Occupied(another) if another.hash == needle => {
-                            found.push(another.frame_offset)
+                            found.push(another.location())
                         }
                         Slot::Empty | Slot::Occupied(_) => return Ok(found),
                     }
@@ -184,19 +191,19 @@ where
         Reader {
             inner: f(self.inner),
             table_offset: self.table_offset,
+            version: self.version,
             header: self.header,
         }
     }
 }
 
 #[cfg_vis(feature = "benchmark-private", pub)]
-#[allow(unused)] // required for benchmarking
 struct Iter<R> {
     inner: R,
+    version: Version,
     positions: iter::StepBy<std::ops::Range<u64>>,
 }
 
-#[allow(unused)] // required for benchmarking
 impl<R> Iterator for Iter<R>
 where
     R: ReadAt + Size,
@@ -204,9 +211,12 @@ where
     type Item = io::Result<Slot>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.positions
-            .next()
-            .map(|pos| Slot::read_from(positioned_io::Cursor::new_pos(&self.inner, pos)))
+        self.positions.next().map(|pos| {
+            read_slot(
+                self.version,
+                positioned_io::Cursor::new_pos(&self.inner, pos),
+            )
+        })
     }
 }
 
@@ -215,7 +225,6 @@ where
     R: ReadAt + Size,
 {
     #[cfg_vis(feature = "benchmark-private", pub)]
-    #[allow(unused)] // required for benchmarking
     fn iter(&self) -> io::Result<Iter<&R>> {
         let end = self
             .inner
@@ -223,9 +232,42 @@ where
             .ok_or_else(|| io::Error::other("couldn't get end of table size"))?;
         Ok(Iter {
             inner: &self.inner,
-            positions: (self.table_offset..end).step_by(Slot::LEN.try_into().unwrap()),
+            version: self.version,
+            positions: (self.table_offset..end).step_by(slot_len(self.version).try_into().unwrap()),
         })
     }
+
+    /// Number of slots of the table, an upper bound of the number of entries.
+    pub fn num_slots(&self) -> io::Result<u64> {
+        let end = self
+            .inner
+            .size()?
+            .ok_or_else(|| io::Error::other("couldn't get end of table size"))?;
+        Ok(end.saturating_sub(self.table_offset) / slot_len(self.version))
+    }
+
+    /// Iterates over the hashes of the entries, see [`cid_hash`].
+    pub fn hashes(&self) -> io::Result<impl Iterator<Item = io::Result<u64>> + '_> {
+        Ok(self.iter()?.filter_map(|slot| match slot {
+            Ok(Slot::Occupied(OccupiedSlot { hash, .. })) => Some(Ok(hash.get())),
+            Ok(Slot::Empty) => None,
+            Err(e) => Some(Err(e)),
+        }))
+    }
+}
+
+/// Location of a block in a `.forest.car.zst` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockLocation {
+    /// Offset of the zstd frame of the block in the file
+    pub frame_offset: u64,
+    /// Offset of the block in the decompressed frame, unknown in [`Version::V1`] indices
+    pub data_offset: Option<u64>,
+}
+
+/// Hash of a [`Cid`] as stored in the index.
+pub fn cid_hash(cid: &Cid) -> u64 {
+    hash::summary(cid).get()
 }
 
 const DEFAULT_LOAD_FACTOR: f64 = 0.8;
@@ -258,7 +300,19 @@ impl Builder {
         }
     }
 
+    /// Creates a [`Version::V1`] writer, without the offsets of the blocks in
+    /// their frames.
     pub fn into_writer(self) -> Writer {
+        self.into_writer_with_version(Version::V1)
+    }
+
+    /// Creates a [`Version::V2`] writer, with the offsets of the blocks in their
+    /// frames.
+    pub fn into_writer_with_block_offsets(self) -> Writer {
+        self.into_writer_with_version(Version::V2)
+    }
+
+    fn into_writer_with_version(self, version: Version) -> Writer {
         let Self {
             load_factor,
             mut slots,
@@ -268,7 +322,7 @@ impl Builder {
         slots.dedup_by_key(|(_, it)| *it);
         let Some(initial_width) = initial_width(slots.len(), load_factor) else {
             return Writer {
-                version: Version::V1,
+                version,
                 header: V1Header {
                     longest_distance: 0,
                     collisions: 0,
@@ -301,7 +355,7 @@ impl Builder {
         }
 
         Writer {
-            version: Version::V1,
+            version,
             header: V1Header {
                 longest_distance: longest_distance.try_into().unwrap(),
                 collisions: collisions.try_into().unwrap(),
@@ -320,10 +374,32 @@ impl Extend<(Cid, u64)> for Builder {
 
 impl Extend<(NonMaximalU64, u64)> for Builder {
     fn extend<T: IntoIterator<Item = (NonMaximalU64, u64)>>(&mut self, iter: T) {
-        self.slots.extend(
-            iter.into_iter()
-                .map(|(hash, frame_offset)| (0, OccupiedSlot { hash, frame_offset })),
-        )
+        self.slots
+            .extend(iter.into_iter().map(|(hash, frame_offset)| {
+                (
+                    0,
+                    OccupiedSlot {
+                        hash,
+                        frame_offset,
+                        data_offset: UNKNOWN_DATA_OFFSET,
+                    },
+                )
+            }))
+    }
+}
+
+impl Extend<(Cid, BlockLocation)> for Builder {
+    fn extend<T: IntoIterator<Item = (Cid, BlockLocation)>>(&mut self, iter: T) {
+        self.slots.extend(iter.into_iter().map(|(cid, location)| {
+            (
+                0,
+                OccupiedSlot {
+                    hash: hash::summary(&cid),
+                    frame_offset: location.frame_offset,
+                    data_offset: location.data_offset.unwrap_or(UNKNOWN_DATA_OFFSET),
+                },
+            )
+        }))
     }
 }
 
@@ -341,6 +417,13 @@ impl FromIterator<(NonMaximalU64, u64)> for Builder {
         this
     }
 }
+impl FromIterator<(Cid, BlockLocation)> for Builder {
+    fn from_iter<T: IntoIterator<Item = (Cid, BlockLocation)>>(iter: T) -> Self {
+        let mut this = Self::default();
+        this.extend(iter);
+        this
+    }
+}
 
 /// Writes the actual slot table to disk.
 ///
@@ -377,7 +460,7 @@ impl Writer {
                 )
                 .unwrap(),
                 header.initial_buckets + 1, /* trailing */
-            ) * Slot::LEN
+            ) * slot_len(*version)
     }
     fn slots(
         min_slots: usize,
@@ -418,7 +501,12 @@ impl Writer {
             header.initial_buckets.try_into().unwrap(),
             slots.iter().copied(),
         ) {
-            write_via_buf(&mut buf, &mut writer, slot).await?;
+            match version {
+                Version::V0 | Version::V1 => {
+                    write_via_buf(&mut buf, &mut writer, V1Slot(slot)).await?
+                }
+                Version::V2 => write_via_buf(&mut buf, &mut writer, slot).await?,
+            }
         }
         Ok(())
     }
@@ -437,6 +525,8 @@ fn initial_width(slots_len: usize, load_factor: f64) -> Option<NonZeroUsize> {
 enum Version {
     V0 = 0xdeadbeef,
     V1 = 0xdeadbeef + 1,
+    /// Adds the offsets of the blocks in the decompressed frames to the slots
+    V2 = 0xdeadbeef + 2,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -463,6 +553,20 @@ struct V1Header {
 struct OccupiedSlot {
     pub hash: NonMaximalU64,
     frame_offset: u64,
+    /// Offset of the block in the decompressed frame, [`UNKNOWN_DATA_OFFSET`] in
+    /// [`Version::V1`] indices
+    data_offset: u64,
+}
+
+const UNKNOWN_DATA_OFFSET: u64 = u64::MAX;
+
+impl OccupiedSlot {
+    fn location(&self) -> BlockLocation {
+        BlockLocation {
+            frame_offset: self.frame_offset,
+            data_offset: (self.data_offset != UNKNOWN_DATA_OFFSET).then_some(self.data_offset),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -477,9 +581,14 @@ impl Slot {
     fn into_raw(self) -> RawSlot {
         match self {
             Slot::Empty => RawSlot::EMPTY,
-            Slot::Occupied(OccupiedSlot { hash, frame_offset }) => RawSlot {
+            Slot::Occupied(OccupiedSlot {
+                hash,
+                frame_offset,
+                data_offset,
+            }) => RawSlot {
                 hash: hash.get(),
                 frame_offset,
+                data_offset,
             },
         }
     }
@@ -494,15 +603,37 @@ impl Slot {
 struct RawSlot {
     hash: u64,
     frame_offset: u64,
+    data_offset: u64,
 }
 
 impl RawSlot {
     const EMPTY: Self = Self {
         hash: u64::MAX,
         frame_offset: u64::MAX,
+        data_offset: u64::MAX,
     };
 }
 
+/// A [`Slot`] as it appears in [`Version::V1`] indices, without the offset of
+/// the block in the decompressed frame.
+struct V1Slot(Slot);
+
+/// Length of the slots of the given version of the index.
+fn slot_len(version: Version) -> u64 {
+    match version {
+        Version::V0 | Version::V1 => <V1Slot as Writeable>::LEN,
+        Version::V2 => <Slot as Writeable>::LEN,
+    }
+}
+
+/// Reads a slot of the given version of the index.
+fn read_slot(version: Version, reader: impl Read) -> io::Result<Slot> {
+    match version {
+        Version::V0 | Version::V1 => V1Slot::read_from(reader).map(|it| it.0),
+        Version::V2 => Slot::read_from(reader),
+    }
+}
+
 //////////////////////////////////////
 // De/serialization                 //
 // (Integers are all little-endian) //
@@ -531,11 +662,43 @@ impl Readable for Slot {
     where
         Self: Sized,
     {
-        let raw @ RawSlot { hash, frame_offset } = Readable::read_from(reader)?;
+        let raw @ RawSlot {
+            hash,
+            frame_offset,
+            data_offset,
+        } = Readable::read_from(reader)?;
         match NonMaximalU64::new(hash) {
-            Some(hash) => Ok(Self::Occupied(OccupiedSlot { hash, frame_offset })),
+            Some(hash) => Ok(Self::Occupied(OccupiedSlot {
+                hash,
+                frame_offset,
+                data_offset,
+            })),
             None => match raw == RawSlot::EMPTY {
                 true => Ok(Self::Empty),
+                false => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "empty slots must have offsets of u64::MAX",
+                )),
+            },
+        }
+    }
+}
+
+impl Readable for V1Slot {
+    fn read_from(mut reader: impl Read) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let hash = reader.read_u64::<LittleEndian>()?;
+        let frame_offset = reader.read_u64::<LittleEndian>()?;
+        match NonMaximalU64::new(hash) {
+            Some(hash) => Ok(Self(Slot::Occupied(OccupiedSlot {
+                hash,
+                frame_offset,
+                data_offset: UNKNOWN_DATA_OFFSET,
+            }))),
+            None => match frame_offset == u64::MAX {
+                true => Ok(Self(Slot::Empty)),
                 false => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     "empty slots must have a frame offset of u64::MAX",
@@ -545,6 +708,18 @@ impl Readable for Slot {
     }
 }
 
+impl Writeable for V1Slot {
+    fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let RawSlot {
+            hash, frame_offset, ..
+        } = self.0.into_raw();
+        writer.write_u64::<LittleEndian>(hash)?;
+        writer.write_u64::<LittleEndian>(frame_offset)?;
+        Ok(())
+    }
+    const LEN: u64 = std::mem::size_of::<u64>() as u64 * 2;
+}
+
 impl Writeable for Slot {
     fn write_to(&self, writer: impl Write) -> io::Result<()> {
         self.into_raw().write_to(writer)
@@ -560,18 +735,24 @@ impl Readable for RawSlot {
         Ok(Self {
             hash: reader.read_u64::<LittleEndian>()?,
             frame_offset: reader.read_u64::<LittleEndian>()?,
+            data_offset: reader.read_u64::<LittleEndian>()?,
         })
     }
 }
 
 impl Writeable for RawSlot {
     fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
-        let Self { hash, frame_offset } = *self;
+        let Self {
+            hash,
+            frame_offset,
+            data_offset,
+        } = *self;
         writer.write_u64::<LittleEndian>(hash)?;
         writer.write_u64::<LittleEndian>(frame_offset)?;
+        writer.write_u64::<LittleEndian>(data_offset)?;
         Ok(())
     }
-    const LEN: u64 = std::mem::size_of::<u64>() as u64 * 2;
+    const LEN: u64 = std::mem::size_of::<u64>() as u64 * 3;
 }
 
 impl Readable for V1Header {
@@ -684,7 +865,12 @@ mod tests {
         }))
         .unwrap();
         for (cid, expected) in reference {
-            let actual = subject.get(cid).unwrap().into_iter().collect();
+            let actual = subject
+                .get(cid)
+                .unwrap()
+                .into_iter()
+                .map(|it| it.frame_offset)
+                .collect();
             assert!(expected.is_subset(&actual)); // collisions
         }
     }
@@ -711,7 +897,12 @@ mod tests {
         }))
         .unwrap();
         for (hash, expected) in &reference {
-            let actual = subject.get_by_hash(*hash).unwrap().into_iter().collect();
+            let actual = subject
+                .get_by_hash(*hash)
+                .unwrap()
+                .into_iter()
+                .map(|it| it.frame_offset)
+                .collect();
             assert!(expected.is_subset(&actual)) // collisions
         }
 
@@ -732,7 +923,45 @@ mod tests {
         );
     }
 
+    /// [`Reader::get`] should return the data offsets of [`Version::V2`]
+    /// indices, and none for [`Version::V1`] indices.
+    fn do_block_locations(reference: HashMap<Cid, (u64, u64)>) {
+        let locations = reference
+            .iter()
+            .map(|(cid, (frame_offset, data_offset))| {
+                (
+                    *cid,
+                    BlockLocation {
+                        frame_offset: *frame_offset,
+                        data_offset: Some(*data_offset % UNKNOWN_DATA_OFFSET),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let write = |writer: Writer| {
+            write_to_vec(|v| {
+                let expected_len = writer.written_len();
+                block_on(writer.write_into(&mut *v))?;
+                assert_eq!(expected_len as usize, v.len());
+                Ok(())
+            })
+        };
+        let builder = || Builder::from_iter(locations.clone());
+        let v2 = Reader::new(write(builder().into_writer_with_block_offsets())).unwrap();
+        let v1 = Reader::new(write(builder().into_writer())).unwrap();
+        for (cid, location) in locations {
+            assert!(v2.get(cid).unwrap().contains(&location)); // collisions
+            assert!(v1.get(cid).unwrap().contains(&BlockLocation {
+                data_offset: None,
+                ..location
+            }));
+        }
+    }
+
     quickcheck::quickcheck! {
+        fn block_locations(reference: HashMap<Cid, (u64, u64)>) -> () {
+            do_block_locations(reference)
+        }
         fn hashmap_of_cids(reference: HashMap<Cid, HashSet<u64>>) -> () {
             do_hashmap_of_cids(reference)
         }
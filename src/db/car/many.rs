@@ -8,11 +8,12 @@
 //!
 //! A single z-frame cache is shared between all read-only stores.
 
-use super::{AnyCar, ZstdFrameCache};
+use super::forest::cid_hash;
+use super::{AnyCar, CidBloomFilter, ZstdFrameCache};
 use crate::blocks::TipsetKey;
 use crate::db::{
     BlockstoreWriteOpsSubscribable, EthMappingsStore, MemoryDB, PersistentStore, SettingsStore,
-    SettingsStoreExt,
+    SettingsStoreExt, StateProvenance, StateProvenanceStore,
 };
 use crate::libp2p_bitswap::BitswapStoreReadWrite;
 use crate::rpc::eth::types::EthHash;
@@ -23,23 +24,30 @@ use crate::{blocks::Tipset, libp2p_bitswap::BitswapStoreRead};
 use anyhow::Context as _;
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
+use itertools::Itertools as _;
 use parking_lot::RwLock;
+use rayon::prelude::*;
 use std::cmp::Ord;
 use std::collections::BinaryHeap;
 use std::{path::PathBuf, sync::Arc};
 
 struct WithHeaviestEpoch {
     pub car: AnyCar<Box<dyn super::RandomAccessFileReader>>,
+    /// Filter of the blocks of the store, to skip the lookups of missing blocks
+    bloom: Option<CidBloomFilter>,
     epoch: ChainEpoch,
 }
 
 impl WithHeaviestEpoch {
-    pub fn new(car: AnyCar<Box<dyn super::RandomAccessFileReader>>) -> anyhow::Result<Self> {
+    pub fn new(
+        car: AnyCar<Box<dyn super::RandomAccessFileReader>>,
+        bloom: Option<CidBloomFilter>,
+    ) -> anyhow::Result<Self> {
         let epoch = car
             .heaviest_tipset()
             .context("store doesn't have a heaviest tipset")?
             .epoch();
-        Ok(Self { car, epoch })
+        Ok(Self { car, bloom, epoch })
     }
 }
 
@@ -101,6 +109,14 @@ impl<WriterT> ManyCar<WriterT> {
     pub fn read_only<ReaderT: super::RandomAccessFileReader>(
         &self,
         any_car: AnyCar<ReaderT>,
+    ) -> anyhow::Result<()> {
+        self.read_only_with_bloom(any_car, None)
+    }
+
+    fn read_only_with_bloom<ReaderT: super::RandomAccessFileReader>(
+        &self,
+        any_car: AnyCar<ReaderT>,
+        bloom: Option<CidBloomFilter>,
     ) -> anyhow::Result<()> {
         let mut read_only = self.read_only.write();
         let key = read_only.len() as u64;
@@ -109,6 +125,7 @@ impl<WriterT> ManyCar<WriterT> {
             any_car
                 .with_cache(self.shared_cache.clone(), key)
                 .into_dyn(),
+            bloom,
         )?);
 
         Ok(())
@@ -123,8 +140,33 @@ impl<WriterT> ManyCar<WriterT> {
     }
 
     pub fn read_only_files(&self, files: impl Iterator<Item = PathBuf>) -> anyhow::Result<()> {
-        for file in files {
-            self.read_only(AnyCar::new(EitherMmapOrRandomAccessFile::open(file)?)?)?;
+        self.read_only_files_with_progress(files, |_| {})
+    }
+
+    /// Opens the files on multiple threads, calling `progress` with the number of bytes decoded
+    /// and indexed as opening goes. Opening `.car` and `.car.zst` files requires reading them
+    /// entirely, see [`AnyCar::new`]. The bloom filters of the files are loaded, or built and
+    /// persisted next to them, see [`CidBloomFilter::load_or_build`].
+    pub fn read_only_files_with_progress(
+        &self,
+        files: impl Iterator<Item = PathBuf>,
+        progress: impl Fn(u64) + Sync,
+    ) -> anyhow::Result<()> {
+        let cars = files
+            .collect_vec()
+            .into_par_iter()
+            .map(|file| {
+                let car = AnyCar::new_with_progress(
+                    EitherMmapOrRandomAccessFile::open(&file)?,
+                    &progress,
+                )
+                .with_context(|| format!("failed to open {}", file.display()))?;
+                let bloom = CidBloomFilter::load_or_build(&file, &car)?;
+                anyhow::Ok((car, bloom))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for (car, bloom) in cars {
+            self.read_only_with_bloom(car, Some(bloom))?;
         }
 
         Ok(())
@@ -174,7 +216,15 @@ impl<WriterT: Blockstore> Blockstore for ManyCar<WriterT> {
         if let Ok(Some(value)) = self.writer.get(k) {
             return Ok(Some(value));
         }
+        let hash = cid_hash(k);
         for reader in self.read_only.read().iter() {
+            if reader
+                .bloom
+                .as_ref()
+                .is_some_and(|bloom| !bloom.might_contain(hash))
+            {
+                continue;
+            }
             if let Some(val) = reader.car.get(k)? {
                 return Ok(Some(val));
             }
@@ -185,6 +235,15 @@ impl<WriterT: Blockstore> Blockstore for ManyCar<WriterT> {
     fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
         self.writer.put_keyed(k, block)
     }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        self.writer.put_many_keyed(blocks)
+    }
 }
 
 impl<WriterT: PersistentStore> PersistentStore for ManyCar<WriterT> {
@@ -224,6 +283,10 @@ impl<WriterT: SettingsStore> SettingsStore for ManyCar<WriterT> {
         SettingsStore::exists(self.writer(), key)
     }
 
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        SettingsStore::delete(self.writer(), key)
+    }
+
     fn setting_keys(&self) -> anyhow::Result<Vec<String>> {
         SettingsStore::setting_keys(self.writer())
     }
@@ -251,6 +314,20 @@ impl<WriterT: EthMappingsStore> EthMappingsStore for ManyCar<WriterT> {
     }
 }
 
+impl<WriterT: StateProvenanceStore> StateProvenanceStore for ManyCar<WriterT> {
+    fn read_state_provenance(&self, state_root: &Cid) -> anyhow::Result<Option<StateProvenance>> {
+        StateProvenanceStore::read_state_provenance(self.writer(), state_root)
+    }
+
+    fn write_state_provenance(
+        &self,
+        state_root: &Cid,
+        provenance: &StateProvenance,
+    ) -> anyhow::Result<()> {
+        StateProvenanceStore::write_state_provenance(self.writer(), state_root, provenance)
+    }
+}
+
 impl<T: Blockstore + SettingsStore> super::super::HeaviestTipsetKeyProvider for ManyCar<T> {
     fn heaviest_tipset_key(&self) -> anyhow::Result<TipsetKey> {
         match SettingsStoreExt::read_obj::<TipsetKey>(self, crate::db::setting_keys::HEAD_KEY)? {
@@ -302,6 +379,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn many_car_files_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [mainnet::DEFAULT_GENESIS, calibnet::DEFAULT_GENESIS]
+            .iter()
+            .enumerate()
+            .map(|(i, genesis)| {
+                let path = dir.path().join(format!("{i}.car"));
+                std::fs::write(&path, genesis).unwrap();
+                path
+            })
+            .collect_vec();
+        let indexed = std::sync::atomic::AtomicU64::new(0);
+        let many = ManyCar::new(MemoryDB::default());
+        many.read_only_files_with_progress(files.into_iter(), |n| {
+            indexed.fetch_add(n, std::sync::atomic::Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(many.len(), 2);
+        assert_eq!(
+            indexed.into_inner(),
+            (mainnet::DEFAULT_GENESIS.len() + calibnet::DEFAULT_GENESIS.len()) as u64
+        );
+    }
+
     #[test]
     fn many_car_calibnet_heaviest() {
         let many = ManyCar::try_from(AnyCar::try_from(calibnet::DEFAULT_GENESIS).unwrap()).unwrap();
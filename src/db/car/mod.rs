@@ -1,11 +1,13 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 mod any;
+mod bloom;
 pub mod forest;
 mod many;
 pub mod plain;
 
 pub use any::AnyCar;
+pub use bloom::{BLOOM_FILE_EXTENSION, CidBloomFilter};
 pub use forest::ForestCar;
 use get_size2::GetSize as _;
 pub use many::ManyCar;
@@ -14,6 +16,7 @@ pub use plain::PlainCar;
 use cid::Cid;
 use positioned_io::{ReadAt, Size};
 use std::{
+    any::Any,
     num::NonZeroUsize,
     sync::{
         LazyLock,
@@ -21,10 +24,35 @@ use std::{
     },
 };
 
-use crate::utils::{cache::SizeTrackingLruCache, get_size::CidWrapper};
+use crate::utils::{
+    cache::SizeTrackingLruCache,
+    get_size::CidWrapper,
+    io::{EitherMmapOrRandomAccessFile, Mmap},
+    misc::memory_budget::scale_to_memory_budget,
+};
 
-pub trait RandomAccessFileReader: ReadAt + Size + Send + Sync + 'static {}
-impl<X: ReadAt + Size + Send + Sync + 'static> RandomAccessFileReader for X {}
+pub trait RandomAccessFileReader: ReadAt + Size + Send + Sync + 'static {
+    fn as_any(&self) -> &dyn Any;
+}
+impl<X: ReadAt + Size + Send + Sync + 'static> RandomAccessFileReader for X {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Returns the bytes of the reader if it is memory-mapped with the zero-copy read path enabled,
+/// see [`Mmap::zero_copy_bytes`].
+fn zero_copy_bytes(reader: &dyn Any) -> Option<&[u8]> {
+    if let Some(mmap) = reader.downcast_ref::<Mmap>() {
+        mmap.zero_copy_bytes()
+    } else if let Some(EitherMmapOrRandomAccessFile::Mmap(mmap)) = reader.downcast_ref() {
+        mmap.zero_copy_bytes()
+    } else if let Some(reader) = reader.downcast_ref::<Box<dyn RandomAccessFileReader>>() {
+        zero_copy_bytes((**reader).as_any())
+    } else {
+        None
+    }
+}
 
 /// Multiple `.forest.car.zst` archives may use the same cache, each with a
 /// unique cache key.
@@ -48,8 +76,8 @@ pub static ZSTD_FRAME_CACHE_DEFAULT_MAX_SIZE: LazyLock<usize> = LazyLock::new(||
             );
         }
     }
-    // 256 MiB
-    256 * 1024 * 1024
+    // 256 MiB at the reference memory budget
+    scale_to_memory_budget(256 * 1024 * 1024, 32 * 1024 * 1024, 4 * 1024 * 1024 * 1024)
 });
 
 pub struct ZstdFrameCache {
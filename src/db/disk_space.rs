@@ -0,0 +1,196 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Guard against running out of disk space, which corrupts `ParityDb` mid-write. A monitor
+//! periodically checks the free space of the volume of the database, warns when it falls under
+//! the thresholds of the configuration, and under the critical threshold pauses the snapshot
+//! exports, and optionally chain sync, until space is freed.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// Disk space guard configuration exposed in Forest.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct DiskSpaceConfig {
+    /// Free space of the database volume, in bytes, under which a warning is logged
+    pub warning_threshold_bytes: u64,
+    /// Free space of the database volume, in bytes, under which snapshot exports are paused
+    pub critical_threshold_bytes: u64,
+    /// Pause chain sync as well under the critical threshold
+    pub pause_sync: bool,
+    /// Interval between two checks, in seconds, use 0 to disable the monitor
+    pub check_interval_secs: u64,
+}
+
+impl Default for DiskSpaceConfig {
+    fn default() -> Self {
+        Self {
+            warning_threshold_bytes: 20 * GIB,
+            critical_threshold_bytes: 5 * GIB,
+            pause_sync: false,
+            check_interval_secs: 30,
+        }
+    }
+}
+
+impl DiskSpaceConfig {
+    fn level(&self, available_bytes: u64) -> DiskSpaceLevel {
+        if available_bytes < self.critical_threshold_bytes {
+            DiskSpaceLevel::Critical
+        } else if available_bytes < self.warning_threshold_bytes {
+            DiskSpaceLevel::Warning
+        } else {
+            DiskSpaceLevel::Ok
+        }
+    }
+}
+
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, strum::Display,
+)]
+pub enum DiskSpaceLevel {
+    /// Free space is above the warning threshold.
+    #[default]
+    Ok,
+    /// Free space is under the warning threshold.
+    Warning,
+    /// Free space is under the critical threshold, snapshot exports are paused.
+    Critical,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiskSpaceStatus {
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    pub level: DiskSpaceLevel,
+    /// Chain sync is paused under the critical threshold
+    pub pause_sync: bool,
+}
+
+impl DiskSpaceStatus {
+    pub fn exports_paused(&self) -> bool {
+        self.level == DiskSpaceLevel::Critical
+    }
+
+    pub fn sync_paused(&self) -> bool {
+        self.pause_sync && self.exports_paused()
+    }
+}
+
+/// Latest status of the disk space, `None` until the monitor checked it.
+static STATUS: LazyLock<watch::Sender<Option<DiskSpaceStatus>>> =
+    LazyLock::new(|| watch::Sender::new(None));
+
+/// Returns the latest status of the disk space, if the monitor is running.
+pub fn status() -> Option<DiskSpaceStatus> {
+    STATUS.borrow().clone()
+}
+
+fn update(status: DiskSpaceStatus) {
+    let available = human_bytes::human_bytes(status.available_bytes as f64);
+    let previous = STATUS.send_replace(Some(status.clone()));
+    if previous.map(|previous| previous.level).unwrap_or_default() == status.level {
+        return;
+    }
+    match status.level {
+        DiskSpaceLevel::Ok => tracing::info!("Free disk space is back to {available}"),
+        DiskSpaceLevel::Warning => tracing::warn!("Free disk space is low: {available}"),
+        DiskSpaceLevel::Critical => tracing::error!(
+            "Free disk space is critically low: {available}. Snapshot exports{} are paused until space is freed",
+            if status.pause_sync {
+                " and chain sync"
+            } else {
+                ""
+            }
+        ),
+    }
+}
+
+/// Checks the free space of the volume of the path.
+fn check(path: &Path, config: &DiskSpaceConfig) -> std::io::Result<DiskSpaceStatus> {
+    let available_bytes = fs4::available_space(path)?;
+    Ok(DiskSpaceStatus {
+        available_bytes,
+        total_bytes: fs4::total_space(path)?,
+        level: config.level(available_bytes),
+        pause_sync: config.pause_sync,
+    })
+}
+
+/// Checks the free space of the volume of the database at the interval of the configuration.
+pub async fn run_monitor(path: PathBuf, config: DiskSpaceConfig) -> anyhow::Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.check_interval_secs));
+    loop {
+        interval.tick().await;
+        match check(&path, &config) {
+            Ok(status) => update(status),
+            Err(e) => tracing::warn!("Failed to check the free space of {}: {e}", path.display()),
+        }
+    }
+}
+
+/// Drives the future, pausing it while `paused` holds for the disk space status.
+pub async fn pause_while<F: Future>(
+    paused: impl Fn(&DiskSpaceStatus) -> bool,
+    future: F,
+) -> F::Output {
+    let mut rx = STATUS.subscribe();
+    let mut future = std::pin::pin!(future);
+    loop {
+        if rx.borrow_and_update().as_ref().is_some_and(&paused) {
+            // The sender is never dropped.
+            let _ = rx.changed().await;
+            continue;
+        }
+        tokio::select! {
+            output = &mut future => return output,
+            _ = rx.changed() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn disk_space_levels() {
+        let config = DiskSpaceConfig::default();
+        assert_eq!(config.level(30 * GIB), DiskSpaceLevel::Ok);
+        assert_eq!(config.level(10 * GIB), DiskSpaceLevel::Warning);
+        assert_eq!(config.level(GIB), DiskSpaceLevel::Critical);
+    }
+
+    // The disk space status is global.
+    #[tokio::test]
+    #[serial]
+    async fn pause_on_critical_level() {
+        let critical = DiskSpaceStatus {
+            level: DiskSpaceLevel::Critical,
+            ..Default::default()
+        };
+        update(critical.clone());
+        assert!(status().unwrap().exports_paused());
+        assert!(!status().unwrap().sync_paused());
+
+        let paused = tokio::spawn(pause_while(DiskSpaceStatus::exports_paused, async { 42 }));
+        let not_paused = pause_while(DiskSpaceStatus::sync_paused, async { 7 }).await;
+        assert_eq!(not_paused, 7);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!paused.is_finished());
+
+        update(DiskSpaceStatus::default());
+        assert_eq!(paused.await.unwrap(), 42);
+    }
+}
@@ -32,6 +32,8 @@
 //! ## Scheduling
 //! When automatic GC is enabled, it by default runs every 7 days (20160 epochs).
 //! The interval can be overridden by setting environment variable `FOREST_SNAPSHOT_GC_INTERVAL_EPOCHS`.
+//! Whether it is due is checked by the [`EpochScheduler`] every 10 epochs, which can be overridden
+//! by setting environment variable `FOREST_SNAPSHOT_GC_CHECK_INTERVAL_EPOCHS`.
 //!
 //! ## Performance
 //! The lite snapshot export step is currently utilizing a depth-first search algorithm, with `O(V+E)` complexity,
@@ -43,12 +45,13 @@
 //!
 
 use crate::blocks::{Tipset, TipsetKey};
-use crate::chain::ExportOptions;
+use crate::chain::{ExportOptions, scheduler::EpochScheduler};
 use crate::cli_shared::chain_path;
 use crate::db::car::forest::new_forest_car_temp_path_in;
 use crate::db::{
     BlockstoreWriteOpsSubscribable, CAR_DB_DIR_NAME, HeaviestTipsetKeyProvider, SettingsStore,
     db_engine::{DbConfig, db_root, open_db},
+    disk_space::{self, DiskSpaceStatus},
     parity_db::{DbColumn, ParityDb},
 };
 use crate::shim::clock::{ChainEpoch, EPOCHS_IN_DAY};
@@ -70,6 +73,7 @@ pub struct SnapshotGarbageCollector<DB> {
     db_root_dir: PathBuf,
     car_db_dir: PathBuf,
     recent_state_roots: i64,
+    archive: bool,
     db_config: DbConfig,
     running: AtomicBool,
     blessed_lite_snapshot: RwLock<Option<PathBuf>>,
@@ -121,6 +125,7 @@ where
                 db_root_dir,
                 car_db_dir,
                 recent_state_roots,
+                archive: config.sync.archive,
                 db_config: config.db_config().clone(),
                 running: AtomicBool::new(false),
                 blessed_lite_snapshot: RwLock::new(None),
@@ -165,7 +170,12 @@ where
         }
     }
 
-    pub async fn scheduler_loop(&self) {
+    /// Registers the periodic check of whether the GC is due on the epoch scheduler.
+    pub fn schedule(self: &Arc<Self>, scheduler: &mut EpochScheduler) {
+        if self.archive {
+            tracing::info!("Snapshot GC scheduler is disabled in archive mode");
+            return;
+        }
         let snap_gc_interval_epochs = std::env::var("FOREST_SNAPSHOT_GC_INTERVAL_EPOCHS")
             .ok()
             .and_then(|i| i.parse().ok())
@@ -175,44 +185,53 @@ where
                 )
             })
             .unwrap_or(EPOCHS_IN_DAY * 7);
-        let snap_gc_check_interval_secs = std::env::var("FOREST_SNAPSHOT_GC_CHECK_INTERVAL_SECONDS")
+        let snap_gc_check_interval_epochs = std::env::var("FOREST_SNAPSHOT_GC_CHECK_INTERVAL_EPOCHS")
             .ok()
             .and_then(|i| i.parse().ok())
             .inspect(|i| {
                 tracing::info!(
-                    "Using snapshot GC check interval seconds {i} set by FOREST_SNAPSHOT_GC_CHECK_INTERVAL_SECONDS"
+                    "Using snapshot GC check interval epochs {i} set by FOREST_SNAPSHOT_GC_CHECK_INTERVAL_EPOCHS"
                 )
             })
-            .unwrap_or(60 * 5);
-        let snap_gc_check_interval = Duration::from_secs(snap_gc_check_interval_secs);
+            .unwrap_or(10);
         tracing::info!(
             "Running snapshot GC scheduler with interval epochs {snap_gc_interval_epochs}"
         );
-        loop {
-            if !self.running.load(Ordering::Relaxed)
-                && let Some(car_db_head_epoch) = *self.car_db_head_epoch.read()
-                && let Some(sync_status) = &*self.sync_status.read()
+        let this = self.clone();
+        scheduler.every("snapshot GC", snap_gc_check_interval_epochs, move |_| {
+            this.maybe_schedule(snap_gc_interval_epochs)
+        });
+    }
+
+    /// Triggers the GC if the chain is in sync and the head is far enough from the CAR database
+    /// head.
+    fn maybe_schedule(&self, snap_gc_interval_epochs: ChainEpoch) {
+        if !self.running.load(Ordering::Relaxed)
+            && let Some(car_db_head_epoch) = *self.car_db_head_epoch.read()
+            && let Some(sync_status) = &*self.sync_status.read()
+        {
+            let sync_status = &*sync_status.read();
+            let network_head_epoch = sync_status.network_head_epoch;
+            let head_epoch = sync_status.current_head_epoch;
+            if head_epoch > 0 // sync_status has been initialized
+                && head_epoch <= network_head_epoch // head epoch is within a sane range
+                && sync_status.is_synced() // chain is in sync
+                && sync_status.active_forks.is_empty() // no active fork
+                && head_epoch - car_db_head_epoch >= snap_gc_interval_epochs // the gap between chain head and car_db head is above threshold
+                && self.trigger_tx.try_send(()).is_ok()
             {
-                let sync_status = &*sync_status.read();
-                let network_head_epoch = sync_status.network_head_epoch;
-                let head_epoch = sync_status.current_head_epoch;
-                if head_epoch > 0 // sync_status has been initialized
-                    && head_epoch <= network_head_epoch // head epoch is within a sane range
-                    && sync_status.is_synced() // chain is in sync
-                    && sync_status.active_forks.is_empty() // no active fork
-                    && head_epoch - car_db_head_epoch >= snap_gc_interval_epochs // the gap between chain head and car_db head is above threshold
-                    && self.trigger_tx.try_send(()).is_ok()
-                {
-                    tracing::info!(%car_db_head_epoch, %head_epoch, %network_head_epoch, %snap_gc_interval_epochs, "Snap GC scheduled");
-                } else {
-                    tracing::debug!(%car_db_head_epoch, %head_epoch, %network_head_epoch, %snap_gc_interval_epochs, "Snap GC not scheduled");
-                }
+                tracing::info!(%car_db_head_epoch, %head_epoch, %network_head_epoch, %snap_gc_interval_epochs, "Snap GC scheduled");
+            } else {
+                tracing::debug!(%car_db_head_epoch, %head_epoch, %network_head_epoch, %snap_gc_interval_epochs, "Snap GC not scheduled");
             }
-            tokio::time::sleep(snap_gc_check_interval).await;
         }
     }
 
     pub fn trigger(&self) -> anyhow::Result<flume::Receiver<()>> {
+        if self.archive {
+            anyhow::bail!("snap gc is disabled in archive mode");
+        }
+
         if self.running.load(Ordering::Relaxed) {
             anyhow::bail!("snap gc has already been running");
         }
@@ -244,14 +263,17 @@ where
             map
         });
         let start = Instant::now();
-        let (head_ts, _) = crate::chain::export_from_head::<Sha256>(
-            &db,
-            self.recent_state_roots,
-            file,
-            Some(ExportOptions {
-                skip_checksum: true,
-                ..Default::default()
-            }),
+        let (head_ts, _) = disk_space::pause_while(
+            DiskSpaceStatus::exports_paused,
+            crate::chain::export_from_head::<Sha256>(
+                &db,
+                self.recent_state_roots,
+                file,
+                Some(ExportOptions {
+                    skip_checksum: true,
+                    ..Default::default()
+                }),
+            ),
         )
         .await?;
         let target_path = self.car_db_dir.join(format!(
@@ -302,9 +324,11 @@ where
             && blessed_lite_snapshot.is_file()
         {
             let mut opts = ParityDb::to_options(self.db_root_dir.clone(), &self.db_config);
+            // The receipts and events columns are kept, they are pruned by their own collector.
             for col in [
                 DbColumn::GraphDagCborBlake2b256 as u8,
                 DbColumn::GraphFull as u8,
+                DbColumn::GraphMessages as u8,
             ] {
                 let start = Instant::now();
                 tracing::info!("pruning parity-db column {col}...");
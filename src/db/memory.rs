@@ -1,7 +1,9 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{EthMappingsStore, SettingsStore, SettingsStoreExt};
+use super::{
+    EthMappingsStore, SettingsStore, SettingsStoreExt, StateProvenance, StateProvenanceStore,
+};
 use crate::blocks::TipsetKey;
 use crate::db::PersistentStore;
 use crate::libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
@@ -21,6 +23,7 @@ pub struct MemoryDB {
     blockchain_persistent_db: RwLock<HashMap<Cid, Vec<u8>>>,
     settings_db: RwLock<HashMap<String, Vec<u8>>>,
     pub eth_mappings_db: RwLock<HashMap<EthHash, Vec<u8>>>,
+    state_provenance_db: RwLock<HashMap<Cid, StateProvenance>>,
 }
 
 impl MemoryDB {
@@ -70,6 +73,11 @@ impl SettingsStore for MemoryDB {
         Ok(self.settings_db.read().contains_key(key))
     }
 
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.settings_db.write().remove(key);
+        Ok(())
+    }
+
     fn setting_keys(&self) -> anyhow::Result<Vec<String>> {
         Ok(self.settings_db.read().keys().cloned().collect_vec())
     }
@@ -111,6 +119,23 @@ impl EthMappingsStore for MemoryDB {
     }
 }
 
+impl StateProvenanceStore for MemoryDB {
+    fn read_state_provenance(&self, state_root: &Cid) -> anyhow::Result<Option<StateProvenance>> {
+        Ok(self.state_provenance_db.read().get(state_root).cloned())
+    }
+
+    fn write_state_provenance(
+        &self,
+        state_root: &Cid,
+        provenance: &StateProvenance,
+    ) -> anyhow::Result<()> {
+        self.state_provenance_db
+            .write()
+            .insert(*state_root, provenance.clone());
+        Ok(())
+    }
+}
+
 impl Blockstore for MemoryDB {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
         Ok(self.blockchain_db.read().get(k).cloned().or(self
@@ -124,6 +149,19 @@ impl Blockstore for MemoryDB {
         self.blockchain_db.write().insert(*k, block.to_vec());
         Ok(())
     }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        let mut db = self.blockchain_db.write();
+        for (k, block) in blocks {
+            db.insert(k, block.as_ref().to_vec());
+        }
+        Ok(())
+    }
 }
 
 impl PersistentStore for MemoryDB {
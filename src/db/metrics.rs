@@ -0,0 +1,42 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use prometheus_client::metrics::{counter::Counter, histogram::Histogram};
+use std::sync::LazyLock;
+
+pub static WRITE_BUFFER_FLUSH_TIME: LazyLock<Histogram> = LazyLock::new(|| {
+    let metric = crate::metrics::default_histogram();
+    crate::metrics::default_registry().register(
+        "db_write_buffer_flush_time",
+        "Duration of write buffer flushes to the database",
+        metric.clone(),
+    );
+    metric
+});
+pub static WRITE_BUFFER_FLUSH_WAIT_TIME: LazyLock<Histogram> = LazyLock::new(|| {
+    let metric = crate::metrics::default_histogram();
+    crate::metrics::default_registry().register(
+        "db_write_buffer_flush_wait_time",
+        "Duration writers were blocked waiting for an in-flight write buffer flush",
+        metric.clone(),
+    );
+    metric
+});
+pub static WRITE_BUFFER_FLUSHED_BYTES: LazyLock<Counter> = LazyLock::new(|| {
+    let metric = Counter::default();
+    crate::metrics::default_registry().register(
+        "db_write_buffer_flushed_bytes",
+        "Total number of bytes flushed from write buffers to the database",
+        metric.clone(),
+    );
+    metric
+});
+pub static QUARANTINED_BLOCKS: LazyLock<Counter> = LazyLock::new(|| {
+    let metric = Counter::default();
+    crate::metrics::default_registry().register(
+        "db_quarantined_blocks",
+        "Number of blocks read from the database whose data didn't match their CID",
+        metric.clone(),
+    );
+    metric
+});
@@ -0,0 +1,207 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Migration logic for databases with the v0.32.1 schema to v0.33.0.
+//! The `GraphMessages`, `GraphReceipts`, `GraphEvents` and `StateProvenance` columns have been
+//! appended to the database, the existing columns are left untouched.
+
+use super::migration_map::MigrationOperation;
+use crate::Config;
+use crate::db::migration::migration_map::MigrationOperationExt as _;
+use anyhow::{Context, anyhow};
+use semver::Version;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+pub(super) struct Migration0_32_1_0_33_0 {
+    from: Version,
+    to: Version,
+}
+
+/// Migrates the database from version 0.32.1 to 0.33.0
+impl MigrationOperation for Migration0_32_1_0_33_0 {
+    fn new(from: Version, to: Version) -> Self
+    where
+        Self: Sized,
+    {
+        Self { from, to }
+    }
+
+    fn from(&self) -> &Version {
+        &self.from
+    }
+
+    fn to(&self) -> &Version {
+        &self.to
+    }
+
+    fn migrate_core(&self, chain_data_path: &Path, _: &Config) -> anyhow::Result<PathBuf> {
+        let old_db = self.old_db_path(chain_data_path);
+        let temp_db = self.temporary_db_path(chain_data_path);
+
+        info!(
+            "Renaming database directory from {} to {}",
+            old_db.display(),
+            temp_db.display()
+        );
+        std::fs::rename(&old_db, &temp_db).context("failed to rename database directory")?;
+
+        // Create a placeholder so the delete step succeeds
+        std::fs::create_dir_all(&old_db).context("failed to create placeholder directory")?;
+
+        add_missing_columns(&temp_db)?;
+
+        info!("Migration completed successfully");
+        Ok(temp_db)
+    }
+}
+
+/// Appends the columns of the v0.33.0 schema missing from the database at `path`. The databases
+/// created by development builds may already have some of them.
+fn add_missing_columns(path: &Path) -> anyhow::Result<()> {
+    let opts = paritydb_0_33_0::to_options(path.to_owned());
+    let existing = parity_db::Options::load_metadata(path)
+        .map_err(|e| anyhow!("error loading database metadata: {e}"))?
+        .context("database metadata not found")?
+        .columns
+        .len();
+    let mut current = parity_db::Options {
+        columns: opts.columns.iter().take(existing).cloned().collect(),
+        ..opts.clone()
+    };
+    for (index, column) in opts.columns.iter().enumerate().skip(existing) {
+        info!("Adding column {index} to database");
+        parity_db::Db::add_column(&mut current, column.clone())
+            .map_err(|e| anyhow!("error adding column {index}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Database settings from Forest `v0.33.0`
+mod paritydb_0_33_0 {
+    use parity_db::{CompressionType, Options};
+    use std::path::PathBuf;
+    use strum::{Display, EnumIter, IntoEnumIterator};
+
+    #[derive(Copy, Clone, Debug, PartialEq, EnumIter, Display)]
+    #[repr(u8)]
+    pub(super) enum DbColumn {
+        GraphDagCborBlake2b256,
+        GraphFull,
+        Settings,
+        EthMappings,
+        PersistentGraph,
+        GraphMessages,
+        GraphReceipts,
+        GraphEvents,
+        StateProvenance,
+    }
+
+    impl DbColumn {
+        fn create_column_options(compression: CompressionType) -> Vec<parity_db::ColumnOptions> {
+            DbColumn::iter()
+                .map(|col| {
+                    match col {
+                        DbColumn::GraphDagCborBlake2b256
+                        | DbColumn::PersistentGraph
+                        | DbColumn::GraphMessages => parity_db::ColumnOptions {
+                            preimage: true,
+                            compression,
+                            ..Default::default()
+                        },
+                        DbColumn::GraphFull => parity_db::ColumnOptions {
+                            preimage: true,
+                            // This is needed for key retrieval.
+                            btree_index: true,
+                            compression,
+                            ..Default::default()
+                        },
+                        DbColumn::Settings => {
+                            parity_db::ColumnOptions {
+                                // explicitly disable preimage for settings column
+                                // othewise we are not able to overwrite entries
+                                preimage: false,
+                                // This is needed for key retrieval.
+                                btree_index: true,
+                                compression,
+                                ..Default::default()
+                            }
+                        }
+                        DbColumn::EthMappings | DbColumn::StateProvenance => {
+                            parity_db::ColumnOptions {
+                                preimage: false,
+                                btree_index: false,
+                                compression,
+                                ..Default::default()
+                            }
+                        }
+                        DbColumn::GraphReceipts | DbColumn::GraphEvents => {
+                            parity_db::ColumnOptions {
+                                preimage: true,
+                                ref_counted: true,
+                                compression,
+                                ..Default::default()
+                            }
+                        }
+                    }
+                })
+                .collect()
+        }
+    }
+
+    pub(super) fn to_options(path: PathBuf) -> Options {
+        Options {
+            path,
+            sync_wal: true,
+            sync_data: true,
+            stats: false,
+            salt: None,
+            columns: DbColumn::create_column_options(CompressionType::Lz4),
+            compression_threshold: [(0, 128)].into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::parity_db::{DbColumn, ParityDb};
+    use crate::db::parity_db_config::ParityDbConfig;
+    use crate::utils::multihash::prelude::*;
+    use cid::Cid;
+    use fvm_ipld_blockstore::Blockstore as _;
+    use fvm_ipld_encoding::DAG_CBOR;
+
+    #[test]
+    fn add_missing_columns_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paritydb");
+        let mut opts = paritydb_0_33_0::to_options(path.clone());
+        opts.columns
+            .truncate(paritydb_0_33_0::DbColumn::PersistentGraph as usize + 1);
+        let cid = Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(b"Cthulhu"));
+        {
+            let db = parity_db::Db::open_or_create(&opts).unwrap();
+            db.commit([(
+                DbColumn::GraphDagCborBlake2b256 as u8,
+                cid.to_bytes(),
+                Some(b"Cthulhu".to_vec()),
+            )])
+            .unwrap();
+        }
+
+        add_missing_columns(&path).unwrap();
+        // Adding the columns again is a no-op
+        add_missing_columns(&path).unwrap();
+
+        assert!(ParityDb::orphaned_columns(&path).unwrap().is_empty());
+        let db = ParityDb::open(path, &ParityDbConfig::default()).unwrap();
+        assert_eq!(
+            db.get(&cid).unwrap().as_deref(),
+            Some(b"Cthulhu".as_slice())
+        );
+        crate::db::with_block_kind(crate::db::BlockKind::Event, || {
+            db.put_keyed(&cid, b"Cthulhu").unwrap();
+        });
+    }
+}
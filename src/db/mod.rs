@@ -1,17 +1,25 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod block_kind;
+mod blockstore_with_memory_overlay;
 mod blockstore_with_read_cache;
 mod blockstore_with_write_buffer;
 pub mod car;
+pub mod disk_space;
 mod memory;
+pub mod metrics;
 pub mod parity_db;
 pub mod parity_db_config;
 
 pub mod gc;
 pub mod ttl;
+pub use block_kind::{
+    BlockKind, current_block_kind, receipts_marker, with_block_kind, with_optional_block_kind,
+};
+pub use blockstore_with_memory_overlay::BlockstoreWithMemoryOverlay;
 pub use blockstore_with_read_cache::*;
-pub use blockstore_with_write_buffer::BlockstoreWithWriteBuffer;
+pub use blockstore_with_write_buffer::{BlockstoreWithWriteBuffer, WriteBufferConfig};
 pub use memory::MemoryDB;
 mod db_mode;
 mod either;
@@ -20,9 +28,11 @@ pub use either::Either;
 
 use crate::blocks::TipsetKey;
 use crate::rpc::eth::types::EthHash;
+use crate::shim::clock::ChainEpoch;
 use anyhow::{Context as _, bail};
 use cid::Cid;
 pub use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+use fvm_ipld_encoding::tuple::*;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::sync::Arc;
@@ -34,6 +44,18 @@ pub mod setting_keys {
     pub const HEAD_KEY: &str = "head";
     /// Key used to store the memory pool configuration in the settings store.
     pub const MPOOL_CONFIG_KEY: &str = "/mpool/config";
+    /// Key used to store the most recently computed tipset states in the settings store.
+    pub const TIPSET_STATES_KEY: &str = "/state_manager/tipset_states";
+    /// Key used to store the epoch up to which receipts have been pruned in the settings store.
+    pub const RECEIPTS_PRUNED_EPOCH_KEY: &str = "/ttl/receipts_pruned_epoch";
+    /// Key used to store the epoch up to which events have been pruned in the settings store.
+    pub const EVENTS_PRUNED_EPOCH_KEY: &str = "/ttl/events_pruned_epoch";
+    /// Key used to store the progress of the chain sync in the settings store.
+    pub const SYNC_CHECKPOINT_KEY: &str = "/chain_sync/checkpoint";
+    /// Key used to store the hints to warm a restarted node up in the settings store.
+    pub const WARM_RESTART_HINTS_KEY: &str = "/chain/warm_restart_hints";
+    /// Key used to store the installed Ethereum filters and their cursors in the settings store.
+    pub const ETH_FILTERS_KEY: &str = "/rpc/eth_filters";
 }
 
 /// Interface used to store and retrieve settings from the database.
@@ -50,6 +72,9 @@ pub trait SettingsStore {
     /// Returns `Ok(true)` if key exists in store.
     fn exists(&self, key: &str) -> anyhow::Result<bool>;
 
+    /// Deletes `key` if it exists in store.
+    fn delete(&self, key: &str) -> anyhow::Result<()>;
+
     /// Returns all setting keys.
     #[allow(dead_code)]
     fn setting_keys(&self) -> anyhow::Result<Vec<String>>;
@@ -68,6 +93,10 @@ impl<T: SettingsStore> SettingsStore for Arc<T> {
         SettingsStore::exists(self.as_ref(), key)
     }
 
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        SettingsStore::delete(self.as_ref(), key)
+    }
+
     fn setting_keys(&self) -> anyhow::Result<Vec<String>> {
         SettingsStore::setting_keys(self.as_ref())
     }
@@ -193,6 +222,41 @@ impl<T: ?Sized + EthMappingsStore> EthMappingsStoreExt for T {
     }
 }
 
+/// Tipset whose computation produced a state root, recorded to trace state roots back to their
+/// origin, e.g. when investigating state migration mismatches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct StateProvenance {
+    pub epoch: ChainEpoch,
+    pub tipset_key: TipsetKey,
+    /// Whether a state migration ran while computing the state
+    pub migration: bool,
+}
+
+/// Interface used to store and retrieve the [`StateProvenance`] of the computed state roots.
+pub trait StateProvenanceStore {
+    fn read_state_provenance(&self, state_root: &Cid) -> anyhow::Result<Option<StateProvenance>>;
+
+    fn write_state_provenance(
+        &self,
+        state_root: &Cid,
+        provenance: &StateProvenance,
+    ) -> anyhow::Result<()>;
+}
+
+impl<T: StateProvenanceStore> StateProvenanceStore for Arc<T> {
+    fn read_state_provenance(&self, state_root: &Cid) -> anyhow::Result<Option<StateProvenance>> {
+        StateProvenanceStore::read_state_provenance(self.as_ref(), state_root)
+    }
+
+    fn write_state_provenance(
+        &self,
+        state_root: &Cid,
+        provenance: &StateProvenance,
+    ) -> anyhow::Result<()> {
+        StateProvenanceStore::write_state_provenance(self.as_ref(), state_root, provenance)
+    }
+}
+
 /// Traits for collecting DB stats
 pub trait DBStatistics {
     fn get_statistics(&self) -> Option<String> {
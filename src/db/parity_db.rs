@@ -1,20 +1,28 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{EthMappingsStore, PersistentStore, SettingsStore};
+use super::{
+    BlockKind, EthMappingsStore, PersistentStore, SettingsStore, StateProvenance,
+    StateProvenanceStore, current_block_kind,
+};
 use crate::blocks::TipsetKey;
-use crate::db::{DBStatistics, parity_db_config::ParityDbConfig};
+use crate::db::{
+    BlockstoreReadCache as _, DBStatistics, TinyLfuBlockstoreReadCache,
+    parity_db_config::ParityDbConfig,
+};
 use crate::libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
+use crate::metrics;
 use crate::rpc::eth::types::EthHash;
 use crate::utils::multihash::prelude::*;
 use anyhow::{Context as _, anyhow};
 use cid::Cid;
 use futures::FutureExt;
 use fvm_ipld_blockstore::Blockstore;
-use fvm_ipld_encoding::DAG_CBOR;
+use fvm_ipld_encoding::{DAG_CBOR, IPLD_RAW};
 use parity_db::{CompressionType, Db, Operation, Options};
 use parking_lot::RwLock;
-use std::path::PathBuf;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 use tracing::warn;
 
@@ -39,6 +47,28 @@ pub enum DbColumn {
     /// Anything stored in this column can be considered permanent, unless manually
     /// deleted.
     PersistentGraph,
+    /// Column for storing messages, see [`BlockKind::Message`]. It is reset along with the other
+    /// graph columns by the garbage collector.
+    GraphMessages,
+    /// Column for storing receipts, see [`BlockKind::Receipt`]. It is kept by the garbage
+    /// collector, and pruned by the receipts `TTL` collector instead.
+    GraphReceipts,
+    /// Column for storing events, see [`BlockKind::Event`]. It is kept by the garbage collector,
+    /// and pruned by the receipts `TTL` collector instead.
+    GraphEvents,
+    /// Column for storing the [`StateProvenance`] of the computed state roots, keyed by state
+    /// root.
+    StateProvenance,
+}
+
+impl From<BlockKind> for DbColumn {
+    fn from(kind: BlockKind) -> Self {
+        match kind {
+            BlockKind::Message => DbColumn::GraphMessages,
+            BlockKind::Receipt => DbColumn::GraphReceipts,
+            BlockKind::Event => DbColumn::GraphEvents,
+        }
+    }
 }
 
 impl DbColumn {
@@ -69,26 +99,60 @@ impl DbColumn {
                         compression,
                         ..Default::default()
                     },
-                    DbColumn::EthMappings => parity_db::ColumnOptions {
+                    DbColumn::EthMappings | DbColumn::StateProvenance => parity_db::ColumnOptions {
                         preimage: false,
                         btree_index: false,
                         compression,
                         ..Default::default()
                     },
+                    DbColumn::GraphMessages => parity_db::ColumnOptions {
+                        preimage: true,
+                        compression,
+                        ..Default::default()
+                    },
+                    DbColumn::GraphReceipts | DbColumn::GraphEvents => parity_db::ColumnOptions {
+                        preimage: true,
+                        // Blocks are shared between the receipts of different tipsets, e.g. the
+                        // empty `AMT`, so they are only deleted once all their writes are
+                        // dereferenced.
+                        ref_counted: true,
+                        compression,
+                        ..Default::default()
+                    },
                 }
             })
             .collect()
     }
 }
 
+/// Result of the integrity check of a column, see [`ParityDb::check_integrity`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnIntegrity {
+    pub column: DbColumn,
+    /// Number of blocks rehashed
+    pub checked: usize,
+    /// Number of blocks whose data doesn't match their CID
+    pub corrupted: usize,
+}
+
 type WriteOpsBroadcastTxSender = tokio::sync::broadcast::Sender<(Cid, Vec<u8>)>;
 
+const QUARANTINED_BLOCKS_CHANNEL_CAP: usize = 1024;
+/// Bound on the references of a corrupted block dropped when quarantining it, a block being
+/// referenced once per write
+const MAX_QUARANTINE_DEREFERENCES: usize = 1 << 16;
+
 pub struct ParityDb {
     pub db: parity_db::Db,
     statistics_enabled: bool,
     // This is needed to maintain backwards-compatibility for pre-persistent-column migrations.
     disable_persistent_fallback: bool,
     write_ops_broadcast_tx: RwLock<Option<WriteOpsBroadcastTxSender>>,
+    /// Sender of the CIDs of the quarantined blocks, see [`ParityDb::subscribe_quarantined_blocks`]
+    quarantined_tx: tokio::sync::broadcast::Sender<Cid>,
+    /// Cache of the blocks read from the graph columns. Blocks are immutable, so it never needs
+    /// to be invalidated.
+    read_cache: Option<TinyLfuBlockstoreReadCache>,
 }
 
 impl ParityDb {
@@ -111,9 +175,149 @@ impl ParityDb {
             statistics_enabled: opts.stats,
             disable_persistent_fallback: false,
             write_ops_broadcast_tx: RwLock::new(None),
+            quarantined_tx: tokio::sync::broadcast::channel(QUARANTINED_BLOCKS_CHANNEL_CAP).0,
+            read_cache: NonZeroUsize::new(config.read_cache_capacity).map(|capacity| {
+                TinyLfuBlockstoreReadCache::new_with_metrics("blockstore".into(), capacity)
+            }),
         })
     }
 
+    /// Compacts the database at `path` by rewriting all its columns to new tables, which also
+    /// rebuilds their indexes. The database must not be open.
+    pub fn compact(path: impl Into<PathBuf>, config: &ParityDbConfig) -> anyhow::Result<()> {
+        let opts = Self::to_options(path.into(), config);
+        let columns = DbColumn::iter().map(|col| col as u8).collect::<Vec<_>>();
+        parity_db::migrate(&opts.path, opts.clone(), true, &columns)
+            .map_err(|e| anyhow!("error compacting the database: {e}"))
+    }
+
+    /// Returns the indexes of the columns of the database at `path` that are not a [`DbColumn`],
+    /// e.g. the columns left behind by a failed migration.
+    pub fn orphaned_columns(path: &Path) -> anyhow::Result<Vec<u8>> {
+        let Some(metadata) = Options::load_metadata(path)
+            .map_err(|e| anyhow!("error loading database metadata: {e}"))?
+        else {
+            return Ok(vec![]);
+        };
+        Ok((DbColumn::iter().count()..metadata.columns.len())
+            .map(|index| index as u8)
+            .collect())
+    }
+
+    /// Checks the blocks of the graph columns by rehashing their data against their CIDs, up to
+    /// `limit` blocks per column if set. The other columns are not content-addressed and are
+    /// skipped.
+    pub fn check_integrity(&self, limit: Option<usize>) -> anyhow::Result<Vec<ColumnIntegrity>> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut report = vec![];
+        for column in DbColumn::iter() {
+            let mut integrity = ColumnIntegrity {
+                column,
+                checked: 0,
+                corrupted: 0,
+            };
+            match column {
+                DbColumn::GraphDagCborBlake2b256
+                | DbColumn::PersistentGraph
+                | DbColumn::GraphMessages
+                | DbColumn::GraphReceipts
+                | DbColumn::GraphEvents => {
+                    // The keys of these columns are hashed by `parity-db` and can't be iterated,
+                    // so the blocks are looked up again by the CIDs of their data instead.
+                    let mut res = anyhow::Ok(());
+                    self.db.iter_column_while(column as u8, |val| {
+                        if integrity.checked >= limit {
+                            return false;
+                        }
+                        let hash = MultihashCode::Blake2b256.digest(&val.value);
+                        let mut found = false;
+                        for codec in [DAG_CBOR, IPLD_RAW] {
+                            match self
+                                .db
+                                .get_size(column as u8, &Cid::new_v1(codec, hash).to_bytes())
+                            {
+                                Ok(size) => found |= size.is_some(),
+                                Err(e) => {
+                                    res = Err(anyhow!("error reading column {column}: {e}"));
+                                    return false;
+                                }
+                            }
+                        }
+                        integrity.checked += 1;
+                        if !found {
+                            integrity.corrupted += 1;
+                        }
+                        true
+                    })?;
+                    res?;
+                }
+                DbColumn::GraphFull => {
+                    let mut iter = self.db.iter(column as u8)?;
+                    while integrity.checked < limit
+                        && let Some((key, value)) = iter.next()?
+                    {
+                        integrity.checked += 1;
+                        let matches = Cid::try_from(key.as_slice()).is_ok_and(|cid| {
+                            MultihashCode::try_from(cid.hash().code())
+                                .is_ok_and(|code| code.digest(&value) == *cid.hash())
+                        });
+                        if !matches {
+                            integrity.corrupted += 1;
+                        }
+                    }
+                }
+                DbColumn::Settings | DbColumn::EthMappings | DbColumn::StateProvenance => continue,
+            }
+            report.push(integrity);
+        }
+        Ok(report)
+    }
+
+    /// Subscribes to the CIDs of the blocks quarantined on read, whose data didn't match their CID.
+    /// They are missing from the database from then on, and should be fetched again.
+    pub fn subscribe_quarantined_blocks(&self) -> tokio::sync::broadcast::Receiver<Cid> {
+        self.quarantined_tx.subscribe()
+    }
+
+    /// Quarantines a block whose data doesn't match its CID, deleting the corrupted copies from
+    /// the columns it may be stored in, so that it is read from the other backings of the
+    /// blockstore or fetched again instead of being used.
+    fn quarantine(&self, k: &Cid) -> anyhow::Result<()> {
+        super::metrics::QUARANTINED_BLOCKS.inc();
+        tracing::error!("Data of block {k} doesn't match its CID, quarantining it");
+        let key = k.to_bytes();
+        for column in [
+            Self::choose_column(k),
+            DbColumn::GraphMessages,
+            DbColumn::GraphReceipts,
+            DbColumn::GraphEvents,
+            DbColumn::PersistentGraph,
+        ] {
+            // A dereference only decrements the reference count of a block in the ref-counted
+            // columns, the corrupted copy is deleted once all its references are dropped.
+            let mut dereferences = 0;
+            while self
+                .read_from_column(&key, column)?
+                .is_some_and(|block| !matches_cid(k, &block))
+            {
+                anyhow::ensure!(
+                    dereferences < MAX_QUARANTINE_DEREFERENCES,
+                    "error quarantining block {k}: still referenced in column {column}"
+                );
+                self.db
+                    .commit_changes([(column as u8, Operation::Dereference(key.clone()))])
+                    .map_err(|e| anyhow!("error quarantining block {k}: {e}"))?;
+                dereferences += 1;
+            }
+        }
+        if let Some(cache) = &self.read_cache {
+            cache.remove(k);
+        }
+        // Nobody may be listening
+        let _ = self.quarantined_tx.send(*k);
+        Ok(())
+    }
+
     /// Returns an appropriate column variant based on the information
     /// in the Cid.
     fn choose_column(cid: &Cid) -> DbColumn {
@@ -125,6 +329,51 @@ impl ParityDb {
         }
     }
 
+    /// Returns the column a block is written to: the column of the [`BlockKind`] of the current
+    /// thread if it has one, and the block is stored in [`DbColumn::GraphDagCborBlake2b256`]
+    /// otherwise.
+    fn choose_write_column(cid: &Cid) -> DbColumn {
+        match (Self::choose_column(cid), current_block_kind()) {
+            (DbColumn::GraphDagCborBlake2b256, Some(kind)) => kind.into(),
+            (column, _) => column,
+        }
+    }
+
+    /// Dereferences the blocks from the column of the kind, the blocks absent from the column
+    /// are skipped. Blocks are deleted once all their writes are dereferenced.
+    pub fn dereference_blocks(
+        &self,
+        kind: BlockKind,
+        cids: impl IntoIterator<Item = Cid>,
+    ) -> anyhow::Result<usize> {
+        let column = DbColumn::from(kind);
+        let mut ops = vec![];
+        let mut dereferenced = vec![];
+        for cid in cids {
+            let key = cid.to_bytes();
+            if self
+                .db
+                .get_size(column as u8, &key)
+                .context("error checking if key exists")?
+                .is_some()
+            {
+                ops.push((column as u8, Operation::Dereference(key)));
+                dereferenced.push(cid);
+            }
+        }
+        let count = ops.len();
+        self.db
+            .commit_changes(ops)
+            .map_err(|e| anyhow!("error dereferencing from column {column}: {e}"))?;
+        // The cached copies of the deleted blocks must not outlive them
+        if let Some(cache) = &self.read_cache {
+            for cid in &dereferenced {
+                cache.remove(cid);
+            }
+        }
+        Ok(count)
+    }
+
     fn read_from_column<K>(&self, key: K, column: DbColumn) -> anyhow::Result<Option<Vec<u8>>>
     where
         K: AsRef<[u8]>,
@@ -162,6 +411,13 @@ impl SettingsStore for ParityDb {
             .context("error checking if key exists")
     }
 
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let tx = [(DbColumn::Settings as u8, key.as_bytes(), None)];
+        self.db
+            .commit(tx)
+            .map_err(|e| anyhow!("error deleting from column {}: {e}", DbColumn::Settings))
+    }
+
     fn setting_keys(&self) -> anyhow::Result<Vec<String>> {
         let mut iter = self.db.iter(DbColumn::Settings as u8)?;
         let mut keys = vec![];
@@ -221,22 +477,79 @@ impl EthMappingsStore for ParityDb {
     }
 }
 
+impl StateProvenanceStore for ParityDb {
+    fn read_state_provenance(&self, state_root: &Cid) -> anyhow::Result<Option<StateProvenance>> {
+        self.read_from_column(state_root.to_bytes(), DbColumn::StateProvenance)?
+            .map(|bytes| fvm_ipld_encoding::from_slice(&bytes))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn write_state_provenance(
+        &self,
+        state_root: &Cid,
+        provenance: &StateProvenance,
+    ) -> anyhow::Result<()> {
+        self.write_to_column(
+            state_root.to_bytes(),
+            fvm_ipld_encoding::to_vec(provenance)?,
+            DbColumn::StateProvenance,
+        )
+    }
+}
+
+/// Returns whether the data of a block hashes to its CID. The blocks hashed with an unknown
+/// function, or inlined in their CID, are assumed to.
+fn matches_cid(k: &Cid, block: &[u8]) -> bool {
+    match MultihashCode::try_from(k.hash().code()) {
+        Ok(MultihashCode::Identity) | Err(_) => true,
+        Ok(code) => code.digest(block) == *k.hash(),
+    }
+}
+
 fn has_subscribers<T>(tx: &tokio::sync::broadcast::Sender<T>) -> bool {
     tx.closed().now_or_never().is_none()
 }
 
 impl Blockstore for ParityDb {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
-        let column = Self::choose_column(k);
-        let res = self.read_from_column(k.to_bytes(), column)?;
-        if res.is_some() {
-            return Ok(res);
+        if let Some(cache) = &self.read_cache {
+            if let Some(block) = cache.get(k) {
+                metrics::LRU_CACHE_HIT
+                    .get_or_create(&metrics::values::BLOCKSTORE)
+                    .inc();
+                return Ok(Some(block));
+            }
+            metrics::LRU_CACHE_MISS
+                .get_or_create(&metrics::values::BLOCKSTORE)
+                .inc();
         }
-        self.get_persistent(k)
+        let mut res = match self.get_graph(k)? {
+            Some(block) => Some(block),
+            None => self.get_persistent(k)?,
+        };
+        if res.as_ref().is_some_and(|block| !matches_cid(k, block)) {
+            self.quarantine(k)?;
+            // Only the valid copies of the block are left, if any
+            res = match self.get_graph(k)? {
+                Some(block) => Some(block),
+                None => self.get_persistent(k)?,
+            };
+            anyhow::ensure!(
+                res.as_ref().is_none_or(|block| matches_cid(k, block)),
+                "data of block {k} doesn't match its CID after quarantining it"
+            );
+        }
+        if let Some(cache) = &self.read_cache
+            && let Some(block) = &res
+        {
+            cache.put(*k, block.clone());
+        }
+        Ok(res)
     }
 
     fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
-        let column = Self::choose_column(k);
+        let column = Self::choose_write_column(k);
         // We can put the data directly into the database without any encoding.
         self.write_to_column(k.to_bytes(), block, column)?;
         match &*self.write_ops_broadcast_tx.read() {
@@ -260,7 +573,7 @@ impl Blockstore for ParityDb {
         let has_subscribers = tx_opt.as_ref().map(has_subscribers).unwrap_or_default();
         let mut values_for_subscriber = vec![];
         let values = blocks.into_iter().map(|(k, v)| {
-            let column = Self::choose_column(&k);
+            let column = Self::choose_write_column(&k);
             let v = v.as_ref().to_vec();
             if has_subscribers {
                 values_for_subscriber.push((k, v.clone()));
@@ -290,15 +603,13 @@ impl PersistentStore for ParityDb {
 
 impl BitswapStoreRead for ParityDb {
     fn contains(&self, cid: &Cid) -> anyhow::Result<bool> {
-        // We need to check both columns because we don't know which one
-        // the data is in. The order is important because most data will
-        // be in the [`DbColumn::GraphDagCborBlake2b256`] column and so
-        // it directly affects performance. If this assumption ever changes
-        // then this code should be modified accordingly.
-        for column in [DbColumn::GraphDagCborBlake2b256, DbColumn::GraphFull] {
+        // We need to check the graph columns because we don't know which one
+        // the data is in, see [`ParityDb::graph_columns`] for the order.
+        let key = cid.to_bytes();
+        for column in Self::graph_columns(cid) {
             if self
                 .db
-                .get_size(column as u8, &cid.to_bytes())
+                .get_size(column as u8, &key)
                 .context("error checking if key exists")?
                 .is_some()
             {
@@ -366,6 +677,40 @@ impl ParityDb {
         (column, Operation::Set(key, value))
     }
 
+    /// Returns the graph columns a block may be stored in, in the order they are looked up. The
+    /// blocks read with a [`BlockKind`] are looked up in the column of the kind first, so that
+    /// the readers of messages, receipts and events find them with a single lookup, and the
+    /// other blocks are looked up in the columns of the kinds last.
+    fn graph_columns(cid: &Cid) -> impl Iterator<Item = DbColumn> {
+        let column = Self::choose_column(cid);
+        let kind = match column {
+            DbColumn::GraphDagCborBlake2b256 => current_block_kind().map(DbColumn::from),
+            _ => None,
+        };
+        let kinds: &[DbColumn] = match column {
+            DbColumn::GraphDagCborBlake2b256 => &[
+                DbColumn::GraphMessages,
+                DbColumn::GraphReceipts,
+                DbColumn::GraphEvents,
+            ],
+            _ => &[],
+        };
+        kind.into_iter()
+            .chain(std::iter::once(column))
+            .chain(kinds.iter().copied().filter(move |it| Some(*it) != kind))
+    }
+
+    // Get data from the graph columns, see [`ParityDb::graph_columns`].
+    fn get_graph(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let key = k.to_bytes();
+        for column in Self::graph_columns(k) {
+            if let Some(block) = self.read_from_column(&key, column)? {
+                return Ok(Some(block));
+            }
+        }
+        Ok(None)
+    }
+
     // Get data from persistent graph column.
     fn get_persistent(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
         if self.disable_persistent_fallback {
@@ -395,8 +740,10 @@ impl super::BlockstoreWriteOpsSubscribable for ParityDb {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::db::{BlockstoreWriteOpsSubscribable, tests::db_utils::parity::TempParityDB};
-    use fvm_ipld_encoding::IPLD_RAW;
+    use crate::db::{
+        BlockstoreWriteOpsSubscribable, metrics::QUARANTINED_BLOCKS,
+        tests::db_utils::parity::TempParityDB,
+    };
     use itertools::Itertools as _;
     use nom::AsBytes;
     use std::ops::Deref;
@@ -412,7 +759,7 @@ mod test {
         let cids = [
             Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&data[0])),
             Cid::new_v1(DAG_CBOR, MultihashCode::Sha2_256.digest(&data[1])),
-            Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(&data[1])),
+            Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(&data[2])),
         ];
 
         let cases = [
@@ -437,8 +784,13 @@ mod test {
                 DbColumn::GraphDagCborBlake2b256 => DbColumn::GraphFull,
                 DbColumn::GraphFull => DbColumn::GraphDagCborBlake2b256,
                 DbColumn::Settings => panic!("invalid column for IPLD data"),
-                DbColumn::EthMappings => panic!("invalid column for IPLD data"),
+                DbColumn::EthMappings | DbColumn::StateProvenance => {
+                    panic!("invalid column for IPLD data")
+                }
                 DbColumn::PersistentGraph => panic!("invalid column for GC enabled IPLD data"),
+                DbColumn::GraphMessages | DbColumn::GraphReceipts | DbColumn::GraphEvents => {
+                    panic!("invalid column for untagged IPLD data")
+                }
             };
             let actual = db.read_from_column(cid.to_bytes(), other_column).unwrap();
             assert!(actual.is_none());
@@ -460,6 +812,229 @@ mod test {
         assert_eq!(b"bloop", actual.as_bytes());
     }
 
+    #[test]
+    fn block_kind_columns_test() {
+        let db = TempParityDB::new();
+        let data = [b"Cthulhu".to_vec(), b"Dagon".to_vec(), b"Hydra".to_vec()];
+        let cids = data
+            .iter()
+            .map(|data| Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(data)))
+            .collect_vec();
+        let raw_cid = Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(&data[0]));
+
+        crate::db::with_block_kind(BlockKind::Receipt, || {
+            db.put_keyed(&cids[0], &data[0]).unwrap();
+            db.put_keyed(&raw_cid, &data[0]).unwrap();
+        });
+        crate::db::with_block_kind(BlockKind::Event, || {
+            db.put_many_keyed([(cids[1], &data[1]), (cids[2], &data[2])])
+                .unwrap();
+        });
+        // Blocks of other codecs are not tagged.
+        assert!(
+            db.read_from_column(raw_cid.to_bytes(), DbColumn::GraphFull)
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            db.read_from_column(cids[0].to_bytes(), DbColumn::GraphDagCborBlake2b256)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            db.read_from_column(cids[0].to_bytes(), DbColumn::GraphReceipts)
+                .unwrap()
+                .is_some()
+        );
+        for (cid, data) in cids.iter().zip(&data) {
+            assert_eq!(
+                Blockstore::get(db.deref(), cid).unwrap().as_ref(),
+                Some(data)
+            );
+            assert!(db.contains(cid).unwrap());
+        }
+
+        // Blocks are deleted once all their writes are dereferenced.
+        crate::db::with_block_kind(BlockKind::Event, || {
+            db.put_keyed(&cids[1], &data[1]).unwrap();
+        });
+        assert_eq!(
+            db.dereference_blocks(BlockKind::Event, [cids[0], cids[1], cids[2]])
+                .unwrap(),
+            2
+        );
+        assert!(Blockstore::get(db.deref(), &cids[0]).unwrap().is_some());
+        assert!(Blockstore::get(db.deref(), &cids[1]).unwrap().is_some());
+        assert!(Blockstore::get(db.deref(), &cids[2]).unwrap().is_none());
+        db.dereference_blocks(BlockKind::Event, [cids[1]]).unwrap();
+        assert!(Blockstore::get(db.deref(), &cids[1]).unwrap().is_none());
+    }
+
+    #[test]
+    fn check_integrity_test() {
+        let db = TempParityDB::new();
+        let data = [b"Cthulhu".to_vec(), b"Dagon".to_vec()];
+        for data in &data {
+            db.put_keyed(
+                &Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(data)),
+                data,
+            )
+            .unwrap();
+            db.put_keyed(
+                &Cid::new_v1(DAG_CBOR, MultihashCode::Sha2_256.digest(data)),
+                data,
+            )
+            .unwrap();
+        }
+        let report = db.check_integrity(None).unwrap();
+        assert!(report.iter().all(|it| it.corrupted == 0));
+
+        // Blocks whose data doesn't match their CID
+        let bad_cid = |code: MultihashCode| Cid::new_v1(DAG_CBOR, code.digest(b"Hydra"));
+        db.write_to_column(
+            bad_cid(MultihashCode::Blake2b256).to_bytes(),
+            b"Mother Hydra",
+            DbColumn::GraphDagCborBlake2b256,
+        )
+        .unwrap();
+        db.write_to_column(
+            bad_cid(MultihashCode::Sha2_256).to_bytes(),
+            b"Mother Hydra",
+            DbColumn::GraphFull,
+        )
+        .unwrap();
+        let report = db.check_integrity(None).unwrap();
+        let column = |column| report.iter().find(|it| it.column == column).unwrap();
+        assert_eq!(
+            column(DbColumn::GraphDagCborBlake2b256),
+            &ColumnIntegrity {
+                column: DbColumn::GraphDagCborBlake2b256,
+                checked: 3,
+                corrupted: 1,
+            }
+        );
+        assert_eq!(column(DbColumn::GraphFull).checked, 3);
+        assert_eq!(column(DbColumn::GraphFull).corrupted, 1);
+        assert!(report.iter().all(|it| it.column != DbColumn::Settings));
+
+        let report = db.check_integrity(Some(1)).unwrap();
+        assert!(report.iter().all(|it| it.checked <= 1));
+    }
+
+    #[test]
+    fn quarantine_corrupted_blocks_test() {
+        let db = TempParityDB::new();
+        let mut rx = db.subscribe_quarantined_blocks();
+        let cid = Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(b"Cthulhu"));
+        db.write_to_column(
+            cid.to_bytes(),
+            b"Mother Hydra",
+            DbColumn::GraphDagCborBlake2b256,
+        )
+        .unwrap();
+        let quarantined = QUARANTINED_BLOCKS.get();
+        assert!(Blockstore::get(db.deref(), &cid).unwrap().is_none());
+        assert!(QUARANTINED_BLOCKS.get() > quarantined);
+        assert_eq!(rx.try_recv().unwrap(), cid);
+        assert!(
+            db.read_from_column(cid.to_bytes(), DbColumn::GraphDagCborBlake2b256)
+                .unwrap()
+                .is_none()
+        );
+
+        // The block can be written again
+        db.put_keyed(&cid, b"Cthulhu").unwrap();
+        assert_eq!(
+            Blockstore::get(db.deref(), &cid).unwrap().as_deref(),
+            Some(b"Cthulhu".as_slice())
+        );
+    }
+
+    #[test]
+    fn quarantine_ref_counted_blocks_test() {
+        let db = TempParityDB::new();
+        let cid = Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(b"Cthulhu"));
+        // The corrupted block is referenced several times
+        for _ in 0..3 {
+            db.write_to_column(cid.to_bytes(), b"Mother Hydra", DbColumn::GraphEvents)
+                .unwrap();
+        }
+        assert!(Blockstore::get(db.deref(), &cid).unwrap().is_none());
+        assert!(
+            db.read_from_column(cid.to_bytes(), DbColumn::GraphEvents)
+                .unwrap()
+                .is_none()
+        );
+
+        // The block can be written again
+        crate::db::with_block_kind(BlockKind::Event, || {
+            db.put_keyed(&cid, b"Cthulhu").unwrap();
+        });
+        assert_eq!(
+            Blockstore::get(db.deref(), &cid).unwrap().as_deref(),
+            Some(b"Cthulhu".as_slice())
+        );
+    }
+
+    #[test]
+    fn compact_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paritydb");
+        let config = ParityDbConfig::default();
+        let cid = Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(b"Cthulhu"));
+        {
+            let db = ParityDb::open(path.clone(), &config).unwrap();
+            db.put_keyed(&cid, b"Cthulhu").unwrap();
+            SettingsStore::write_bin(&db, "key", b"value").unwrap();
+        }
+        assert!(ParityDb::orphaned_columns(&path).unwrap().is_empty());
+        ParityDb::compact(path.clone(), &config).unwrap();
+
+        let db = ParityDb::open(path, &config).unwrap();
+        assert_eq!(
+            Blockstore::get(&db, &cid).unwrap().as_deref(),
+            Some(b"Cthulhu".as_slice())
+        );
+        assert_eq!(
+            SettingsStore::read_bin(&db, "key").unwrap().as_deref(),
+            Some(b"value".as_slice())
+        );
+    }
+
+    #[test]
+    fn graph_columns_test() {
+        let data = [0u8; 32];
+        let cid = Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&data));
+        assert_eq!(
+            ParityDb::graph_columns(&cid).collect_vec(),
+            [
+                DbColumn::GraphDagCborBlake2b256,
+                DbColumn::GraphMessages,
+                DbColumn::GraphReceipts,
+                DbColumn::GraphEvents,
+            ]
+        );
+        // The blocks read with a kind are looked up in its column first.
+        crate::db::with_block_kind(BlockKind::Receipt, || {
+            assert_eq!(
+                ParityDb::graph_columns(&cid).collect_vec(),
+                [
+                    DbColumn::GraphReceipts,
+                    DbColumn::GraphDagCborBlake2b256,
+                    DbColumn::GraphMessages,
+                    DbColumn::GraphEvents,
+                ]
+            );
+        });
+        let raw_cid = Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(&data));
+        crate::db::with_block_kind(BlockKind::Receipt, || {
+            assert_eq!(
+                ParityDb::graph_columns(&raw_cid).collect_vec(),
+                [DbColumn::GraphFull]
+            );
+        });
+    }
+
     #[test]
     fn choose_column_test() {
         let data = [0u8; 32];
@@ -496,31 +1071,24 @@ mod test {
             b"R'lyeh wgah'nagl fhtagn!!".to_vec(),
         ];
 
-        let persistent_data = data
-            .clone()
-            .into_iter()
-            .map(|mut entry| {
-                entry.push(255);
-                entry
-            })
-            .collect_vec();
-
         let cids = [
             Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&data[0])),
             Cid::new_v1(DAG_CBOR, MultihashCode::Sha2_256.digest(&data[1])),
-            Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(&data[1])),
+            Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(&data[2])),
         ];
 
-        for idx in 0..3 {
-            let cid = &cids[idx];
-            let persistent_entry = &persistent_data[idx];
-            let data_entry = &data[idx];
-            db.put_keyed_persistent(cid, persistent_entry).unwrap();
+        for (cid, data_entry) in cids.iter().zip(&data) {
+            db.put_keyed_persistent(cid, data_entry).unwrap();
             // Check that we get persistent data if the data is otherwise absent from the GC enabled
             // storage.
+            assert!(
+                db.read_from_column(cid.to_bytes(), ParityDb::choose_column(cid))
+                    .unwrap()
+                    .is_none()
+            );
             assert_eq!(
                 Blockstore::get(db.deref(), cid).unwrap(),
-                Some(persistent_entry.clone())
+                Some(data_entry.clone())
             );
             assert!(
                 db.read_from_column(cid.to_bytes(), DbColumn::PersistentGraph)
@@ -528,6 +1096,11 @@ mod test {
                     .is_some()
             );
             db.put_keyed(cid, data_entry).unwrap();
+            assert!(
+                db.read_from_column(cid.to_bytes(), ParityDb::choose_column(cid))
+                    .unwrap()
+                    .is_some()
+            );
             assert_eq!(
                 Blockstore::get(db.deref(), cid).unwrap(),
                 Some(data_entry.clone())
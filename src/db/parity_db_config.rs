@@ -1,12 +1,34 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use crate::shim::clock::ChainEpoch;
+use crate::utils::misc::memory_budget::scale_to_memory_budget;
 use serde::{Deserialize, Serialize};
 
 /// `ParityDb` configuration exposed in Forest.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct ParityDbConfig {
     pub enable_statistics: bool,
+    /// Number of blocks kept in the in-memory read cache, use 0 to disable the cache. Sized from
+    /// the memory budget by default, 100000 blocks at the reference budget.
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub read_cache_capacity: usize,
+    /// Number of epochs the receipts of the tipsets are kept for, they are kept forever if unset.
+    /// Receipts are kept at least as long as the events.
+    pub receipts_retention_epochs: Option<ChainEpoch>,
+    /// Number of epochs the events of the tipsets are kept for, they are kept forever if unset
+    pub events_retention_epochs: Option<ChainEpoch>,
+}
+
+impl Default for ParityDbConfig {
+    fn default() -> Self {
+        Self {
+            enable_statistics: false,
+            read_cache_capacity: scale_to_memory_budget(100_000, 10_000, 2_000_000),
+            receipts_retention_epochs: None,
+            events_retention_epochs: None,
+        }
+    }
 }
@@ -1,6 +1,8 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod receipts;
+
 use crate::eth::EthChainId;
 use crate::message::ChainMessage;
 use crate::rpc::eth::{eth_tx_from_signed_eth_message, types::EthHash};
@@ -11,6 +13,8 @@ use std::time::Duration;
 
 use super::EthMappingsStore;
 
+pub use receipts::ReceiptCollector;
+
 pub struct EthMappingCollector<DB> {
     db: Arc<DB>,
     eth_chain_id: EthChainId,
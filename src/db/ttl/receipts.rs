@@ -0,0 +1,172 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Pruning of the receipts and events of the tipsets older than their retention. They are stored
+//! in their own `ParityDb` columns, and are dereferenced there as the tipsets age, independently
+//! of the state, which is pruned by the garbage collector.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+
+use crate::blocks::{Tipset, TipsetKey};
+use crate::chain::ChainStore;
+use crate::chain::index::ResolveNullTipset;
+use crate::db::parity_db::ParityDb;
+use crate::db::parity_db_config::ParityDbConfig;
+use crate::db::{BlockKind, SettingsStoreExt as _, receipts_marker, setting_keys};
+use crate::shim::clock::ChainEpoch;
+use crate::utils::encoding::extract_cids;
+
+/// Interval between two prunings.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub struct ReceiptCollector<DB> {
+    chain_store: Arc<ChainStore<DB>>,
+    parity_db: Arc<ParityDb>,
+    receipts_retention_epochs: Option<ChainEpoch>,
+    events_retention_epochs: Option<ChainEpoch>,
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> ReceiptCollector<DB> {
+    /// Creates a `TTL` collector for the receipts and events, with the retentions of the
+    /// configuration. Nothing is pruned in archive mode.
+    pub fn new(
+        chain_store: Arc<ChainStore<DB>>,
+        parity_db: Arc<ParityDb>,
+        config: &ParityDbConfig,
+        archive: bool,
+    ) -> Self {
+        let retention = |epochs: Option<ChainEpoch>| epochs.filter(|_| !archive);
+        Self {
+            chain_store,
+            parity_db,
+            receipts_retention_epochs: retention(config.receipts_retention_epochs),
+            events_retention_epochs: retention(config.events_retention_epochs),
+        }
+    }
+
+    /// Returns `true` if the events, and possibly the receipts, are pruned.
+    pub fn is_enabled(&self) -> bool {
+        self.events_retention_epochs.is_some()
+    }
+
+    /// Returns the retention of the blocks of the kind. Receipts are kept at least as long as the
+    /// events, as the events are found through them.
+    fn retention(&self, kind: BlockKind) -> Option<ChainEpoch> {
+        match kind {
+            BlockKind::Event => self.events_retention_epochs,
+            BlockKind::Receipt => {
+                match (self.receipts_retention_epochs, self.events_retention_epochs) {
+                    (Some(receipts), Some(events)) => Some(receipts.max(events)),
+                    _ => None,
+                }
+            }
+            BlockKind::Message => None,
+        }
+    }
+
+    /// Dereferences the blocks of the kind of the tipsets older than its retention, from the
+    /// oldest tipset not pruned yet. Returns the number of dereferenced blocks.
+    fn prune(&self, head: &Tipset, kind: BlockKind) -> anyhow::Result<usize> {
+        let (Some(retention), Some(key)) = (self.retention(kind), pruned_epoch_key(kind)) else {
+            return Ok(0);
+        };
+        let until = head.epoch() - retention;
+        let pruned: ChainEpoch = self.parity_db.read_obj(key)?.unwrap_or(-1);
+        if until <= pruned {
+            return Ok(0);
+        }
+        let chain_index = self.chain_store.chain_index();
+        // The chain may not go back that far, e.g. on a node started from a recent snapshot.
+        let Ok(from) =
+            chain_index.tipset_by_height(until, head.clone(), ResolveNullTipset::TakeOlder)
+        else {
+            return Ok(0);
+        };
+        // Tipsets are pruned from the oldest, so that the progress is saved as they are, and no
+        // block is dereferenced twice.
+        let keys: Vec<TipsetKey> = chain_index
+            .chain(from)
+            .take_while(|tipset| tipset.epoch() > pruned)
+            .map(|tipset| tipset.key().clone())
+            .collect();
+        let mut count = 0;
+        for key_of_tipset in keys.into_iter().rev() {
+            let tipset = chain_index.load_required_tipset(&key_of_tipset)?;
+            let mut blocks = self.blocks(tipset.min_ticket_block().message_receipts)?;
+            // The tipset is no longer marked as written with its receipts, see `receipts_marker`.
+            if kind == BlockKind::Receipt {
+                blocks.push(receipts_marker(tipset.key())?.0);
+            }
+            count += self.parity_db.dereference_blocks(kind, blocks)?;
+            self.parity_db.write_obj(key, &tipset.epoch())?;
+        }
+        Ok(count)
+    }
+
+    /// Returns the blocks of the receipts `AMT` of the root, and of the events `AMTs` its receipts
+    /// link to, once per link, as they are referenced once per write.
+    fn blocks(&self, root: Cid) -> anyhow::Result<Vec<Cid>> {
+        let mut blocks = vec![];
+        let mut stack = vec![root];
+        while let Some(cid) = stack.pop() {
+            // The events of pruned receipts may already be pruned.
+            let Some(block) = self.parity_db.get(&cid)? else {
+                continue;
+            };
+            blocks.push(cid);
+            stack.extend(extract_cids(&block)?);
+        }
+        Ok(blocks)
+    }
+
+    fn ttl_workflow(&self) -> anyhow::Result<()> {
+        let head = self.chain_store.heaviest_tipset();
+        // Events first, they are found through the receipts.
+        for kind in [BlockKind::Event, BlockKind::Receipt] {
+            let count = self.prune(&head, kind)?;
+            tracing::debug!("Dereferenced {count} {kind:?} blocks");
+        }
+        Ok(())
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        loop {
+            tokio::time::sleep(PRUNE_INTERVAL).await;
+
+            self.ttl_workflow()?;
+        }
+    }
+}
+
+fn pruned_epoch_key(kind: BlockKind) -> Option<&'static str> {
+    match kind {
+        BlockKind::Receipt => Some(setting_keys::RECEIPTS_PRUNED_EPOCH_KEY),
+        BlockKind::Event => Some(setting_keys::EVENTS_PRUNED_EPOCH_KEY),
+        BlockKind::Message => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn disabled_in_archive_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ParityDbConfig {
+            events_retention_epochs: Some(100),
+            ..Default::default()
+        };
+        let parity_db = Arc::new(ParityDb::open(dir.path(), &config).unwrap());
+        let chain_store = crate::test_utils::ChainHarness::new().chain_store;
+        let collector = |archive| {
+            ReceiptCollector::new(chain_store.clone(), parity_db.clone(), &config, archive)
+        };
+        assert!(collector(false).is_enabled());
+        assert!(!collector(true).is_enabled());
+    }
+}
@@ -14,6 +14,7 @@ use thiserror::Error;
 
 mod validation;
 mod weight;
+pub(crate) use validation::{validate_ticket_election, validate_winner_election};
 
 #[derive(Debug, Error)]
 pub enum FilecoinConsensusError {
@@ -6,7 +6,7 @@ use std::{collections::BTreeMap, sync::Arc};
 use crate::beacon::{BeaconEntry, BeaconSchedule, IGNORE_DRAND_VAR};
 use crate::blocks::{Block, CachingBlockHeader, Tipset};
 use crate::chain::ChainStore;
-use crate::chain_sync::collect_errs;
+use crate::chain_sync::{collect_errs, is_header_checked};
 use crate::networks::{ChainConfig, Height};
 use crate::shim::actors::PowerActorStateLoad as _;
 use crate::shim::actors::power;
@@ -18,11 +18,13 @@ use crate::shim::{
     address::Address,
     randomness::Randomness,
     sector::{PoStProof, SectorInfo},
+    state_tree::StateTree,
     version::NetworkVersion,
 };
-use crate::state_manager::StateManager;
+use crate::state_manager::{StateManager, utils::miner_eligible_to_mine};
 use crate::utils::encoding::prover_id_from_u64;
 use crate::utils::misc::env::is_env_truthy;
+use crate::utils::proofs_api::verify_on_pool;
 use cid::Cid;
 use fil_actors_shared::filecoin_proofs_api::{PublicReplicaInfo, SectorId, post};
 use fil_actors_shared::v10::runtime::DomainSeparationTag;
@@ -67,7 +69,7 @@ pub(in crate::fil_cns) async fn validate_block<DB: Blockstore + Sync + Send + 's
     let win_p_nv = state_manager.get_network_version(base_tipset.epoch());
 
     // Retrieve lookback tipset for validation
-    let (lookback_tipset, lookback_state) = ChainStore::get_lookback_tipset_for_round(
+    let (_, lookback_state) = ChainStore::get_lookback_tipset_for_round(
         chain_store.chain_index(),
         state_manager.chain_config(),
         &base_tipset,
@@ -115,13 +117,13 @@ pub(in crate::fil_cns) async fn validate_block<DB: Blockstore + Sync + Send + 's
         let lookback_state = lookback_state.clone();
         move || {
             validate_winner_election(
+                state_manager.blockstore(),
+                state_manager.chain_config(),
                 block.header(),
                 &base_tipset,
-                &lookback_tipset,
                 &lookback_state,
                 &prev_beacon,
                 &work_addr,
-                &state_manager,
             )
         }
     });
@@ -142,32 +144,37 @@ pub(in crate::fil_cns) async fn validate_block<DB: Blockstore + Sync + Send + 's
         });
     }
 
-    // Ticket election proof validations
-    validations.spawn_blocking({
-        let block = block.clone();
-        let prev_beacon = prev_beacon.clone();
-        let chain_config = state_manager.chain_config().clone();
-        move || {
-            validate_ticket_election(
+    // Ticket election proof validations, unless the header was checked when it was gossiped
+    if !is_header_checked(block.cid()) {
+        validations.spawn_blocking({
+            let block = block.clone();
+            let prev_beacon = prev_beacon.clone();
+            let chain_config = state_manager.chain_config().clone();
+            move || {
+                validate_ticket_election(
+                    block.header(),
+                    &base_tipset,
+                    &prev_beacon,
+                    &work_addr,
+                    &chain_config,
+                )
+            }
+        });
+    }
+
+    // Winning PoSt proof validation, on the proof verification pool
+    validations.spawn(async move {
+        verify_on_pool(move || {
+            verify_winning_post_proof::<_>(
+                &state_manager,
+                win_p_nv,
                 block.header(),
-                &base_tipset,
                 &prev_beacon,
-                &work_addr,
-                &chain_config,
+                &lookback_state,
             )
-        }
-    });
-
-    // Winning PoSt proof validation
-    validations.spawn_blocking(move || {
-        verify_winning_post_proof::<_>(
-            &state_manager,
-            win_p_nv,
-            block.header(),
-            &prev_beacon,
-            &lookback_state,
-        )?;
-        Ok(())
+        })
+        .await
+        .map_err(|e| FilecoinConsensusError::WinningPoStValidation(e.to_string()))?
     });
 
     // Collect the errors from the async validations
@@ -238,21 +245,33 @@ fn validate_miner<DB: Blockstore>(
     Ok(())
 }
 
-fn validate_winner_election<DB: Blockstore + Sync + Send + 'static>(
+/// Checks the election proof of the block: the miner is eligible to mine and its win count
+/// matches its power in the lookback state.
+pub(crate) fn validate_winner_election<DB: Blockstore>(
+    db: &Arc<DB>,
+    chain_config: &ChainConfig,
     header: &CachingBlockHeader,
     base_tipset: &Tipset,
-    lookback_tipset: &Tipset,
     lookback_state: &Cid,
     prev_beacon: &BeaconEntry,
     work_addr: &Address,
-    state_manager: &StateManager<DB>,
 ) -> Result<(), FilecoinConsensusError> {
-    // Safe to unwrap because checked to `Some` in sanity check
-    let election_proof = header.election_proof.as_ref().unwrap();
+    let election_proof = header
+        .election_proof
+        .as_ref()
+        .ok_or(FilecoinConsensusError::BlockWithoutElectionProof)?;
     if election_proof.win_count < 1 {
         return Err(FilecoinConsensusError::NotClaimingWin);
     }
-    let hp = state_manager.eligible_to_mine(&header.miner_address, base_tipset, lookback_tipset)?;
+    let hp = miner_eligible_to_mine(
+        db,
+        &chain_config.policy,
+        chain_config.network_version(base_tipset.epoch()),
+        &header.miner_address,
+        base_tipset,
+        lookback_state,
+    )
+    .map_err(|e| FilecoinConsensusError::MinerPowerUnavailable(e.to_string()))?;
     if !hp {
         return Err(FilecoinConsensusError::MinerNotEligibleToMine);
     }
@@ -271,13 +290,32 @@ fn validate_winner_election<DB: Blockstore + Sync + Send + 'static>(
 
     verify_election_post_vrf(work_addr, &vrf_base, election_proof.vrfproof.as_bytes())?;
 
-    if state_manager.is_miner_slashed(&header.miner_address, base_tipset.parent_state())? {
+    let load_power = |state_root: &Cid| -> anyhow::Result<power::State> {
+        StateTree::new_from_root(db.clone(), state_root)?.get_actor_state()
+    };
+    let base_power = load_power(base_tipset.parent_state())
+        .map_err(|_| FilecoinConsensusError::PowerActorUnavailable)?;
+    let slashed = base_power
+        .miner_power(db, miner_address)
+        .map_err(|e| FilecoinConsensusError::MinerPowerUnavailable(e.to_string()))?
+        .is_none();
+    if slashed {
         return Err(FilecoinConsensusError::InvalidOrSlashedMiner);
     }
 
-    let (mpow, tpow) = state_manager
-        .get_power(lookback_state, Some(&header.miner_address))?
+    let lookback_power =
+        load_power(lookback_state).map_err(|_| FilecoinConsensusError::PowerActorUnavailable)?;
+    let mpow = lookback_power
+        .miner_power(db, miner_address)
+        .map_err(|e| FilecoinConsensusError::MinerPowerUnavailable(e.to_string()))?
         .ok_or(FilecoinConsensusError::MinerPowerNotAvailable)?;
+    let meets_minimum = lookback_power
+        .miner_nominal_power_meets_consensus_minimum(&chain_config.policy, db, miner_address)
+        .map_err(|e| FilecoinConsensusError::MinerPowerUnavailable(e.to_string()))?;
+    if !meets_minimum {
+        return Err(FilecoinConsensusError::MinerPowerNotAvailable);
+    }
+    let tpow = lookback_power.total_power();
 
     let j = election_proof.compute_win_count(&mpow.quality_adj_power, &tpow.quality_adj_power);
 
@@ -291,7 +329,7 @@ fn validate_winner_election<DB: Blockstore + Sync + Send + 'static>(
     Ok(())
 }
 
-fn validate_ticket_election(
+pub(crate) fn validate_ticket_election(
     header: &CachingBlockHeader,
     base_tipset: &Tipset,
     prev_beacon: &BeaconEntry,
@@ -321,12 +359,11 @@ fn validate_ticket_election(
     )
     .map_err(|e| FilecoinConsensusError::DrawingChainRandomness(e.to_string()))?;
 
-    verify_election_post_vrf(
-        work_addr,
-        &vrf_base,
-        // Safe to unwrap here because of block sanity checks
-        header.ticket.as_ref().unwrap().vrfproof.as_bytes(),
-    )?;
+    let ticket = header
+        .ticket
+        .as_ref()
+        .ok_or(FilecoinConsensusError::BlockWithoutTicket)?;
+    verify_election_post_vrf(work_addr, &vrf_base, ticket.vrfproof.as_bytes())?;
 
     Ok(())
 }
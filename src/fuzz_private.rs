@@ -0,0 +1,34 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Entry points of the fuzz targets in `fuzz/`. They decode untrusted bytes the way the node
+//! does, and must never panic nor overflow the stack.
+
+use ipld_core::ipld::Ipld;
+
+use crate::blocks::GossipBlock;
+use crate::libp2p::chain_exchange::{ChainExchangeRequest, ChainExchangeResponse};
+use crate::libp2p::hello::{HelloRequest, HelloResponse};
+use crate::libp2p::rpc::decode_message;
+use crate::message::SignedMessage;
+use crate::utils::encoding::{from_slice_with_fallback, from_slice_with_fallback_and_max_depth};
+
+/// Decodes arbitrary `DAG_CBOR`, with the default and with the given maximum nesting depth.
+pub fn fuzz_from_slice_with_fallback(data: &[u8], max_depth: usize) {
+    let _ = from_slice_with_fallback::<Ipld>(data);
+    let _ = from_slice_with_fallback_and_max_depth::<Ipld>(data, max_depth);
+}
+
+/// Decodes a request-response message of the chain exchange and hello protocols.
+pub fn fuzz_request_response(data: &[u8]) {
+    let _ = decode_message::<ChainExchangeRequest>(data);
+    let _ = decode_message::<ChainExchangeResponse>(data);
+    let _ = decode_message::<HelloRequest>(data);
+    let _ = decode_message::<HelloResponse>(data);
+}
+
+/// Decodes a message of the block and message gossipsub topics.
+pub fn fuzz_gossipsub(data: &[u8]) {
+    let _ = from_slice_with_fallback::<GossipBlock>(data);
+    let _ = from_slice_with_fallback::<SignedMessage>(data);
+}
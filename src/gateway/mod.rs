@@ -0,0 +1,172 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Read-only HTTP gateway serving the blocks of the node's blockstore, so that IPLD tooling can
+//! fetch chain data without the JSON-RPC wrapper.
+//!
+//! Only the block responses of the
+//! [trustless gateway specification](https://specs.ipfs.tech/http-gateways/trustless-gateway/)
+//! are supported, i.e. `GET /ipfs/<cid>?format=raw`, or `GET /ipfs/<cid>` with the
+//! `Accept: application/vnd.ipld.raw` header. `HEAD` requests are answered as well.
+
+use std::str::FromStr as _;
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use http::{
+    HeaderMap, StatusCode,
+    header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH, X_CONTENT_TYPE_OPTIONS},
+};
+use serde::Deserialize;
+
+/// <https://www.iana.org/assignments/media-types/application/vnd.ipld.raw>
+const RAW_CONTENT_TYPE: &str = "application/vnd.ipld.raw";
+/// Blocks are content-addressed and never change, one year is the longest cache lifetime
+/// honored by the browsers.
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=29030400, immutable";
+
+/// Initializes the gateway server, serving the blocks of `db` on `/ipfs/{cid}`.
+pub(crate) async fn init_gateway_server<DB>(
+    db: Arc<DB>,
+    tcp_listener: tokio::net::TcpListener,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    let gateway_service = Router::new()
+        .route("/ipfs/{cid}", get(get_block::<DB>))
+        .with_state(db);
+
+    axum::serve(tcp_listener, gateway_service).await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockQuery {
+    format: Option<String>,
+}
+
+async fn get_block<DB: Blockstore>(
+    State(db): State<Arc<DB>>,
+    Path(cid): Path<String>,
+    Query(query): Query<BlockQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let Ok(cid) = Cid::from_str(&cid) else {
+        return (StatusCode::BAD_REQUEST, format!("invalid CID: {cid}")).into_response();
+    };
+    if !accepts_raw(query.format.as_deref(), &headers) {
+        return (
+            StatusCode::NOT_ACCEPTABLE,
+            format!("only raw blocks are served, request `?format=raw` or `{RAW_CONTENT_TYPE}`"),
+        )
+            .into_response();
+    }
+    let block = match db.get(&cid) {
+        Ok(Some(block)) => block,
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, format!("block {cid} not found")).into_response();
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let etag = format!("\"{cid}.raw\"");
+    if etag_matches(&headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response();
+    }
+    (
+        [
+            (CONTENT_TYPE, RAW_CONTENT_TYPE),
+            (ETAG, etag.as_str()),
+            (CACHE_CONTROL, IMMUTABLE_CACHE_CONTROL),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        ],
+        block,
+    )
+        .into_response()
+}
+
+/// The `format` query parameter takes precedence over the `Accept` header.
+fn accepts_raw(format: Option<&str>, headers: &HeaderMap) -> bool {
+    match format {
+        Some(format) => format == "raw",
+        None => headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|it| it.to_str().ok())
+            .flat_map(|it| it.split(','))
+            .any(|it| it.split(';').next().unwrap_or_default().trim() == RAW_CONTENT_TYPE),
+    }
+}
+
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|it| it.to_str().ok())
+        .flat_map(|it| it.split(','))
+        .map(|it| it.trim().trim_start_matches("W/"))
+        .any(|it| it == etag || it == "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::utils::db::CborStoreExt as _;
+
+    #[tokio::test]
+    async fn serve_raw_blocks() {
+        let db = Arc::new(MemoryDB::default());
+        let cid = db.put_cbor_default(&(1u64, "block")).unwrap();
+        let block = db.get(&cid).unwrap().unwrap();
+        let missing = MemoryDB::default().put_cbor_default(&(2u64,)).unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(init_gateway_server(db, listener));
+
+        let client = reqwest::Client::new();
+        let url = |cid: &str, query: &str| format!("http://{address}/ipfs/{cid}{query}");
+
+        let response = client
+            .get(url(&cid.to_string(), "?format=raw"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], RAW_CONTENT_TYPE);
+        let etag = response.headers()[ETAG].clone();
+        assert_eq!(etag, format!("\"{cid}.raw\"").as_str());
+        assert_eq!(response.bytes().await.unwrap(), block);
+
+        let response = client
+            .get(url(&cid.to_string(), ""))
+            .header(ACCEPT, RAW_CONTENT_TYPE)
+            .header(IF_NONE_MATCH, etag)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        for (cid, query, status) in [
+            (cid.to_string(), "", StatusCode::NOT_ACCEPTABLE),
+            (cid.to_string(), "?format=car", StatusCode::NOT_ACCEPTABLE),
+            (missing.to_string(), "?format=raw", StatusCode::NOT_FOUND),
+            (
+                "not-a-cid".to_owned(),
+                "?format=raw",
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let response = client.get(url(&cid, query)).send().await.unwrap();
+            assert_eq!(response.status(), status, "{cid}{query}");
+        }
+    }
+}
@@ -0,0 +1,879 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Read-only GraphQL API over the chain data, for the frontends that prefer GraphQL over
+//! JSON-RPC. The queries are resolved with the same store and state manager reads as the RPC
+//! methods.
+//!
+//! The endpoint is served on `/graphql`, with a GraphiQL playground on `/`. The lists that can be
+//! long are [connections](https://relay.dev/graphql/connections.htm), paginated forward with the
+//! `first` and `after` arguments. The queries are limited in depth and in complexity, the
+//! complexity of the fields of a connection page being multiplied by its size.
+
+use std::str::FromStr as _;
+use std::sync::Arc;
+
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject,
+    connection::{Connection, Edge, query},
+    http::GraphiQLSource,
+};
+use async_graphql_axum::GraphQL;
+use axum::{Router, response::Html, routing::get};
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+
+use crate::blocks::{CachingBlockHeader, Tipset};
+use crate::chain::{block_messages, get_chain_message, index::ResolveNullTipset};
+use crate::interpreter::BlockMessages;
+use crate::message::ChainMessage;
+use crate::shim::{
+    address::Address, clock::ChainEpoch, econ::TokenAmount, executor::Receipt,
+    state_tree::ActorState,
+};
+use crate::state_manager::StateManager;
+
+/// Number of items of a connection page when `first` is omitted
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// Maximum number of items of a connection page
+const MAX_PAGE_SIZE: usize = 100;
+/// Maximum nesting depth of a query
+const MAX_QUERY_DEPTH: usize = 16;
+/// Maximum complexity of a query, i.e. its number of resolved fields
+const MAX_QUERY_COMPLEXITY: usize = 10_000;
+
+pub(crate) type ChainSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Initializes the GraphQL server, resolving the queries with the state manager.
+pub(crate) async fn init_graphql_server<DB>(
+    state_manager: Arc<StateManager<DB>>,
+    tcp_listener: tokio::net::TcpListener,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    let graphql_service = Router::new()
+        .route("/", get(graphiql))
+        .route_service("/graphql", GraphQL::new(schema(state_manager)));
+
+    axum::serve(tcp_listener, graphql_service).await?;
+    Ok(())
+}
+
+fn schema(reader: Arc<dyn ChainReader>) -> ChainSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(reader)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// Reads of the chain data the queries are resolved with, so that the schema does not depend on
+/// the type of the database.
+trait ChainReader: Send + Sync {
+    fn heaviest_tipset(&self) -> Tipset;
+    fn tipset_by_height(
+        &self,
+        epoch: ChainEpoch,
+        head: Tipset,
+        resolve: ResolveNullTipset,
+    ) -> anyhow::Result<Tipset>;
+    fn parent(&self, ts: &Tipset) -> anyhow::Result<Tipset>;
+    fn block_header(&self, cid: &Cid) -> anyhow::Result<Option<CachingBlockHeader>>;
+    fn block_messages(&self, header: &CachingBlockHeader) -> anyhow::Result<Vec<ChainMessage>>;
+    /// Messages of the tipset, deduplicated, in execution order
+    fn tipset_messages(&self, ts: &Tipset) -> anyhow::Result<Vec<ChainMessage>>;
+    fn receipts(&self, receipts_root: Cid) -> anyhow::Result<Vec<Receipt>>;
+    fn message(&self, cid: &Cid) -> anyhow::Result<Option<ChainMessage>>;
+    fn actor(&self, address: &Address, state_root: Cid) -> anyhow::Result<Option<ActorState>>;
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> ChainReader for StateManager<DB> {
+    fn heaviest_tipset(&self) -> Tipset {
+        self.heaviest_tipset()
+    }
+
+    fn tipset_by_height(
+        &self,
+        epoch: ChainEpoch,
+        head: Tipset,
+        resolve: ResolveNullTipset,
+    ) -> anyhow::Result<Tipset> {
+        Ok(self.chain_index().tipset_by_height(epoch, head, resolve)?)
+    }
+
+    fn parent(&self, ts: &Tipset) -> anyhow::Result<Tipset> {
+        self.chain_index().load_required_tipset(ts.parents())
+    }
+
+    fn block_header(&self, cid: &Cid) -> anyhow::Result<Option<CachingBlockHeader>> {
+        CachingBlockHeader::load(self.blockstore(), *cid)
+    }
+
+    fn block_messages(&self, header: &CachingBlockHeader) -> anyhow::Result<Vec<ChainMessage>> {
+        let (unsigned, signed) = block_messages(self.blockstore(), header)?;
+        Ok(unsigned
+            .into_iter()
+            .map(ChainMessage::Unsigned)
+            .chain(signed.into_iter().map(ChainMessage::Signed))
+            .collect())
+    }
+
+    fn tipset_messages(&self, ts: &Tipset) -> anyhow::Result<Vec<ChainMessage>> {
+        Ok(BlockMessages::for_tipset(self.blockstore(), ts)?
+            .into_iter()
+            .flat_map(|it| it.messages)
+            .collect())
+    }
+
+    fn receipts(&self, receipts_root: Cid) -> anyhow::Result<Vec<Receipt>> {
+        Receipt::get_receipts(self.blockstore(), receipts_root)
+    }
+
+    fn message(&self, cid: &Cid) -> anyhow::Result<Option<ChainMessage>> {
+        if !self.blockstore().has(cid)? {
+            return Ok(None);
+        }
+        Ok(Some(get_chain_message(self.blockstore(), cid)?))
+    }
+
+    fn actor(&self, address: &Address, state_root: Cid) -> anyhow::Result<Option<ActorState>> {
+        self.get_actor(address, state_root)
+    }
+}
+
+fn reader<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Arc<dyn ChainReader>> {
+    ctx.data::<Arc<dyn ChainReader>>()
+}
+
+fn parse_cid(cid: &str) -> async_graphql::Result<Cid> {
+    Cid::from_str(cid).map_err(|e| format!("invalid CID {cid}: {e}").into())
+}
+
+fn page_size(first: Option<usize>) -> usize {
+    first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+/// Complexity of a connection page, the complexity of an item times the size of the page
+fn page_complexity(first: Option<i32>, child_complexity: usize) -> usize {
+    page_size(first.and_then(|it| usize::try_from(it).ok())).saturating_mul(child_complexity)
+}
+
+/// A page of messages, with their receipts if any. The cursors are the indices of the messages.
+fn message_page(
+    messages: Vec<ChainMessage>,
+    receipts: &[Receipt],
+    after: Option<usize>,
+    first: Option<usize>,
+) -> Connection<usize, MessageNode> {
+    let start = after.map(|index| index + 1).unwrap_or_default();
+    let end = start.saturating_add(page_size(first)).min(messages.len());
+    let mut connection = Connection::new(start > 0, end < messages.len());
+    connection
+        .edges
+        .extend(
+            messages
+                .into_iter()
+                .enumerate()
+                .take(end)
+                .skip(start)
+                .map(|(index, message)| {
+                    let receipt = receipts.get(index).cloned();
+                    Edge::new(index, MessageNode { message, receipt })
+                }),
+        );
+    connection
+}
+
+pub(crate) struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The heaviest tipset of the node
+    async fn head(&self, ctx: &Context<'_>) -> async_graphql::Result<TipsetNode> {
+        Ok(TipsetNode(reader(ctx)?.heaviest_tipset()))
+    }
+
+    /// The tipset at an epoch of the heaviest chain. A null epoch resolves to the closest earlier
+    /// tipset.
+    async fn tipset(
+        &self,
+        ctx: &Context<'_>,
+        epoch: ChainEpoch,
+    ) -> async_graphql::Result<TipsetNode> {
+        let reader = reader(ctx)?;
+        let head = reader.heaviest_tipset();
+        Ok(TipsetNode(reader.tipset_by_height(
+            epoch,
+            head,
+            ResolveNullTipset::TakeOlder,
+        )?))
+    }
+
+    /// The tipsets of the heaviest chain from the epoch `to`, the head if omitted, down to the
+    /// epoch `from`, most recent first. The cursors are the epochs of the tipsets.
+    #[graphql(complexity = "page_complexity(first, child_complexity)")]
+    async fn tipsets(
+        &self,
+        ctx: &Context<'_>,
+        from: ChainEpoch,
+        to: Option<ChainEpoch>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<ChainEpoch, TipsetNode>> {
+        let reader = reader(ctx)?;
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<ChainEpoch>, _, first, _| async move {
+                let head = reader.heaviest_tipset();
+                let to = [to, after.map(|epoch| epoch - 1)]
+                    .into_iter()
+                    .flatten()
+                    .min()
+                    .unwrap_or(head.epoch())
+                    .min(head.epoch());
+                let limit = page_size(first);
+                let mut tipsets = vec![];
+                if to >= from {
+                    let mut ts = reader.tipset_by_height(to, head, ResolveNullTipset::TakeOlder)?;
+                    // One more tipset than the page tells whether there is a next page
+                    while ts.epoch() >= from && tipsets.len() <= limit {
+                        let parent = (ts.epoch() > 0).then(|| reader.parent(&ts)).transpose()?;
+                        tipsets.push(ts);
+                        match parent {
+                            Some(parent) => ts = parent,
+                            None => break,
+                        }
+                    }
+                }
+                let has_next_page = tipsets.len() > limit;
+                tipsets.truncate(limit);
+                let mut connection = Connection::new(after.is_some(), has_next_page);
+                connection.edges.extend(
+                    tipsets
+                        .into_iter()
+                        .map(|ts| Edge::new(ts.epoch(), TipsetNode(ts))),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// A block header by CID
+    async fn block(
+        &self,
+        ctx: &Context<'_>,
+        cid: String,
+    ) -> async_graphql::Result<Option<BlockNode>> {
+        Ok(reader(ctx)?.block_header(&parse_cid(&cid)?)?.map(BlockNode))
+    }
+
+    /// A message by CID. Its receipt is only resolved from the messages of a tipset.
+    async fn message(
+        &self,
+        ctx: &Context<'_>,
+        cid: String,
+    ) -> async_graphql::Result<Option<MessageNode>> {
+        Ok(reader(ctx)?
+            .message(&parse_cid(&cid)?)?
+            .map(|message| MessageNode {
+                message,
+                receipt: None,
+            }))
+    }
+
+    /// An actor in the state the tipset at the epoch was mined on, the head if omitted
+    async fn actor(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        epoch: Option<ChainEpoch>,
+    ) -> async_graphql::Result<Option<ActorNode>> {
+        let reader = reader(ctx)?;
+        let address = Address::from_str(&address)?;
+        let head = reader.heaviest_tipset();
+        let ts = match epoch {
+            Some(epoch) => reader.tipset_by_height(epoch, head, ResolveNullTipset::TakeOlder)?,
+            None => head,
+        };
+        Ok(reader
+            .actor(&address, *ts.parent_state())?
+            .map(|actor| ActorNode::new(address, actor)))
+    }
+}
+
+pub(crate) struct TipsetNode(Tipset);
+
+#[Object(name = "Tipset")]
+impl TipsetNode {
+    async fn epoch(&self) -> ChainEpoch {
+        self.0.epoch()
+    }
+
+    /// CIDs of the blocks of the tipset
+    async fn key(&self) -> Vec<String> {
+        self.0.cids().iter().map(Cid::to_string).collect()
+    }
+
+    async fn blocks(&self) -> Vec<BlockNode> {
+        self.0
+            .block_headers()
+            .iter()
+            .cloned()
+            .map(BlockNode)
+            .collect()
+    }
+
+    async fn parents(&self) -> Vec<String> {
+        self.0
+            .parents()
+            .to_cids()
+            .into_iter()
+            .map(|cid| cid.to_string())
+            .collect()
+    }
+
+    async fn parent_state_root(&self) -> String {
+        self.0.parent_state().to_string()
+    }
+
+    async fn parent_weight(&self) -> String {
+        self.0.weight().to_string()
+    }
+
+    async fn min_timestamp(&self) -> u64 {
+        self.0.min_timestamp()
+    }
+
+    /// The messages executed in the tipset, in execution order, with their receipts once the
+    /// tipset has a child on the heaviest chain. The cursors are the indices of the messages.
+    #[graphql(complexity = "page_complexity(first, child_complexity)")]
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<usize, MessageNode>> {
+        let reader = reader(ctx)?;
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<usize>, _, first, _| async move {
+                let messages = reader.tipset_messages(&self.0)?;
+                let receipts = match self.child(reader.as_ref())? {
+                    Some(child) => reader.receipts(*child.parent_message_receipts())?,
+                    None => vec![],
+                };
+                Ok::<_, async_graphql::Error>(message_page(messages, &receipts, after, first))
+            },
+        )
+        .await
+    }
+}
+
+impl TipsetNode {
+    /// The child of the tipset on the heaviest chain, which holds the receipts of its messages
+    fn child(&self, reader: &dyn ChainReader) -> anyhow::Result<Option<Tipset>> {
+        let head = reader.heaviest_tipset();
+        if self.0.epoch() >= head.epoch() {
+            return Ok(None);
+        }
+        let child =
+            reader.tipset_by_height(self.0.epoch() + 1, head, ResolveNullTipset::TakeNewer)?;
+        Ok((child.parents() == self.0.key()).then_some(child))
+    }
+}
+
+pub(crate) struct BlockNode(CachingBlockHeader);
+
+#[Object(name = "Block")]
+impl BlockNode {
+    async fn cid(&self) -> String {
+        self.0.cid().to_string()
+    }
+
+    async fn epoch(&self) -> ChainEpoch {
+        self.0.epoch
+    }
+
+    async fn miner(&self) -> String {
+        self.0.miner_address.to_string()
+    }
+
+    async fn timestamp(&self) -> u64 {
+        self.0.timestamp
+    }
+
+    async fn parents(&self) -> Vec<String> {
+        self.0
+            .parents
+            .to_cids()
+            .into_iter()
+            .map(|cid| cid.to_string())
+            .collect()
+    }
+
+    async fn parent_weight(&self) -> String {
+        self.0.weight.to_string()
+    }
+
+    async fn parent_state_root(&self) -> String {
+        self.0.state_root.to_string()
+    }
+
+    /// The parent base fee, in attoFIL
+    async fn parent_base_fee(&self) -> String {
+        self.0.parent_base_fee.atto().to_string()
+    }
+
+    async fn win_count(&self) -> i64 {
+        self.0
+            .election_proof
+            .as_ref()
+            .map(|it| it.win_count)
+            .unwrap_or_default()
+    }
+
+    /// The messages included in the block, the BLS ones first. Their receipts are only resolved
+    /// from the messages of a tipset. The cursors are the indices of the messages.
+    #[graphql(complexity = "page_complexity(first, child_complexity)")]
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<usize, MessageNode>> {
+        let reader = reader(ctx)?;
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<usize>, _, first, _| async move {
+                let messages = reader.block_messages(&self.0)?;
+                Ok::<_, async_graphql::Error>(message_page(messages, &[], after, first))
+            },
+        )
+        .await
+    }
+}
+
+pub(crate) struct MessageNode {
+    message: ChainMessage,
+    receipt: Option<Receipt>,
+}
+
+#[Object(name = "Message")]
+impl MessageNode {
+    async fn cid(&self) -> String {
+        self.message.cid().to_string()
+    }
+
+    async fn from(&self) -> String {
+        self.message.message().from.to_string()
+    }
+
+    async fn to(&self) -> String {
+        self.message.message().to.to_string()
+    }
+
+    async fn nonce(&self) -> u64 {
+        self.message.message().sequence
+    }
+
+    /// The value, in attoFIL
+    async fn value(&self) -> String {
+        self.message.message().value.atto().to_string()
+    }
+
+    async fn method(&self) -> u64 {
+        self.message.message().method_num
+    }
+
+    /// The parameters, in base64
+    async fn params(&self) -> String {
+        BASE64_STANDARD.encode(self.message.message().params.bytes())
+    }
+
+    async fn gas_limit(&self) -> u64 {
+        self.message.message().gas_limit
+    }
+
+    /// The gas fee cap, in attoFIL
+    async fn gas_fee_cap(&self) -> String {
+        self.message.message().gas_fee_cap.atto().to_string()
+    }
+
+    /// The gas premium, in attoFIL
+    async fn gas_premium(&self) -> String {
+        self.message.message().gas_premium.atto().to_string()
+    }
+
+    async fn receipt(&self) -> Option<ReceiptNode> {
+        self.receipt.as_ref().map(ReceiptNode::new)
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Receipt")]
+pub(crate) struct ReceiptNode {
+    exit_code: u32,
+    /// The return value, in base64
+    return_data: String,
+    gas_used: u64,
+    events_root: Option<String>,
+}
+
+impl ReceiptNode {
+    fn new(receipt: &Receipt) -> Self {
+        Self {
+            exit_code: receipt.exit_code().value(),
+            return_data: BASE64_STANDARD.encode(receipt.return_data().bytes()),
+            gas_used: receipt.gas_used(),
+            events_root: receipt.events_root().map(|cid| cid.to_string()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Actor")]
+pub(crate) struct ActorNode {
+    address: String,
+    code: String,
+    /// Root of the state of the actor
+    head: String,
+    nonce: u64,
+    /// The balance, in attoFIL
+    balance: String,
+    delegated_address: Option<String>,
+}
+
+impl ActorNode {
+    fn new(address: Address, actor: ActorState) -> Self {
+        Self {
+            address: address.to_string(),
+            code: actor.code.to_string(),
+            head: actor.state.to_string(),
+            nonce: actor.sequence,
+            balance: TokenAmount::from(&actor.balance).atto().to_string(),
+            delegated_address: actor
+                .delegated_address
+                .map(|it| Address::from(it).to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{Chain4U, chain4u};
+    use crate::chain::ChainStore;
+    use crate::db::MemoryDB;
+    use crate::networks::ChainConfig;
+    use crate::shim::message::Message;
+    use crate::utils::db::CborStoreExt as _;
+    use anyhow::Context as _;
+    use itertools::Itertools as _;
+    use serde_json::json;
+
+    /// Chain of tipsets whose tipsets all hold the same messages and receipts
+    struct FakeChain {
+        tipsets: Vec<Tipset>,
+        messages: Vec<ChainMessage>,
+        receipts: Vec<Receipt>,
+        actor: (Address, ActorState),
+    }
+
+    impl ChainReader for FakeChain {
+        fn heaviest_tipset(&self) -> Tipset {
+            self.tipsets.last().cloned().unwrap()
+        }
+
+        fn tipset_by_height(
+            &self,
+            epoch: ChainEpoch,
+            _head: Tipset,
+            _resolve: ResolveNullTipset,
+        ) -> anyhow::Result<Tipset> {
+            self.tipsets
+                .iter()
+                .find(|ts| ts.epoch() == epoch)
+                .cloned()
+                .context("no tipset at this epoch")
+        }
+
+        fn parent(&self, ts: &Tipset) -> anyhow::Result<Tipset> {
+            self.tipsets
+                .iter()
+                .find(|it| it.key() == ts.parents())
+                .cloned()
+                .context("no parent")
+        }
+
+        fn block_header(&self, cid: &Cid) -> anyhow::Result<Option<CachingBlockHeader>> {
+            Ok(self
+                .tipsets
+                .iter()
+                .flat_map(|ts| ts.block_headers().iter())
+                .find(|header| header.cid() == cid)
+                .cloned())
+        }
+
+        fn block_messages(&self, _: &CachingBlockHeader) -> anyhow::Result<Vec<ChainMessage>> {
+            Ok(self.messages.clone())
+        }
+
+        fn tipset_messages(&self, _: &Tipset) -> anyhow::Result<Vec<ChainMessage>> {
+            Ok(self.messages.clone())
+        }
+
+        fn receipts(&self, _: Cid) -> anyhow::Result<Vec<Receipt>> {
+            Ok(self.receipts.clone())
+        }
+
+        fn message(&self, cid: &Cid) -> anyhow::Result<Option<ChainMessage>> {
+            Ok(self.messages.iter().find(|it| it.cid() == *cid).cloned())
+        }
+
+        fn actor(&self, address: &Address, _: Cid) -> anyhow::Result<Option<ActorState>> {
+            Ok((*address == self.actor.0).then(|| self.actor.1.clone()))
+        }
+    }
+
+    fn fake_chain_schema() -> (ChainSchema, Arc<FakeChain>) {
+        let c4u = Chain4U::new();
+        chain4u! {
+            in c4u;
+            t0 @ [_genesis] -> t1 @ [_b_1] -> t2 @ [_b_2]
+        };
+        let messages = (0..3)
+            .map(|sequence| {
+                ChainMessage::Unsigned(Message {
+                    sequence,
+                    ..Default::default()
+                })
+            })
+            .collect_vec();
+        let receipts = (0..3)
+            .map(|gas_used| {
+                Receipt::V4(fvm_shared4::receipt::Receipt {
+                    exit_code: fvm_shared4::error::ExitCode::OK,
+                    return_data: Default::default(),
+                    gas_used,
+                    events_root: None,
+                })
+            })
+            .collect_vec();
+        let code = MemoryDB::default().put_cbor_default(&"code").unwrap();
+        let actor = ActorState::new(code, code, TokenAmount::from_atto(42), 7, None);
+        let chain = Arc::new(FakeChain {
+            tipsets: vec![t0.clone(), t1.clone(), t2.clone()],
+            messages,
+            receipts,
+            actor: (Address::new_id(1234), actor),
+        });
+        (schema(chain.clone()), chain)
+    }
+
+    #[tokio::test]
+    async fn query_messages_and_receipts() {
+        let (schema, chain) = fake_chain_schema();
+        let query = |epoch: ChainEpoch, after: Option<&str>| {
+            let after = after
+                .map(|cursor| format!(r#", after: "{cursor}""#))
+                .unwrap_or_default();
+            format!(
+                r#"{{ tipset(epoch: {epoch}) {{ messages(first: 2{after}) {{
+                    edges {{ node {{ cid nonce receipt {{ exitCode gasUsed }} }} }}
+                    pageInfo {{ hasNextPage endCursor }}
+                }} }} }}"#
+            )
+        };
+
+        // The receipts of the messages of a tipset are in its child
+        let response = schema.execute(query(1, None)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let messages = &data["tipset"]["messages"];
+        assert_eq!(messages["edges"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            messages["edges"][0]["node"]["cid"],
+            chain.messages[0].cid().to_string()
+        );
+        assert_eq!(messages["edges"][1]["node"]["nonce"], 1);
+        assert_eq!(messages["edges"][1]["node"]["receipt"]["gasUsed"], 1);
+        assert_eq!(messages["edges"][1]["node"]["receipt"]["exitCode"], 0);
+        assert_eq!(messages["pageInfo"]["hasNextPage"], true);
+
+        let end_cursor = messages["pageInfo"]["endCursor"].as_str().unwrap();
+        let response = schema.execute(query(1, Some(end_cursor))).await;
+        let data = response.data.into_json().unwrap();
+        let messages = &data["tipset"]["messages"];
+        assert_eq!(messages["edges"].as_array().unwrap().len(), 1);
+        assert_eq!(messages["edges"][0]["node"]["nonce"], 2);
+        assert_eq!(messages["edges"][0]["node"]["receipt"]["gasUsed"], 2);
+        assert_eq!(messages["pageInfo"]["hasNextPage"], false);
+
+        // The head has no receipts yet
+        let response = schema.execute(query(2, None)).await;
+        let data = response.data.into_json().unwrap();
+        assert_eq!(
+            data["tipset"]["messages"]["edges"][0]["node"]["receipt"],
+            json!(null)
+        );
+
+        // Nor have the messages of a block
+        let response = schema
+            .execute(format!(
+                r#"{{ block(cid: "{}") {{ messages(first: 1) {{
+                    edges {{ node {{ nonce receipt {{ gasUsed }} }} }}
+                    pageInfo {{ hasNextPage }}
+                }} }} }}"#,
+                chain.tipsets[1].block_headers().first().cid()
+            ))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let messages = &data["block"]["messages"];
+        assert_eq!(messages["edges"].as_array().unwrap().len(), 1);
+        assert_eq!(messages["edges"][0]["node"]["receipt"], json!(null));
+        assert_eq!(messages["pageInfo"]["hasNextPage"], true);
+
+        let response = schema
+            .execute(format!(
+                r#"{{ message(cid: "{}") {{ nonce }} }}"#,
+                chain.messages[2].cid()
+            ))
+            .await;
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["message"]["nonce"], 2);
+    }
+
+    #[tokio::test]
+    async fn query_actor() {
+        let (schema, chain) = fake_chain_schema();
+        let response = schema
+            .execute(
+                r#"{
+                    actor(address: "f01234", epoch: 1) { address code nonce balance delegatedAddress }
+                    missing: actor(address: "f05678") { nonce }
+                }"#,
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["actor"]["address"], "f01234");
+        assert_eq!(data["actor"]["code"], chain.actor.1.code.to_string());
+        assert_eq!(data["actor"]["nonce"], 7);
+        assert_eq!(data["actor"]["balance"], "42");
+        assert_eq!(data["actor"]["delegatedAddress"], json!(null));
+        assert_eq!(data["missing"], json!(null));
+
+        let response = schema
+            .execute(r#"{ actor(address: "not an address") { nonce } }"#)
+            .await;
+        assert!(!response.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reject_complex_queries() {
+        let (schema, _) = fake_chain_schema();
+        // Up to 100 messages of up to 100 tipsets
+        let response = schema
+            .execute(
+                r#"{ tipsets(from: 0, first: 100) { edges { node {
+                    messages(first: 100) { edges { node { cid nonce value } } }
+                } } } }"#,
+            )
+            .await;
+        assert!(!response.errors.is_empty());
+
+        let response = schema
+            .execute(
+                r#"{ tipsets(from: 0, first: 10) { edges { node {
+                    messages(first: 10) { edges { node { cid nonce value } } }
+                } } } }"#,
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn query_tipsets_and_blocks() {
+        let db = Arc::new(MemoryDB::default());
+        let c4u = Chain4U::with_blockstore(db.clone());
+        chain4u! {
+            in c4u;
+            [genesis] -> [b_1] -> [b_2_0, b_2_1] -> head @ [b_3]
+        };
+        let cs = ChainStore::new(
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            Arc::new(ChainConfig::default()),
+            CachingBlockHeader::new(genesis.clone()),
+        )
+        .unwrap();
+        cs.set_heaviest_tipset(head.clone()).unwrap();
+        let schema = schema(Arc::new(StateManager::new(Arc::new(cs)).unwrap()));
+
+        let response = schema
+            .execute(format!(
+                r#"{{
+                    head {{ epoch blocks {{ cid }} }}
+                    tipsets(from: 0, first: 2) {{
+                        edges {{ cursor node {{ epoch key }} }}
+                        pageInfo {{ hasNextPage endCursor }}
+                    }}
+                    block(cid: "{}") {{ epoch miner }}
+                }}"#,
+                b_1.cid()
+            ))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["head"]["epoch"], head.epoch());
+        assert_eq!(data["head"]["blocks"][0]["cid"], b_3.cid().to_string());
+        assert_eq!(data["tipsets"]["edges"][0]["node"]["epoch"], head.epoch());
+        assert_eq!(
+            data["tipsets"]["edges"][1]["node"]["key"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(data["tipsets"]["pageInfo"]["hasNextPage"], true);
+        assert_eq!(data["block"]["epoch"], b_1.epoch);
+
+        // The next page resumes below the end cursor
+        let end_cursor = data["tipsets"]["pageInfo"]["endCursor"].as_str().unwrap();
+        let response = schema
+            .execute(format!(
+                r#"{{ tipsets(from: 0, first: 10, after: "{end_cursor}") {{
+                    edges {{ node {{ epoch }} }}
+                    pageInfo {{ hasNextPage }}
+                }} }}"#
+            ))
+            .await;
+        let data = response.data.into_json().unwrap();
+        let epochs = data["tipsets"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["epoch"].clone())
+            .collect_vec();
+        assert_eq!(epochs, [json!(b_1.epoch), json!(genesis.epoch)]);
+        assert_eq!(data["tipsets"]["pageInfo"]["hasNextPage"], false);
+    }
+}
@@ -2,16 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod empty_map;
+mod walk;
+
 use std::ops::SubAssign;
 
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-#[cfg(test)]
 use super::Ipld;
-#[cfg(test)]
 use Selector::*;
 
+pub use walk::walk_dag;
+
 /// Selectors are expressions that identify and select a subset of data from an
 /// IPLD DAG. Selectors are themselves IPLD and can be serialized and
 /// de-serialized as such.
@@ -202,7 +204,6 @@ pub enum Condition {
     Or,
 }
 
-#[cfg(test)]
 impl Selector {
     /// Processes and returns resultant selector node
     pub fn explore(self, ipld: &Ipld, p: &str) -> Option<Selector> {
@@ -301,6 +302,29 @@ impl Selector {
         }
     }
 
+    /// Returns the selector of the sub-DAG at the path, i.e. the `/`-separated fields or list
+    /// indices from the root, links being followed transparently. The sub-DAG is explored up to
+    /// `depth` IPLD nodes below the path, or entirely if `None`.
+    pub fn from_path(path: &str, depth: Option<u64>) -> Self {
+        let sub_dag = match depth {
+            Some(0) => Matcher,
+            _ => ExploreRecursive {
+                sequence: Box::new(ExploreAll {
+                    next: Box::new(ExploreRecursiveEdge),
+                }),
+                limit: depth.map_or(RecursionLimit::None, RecursionLimit::Depth),
+                stop_at: None,
+                current: None,
+            },
+        };
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .rev()
+            .fold(sub_dag, |next, segment| ExploreFields {
+                fields: IndexMap::from_iter([(segment.to_owned(), next)]),
+            })
+    }
+
     fn from_selectors(mut vec: Vec<Self>) -> Option<Self> {
         match vec.len() {
             0 | 1 => vec.pop(),
@@ -309,7 +333,6 @@ impl Selector {
     }
 }
 
-#[cfg(test)]
 fn replace_recursive_edge(next_sel: Selector, replace: Option<Selector>) -> Option<Selector> {
     match next_sel {
         ExploreRecursiveEdge => replace,
@@ -326,7 +349,6 @@ fn replace_recursive_edge(next_sel: Selector, replace: Option<Selector>) -> Opti
     }
 }
 
-#[cfg(test)]
 fn has_recursive_edge(next_sel: &Selector) -> bool {
     match next_sel {
         ExploreRecursiveEdge => true,
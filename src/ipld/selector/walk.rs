@@ -0,0 +1,98 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Context as _;
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::DAG_CBOR;
+
+use super::{Ipld, Selector};
+use crate::cid_collections::CidHashSet;
+
+/// Walks the DAG from the root with the selector, calling `on_block` once on each block covered
+/// by the traversal, i.e. the blocks of the selected nodes and of the nodes leading to them, in
+/// depth-first order. Links are followed transparently, only `DAG_CBOR` blocks are explored.
+pub fn walk_dag<BS: Blockstore>(
+    store: &BS,
+    root: Cid,
+    selector: Selector,
+    mut on_block: impl FnMut(Cid, Vec<u8>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut seen = CidHashSet::default();
+    let mut stack = vec![(Ipld::Link(root), selector)];
+    while let Some((node, selector)) = stack.pop() {
+        let node = match node {
+            Ipld::Link(cid) => {
+                let block = store
+                    .get(&cid)?
+                    .with_context(|| format!("block {cid} is missing from the store"))?;
+                let node = if cid.codec() == DAG_CBOR {
+                    Some(fvm_ipld_encoding::from_slice::<Ipld>(&block)?)
+                } else {
+                    None
+                };
+                if seen.insert(cid) {
+                    on_block(cid, block)?;
+                }
+                match node {
+                    Some(node) => node,
+                    None => continue,
+                }
+            }
+            node => node,
+        };
+        if selector == Selector::Matcher {
+            continue;
+        }
+        let children: Vec<(String, Ipld)> = match &node {
+            Ipld::Map(map) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            Ipld::List(list) => list
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v.clone()))
+                .collect(),
+            _ => continue,
+        };
+        // Pushed in reverse so that the children are walked in order.
+        for (segment, child) in children.into_iter().rev() {
+            if let Some(next) = selector.clone().explore(&node, &segment) {
+                stack.push((child, next));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::utils::db::CborStoreExt as _;
+
+    #[test]
+    fn walk_path() {
+        let db = MemoryDB::default();
+        let leaf = db.put_cbor_default(&(1u64, 2u64)).unwrap();
+        let skipped = db.put_cbor_default(&(3u64,)).unwrap();
+        let inner = db.put_cbor_default(&(leaf, skipped)).unwrap();
+        let root = db.put_cbor_default(&(0u64, inner)).unwrap();
+        let walk = |selector| {
+            let mut cids = vec![];
+            walk_dag(&db, root, selector, |cid, _| {
+                cids.push(cid);
+                Ok(())
+            })
+            .unwrap();
+            cids
+        };
+        assert_eq!(
+            walk(Selector::from_path("", None)),
+            vec![root, inner, leaf, skipped]
+        );
+        assert_eq!(
+            walk(Selector::from_path("/1/0", None)),
+            vec![root, inner, leaf]
+        );
+        assert_eq!(walk(Selector::from_path("1", Some(0))), vec![root, inner]);
+    }
+}
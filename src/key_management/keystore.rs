@@ -330,6 +330,18 @@ impl KeyStore {
         Ok(())
     }
 
+    /// Insert the `KeyInfo` of a key, replacing the existing one in a single write. Returns the
+    /// replaced `KeyInfo`.
+    pub fn replace(&mut self, key: &str, key_info: KeyInfo) -> anyhow::Result<Option<KeyInfo>> {
+        let replaced = self.key_info.insert(key.to_string(), key_info);
+
+        if self.persistence.is_some() {
+            self.flush()?;
+        }
+
+        Ok(replaced)
+    }
+
     /// Remove the key and corresponding `KeyInfo` from the `KeyStore`
     pub fn remove(&mut self, key: &str) -> anyhow::Result<KeyInfo> {
         let key_out = self.key_info.remove(key).ok_or(Error::KeyInfo)?;
@@ -411,6 +423,24 @@ impl EncryptedKeyStore {
     }
 }
 
+/// Encrypts data with a passphrase like the encrypted keystore: the `Argon2id` salt of the key is
+/// prepended to the `XSalsa20Poly1305` ciphertext.
+pub fn encrypt_with_passphrase(passphrase: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let (salt, encryption_key) = EncryptedKeyStore::derive_key(passphrase, None)?;
+    let mut encrypted = salt.to_vec();
+    encrypted.extend(EncryptedKeyStore::encrypt(&encryption_key, data)?);
+    Ok(encrypted)
+}
+
+/// Decrypts data encrypted with [`encrypt_with_passphrase`].
+pub fn decrypt_with_passphrase(passphrase: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let (salt, ciphertext) = data
+        .split_first_chunk::<RECOMMENDED_SALT_LEN>()
+        .ok_or_else(|| anyhow::anyhow!("encrypted data is too short"))?;
+    let (_, encryption_key) = EncryptedKeyStore::derive_key(passphrase, Some(*salt))?;
+    EncryptedKeyStore::decrypt(&encryption_key, ciphertext)
+}
+
 fn map_err_to_anyhow<T: Display>(e: T) -> anyhow::Error {
     anyhow::Error::msg(e.to_string())
 }
@@ -449,6 +479,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_passphrase_encryption() {
+        let message = b"foo is coming";
+        let encrypted = encrypt_with_passphrase(PASSPHRASE, message).unwrap();
+        assert_eq!(
+            decrypt_with_passphrase(PASSPHRASE, &encrypted).unwrap(),
+            message
+        );
+        assert!(decrypt_with_passphrase("wrong", &encrypted).is_err());
+        assert!(decrypt_with_passphrase(PASSPHRASE, &encrypted[..8]).is_err());
+    }
+
     #[test]
     fn test_decrypt_message() {
         let (_, private_key) = EncryptedKeyStore::derive_key(PASSPHRASE, None).unwrap();
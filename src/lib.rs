@@ -52,7 +52,10 @@ mod documentation;
 mod eth;
 mod f3;
 mod fil_cns;
+mod gateway;
 mod genesis;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod health;
 mod interpreter;
 mod ipld;
@@ -105,6 +108,12 @@ pub mod doctest_private {
 #[doc(hidden)]
 pub mod benchmark_private;
 
+/// These items are semver-exempt, and exist for forest author use only
+// Allow fuzzing of forest internals
+#[cfg(feature = "fuzz-private")]
+#[doc(hidden)]
+pub mod fuzz_private;
+
 /// These items are semver-exempt, and exist for forest author use only
 // Allow interop tests of forest internals
 #[cfg(feature = "interop-tests-private")]
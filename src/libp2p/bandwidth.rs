@@ -0,0 +1,382 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Bandwidth accounting in total, per peer and per protocol.
+//!
+//! The totals and the per-peer counts include all the traffic of the connections, metered by
+//! [`BandwidthMuxer`]. The muxer does not know which protocol a stream speaks, so the
+//! per-protocol counts are metered by the codecs of the request-response protocols, and by
+//! topic for `gossipsub` messages. They only count towards the `protocol_bandwidth_bytes`
+//! metric, the per-peer counts are not exported as metrics to keep their cardinality bounded.
+
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, ready},
+    time::Instant,
+};
+
+use ahash::HashMap;
+use futures::{AsyncRead, AsyncWrite};
+use libp2p::{
+    PeerId,
+    core::muxing::{StreamMuxer, StreamMuxerEvent},
+};
+use parking_lot::{Mutex, RwLock};
+use pin_project_lite::pin_project;
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family},
+};
+
+/// Bandwidth of the node.
+pub static BANDWIDTH: LazyLock<BandwidthCounter> = LazyLock::new(BandwidthCounter::default);
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ProtocolLabel {
+    protocol: String,
+    direction: &'static str,
+}
+
+static PROTOCOL_BANDWIDTH_BYTES: LazyLock<Family<ProtocolLabel, Counter>> = LazyLock::new(|| {
+    let metric = Family::default();
+    crate::metrics::default_registry().register(
+        "protocol_bandwidth_bytes",
+        "Bytes sent and received per protocol",
+        metric.clone(),
+    );
+    metric
+});
+
+/// Bytes transferred, and the transfer rates in bytes per second.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub total_in: u64,
+    pub total_out: u64,
+    pub rate_in: f64,
+    pub rate_out: f64,
+}
+
+#[derive(Debug, Default)]
+struct Rates {
+    sampled_at: Option<Instant>,
+    total_in: u64,
+    total_out: u64,
+    rate_in: f64,
+    rate_out: f64,
+}
+
+/// Counts the bytes transferred in both directions.
+#[derive(Debug, Default)]
+pub struct Meter {
+    total_in: AtomicU64,
+    total_out: AtomicU64,
+    rates: Mutex<Rates>,
+    /// Meter that every transfer also counts towards.
+    parent: Option<Arc<Meter>>,
+    /// Metrics of the inbound and outbound transfers.
+    counters: Option<(Counter, Counter)>,
+}
+
+impl Meter {
+    pub fn record_in(&self, bytes: usize) {
+        self.total_in.fetch_add(bytes as u64, Ordering::Relaxed);
+        if let Some(parent) = &self.parent {
+            parent.record_in(bytes);
+        }
+        if let Some((counter, _)) = &self.counters {
+            counter.inc_by(bytes as u64);
+        }
+    }
+
+    pub fn record_out(&self, bytes: usize) {
+        self.total_out.fetch_add(bytes as u64, Ordering::Relaxed);
+        if let Some(parent) = &self.parent {
+            parent.record_out(bytes);
+        }
+        if let Some((_, counter)) = &self.counters {
+            counter.inc_by(bytes as u64);
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let rates = self.rates.lock();
+        Stats {
+            total_in: self.total_in.load(Ordering::Relaxed),
+            total_out: self.total_out.load(Ordering::Relaxed),
+            rate_in: rates.rate_in,
+            rate_out: rates.rate_out,
+        }
+    }
+
+    /// Sets the rates to the average since the previous update.
+    fn update_rates(&self, now: Instant) {
+        let total_in = self.total_in.load(Ordering::Relaxed);
+        let total_out = self.total_out.load(Ordering::Relaxed);
+        let mut rates = self.rates.lock();
+        if let Some(sampled_at) = rates.sampled_at {
+            let secs = now.duration_since(sampled_at).as_secs_f64();
+            if secs > 0.0 {
+                rates.rate_in = total_in.saturating_sub(rates.total_in) as f64 / secs;
+                rates.rate_out = total_out.saturating_sub(rates.total_out) as f64 / secs;
+            }
+        }
+        rates.sampled_at = Some(now);
+        rates.total_in = total_in;
+        rates.total_out = total_out;
+    }
+
+    fn is_idle(&self) -> bool {
+        let rates = self.rates.lock();
+        rates.rate_in == 0.0 && rates.rate_out == 0.0
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BandwidthCounter {
+    total: Arc<Meter>,
+    peers: RwLock<HashMap<PeerId, Arc<Meter>>>,
+    protocols: RwLock<HashMap<String, Arc<Meter>>>,
+}
+
+impl BandwidthCounter {
+    /// Returns the meter of a peer, which also counts towards the totals.
+    pub fn peer_meter(&self, peer_id: PeerId) -> Arc<Meter> {
+        self.peers
+            .write()
+            .entry(peer_id)
+            .or_insert_with(|| {
+                Arc::new(Meter {
+                    parent: Some(self.total.clone()),
+                    ..Default::default()
+                })
+            })
+            .clone()
+    }
+
+    /// Returns the meter of a protocol.
+    pub fn protocol_meter(&self, protocol: &str) -> Arc<Meter> {
+        if let Some(meter) = self.protocols.read().get(protocol) {
+            return meter.clone();
+        }
+        self.protocols
+            .write()
+            .entry(protocol.to_owned())
+            .or_insert_with(|| {
+                let counter = |direction| {
+                    PROTOCOL_BANDWIDTH_BYTES
+                        .get_or_create(&ProtocolLabel {
+                            protocol: protocol.to_owned(),
+                            direction,
+                        })
+                        .clone()
+                };
+                Arc::new(Meter {
+                    counters: Some((counter("in"), counter("out"))),
+                    ..Default::default()
+                })
+            })
+            .clone()
+    }
+
+    pub fn totals(&self) -> Stats {
+        self.total.stats()
+    }
+
+    pub fn by_peer(&self) -> HashMap<PeerId, Stats> {
+        self.peers
+            .read()
+            .iter()
+            .map(|(peer_id, meter)| (*peer_id, meter.stats()))
+            .collect()
+    }
+
+    pub fn by_protocol(&self) -> HashMap<String, Stats> {
+        self.protocols
+            .read()
+            .iter()
+            .map(|(protocol, meter)| (protocol.clone(), meter.stats()))
+            .collect()
+    }
+
+    /// Updates the rates of all the meters, and drops the meters of the peers that are no
+    /// longer connected once they are idle.
+    pub fn update_rates(&self) {
+        let now = Instant::now();
+        self.total.update_rates(now);
+        self.peers.write().retain(|_, meter| {
+            meter.update_rates(now);
+            Arc::strong_count(meter) > 1 || !meter.is_idle()
+        });
+        for meter in self.protocols.read().values() {
+            meter.update_rates(now);
+        }
+    }
+}
+
+pin_project! {
+    /// A [`StreamMuxer`] that meters the streams of a connection.
+    pub struct BandwidthMuxer<M> {
+        #[pin]
+        inner: M,
+        meter: Arc<Meter>,
+    }
+}
+
+impl<M> BandwidthMuxer<M> {
+    pub fn new(inner: M, peer_id: PeerId) -> Self {
+        Self {
+            inner,
+            meter: BANDWIDTH.peer_meter(peer_id),
+        }
+    }
+}
+
+impl<M: StreamMuxer> StreamMuxer for BandwidthMuxer<M> {
+    type Substream = MeteredStream<M::Substream>;
+    type Error = M::Error;
+
+    fn poll_inbound(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let this = self.project();
+        let stream = ready!(this.inner.poll_inbound(cx))?;
+        Poll::Ready(Ok(MeteredStream::new(stream, this.meter.clone())))
+    }
+
+    fn poll_outbound(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let this = self.project();
+        let stream = ready!(this.inner.poll_outbound(cx))?;
+        Poll::Ready(Ok(MeteredStream::new(stream, this.meter.clone())))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().inner.poll_close(cx)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+        self.project().inner.poll(cx)
+    }
+}
+
+pin_project! {
+    /// A stream that counts the bytes read and written with a [`Meter`].
+    pub struct MeteredStream<S> {
+        #[pin]
+        inner: S,
+        meter: Arc<Meter>,
+    }
+}
+
+impl<S> MeteredStream<S> {
+    pub fn new(inner: S, meter: Arc<Meter>) -> Self {
+        Self { inner, meter }
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for MeteredStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let n = ready!(this.inner.poll_read(cx, buf))?;
+        this.meter.record_in(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [io::IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let n = ready!(this.inner.poll_read_vectored(cx, bufs))?;
+        this.meter.record_in(n);
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for MeteredStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let n = ready!(this.inner.poll_write(cx, buf))?;
+        this.meter.record_out(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let n = ready!(this.inner.poll_write_vectored(cx, bufs))?;
+        this.meter.record_out(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{AsyncReadExt as _, AsyncWriteExt as _};
+
+    #[tokio::test]
+    async fn metered_stream() {
+        let counter = BandwidthCounter::default();
+        let peer_id = PeerId::random();
+        let peer_meter = counter.peer_meter(peer_id);
+        let protocol_meter = counter.protocol_meter("/test/1.0.0");
+
+        let mut stream = MeteredStream::new(
+            MeteredStream::new(futures::io::Cursor::new(vec![0; 10]), peer_meter.clone()),
+            protocol_meter,
+        );
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(&[1; 6]).await.unwrap();
+        drop(stream);
+
+        let expected = Stats {
+            total_in: 4,
+            total_out: 6,
+            ..Default::default()
+        };
+        assert_eq!(counter.totals(), expected);
+        assert_eq!(counter.by_peer()[&peer_id], expected);
+        assert_eq!(counter.by_protocol()["/test/1.0.0"], expected);
+
+        // The peer is still referenced, its stats are kept.
+        counter.update_rates();
+        assert!(counter.by_peer().contains_key(&peer_id));
+        // Once idle and no longer referenced, they are dropped.
+        drop(peer_meter);
+        counter.update_rates();
+        assert!(!counter.by_peer().contains_key(&peer_id));
+    }
+}
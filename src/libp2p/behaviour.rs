@@ -10,7 +10,7 @@ use super::{
     PeerManager,
     discovery::{DerivedDiscoveryBehaviourEvent, DiscoveryEvent, PeerInfo},
 };
-use crate::libp2p_bitswap::BitswapBehaviour;
+use crate::libp2p_bitswap::{BitswapBehaviour, BitswapServerConfig};
 use crate::utils::{encoding::blake2b_256, version::FOREST_VERSION_STRING};
 use crate::{
     libp2p::{
@@ -122,7 +122,11 @@ impl ForestBehaviour {
             ],
             request_response::Config::default()
                 .with_max_concurrent_streams(max_concurrent_request_response_streams),
-        );
+        )
+        .with_server_config(BitswapServerConfig {
+            max_bytes_per_peer_per_second: config.bitswap_max_bytes_per_peer_per_second,
+            chain_data_only: config.bitswap_chain_data_only,
+        });
         crate::libp2p_bitswap::register_metrics(&mut crate::metrics::collector_registry());
 
         let discovery = DiscoveryConfig::new(local_key.public(), network_name)
@@ -9,6 +9,7 @@ use libp2p::{
     },
     swarm::{NetworkBehaviour, THandlerOutEvent, derive_prelude::*},
 };
+use strum::IntoEnumIterator as _;
 use tracing::debug;
 
 use super::*;
@@ -28,7 +29,7 @@ impl ChainExchangeBehaviour {
     pub fn new(cfg: request_response::Config) -> Self {
         Self {
             inner: InnerBehaviour::new(
-                [(CHAIN_EXCHANGE_PROTOCOL_NAME, ProtocolSupport::Full)],
+                ChainExchangeProtocol::iter().map(|protocol| (protocol, ProtocolSupport::Full)),
                 cfg,
             ),
             response_channels: Default::default(),
@@ -6,6 +6,7 @@ use std::convert::TryFrom;
 use crate::blocks::{BLOCK_MESSAGE_LIMIT, Block, CachingBlockHeader, FullTipset, Tipset};
 use crate::message::SignedMessage;
 use crate::shim::message::Message;
+use ahash::HashMap;
 use cid::Cid;
 use fvm_ipld_encoding::tuple::*;
 use nunny::Vec as NonEmpty;
@@ -141,6 +142,7 @@ impl ChainExchangeResponse {
     }
 }
 /// Contains all BLS and SECP messages and their indexes per block
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct CompactedMessages {
     /// Unsigned BLS messages.
@@ -156,6 +158,7 @@ pub struct CompactedMessages {
 }
 
 /// Contains the blocks and messages in a particular tipset
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple, Default)]
 pub struct TipsetBundle {
     /// The blocks in the tipset.
@@ -198,6 +201,61 @@ impl TryFrom<&TipsetBundle> for FullTipset {
     }
 }
 
+impl From<FullTipset> for TipsetBundle {
+    fn from(fts: FullTipset) -> Self {
+        // Appends the messages that are not compacted yet, and returns the indexes of all of them.
+        fn compact<T>(
+            messages: Vec<T>,
+            cid: impl Fn(&T) -> Cid,
+            order: &mut HashMap<Cid, u64>,
+            compacted: &mut Vec<T>,
+        ) -> Vec<u64> {
+            messages
+                .into_iter()
+                .map(|message| {
+                    *order.entry(cid(&message)).or_insert_with(|| {
+                        compacted.push(message);
+                        compacted.len() as u64 - 1
+                    })
+                })
+                .collect()
+        }
+
+        let (mut bls_order, mut secp_order) = (HashMap::default(), HashMap::default());
+        let mut blocks = vec![];
+        let mut messages = CompactedMessages {
+            bls_msgs: vec![],
+            bls_msg_includes: vec![],
+            secp_msgs: vec![],
+            secp_msg_includes: vec![],
+        };
+        for block in fts.into_blocks() {
+            let Block {
+                header,
+                bls_messages,
+                secp_messages,
+            } = block;
+            blocks.push(header);
+            messages.bls_msg_includes.push(compact(
+                bls_messages,
+                Message::cid,
+                &mut bls_order,
+                &mut messages.bls_msgs,
+            ));
+            messages.secp_msg_includes.push(compact(
+                secp_messages,
+                SignedMessage::cid,
+                &mut secp_order,
+                &mut messages.secp_msgs,
+            ));
+        }
+        TipsetBundle {
+            blocks,
+            messages: Some(messages),
+        }
+    }
+}
+
 /// Constructs a [`FullTipset`] from headers and compacted messages from a
 /// bundle.
 fn fts_from_bundle_parts(
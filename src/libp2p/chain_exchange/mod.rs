@@ -7,11 +7,74 @@ mod provider;
 pub use behaviour::*;
 
 pub use self::{message::*, provider::*};
-use super::rpc::CborRequestResponse;
+use super::rpc::{CborProtocol, CborRequestResponse};
 
 /// Libp2p protocol name for `ChainExchange`.
 pub const CHAIN_EXCHANGE_PROTOCOL_NAME: &str = "/fil/chain/xchg/0.0.1";
 
+/// Libp2p protocol name for `ChainExchange` with `zstd`-compressed responses. It is a Forest
+/// extension, not part of the Filecoin specification. Compacted messages compress well, so it is
+/// preferred over [`CHAIN_EXCHANGE_PROTOCOL_NAME`], which remains supported for the peers that
+/// do not speak it, e.g. Lotus nodes.
+pub const CHAIN_EXCHANGE_ZSTD_PROTOCOL_NAME: &str = "/forest/chain/xchg/zstd/0.0.1";
+
+/// Versions of the `ChainExchange` protocol, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumIter)]
+pub enum ChainExchangeProtocol {
+    Zstd,
+    Uncompressed,
+}
+
+impl AsRef<str> for ChainExchangeProtocol {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Zstd => CHAIN_EXCHANGE_ZSTD_PROTOCOL_NAME,
+            Self::Uncompressed => CHAIN_EXCHANGE_PROTOCOL_NAME,
+        }
+    }
+}
+
+impl CborProtocol for ChainExchangeProtocol {
+    fn zstd_responses(&self) -> bool {
+        matches!(self, Self::Zstd)
+    }
+}
+
 /// `ChainExchange` protocol codec to be used within the RPC service.
 pub type ChainExchangeCodec =
-    CborRequestResponse<&'static str, ChainExchangeRequest, ChainExchangeResponse>;
+    CborRequestResponse<ChainExchangeProtocol, ChainExchangeRequest, ChainExchangeResponse>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::request_response::Codec as _;
+    use strum::IntoEnumIterator as _;
+
+    #[tokio::test]
+    async fn compressed_responses() {
+        let response = ChainExchangeResponse {
+            status: ChainExchangeResponseStatus::GoAway,
+            message: "go away ".repeat(100),
+            chain: vec![],
+        };
+        let uncompressed = fvm_ipld_encoding::to_vec(&response).unwrap();
+        for protocol in ChainExchangeProtocol::iter() {
+            let mut codec = ChainExchangeCodec::default();
+            let mut io = futures::io::Cursor::new(vec![]);
+            codec
+                .write_response(&protocol, &mut io, response.clone())
+                .await
+                .unwrap();
+            let bytes = io.into_inner();
+            assert_eq!(
+                bytes.len() < uncompressed.len(),
+                protocol == ChainExchangeProtocol::Zstd
+            );
+            let decoded = codec
+                .read_response(&protocol, &mut bytes.as_slice())
+                .await
+                .unwrap();
+            assert_eq!(decoded, response);
+        }
+    }
+}
@@ -27,12 +27,24 @@ pub struct Libp2pConfig {
         |g| vec![Ipv4Addr::arbitrary(g).into()]
     )))]
     pub bootstrap_peers: Vec<Multiaddr>,
+    /// Use only the bootstrap peers above, even if there are none, instead of falling back to the
+    /// built-in bootstrap peers of the network, and do not reconnect to the peers of the previous
+    /// run.
+    pub override_bootstrap_peers: bool,
     /// MDNS discovery enabled.
     pub mdns: bool,
     /// Kademlia discovery enabled.
     pub kademlia: bool,
     /// Target peer count.
     pub target_peer_count: u32,
+    /// Number of connected peers below which the node bootstraps again, dialing the bootstrap
+    /// peers and running a Kademlia bootstrap. Checked every minute, `0` disables it.
+    pub rebootstrap_peer_threshold: u32,
+    /// Maximum number of bytes of blocks served to each peer over bitswap per second. `0`, the
+    /// default, disables the limit.
+    pub bitswap_max_bytes_per_peer_per_second: u64,
+    /// Serve only chain data over bitswap, i.e. the `DAG_CBOR` blocks hashed with `Blake2b256`.
+    pub bitswap_chain_data_only: bool,
 }
 
 impl Default for Libp2pConfig {
@@ -43,9 +55,13 @@ impl Default for Libp2pConfig {
                 "/ip4/0.0.0.0/udp/0/quic-v1".parse().expect("Infallible"),
             ],
             bootstrap_peers: vec![],
+            override_bootstrap_peers: false,
             mdns: false,
             kademlia: true,
             target_peer_count: 75,
+            rebootstrap_peer_threshold: 25,
+            bitswap_max_bytes_per_peer_per_second: 0,
+            bitswap_chain_data_only: false,
         }
     }
 }
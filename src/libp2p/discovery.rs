@@ -29,6 +29,7 @@ use libp2p::{
 use tokio::time::Interval;
 use tracing::{debug, info, trace, warn};
 
+use super::peerstore::Peerstore;
 use crate::{networks::GenesisNetworkName, utils::version::FOREST_VERSION_STRING};
 
 #[derive(NetworkBehaviour)]
@@ -102,12 +103,8 @@ impl<'a> DiscoveryConfig<'a> {
         user_defined: impl IntoIterator<Item = Multiaddr>,
     ) -> anyhow::Result<Self> {
         for mut addr in user_defined.into_iter() {
-            if let Some((_, Protocol::Dnsaddr(addr))) = addr
-                .iter()
-                .enumerate()
-                .find(|(_, p)| matches!(p, Protocol::Dnsaddr(_)))
-            {
-                for pair in resolve_libp2p_dnsaddr(&addr).await? {
+            if let Some(name) = dnsaddr_name(&addr) {
+                for pair in resolve_libp2p_dnsaddr(&name).await? {
                     self.user_defined.push(pair)
                 }
             } else if let Some(Protocol::P2p(peer_id)) = addr.pop() {
@@ -286,6 +283,22 @@ impl DiscoveryBehaviour {
         }
     }
 
+    /// Adds the addresses of a known peer, e.g. from the peerstore or a refreshed bootstrap list,
+    /// and dials it unless it is already connected.
+    pub fn add_known_peer(&mut self, peer_id: PeerId, addresses: Vec<Multiaddr>) {
+        if let Some(kademlia) = self.discovery.kademlia.as_mut() {
+            for address in &addresses {
+                kademlia.add_address(&peer_id, address.clone());
+            }
+        }
+        self.pending_dial_opts.push_back(
+            DialOpts::peer_id(peer_id)
+                .condition(PeerCondition::Disconnected)
+                .addresses(addresses)
+                .build(),
+        );
+    }
+
     /// Gets the NAT status.
     pub fn nat_status(&self) -> autonat::NatStatus {
         self.discovery.autonat.nat_status()
@@ -553,7 +566,61 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 
 // Note: The function is async because the sync API `hickory_resolver::Resolver` is a wrapper of
 // the async API and does not work inside another tokio runtime
-async fn resolve_libp2p_dnsaddr(name: &str) -> anyhow::Result<Vec<(PeerId, Multiaddr)>> {
+/// Returns the name of a `/dnsaddr` address.
+pub(in crate::libp2p) fn dnsaddr_name(addr: &Multiaddr) -> Option<String> {
+    addr.iter().find_map(|p| match p {
+        Protocol::Dnsaddr(name) => Some(name.into_owned()),
+        _ => None,
+    })
+}
+
+/// Resolves the `/dnsaddr` bootstrap addresses into `/p2p` terminated addresses, and caches the
+/// resolutions in the peerstore. A name that fails to resolve falls back to its last cached
+/// resolution, so that an unreachable DNS server does not prevent bootstrapping.
+pub(in crate::libp2p) async fn resolve_bootstrap_peers(
+    bootstrap_peers: &[Multiaddr],
+    peerstore: &mut Peerstore,
+) -> Vec<Multiaddr> {
+    let mut resolved = Vec::with_capacity(bootstrap_peers.len());
+    for addr in bootstrap_peers {
+        let Some(name) = dnsaddr_name(addr) else {
+            resolved.push(addr.clone());
+            continue;
+        };
+        let pairs = match resolve_libp2p_dnsaddr(&name).await {
+            Ok(pairs) if !pairs.is_empty() => {
+                peerstore.set_dnsaddr_peers(&name, &pairs);
+                pairs
+            }
+            result => {
+                let cached = peerstore.dnsaddr_peers(&name);
+                match result {
+                    Ok(_) => warn!(
+                        "No peers found at {addr}, using {} cached peers",
+                        cached.len()
+                    ),
+                    Err(e) => {
+                        warn!(
+                            "Failed to resolve {addr}: {e}, using {} cached peers",
+                            cached.len()
+                        )
+                    }
+                }
+                cached
+            }
+        };
+        resolved.extend(
+            pairs
+                .into_iter()
+                .map(|(peer_id, addr)| addr.with(Protocol::P2p(peer_id))),
+        );
+    }
+    resolved
+}
+
+pub(in crate::libp2p) async fn resolve_libp2p_dnsaddr(
+    name: &str,
+) -> anyhow::Result<Vec<(PeerId, Multiaddr)>> {
     let resolver = hickory_resolver::TokioResolver::builder_tokio()?.build();
 
     let name = ["_dnsaddr.", name].concat();
@@ -626,6 +693,32 @@ mod tests {
         run.retry(ExponentialBuilder::default()).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn resolve_bootstrap_peers_fallback() {
+        let cached = (
+            PeerId::random(),
+            Multiaddr::from_str("/ip4/1.2.3.4/tcp/1234").unwrap(),
+        );
+        let mut peerstore = Peerstore::default();
+        peerstore.set_dnsaddr_peers("bootstrap.invalid", &[cached.clone()]);
+
+        let static_peer = Multiaddr::from_str("/ip4/5.6.7.8/tcp/5678")
+            .unwrap()
+            .with(Protocol::P2p(PeerId::random()));
+        let resolved = resolve_bootstrap_peers(
+            &[
+                Multiaddr::from_str("/dnsaddr/bootstrap.invalid").unwrap(),
+                static_peer.clone(),
+            ],
+            &mut peerstore,
+        )
+        .await;
+        assert_eq!(
+            resolved,
+            vec![cached.1.with(Protocol::P2p(cached.0)), static_peer]
+        );
+    }
+
     #[tokio::test]
     async fn kademlia_test() {
         async fn new_discovery(
@@ -1,6 +1,7 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod bandwidth;
 mod behaviour;
 pub mod chain_exchange;
 mod config;
@@ -10,6 +11,7 @@ pub mod hello;
 pub mod keypair;
 pub mod metrics;
 mod peer_manager;
+mod peerstore;
 pub mod ping;
 pub mod rpc;
 mod service;
@@ -0,0 +1,206 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! A persistent store of good peers and of the last resolution of the `dnsaddr` bootstrap
+//! entries. It lets the node reconnect to the peers it knew before a restart, and bootstrap when
+//! the bootstrap nodes or their DNS records are unreachable.
+
+use std::{collections::BTreeMap, io::Write as _, path::Path, time::Duration};
+
+use libp2p::{Multiaddr, PeerId, multiaddr::Protocol};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use tracing::{debug, warn};
+
+const PEERSTORE_FILE: &str = "peerstore.json";
+
+/// Maximum number of peers kept in the store.
+const MAX_PEERS: usize = 200;
+
+/// Maximum number of addresses kept per peer.
+const MAX_ADDRESSES_PER_PEER: usize = 8;
+
+/// Peers that have not been seen for this long are dropped from the store.
+const PEER_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Peerstore {
+    /// Good peers, most recently seen first.
+    peers: Vec<PeerRecord>,
+    /// The peers of the last successful resolution of every `dnsaddr` name, as `/p2p`
+    /// terminated addresses.
+    dnsaddr: BTreeMap<String, Vec<Multiaddr>>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PeerRecord {
+    #[serde_as(as = "DisplayFromStr")]
+    peer_id: PeerId,
+    addresses: Vec<Multiaddr>,
+    /// Unix timestamp of the last time the peer was seen, in seconds.
+    last_seen: u64,
+}
+
+impl Peerstore {
+    /// Loads the peerstore of the given directory, or returns an empty one if it doesn't exist
+    /// or cannot be decoded.
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(PEERSTORE_FILE);
+        match std::fs::read(&path) {
+            Ok(bytes) => match serde_json::from_slice(&bytes) {
+                Ok(peerstore) => {
+                    debug!("Loaded peerstore from {}", path.display());
+                    peerstore
+                }
+                Err(e) => {
+                    warn!("Ignoring invalid peerstore {}: {e}", path.display());
+                    Self::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Failed to read peerstore {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Saves the peerstore to the given directory, replacing the previous one atomically.
+    pub fn save(&self, data_dir: &Path) -> anyhow::Result<()> {
+        let mut file = tempfile::Builder::new().tempfile_in(data_dir)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.persist(data_dir.join(PEERSTORE_FILE))?;
+        Ok(())
+    }
+
+    /// Returns the stored peers and their addresses, most recently seen first.
+    pub fn peers(&self) -> impl Iterator<Item = (PeerId, &[Multiaddr])> {
+        self.peers
+            .iter()
+            .map(|record| (record.peer_id, record.addresses.as_slice()))
+    }
+
+    /// Records peers seen at the given Unix timestamp, and drops the expired ones.
+    pub fn record_peers(
+        &mut self,
+        peers: impl IntoIterator<Item = (PeerId, Vec<Multiaddr>)>,
+        now: u64,
+    ) {
+        for (peer_id, mut addresses) in peers {
+            if addresses.is_empty() {
+                continue;
+            }
+            addresses.truncate(MAX_ADDRESSES_PER_PEER);
+            match self
+                .peers
+                .iter_mut()
+                .find(|record| record.peer_id == peer_id)
+            {
+                Some(record) => {
+                    record.addresses = addresses;
+                    record.last_seen = now;
+                }
+                None => self.peers.push(PeerRecord {
+                    peer_id,
+                    addresses,
+                    last_seen: now,
+                }),
+            }
+        }
+        let expiry = now.saturating_sub(PEER_EXPIRY.as_secs());
+        self.peers.retain(|record| record.last_seen > expiry);
+        self.peers.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        self.peers.truncate(MAX_PEERS);
+    }
+
+    /// Returns the peers of the last successful resolution of a `dnsaddr` name.
+    pub fn dnsaddr_peers(&self, name: &str) -> Vec<(PeerId, Multiaddr)> {
+        self.dnsaddr
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(|addr| {
+                let mut addr = addr.clone();
+                match addr.pop() {
+                    Some(Protocol::P2p(peer_id)) => Some((peer_id, addr)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Caches the peers a `dnsaddr` name resolves to.
+    pub fn set_dnsaddr_peers(&mut self, name: &str, peers: &[(PeerId, Multiaddr)]) {
+        self.dnsaddr.insert(
+            name.to_owned(),
+            peers
+                .iter()
+                .map(|(peer_id, addr)| addr.clone().with(Protocol::P2p(*peer_id)))
+                .collect(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peerstore_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Peerstore::load(dir.path()), Peerstore::default());
+
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234".parse().unwrap();
+        let mut peerstore = Peerstore::default();
+        peerstore.record_peers([(a, vec![addr.clone()]), (b, vec![])], 100);
+        peerstore.set_dnsaddr_peers("bootstrap.example.com", &[(b, addr.clone())]);
+        peerstore.save(dir.path()).unwrap();
+
+        let loaded = Peerstore::load(dir.path());
+        assert_eq!(loaded, peerstore);
+        // Peers without addresses are not recorded.
+        assert_eq!(
+            loaded.peers().collect::<Vec<_>>(),
+            vec![(a, [addr.clone()].as_slice())]
+        );
+        assert_eq!(
+            loaded.dnsaddr_peers("bootstrap.example.com"),
+            vec![(b, addr)]
+        );
+        assert!(loaded.dnsaddr_peers("unknown.example.com").is_empty());
+
+        // An invalid peerstore is ignored.
+        std::fs::write(dir.path().join(PEERSTORE_FILE), b"not json").unwrap();
+        assert_eq!(Peerstore::load(dir.path()), Peerstore::default());
+    }
+
+    #[test]
+    fn record_peers_expiry_and_order() {
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234".parse().unwrap();
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let mut peerstore = Peerstore::default();
+        peerstore.record_peers([(a, vec![addr.clone()])], 100);
+        peerstore.record_peers([(b, vec![addr.clone()])], 200);
+        assert_eq!(
+            peerstore.peers().map(|(peer, _)| peer).collect::<Vec<_>>(),
+            vec![b, a]
+        );
+
+        // Seeing a peer again refreshes it.
+        peerstore.record_peers([(a, vec![addr.clone()])], 300);
+        assert_eq!(
+            peerstore.peers().map(|(peer, _)| peer).collect::<Vec<_>>(),
+            vec![a, b]
+        );
+
+        // `b` expires, `a` was seen recently enough.
+        peerstore.record_peers([], 200 + PEER_EXPIRY.as_secs());
+        assert_eq!(
+            peerstore.peers().map(|(peer, _)| peer).collect::<Vec<_>>(),
+            vec![a]
+        );
+    }
+}
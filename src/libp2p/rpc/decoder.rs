@@ -8,6 +8,15 @@ use futures::prelude::*;
 use pin_project_lite::pin_project;
 use tracing::warn;
 
+use crate::utils::encoding::{MAX_NESTING_DEPTH, check_cbor_limits};
+
+/// Decodes a complete `DAG_CBOR` message received from a peer, rejecting messages that exceed
+/// the CBOR limits before they are decoded.
+pub fn decode_message<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> anyhow::Result<T> {
+    check_cbor_limits(bytes, *MAX_NESTING_DEPTH)?;
+    Ok(serde_ipld_dagcbor::de::from_reader(bytes)?)
+}
+
 pin_project! {
     #[derive(Debug)]
     pub(super) struct DagCborDecodingReader<B, T> {
@@ -48,8 +57,7 @@ where
             let n = std::task::ready!(this.io.poll_read(cx, &mut buf))?;
             // Terminated
             if n == 0 {
-                let item =
-                    serde_ipld_dagcbor::de::from_reader(&self.bytes[..]).map_err(io::Error::other);
+                let item = decode_message(&self.bytes[..]).map_err(io::Error::other);
                 return Poll::Ready(item);
             }
             *this.bytes_read += n;
@@ -67,7 +75,7 @@ where
             // Assuming io will be re-used to send new messages.
             //
             // Note: `from_reader` ensures no trailing data left in `bytes`
-            if let Ok(r) = serde_ipld_dagcbor::de::from_reader(&this.bytes[..]) {
+            if let Ok(r) = decode_message(&this.bytes[..]) {
                 return Poll::Ready(Ok(r));
             }
         }
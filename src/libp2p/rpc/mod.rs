@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod decoder;
+pub use decoder::decode_message;
 use std::{io, marker::PhantomData, time::Duration};
 
 use async_trait::async_trait;
@@ -10,6 +11,20 @@ use futures::prelude::*;
 use libp2p::request_response::{self, OutboundFailure};
 use serde::{Serialize, de::DeserializeOwned};
 
+use crate::libp2p::bandwidth::{BANDWIDTH, MeteredStream};
+
+/// Protocol of a [`CborRequestResponse`].
+pub trait CborProtocol: AsRef<str> + Send + Clone {
+    /// Whether the responses are compressed with `zstd`.
+    fn zstd_responses(&self) -> bool;
+}
+
+impl CborProtocol for &'static str {
+    fn zstd_responses(&self) -> bool {
+        false
+    }
+}
+
 /// Generic `Cbor` `RequestResponse` type. This is just needed to satisfy
 /// [`request_response::Codec`] for Hello and `ChainExchange` protocols without
 /// duplication.
@@ -76,7 +91,7 @@ impl From<OutboundFailure> for RequestResponseError {
 #[async_trait]
 impl<P, RQ, RS> request_response::Codec for CborRequestResponse<P, RQ, RS>
 where
-    P: AsRef<str> + Send + Clone,
+    P: CborProtocol,
     RQ: Serialize + DeserializeOwned + Send + Sync,
     RS: Serialize + DeserializeOwned + Send + Sync,
 {
@@ -84,51 +99,72 @@ where
     type Request = RQ;
     type Response = RS;
 
-    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    async fn read_request<T>(
+        &mut self,
+        protocol: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Request>
     where
         T: AsyncRead + Unpin + Send,
     {
-        read_request_and_decode(io).await
+        let mut io = metered(protocol, io);
+        read_request_and_decode(&mut io).await
     }
 
     async fn read_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
     ) -> io::Result<Self::Response>
     where
         T: AsyncRead + Unpin + Send,
     {
         let mut bytes = vec![];
-        io.read_to_end(&mut bytes).await?;
-        serde_ipld_dagcbor::de::from_reader(bytes.as_slice()).map_err(io::Error::other)
+        metered(protocol, io).read_to_end(&mut bytes).await?;
+        if protocol.zstd_responses() {
+            bytes = zstd_decompress(&bytes)?;
+        }
+        decode_message(&bytes).map_err(io::Error::other)
     }
 
     async fn write_request<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         req: Self::Request,
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        encode_and_write(io, req).await
+        encode_and_write(&mut metered(protocol, io), req).await
     }
 
     async fn write_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         res: Self::Response,
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        encode_and_write(io, res).await
+        let mut io = metered(protocol, io);
+        if protocol.zstd_responses() {
+            let bytes = fvm_ipld_encoding::to_vec(&res).map_err(io::Error::other)?;
+            let compressed = zstd::bulk::compress(&bytes, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            io.write_all(&compressed).await?;
+            io.close().await
+        } else {
+            encode_and_write(&mut io, res).await
+        }
     }
 }
 
+/// Meters the bytes of a stream towards its protocol.
+fn metered<'a, T>(protocol: &impl AsRef<str>, io: &'a mut T) -> MeteredStream<&'a mut T> {
+    MeteredStream::new(io, BANDWIDTH.protocol_meter(protocol.as_ref()))
+}
+
 // Because of how lotus implements the protocol, it will deadlock when calling
 // `io.ReadToEnd` on requests.
 //
@@ -181,3 +217,22 @@ where
     io.close().await?;
     Ok(())
 }
+
+/// Decompresses a `zstd`-compressed response, limiting its decompressed size so that a peer
+/// cannot exhaust the memory with a small payload.
+fn zstd_decompress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    const MAX_DECOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+    use io::Read as _;
+
+    let mut decompressed = vec![];
+    zstd::Decoder::new(bytes)?
+        .take(MAX_DECOMPRESSED_BYTES + 1)
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() as u64 > MAX_DECOMPRESSED_BYTES {
+        return Err(io::Error::other(format!(
+            "Decompressed response exceeds the maximum allowed {MAX_DECOMPRESSED_BYTES}B"
+        )));
+    }
+    Ok(decompressed)
+}
@@ -2,12 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use std::{
+    path::PathBuf,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use crate::{blocks::GossipBlock, rpc::net::NetInfoResult};
-use crate::{chain::ChainStore, utils::encoding::from_slice_with_fallback};
+use crate::{
+    chain::{ChainStore, scheduler::EpochScheduler},
+    shim::clock::ChainEpoch,
+    utils::encoding::from_slice_with_fallback,
+};
 use crate::{
     libp2p_bitswap::{
         BitswapStoreRead, BitswapStoreReadWrite, request_manager::BitswapRequestManager,
@@ -23,15 +28,15 @@ use futures::{select, stream::StreamExt as _};
 use fvm_ipld_blockstore::Blockstore;
 pub use libp2p::gossipsub::{IdentTopic, Topic};
 use libp2p::{
-    PeerId, Swarm, SwarmBuilder,
+    PeerId, Swarm, SwarmBuilder, Transport as _,
     autonat::NatStatus,
     connection_limits::Exceeded,
-    core::Multiaddr,
+    core::{Multiaddr, muxing::StreamMuxerBox, upgrade},
     gossipsub, identify,
     identity::Keypair,
     metrics::{Metrics, Recorder},
     multiaddr::Protocol,
-    noise, ping, request_response,
+    noise, ping, quic, request_response,
     swarm::{DialError, SwarmEvent},
     tcp, yamux,
 };
@@ -41,8 +46,13 @@ use tracing::{debug, error, info, trace, warn};
 
 use super::{
     ForestBehaviour, ForestBehaviourEvent, Libp2pConfig,
+    bandwidth::{BANDWIDTH, BandwidthMuxer},
     chain_exchange::{ChainExchangeRequest, ChainExchangeResponse, make_chain_exchange_response},
-    discovery::{DerivedDiscoveryBehaviourEvent, PeerInfo},
+    discovery::{
+        DerivedDiscoveryBehaviourEvent, PeerInfo, dnsaddr_name, resolve_bootstrap_peers,
+        resolve_libp2p_dnsaddr,
+    },
+    peerstore::Peerstore,
 };
 use crate::libp2p::{
     PeerManager, PeerOperation,
@@ -91,6 +101,9 @@ const PUBSUB_TOPICS: [&str; 2] = [PUBSUB_BLOCK_STR, PUBSUB_MSG_STR];
 
 pub const BITSWAP_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Number of epochs between the saves of the peerstore, on top of the save on shutdown
+const PEERSTORE_SAVE_INTERVAL_EPOCHS: ChainEpoch = 10;
+
 /// Events emitted by this Service.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
@@ -169,6 +182,16 @@ pub enum NetRPCMethods {
 pub struct Libp2pService<DB> {
     swarm: Swarm<ForestBehaviour>,
     bootstrap_peers: HashMap<PeerId, Multiaddr>,
+    /// Number of connected peers below which the node bootstraps again.
+    rebootstrap_peer_threshold: u32,
+    /// Names of the `/dnsaddr` bootstrap addresses, resolved again periodically.
+    dnsaddr_names: Vec<String>,
+    peerstore: Peerstore,
+    /// Directory of the peerstore.
+    data_dir: PathBuf,
+    /// Requests to save the peerstore, sent periodically by the epoch scheduler.
+    peerstore_save_tx: Sender<()>,
+    peerstore_save_rx: flume::Receiver<()>,
     cs: Arc<ChainStore<DB>>,
     peer_manager: Arc<PeerManager>,
     network_receiver_in: flume::Receiver<NetworkMessage>,
@@ -184,24 +207,52 @@ where
     DB: Blockstore + BitswapStoreReadWrite + Sync + Send + 'static,
 {
     pub async fn new(
-        config: Libp2pConfig,
+        mut config: Libp2pConfig,
         cs: Arc<ChainStore<DB>>,
         peer_manager: Arc<PeerManager>,
         net_keypair: Keypair,
         network_name: GenesisNetworkName,
         genesis_cid: Cid,
+        data_dir: PathBuf,
     ) -> anyhow::Result<Self> {
+        let mut peerstore = Peerstore::load(&data_dir);
+        let dnsaddr_names = config
+            .bootstrap_peers
+            .iter()
+            .filter_map(dnsaddr_name)
+            .collect();
+        config.bootstrap_peers =
+            resolve_bootstrap_peers(&config.bootstrap_peers, &mut peerstore).await;
+
         let behaviour =
             ForestBehaviour::new(&net_keypair, &config, &network_name, peer_manager.clone())
                 .await?;
         let mut swarm = SwarmBuilder::with_existing_identity(net_keypair)
             .with_tokio()
-            .with_tcp(
-                tcp::Config::default().nodelay(true),
-                noise::Config::new,
-                yamux::Config::default,
+            .with_other_transport(
+                |key| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                    // Meter the connections of both transports per peer.
+                    let tcp = tcp::tokio::Transport::new(tcp::Config::default().nodelay(true))
+                        .upgrade(upgrade::Version::V1Lazy)
+                        .authenticate(noise::Config::new(key)?)
+                        .multiplex(yamux::Config::default())
+                        .map(|(peer_id, muxer), _| {
+                            (
+                                peer_id,
+                                StreamMuxerBox::new(BandwidthMuxer::new(muxer, peer_id)),
+                            )
+                        });
+                    let quic = quic::tokio::Transport::new(quic::Config::new(key)).map(
+                        |(peer_id, muxer), _| {
+                            (
+                                peer_id,
+                                StreamMuxerBox::new(BandwidthMuxer::new(muxer, peer_id)),
+                            )
+                        },
+                    );
+                    Ok(tcp.or_transport(quic).map(|either, _| either.into_inner()))
+                },
             )?
-            .with_quic()
             .with_dns()?
             .with_bandwidth_metrics(&mut crate::metrics::collector_registry())
             .with_behaviour(|_| behaviour)?
@@ -224,6 +275,7 @@ where
 
         let (network_sender_in, network_receiver_in) = flume::unbounded();
         let (network_sender_out, network_receiver_out) = flume::unbounded();
+        let (peerstore_save_tx, peerstore_save_rx) = flume::bounded(1);
 
         // Hint at the multihash which has to go in the `/p2p/<multihash>` part of the
         // peer's multiaddress. Useful if others want to use this node to bootstrap
@@ -252,6 +304,16 @@ where
             anyhow::bail!("p2p peer failed to listen on any network endpoints");
         }
 
+        // Reconnect to the peers of the previous run, in case the bootstrap peers are unreachable.
+        if !config.override_bootstrap_peers {
+            for (peer_id, addresses) in peerstore.peers().take(config.target_peer_count as usize) {
+                swarm
+                    .behaviour_mut()
+                    .discovery
+                    .add_known_peer(peer_id, addresses.to_vec());
+            }
+        }
+
         let bootstrap_peers = config
             .bootstrap_peers
             .iter()
@@ -264,6 +326,12 @@ where
         Ok(Libp2pService {
             swarm,
             bootstrap_peers,
+            rebootstrap_peer_threshold: config.rebootstrap_peer_threshold,
+            dnsaddr_names,
+            peerstore,
+            data_dir,
+            peerstore_save_tx,
+            peerstore_save_rx,
             cs,
             peer_manager,
             network_receiver_in,
@@ -305,14 +373,23 @@ where
             None
         };
 
-        const BOOTSTRAP_PEER_DIALER_INTERVAL: tokio::time::Duration =
-            tokio::time::Duration::from_secs(60);
-        let mut bootstrap_peer_dialer_interval_stream =
-            IntervalStream::new(tokio::time::interval_at(
-                tokio::time::Instant::now() + BOOTSTRAP_PEER_DIALER_INTERVAL,
-                BOOTSTRAP_PEER_DIALER_INTERVAL,
-            ))
-            .fuse();
+        const REBOOTSTRAP_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(60);
+        let mut rebootstrap_interval_stream = IntervalStream::new(tokio::time::interval_at(
+            tokio::time::Instant::now() + REBOOTSTRAP_INTERVAL,
+            REBOOTSTRAP_INTERVAL,
+        ))
+        .fuse();
+
+        let mut peerstore_save_stream = self.peerstore_save_rx.stream().fuse();
+
+        const DNSADDR_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+        let mut dnsaddr_refresh_interval_stream = IntervalStream::new(tokio::time::interval_at(
+            tokio::time::Instant::now() + DNSADDR_REFRESH_INTERVAL,
+            DNSADDR_REFRESH_INTERVAL,
+        ))
+        .fuse();
+        let (dnsaddr_tx, dnsaddr_rx) = flume::unbounded();
+        let mut dnsaddr_rx_stream = dnsaddr_rx.stream().fuse();
         loop {
             select! {
                 swarm_event = swarm_stream.next() => match swarm_event {
@@ -352,6 +429,7 @@ where
                 interval_event = interval.next() => if interval_event.is_some() {
                     // Print peer count on an interval.
                     trace!("Peers connected: {}", swarm_stream.get_mut().behaviour_mut().peers().len());
+                    BANDWIDTH.update_rates();
                 },
                 cs_pair_opt = cx_response_rx_stream.next() => {
                     if let Some((_request_id, channel, cx_response)) = cs_pair_opt {
@@ -372,11 +450,39 @@ where
                         handle_peer_ops(swarm_stream.get_mut(), peer_ops, &self.bootstrap_peers);
                     }
                 },
-                _ = bootstrap_peer_dialer_interval_stream.next() => {
-                    dial_to_bootstrap_peers_if_needed(swarm_stream.get_mut(), &self.bootstrap_peers);
+                _ = rebootstrap_interval_stream.next() => {
+                    rebootstrap_if_needed(swarm_stream.get_mut(), &self.bootstrap_peers, self.rebootstrap_peer_threshold);
+                }
+                _ = peerstore_save_stream.next() => {
+                    save_peerstore(swarm_stream.get_ref(), &self.peer_manager, &mut self.peerstore, &self.data_dir);
+                }
+                _ = dnsaddr_refresh_interval_stream.next() => {
+                    // Resolve in the background, not to block the event loop on DNS.
+                    for name in &self.dnsaddr_names {
+                        let name = name.clone();
+                        let dnsaddr_tx = dnsaddr_tx.clone();
+                        tokio::spawn(async move {
+                            match resolve_libp2p_dnsaddr(&name).await {
+                                Ok(pairs) if !pairs.is_empty() => dnsaddr_tx.send_or_warn((name, pairs)),
+                                Ok(_) => warn!("No peers found at /dnsaddr/{name}"),
+                                Err(e) => warn!("Failed to refresh /dnsaddr/{name}: {e}"),
+                            }
+                        });
+                    }
+                }
+                dnsaddr_opt = dnsaddr_rx_stream.next() => {
+                    if let Some((name, pairs)) = dnsaddr_opt {
+                        update_dnsaddr_bootstrap_peers(swarm_stream.get_mut(), &mut self.bootstrap_peers, &mut self.peerstore, &name, pairs);
+                    }
                 }
             };
         }
+        save_peerstore(
+            swarm_stream.get_ref(),
+            &self.peer_manager,
+            &mut self.peerstore,
+            &self.data_dir,
+        );
         Ok(())
     }
 
@@ -393,12 +499,36 @@ where
     pub fn peer_manager(&self) -> &Arc<PeerManager> {
         &self.peer_manager
     }
+
+    /// Registers the periodic saving of the peerstore on the epoch scheduler.
+    pub fn schedule_peerstore_save(&self, scheduler: &mut EpochScheduler) {
+        let peerstore_save_tx = self.peerstore_save_tx.clone();
+        scheduler.every(
+            "peerstore save",
+            PEERSTORE_SAVE_INTERVAL_EPOCHS,
+            move |_| {
+                // A save already pending covers this one.
+                let _ = peerstore_save_tx.try_send(());
+            },
+        );
+    }
 }
 
-fn dial_to_bootstrap_peers_if_needed(
+/// Bootstraps again when the node is short of peers, e.g. after a network outage, dialing the
+/// bootstrap peers it is not connected to and running a Kademlia bootstrap.
+fn rebootstrap_if_needed(
     swarm: &mut Swarm<ForestBehaviour>,
     bootstrap_peers: &HashMap<PeerId, Multiaddr>,
+    threshold: u32,
 ) {
+    let peer_count = swarm.behaviour().peers().len();
+    if peer_count >= threshold as usize {
+        return;
+    }
+    info!("Connected to {peer_count} peers, below {threshold}, bootstrapping again");
+    if let Err(e) = swarm.behaviour_mut().bootstrap() {
+        debug!("Failed to bootstrap with Kademlia: {e}");
+    }
     for (peer, ma) in bootstrap_peers {
         if !swarm.behaviour().peers().contains(peer) {
             info!("Re-dialing to bootstrap peer at {ma}");
@@ -409,6 +539,62 @@ fn dial_to_bootstrap_peers_if_needed(
     }
 }
 
+/// Records the connected peers that passed the hello handshake, best first, and saves the
+/// peerstore.
+fn save_peerstore(
+    swarm: &Swarm<ForestBehaviour>,
+    peer_manager: &PeerManager,
+    peerstore: &mut Peerstore,
+    data_dir: &std::path::Path,
+) {
+    let behaviour = swarm.behaviour();
+    let peers = peer_manager
+        .sorted_peers()
+        .into_iter()
+        .filter_map(|peer_id| {
+            let info = behaviour.peer_info(&peer_id)?;
+            // Prefer the listen addresses, the address of an inbound connection is not dialable.
+            let addresses = match &info.identify_info {
+                Some(identify_info) => identify_info.listen_addrs.clone(),
+                None => info.addresses.iter().cloned().collect(),
+            };
+            Some((peer_id, addresses))
+        })
+        .collect::<Vec<_>>();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time before unix epoch")
+        .as_secs();
+    peerstore.record_peers(peers, now);
+    match peerstore.save(data_dir) {
+        Ok(()) => debug!("Saved {} peers to the peerstore", peerstore.peers().count()),
+        Err(e) => warn!("Failed to save the peerstore: {e}"),
+    }
+}
+
+/// Replaces the bootstrap peers of a `/dnsaddr` name with the peers it now resolves to, and
+/// connects to the new ones.
+fn update_dnsaddr_bootstrap_peers(
+    swarm: &mut Swarm<ForestBehaviour>,
+    bootstrap_peers: &mut HashMap<PeerId, Multiaddr>,
+    peerstore: &mut Peerstore,
+    name: &str,
+    pairs: Vec<(PeerId, Multiaddr)>,
+) {
+    for (peer_id, _) in peerstore.dnsaddr_peers(name) {
+        bootstrap_peers.remove(&peer_id);
+    }
+    peerstore.set_dnsaddr_peers(name, &pairs);
+    debug!("Refreshed /dnsaddr/{name}: {} peers", pairs.len());
+    for (peer_id, addr) in pairs {
+        bootstrap_peers.insert(peer_id, addr.clone().with(Protocol::P2p(peer_id)));
+        swarm
+            .behaviour_mut()
+            .discovery
+            .add_known_peer(peer_id, vec![addr]);
+    }
+}
+
 fn handle_peer_ops(
     swarm: &mut Swarm<ForestBehaviour>,
     peer_ops: PeerOperation,
@@ -445,8 +631,12 @@ async fn handle_network_message(
 ) {
     match message {
         NetworkMessage::PubsubMessage { topic, message } => {
-            if let Err(e) = swarm.behaviour_mut().publish(topic, message) {
-                warn!("Failed to send gossipsub message: {:?}", e);
+            let (topic_hash, len) = (topic.hash(), message.len());
+            match swarm.behaviour_mut().publish(topic, message) {
+                Ok(_) => BANDWIDTH
+                    .protocol_meter(topic_hash.as_str())
+                    .record_out(len),
+                Err(e) => warn!("Failed to send gossipsub message: {:?}", e),
             }
         }
         NetworkMessage::HelloRequest {
@@ -636,6 +826,7 @@ async fn handle_gossip_event(
     {
         let topic = message.topic.as_str();
         let message = message.data;
+        BANDWIDTH.protocol_meter(topic).record_in(message.len());
         trace!("Got a Gossip Message from {:?}", source);
         if topic == pubsub_block_str {
             match from_slice_with_fallback::<GossipBlock>(&message) {
@@ -11,6 +11,7 @@ use futures::{
 };
 use libp2p::request_response;
 
+use crate::libp2p::bandwidth::{BANDWIDTH, MeteredStream};
 use crate::libp2p_bitswap::{bitswap_pb::mod_Message::BlockPresenceType, prefix::Prefix, *};
 
 // 2MB Block Size according to the specs at https://github.com/ipfs/specs/blob/main/BITSWAP.md
@@ -29,10 +30,15 @@ impl request_response::Codec for BitswapRequestResponseCodec {
     type Request = Vec<BitswapMessage>;
     type Response = ();
 
-    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> IOResult<Self::Request>
+    async fn read_request<T>(
+        &mut self,
+        protocol: &Self::Protocol,
+        io: &mut T,
+    ) -> IOResult<Self::Request>
     where
         T: AsyncRead + Send + Unpin,
     {
+        let io = MeteredStream::new(io, BANDWIDTH.protocol_meter(protocol));
         let pb_msg: bitswap_pb::Message = FramedRead::new(io, codec())
             .next()
             .await
@@ -82,7 +88,7 @@ impl request_response::Codec for BitswapRequestResponseCodec {
     /// Sending both `bitswap` requests and responses
     async fn write_request<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         mut messages: Self::Request,
     ) -> IOResult<()>
@@ -96,6 +102,7 @@ impl request_response::Codec for BitswapRequestResponseCodec {
         );
 
         let data = messages.swap_remove(0).into_proto()?;
+        let io = MeteredStream::new(io, BANDWIDTH.protocol_meter(protocol));
         let mut framed = FramedWrite::new(io, codec());
         framed.send(data).await?;
         framed.close().await?;
@@ -1,7 +1,7 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use libp2p::{PeerId, request_response};
 
@@ -33,7 +33,12 @@ pub fn handle_event_impl<S: BitswapStoreRead>(
                 for message in request {
                     match message {
                         BitswapMessage::Request(request) => {
-                            if let Some(response) = handle_inbound_request(store, &request) {
+                            if let Some(response) = bitswap.server.handle_inbound_request(
+                                &peer,
+                                store,
+                                &request,
+                                Instant::now(),
+                            ) {
                                 bitswap.send_response(&peer, (request.cid, response));
                             }
                         }
@@ -67,35 +72,3 @@ pub fn handle_event_impl<S: BitswapStoreRead>(
 
     Ok(())
 }
-
-fn handle_inbound_request<S: BitswapStoreRead>(
-    store: &S,
-    request: &BitswapRequest,
-) -> Option<BitswapResponse> {
-    if request.cancel {
-        return None;
-    }
-
-    match request.ty {
-        RequestType::Have => {
-            metrics::message_counter_inbound_request_have().inc();
-            let have = store.contains(&request.cid).ok().unwrap_or_default();
-            if have || request.send_dont_have {
-                Some(BitswapResponse::Have(have))
-            } else {
-                None
-            }
-        }
-        RequestType::Block => {
-            metrics::message_counter_inbound_request_block().inc();
-            let block = store.get(&request.cid).ok().unwrap_or_default();
-            if let Some(data) = block {
-                Some(BitswapResponse::Block(data))
-            } else if request.send_dont_have {
-                Some(BitswapResponse::Have(false))
-            } else {
-                None
-            }
-        }
-    }
-}
@@ -22,6 +22,8 @@ impl TypeLabel {
 
 static MESSAGE_COUNTER: LazyLock<Family<TypeLabel, Counter>> = LazyLock::new(Default::default);
 static CONTAINER_CAPACITIES: LazyLock<Family<TypeLabel, Gauge>> = LazyLock::new(Default::default);
+pub(in crate::libp2p_bitswap) static SERVED_BYTES: LazyLock<Counter> =
+    LazyLock::new(Default::default);
 pub(in crate::libp2p_bitswap) static GET_BLOCK_TIME: LazyLock<Histogram> = LazyLock::new(|| {
     Histogram::new([
         0.1, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0,
@@ -45,6 +47,11 @@ pub fn register_metrics(registry: &mut Registry) {
         "Duration of get_block",
         GET_BLOCK_TIME.clone(),
     );
+    registry.register(
+        "bitswap_served_bytes",
+        "Number of bytes of the blocks served to peers",
+        SERVED_BYTES.clone(),
+    );
 }
 
 pub(in crate::libp2p_bitswap) fn inbound_stream_count<'a>() -> MappedRwLockReadGuard<'a, Counter> {
@@ -75,6 +82,16 @@ pub(in crate::libp2p_bitswap) fn message_counter_inbound_request_block<'a>()
     MESSAGE_COUNTER.get_or_create(&TypeLabel::new("inbound_request_block"))
 }
 
+pub(in crate::libp2p_bitswap) fn message_counter_inbound_request_block_rate_limited<'a>()
+-> MappedRwLockReadGuard<'a, Counter> {
+    MESSAGE_COUNTER.get_or_create(&TypeLabel::new("inbound_request_block_rate_limited"))
+}
+
+pub(in crate::libp2p_bitswap) fn message_counter_inbound_request_block_not_chain_data<'a>()
+-> MappedRwLockReadGuard<'a, Counter> {
+    MESSAGE_COUNTER.get_or_create(&TypeLabel::new("inbound_request_block_not_chain_data"))
+}
+
 pub(in crate::libp2p_bitswap) fn message_counter_outbound_request_cancel<'a>()
 -> MappedRwLockReadGuard<'a, Counter> {
     MESSAGE_COUNTER.get_or_create(&TypeLabel::new("outbound_request_cancel"))
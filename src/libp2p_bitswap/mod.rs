@@ -8,6 +8,7 @@
 //!
 //! - Compatible with [`go-bitswap`](https://github.com/ipfs/go-bitswap)
 //! - Optional request manager
+//! - Per-peer rate limits on the served blocks
 //! - Prometheus metrics
 //!
 //! ## Usage
@@ -42,6 +43,9 @@ pub use metrics::register_metrics;
 
 pub mod request_manager;
 
+mod server;
+pub use server::BitswapServerConfig;
+
 mod store;
 pub use store::*;
 
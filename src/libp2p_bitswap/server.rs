@@ -0,0 +1,211 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Serving side of `bitswap`, which answers the requests of the peers from the
+//! local block store. Peers are rate-limited on the bytes of the blocks they
+//! are sent, and serving can be restricted to chain data, so that a hostile
+//! peer cannot use the node as a free data egress node.
+
+use std::time::Instant;
+
+use ahash::HashMap;
+use fvm_ipld_encoding::DAG_CBOR;
+use libp2p::PeerId;
+
+use crate::libp2p_bitswap::*;
+use crate::utils::multihash::MultihashCode;
+
+/// Configuration of the serving side of `bitswap`
+#[derive(Debug, Clone, Default)]
+pub struct BitswapServerConfig {
+    /// Maximum number of bytes of blocks served to each peer per second, with
+    /// bursts of up to one second of traffic. `0` disables the limit.
+    pub max_bytes_per_peer_per_second: u64,
+    /// Serves only chain data, i.e. the `DAG_CBOR` blocks hashed with
+    /// `Blake2b256`, which are what the Filecoin peers request.
+    pub chain_data_only: bool,
+}
+
+#[derive(Debug, Default)]
+pub(in crate::libp2p_bitswap) struct BitswapServer {
+    config: BitswapServerConfig,
+    budgets: HashMap<PeerId, ByteBudget>,
+}
+
+impl BitswapServer {
+    pub fn new(config: BitswapServerConfig) -> Self {
+        Self {
+            config,
+            budgets: Default::default(),
+        }
+    }
+
+    pub fn on_peer_disconnected(&mut self, peer: &PeerId) {
+        self.budgets.remove(peer);
+    }
+
+    pub fn handle_inbound_request<S: BitswapStoreRead>(
+        &mut self,
+        peer: &PeerId,
+        store: &S,
+        request: &BitswapRequest,
+        now: Instant,
+    ) -> Option<BitswapResponse> {
+        if request.cancel {
+            return None;
+        }
+
+        let dont_have = || {
+            request
+                .send_dont_have
+                .then_some(BitswapResponse::Have(false))
+        };
+        match request.ty {
+            RequestType::Have => {
+                metrics::message_counter_inbound_request_have().inc();
+                if !self.serves(&request.cid) {
+                    return dont_have();
+                }
+                let have = store.contains(&request.cid).ok().unwrap_or_default();
+                if have {
+                    Some(BitswapResponse::Have(true))
+                } else {
+                    dont_have()
+                }
+            }
+            RequestType::Block => {
+                metrics::message_counter_inbound_request_block().inc();
+                if !self.serves(&request.cid) {
+                    metrics::message_counter_inbound_request_block_not_chain_data().inc();
+                    return dont_have();
+                }
+                let rate = self.config.max_bytes_per_peer_per_second;
+                if rate > 0 {
+                    let budget = self
+                        .budgets
+                        .entry(*peer)
+                        .or_insert_with(|| ByteBudget::new(rate, now));
+                    if !budget.refill(rate, now) {
+                        metrics::message_counter_inbound_request_block_rate_limited().inc();
+                        // The block may be there, the peer is left to request it again.
+                        return None;
+                    }
+                }
+                match store.get(&request.cid).ok().flatten() {
+                    Some(data) => {
+                        if let Some(budget) = self.budgets.get_mut(peer) {
+                            budget.spend(data.len() as u64);
+                        }
+                        metrics::SERVED_BYTES.inc_by(data.len() as u64);
+                        Some(BitswapResponse::Block(data))
+                    }
+                    None => dont_have(),
+                }
+            }
+        }
+    }
+
+    fn serves(&self, cid: &Cid) -> bool {
+        !self.config.chain_data_only
+            || (cid.codec() == DAG_CBOR
+                && cid.hash().code() == u64::from(MultihashCode::Blake2b256))
+    }
+}
+
+/// Token bucket of the bytes a peer may be sent. The size of a block is only
+/// known once it is read, so a block is served as long as the budget is
+/// positive, and the budget may go into debt, which is paid off before the
+/// next block is served.
+#[derive(Debug)]
+struct ByteBudget {
+    available: i128,
+    refilled_at: Instant,
+}
+
+impl ByteBudget {
+    fn new(rate: u64, now: Instant) -> Self {
+        Self {
+            available: rate.into(),
+            refilled_at: now,
+        }
+    }
+
+    /// Refills the budget for the time elapsed since the last refill, and
+    /// returns whether a block may be served.
+    fn refill(&mut self, rate: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.refilled_at = now;
+        let refill = elapsed.as_nanos() * u128::from(rate) / 1_000_000_000;
+        self.available = self
+            .available
+            .saturating_add(i128::try_from(refill).unwrap_or(i128::MAX))
+            .min(rate.into());
+        self.available > 0
+    }
+
+    fn spend(&mut self, bytes: u64) {
+        self.available -= i128::from(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::utils::db::CborStoreExt as _;
+    use fvm_ipld_blockstore::Blockstore as _;
+    use multihash_derive::MultihashDigest as _;
+    use std::time::Duration;
+
+    #[test]
+    fn rate_limit_per_peer() {
+        let store = MemoryDB::default();
+        let cid = store.put_cbor_default(&vec![0u8; 100]).unwrap();
+        let mut server = BitswapServer::new(BitswapServerConfig {
+            max_bytes_per_peer_per_second: 150,
+            chain_data_only: false,
+        });
+        let request = BitswapRequest::new_block(cid).send_dont_have(true);
+        let (peer, other) = (PeerId::random(), PeerId::random());
+        let now = Instant::now();
+        let mut serve = |peer, now| server.handle_inbound_request(&peer, &store, &request, now);
+        let served =
+            |response: Option<BitswapResponse>| matches!(response, Some(BitswapResponse::Block(_)));
+        // The budget of a second goes into debt on the second block
+        assert!(served(serve(peer, now)));
+        assert!(served(serve(peer, now)));
+        // A rate-limited request is not answered with a `DONT_HAVE`
+        assert!(serve(peer, now).is_none());
+        // Other peers have their own budget
+        assert!(served(serve(other, now)));
+        // The debt is paid off over time
+        assert!(serve(peer, now + Duration::from_millis(300)).is_none());
+        assert!(served(serve(peer, now + Duration::from_millis(800))));
+    }
+
+    #[test]
+    fn chain_data_only() {
+        let store = MemoryDB::default();
+        let chain_cid = store.put_cbor_default(&42u64).unwrap();
+        let raw_cid = Cid::new_v1(
+            fvm_ipld_encoding::IPLD_RAW,
+            MultihashCode::Blake2b256.digest(b"raw"),
+        );
+        store.put_keyed(&raw_cid, b"raw").unwrap();
+        let mut server = BitswapServer::new(BitswapServerConfig {
+            max_bytes_per_peer_per_second: 0,
+            chain_data_only: true,
+        });
+        let peer = PeerId::random();
+        let mut serve = |cid| {
+            server.handle_inbound_request(
+                &peer,
+                &store,
+                &BitswapRequest::new_block(cid),
+                Instant::now(),
+            )
+        };
+        assert!(matches!(serve(chain_cid), Some(BitswapResponse::Block(_))));
+        assert!(serve(raw_cid).is_none());
+    }
+}
@@ -10,67 +10,68 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
-#[serde(rename_all = "PascalCase")]
-pub struct EVMConstructorParamsLotusJson {
-    pub creator: [u8; 20],
-    #[schemars(with = "LotusJson<RawBytes>")]
-    #[serde(with = "crate::lotus_json")]
-    pub initcode: RawBytes,
+#[derive(HasLotusJson)]
+#[lotus_json(remote = "fil_actor_evm_state::v10::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v11::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v12::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v13::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v14::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v15::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v16::ConstructorParams")]
+#[lotus_json(remote = "fil_actor_evm_state::v17::ConstructorParams")]
+#[lotus_json(snapshot(
+    json = r#"{
+        "Creator": [0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],
+        "Initcode": "ESIzRFU="
+    }"#,
+    value = r#"Self {
+        creator: eth_address::from_lotus_json([0; 20]),
+        initcode: RawBytes::new(hex::decode("1122334455").unwrap()),
+    }"#
+))]
+struct EVMConstructorParams {
+    #[lotus_json(with = "eth_address")]
+    creator: [u8; 20],
+    initcode: RawBytes,
 }
 
-macro_rules! impl_evm_constructor_params {
-    ($($version:literal),+) => {
-        $(
-            paste! {
-                mod [<impl_evm_constructor_params_ $version>] {
-                    use super::*;
-                    type T = fil_actor_evm_state::[<v $version>]::ConstructorParams;
-                    #[test]
-                    fn snapshots() {
-                        crate::lotus_json::assert_all_snapshots::<T>();
-                    }
-
-                    impl HasLotusJson for T {
-                        type LotusJson = EVMConstructorParamsLotusJson;
+/// Converts the Ethereum addresses of the EVM actor versions to and from their bytes.
+mod eth_address {
+    use pastey::paste;
 
-                        #[cfg(test)]
-                        fn snapshots() -> Vec<(serde_json::Value, Self)> {
-                            vec![
-                                (
-                                    json!({
-                                            "Creator": [0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],
-                                            "Initcode": "ESIzRFU="
-                                        }),
-                                    Self {
-                                        creator: fil_actor_evm_state::evm_shared::[<v $version>]::address::EthAddress([0; 20]),
-                                        initcode: RawBytes::new(hex::decode("1122334455").unwrap()),
-                                    },
-                                ),
-                            ]
-                        }
+    pub trait EthAddress {
+        fn from_bytes(bytes: [u8; 20]) -> Self;
+        fn into_bytes(self) -> [u8; 20];
+    }
 
-                        fn into_lotus_json(self) -> Self::LotusJson {
-                            EVMConstructorParamsLotusJson {
-                                creator: self.creator.0,
-                                initcode: self.initcode,
-                            }
+    macro_rules! impl_eth_address {
+        ($($version:literal),+) => {
+            $(
+                paste! {
+                    impl EthAddress for fil_actor_evm_state::evm_shared::[<v $version>]::address::EthAddress {
+                        fn from_bytes(bytes: [u8; 20]) -> Self {
+                            Self(bytes)
                         }
 
-                        fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
-                            Self {
-                                creator: fil_actor_evm_state::evm_shared::[<v $version>]::address::EthAddress(lotus_json.creator),
-                                initcode: lotus_json.initcode,
-                            }
+                        fn into_bytes(self) -> [u8; 20] {
+                            self.0
                         }
                     }
                 }
-            }
-        )+
-    };
-}
+            )+
+        };
+    }
+
+    impl_eth_address!(10, 11, 12, 13, 14, 15, 16, 17);
 
-impl_evm_constructor_params!(10, 11, 12, 13, 14, 15, 16, 17);
+    pub fn into_lotus_json(address: impl EthAddress) -> [u8; 20] {
+        address.into_bytes()
+    }
+
+    pub fn from_lotus_json<T: EthAddress>(bytes: [u8; 20]) -> T {
+        T::from_bytes(bytes)
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "PascalCase")]
@@ -62,4 +62,3 @@ impl HasLotusJson for MinerPower {
         }
     }
 }
-crate::test_snapshots!(MinerPower);
@@ -96,6 +96,9 @@
 //!   If you do this, you MUST manually add snapshot and `quickcheck` tests.
 //!
 //! ### Compound structs
+//! Prefer `#[derive(HasLotusJson)]`, which generates the lotus JSON struct, the conversions and
+//! the snapshot test, see [`beneficiary_term`]. Types of other crates are supported with
+//! `#[lotus_json(remote = "...")]`. Otherwise:
 //! - Each field of a struct should be wrapped with [`LotusJson`].
 //! - Implementations of [`HasLotusJson::into_lotus_json`] and [`HasLotusJson::from_lotus_json`]
 //!   should use [`Into`] and [`LotusJson::into_inner`] calls
@@ -119,10 +122,10 @@
 //!
 //! # Future work
 //! - use [`proptest`](https://docs.rs/proptest/) to test the parser pipeline
-//! - use a derive macro for simple compound structs
 
 use crate::shim::actors::miner::DeadlineInfo;
 use derive_more::From;
+pub use forest_derive::HasLotusJson;
 use fvm_shared4::piece::PaddedPieceSize;
 use schemars::{JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned};
@@ -201,7 +204,9 @@ decl_and_test!(
     gossip_block for crate::blocks::GossipBlock,
     key_info for crate::key_management::KeyInfo,
     message for crate::shim::message::Message,
+    miner_power for crate::shim::actors::miner::MinerPower,
     po_st_proof for crate::shim::sector::PoStProof,
+    power_claim for crate::shim::actors::power::Claim,
     registered_po_st_proof for crate::shim::sector::RegisteredPoStProof,
     registered_seal_proof for crate::shim::sector::RegisteredSealProof,
     sector_info for crate::shim::sector::SectorInfo,
@@ -229,12 +234,10 @@ mod filter_estimate;
 mod hash_map;
 mod ipld; // NaN != NaN
 mod miner_info; // fil_actor_miner_state::v12::MinerInfo: !quickcheck::Arbitrary
-mod miner_power; // actors::miner::MinerInfo: !quickcheck::Arbitrary
 mod nonempty; // can't make snapshots of generic type
 mod opt; // can't make snapshots of generic type
 mod padded_piece_size;
 mod pending_beneficiary_change; // fil_actor_miner_state::v12::PendingBeneficiaryChange: !quickcheck::Arbitrary
-mod power_claim; // actors::power::Claim: !quickcheck::Arbitrary
 mod raw_bytes; // fvm_ipld_encoding::RawBytes: !quickcheck::Arbitrary
 mod receipt; // shim type roundtrip is wrong - see module
 mod token_state;
@@ -658,9 +661,95 @@ mod fixme {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shim::econ::TokenAmount;
+    use ::cid::Cid;
     use ipld_core::serde::SerdeError;
     use serde::de::{IntoDeserializer, value::StringDeserializer};
 
+    /// A type of another crate, whose lotus JSON is derived with `#[lotus_json(remote = "...")]`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DeriveTest {
+        amount: TokenAmount,
+        bytes: Vec<u8>,
+        code: Cid,
+        id: u64,
+        tip: Option<TokenAmount>,
+    }
+
+    mod hex_id {
+        pub fn into_lotus_json(id: u64) -> String {
+            format!("{id:#x}")
+        }
+
+        pub fn from_lotus_json(id: String) -> u64 {
+            u64::from_str_radix(id.trim_start_matches("0x"), 16).unwrap()
+        }
+    }
+
+    #[derive(HasLotusJson)]
+    #[lotus_json(remote = "DeriveTest", rename_all = "camelCase")]
+    #[lotus_json(snapshot(
+        json = r#"{
+            "amount": "1",
+            "Bytes": "AQI=",
+            "code": { "/": "baeaaaaa" },
+            "id": "0x2a",
+            "tip": "2"
+        }"#,
+        value = r#"DeriveTest {
+            amount: TokenAmount::from_atto(1),
+            bytes: vec![1, 2],
+            code: Cid::default(),
+            id: 42,
+            tip: Some(TokenAmount::from_atto(2)),
+        }"#
+    ))]
+    // The unset optional field is omitted
+    #[lotus_json(snapshot(
+        json = r#"{ "amount": "0", "Bytes": "", "code": { "/": "baeaaaaa" }, "id": "0x0" }"#,
+        value = r#"DeriveTest {
+            amount: TokenAmount::default(),
+            bytes: vec![],
+            code: Cid::default(),
+            id: 0,
+            tip: None,
+        }"#
+    ))]
+    struct DeriveTestMirror {
+        amount: TokenAmount,
+        #[lotus_json(rename = "Bytes", base64)]
+        bytes: Vec<u8>,
+        #[lotus_json(cid)]
+        code: Cid,
+        #[lotus_json(with = "hex_id")]
+        id: String,
+        #[lotus_json(optional)]
+        tip: Option<TokenAmount>,
+    }
+
+    #[test]
+    fn derive_lotus_json_round_trip() {
+        let value = DeriveTest {
+            amount: TokenAmount::from_atto(3),
+            bytes: vec![4, 5, 6],
+            code: Cid::default(),
+            id: 7,
+            tip: None,
+        };
+        let lotus_json = value.clone().into_lotus_json();
+        assert_eq!(lotus_json.id, "0x7");
+        assert_eq!(DeriveTest::from_lotus_json(lotus_json), value);
+        // The optional field may be missing from the JSON
+        assert!(
+            serde_json::from_str::<DeriveTestMirrorLotusJson>(
+                r#"{ "amount": "0", "Bytes": "", "code": { "/": "baeaaaaa" }, "id": "0x0" }"#
+            )
+            .unwrap()
+            .tip
+            .is_none()
+        );
+    }
+
     #[derive(Debug, Deserialize, Serialize, PartialEq)]
     struct HexifyVecBytesTest {
         #[serde(with = "hexify_vec_bytes")]
@@ -49,8 +49,3 @@ impl HasLotusJson for Claim {
         }
     }
 }
-
-#[test]
-fn snapshots() {
-    assert_all_snapshots::<Claim>();
-}
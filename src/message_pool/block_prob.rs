@@ -1,12 +1,80 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+//! Model of the number of blocks per epoch. The winners of an epoch follow a Poisson distribution,
+//! which gives the probability of a block being included in a tipset given the quality of its
+//! ticket, used to weigh the message chains in the selection, and the expected wins of a miner
+//! given its share of the network power.
+
 use std::f64::consts::E;
 
+use serde::{Deserialize, Serialize};
 use statrs::function::gamma::ln_gamma;
 
 const MAX_BLOCKS: usize = 15;
-const MU: f64 = 5.0;
+const EXPECTED_WINNERS_PER_EPOCH: u32 = 5;
+
+/// Parameters of the block probability model.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct BlockProbabilityConfig {
+    /// Expected number of winning blocks per epoch, the mean of the Poisson distribution of the
+    /// winners
+    pub expected_winners_per_epoch: u32,
+}
+
+impl Default for BlockProbabilityConfig {
+    fn default() -> Self {
+        Self {
+            expected_winners_per_epoch: EXPECTED_WINNERS_PER_EPOCH,
+        }
+    }
+}
+
+impl BlockProbabilityConfig {
+    fn mu(&self) -> f64 {
+        self.expected_winners_per_epoch.max(1) as f64
+    }
+
+    /// Probabilities of a block being included in the tipset, for each place of its ticket among
+    /// the tickets of the winners, up to [`MAX_BLOCKS`].
+    pub fn block_probabilities(&self, tq: f64) -> Vec<f64> {
+        let no_winners = no_winners_prob_assuming_more_than_one(self.mu());
+        let p = 1.0 - tq;
+        (0..MAX_BLOCKS)
+            .map(|place| {
+                no_winners
+                    .iter()
+                    .enumerate()
+                    .map(|(other_winner, p_case)| {
+                        p_case * bino_pdf(place as f64, other_winner as f64, p)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Expected number of blocks won per epoch by a miner with the given share of the network
+    /// power.
+    pub fn expected_wins_per_epoch(&self, power_share: f64) -> f64 {
+        self.mu() * power_share.clamp(0.0, 1.0)
+    }
+
+    /// Probabilities of a miner with the given share of the network power winning 0, 1, ... blocks
+    /// in an epoch, up to [`MAX_BLOCKS`].
+    pub fn win_count_probabilities(&self, power_share: f64) -> Vec<f64> {
+        let lambda = self.expected_wins_per_epoch(power_share);
+        if lambda == 0.0 {
+            return (0..MAX_BLOCKS)
+                .map(|i| if i == 0 { 1.0 } else { 0.0 })
+                .collect();
+        }
+        (0..MAX_BLOCKS)
+            .map(|i| poiss_pdf(i as f64, lambda, lambda))
+            .collect()
+    }
+}
 
 fn poiss_pdf(x: f64, mu: f64, cond: f64) -> f64 {
     let ln_gamma = ln_gamma(x + 1.0);
@@ -18,18 +86,18 @@ fn poiss_pdf(x: f64, mu: f64, cond: f64) -> f64 {
 /// Calculate the number of winners for each block number, up to [`MAX_BLOCKS`].
 // * This will be needed for optimal message selection
 #[cfg(test)]
-fn no_winners_prob() -> Vec<f64> {
+fn no_winners_prob(mu: f64) -> Vec<f64> {
     (0..MAX_BLOCKS)
-        .map(|i| poiss_pdf(i as f64, MU, MU))
+        .map(|i| poiss_pdf(i as f64, mu, mu))
         .collect()
 }
 
 /// Calculate the number of winners for each block number, up to [`MAX_BLOCKS`],
 /// assuming at least one winner.
-fn no_winners_prob_assuming_more_than_one() -> Vec<f64> {
-    let cond = (E.powf(5.0) - 1.0).log(E);
+fn no_winners_prob_assuming_more_than_one(mu: f64) -> Vec<f64> {
+    let cond = (E.powf(mu) - 1.0).log(E);
     (0..MAX_BLOCKS)
-        .map(|i| poiss_pdf(i as f64, MU, cond))
+        .map(|i| poiss_pdf(i as f64, mu, cond))
         .collect()
 }
 
@@ -76,25 +144,9 @@ fn bino_pdf(x: f64, trials: f64, p: f64) -> f64 {
     coef * pow
 }
 
-pub fn block_probabilities(tq: f64) -> Vec<f64> {
-    let no_winners = no_winners_prob_assuming_more_than_one();
-    let p = 1.0 - tq;
-    (0..MAX_BLOCKS)
-        .map(|place| {
-            no_winners
-                .iter()
-                .enumerate()
-                .map(|(other_winner, p_case)| {
-                    p_case * bino_pdf(place as f64, other_winner as f64, p)
-                })
-                .sum()
-        })
-        .collect()
-}
-
 #[test]
 fn test_block_probability() {
-    let bp = block_probabilities(1.0 - 0.15);
+    let bp = BlockProbabilityConfig::default().block_probabilities(1.0 - 0.15);
     for i in 0..bp.len() - 1 {
         assert!(bp[i] >= bp[i + 1]);
     }
@@ -104,7 +156,7 @@ fn test_block_probability() {
 fn test_winner_probability() {
     use rand::Rng;
     let n = 1_000_000;
-    let winner_prob = no_winners_prob();
+    let winner_prob = no_winners_prob(BlockProbabilityConfig::default().mu());
     let mut sum = 0.0;
 
     // Generates a radnom number from 0 to not including 1
@@ -124,3 +176,17 @@ fn test_winner_probability() {
     let avg = sum / (n as f64);
     assert!((avg - 5.0).abs() > 0.01, "Average too far off ");
 }
+
+#[test]
+fn test_win_count_probability() {
+    let config = BlockProbabilityConfig::default();
+    let probs = config.win_count_probabilities(0.1);
+    assert!((probs.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    let expected: f64 = probs.iter().enumerate().map(|(i, p)| i as f64 * p).sum();
+    assert!((expected - config.expected_wins_per_epoch(0.1)).abs() < 1e-9);
+    assert!((expected - 0.5).abs() < 1e-9);
+
+    let probs = config.win_count_probabilities(0.0);
+    assert_eq!(probs[0], 1.0);
+    assert!(probs[1..].iter().all(|p| *p == 0.0));
+}
@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use crate::{
     db::{SettingsStore, setting_keys::MPOOL_CONFIG_KEY},
+    message_pool::BlockProbabilityConfig,
     shim::address::Address,
     utils::encoding::from_slice_with_fallback,
 };
@@ -27,6 +28,9 @@ pub struct MpoolConfig {
     pub replace_by_fee_ratio: f64,
     pub prune_cooldown: Duration,
     pub gas_limit_overestimation: f64,
+    /// Block probability model of the message selection, set from the node configuration
+    #[serde(default)]
+    pub block_probability: BlockProbabilityConfig,
 }
 
 impl Default for MpoolConfig {
@@ -38,6 +42,7 @@ impl Default for MpoolConfig {
             replace_by_fee_ratio: REPLACE_BY_FEE_RATIO,
             prune_cooldown: PRUNE_COOLDOWN,
             gas_limit_overestimation: GAS_LIMIT_OVERESTIMATION,
+            block_probability: BlockProbabilityConfig::default(),
         }
     }
 }
@@ -28,6 +28,8 @@ pub enum Error {
     InvalidFromAddr,
     #[error("Message with sequence already in mempool")]
     DuplicateSequence,
+    #[error("Message sequence {0} leaves a gap, the next sequence is {1}")]
+    SequenceGap(u64, u64),
     #[error("Validation Error: {0}")]
     SoftValidationFailure(String),
     #[error("Too many pending messages from actor {0} (trusted: {1})")]
@@ -5,11 +5,17 @@ mod config;
 mod errors;
 mod msg_chain;
 mod msgpool;
+pub mod testing;
 
 pub use self::{
     config::*,
     errors::*,
-    msgpool::{msg_pool::MessagePool, provider::MpoolRpcProvider, *},
+    msgpool::{
+        check::{CheckStatus, CheckStatusCode},
+        msg_pool::MessagePool,
+        provider::MpoolRpcProvider,
+        *,
+    },
 };
 
-pub use block_prob::block_probabilities;
+pub use block_prob::BlockProbabilityConfig;
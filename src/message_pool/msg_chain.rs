@@ -301,14 +301,17 @@ pub struct MsgChainNode {
 
 impl MsgChainNode {
     pub fn compare(&self, other: &Self) -> Ordering {
-        if approx_cmp(self.gas_perf, other.gas_perf) == Ordering::Greater
-            || approx_cmp(self.gas_perf, other.gas_perf) == Ordering::Equal
-                && self.gas_reward.cmp(&other.gas_reward) == Ordering::Greater
-        {
-            return Ordering::Greater;
-        }
+        approx_cmp(self.gas_perf, other.gas_perf)
+            .then_with(|| self.gas_reward.cmp(&other.gas_reward))
+            // Ties are broken on the first message, so that the selection doesn't depend on the
+            // iteration order of the pending messages.
+            .then_with(|| other.first_message_key().cmp(&self.first_message_key()))
+    }
 
-        Ordering::Less
+    fn first_message_key(&self) -> Option<(Vec<u8>, u64)> {
+        self.msgs
+            .first()
+            .map(|m| (m.from().to_bytes(), m.sequence()))
     }
 
     pub fn set_eff_perf(&mut self, prev: Option<(f64, u64)>) {
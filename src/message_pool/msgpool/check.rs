@@ -0,0 +1,315 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Static checks of messages against the current tipset and the pending messages of their
+//! senders, which report every problem of a message without adding it to the pool.
+
+use ahash::{HashMap, HashMapExt as _};
+use fvm_ipld_encoding::to_vec;
+use serde_json::{Value, json};
+
+use crate::blocks::Tipset;
+use crate::chain::MINIMUM_BASE_FEE;
+use crate::message::{Message as _, valid_for_block_inclusion};
+use crate::networks::NEWEST_NETWORK_VERSION;
+use crate::shim::{
+    address::Address,
+    econ::TokenAmount,
+    gas::{Gas, price_list_by_network_version},
+    message::Message,
+};
+
+use crate::message_pool::{
+    errors::Error,
+    msgpool::{
+        BASE_FEE_LOWER_BOUND_FACTOR,
+        msg_pool::{MAX_MESSAGE_SIZE, MessagePool},
+    },
+    provider::Provider,
+    utils::get_base_fee_lower_bound,
+};
+
+/// Room left for the signature by the size check of the unsigned messages
+const SIGNATURE_SIZE_ALLOWANCE: usize = 128;
+
+/// Codes of the message checks, matching the Lotus ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CheckStatusCode {
+    MessageSerialize = 1,
+    MessageSize = 2,
+    MessageValidity = 3,
+    MessageMinGas = 4,
+    MessageMinBaseFee = 5,
+    MessageBaseFee = 6,
+    MessageBaseFeeLowerBound = 7,
+    MessageGetStateNonce = 9,
+    MessageNonce = 10,
+    MessageGetStateBalance = 11,
+    MessageBalance = 12,
+}
+
+/// Result of a check of a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckStatus {
+    pub code: CheckStatusCode,
+    /// Why the check failed, `None` if it passed
+    pub err: Option<String>,
+    /// Values the message was checked against
+    pub hint: Value,
+}
+
+impl CheckStatus {
+    fn new(code: CheckStatusCode, err: Option<String>, hint: Value) -> Self {
+        Self { code, err, hint }
+    }
+
+    pub fn ok(&self) -> bool {
+        self.err.is_none()
+    }
+}
+
+/// Next nonce and funds of a sender, on top of its pending messages and of the messages checked
+/// so far
+struct SenderState {
+    next_nonce: u64,
+    balance: TokenAmount,
+    required_funds: TokenAmount,
+}
+
+impl<T> MessagePool<T>
+where
+    T: Provider,
+{
+    /// Checks messages against the current tipset and the pending messages of their senders,
+    /// returning the results of the checks of each message. The messages of a sender are checked
+    /// in order, each on top of the previous ones. The nonce of a message is only checked if it is
+    /// flagged as valid, i.e. as meant to be the next nonce of the sender.
+    pub fn check_messages(&self, msgs: &[(Message, bool)]) -> Result<Vec<Vec<CheckStatus>>, Error> {
+        let cur_ts = self.current_tipset();
+        let price_list =
+            price_list_by_network_version(self.chain_config.network_version(cur_ts.epoch()));
+        let base_fee = self.api.chain_compute_base_fee(&cur_ts)?;
+        let base_fee_lower_bound = get_base_fee_lower_bound(&base_fee, BASE_FEE_LOWER_BOUND_FACTOR);
+
+        let mut senders: HashMap<Address, Result<SenderState, String>> = HashMap::new();
+        let mut results = Vec::with_capacity(msgs.len());
+        for (msg, valid_nonce) in msgs {
+            let mut checks = Vec::new();
+
+            let size = match to_vec(msg) {
+                Ok(bytes) => bytes.len(),
+                Err(e) => {
+                    checks.push(CheckStatus::new(
+                        CheckStatusCode::MessageSerialize,
+                        Some(e.to_string()),
+                        Value::Null,
+                    ));
+                    results.push(checks);
+                    continue;
+                }
+            };
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageSerialize,
+                None,
+                Value::Null,
+            ));
+
+            let max_size = MAX_MESSAGE_SIZE - SIGNATURE_SIZE_ALLOWANCE;
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageSize,
+                (size > max_size).then(|| format!("message size {size} exceeds {max_size}")),
+                json!({ "maxSize": max_size, "actualSize": size }),
+            ));
+
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageValidity,
+                valid_for_block_inclusion(msg, Gas::new(0), NEWEST_NETWORK_VERSION)
+                    .err()
+                    .map(|e| e.to_string()),
+                Value::Null,
+            ));
+
+            let min_gas = price_list.on_chain_message(size).total();
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageMinGas,
+                (Gas::new(msg.gas_limit) < min_gas).then(|| {
+                    format!(
+                        "gas limit {} is below the cost {min_gas} of storing the message on chain",
+                        msg.gas_limit
+                    )
+                }),
+                json!({ "minGas": min_gas.round_up() }),
+            ));
+
+            let fee_cap = msg.gas_fee_cap();
+            let min_base_fee = TokenAmount::from_atto(MINIMUM_BASE_FEE);
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageMinBaseFee,
+                (fee_cap < min_base_fee)
+                    .then(|| format!("gas fee cap {fee_cap} is below the minimum base fee")),
+                json!({ "minBaseFee": min_base_fee.atto().to_string() }),
+            ));
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageBaseFee,
+                (fee_cap < base_fee)
+                    .then(|| format!("gas fee cap {fee_cap} is below the current base fee")),
+                json!({ "baseFee": base_fee.atto().to_string() }),
+            ));
+            checks.push(CheckStatus::new(
+                CheckStatusCode::MessageBaseFeeLowerBound,
+                (fee_cap < base_fee_lower_bound).then(|| {
+                    format!("gas fee cap {fee_cap} is below the lower bound of the base fee")
+                }),
+                json!({
+                    "baseFeeLowerBound": base_fee_lower_bound.atto().to_string(),
+                    "baseFee": base_fee.atto().to_string(),
+                }),
+            ));
+
+            let state = senders
+                .entry(msg.from)
+                .or_insert_with(|| self.sender_state(&msg.from, &cur_ts));
+            match state {
+                Ok(state) => {
+                    checks.push(CheckStatus::new(
+                        CheckStatusCode::MessageGetStateNonce,
+                        None,
+                        Value::Null,
+                    ));
+                    if *valid_nonce {
+                        let matches = msg.sequence == state.next_nonce;
+                        checks.push(CheckStatus::new(
+                            CheckStatusCode::MessageNonce,
+                            (!matches).then(|| {
+                                format!(
+                                    "message nonce {} doesn't match the next nonce {}",
+                                    msg.sequence, state.next_nonce
+                                )
+                            }),
+                            json!({ "nextNonce": state.next_nonce }),
+                        ));
+                        if matches {
+                            state.next_nonce += 1;
+                        }
+                    }
+
+                    checks.push(CheckStatus::new(
+                        CheckStatusCode::MessageGetStateBalance,
+                        None,
+                        Value::Null,
+                    ));
+                    state.required_funds += msg.required_funds() + &msg.value;
+                    checks.push(CheckStatus::new(
+                        CheckStatusCode::MessageBalance,
+                        (state.balance < state.required_funds).then(|| {
+                            format!(
+                                "balance {} does not cover the required funds {}",
+                                state.balance, state.required_funds
+                            )
+                        }),
+                        json!({
+                            "balance": state.balance.atto().to_string(),
+                            "requiredFunds": state.required_funds.atto().to_string(),
+                        }),
+                    ));
+                }
+                Err(e) => {
+                    checks.push(CheckStatus::new(
+                        CheckStatusCode::MessageGetStateNonce,
+                        Some(e.clone()),
+                        Value::Null,
+                    ));
+                    checks.push(CheckStatus::new(
+                        CheckStatusCode::MessageGetStateBalance,
+                        Some(e.clone()),
+                        Value::Null,
+                    ));
+                }
+            }
+            results.push(checks);
+        }
+        Ok(results)
+    }
+
+    fn sender_state(&self, addr: &Address, cur_ts: &Tipset) -> Result<SenderState, String> {
+        let actor = self
+            .api
+            .get_actor_after(addr, cur_ts)
+            .map_err(|e| e.to_string())?;
+        let mut state = SenderState {
+            next_nonce: actor.sequence,
+            balance: TokenAmount::from(&actor.balance),
+            required_funds: TokenAmount::default(),
+        };
+        if let Some(mset) = self.pending.read().get(addr) {
+            state.next_nonce = state.next_nonce.max(mset.next_sequence);
+            for m in mset.msgs.values() {
+                state.required_funds += m.required_funds() + m.value();
+            }
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message_pool::msgpool::test_provider::TestApi;
+    use itertools::Itertools as _;
+    use tokio::task::JoinSet;
+
+    #[tokio::test]
+    async fn check_nonces_and_balance() {
+        let sender = Address::new_id(1000);
+        let api = TestApi::default();
+        api.set_state_sequence(&sender, 5);
+        // Covers the funds of three messages
+        api.set_state_balance_raw(&sender, TokenAmount::from_atto(3_500_000_000u64));
+        let (tx, _rx) = flume::bounded(50);
+        let mut services = JoinSet::new();
+        let mpool = MessagePool::new(
+            api,
+            tx,
+            Default::default(),
+            Default::default(),
+            &mut services,
+        )
+        .unwrap();
+
+        let msg = |sequence| Message {
+            from: sender,
+            to: Address::new_id(1001),
+            sequence,
+            gas_limit: 10_000_000,
+            gas_fee_cap: TokenAmount::from_atto(101),
+            gas_premium: TokenAmount::from_atto(1),
+            ..Default::default()
+        };
+        let results = mpool
+            .check_messages(&[
+                (msg(5), true),
+                (msg(7), true),
+                (msg(6), true),
+                (msg(9), false),
+            ])
+            .unwrap();
+        let failed = |checks: &[CheckStatus]| {
+            checks
+                .iter()
+                .filter(|it| !it.ok())
+                .map(|it| it.code)
+                .collect_vec()
+        };
+        assert_eq!(failed(&results[0]), []);
+        assert_eq!(failed(&results[1]), [CheckStatusCode::MessageNonce]);
+        assert_eq!(failed(&results[2]), []);
+        // The nonce is not checked, and the balance does not cover a fourth message
+        assert_eq!(failed(&results[3]), [CheckStatusCode::MessageBalance]);
+        assert!(
+            results[3]
+                .iter()
+                .all(|it| it.code != CheckStatusCode::MessageNonce)
+        );
+    }
+}
@@ -1,9 +1,11 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub(in crate::message_pool) mod check;
 pub(in crate::message_pool) mod metrics;
 pub(in crate::message_pool) mod msg_pool;
 pub(in crate::message_pool) mod provider;
+pub(in crate::message_pool) mod republish;
 pub mod selection;
 #[cfg(test)]
 pub mod test_provider;
@@ -15,14 +17,16 @@ use crate::blocks::Tipset;
 use crate::libp2p::{NetworkMessage, PUBSUB_MSG_STR, Topic};
 use crate::message::{Message as MessageTrait, SignedMessage};
 use crate::networks::ChainConfig;
-use crate::shim::{address::Address, crypto::Signature};
+use crate::shim::{address::Address, crypto::Signature, econ::TokenAmount};
 use crate::utils::cache::SizeTrackingLruCache;
 use crate::utils::get_size::CidWrapper;
 use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use cid::Cid;
 use fvm_ipld_encoding::to_vec;
+use itertools::Itertools as _;
 use parking_lot::RwLock as SyncRwLock;
-use tracing::error;
+use republish::RepublishBackoff;
+use tracing::{error, info, warn};
 use utils::{get_base_fee_lower_bound, recover_sig};
 
 use super::errors::Error;
@@ -52,6 +56,8 @@ where
     Ok(base_sequence)
 }
 
+/// Re-gossips the pending local messages that can be included on top of the current tipset and
+/// are due for republishing according to the backoff.
 #[allow(clippy::too_many_arguments)]
 async fn republish_pending_messages<T>(
     api: &T,
@@ -61,6 +67,7 @@ async fn republish_pending_messages<T>(
     republished: &SyncRwLock<HashSet<Cid>>,
     local_addrs: &SyncRwLock<Vec<Address>>,
     chain_config: &ChainConfig,
+    backoff: &mut RepublishBackoff,
 ) -> Result<(), Error>
 where
     T: Provider,
@@ -72,23 +79,39 @@ where
 
     // Only republish messages from local addresses, ie. transactions which were
     // sent to this node directly.
-    for actor in local_addrs.read().iter() {
-        if let Some(mset) = pending.read().get(actor) {
-            if mset.msgs.is_empty() {
-                continue;
-            }
-            let mut pend: HashMap<u64, SignedMessage> = HashMap::with_capacity(mset.msgs.len());
-            for (nonce, m) in mset.msgs.clone().into_iter() {
-                pend.insert(nonce, m);
-            }
+    let local_addrs = local_addrs.read().clone();
+    for actor in local_addrs.iter() {
+        let Some(msgs) = pending.read().get(actor).map(|mset| mset.msgs.clone()) else {
+            continue;
+        };
+        if msgs.is_empty() {
+            continue;
+        }
+        // The messages below the nonce of the actor on chain are included already
+        let chain_nonce = get_state_sequence(api, actor, &ts)?;
+        backoff.on_chain_nonce(*actor, chain_nonce, ts.epoch());
+        let pend: HashMap<u64, SignedMessage> = msgs
+            .into_iter()
+            .filter(|(nonce, _)| *nonce >= chain_nonce)
+            .collect();
+        if !pend.is_empty() {
             pending_map.insert(*actor, pend);
         }
     }
+    backoff.retain(
+        &pending_map
+            .values()
+            .flat_map(|pend| pend.values().map(|m| m.cid()))
+            .collect(),
+    );
 
     let msgs = select_messages_for_block(api, chain_config, &ts, pending_map)?;
 
     let network_name = chain_config.network.genesis_name();
-    for m in msgs.iter() {
+    for m in msgs
+        .iter()
+        .filter(|m| backoff.is_due(m.cid(), m.from(), ts.epoch()))
+    {
         let mb = to_vec(m)?;
         network_sender
             .send_async(NetworkMessage::PubsubMessage {
@@ -274,6 +297,7 @@ where
             .await
             .map_err(|e| Error::Other(format!("Republish receiver dropped: {e}")))?;
     }
+    let reorg = !rmsgs.is_empty();
     for (_, hm) in rmsgs {
         for (_, msg) in hm {
             let sequence = get_state_sequence(api, &msg.from(), &cur_tipset.read().clone())?;
@@ -289,9 +313,73 @@ where
             }
         }
     }
+    if reorg {
+        let ts = cur_tipset.read().clone();
+        let removed = revalidate_pending(api, pending, &ts);
+        if removed > 0 {
+            info!(
+                "Removed {removed} pending messages invalidated by the reorg to epoch {}",
+                ts.epoch()
+            );
+        }
+    }
     Ok(())
 }
 
+/// This is a helper function for `head_change`. After a reorg, the messages of the pool, including
+/// the re-injected ones of the reverted tipsets, are checked again against the state of the new
+/// head: messages whose sequence was already used on the new chain are removed, and so are the
+/// messages their sender can no longer pay for. Like the required funds in Lotus, the balance
+/// must cover the pending messages of the sender together, in sequence order, the first message
+/// it doesn't cover and the following ones are removed. Returns the number of removed messages.
+pub(in crate::message_pool) fn revalidate_pending<T>(
+    api: &T,
+    pending: &SyncRwLock<HashMap<Address, MsgSet>>,
+    cur_ts: &Tipset,
+) -> usize
+where
+    T: Provider,
+{
+    let senders: Vec<Address> = pending.read().keys().copied().collect();
+    let mut removed = 0;
+    for from in senders {
+        let actor = match api.get_actor_after(&from, cur_ts) {
+            Ok(actor) => actor,
+            Err(e) => {
+                warn!("Failed to load sender {from} after reorg: {e}");
+                continue;
+            }
+        };
+        let balance = TokenAmount::from(&actor.balance);
+        let mut required_funds = TokenAmount::default();
+        let invalid: Vec<(u64, bool)> = pending
+            .read()
+            .get(&from)
+            .map(|mset| {
+                mset.msgs
+                    .values()
+                    .sorted_by_key(|msg| msg.sequence())
+                    .filter_map(|msg| {
+                        if msg.sequence() < actor.sequence {
+                            return Some((msg.sequence(), true));
+                        }
+                        required_funds += msg.required_funds();
+                        (required_funds > balance).then_some((msg.sequence(), false))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (sequence, applied) in invalid {
+            if let Err(e) = remove(&from, pending, sequence, applied) {
+                error!("Failed to remove message {sequence} of {from} after reorg: {e}");
+                continue;
+            }
+            removed += 1;
+        }
+    }
+    removed
+}
+
 /// This is a helper function for `head_change`. This method will remove a
 /// sequence for a from address from the messages selected by priority hash-map.
 /// It also removes the 'from' address and sequence from the `MessagePool`.
@@ -330,7 +418,7 @@ pub(in crate::message_pool) fn add_to_selected_msgs(
 
 #[cfg(test)]
 pub mod tests {
-    use std::{borrow::BorrowMut, time::Duration};
+    use std::{borrow::BorrowMut, sync::Arc, time::Duration};
 
     use crate::blocks::Tipset;
     use crate::key_management::{KeyStore, KeyStoreConfig, Wallet};
@@ -342,6 +430,7 @@ pub mod tests {
         econ::TokenAmount,
         message::{Message, Message_v3},
     };
+    use itertools::Itertools as _;
     use num_traits::Zero;
     use test_provider::*;
     use tokio::task::JoinSet;
@@ -604,6 +693,90 @@ pub mod tests {
         assert_eq!(p.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_revalidate_messages_on_reorg() {
+        let keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let mut wallet = Wallet::new(keystore);
+        let sender = wallet.generate_addr(SignatureType::Bls).unwrap();
+        let target = Address::new_id(1001);
+
+        let a = mock_block(1, 1);
+        let b = mock_block_with_parents(&Tipset::from(&a), 1, 1);
+        let smsgs: Vec<SignedMessage> = (0..4)
+            .map(|i| create_smsg(&target, &sender, wallet.borrow_mut(), i, 1000000, 1))
+            .collect();
+
+        let (tx, _rx) = flume::bounded(50);
+        let mut services = JoinSet::new();
+        let mpool = MessagePool::new(
+            TestApi::default(),
+            tx,
+            Default::default(),
+            Default::default(),
+            &mut services,
+        )
+        .unwrap();
+        {
+            let mut api = mpool.api.inner.lock();
+            api.set_block_messages(&a, vec![smsgs[0].clone()]);
+            api.set_block_messages(&b, smsgs[1..4].to_vec());
+        }
+        for msg in &smsgs {
+            mpool.add(msg.clone()).unwrap();
+        }
+
+        let change_head = |revert: Vec<Tipset>, apply: Vec<Tipset>| {
+            head_change(
+                mpool.api.as_ref(),
+                mpool.bls_sig_cache.as_ref(),
+                mpool.repub_trigger.clone(),
+                mpool.republished.as_ref(),
+                mpool.pending.as_ref(),
+                mpool.cur_tipset.as_ref(),
+                revert,
+                apply,
+            )
+        };
+        change_head(vec![], vec![Tipset::from(&a), Tipset::from(&b)])
+            .await
+            .unwrap();
+        assert!(mpool.pending().unwrap().0.is_empty());
+
+        // On the new chain, the sequence 1 of the sender was used by another message, the
+        // re-injected message with this sequence is invalid.
+        mpool.api.set_state_sequence(&sender, 2);
+        change_head(vec![Tipset::from(&b)], vec![]).await.unwrap();
+        let (pending, _) = mpool.pending().unwrap();
+        assert_eq!(
+            pending.iter().map(|m| m.sequence()).sorted().collect_vec(),
+            vec![2, 3]
+        );
+        assert_eq!(mpool.get_sequence(&sender).unwrap(), 4);
+
+        // On the new chain, the sender can only pay for two of its messages together, while it
+        // could pay for each of them.
+        mpool.api.set_state_sequence(&sender, 0);
+        change_head(vec![], vec![Tipset::from(&b)]).await.unwrap();
+        let required_funds = smsgs[1].required_funds();
+        mpool
+            .api
+            .set_state_balance_raw(&sender, &required_funds * 2_u64 + TokenAmount::from_atto(1));
+        change_head(vec![Tipset::from(&b)], vec![]).await.unwrap();
+        let (pending, _) = mpool.pending().unwrap();
+        assert_eq!(
+            pending.iter().map(|m| m.sequence()).sorted().collect_vec(),
+            vec![1, 2]
+        );
+
+        // On the new chain, the sender can no longer pay for any message.
+        change_head(vec![], vec![Tipset::from(&b)]).await.unwrap();
+        mpool
+            .api
+            .set_state_balance_raw(&sender, TokenAmount::from_atto(1));
+        change_head(vec![Tipset::from(&b)], vec![]).await.unwrap();
+        assert!(mpool.pending().unwrap().0.is_empty());
+    }
+
     #[tokio::test]
     async fn test_async_message_pool() {
         let keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
@@ -650,6 +823,109 @@ pub mod tests {
         assert_eq!(cur_ts, tipset);
     }
 
+    #[tokio::test]
+    async fn test_batch_push() {
+        let keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let mut wallet = Wallet::new(keystore);
+        let sender = wallet.generate_addr(SignatureType::Secp256k1).unwrap();
+        let target = wallet.generate_addr(SignatureType::Secp256k1).unwrap();
+
+        let tma = TestApi::default();
+        tma.set_state_sequence(&sender, 0);
+        // Covers the funds of two messages but not three
+        tma.set_state_balance_raw(&sender, TokenAmount::from_atto(250_000_000));
+        let (tx, _rx) = flume::bounded(50);
+        let mut services = JoinSet::new();
+        let mpool = MessagePool::new(
+            tma,
+            tx,
+            Default::default(),
+            Default::default(),
+            &mut services,
+        )
+        .unwrap();
+
+        let smsg_vec = (0..4)
+            .map(|i| create_smsg(&target, &sender, wallet.borrow_mut(), i, 1000000, 1))
+            .collect_vec();
+
+        // The funds of the batch are checked together, and nothing is admitted on failure
+        assert_eq!(
+            mpool.batch_push(smsg_vec[..3].to_vec()).await,
+            Err(Error::NotEnoughFunds)
+        );
+        assert_eq!(mpool.get_sequence(&sender).unwrap(), 0);
+
+        // Untrusted batches may not leave sequence gaps
+        assert_eq!(
+            mpool
+                .batch_push_untrusted(vec![smsg_vec[0].clone(), smsg_vec[2].clone()])
+                .await,
+            Err(Error::SequenceGap(2, 1))
+        );
+        assert_eq!(mpool.get_sequence(&sender).unwrap(), 0);
+
+        // Messages are admitted in sequence order, the CIDs are returned in input order
+        let batch = vec![smsg_vec[1].clone(), smsg_vec[0].clone()];
+        let cids = mpool.batch_push_untrusted(batch.clone()).await.unwrap();
+        assert_eq!(cids, batch.iter().map(|m| m.cid()).collect_vec());
+        assert_eq!(mpool.get_sequence(&sender).unwrap(), 2);
+
+        // Untrusted messages must be covered along with the pending messages of the sender
+        assert_eq!(
+            mpool.push_untrusted(smsg_vec[2].clone()).await,
+            Err(Error::NotEnoughFunds)
+        );
+        mpool.push(smsg_vec[2].clone()).await.unwrap();
+        assert_eq!(mpool.get_sequence(&sender).unwrap(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_batch_push() {
+        let keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let mut wallet = Wallet::new(keystore);
+        let sender = wallet.generate_addr(SignatureType::Secp256k1).unwrap();
+        let target = wallet.generate_addr(SignatureType::Secp256k1).unwrap();
+
+        let tma = TestApi::default();
+        tma.set_state_sequence(&sender, 0);
+        tma.set_state_balance_raw(&sender, TokenAmount::from_atto(1_000_000_000));
+        let (tx, _rx) = flume::bounded(500);
+        let mut services = JoinSet::new();
+        let mpool = Arc::new(
+            MessagePool::new(
+                tma,
+                tx,
+                Default::default(),
+                Default::default(),
+                &mut services,
+            )
+            .unwrap(),
+        );
+
+        let batch = (0..2)
+            .map(|i| create_smsg(&target, &sender, wallet.borrow_mut(), i, 1000000, 1))
+            .collect_vec();
+
+        // The same batch is pushed concurrently, only one push may admit it
+        let mut pushes = JoinSet::new();
+        for _ in 0..16 {
+            let mpool = mpool.clone();
+            let batch = batch.clone();
+            pushes.spawn(async move { mpool.batch_push_untrusted(batch).await });
+        }
+        let results = pushes.join_all().await;
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+        assert!(
+            results
+                .iter()
+                .filter_map(|result| result.as_ref().err())
+                .all(|e| *e == Error::DuplicateSequence)
+        );
+        assert_eq!(mpool.get_sequence(&sender).unwrap(), 2);
+        assert_eq!(mpool.pending_for(&sender).unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_msg_chains() {
         let keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
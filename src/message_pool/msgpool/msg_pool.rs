@@ -6,10 +6,10 @@
 // inclusion in the chain. Messages are added either directly for locally
 // published messages or through pubsub propagation.
 
-use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+use std::{collections::hash_map::Entry, num::NonZeroUsize, sync::Arc};
 
 use crate::blocks::{CachingBlockHeader, Tipset};
-use crate::chain::{HeadChange, MINIMUM_BASE_FEE};
+use crate::chain::{HeadChange, MINIMUM_BASE_FEE, scheduler::EpochScheduler};
 #[cfg(test)]
 use crate::db::SettingsStore;
 use crate::eth::is_valid_eth_tx_for_sending;
@@ -18,6 +18,7 @@ use crate::message::{ChainMessage, Message, SignedMessage, valid_for_block_inclu
 use crate::networks::{ChainConfig, NEWEST_NETWORK_VERSION};
 use crate::shim::{
     address::Address,
+    clock::ChainEpoch,
     crypto::{Signature, SignatureType},
     econ::TokenAmount,
     gas::{Gas, price_list_by_network_version},
@@ -33,7 +34,7 @@ use fvm_ipld_encoding::to_vec;
 use itertools::Itertools;
 use nonzero_ext::nonzero;
 use parking_lot::RwLock as SyncRwLock;
-use tokio::{sync::broadcast::error::RecvError, task::JoinSet, time::interval};
+use tokio::{sync::broadcast::error::RecvError, task::JoinSet};
 use tracing::warn;
 
 use crate::message_pool::{
@@ -42,7 +43,7 @@ use crate::message_pool::{
     head_change, metrics,
     msgpool::{
         BASE_FEE_LOWER_BOUND_FACTOR_CONSERVATIVE, RBF_DENOM, RBF_NUM, recover_sig,
-        republish_pending_messages,
+        republish::RepublishBackoff, republish_pending_messages,
     },
     provider::Provider,
     utils::get_base_fee_lower_bound,
@@ -52,11 +53,15 @@ use crate::message_pool::{
 const BLS_SIG_CACHE_SIZE: NonZeroUsize = nonzero!(40000usize);
 const SIG_VAL_CACHE_SIZE: NonZeroUsize = nonzero!(32000usize);
 
+/// Number of epochs between the checks for pending local messages due for republishing, on top
+/// of the checks on head changes. Each message is republished with an exponential backoff.
+const REPUBLISH_INTERVAL_EPOCHS: ChainEpoch = 1;
+
 pub const MAX_ACTOR_PENDING_MESSAGES: u64 = 1000;
 pub const MAX_UNTRUSTED_ACTOR_PENDING_MESSAGES: u64 = 10;
 /// Maximum size of a serialized message in bytes. This is an anti-DOS measure to prevent
 /// large messages from being added to the message pool.
-const MAX_MESSAGE_SIZE: usize = 64 << 10; // 64 KiB
+pub(in crate::message_pool) const MAX_MESSAGE_SIZE: usize = 64 << 10; // 64 KiB
 
 /// Trust policy for whether a message is from a trusted or untrusted source.
 /// Untrusted sources are subject to stricter limits.
@@ -71,7 +76,7 @@ pub enum TrustPolicy {
 #[derive(Clone, Default, Debug)]
 pub struct MsgSet {
     pub(in crate::message_pool) msgs: HashMap<u64, SignedMessage>,
-    next_sequence: u64,
+    pub(in crate::message_pool) next_sequence: u64,
 }
 
 impl MsgSet {
@@ -105,6 +110,18 @@ impl MsgSet {
     }
 
     fn add<T>(&mut self, api: &T, m: SignedMessage, trusted: bool) -> Result<(), Error>
+    where
+        T: Provider,
+    {
+        self.check_add(api, &m, trusted)?;
+        if self.insert(m) {
+            metrics::MPOOL_MESSAGE_TOTAL.inc();
+        }
+        Ok(())
+    }
+
+    /// Checks that a message can be added to the `MsgSet`, without adding it.
+    fn check_add<T>(&self, api: &T, m: &SignedMessage, trusted: bool) -> Result<(), Error>
     where
         T: Provider,
     {
@@ -114,10 +131,6 @@ impl MsgSet {
             api.max_untrusted_actor_pending_messages()
         };
 
-        if self.msgs.is_empty() || m.sequence() >= self.next_sequence {
-            self.next_sequence = m.sequence() + 1;
-        }
-
         let has_existing = if let Some(exms) = self.msgs.get(&m.sequence()) {
             if m.cid() != exms.cid() {
                 let premium = &exms.message().gas_premium;
@@ -142,12 +155,33 @@ impl MsgSet {
                 trusted,
             ));
         }
-        if self.msgs.insert(m.sequence(), m).is_none() {
-            metrics::MPOOL_MESSAGE_TOTAL.inc();
+        // Untrusted sources may not leave gaps, which would keep the messages of the actor
+        // pending until they are filled
+        if !trusted && m.sequence() > self.next_sequence {
+            return Err(Error::SequenceGap(m.sequence(), self.next_sequence));
         }
         Ok(())
     }
 
+    /// Inserts a message that passed [`MsgSet::check_add`], returning whether it is new rather
+    /// than a replacement.
+    fn insert(&mut self, m: SignedMessage) -> bool {
+        if self.msgs.is_empty() || m.sequence() >= self.next_sequence {
+            self.next_sequence = m.sequence() + 1;
+        }
+        self.msgs.insert(m.sequence(), m).is_none()
+    }
+
+    /// Returns the funds required by the messages of the set, except the one with the given
+    /// sequence, which may be replaced.
+    fn required_funds_except(&self, sequence: u64) -> TokenAmount {
+        let mut funds = TokenAmount::default();
+        for (_, m) in self.msgs.iter().filter(|(s, _)| **s != sequence) {
+            funds += m.required_funds();
+        }
+        funds
+    }
+
     /// Removes message with the given sequence. If applied, update the set's
     /// next sequence.
     pub fn rm(&mut self, sequence: u64, applied: bool) {
@@ -232,23 +266,112 @@ where
         msg: SignedMessage,
         trust_policy: TrustPolicy,
     ) -> Result<Cid, Error> {
-        self.check_message(&msg)?;
-        let cid = msg.cid();
-        let cur_ts = self.current_tipset();
-        let publish = self.add_tipset(msg.clone(), &cur_ts, true, trust_policy)?;
-        let msg_ser = to_vec(&msg)?;
+        let mut cids = self.batch_push_internal(vec![msg], trust_policy).await?;
+        Ok(cids.remove(0))
+    }
+
+    /// Push a batch of signed messages to the `MessagePool`. All the messages are validated
+    /// before any is added, so that an invalid message does not leave the batch partially
+    /// admitted. The messages of each sender are validated and added in the order of their
+    /// sequences, and their funds are checked together. Messages from untrusted sources must
+    /// not leave sequence gaps, and their funds are checked together with the pending messages
+    /// of the sender. The pending messages are locked from the checks that depend on them to the
+    /// insertion of the batch, so that concurrent pushes cannot invalidate it in between.
+    pub async fn batch_push_internal(
+        &self,
+        msgs: Vec<SignedMessage>,
+        trust_policy: TrustPolicy,
+    ) -> Result<Vec<Cid>, Error> {
+        let cids = msgs.iter().map(|msg| msg.cid()).collect();
+        let validated = self.batch_add(msgs, trust_policy)?;
+
         let network_name = self.chain_config.network.genesis_name();
-        self.add_local(msg)?;
-        if publish {
-            self.network_sender
-                .send_async(NetworkMessage::PubsubMessage {
-                    topic: Topic::new(format!("{PUBSUB_MSG_STR}/{network_name}")),
-                    message: msg_ser,
-                })
-                .await
-                .map_err(|_| Error::Other("Network receiver dropped".to_string()))?;
+        for (msg, publish) in validated {
+            self.add_local(msg.clone())?;
+            if publish {
+                self.network_sender
+                    .send_async(NetworkMessage::PubsubMessage {
+                        topic: Topic::new(format!("{PUBSUB_MSG_STR}/{network_name}")),
+                        message: to_vec(&msg)?,
+                    })
+                    .await
+                    .map_err(|_| Error::Other("Network receiver dropped".to_string()))?;
+            }
         }
-        Ok(cid)
+        Ok(cids)
+    }
+
+    /// Validates the messages of a batch and adds them to the pending messages, all of them or
+    /// none. Returns the messages with whether they should be published.
+    fn batch_add(
+        &self,
+        mut msgs: Vec<SignedMessage>,
+        trust_policy: TrustPolicy,
+    ) -> Result<Vec<(SignedMessage, bool)>, Error> {
+        let cur_ts = self.current_tipset();
+        msgs.sort_by_key(|msg| (msg.from(), msg.sequence()));
+
+        // The checks that don't depend on the pending messages, and the state reads, are done
+        // before locking them.
+        let mut state_sequences: HashMap<Address, u64> = HashMap::new();
+        let mut checked = Vec::with_capacity(msgs.len());
+        for msg in msgs {
+            self.check_message(&msg)?;
+            let (publish, balance) = self.validate_sender(&msg, &cur_ts, true)?;
+            if let Entry::Vacant(entry) = state_sequences.entry(msg.from()) {
+                entry.insert(self.get_state_sequence(&msg.from(), &cur_ts)?);
+            }
+            checked.push((msg, publish, balance));
+        }
+
+        let mut pending = self.pending.write();
+        // Pending messages of the senders, to which the messages of the batch are added as they
+        // are validated
+        let mut msets: HashMap<Address, MsgSet> = HashMap::new();
+        let mut batch_funds: HashMap<Address, TokenAmount> = HashMap::new();
+        let mut validated = Vec::with_capacity(checked.len());
+        let mut new_msgs = 0;
+        for (msg, publish, balance) in checked {
+            let from = msg.from();
+            let mset = msets
+                .entry(from)
+                .or_insert_with(|| match pending.get(&from) {
+                    Some(mset) => mset.clone(),
+                    None => MsgSet::new(state_sequences.get(&from).copied().unwrap_or_default()),
+                });
+            let other_funds = match trust_policy {
+                TrustPolicy::Trusted => batch_funds.get(&from).cloned().unwrap_or_default(),
+                TrustPolicy::Untrusted => mset.required_funds_except(msg.sequence()),
+            };
+            check_funds(&msg, &balance, &other_funds)?;
+            mset.check_add(
+                self.api.as_ref(),
+                &msg,
+                trust_policy == TrustPolicy::Trusted,
+            )?;
+            if mset.insert(msg.clone()) {
+                new_msgs += 1;
+            }
+            *batch_funds.entry(from).or_default() += msg.required_funds();
+            validated.push((msg, publish));
+        }
+        // The sets of the senders already hold the messages of the batch
+        pending.extend(msets);
+        drop(pending);
+
+        for (msg, _) in &validated {
+            self.api.put_message(&ChainMessage::Signed(msg.clone()))?;
+            self.api
+                .put_message(&ChainMessage::Unsigned(msg.message().clone()))?;
+        }
+        for (msg, _) in &validated {
+            if msg.signature().signature_type() == SignatureType::Bls {
+                self.bls_sig_cache
+                    .push(msg.cid().into(), msg.signature().clone());
+            }
+        }
+        metrics::MPOOL_MESSAGE_TOTAL.inc_by(new_msgs);
+        Ok(validated)
     }
 
     /// Push a signed message to the `MessagePool` from an trusted source.
@@ -261,6 +384,16 @@ where
         self.push_internal(msg, TrustPolicy::Untrusted).await
     }
 
+    /// Push a batch of signed messages to the `MessagePool` from a trusted source.
+    pub async fn batch_push(&self, msgs: Vec<SignedMessage>) -> Result<Vec<Cid>, Error> {
+        self.batch_push_internal(msgs, TrustPolicy::Trusted).await
+    }
+
+    /// Push a batch of signed messages to the `MessagePool` from an untrusted source.
+    pub async fn batch_push_untrusted(&self, msgs: Vec<SignedMessage>) -> Result<Vec<Cid>, Error> {
+        self.batch_push_internal(msgs, TrustPolicy::Untrusted).await
+    }
+
     fn check_message(&self, msg: &SignedMessage) -> Result<(), Error> {
         if to_vec(msg)?.len() > MAX_MESSAGE_SIZE {
             return Err(Error::MessageTooBig);
@@ -312,6 +445,34 @@ where
         local: bool,
         trust_policy: TrustPolicy,
     ) -> Result<bool, Error> {
+        let publish = self.validate_for_tipset(&msg, cur_ts, local, &TokenAmount::default())?;
+        self.add_helper(msg, trust_policy)?;
+        Ok(publish)
+    }
+
+    /// Verify the `state_sequence` and balance for the sender of the message, the balance
+    /// covering `other_funds` on top of the funds required by the message. Returns whether the
+    /// message should be published.
+    fn validate_for_tipset(
+        &self,
+        msg: &SignedMessage,
+        cur_ts: &Tipset,
+        local: bool,
+        other_funds: &TokenAmount,
+    ) -> Result<bool, Error> {
+        let (publish, balance) = self.validate_sender(msg, cur_ts, local)?;
+        check_funds(msg, &balance, other_funds)?;
+        Ok(publish)
+    }
+
+    /// Verify the `state_sequence` of the sender of the message and that it may send it. Returns
+    /// whether the message should be published, and the balance of the sender.
+    fn validate_sender(
+        &self,
+        msg: &SignedMessage,
+        cur_ts: &Tipset,
+        local: bool,
+    ) -> Result<(bool, TokenAmount), Error> {
         let sequence = self.get_state_sequence(&msg.from(), cur_ts)?;
 
         if sequence > msg.message().sequence {
@@ -324,7 +485,7 @@ where
         let nv = self.chain_config.network_version(cur_ts.epoch() + 1);
         let eth_chain_id = self.chain_config.eth_chain_id;
         if msg.signature().signature_type() == SignatureType::Delegated
-            && !is_valid_eth_tx_for_sending(eth_chain_id, nv, &msg)
+            && !is_valid_eth_tx_for_sending(eth_chain_id, nv, msg)
         {
             return Err(Error::Other(
                 "Invalid Ethereum message for the current network version".to_owned(),
@@ -336,16 +497,10 @@ where
             ));
         }
 
-        let publish = verify_msg_before_add(&msg, cur_ts, local, &self.chain_config)?;
+        let publish = verify_msg_before_add(msg, cur_ts, local, &self.chain_config)?;
 
         let balance = self.get_state_balance(&msg.from(), cur_ts)?;
-
-        let msg_balance = msg.required_funds();
-        if balance < msg_balance {
-            return Err(Error::NotEnoughFunds);
-        }
-        self.add_helper(msg, trust_policy)?;
-        Ok(publish)
+        Ok((publish, balance))
     }
 
     /// Finish verifying signed message before adding it to the pending `mset`
@@ -517,7 +672,6 @@ where
         ));
         let local_msgs = Arc::new(SyncRwLock::new(HashSet::new()));
         let republished = Arc::new(SyncRwLock::new(HashSet::new()));
-        let block_delay = chain_config.block_delay_secs;
 
         let (repub_trigger, repub_trigger_rx) = flume::bounded::<()>(4);
         let mut mp = MessagePool {
@@ -586,16 +740,12 @@ where
         let republished = mp.republished.clone();
         let local_addrs = mp.local_addrs.clone();
         let network_sender = Arc::new(mp.network_sender.clone());
-        let republish_interval = (10 * block_delay + chain_config.propagation_delay_secs) as u64;
-        // Reacts to republishing requests
+        // Reacts to republishing requests, triggered on head changes and periodically by the
+        // epoch scheduler
         services.spawn(async move {
             let mut repub_trigger_rx = repub_trigger_rx.stream();
-            let mut interval = interval(Duration::from_secs(republish_interval));
-            loop {
-                tokio::select! {
-                    _ = interval.tick() => (),
-                    _ = repub_trigger_rx.next() => (),
-                }
+            let mut backoff = RepublishBackoff::default();
+            while repub_trigger_rx.next().await.is_some() {
                 if let Err(e) = republish_pending_messages(
                     api.as_ref(),
                     network_sender.as_ref(),
@@ -604,15 +754,26 @@ where
                     republished.as_ref(),
                     local_addrs.as_ref(),
                     &chain_config,
+                    &mut backoff,
                 )
                 .await
                 {
                     warn!("Failed to republish pending messages: {}", e.to_string());
                 }
             }
+            Ok(())
         });
         Ok(mp)
     }
+
+    /// Registers the periodic republishing of the pending local messages on the epoch scheduler.
+    pub fn schedule_republish(&self, scheduler: &mut EpochScheduler) {
+        let repub_trigger = self.repub_trigger.clone();
+        scheduler.every("mpool republish", REPUBLISH_INTERVAL_EPOCHS, move |_| {
+            // A republish already pending covers this one.
+            let _ = repub_trigger.try_send(());
+        });
+    }
 }
 
 // Helpers for MessagePool
@@ -683,6 +844,19 @@ fn verify_msg_before_add(
     Ok(local)
 }
 
+/// Checks that the balance of the sender covers `other_funds` on top of the funds required by
+/// the message.
+fn check_funds(
+    m: &SignedMessage,
+    balance: &TokenAmount,
+    other_funds: &TokenAmount,
+) -> Result<(), Error> {
+    if balance < &(m.required_funds() + other_funds) {
+        return Err(Error::NotEnoughFunds);
+    }
+    Ok(())
+}
+
 /// Remove a message from pending given the from address and sequence.
 pub fn remove(
     from: &Address,
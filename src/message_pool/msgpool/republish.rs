@@ -0,0 +1,104 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use ahash::{HashMap, HashSet};
+use cid::Cid;
+
+use crate::shim::{address::Address, clock::ChainEpoch};
+
+/// Epochs before the first republishing of a pending local message, doubled on every
+/// republishing
+const REPUBLISH_BACKOFF_BASE_EPOCHS: ChainEpoch = 2;
+/// Maximum number of epochs between two republishings of a pending local message, an hour
+const REPUBLISH_BACKOFF_MAX_EPOCHS: ChainEpoch = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RepublishState {
+    actor: Address,
+    attempts: u32,
+    next_epoch: ChainEpoch,
+}
+
+/// Exponential backoff of the republishing of the pending local messages, so that the messages
+/// submitted during poor connectivity eventually propagate without flooding the network.
+///
+/// The backoff is chain-aware: when the nonce of an actor moves on chain, its messages are
+/// propagating again and its remaining messages are republished right away.
+#[derive(Debug, Default)]
+pub(in crate::message_pool) struct RepublishBackoff {
+    messages: HashMap<Cid, RepublishState>,
+    chain_nonces: HashMap<Address, u64>,
+}
+
+impl RepublishBackoff {
+    /// Records the nonce of an actor on chain, resetting the backoff of its messages if it moved.
+    pub fn on_chain_nonce(&mut self, actor: Address, nonce: u64, epoch: ChainEpoch) {
+        if self.chain_nonces.insert(actor, nonce) == Some(nonce) {
+            return;
+        }
+        for state in self.messages.values_mut() {
+            if state.actor == actor {
+                state.attempts = 0;
+                state.next_epoch = epoch;
+            }
+        }
+    }
+
+    /// Returns whether a message is due for republishing at the epoch, scheduling its next
+    /// republishing if so.
+    pub fn is_due(&mut self, cid: Cid, actor: Address, epoch: ChainEpoch) -> bool {
+        let state = self.messages.entry(cid).or_insert(RepublishState {
+            actor,
+            attempts: 0,
+            next_epoch: epoch,
+        });
+        if state.next_epoch > epoch {
+            return false;
+        }
+        let delay = REPUBLISH_BACKOFF_BASE_EPOCHS
+            .saturating_mul(1 << state.attempts.min(32))
+            .min(REPUBLISH_BACKOFF_MAX_EPOCHS);
+        state.attempts = state.attempts.saturating_add(1);
+        state.next_epoch = epoch + delay;
+        true
+    }
+
+    /// Forgets the messages that are no longer pending, and the actors without any.
+    pub fn retain(&mut self, pending: &HashSet<Cid>) {
+        self.messages.retain(|cid, _| pending.contains(cid));
+        let actors: HashSet<Address> = self.messages.values().map(|it| it.actor).collect();
+        self.chain_nonces.retain(|actor, _| actors.contains(actor));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::multihash::MultihashCode;
+    use multihash_derive::MultihashDigest as _;
+
+    #[test]
+    fn exponential_backoff() {
+        let actor = Address::new_id(1000);
+        let cid = Cid::new_v1(
+            fvm_ipld_encoding::DAG_CBOR,
+            MultihashCode::Blake2b256.digest(b"message"),
+        );
+        let mut backoff = RepublishBackoff::default();
+        backoff.on_chain_nonce(actor, 0, 0);
+        let due: Vec<ChainEpoch> = (0..=400)
+            .filter(|&epoch| backoff.is_due(cid, actor, epoch))
+            .collect();
+        assert_eq!(due, [0, 2, 6, 14, 30, 62, 126, 246, 366]);
+
+        // The nonce of the actor moved on chain
+        backoff.on_chain_nonce(actor, 1, 401);
+        assert!(backoff.is_due(cid, actor, 401));
+        assert!(!backoff.is_due(cid, actor, 402));
+        assert!(backoff.is_due(cid, actor, 403));
+
+        backoff.retain(&HashSet::default());
+        assert!(backoff.messages.is_empty());
+        assert!(backoff.chain_nonces.is_empty());
+    }
+}
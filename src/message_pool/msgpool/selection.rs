@@ -442,7 +442,10 @@ where
         // 4. Compute effective performance for each chain, based on the partition they
         // fall into    The effective performance is the gas_perf of the chain *
         // block probability
-        let block_prob = crate::message_pool::block_probabilities(ticket_quality);
+        let block_prob = self
+            .config
+            .block_probability
+            .block_probabilities(ticket_quality);
         let mut eff_chains = 0;
         for i in 0..MAX_BLOCKS {
             for k in &partitions[i] {
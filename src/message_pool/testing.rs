@@ -0,0 +1,414 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Synthetic message pools exercising the message selection, in the unit tests and in the
+//! `forest-tool benchmark mpool-select` harness. A [`Corpus`] of actors and pending messages is
+//! generated from a seed, loaded into a [`MessagePool`] backed by a [`SimProvider`], and the
+//! selected messages are checked against the invariants of the selection.
+
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use ahash::HashMap;
+use anyhow::ensure;
+use async_trait::async_trait;
+use cid::Cid;
+use itertools::Itertools as _;
+use num_traits::Signed as _;
+use rand::{Rng as _, SeedableRng as _};
+use tokio::sync::broadcast::{self, Receiver as Subscriber, Sender as Publisher};
+use tokio::task::JoinSet;
+
+use crate::blocks::{
+    BLOCK_MESSAGE_LIMIT, CachingBlockHeader, RawBlockHeader, Ticket, Tipset, TipsetKey, VRFProof,
+};
+use crate::chain::HeadChange;
+use crate::message::{ChainMessage, Message as MessageTrait, SignedMessage};
+use crate::message_pool::{
+    Error, MessagePool, msg_pool::MsgSet, provider::Provider, utils::get_gas_reward,
+};
+use crate::shim::{
+    address::Address,
+    crypto::Signature,
+    econ::{BLOCK_GAS_LIMIT, TokenAmount},
+    message::{Message, Message_v3},
+    state_tree::ActorState,
+};
+
+/// Base fee of the simulated tipset, in attoFIL.
+pub const SIM_BASE_FEE: u64 = 100;
+
+/// Distribution of the actors and of the pending messages of a [`Corpus`].
+#[derive(Debug, Clone)]
+pub struct CorpusConfig {
+    /// Number of sending actors
+    pub actors: usize,
+    /// Maximum number of pending messages per actor
+    pub max_messages_per_actor: u64,
+    /// Range of the gas limits of the messages
+    pub gas_limit: RangeInclusive<u64>,
+    /// Range of the gas premiums of the messages, in attoFIL
+    pub gas_premium: RangeInclusive<u64>,
+    /// Share of the messages whose fee cap is under the base fee
+    pub underpriced_ratio: f64,
+    /// Share of the actors whose balance can't pay for all their messages
+    pub underfunded_ratio: f64,
+    /// Seed of the generator, the same seed always generates the same corpus
+    pub seed: u64,
+}
+
+impl Default for CorpusConfig {
+    fn default() -> Self {
+        Self {
+            actors: 100,
+            max_messages_per_actor: 20,
+            gas_limit: 1_000_000..=20_000_000,
+            gas_premium: 1..=10_000,
+            underpriced_ratio: 0.1,
+            underfunded_ratio: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+/// A sending actor, in the state of the simulated tipset.
+#[derive(Debug, Clone)]
+pub struct SimActor {
+    pub address: Address,
+    pub sequence: u64,
+    pub balance: TokenAmount,
+}
+
+/// Actors and their pending messages.
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    pub actors: Vec<SimActor>,
+    pub messages: Vec<SignedMessage>,
+}
+
+impl Corpus {
+    pub fn generate(config: &CorpusConfig) -> Self {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(config.seed);
+        let base_fee = TokenAmount::from_atto(SIM_BASE_FEE);
+        let mut actors = Vec::with_capacity(config.actors);
+        let mut messages = vec![];
+        for i in 0..config.actors {
+            let address = Address::new_id(1000 + i as u64);
+            let sequence = rng.gen_range(0..10);
+            let count = rng.gen_range(1..=config.max_messages_per_actor.max(1));
+            let mut required = TokenAmount::default();
+            for n in 0..count {
+                let gas_limit = rng.gen_range(config.gas_limit.clone());
+                let gas_premium = TokenAmount::from_atto(rng.gen_range(config.gas_premium.clone()));
+                let gas_fee_cap = if rng.gen_bool(config.underpriced_ratio) {
+                    TokenAmount::from_atto(rng.gen_range(0..SIM_BASE_FEE))
+                } else {
+                    &base_fee + &gas_premium
+                };
+                let message: Message = Message_v3 {
+                    to: Address::new_id(1).into(),
+                    from: address.into(),
+                    sequence: sequence + n,
+                    gas_limit,
+                    gas_fee_cap: gas_fee_cap.into(),
+                    gas_premium: gas_premium.into(),
+                    ..Message_v3::default()
+                }
+                .into();
+                let message =
+                    SignedMessage::new_unchecked(message, Signature::new_secp256k1(vec![]));
+                required += message.required_funds();
+                messages.push(message);
+            }
+            // Underfunded actors can only pay for a part of their messages.
+            let balance = if rng.gen_bool(config.underfunded_ratio) {
+                required.div_floor(rng.gen_range(2..=4))
+            } else {
+                required
+            };
+            actors.push(SimActor {
+                address,
+                sequence,
+                balance,
+            });
+        }
+        Self { actors, messages }
+    }
+
+    /// Total gas limit of the messages.
+    pub fn gas_limit(&self) -> u64 {
+        self.messages.iter().map(|m| m.gas_limit()).sum()
+    }
+
+    /// Returns `true` if all the messages are profitable, can be paid for, and fit in a block.
+    /// The selection must then include all of them.
+    fn is_uncongested(&self) -> bool {
+        let base_fee = TokenAmount::from_atto(SIM_BASE_FEE);
+        let required = self
+            .messages
+            .iter()
+            .into_grouping_map_by(|m| m.from())
+            .fold(TokenAmount::default(), |acc, _, m| acc + m.required_funds());
+        self.messages.len() <= BLOCK_MESSAGE_LIMIT
+            && self.gas_limit() <= BLOCK_GAS_LIMIT
+            && self
+                .messages
+                .iter()
+                .all(|m| get_gas_reward(m, &base_fee).is_positive())
+            && self.actors.iter().all(|actor| {
+                required
+                    .get(&actor.address)
+                    .is_none_or(|required| required <= &actor.balance)
+            })
+    }
+
+    /// Checks the invariants of a selection of the messages of the corpus:
+    /// - the selection fits in a block;
+    /// - the messages of each sender are selected in sequence order, from the sequence of the
+    ///   sender, and the sender can pay for them;
+    /// - when the block isn't congested, all the messages are selected.
+    pub fn check(&self, selected: &[SignedMessage]) -> anyhow::Result<SelectionStats> {
+        let base_fee = TokenAmount::from_atto(SIM_BASE_FEE);
+        ensure!(
+            selected.len() <= BLOCK_MESSAGE_LIMIT,
+            "selected {} messages, over the block message limit",
+            selected.len()
+        );
+        let gas_limit: u64 = selected.iter().map(|m| m.gas_limit()).sum();
+        ensure!(
+            gas_limit <= BLOCK_GAS_LIMIT,
+            "selected {gas_limit} gas, over the block gas limit"
+        );
+        ensure!(
+            selected.iter().map(|m| m.cid()).all_unique(),
+            "selected duplicate messages"
+        );
+        let mut by_sender = selected.iter().into_group_map_by(|m| m.from());
+        for actor in &self.actors {
+            let Some(msgs) = by_sender.remove(&actor.address) else {
+                continue;
+            };
+            let sequences = msgs.iter().map(|m| m.sequence()).sorted().collect_vec();
+            ensure!(
+                sequences
+                    .iter()
+                    .copied()
+                    .eq(actor.sequence..actor.sequence + sequences.len() as u64),
+                "selected sequences {sequences:?} of {} don't follow its sequence {}",
+                actor.address,
+                actor.sequence
+            );
+            let required = msgs
+                .iter()
+                .fold(TokenAmount::default(), |acc, m| acc + m.required_funds());
+            ensure!(
+                required <= actor.balance,
+                "selected messages of {} require {required}, over its balance {}",
+                actor.address,
+                actor.balance
+            );
+        }
+        ensure!(by_sender.is_empty(), "selected messages of unknown senders");
+        if self.is_uncongested() {
+            ensure!(
+                selected.len() == self.messages.len(),
+                "selected {} of the {} messages of an uncongested pool",
+                selected.len(),
+                self.messages.len()
+            );
+        }
+        Ok(SelectionStats {
+            messages: selected.len(),
+            gas_limit,
+            revenue: selected.iter().fold(TokenAmount::default(), |acc, m| {
+                acc + get_gas_reward(m, &base_fee)
+            }),
+        })
+    }
+}
+
+/// Summary of a selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionStats {
+    pub messages: usize,
+    pub gas_limit: u64,
+    /// Gas reward of the selected messages for the block miner
+    pub revenue: TokenAmount,
+}
+
+/// [`Provider`] serving the state of the actors of a [`Corpus`] on a single tipset.
+pub struct SimProvider {
+    tipset: Tipset,
+    actors: HashMap<Address, SimActor>,
+    publisher: Publisher<HeadChange>,
+}
+
+impl SimProvider {
+    pub fn new(actors: &[SimActor]) -> Self {
+        let header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(0),
+            ticket: Some(Ticket::new(VRFProof::new(vec![0; 32]))),
+            ..Default::default()
+        });
+        let (publisher, _) = broadcast::channel(1);
+        Self {
+            tipset: Tipset::from(header),
+            actors: actors
+                .iter()
+                .map(|actor| (actor.address, actor.clone()))
+                .collect(),
+            publisher,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for SimProvider {
+    fn subscribe_head_changes(&self) -> Subscriber<HeadChange> {
+        self.publisher.subscribe()
+    }
+
+    fn get_heaviest_tipset(&self) -> Tipset {
+        self.tipset.clone()
+    }
+
+    fn put_message(&self, _msg: &ChainMessage) -> Result<Cid, Error> {
+        Ok(Cid::default())
+    }
+
+    fn get_actor_after(&self, addr: &Address, _ts: &Tipset) -> Result<ActorState, Error> {
+        let actor = self
+            .actors
+            .get(addr)
+            .ok_or_else(|| Error::Other(format!("unknown actor {addr}")))?;
+        Ok(ActorState::new(
+            // Account Actor code (v10, calibnet)
+            Cid::try_from("bafk2bzacebhfuz3sv7duvk653544xsxhdn4lsmy7ol7k6gdgancyctvmd7lnq")
+                .map_err(|e| Error::Other(e.to_string()))?,
+            Cid::default(),
+            actor.balance.clone(),
+            actor.sequence,
+            None,
+        ))
+    }
+
+    fn messages_for_block(
+        &self,
+        _h: &CachingBlockHeader,
+    ) -> Result<(Vec<Message>, Vec<SignedMessage>), Error> {
+        Ok((vec![], vec![]))
+    }
+
+    fn load_tipset(&self, tsk: &TipsetKey) -> Result<Tipset, Error> {
+        if tsk == self.tipset.key() {
+            Ok(self.tipset.clone())
+        } else {
+            Err(Error::Other(format!("unknown tipset {tsk}")))
+        }
+    }
+
+    fn chain_compute_base_fee(&self, _ts: &Tipset) -> Result<TokenAmount, Error> {
+        Ok(TokenAmount::from_atto(SIM_BASE_FEE))
+    }
+}
+
+/// A message pool loaded with the pending messages of a [`Corpus`].
+pub struct Simulation {
+    pub corpus: Corpus,
+    pool: MessagePool<SimProvider>,
+}
+
+impl Simulation {
+    pub fn new(corpus: Corpus, services: &mut JoinSet<anyhow::Result<()>>) -> Result<Self, Error> {
+        let (network_sender, _) = flume::bounded(1);
+        let pool = MessagePool::new(
+            SimProvider::new(&corpus.actors),
+            network_sender,
+            Default::default(),
+            Arc::default(),
+            services,
+        )?;
+        // The messages are added as is, their signatures are dummies.
+        {
+            let mut pending = pool.pending.write();
+            for actor in &corpus.actors {
+                pending.insert(actor.address, MsgSet::new(actor.sequence));
+            }
+            for message in &corpus.messages {
+                if let Some(mset) = pending.get_mut(&message.from()) {
+                    mset.add_trusted(pool.api.as_ref(), message.clone())?;
+                }
+            }
+        }
+        Ok(Self { corpus, pool })
+    }
+
+    /// Selects the messages of a block with the ticket quality, on the simulated tipset.
+    pub fn select(&self, ticket_quality: f64) -> Result<Vec<SignedMessage>, Error> {
+        self.pool
+            .select_messages(&self.pool.api.get_heaviest_tipset(), ticket_quality)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate(config: &CorpusConfig, ticket_quality: f64) -> (Corpus, Vec<SignedMessage>) {
+        let mut services = JoinSet::new();
+        let simulation = Simulation::new(Corpus::generate(config), &mut services).unwrap();
+        let selected = simulation.select(ticket_quality).unwrap();
+        (simulation.corpus, selected)
+    }
+
+    #[tokio::test]
+    async fn selection_invariants() {
+        for seed in 0..5 {
+            for ticket_quality in [1.0, 0.5] {
+                let config = CorpusConfig {
+                    seed,
+                    ..Default::default()
+                };
+                let (corpus, selected) = simulate(&config, ticket_quality);
+                corpus.check(&selected).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn uncongested_selection_includes_all_messages() {
+        let config = CorpusConfig {
+            actors: 20,
+            max_messages_per_actor: 5,
+            underpriced_ratio: 0.0,
+            underfunded_ratio: 0.0,
+            ..Default::default()
+        };
+        let (corpus, selected) = simulate(&config, 1.0);
+        assert!(corpus.is_uncongested());
+        let stats = corpus.check(&selected).unwrap();
+        assert_eq!(stats.messages, corpus.messages.len());
+    }
+
+    #[tokio::test]
+    async fn deterministic_tie_breaking() {
+        // Messages of the same gas limit and premium tie, and don't all fit in the block.
+        let config = CorpusConfig {
+            actors: 300,
+            max_messages_per_actor: 1,
+            gas_limit: 50_000_000..=50_000_000,
+            gas_premium: 1_000..=1_000,
+            underpriced_ratio: 0.0,
+            underfunded_ratio: 0.0,
+            ..Default::default()
+        };
+        let (corpus, selected) = simulate(&config, 1.0);
+        assert!(corpus.gas_limit() > BLOCK_GAS_LIMIT);
+        corpus.check(&selected).unwrap();
+        // The pools iterate their pending messages in different orders.
+        for _ in 0..3 {
+            let (_, other) = simulate(&config, 1.0);
+            assert_eq!(other, selected);
+        }
+    }
+}
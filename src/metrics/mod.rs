@@ -12,6 +12,7 @@ use prometheus_client::{
     metrics::{
         counter::Counter,
         family::Family,
+        gauge::Gauge,
         histogram::{Histogram, exponential_buckets},
     },
 };
@@ -69,6 +70,45 @@ pub static RPC_METHOD_FAILURE: LazyLock<Family<RpcMethodLabel, Counter>> = LazyL
     metric
 });
 
+pub static RPC_METHOD_CALLS: LazyLock<Family<RpcMethodLabel, Counter>> = LazyLock::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY
+        .write()
+        .register("rpc_method_calls", "Number of RPC calls", metric.clone());
+    metric
+});
+
+pub static RPC_METHOD_ERRORS: LazyLock<Family<RpcMethodErrorLabel, Counter>> =
+    LazyLock::new(|| {
+        let metric = Family::default();
+        DEFAULT_REGISTRY.write().register(
+            "rpc_method_errors",
+            "Number of failed RPC calls by error code",
+            metric.clone(),
+        );
+        metric
+    });
+
+pub static RPC_METHOD_IN_FLIGHT: LazyLock<Family<RpcMethodLabel, Gauge>> = LazyLock::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_method_in_flight",
+        "Number of RPC calls in progress",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_METHOD_PROXIED: LazyLock<Family<RpcMethodLabel, Counter>> = LazyLock::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_method_proxied",
+        "Number of RPC calls proxied to the upstream node",
+        metric.clone(),
+    );
+    metric
+});
+
 pub static RPC_METHOD_TIME: LazyLock<Family<RpcMethodLabel, Histogram>> = LazyLock::new(|| {
     let metric = Family::<RpcMethodLabel, Histogram>::new_with_constructor(|| {
         // Histogram with 5 buckets starting from 0.1ms going to 1s, each bucket 10 times as big as the last.
@@ -84,6 +124,7 @@ pub static RPC_METHOD_TIME: LazyLock<Family<RpcMethodLabel, Histogram>> = LazyLo
 
 pub async fn init_prometheus<DB>(
     prometheus_listener: TcpListener,
+    tls: Option<tokio_rustls::TlsAcceptor>,
     db_directory: PathBuf,
     db: Arc<DB>,
     chain_config: Arc<ChainConfig>,
@@ -120,7 +161,14 @@ where
         .with_state(db);
 
     // Wait for server to exit
-    Ok(axum::serve(prometheus_listener, app.into_make_service()).await?)
+    match tls {
+        Some(acceptor) => Ok(axum::serve(
+            crate::utils::net::TlsListener::new(prometheus_listener, acceptor),
+            app.into_make_service(),
+        )
+        .await?),
+        None => Ok(axum::serve(prometheus_listener, app.into_make_service()).await?),
+    }
 }
 
 async fn collect_prometheus_metrics() -> impl IntoResponse {
@@ -169,6 +217,12 @@ pub struct RpcMethodLabel {
     pub method: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RpcMethodErrorLabel {
+    pub method: String,
+    pub code: i32,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet, derive_more::Constructor)]
 pub struct KindLabel {
     kind: &'static str,
@@ -181,6 +235,8 @@ pub mod values {
     pub const TIPSET: KindLabel = KindLabel::new("tipset");
     /// tipset cache in state manager
     pub const STATE_MANAGER_TIPSET: KindLabel = KindLabel::new("sm_tipset");
+    /// block cache in the database
+    pub const BLOCKSTORE: KindLabel = KindLabel::new("blockstore");
 }
 
 pub fn default_histogram() -> Histogram {
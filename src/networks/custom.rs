@@ -0,0 +1,246 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Chain configuration of private networks, loaded from a user-provided TOML or JSON file.
+
+use std::path::{Path, PathBuf};
+
+use ahash::HashMap;
+use anyhow::{Context as _, bail, ensure};
+use cid::Cid;
+use libp2p::Multiaddr;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use strum::IntoEnumIterator as _;
+
+use super::{ChainConfig, Height, HeightInfo, NetworkChain};
+use crate::eth::EthChainId;
+use crate::shim::{clock::ChainEpoch, version::NetworkVersion};
+
+/// Overrides of the built-in chain configuration. Unset fields keep the values of the built-in
+/// configuration of the selected network, which is the devnet one for private networks.
+#[derive(Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomChainConfig {
+    pub genesis_cid: Option<String>,
+    pub bootstrap_peers: Option<Vec<Multiaddr>>,
+    pub block_delay_secs: Option<u32>,
+    pub propagation_delay_secs: Option<u32>,
+    pub allowable_clock_drift_secs: Option<u64>,
+    pub max_height_drift_epochs: Option<u64>,
+    pub genesis_network: Option<NetworkVersion>,
+    pub eth_chain_id: Option<EthChainId>,
+    /// Genesis CAR file, relative to the directory of the chain configuration file. Used when
+    /// `client.genesis_file` is unset.
+    pub genesis_file: Option<PathBuf>,
+    /// Network upgrade schedule. Replaces the built-in schedule as a whole.
+    pub height_infos: Option<HashMap<Height, CustomHeightInfo>>,
+}
+
+#[serde_as]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomHeightInfo {
+    pub epoch: ChainEpoch,
+    /// Manifest CID of the actor bundle of the upgrade
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub bundle: Option<Cid>,
+}
+
+impl CustomChainConfig {
+    /// Loads the chain configuration from a JSON file if its extension is `.json`, or from a TOML
+    /// file otherwise.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read chain configuration {}", path.display()))?;
+        let config: anyhow::Result<Self> = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(Into::into)
+        } else {
+            toml::from_str(&content).map_err(Into::into)
+        };
+        let mut config =
+            config.with_context(|| format!("invalid chain configuration {}", path.display()))?;
+        if let Some(genesis_file) = &mut config.genesis_file
+            && let Some(dir) = path.parent()
+        {
+            *genesis_file = dir.join(&*genesis_file);
+        }
+        Ok(config)
+    }
+
+    /// Builds the chain configuration of `network_chain`, applying the overrides on top of its
+    /// built-in configuration, and validates the resulting upgrade schedule.
+    pub fn to_chain_config(&self, network_chain: &NetworkChain) -> anyhow::Result<ChainConfig> {
+        let mut config = ChainConfig::from_chain(network_chain);
+        if let Some(genesis_cid) = &self.genesis_cid {
+            config.genesis_cid = Some(genesis_cid.clone());
+        }
+        if let Some(bootstrap_peers) = &self.bootstrap_peers {
+            config.bootstrap_peers = bootstrap_peers.clone();
+        }
+        if let Some(block_delay_secs) = self.block_delay_secs {
+            config.block_delay_secs = block_delay_secs;
+        }
+        if let Some(propagation_delay_secs) = self.propagation_delay_secs {
+            config.propagation_delay_secs = propagation_delay_secs;
+        }
+        if let Some(allowable_clock_drift_secs) = self.allowable_clock_drift_secs {
+            config.allowable_clock_drift_secs = allowable_clock_drift_secs;
+        }
+        if let Some(max_height_drift_epochs) = self.max_height_drift_epochs {
+            config.max_height_drift_epochs = max_height_drift_epochs;
+        }
+        if let Some(genesis_network) = self.genesis_network {
+            config.genesis_network = genesis_network;
+        }
+        if let Some(eth_chain_id) = self.eth_chain_id {
+            config.eth_chain_id = eth_chain_id;
+        }
+        if let Some(height_infos) = &self.height_infos {
+            config.height_infos = height_infos
+                .iter()
+                .map(|(height, CustomHeightInfo { epoch, bundle })| {
+                    (
+                        *height,
+                        HeightInfo {
+                            epoch: *epoch,
+                            bundle: *bundle,
+                        },
+                    )
+                })
+                .collect();
+        }
+        validate_height_infos(&config.height_infos)?;
+        Ok(config)
+    }
+}
+
+/// Validates a network upgrade schedule. Upgrades scheduled after genesis must be in upgrade
+/// order, and at least one upgrade must provide an actor bundle.
+pub fn validate_height_infos(height_infos: &HashMap<Height, HeightInfo>) -> anyhow::Result<()> {
+    let mut previous: Option<(Height, ChainEpoch)> = None;
+    for height in Height::iter() {
+        let Some(info) = height_infos.get(&height) else {
+            continue;
+        };
+        // Upgrades at negative epochs are already active at genesis.
+        if info.epoch < 0 {
+            continue;
+        }
+        if let Some((previous_height, previous_epoch)) = previous
+            && info.epoch < previous_epoch
+        {
+            bail!(
+                "the {height} upgrade (epoch {}) is scheduled before the {previous_height} upgrade (epoch {previous_epoch})",
+                info.epoch
+            );
+        }
+        previous = Some((height, info.epoch));
+    }
+    ensure!(
+        height_infos.values().any(|info| info.bundle.is_some()),
+        "no network upgrade provides an actor bundle"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_chain_config_overrides() {
+        let custom: CustomChainConfig = toml::from_str(
+            r#"
+            block_delay_secs = 4
+            max_height_drift_epochs = 30
+            eth_chain_id = 31415926
+            bootstrap_peers = ["/ip4/10.0.0.1/tcp/1347"]
+
+            [height_infos.Breeze]
+            epoch = -1
+
+            [height_infos.Watermelon]
+            epoch = 10
+            bundle = "bafy2bzacedbedgynklc4dgpyxippkxmba2mgtw7ecntoneclsvvl4klqwuyyy"
+            "#,
+        )
+        .unwrap();
+        let network = NetworkChain::Devnet("mynet".into());
+        let config = custom.to_chain_config(&network).unwrap();
+        assert_eq!(config.network, network);
+        assert_eq!(config.block_delay_secs, 4);
+        assert_eq!(config.max_height_drift_epochs, 30);
+        assert_eq!(config.eth_chain_id, 31415926);
+        assert_eq!(config.bootstrap_peers.len(), 1);
+        assert_eq!(config.height_infos.len(), 2);
+        assert!(config.height_infos[&Height::Watermelon].bundle.is_some());
+        // Unset fields keep the devnet values.
+        assert_eq!(
+            config.propagation_delay_secs,
+            ChainConfig::devnet().propagation_delay_secs
+        );
+        assert_eq!(
+            config.allowable_clock_drift_secs,
+            ChainConfig::devnet().allowable_clock_drift_secs
+        );
+
+        assert!(toml::from_str::<CustomChainConfig>("unknown_field = 1").is_err());
+    }
+
+    #[test]
+    fn custom_chain_config_from_json_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("mynet.json");
+        std::fs::write(
+            &path,
+            r#"{ "genesis_file": "genesis.car", "height_infos": { "Watermelon": { "epoch": -1, "bundle": "bafy2bzacedbedgynklc4dgpyxippkxmba2mgtw7ecntoneclsvvl4klqwuyyy" } } }"#,
+        )
+        .unwrap();
+        let custom = CustomChainConfig::from_file(&path).unwrap();
+        assert_eq!(
+            custom.genesis_file,
+            Some(temp_dir.path().join("genesis.car"))
+        );
+        assert!(
+            custom
+                .to_chain_config(&NetworkChain::Devnet("mynet".into()))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_schedule() {
+        let bundle = Some(
+            Cid::try_from("bafy2bzacedbedgynklc4dgpyxippkxmba2mgtw7ecntoneclsvvl4klqwuyyy")
+                .unwrap(),
+        );
+        let schedule = |infos: &[(Height, ChainEpoch, Option<Cid>)]| {
+            infos
+                .iter()
+                .map(|&(height, epoch, bundle)| (height, HeightInfo { epoch, bundle }))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert!(validate_height_infos(&ChainConfig::devnet().height_infos).is_ok());
+        assert!(
+            validate_height_infos(&schedule(&[
+                (Height::Breeze, -1, None),
+                (Height::Watermelon, 10, bundle),
+                (Height::Dragon, 10, bundle),
+            ]))
+            .is_ok()
+        );
+        // Not monotonic
+        assert!(
+            validate_height_infos(&schedule(&[
+                (Height::Watermelon, 20, bundle),
+                (Height::Dragon, 10, bundle),
+            ]))
+            .is_err()
+        );
+        // No actor bundle
+        assert!(validate_height_infos(&schedule(&[(Height::Watermelon, 10, None)])).is_err());
+    }
+}
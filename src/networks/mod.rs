@@ -17,10 +17,10 @@ use strum_macros::EnumIter;
 use tracing::warn;
 
 use crate::beacon::{BeaconPoint, BeaconSchedule, DrandBeacon, DrandConfig};
-use crate::db::SettingsStore;
+use crate::db::{SettingsStore, WriteBufferConfig};
 use crate::eth::EthChainId;
 use crate::shim::{
-    clock::{ChainEpoch, EPOCH_DURATION_SECONDS, EPOCHS_IN_DAY},
+    clock::{ALLOWABLE_CLOCK_DRIFT, ChainEpoch, EPOCH_DURATION_SECONDS, EPOCHS_IN_DAY},
     econ::TokenAmount,
     machine::BuiltinActorManifest,
     runtime::Policy,
@@ -38,6 +38,9 @@ pub use actors_bundle::{
     generate_actor_bundle, get_actor_bundles_metadata,
 };
 
+mod custom;
+pub use custom::{CustomChainConfig, CustomHeightInfo};
+
 mod drand;
 
 pub mod network_name;
@@ -54,6 +57,9 @@ pub const NEWEST_NETWORK_VERSION: NetworkVersion = NetworkVersion::V25;
 
 const ENV_FOREST_BLOCK_DELAY_SECS: &str = "FOREST_BLOCK_DELAY_SECS";
 const ENV_FOREST_PROPAGATION_DELAY_SECS: &str = "FOREST_PROPAGATION_DELAY_SECS";
+
+/// Default of [`ChainConfig::max_height_drift_epochs`].
+const MAX_HEIGHT_DRIFT_EPOCHS: u64 = 5;
 const ENV_PLEDGE_RULE_RAMP: &str = "FOREST_PLEDGE_RULE_RAMP";
 
 static INITIAL_FIL_RESERVED: LazyLock<TokenAmount> =
@@ -265,6 +271,11 @@ pub struct ChainConfig {
     pub bootstrap_peers: Vec<Multiaddr>,
     pub block_delay_secs: u32,
     pub propagation_delay_secs: u32,
+    /// How many seconds the timestamps of blocks may be ahead of the local clock.
+    pub allowable_clock_drift_secs: u64,
+    /// How many epochs tipsets may be ahead of the epoch expected from the local clock and the
+    /// block delay.
+    pub max_height_drift_epochs: u64,
     pub genesis_network: NetworkVersion,
     pub height_infos: HashMap<Height, HeightInfo>,
     #[cfg_attr(test, arbitrary(gen(|_g| Policy::default())))]
@@ -283,6 +294,8 @@ pub struct ChainConfig {
     pub enable_indexer: bool,
     pub enable_receipt_event_caching: bool,
     pub default_max_fee: TokenAmount,
+    /// Write buffer used by state migrations
+    pub write_buffer: WriteBufferConfig,
 }
 
 impl ChainConfig {
@@ -297,6 +310,8 @@ impl ChainConfig {
                 EPOCH_DURATION_SECONDS as u32,
             ),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 10),
+            allowable_clock_drift_secs: ALLOWABLE_CLOCK_DRIFT,
+            max_height_drift_epochs: MAX_HEIGHT_DRIFT_EPOCHS,
             genesis_network: GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_mainnet_policy!(v13).into(),
@@ -317,6 +332,7 @@ impl ChainConfig {
             enable_indexer: false,
             enable_receipt_event_caching: true,
             default_max_fee: TokenAmount::zero(),
+            write_buffer: WriteBufferConfig::default(),
         }
     }
 
@@ -331,6 +347,8 @@ impl ChainConfig {
                 EPOCH_DURATION_SECONDS as u32,
             ),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 10),
+            allowable_clock_drift_secs: ALLOWABLE_CLOCK_DRIFT,
+            max_height_drift_epochs: MAX_HEIGHT_DRIFT_EPOCHS,
             genesis_network: GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_calibnet_policy!(v13).into(),
@@ -354,6 +372,7 @@ impl ChainConfig {
             enable_indexer: false,
             enable_receipt_event_caching: true,
             default_max_fee: TokenAmount::zero(),
+            write_buffer: WriteBufferConfig::default(),
         }
     }
 
@@ -365,6 +384,8 @@ impl ChainConfig {
             bootstrap_peers: Vec::new(),
             block_delay_secs: env_or_default(ENV_FOREST_BLOCK_DELAY_SECS, 4),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 1),
+            allowable_clock_drift_secs: ALLOWABLE_CLOCK_DRIFT,
+            max_height_drift_epochs: MAX_HEIGHT_DRIFT_EPOCHS,
             genesis_network: *GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_devnet_policy!(v13).into(),
@@ -381,6 +402,7 @@ impl ChainConfig {
             enable_indexer: false,
             enable_receipt_event_caching: true,
             default_max_fee: TokenAmount::zero(),
+            write_buffer: WriteBufferConfig::default(),
         }
     }
 
@@ -395,6 +417,8 @@ impl ChainConfig {
                 EPOCH_DURATION_SECONDS as u32,
             ),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 6),
+            allowable_clock_drift_secs: ALLOWABLE_CLOCK_DRIFT,
+            max_height_drift_epochs: MAX_HEIGHT_DRIFT_EPOCHS,
             genesis_network: GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_butterfly_policy!(v13).into(),
@@ -414,6 +438,7 @@ impl ChainConfig {
             enable_indexer: false,
             enable_receipt_event_caching: true,
             default_max_fee: TokenAmount::zero(),
+            write_buffer: WriteBufferConfig::default(),
         }
     }
 
@@ -1,9 +1,9 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::auth::{JWT_IDENTIFIER, verify_token};
+use crate::auth::verify_token_with_keystore;
 use crate::key_management::KeyStore;
-use crate::rpc::{CANCEL_METHOD_NAME, Permission, RpcMethod as _, chain};
+use crate::rpc::{CANCEL_METHOD_NAME, Permission, RpcMethod as _, chain, eth};
 use ahash::{HashMap, HashMapExt as _};
 use futures::future::Either;
 use http::{
@@ -35,7 +35,16 @@ static METHOD_NAME2REQUIRED_PERMISSION: LazyLock<HashMap<&str, Permission>> = La
     }
     super::for_each_rpc_method!(insert);
 
+    // In the signer mode, the accounts of the wallet are only listed to admins.
+    if eth::signer::is_enabled() {
+        access.insert(eth::EthAccounts::NAME, Permission::Admin);
+        if let Some(alias) = eth::EthAccounts::NAME_ALIAS {
+            access.insert(alias, Permission::Admin);
+        }
+    }
+
     access.insert(chain::CHAIN_NOTIFY, Permission::Read);
+    access.insert(chain::ACTOR_CODE_CHANGES, Permission::Read);
     access.insert(CANCEL_METHOD_NAME, Permission::Read);
 
     access
@@ -152,15 +161,39 @@ where
 
 /// Verify JWT Token and return the token's permissions.
 fn auth_verify(token: &str, keystore: &RwLock<KeyStore>) -> anyhow::Result<Vec<String>> {
-    let key_info = keystore.read().get(JWT_IDENTIFIER)?;
-    Ok(verify_token(token, key_info.private_key())?)
+    verify_token_with_keystore(token, &keystore.read())
 }
 
-fn check_permissions(
+pub(super) fn check_permissions(
     keystore: &RwLock<KeyStore>,
     auth_header: Option<&HeaderValue>,
     method: &str,
 ) -> Result<bool, ErrorCode> {
+    let claims = caller_claims(keystore, auth_header)?;
+    match required_permission(method) {
+        Some(required_by_method) => Ok(is_allowed(required_by_method, &claims)),
+        None => Err(ErrorCode::MethodNotFound),
+    }
+}
+
+/// Checks that the caller is granted the permission, regardless of the method called.
+pub(super) fn check_permission(
+    keystore: &RwLock<KeyStore>,
+    auth_header: Option<&HeaderValue>,
+    required: Permission,
+) -> Result<bool, ErrorCode> {
+    Ok(is_allowed(required, &caller_claims(keystore, auth_header)?))
+}
+
+/// Returns the permission required by a method served locally.
+pub(super) fn required_permission(method: &str) -> Option<Permission> {
+    METHOD_NAME2REQUIRED_PERMISSION.get(method).copied()
+}
+
+fn caller_claims(
+    keystore: &RwLock<KeyStore>,
+    auth_header: Option<&HeaderValue>,
+) -> Result<Vec<String>, ErrorCode> {
     let claims = match auth_header {
         Some(token) => {
             let token = token
@@ -176,11 +209,7 @@ fn check_permissions(
         None => vec!["read".to_owned()],
     };
     debug!("Decoded JWT Claims: {}", claims.join(","));
-
-    match METHOD_NAME2REQUIRED_PERMISSION.get(&method) {
-        Some(required_by_method) => Ok(is_allowed(*required_by_method, &claims)),
-        None => Err(ErrorCode::MethodNotFound),
-    }
+    Ok(claims)
 }
 
 #[cfg(test)]
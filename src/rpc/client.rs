@@ -6,25 +6,29 @@
 //! - Support [`rpc::Request`](crate::rpc::Request).
 //! - Support different
 //!   - endpoint paths (`v0`, `v1`).
-//!   - communication protocols (`ws`, `http`).
+//!   - communication protocols (`ws`, `http`), over TCP or unix sockets.
 //! - Support per-request timeouts.
 
 use std::env;
 use std::fmt::{self, Debug};
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 use std::time::Duration;
 
 use anyhow::bail;
 use futures::future::Either;
+use futures::{Stream, StreamExt as _};
 use http::{HeaderMap, HeaderValue, header};
 use jsonrpsee::core::ClientError;
-use jsonrpsee::core::client::ClientT as _;
+use jsonrpsee::core::client::{ClientT as _, SubscriptionClientT as _};
 use jsonrpsee::core::params::{ArrayParams, ObjectParams};
 use jsonrpsee::core::traits::ToRpcParams;
 use serde::de::DeserializeOwned;
+use tokio_util::compat::TokioAsyncReadCompatExt as _;
 use tracing::{Instrument, Level, debug};
 use url::Url;
 
+use super::channel::{ChannelId, NOTIF_METHOD_NAME};
 use super::{ApiPaths, MAX_REQUEST_BODY_SIZE, MAX_RESPONSE_BODY_SIZE, Request};
 
 /// A JSON-RPC client that can dispatch either a [`crate::rpc::Request`] to a single URL.
@@ -32,6 +36,8 @@ pub struct Client {
     /// SHOULD end in a slash, due to our use of [`Url::join`].
     base_url: Url,
     token: Option<String>,
+    /// Unix socket to dial instead of the host of `base_url`.
+    socket: Option<PathBuf>,
     // just having these versions inline is easier than using a map
     v0: tokio::sync::OnceCell<UrlClient>,
     v1: tokio::sync::OnceCell<UrlClient>,
@@ -45,11 +51,22 @@ impl Client {
     pub fn default_or_from_env(token: Option<&str>) -> anyhow::Result<Self> {
         static DEFAULT: LazyLock<Url> = LazyLock::new(|| "http://127.0.0.1:2345/".parse().unwrap());
 
+        let mut socket = None;
         let mut base_url = match env::var("FULLNODE_API_INFO") {
-            Ok(it) => {
-                let crate::utils::UrlFromMultiAddr(url) = it.parse()?;
-                url
-            }
+            Ok(it) => match unix_api_info(&it) {
+                Some((password, path)) => {
+                    socket = Some(path);
+                    let mut url = DEFAULT.clone();
+                    if url.set_password(password).is_err() {
+                        bail!("unsupported password")
+                    }
+                    url
+                }
+                None => {
+                    let crate::utils::UrlFromMultiAddr(url) = it.parse()?;
+                    url
+                }
+            },
             Err(env::VarError::NotPresent) => DEFAULT.clone(),
             Err(e @ env::VarError::NotUnicode(_)) => bail!(e),
         };
@@ -72,7 +89,11 @@ impl Client {
                 }
             }
         }
-        Ok(Self::from_url(base_url))
+        let client = Self::from_url(base_url);
+        Ok(match socket {
+            Some(socket) => client.with_socket(socket),
+            None => client,
+        })
     }
     pub fn from_url(mut base_url: Url) -> Self {
         let token = base_url.password().map(Into::into);
@@ -80,14 +101,25 @@ impl Client {
         Self {
             token,
             base_url,
+            socket: None,
             v0: Default::default(),
             v1: Default::default(),
             v2: Default::default(),
         }
     }
+    /// Dial the unix socket instead of the host of the URL. Requests are sent over WebSocket.
+    pub fn with_socket(self, socket: PathBuf) -> Self {
+        Self {
+            socket: Some(socket),
+            ..self
+        }
+    }
     pub fn base_url(&self) -> &Url {
         &self.base_url
     }
+    pub fn socket(&self) -> Option<&Path> {
+        self.socket.as_deref()
+    }
     pub async fn call<T: crate::lotus_json::HasLotusJson + std::fmt::Debug>(
         &self,
         req: Request<T>,
@@ -150,6 +182,40 @@ impl Client {
         };
         work.instrument(span.or_current()).await
     }
+    /// Opens a channel of a subscription method, e.g. `Filecoin.ChainNotify`, and returns the
+    /// stream of the values sent on it. Channels are opened over WebSocket, on a connection of
+    /// their own that is closed when the stream is dropped.
+    pub async fn subscribe_channel<T: crate::lotus_json::HasLotusJson>(
+        &self,
+        method_name: &str,
+        path: ApiPaths,
+    ) -> Result<impl Stream<Item = Result<T, ClientError>> + use<T>, ClientError> {
+        let mut url = self
+            .base_url
+            .join(path.path())
+            .map_err(|it| ClientError::Custom(format!("creating url for endpoint failed: {it}")))?;
+        let scheme = match url.scheme() {
+            "https" | "wss" => "wss",
+            _ => "ws",
+        };
+        let _ = url.set_scheme(scheme);
+        let client = UrlClient::new(url, self.token.clone(), self.socket.as_deref()).await?;
+        // Values are sent as notifications, which are dropped unless subscribed to before the
+        // channel is opened.
+        let values = client
+            .subscribe_to_method::<(ChannelId, T::LotusJson)>(NOTIF_METHOD_NAME)
+            .await?;
+        let channel_id: ChannelId = client.request(method_name, ArrayParams::new()).await?;
+        Ok(values.filter_map(move |value| {
+            // The stream owns the connection.
+            let _ = &client;
+            std::future::ready(match value {
+                Ok((id, value)) if id == channel_id => Some(Ok(T::from_lotus_json(value))),
+                Ok(_) => None,
+                Err(e) => Some(Err(ClientError::ParseError(e))),
+            })
+        }))
+    }
     async fn get_or_init_client(&self, path: ApiPaths) -> Result<&UrlClient, ClientError> {
         match path {
             ApiPaths::V0 => &self.v0,
@@ -160,12 +226,38 @@ impl Client {
             let url = self.base_url.join(path.path()).map_err(|it| {
                 ClientError::Custom(format!("creating url for endpoint failed: {it}"))
             })?;
-            UrlClient::new(url, self.token.clone()).await
+            UrlClient::new(url, self.token.clone(), self.socket.as_deref()).await
         })
         .await
     }
 }
 
+/// `"hunter2:/unix/run/forest/rpc.sock" -> (Some("hunter2"), "/run/forest/rpc.sock")`
+///
+/// Like in Lotus, the path of a `unix` multiaddr is the rest of the multiaddr, which
+/// [`multiaddr::Multiaddr`] doesn't support.
+fn unix_api_info(api_info: &str) -> Option<(Option<&str>, PathBuf)> {
+    let (password, addr) = match api_info.split_once(':') {
+        Some((first, rest)) => (Some(first), rest),
+        None => (None, api_info),
+    };
+    let path = addr.strip_prefix("/unix/")?;
+    Some((password, Path::new("/").join(path)))
+}
+
+#[test]
+fn test_unix_api_info() {
+    assert_eq!(
+        unix_api_info("hunter2:/unix/run/forest/rpc.sock"),
+        Some((Some("hunter2"), PathBuf::from("/run/forest/rpc.sock")))
+    );
+    assert_eq!(
+        unix_api_info("/unix/tmp/rpc.sock"),
+        Some((None, PathBuf::from("/tmp/rpc.sock")))
+    );
+    assert_eq!(unix_api_info("hunter2:/ip4/127.0.0.1/tcp/2345/http"), None);
+}
+
 fn trace_params(params: impl jsonrpsee::core::traits::ToRpcParams) {
     if tracing::enabled!(Level::TRACE) {
         match params.to_rpc_params() {
@@ -192,7 +284,11 @@ impl Debug for UrlClient {
 }
 
 impl UrlClient {
-    async fn new(url: Url, token: impl Into<Option<String>>) -> Result<Self, ClientError> {
+    async fn new(
+        url: Url,
+        token: impl Into<Option<String>>,
+        socket: Option<&Path>,
+    ) -> Result<Self, ClientError> {
         const ONE_DAY: Duration = Duration::from_secs(24 * 3600); // we handle timeouts ourselves.
         let headers = match token.into() {
             Some(token) => HeaderMap::from_iter([(
@@ -208,6 +304,25 @@ impl UrlClient {
             )]),
             None => HeaderMap::new(),
         };
+        if let Some(socket) = socket {
+            let stream = tokio::net::UnixStream::connect(socket).await.map_err(|e| {
+                ClientError::Custom(format!("failed to dial {}: {e}", socket.display()))
+            })?;
+            // jsonrpsee's HTTP client can't be given a stream, so both schemes are dialed over
+            // WebSocket.
+            let mut ws_url = url.clone();
+            let _ = ws_url.set_scheme("ws");
+            let inner = UrlClientInner::Ws(
+                jsonrpsee::ws_client::WsClientBuilder::new()
+                    .set_headers(headers)
+                    .max_request_size(MAX_REQUEST_BODY_SIZE)
+                    .max_response_size(MAX_RESPONSE_BODY_SIZE)
+                    .request_timeout(ONE_DAY)
+                    .build_with_stream(ws_url, stream.compat())
+                    .await?,
+            );
+            return Ok(Self { url, inner });
+        }
         let inner = match url.scheme() {
             "ws" | "wss" => UrlClientInner::Ws(
                 jsonrpsee::ws_client::WsClientBuilder::new()
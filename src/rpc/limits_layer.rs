@@ -0,0 +1,425 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Middleware layer protecting the node from pathological queries. The requests and the responses
+//! of the methods are limited in size, per class of methods, and the calls slower than a threshold
+//! are logged. The parameters of the slow calls are redacted, unless their methods are listed as
+//! safe to log, as they may hold sensitive data, e.g. signed messages or private keys.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::Either;
+use jsonrpsee::MethodResponse;
+use jsonrpsee::core::middleware::{Batch, Notification};
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::types::error::{
+    OVERSIZED_RESPONSE_CODE, OVERSIZED_RESPONSE_MSG, reject_too_big_request,
+};
+use jsonrpsee::types::{ErrorObject, Id};
+use serde::{Deserialize, Serialize};
+use tower::Layer;
+
+use super::{MAX_REQUEST_BODY_SIZE, MAX_RESPONSE_BODY_SIZE, batch_one_by_one};
+
+/// Maximum length of the summary of the parameters of the slow calls in the logs.
+const PARAMS_SUMMARY_LEN: usize = 256;
+
+/// Size limits of a class of RPC methods. Unset limits fall back to the default ones.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct RpcMethodLimits {
+    /// Maximum size of the request body, in bytes
+    pub max_request_body_bytes: Option<u32>,
+    /// Maximum size of the response body, in bytes
+    pub max_response_body_bytes: Option<u32>,
+}
+
+/// RPC request and response size limits, and slow-call logging
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct RpcLimitsConfig {
+    /// Maximum size of a request body, in bytes
+    pub max_request_body_bytes: u32,
+    /// Maximum size of a response body, in bytes
+    pub max_response_body_bytes: u32,
+    /// Limits of the classes of methods, keyed by method name prefix, e.g. `Filecoin.State` or
+    /// `Eth`. The longest matching prefix applies.
+    pub method_classes: BTreeMap<String, RpcMethodLimits>,
+    /// Calls slower than this, in milliseconds, are logged, use 0 to disable
+    pub slow_call_threshold_ms: u64,
+    /// Method name prefixes of the slow calls logged with a summary of their parameters, the
+    /// parameters of the other calls are redacted
+    pub slow_call_logged_params: Vec<String>,
+}
+
+impl Default for RpcLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_request_body_bytes: MAX_REQUEST_BODY_SIZE,
+            max_response_body_bytes: MAX_RESPONSE_BODY_SIZE,
+            method_classes: BTreeMap::new(),
+            slow_call_threshold_ms: 0,
+            slow_call_logged_params: vec![],
+        }
+    }
+}
+
+impl RpcLimitsConfig {
+    /// Returns the limits of the class of the method, with the unset ones defaulted.
+    fn limits(&self, method_name: &str) -> (u32, u32) {
+        let class = self
+            .method_classes
+            .iter()
+            .filter(|(prefix, _)| method_name.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, limits)| limits);
+        (
+            class
+                .and_then(|limits| limits.max_request_body_bytes)
+                .unwrap_or(self.max_request_body_bytes),
+            class
+                .and_then(|limits| limits.max_response_body_bytes)
+                .unwrap_or(self.max_response_body_bytes),
+        )
+    }
+
+    /// Largest request body limit of all the classes, enforced by the server on the whole body.
+    pub fn server_max_request_body_bytes(&self) -> u32 {
+        self.method_classes
+            .values()
+            .filter_map(|limits| limits.max_request_body_bytes)
+            .fold(self.max_request_body_bytes, u32::max)
+    }
+
+    /// Largest response body limit of all the classes, enforced by the server on the whole body.
+    pub fn server_max_response_body_bytes(&self) -> u32 {
+        self.method_classes
+            .values()
+            .filter_map(|limits| limits.max_response_body_bytes)
+            .fold(self.max_response_body_bytes, u32::max)
+    }
+
+    fn slow_call_threshold(&self) -> Option<Duration> {
+        (self.slow_call_threshold_ms > 0)
+            .then(|| Duration::from_millis(self.slow_call_threshold_ms))
+    }
+
+    /// Returns the parameters of a call to keep for the slow-call logs, if they may be logged.
+    fn loggable_params(&self, method_name: &str, params: Option<&str>) -> Option<String> {
+        self.slow_call_threshold()?;
+        self.slow_call_logged_params
+            .iter()
+            .any(|prefix| method_name.starts_with(prefix.as_str()))
+            .then(|| params.unwrap_or("[]").to_owned())
+    }
+}
+
+#[derive(Clone, Default)]
+pub(super) struct LimitsLayer {
+    config: Arc<RpcLimitsConfig>,
+}
+
+impl LimitsLayer {
+    pub fn new(config: Arc<RpcLimitsConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for LimitsLayer {
+    type Service = Limits<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Limits {
+            service,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Limits<S> {
+    service: S,
+    config: Arc<RpcLimitsConfig>,
+}
+
+impl<S> Limits<S> {
+    fn check_request(
+        &self,
+        method_name: &str,
+        params: Option<&str>,
+    ) -> Result<(), ErrorObject<'static>> {
+        let (max_request_body_bytes, _) = self.config.limits(method_name);
+        if params.map_or(0, str::len) > max_request_body_bytes as usize {
+            Err(reject_too_big_request(max_request_body_bytes))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn limit<F>(
+        config: Arc<RpcLimitsConfig>,
+        id: Id<'static>,
+        method_name: String,
+        params: Option<String>,
+        future: F,
+    ) -> MethodResponse
+    where
+        F: Future<Output = MethodResponse>,
+    {
+        let start_time = Instant::now();
+        let resp = future.await;
+        let elapsed = start_time.elapsed();
+        if let Some(threshold) = config.slow_call_threshold()
+            && elapsed > threshold
+        {
+            tracing::warn!(
+                "Slow RPC call: {method_name} took {}. Params: {}",
+                humantime::format_duration(elapsed),
+                params
+                    .as_deref()
+                    .map_or_else(|| "<redacted>".into(), summarize_params)
+            );
+        }
+        let (_, max_response_body_bytes) = config.limits(&method_name);
+        let size = resp.as_json().get().len();
+        // Subscription responses are streamed, only their results are limited.
+        if size > max_response_body_bytes as usize && !resp.is_subscription() {
+            tracing::debug!(
+                "RPC response of {method_name} is too big: {size} bytes, limit {max_response_body_bytes}"
+            );
+            return MethodResponse::error(
+                id,
+                ErrorObject::owned(
+                    OVERSIZED_RESPONSE_CODE,
+                    OVERSIZED_RESPONSE_MSG,
+                    Some(format!(
+                        "Exceeded max limit of {max_response_body_bytes} bytes"
+                    )),
+                ),
+            );
+        }
+        resp
+    }
+}
+
+impl<S> RpcServiceT for Limits<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync
+        + Clone
+        + 'static,
+{
+    type MethodResponse = S::MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(
+        &self,
+        req: jsonrpsee::types::Request<'a>,
+    ) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+        match self.check_request(req.method_name(), req.params().as_str()) {
+            Ok(()) => Either::Left(Self::limit(
+                self.config.clone(),
+                req.id().into_owned(),
+                req.method_name().to_owned(),
+                // Only kept for the slow-call logs.
+                self.config
+                    .loggable_params(req.method_name(), req.params().as_str()),
+                self.service.call(req),
+            )),
+            Err(e) => Either::Right(async move { MethodResponse::error(req.id(), e) }),
+        }
+    }
+
+    fn notification<'a>(
+        &self,
+        n: Notification<'a>,
+    ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+        let params = n.params().as_ref().map(|params| params.get());
+        match self.check_request(n.method_name(), params) {
+            Ok(()) => Either::Left(Self::limit(
+                self.config.clone(),
+                Id::Null,
+                n.method_name().to_owned(),
+                self.config.loggable_params(n.method_name(), params),
+                self.service.notification(n),
+            )),
+            Err(e) => Either::Right(async move { MethodResponse::error(Id::Null, e) }),
+        }
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+        // The calls of the batch are limited and timed one by one, the batch response as a whole
+        // by the largest limit of the classes.
+        batch_one_by_one(
+            self,
+            batch,
+            self.config.server_max_response_body_bytes() as usize,
+        )
+    }
+}
+
+/// Truncates the parameters to a summary short enough for the logs.
+fn summarize_params(params: &str) -> String {
+    if params.len() <= PARAMS_SUMMARY_LEN {
+        return params.to_owned();
+    }
+    let end = (0..=PARAMS_SUMMARY_LEN)
+        .rev()
+        .find(|&i| params.is_char_boundary(i))
+        .unwrap_or_default();
+    format!(
+        "{}... ({} bytes)",
+        params.get(..end).unwrap_or_default(),
+        params.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_class_limits() {
+        let config = RpcLimitsConfig {
+            max_request_body_bytes: 100,
+            max_response_body_bytes: 200,
+            method_classes: BTreeMap::from_iter([
+                (
+                    "Filecoin.".into(),
+                    RpcMethodLimits {
+                        max_request_body_bytes: Some(10),
+                        max_response_body_bytes: None,
+                    },
+                ),
+                (
+                    "Filecoin.State".into(),
+                    RpcMethodLimits {
+                        max_request_body_bytes: None,
+                        max_response_body_bytes: Some(1000),
+                    },
+                ),
+            ]),
+            slow_call_threshold_ms: 0,
+            slow_call_logged_params: vec![],
+        };
+        assert_eq!(config.limits("Eth.BlockNumber"), (100, 200));
+        assert_eq!(config.limits("Filecoin.ChainHead"), (10, 200));
+        assert_eq!(config.limits("Filecoin.StateCall"), (100, 1000));
+        assert_eq!(config.server_max_request_body_bytes(), 100);
+        assert_eq!(config.server_max_response_body_bytes(), 1000);
+        assert_eq!(config.slow_call_threshold(), None);
+    }
+
+    #[test]
+    fn slow_call_params_are_redacted() {
+        let config = RpcLimitsConfig {
+            slow_call_threshold_ms: 1000,
+            slow_call_logged_params: vec!["Filecoin.Chain".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.loggable_params("Filecoin.ChainGetTipSet", Some("[1]")),
+            Some("[1]".into())
+        );
+        assert_eq!(
+            config.loggable_params("Filecoin.ChainHead", None),
+            Some("[]".into())
+        );
+        assert_eq!(
+            config.loggable_params("Filecoin.WalletImport", Some("[1]")),
+            None
+        );
+        // Nothing is kept if the slow calls are not logged.
+        let config = RpcLimitsConfig {
+            slow_call_threshold_ms: 0,
+            ..config
+        };
+        assert_eq!(
+            config.loggable_params("Filecoin.ChainGetTipSet", Some("[1]")),
+            None
+        );
+    }
+
+    #[test]
+    fn params_summary() {
+        assert_eq!(summarize_params("[1,2]"), "[1,2]");
+        let params = format!("[\"{}\"]", "é".repeat(PARAMS_SUMMARY_LEN));
+        let summary = summarize_params(&params);
+        assert!(summary.len() < params.len());
+        assert!(summary.ends_with(&format!("... ({} bytes)", params.len())));
+    }
+
+    /// Service answering every call with a 300 bytes long string
+    #[derive(Clone)]
+    struct Verbose;
+
+    impl RpcServiceT for Verbose {
+        type MethodResponse = MethodResponse;
+        type NotificationResponse = MethodResponse;
+        type BatchResponse = MethodResponse;
+
+        fn call<'a>(
+            &self,
+            req: jsonrpsee::types::Request<'a>,
+        ) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+            std::future::ready(MethodResponse::response(
+                req.id(),
+                jsonrpsee::server::ResponsePayload::success("a".repeat(300)),
+                usize::MAX,
+            ))
+        }
+
+        fn batch<'a>(&self, _: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+            async { unreachable!("the calls of a batch are limited one by one") }
+        }
+
+        fn notification<'a>(
+            &self,
+            _: Notification<'a>,
+        ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+            std::future::ready(MethodResponse::notification())
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_calls_are_limited() {
+        let config = RpcLimitsConfig {
+            max_response_body_bytes: 200,
+            method_classes: BTreeMap::from_iter([(
+                "Filecoin.State".into(),
+                RpcMethodLimits {
+                    max_request_body_bytes: None,
+                    max_response_body_bytes: Some(1000),
+                },
+            )]),
+            ..Default::default()
+        };
+        let limits = LimitsLayer::new(Arc::new(config)).layer(Verbose);
+        let mut batch = Batch::new();
+        for (i, method_name) in ["Filecoin.ChainHead", "Filecoin.StateCall"]
+            .into_iter()
+            .enumerate()
+        {
+            batch.push(jsonrpsee::types::Request::owned(
+                method_name.into(),
+                None,
+                Id::Number(i as u64),
+            ));
+        }
+        let response = limits.batch(batch).await;
+        let responses: Vec<serde_json::Value> =
+            serde_json::from_str(response.as_json().get()).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["error"]["code"], OVERSIZED_RESPONSE_CODE);
+        assert_eq!(responses[1]["result"], "a".repeat(300));
+    }
+}
@@ -20,12 +20,11 @@ pub enum AuthNew {}
 
 impl AuthNew {
     pub fn create_token(
-        keystore: &KeyStore,
+        keystore: &mut KeyStore,
         token_exp: Duration,
         permissions: Vec<String>,
     ) -> anyhow::Result<String> {
-        let ki = keystore.get(JWT_IDENTIFIER)?;
-        Ok(create_token(permissions, ki.private_key(), token_exp)?)
+        issue_token(keystore, permissions, token_exp)
     }
 }
 
@@ -42,14 +41,14 @@ impl RpcMethod<2> for AuthNew {
         ctx: Ctx<impl Blockstore>,
         (permissions, expiration_secs): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        let ks = ctx.keystore.read();
+        let mut ks = ctx.keystore.write();
         // Lotus admin tokens do not expire but Forest requires all JWT tokens to
         // have an expiration date. So we set the expiration date to 100 years in
         // the future to match user-visible behavior of Lotus.
         let token_exp = expiration_secs
             .map(chrono::Duration::seconds)
             .unwrap_or_else(|| chrono::Duration::days(365 * 100));
-        let token = Self::create_token(&ks, token_exp, permissions)?;
+        let token = Self::create_token(&mut ks, token_exp, permissions)?;
         Ok(token.as_bytes().to_vec())
     }
 }
@@ -68,12 +67,54 @@ impl RpcMethod<1> for AuthVerify {
     ) -> Result<Self::Ok, ServerError> {
         let ks = ctx.keystore.read();
         let token = header_raw.trim_start_matches("Bearer ");
-        let ki = ks.get(JWT_IDENTIFIER)?;
-        let perms = verify_token(token, ki.private_key())?;
+        let perms = verify_token_with_keystore(token, &ks)?;
         Ok(perms)
     }
 }
 
+pub enum ForestAuthList {}
+impl RpcMethod<0> for ForestAuthList {
+    const NAME: &'static str = "Forest.AuthList";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Lists the JWT tokens issued by the node that did not expire, including the revoked ones.",
+    );
+
+    type Params = ();
+    type Ok = Vec<TokenInfo>;
+
+    async fn handle(ctx: Ctx<impl Blockstore>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(list_tokens(&ctx.keystore.read())?)
+    }
+}
+
+pub enum ForestAuthRevoke {}
+impl RpcMethod<1> for ForestAuthRevoke {
+    const NAME: &'static str = "Forest.AuthRevoke";
+    const PARAM_NAMES: [&'static str; 1] = ["id"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Revokes the JWT token with the given identifier, as listed by Forest.AuthList. Returns false if it was already revoked.",
+    );
+
+    type Params = (String,);
+    type Ok = bool;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (id,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let revoked = revoke_token(&mut ctx.keystore.write(), &id)?;
+        if revoked {
+            tracing::info!("Revoked the JWT token {id}");
+        }
+        Ok(revoked)
+    }
+}
+
 #[serde_as]
 #[derive(Clone, Deserialize, Serialize, JsonSchema)]
 pub struct AuthNewParams {
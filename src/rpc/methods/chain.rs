@@ -8,10 +8,11 @@ use types::*;
 #[cfg(test)]
 use crate::blocks::RawBlockHeader;
 use crate::blocks::{Block, CachingBlockHeader, Tipset, TipsetKey};
-use crate::chain::index::ResolveNullTipset;
+use crate::chain::index::{ResolveNullTipset, TipsetAtHeight};
 use crate::chain::{ChainStore, ExportOptions, FilecoinSnapshotVersion, HeadChange};
 use crate::chain_sync::{get_full_tipset, load_full_tipset};
 use crate::cid_collections::CidHashSet;
+use crate::db::disk_space::{self, DiskSpaceStatus};
 use crate::ipld::DfsIter;
 use crate::ipld::{CHAIN_EXPORT_STATUS, cancel_export, end_export, start_export};
 use crate::lotus_json::{HasLotusJson, LotusJson, lotus_json_with_self};
@@ -25,10 +26,12 @@ use crate::rpc::eth::{
 use crate::rpc::f3::F3ExportLatestSnapshot;
 use crate::rpc::types::*;
 use crate::rpc::{ApiPaths, Ctx, EthEventHandler, Permission, RpcMethod, ServerError};
+use crate::shim::address::Address;
 use crate::shim::clock::ChainEpoch;
 use crate::shim::error::ExitCode;
 use crate::shim::executor::Receipt;
 use crate::shim::message::Message;
+use crate::state_manager::actor_code::{self, ActorCodeChange};
 use crate::utils::db::CborStoreExt as _;
 use crate::utils::io::VoidAsyncWriter;
 use crate::utils::misc::env::is_env_truthy;
@@ -38,6 +41,7 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::{CborStore, RawBytes};
 use hex::ToHex;
 use ipld_core::ipld::Ipld;
+use itertools::Itertools as _;
 use jsonrpsee::types::Params;
 use jsonrpsee::types::error::ErrorObjectOwned;
 use num::BigInt;
@@ -254,7 +258,8 @@ impl RpcMethod<1> for ChainGetMessage {
     }
 }
 
-/// Returns the events stored under the given event AMT root CID.
+/// Returns the events stored under the given event AMT root CID, e.g. the `EventsRoot` of a
+/// receipt returned by `Filecoin.StateReplay`.
 /// Errors if the root CID cannot be found in the blockstore.
 pub enum ChainGetEvents {}
 impl RpcMethod<1> for ChainGetEvents {
@@ -271,6 +276,12 @@ impl RpcMethod<1> for ChainGetEvents {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (root_cid,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
+        if !ctx.store().has(&root_cid)? {
+            return Err(anyhow::anyhow!(
+                "events root {root_cid} not found, its events may have been pruned, replay the message with Filecoin.StateReplay to store them again"
+            )
+            .into());
+        }
         let events = EthEventHandler::get_events_by_event_root(&ctx, &root_cid)?;
         Ok(events)
     }
@@ -306,20 +317,22 @@ impl RpcMethod<1> for ChainGetParentMessages {
 }
 
 pub enum ChainGetParentReceipts {}
-impl RpcMethod<1> for ChainGetParentReceipts {
+impl RpcMethod<3> for ChainGetParentReceipts {
     const NAME: &'static str = "Filecoin.ChainGetParentReceipts";
-    const PARAM_NAMES: [&'static str; 1] = ["blockCid"];
+    const N_REQUIRED_PARAMS: usize = 1;
+    const PARAM_NAMES: [&'static str; 3] = ["blockCid", "offset", "limit"];
     const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
     const PERMISSION: Permission = Permission::Read;
-    const DESCRIPTION: Option<&'static str> =
-        Some("Returns the message receipts included in the blocks of the parent tipset.");
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the message receipts included in the blocks of the parent tipset, optionally only the `limit` receipts starting at index `offset`.",
+    );
 
-    type Params = (Cid,);
+    type Params = (Cid, Option<u64>, Option<u64>);
     type Ok = Vec<ApiReceipt>;
 
     async fn handle(
         ctx: Ctx<impl Blockstore>,
-        (block_cid,): Self::Params,
+        (block_cid, offset, limit): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         let store = ctx.store();
         let block_header: CachingBlockHeader = store
@@ -328,25 +341,34 @@ impl RpcMethod<1> for ChainGetParentReceipts {
         if block_header.epoch == 0 {
             return Ok(vec![]);
         }
-        let receipts = Receipt::get_receipts(store, block_header.message_receipts)
-            .map_err(|_| {
-                ErrorObjectOwned::owned::<()>(
-                    1,
-                    format!(
-                        "failed to root: ipld: could not find {}",
-                        block_header.message_receipts
-                    ),
-                    None,
-                )
-            })?
-            .iter()
-            .map(|r| ApiReceipt {
-                exit_code: r.exit_code().into(),
-                return_data: r.return_data(),
-                gas_used: r.gas_used(),
-                events_root: r.events_root(),
-            })
-            .collect();
+        let limit = limit
+            .map(usize::try_from)
+            .transpose()
+            .context("limit is too large")?;
+        let receipts = Receipt::get_receipts_ranged(
+            store,
+            block_header.message_receipts,
+            offset.unwrap_or_default(),
+            limit,
+        )
+        .map_err(|_| {
+            ErrorObjectOwned::owned::<()>(
+                1,
+                format!(
+                    "failed to root: ipld: could not find {}",
+                    block_header.message_receipts
+                ),
+                None,
+            )
+        })?
+        .iter()
+        .map(|r| ApiReceipt {
+            exit_code: r.exit_code().into(),
+            return_data: r.return_data(),
+            gas_used: r.gas_used(),
+            events_root: r.events_root(),
+        })
+        .collect();
 
         Ok(receipts)
     }
@@ -454,6 +476,8 @@ impl RpcMethod<1> for ForestChainExport {
                 let chain_export =
                     crate::chain::export::<Sha256>(&db, &start_ts, recent_roots, writer, options);
 
+                let chain_export =
+                    disk_space::pause_while(DiskSpaceStatus::exports_paused, chain_export);
                 tokio::select! {
                     result = chain_export => {
                         result.map(|checksum_opt| ApiExportResult::Done(checksum_opt.map(|hash| hash.encode_hex())))
@@ -499,6 +523,8 @@ impl RpcMethod<1> for ForestChainExport {
                     options,
                 );
 
+                let chain_export =
+                    disk_space::pause_while(DiskSpaceStatus::exports_paused, chain_export);
                 tokio::select! {
                     result = chain_export => {
                         result.map(|checksum_opt| ApiExportResult::Done(checksum_opt.map(|hash| hash.encode_hex())))
@@ -838,7 +864,7 @@ impl RpcMethod<2> for ChainGetPath {
 /// ```
 ///
 /// Exposes errors from the [`Blockstore`], and returns an error if there is no common ancestor.
-fn impl_chain_get_path(
+pub(crate) fn impl_chain_get_path(
     chain_store: &ChainStore<impl Blockstore>,
     from: &TipsetKey,
     to: &TipsetKey,
@@ -886,7 +912,9 @@ impl RpcMethod<2> for ChainGetTipSetByHeight {
     const PARAM_NAMES: [&'static str; 2] = ["height", "tipsetKey"];
     const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
     const PERMISSION: Permission = Permission::Read;
-    const DESCRIPTION: Option<&'static str> = Some("Returns the tipset at the specified height.");
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the tipset at the specified height of the chain of the given tipset. If the height is a null round, returns the nearest tipset at an earlier height.",
+    );
 
     type Params = (ChainEpoch, ApiTipsetKey);
     type Ok = Tipset;
@@ -912,9 +940,7 @@ impl RpcMethod<2> for ChainGetTipSetAfterHeight {
     const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
     const PERMISSION: Permission = Permission::Read;
     const DESCRIPTION: Option<&'static str> = Some(
-        "Looks back and returns the tipset at the specified epoch.
-    If there are no blocks at the given epoch,
-    returns the first non-nil tipset at a later epoch.",
+        "Returns the tipset at the specified height of the chain of the given tipset. If the height is a null round, returns the nearest tipset at a later height.",
     );
 
     type Params = (ChainEpoch, ApiTipsetKey);
@@ -934,6 +960,33 @@ impl RpcMethod<2> for ChainGetTipSetAfterHeight {
     }
 }
 
+pub enum ChainGetTipSetAtHeight {}
+impl RpcMethod<2> for ChainGetTipSetAtHeight {
+    const NAME: &'static str = "Forest.ChainGetTipSetAtHeight";
+    const PARAM_NAMES: [&'static str; 2] = ["height", "tipsetKey"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the tipset at the specified height of the chain of the given tipset, or null if the height is a null round. Fails if the tipset is missing.",
+    );
+
+    type Params = (ChainEpoch, ApiTipsetKey);
+    type Ok = Option<Tipset>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (height, ApiTipsetKey(tipset_key)): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let ts = ctx
+            .chain_store()
+            .load_required_tipset_or_heaviest(&tipset_key)?;
+        match ctx.chain_index().tipset_at_height(height, ts)? {
+            TipsetAtHeight::Tipset(ts) => Ok(Some(ts)),
+            TipsetAtHeight::NullRound { .. } => Ok(None),
+        }
+    }
+}
+
 pub enum ChainGetGenesis {}
 impl RpcMethod<0> for ChainGetGenesis {
     const NAME: &'static str = "Filecoin.ChainGetGenesis";
@@ -1175,6 +1228,9 @@ impl RpcMethod<1> for ChainSetHead {
     const PARAM_NAMES: [&'static str; 1] = ["tsk"];
     const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
     const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Sets the head of the chain to the given tipset, which must be connected to the current chain. When switching to another fork, the blocks of the abandoned tipsets are marked as bad, so that they are not followed again.",
+    );
 
     type Params = (TipsetKey,);
     type Ok = ();
@@ -1184,23 +1240,116 @@ impl RpcMethod<1> for ChainSetHead {
         (tsk,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         // This is basically a port of the reference implementation at
-        // https://github.com/filecoin-project/lotus/blob/v1.23.0/node/impl/full/chain.go#L321
+        // https://github.com/filecoin-project/lotus/blob/v1.23.0/node/impl/full/chain.go#L321,
+        // which only rewinds the head, extended to switch to other forks.
 
         let new_head = ctx.chain_index().load_required_tipset(&tsk)?;
-        let mut current = ctx.chain_store().heaviest_tipset();
-        while current.epoch() >= new_head.epoch() {
-            for cid in current.key().to_cids() {
-                ctx.chain_store().unmark_block_as_validated(&cid);
+        if let Some(bad_blocks) = &ctx.bad_blocks
+            && let Some(cid) = tsk.iter().find(|cid| bad_blocks.peek(cid).is_some())
+        {
+            return Err(anyhow::anyhow!("block {cid} of the new head is marked as bad").into());
+        }
+
+        // The new head must be connected to the current chain, so that the reverted tipsets are
+        // known.
+        let path = impl_chain_get_path(
+            ctx.chain_store(),
+            ctx.chain_store().heaviest_tipset().key(),
+            &tsk,
+        )
+        .context("the new head is not connected to the current chain")?;
+        let abandoned = abandoned_blocks(&path);
+        for cid in abandoned.iter().copied().chain(tsk.iter()) {
+            ctx.chain_store().unmark_block_as_validated(&cid);
+        }
+        // Rewinding the head keeps following the same chain, so only the blocks of the forks
+        // switched away from are marked as bad.
+        if switches_fork(&path)
+            && let Some(bad_blocks) = &ctx.bad_blocks
+        {
+            for cid in &abandoned {
+                bad_blocks.push(*cid);
             }
-            let parents = &current.block_headers().first().parents;
-            current = ctx.chain_index().load_required_tipset(parents)?;
         }
+        tracing::warn!(
+            "Setting the head to tipset {} at epoch {}, abandoning {} blocks",
+            new_head.key(),
+            new_head.epoch(),
+            abandoned.len()
+        );
         ctx.chain_store()
             .set_heaviest_tipset(new_head)
             .map_err(Into::into)
     }
 }
 
+pub enum ForestChainCheckpointAdd {}
+impl RpcMethod<1> for ForestChainCheckpointAdd {
+    const NAME: &'static str = "Forest.ChainCheckpointAdd";
+    const PARAM_NAMES: [&'static str; 1] = ["tsk"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Adds the given tipset of the current chain to the checkpoints, which the node refuses to deviate from when syncing.",
+    );
+
+    type Params = (TipsetKey,);
+    type Ok = ();
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (tsk,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let tipset = ctx.chain_index().load_required_tipset(&tsk)?;
+        let canonical = ctx.chain_index().tipset_by_height(
+            tipset.epoch(),
+            ctx.chain_store().heaviest_tipset(),
+            ResolveNullTipset::TakeOlder,
+        )?;
+        if canonical.key() != &tsk {
+            return Err(anyhow::anyhow!("tipset {tsk} is not on the current chain").into());
+        }
+        ctx.chain_store()
+            .checkpoints()
+            .write()
+            .add(tipset.epoch(), tsk)?;
+        tracing::info!(
+            "Added the checkpoint {} at epoch {}",
+            tipset.key(),
+            tipset.epoch()
+        );
+        Ok(())
+    }
+}
+
+/// Returns the blocks of the tipsets a path reverts, except those it applies again, as the blocks
+/// of incomplete tipsets may be.
+fn abandoned_blocks(path: &[PathChange]) -> Vec<Cid> {
+    let applied: CidHashSet = path
+        .iter()
+        .filter_map(|change| match change {
+            PathChange::Apply(ts) => Some(ts.key().iter()),
+            PathChange::Revert(_) => None,
+        })
+        .flatten()
+        .collect();
+    path.iter()
+        .filter_map(|change| match change {
+            PathChange::Revert(ts) => Some(ts.key().iter()),
+            PathChange::Apply(_) => None,
+        })
+        .flatten()
+        .filter(|cid| !applied.contains(cid))
+        .unique()
+        .collect()
+}
+
+/// Returns whether a path switches to another fork, rather than only rewinding the chain.
+fn switches_fork(path: &[PathChange]) -> bool {
+    path.iter()
+        .any(|change| matches!(change, PathChange::Apply(_)))
+}
+
 pub enum ChainGetMinBaseFee {}
 impl RpcMethod<1> for ChainGetMinBaseFee {
     const NAME: &'static str = "Forest.ChainGetMinBaseFee";
@@ -1309,6 +1458,67 @@ pub(crate) fn chain_notify<DB: Blockstore>(
     receiver
 }
 
+pub const ACTOR_CODE_CHANGES: &str = "Forest.ActorCodeChanges";
+/// Maximum number of addresses a [`ACTOR_CODE_CHANGES`] subscription watches, their actors are
+/// loaded at every new head.
+const ACTOR_CODE_CHANGES_MAX_WATCHED: usize = 100;
+
+/// Subscribes to the actor code changes of the new heads, i.e. the migrations of the built-in
+/// actors and the code changes of the watched addresses given as the optional parameter.
+pub(crate) fn actor_code_changes<DB: Blockstore + Send + Sync + 'static>(
+    params: Params<'_>,
+    data: &crate::rpc::RPCState<DB>,
+) -> Subscriber<Vec<ActorCodeChange>> {
+    let (sender, receiver) = broadcast::channel(HEAD_CHANNEL_CAPACITY);
+    let watched = watched_actors(params);
+
+    let mut subscriber = data.chain_store().publisher().subscribe();
+    let db = data.store_owned();
+    tokio::spawn(async move {
+        while let Ok(v) = subscriber.recv().await {
+            // Changes are rare, so the subscription is not closed by a failed send.
+            if sender.receiver_count() == 0 {
+                break;
+            }
+            let HeadChange::Apply(ts) = v;
+            let changes = match actor_code::tipset_changes(&db, &ts, &watched) {
+                Ok(changes) => changes,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to get the actor code changes of tipset {}: {e}",
+                        ts.key()
+                    );
+                    continue;
+                }
+            };
+            if !changes.is_empty() && sender.send(changes).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// The watched addresses of an [`ACTOR_CODE_CHANGES`] subscription, at most
+/// [`ACTOR_CODE_CHANGES_MAX_WATCHED`] of them.
+fn watched_actors(params: Params<'_>) -> Vec<Address> {
+    let mut watched = match params.sequence().optional_next::<LotusJson<Vec<Address>>>() {
+        Ok(watched) => watched.map(LotusJson::into_inner).unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("Invalid {ACTOR_CODE_CHANGES} parameters, no address is watched: {e}");
+            vec![]
+        }
+    };
+    if watched.len() > ACTOR_CODE_CHANGES_MAX_WATCHED {
+        tracing::warn!(
+            "{ACTOR_CODE_CHANGES} watches at most {ACTOR_CODE_CHANGES_MAX_WATCHED} addresses, the {} others are ignored",
+            watched.len() - ACTOR_CODE_CHANGES_MAX_WATCHED
+        );
+        watched.truncate(ACTOR_CODE_CHANGES_MAX_WATCHED);
+    }
+    watched
+}
+
 async fn load_api_messages_from_tipset<DB: Blockstore + Send + Sync + 'static>(
     ctx: &crate::rpc::RPCState<DB>,
     tipset_keys: &TipsetKey,
@@ -1564,6 +1774,20 @@ mod tests {
     use itertools::Itertools as _;
     use std::sync::Arc;
 
+    #[test]
+    fn watched_actors_cap() {
+        let addresses = (0..ACTOR_CODE_CHANGES_MAX_WATCHED as u64 + 10)
+            .map(|id| Address::new_id(id).to_string())
+            .collect_vec();
+        let params = serde_json::to_string(&[&addresses]).unwrap();
+        let watched = watched_actors(Params::new(Some(&params)));
+        assert_eq!(watched.len(), ACTOR_CODE_CHANGES_MAX_WATCHED);
+        assert_eq!(watched[0], Address::new_id(0));
+
+        assert!(watched_actors(Params::new(None)).is_empty());
+        assert!(watched_actors(Params::new(Some("[42]"))).is_empty());
+    }
+
     #[test]
     fn revert_to_ancestor_linear() {
         let store = ChainStore::calibnet();
@@ -1672,6 +1896,78 @@ mod tests {
         let _ = (a, c1);
     }
 
+    #[test]
+    fn set_head_abandoned_blocks() {
+        let store = ChainStore::calibnet();
+        chain4u! {
+            in store.blockstore();
+            [_genesis = store.genesis_block_header()]
+            -> [a, b] -> [c1] -> [d1]
+        };
+        chain4u! {
+            from [a, b] in store.blockstore();
+            [c2]
+        };
+        let abandoned = |from: &[&RawBlockHeader], to: &[&RawBlockHeader]| {
+            let path =
+                impl_chain_get_path(&store, from.make_tipset().key(), to.make_tipset().key())
+                    .unwrap();
+            abandoned_blocks(&path)
+        };
+        let cids = |headers: &[&RawBlockHeader]| {
+            headers
+                .iter()
+                .map(|header| *CachingBlockHeader::new((*header).clone()).cid())
+                .collect_vec()
+        };
+
+        let switches = |from: &[&RawBlockHeader], to: &[&RawBlockHeader]| {
+            switches_fork(
+                &impl_chain_get_path(&store, from.make_tipset().key(), to.make_tipset().key())
+                    .unwrap(),
+            )
+        };
+
+        assert!(!switches(&[d1], &[c1]));
+        assert!(switches(&[d1], &[c2]));
+        assert!(switches(&[c1], &[a]));
+        assert_eq!(abandoned(&[d1], &[c1]), cids(&[d1]));
+        assert_eq!(abandoned(&[d1], &[c2]), cids(&[d1, c1]));
+        // The block of the incomplete tipset is applied again.
+        assert_eq!(abandoned(&[c1], &[a]), cids(&[c1, b]));
+        assert!(abandoned(&[c1], &[d1]).is_empty());
+    }
+
+    #[test]
+    fn checkpointed_headers() {
+        let store = ChainStore::calibnet();
+        chain4u! {
+            in store.blockstore();
+            [_genesis = store.genesis_block_header()]
+            -> [a, b] -> [c1] -> [d1]
+        };
+        chain4u! {
+            from [a, b] in store.blockstore();
+            [c2]
+        };
+        let is_checkpointed = |header: &RawBlockHeader| {
+            store.is_checkpointed(&CachingBlockHeader::new(header.clone()))
+        };
+
+        assert!(!is_checkpointed(c1));
+
+        store
+            .checkpoints()
+            .write()
+            .add(d1.epoch, [d1].make_tipset().key().clone())
+            .unwrap();
+        assert!(is_checkpointed(a));
+        assert!(is_checkpointed(b));
+        assert!(is_checkpointed(c1));
+        assert!(is_checkpointed(d1));
+        assert!(!is_checkpointed(c2));
+    }
+
     impl ChainStore<Chain4U<ManyCar>> {
         fn _load(genesis_car: &'static [u8], genesis_cid: Cid) -> Self {
             let db = Arc::new(Chain4U::with_blockstore(
@@ -1779,4 +2075,22 @@ mod tests {
             "expected change (left) does not match actual change (right)"
         )
     }
+
+    #[tokio::test]
+    async fn chain_get_tipset_at_height() {
+        let harness = crate::test_utils::ChainHarness::new();
+        harness.produce_empty(1).unwrap();
+        // Epoch 2 is a null round.
+        harness
+            .produce(1, [crate::test_utils::BlockSpec::default()])
+            .unwrap();
+        let ctx = harness.rpc_state();
+        let at_height =
+            |height| ChainGetTipSetAtHeight::handle(ctx.clone(), (height, ApiTipsetKey(None)));
+
+        assert_eq!(at_height(3).await.unwrap(), Some(harness.head()));
+        assert_eq!(at_height(2).await.unwrap(), None);
+        assert_eq!(at_height(1).await.unwrap().unwrap().epoch(), 1);
+        at_height(4).await.unwrap_err();
+    }
 }
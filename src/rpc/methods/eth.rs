@@ -6,6 +6,7 @@ mod eth_tx;
 pub mod filter;
 pub mod pubsub;
 pub(crate) mod pubsub_trait;
+pub mod signer;
 mod trace;
 pub mod types;
 mod utils;
@@ -54,14 +55,13 @@ use crate::shim::gas::GasOutputs;
 use crate::shim::message::Message;
 use crate::shim::trace::{CallReturn, ExecutionEvent};
 use crate::shim::{clock::ChainEpoch, state_tree::StateTree};
-use crate::state_manager::StateLookupPolicy;
-use crate::utils::cache::SizeTrackingLruCache;
+use crate::state_manager::{ActorStateOverride, StateLookupPolicy, StateOverrides};
+use crate::utils::cache::{LruValueConstraints, SizeTrackingLruCache};
 use crate::utils::db::BlockstoreExt as _;
 use crate::utils::encoding::from_slice_with_fallback;
 use crate::utils::get_size::{CidWrapper, big_int_heap_size_helper};
 use crate::utils::misc::env::env_or_default;
 use crate::utils::multihash::prelude::*;
-use ahash::HashSet;
 use anyhow::{Context, Error, Result, anyhow, bail, ensure};
 use cid::Cid;
 use enumflags2::{BitFlags, make_bitflags};
@@ -79,8 +79,9 @@ use std::num::NonZeroUsize;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 use tracing::log;
-use utils::{decode_payload, lookup_eth_address};
+use utils::{decode_payload, get_eth_actor, lookup_eth_address};
 
 static FOREST_TRACE_FILTER_MAX_RESULT: LazyLock<u64> =
     LazyLock::new(|| env_or_default("FOREST_TRACE_FILTER_MAX_RESULT", 500));
@@ -862,11 +863,18 @@ impl RpcMethod<0> for EthAccounts {
     type Ok = Vec<String>;
 
     async fn handle(
-        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        // EthAccounts will always return [] since we don't expect Forest to manage private keys
-        Ok(vec![])
+        // Forest doesn't manage Ethereum accounts unless the signer mode is enabled, in which
+        // case this method requires an admin token.
+        if !signer::is_enabled() {
+            return Ok(vec![]);
+        }
+        Ok(signer::accounts(&ctx.keystore.read())?
+            .into_iter()
+            .map(|addr| format!("{:#x}", addr.0))
+            .collect())
     }
 }
 
@@ -1009,13 +1017,12 @@ async fn eth_get_balance<DB: Blockstore + Send + Sync + 'static>(
     address: &EthAddress,
     ts: &Tipset,
 ) -> Result<EthBigInt> {
-    let fil_addr = address.to_filecoin_address()?;
     let (state_cid, _) = ctx
         .state_manager
         .tipset_state(ts, StateLookupPolicy::Enabled)
         .await?;
     let state_tree = ctx.state_manager.get_state_tree(&state_cid)?;
-    match state_tree.get_actor(&fil_addr)? {
+    match get_eth_actor(ctx.chain_store(), &state_tree, address)? {
         Some(actor) => Ok(EthBigInt(actor.balance.atto().clone())),
         None => Ok(EthBigInt::default()), // Balance is 0 if the actor doesn't exist
     }
@@ -2084,11 +2091,35 @@ async fn eth_estimate_gas<DB>(
 where
     DB: Blockstore + Send + Sync + 'static,
 {
+    static ETH_ESTIMATE_GAS_CACHE: LazyLock<EthCallCache<EthUint64>> = LazyLock::new(|| {
+        SizeTrackingLruCache::new_with_metrics("eth_estimate_gas".into(), eth_call_cache_size())
+    });
+
     let mut msg = Message::try_from(tx)?;
     // Set the gas limit to the zero sentinel value, which makes
     // gas estimation actually run.
     msg.gas_limit = 0;
+    let key = EthCallCacheKey {
+        tipset_key: tipset.key().clone(),
+        message_cid: msg.cid(),
+        state_overrides: StateOverrides::default(),
+    };
+    memoized_call(
+        &ETH_ESTIMATE_GAS_CACHE,
+        key,
+        eth_estimate_gas_uncached(ctx, msg, tipset),
+    )
+    .await
+}
 
+async fn eth_estimate_gas_uncached<DB>(
+    ctx: &Ctx<DB>,
+    mut msg: Message,
+    tipset: Tipset,
+) -> Result<EthUint64, ServerError>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
     match gas::estimate_message_gas(ctx, msg.clone(), None, tipset.key().clone().into()).await {
         Err(mut err) => {
             // On failure, GasEstimateMessageGas doesn't actually return the invocation result,
@@ -2098,7 +2129,8 @@ where
             // guts of EthCall). This will give us an ethereum specific error with revert
             // information.
             msg.set_gas_limit(BLOCK_GAS_LIMIT);
-            if let Err(e) = apply_message(ctx, Some(tipset), msg).await {
+            if let Err(e) = apply_message(ctx, Some(tipset), msg, &StateOverrides::default()).await
+            {
                 // if the error is an execution reverted, return it directly
                 if e.downcast_ref::<EthErrors>()
                     .is_some_and(|eth_err| matches!(eth_err, EthErrors::ExecutionReverted { .. }))
@@ -2124,13 +2156,14 @@ async fn apply_message<DB>(
     ctx: &Ctx<DB>,
     tipset: Option<Tipset>,
     msg: Message,
+    state_overrides: &StateOverrides,
 ) -> Result<ApiInvocResult, Error>
 where
     DB: Blockstore + Send + Sync + 'static,
 {
     let invoc_res = ctx
         .state_manager
-        .apply_on_state_with_gas(tipset, msg, StateLookupPolicy::Enabled)
+        .apply_on_state_with_gas(tipset, msg, state_overrides, StateLookupPolicy::Enabled)
         .await
         .map_err(|e| anyhow::anyhow!("failed to apply on state with gas: {e}"))?;
 
@@ -2225,6 +2258,7 @@ where
                 &mut msg.into(),
                 prior_messages,
                 Some(ts),
+                &StateOverrides::default(),
                 VMTrace::NotTraced,
                 StateLookupPolicy::Enabled,
             )
@@ -2491,8 +2525,7 @@ where
         .tipset_state(ts, StateLookupPolicy::Enabled)
         .await?;
     let state_tree = ctx.state_manager.get_state_tree(&state)?;
-    let Some(actor) = state_tree
-        .get_actor(&to_address)
+    let Some(actor) = get_eth_actor(ctx.chain_store(), &state_tree, eth_address)
         .with_context(|| format!("failed to lookup contract {}", eth_address.0))?
     else {
         return Ok(Default::default());
@@ -2613,9 +2646,8 @@ async fn get_storage_at<DB: Blockstore + Send + Sync + 'static>(
         .tipset_state(&ts, StateLookupPolicy::Enabled)
         .await?;
     let make_empty_result = || EthBytes(vec![0; EVM_WORD_LENGTH]);
-    let Some(actor) = ctx
-        .state_manager
-        .get_actor(&to_address, state)
+    let state_tree = ctx.state_manager.get_state_tree(&state)?;
+    let Some(actor) = get_eth_actor(ctx.chain_store(), &state_tree, &eth_address)
         .with_context(|| format!("failed to lookup contract {}", eth_address.0))?
     else {
         return Ok(make_empty_result());
@@ -3061,58 +3093,166 @@ impl RpcMethod<1> for EthGetTransactionHashByCid {
 }
 
 pub enum EthCall {}
-impl RpcMethod<2> for EthCall {
+impl RpcMethod<3> for EthCall {
     const NAME: &'static str = "Filecoin.EthCall";
     const NAME_ALIAS: Option<&'static str> = Some("eth_call");
     const N_REQUIRED_PARAMS: usize = 2;
-    const PARAM_NAMES: [&'static str; 2] = ["tx", "blockParam"];
+    const PARAM_NAMES: [&'static str; 3] = ["tx", "blockParam", "stateOverrides"];
     const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
     const PERMISSION: Permission = Permission::Read;
-    type Params = (EthCallMessage, BlockNumberOrHash);
+    type Params = (EthCallMessage, BlockNumberOrHash, Option<EthStateOverrides>);
     type Ok = EthBytes;
     async fn handle(
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
-        (tx, block_param): Self::Params,
+        (tx, block_param, state_overrides): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         let ts = tipset_by_block_number_or_hash(
             ctx.chain_store(),
             block_param,
             ResolveNullTipset::TakeOlder,
         )?;
-        eth_call(&ctx, tx, ts).await
+        eth_call(&ctx, tx, ts, state_overrides.unwrap_or_default()).await
     }
 }
 
 pub enum EthCallV2 {}
-impl RpcMethod<2> for EthCallV2 {
+impl RpcMethod<3> for EthCallV2 {
     const NAME: &'static str = "Filecoin.EthCall";
     const NAME_ALIAS: Option<&'static str> = Some("eth_call");
     const N_REQUIRED_PARAMS: usize = 2;
-    const PARAM_NAMES: [&'static str; 2] = ["tx", "blockParam"];
+    const PARAM_NAMES: [&'static str; 3] = ["tx", "blockParam", "stateOverrides"];
     const API_PATHS: BitFlags<ApiPaths> = make_bitflags!(ApiPaths::V2);
     const PERMISSION: Permission = Permission::Read;
-    type Params = (EthCallMessage, ExtBlockNumberOrHash);
+    type Params = (
+        EthCallMessage,
+        ExtBlockNumberOrHash,
+        Option<EthStateOverrides>,
+    );
     type Ok = EthBytes;
     async fn handle(
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
-        (tx, block_param): Self::Params,
+        (tx, block_param, state_overrides): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         let ts = tipset_by_block_number_or_hash_v2(&ctx, block_param, ResolveNullTipset::TakeOlder)
             .await?;
-        eth_call(&ctx, tx, ts).await
+        eth_call(&ctx, tx, ts, state_overrides.unwrap_or_default()).await
     }
 }
 
+/// Key of the memoized results of `eth_call` and `eth_estimateGas`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct EthCallCacheKey {
+    tipset_key: TipsetKey,
+    message_cid: Cid,
+    state_overrides: StateOverrides,
+}
+
+impl GetSize for EthCallCacheKey {
+    fn get_heap_size(&self) -> usize {
+        self.tipset_key.get_heap_size()
+            + self
+                .state_overrides
+                .iter()
+                .map(|(address, state_override)| {
+                    std::mem::size_of::<(FilecoinAddress, ActorStateOverride)>()
+                        + address.get_heap_size()
+                        + state_override.get_heap_size()
+                })
+                .sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoizedCall<T> {
+    cached_at: Instant,
+    result: T,
+}
+
+impl<T: GetSize> GetSize for MemoizedCall<T> {
+    fn get_heap_size(&self) -> usize {
+        self.result.get_heap_size()
+    }
+}
+
+type EthCallCache<T> = SizeTrackingLruCache<EthCallCacheKey, MemoizedCall<T>>;
+
+/// Dashboards poll the same calls at the same tipset over and over. Their results only depend on
+/// the tipset, except for the gas estimations which also depend on the message pool, hence the
+/// short lifetime of the memoized results.
+const ETH_CALL_CACHE_TTL: Duration = Duration::from_secs(30);
+
+fn eth_call_cache_size() -> NonZeroUsize {
+    const DEFAULT_CACHE_SIZE: NonZeroUsize = nonzero!(1000usize);
+    std::env::var("FOREST_ETH_CALL_CACHE_SIZE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_CACHE_SIZE)
+}
+
+/// Returns the memoized result of a call if it is recent enough, or makes the call and memoizes
+/// its result if it succeeds.
+async fn memoized_call<T>(
+    cache: &EthCallCache<T>,
+    key: EthCallCacheKey,
+    call: impl Future<Output = Result<T, ServerError>>,
+) -> Result<T, ServerError>
+where
+    T: LruValueConstraints,
+{
+    if let Some(memoized) = cache.get_cloned(&key)
+        && memoized.cached_at.elapsed() < ETH_CALL_CACHE_TTL
+    {
+        return Ok(memoized.result);
+    }
+    let result = call.await?;
+    cache.push(
+        key,
+        MemoizedCall {
+            cached_at: Instant::now(),
+            result: result.clone(),
+        },
+    );
+    Ok(result)
+}
+
 async fn eth_call<DB>(
     ctx: &Ctx<DB>,
     tx: EthCallMessage,
     ts: Tipset,
+    state_overrides: EthStateOverrides,
 ) -> Result<EthBytes, ServerError>
 where
     DB: Blockstore + Send + Sync + 'static,
 {
+    static ETH_CALL_CACHE: LazyLock<EthCallCache<EthBytes>> = LazyLock::new(|| {
+        SizeTrackingLruCache::new_with_metrics("eth_call".into(), eth_call_cache_size())
+    });
+
     let msg = Message::try_from(tx)?;
-    let invoke_result = apply_message(ctx, Some(ts), msg.clone()).await?;
+    let state_overrides = StateOverrides::try_from(state_overrides)?;
+    let key = EthCallCacheKey {
+        tipset_key: ts.key().clone(),
+        message_cid: msg.cid(),
+        state_overrides: state_overrides.clone(),
+    };
+    memoized_call(
+        &ETH_CALL_CACHE,
+        key,
+        eth_call_uncached(ctx, msg, ts, &state_overrides),
+    )
+    .await
+}
+
+async fn eth_call_uncached<DB>(
+    ctx: &Ctx<DB>,
+    msg: Message,
+    ts: Tipset,
+    state_overrides: &StateOverrides,
+) -> Result<EthBytes, ServerError>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    let invoke_result = apply_message(ctx, Some(ts), msg.clone(), state_overrides).await?;
 
     if msg.to() == FilecoinAddress::ETHEREUM_ACCOUNT_MANAGER_ACTOR {
         Ok(EthBytes::default())
@@ -3456,6 +3596,53 @@ impl RpcMethod<1> for EthSendRawTransactionUntrusted {
     }
 }
 
+pub enum EthSendTransaction {}
+impl RpcMethod<1> for EthSendTransaction {
+    const NAME: &'static str = "Filecoin.EthSendTransaction";
+    const NAME_ALIAS: Option<&'static str> = Some("eth_sendTransaction");
+    const PARAM_NAMES: [&'static str; 1] = ["tx"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all_with_v2();
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Signs a transaction with the wallet key of its sender, and pushes it to the message pool. Only available in the signer mode.",
+    );
+
+    type Params = (EthCallMessage,);
+    type Ok = EthHash;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (tx,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        signer::ensure_enabled()?;
+        tx.from.as_ref().context("the from address is required")?;
+        // Unset fields are estimated.
+        let gas_limit = tx.gas.map(|gas| gas.0).unwrap_or_default();
+        let gas_fee_cap = tx
+            .max_fee_per_gas
+            .as_ref()
+            .or(tx.gas_price.as_ref())
+            .cloned();
+        let gas_premium = tx
+            .max_priority_fee_per_gas
+            .as_ref()
+            .or(tx.gas_price.as_ref())
+            .cloned();
+        let mut message = Message::try_from(tx)?;
+        message.gas_limit = gas_limit;
+        message.gas_fee_cap = gas_fee_cap.map(|fee| fee.0.into()).unwrap_or_default();
+        message.gas_premium = gas_premium.map(|fee| fee.0.into()).unwrap_or_default();
+        let key_info = crate::key_management::try_find(&message.from, &mut ctx.keystore.write())?;
+
+        let mut message =
+            gas::estimate_message_gas(&ctx, message, None, Default::default()).await?;
+        message.sequence = ctx.mpool.get_sequence(&message.from)?;
+        let smsg = signer::sign_message(&key_info, &message, ctx.chain_config().eth_chain_id)?;
+        let cid = ctx.mpool.as_ref().push(smsg).await?;
+        Ok(cid.into())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct CollectedEvent {
     pub(crate) entries: Vec<EventEntry>,
@@ -3732,7 +3919,12 @@ impl RpcMethod<1> for EthGetFilterLogs {
                 let recent_events: Vec<CollectedEvent> = events
                     .clone()
                     .into_iter()
-                    .filter(|event| !event_filter.collected.contains(event))
+                    .filter(|event| {
+                        !event_filter.collected.contains(event)
+                            && event_filter
+                                .cursor
+                                .is_none_or(|cursor| event.height > cursor)
+                    })
                     .collect();
                 let filter = Arc::new(EventFilter {
                     id: event_filter.id.clone(),
@@ -3741,6 +3933,7 @@ impl RpcMethod<1> for EthGetFilterLogs {
                     keys_with_codec: event_filter.keys_with_codec.clone(),
                     max_results: event_filter.max_results,
                     collected: events.clone(),
+                    cursor: event_filter.cursor,
                 });
                 store.update(filter);
                 return Ok(eth_filter_result_from_events(&ctx, &recent_events)?);
@@ -3782,7 +3975,12 @@ impl RpcMethod<1> for EthGetFilterChanges {
                 let recent_events: Vec<CollectedEvent> = events
                     .clone()
                     .into_iter()
-                    .filter(|event| !event_filter.collected.contains(event))
+                    .filter(|event| {
+                        !event_filter.collected.contains(event)
+                            && event_filter
+                                .cursor
+                                .is_none_or(|cursor| event.height > cursor)
+                    })
                     .collect();
                 let filter = Arc::new(EventFilter {
                     id: event_filter.id.clone(),
@@ -3791,6 +3989,7 @@ impl RpcMethod<1> for EthGetFilterChanges {
                     keys_with_codec: event_filter.keys_with_codec.clone(),
                     max_results: event_filter.max_results,
                     collected: events.clone(),
+                    cursor: event_filter.cursor,
                 });
                 store.update(filter);
                 return Ok(eth_filter_result_from_events(&ctx, &recent_events)?);
@@ -3911,7 +4110,35 @@ impl RpcMethod<1> for EthTraceBlockV2 {
     }
 }
 
+/// Returns the traces of a tipset. They are indexed by tipset, as they are expensive to compute
+/// and `trace_filter` requests over overlapping ranges recompute the same tipsets.
 async fn eth_trace_block<DB>(ctx: &Ctx<DB>, ts: &Tipset) -> Result<Vec<EthBlockTrace>, ServerError>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    static ETH_BLOCK_TRACES_CACHE: LazyLock<SizeTrackingLruCache<CidWrapper, Vec<EthBlockTrace>>> =
+        LazyLock::new(|| {
+            const DEFAULT_CACHE_SIZE: NonZeroUsize = nonzero!(500usize);
+            let cache_size = std::env::var("FOREST_ETH_TRACE_CACHE_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_CACHE_SIZE);
+            SizeTrackingLruCache::new_with_metrics("eth_block_traces".into(), cache_size)
+        });
+
+    let key = ts.key().cid()?.into();
+    if let Some(traces) = ETH_BLOCK_TRACES_CACHE.get_cloned(&key) {
+        return Ok(traces);
+    }
+    let traces = eth_trace_block_uncached(ctx, ts).await?;
+    ETH_BLOCK_TRACES_CACHE.push(key, traces.clone());
+    Ok(traces)
+}
+
+async fn eth_trace_block_uncached<DB>(
+    ctx: &Ctx<DB>,
+    ts: &Tipset,
+) -> Result<Vec<EthBlockTrace>, ServerError>
 where
     DB: Blockstore + Send + Sync + 'static,
 {
@@ -4110,18 +4337,14 @@ where
     Ok(all_traces)
 }
 
-fn get_eth_block_number_from_string<DB: Blockstore>(
+/// Resolves a `fromBlock` or `toBlock` of a trace filter, which defaults to `latest`.
+fn get_eth_tipset_from_string<DB: Blockstore>(
     chain_store: &ChainStore<DB>,
     block: Option<&str>,
     resolve: ResolveNullTipset,
-) -> Result<EthUint64> {
-    let block_param = match block {
-        Some(block_str) => ExtBlockNumberOrHash::from_str(block_str)?,
-        None => bail!("cannot parse fromBlock"),
-    };
-    Ok(EthUint64(
-        tipset_by_ext_block_number_or_hash(chain_store, block_param, resolve)?.epoch() as u64,
-    ))
+) -> Result<Tipset> {
+    let block_param = ExtBlockNumberOrHash::from_str(block.unwrap_or("latest"))?;
+    tipset_by_ext_block_number_or_hash(chain_store, block_param, resolve)
 }
 
 pub enum EthTraceFilter {}
@@ -4141,21 +4364,21 @@ impl RpcMethod<1> for EthTraceFilter {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (filter,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        let from_block = get_eth_block_number_from_string(
+        let from_block = get_eth_tipset_from_string(
             ctx.chain_store(),
             filter.from_block.as_deref(),
             ResolveNullTipset::TakeNewer,
         )
         .context("cannot parse fromBlock")?;
 
-        let to_block = get_eth_block_number_from_string(
+        let to_block = get_eth_tipset_from_string(
             ctx.chain_store(),
             filter.to_block.as_deref(),
             ResolveNullTipset::TakeOlder,
         )
         .context("cannot parse toBlock")?;
 
-        Ok(trace_filter(ctx, filter, from_block, to_block)
+        Ok(trace_filter(ctx, filter, from_block.epoch(), to_block)
             .await?
             .into_iter()
             .sorted_by_key(|trace| {
@@ -4169,13 +4392,15 @@ impl RpcMethod<1> for EthTraceFilter {
     }
 }
 
+/// Collects the traces matching the filter of the tipsets from `from_epoch` to `to_tipset`, in
+/// chain order so that the `after` offset is stable across paginated requests.
 async fn trace_filter(
     ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
     filter: EthTraceFilterCriteria,
-    from_block: EthUint64,
-    to_block: EthUint64,
-) -> Result<HashSet<EthBlockTrace>> {
-    let mut results = HashSet::default();
+    from_epoch: ChainEpoch,
+    to_tipset: Tipset,
+) -> Result<Vec<EthBlockTrace>> {
+    let mut results = vec![];
     if let Some(EthUint64(0)) = filter.count {
         return Ok(results);
     }
@@ -4187,14 +4412,17 @@ async fn trace_filter(
         *FOREST_TRACE_FILTER_MAX_RESULT
     );
 
+    // Null rounds have no tipsets, walking the parents skips them.
+    let mut tipsets = ctx
+        .chain_index()
+        .chain(to_tipset)
+        .take_while(|tipset| tipset.epoch() >= from_epoch)
+        .collect_vec();
+    tipsets.reverse();
+
     let mut trace_counter = 0;
-    for blk_num in from_block.0..=to_block.0 {
-        let block_traces = EthTraceBlock::handle(
-            ctx.clone(),
-            (ExtBlockNumberOrHash::from_block_number(blk_num as i64),),
-        )
-        .await?;
-        for block_trace in block_traces {
+    for tipset in tipsets {
+        for block_trace in eth_trace_block(&ctx, &tipset).await? {
             if block_trace
                 .trace
                 .match_filter_criteria(&filter.from_address, &filter.to_address)?
@@ -4206,7 +4434,7 @@ async fn trace_filter(
                     continue;
                 }
 
-                results.insert(block_trace);
+                results.push(block_trace);
 
                 if filter.count.is_some() && results.len() >= count as usize {
                     return Ok(results);
@@ -4749,4 +4977,27 @@ mod test {
             "overflow with all ones"
         );
     }
+
+    #[tokio::test]
+    async fn trace_filter_block_range() {
+        let harness = crate::test_utils::ChainHarness::new();
+        harness.produce_empty(3).unwrap();
+        // Epochs 4 and 5 are null rounds.
+        harness
+            .produce(2, [crate::test_utils::BlockSpec::default()])
+            .unwrap();
+        let chain_store = &harness.chain_store;
+
+        let latest =
+            get_eth_tipset_from_string(chain_store, None, ResolveNullTipset::TakeOlder).unwrap();
+        // The latest executed tipset is the parent of the head.
+        assert_eq!(latest.key(), harness.head().parents());
+        let from =
+            get_eth_tipset_from_string(chain_store, Some("0x4"), ResolveNullTipset::TakeNewer)
+                .unwrap();
+        assert_eq!(from.epoch(), 6);
+        let to = get_eth_tipset_from_string(chain_store, Some("0x5"), ResolveNullTipset::TakeOlder)
+            .unwrap();
+        assert_eq!(to.epoch(), 3);
+    }
 }
@@ -3,7 +3,7 @@
 
 use super::*;
 pub use crate::eth::{
-    EIP_1559_TX_TYPE, EIP_LEGACY_TX_TYPE, ETH_LEGACY_HOMESTEAD_TX_CHAIN_ID, EthTx,
+    EIP_1559_TX_TYPE, EIP_2930_TX_TYPE, EIP_LEGACY_TX_TYPE, ETH_LEGACY_HOMESTEAD_TX_CHAIN_ID, EthTx,
 };
 
 impl From<EthLegacyHomesteadTxArgs> for ApiEthTx {
@@ -113,3 +113,133 @@ impl From<EthTx> for ApiEthTx {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::parse_eth_transaction;
+
+    /// Recovers the signed transaction of an API transaction, to check that the conversion to
+    /// the API representation is lossless.
+    fn eth_tx_from_api(tx: ApiEthTx) -> anyhow::Result<EthTx> {
+        ensure!(
+            tx.access_list.is_empty(),
+            "access lists are not supported by the FEVM"
+        );
+        match tx.r#type.0 {
+            EIP_LEGACY_TX_TYPE => {
+                let gas_price = tx
+                    .gas_price
+                    .context("legacy transaction without a gas price")?
+                    .0;
+                if tx.chain_id.0 == ETH_LEGACY_HOMESTEAD_TX_CHAIN_ID {
+                    Ok(EthTx::Homestead(Box::new(EthLegacyHomesteadTxArgs {
+                        nonce: tx.nonce.0,
+                        gas_price,
+                        gas_limit: tx.gas.0,
+                        to: tx.to,
+                        value: tx.value.0,
+                        input: tx.input.0,
+                        v: tx.v.0,
+                        r: tx.r.0,
+                        s: tx.s.0,
+                    })))
+                } else {
+                    Ok(EthTx::Eip155(Box::new(EthLegacyEip155TxArgs {
+                        chain_id: tx.chain_id.0,
+                        nonce: tx.nonce.0,
+                        gas_price,
+                        gas_limit: tx.gas.0,
+                        to: tx.to,
+                        value: tx.value.0,
+                        input: tx.input.0,
+                        v: tx.v.0,
+                        r: tx.r.0,
+                        s: tx.s.0,
+                    })))
+                }
+            }
+            t if t == u64::from(EIP_1559_TX_TYPE) => {
+                Ok(EthTx::Eip1559(Box::new(EthEip1559TxArgs {
+                    chain_id: tx.chain_id.0,
+                    nonce: tx.nonce.0,
+                    to: tx.to,
+                    value: tx.value.0,
+                    max_fee_per_gas: tx
+                        .max_fee_per_gas
+                        .context("EIP-1559 transaction without a max fee per gas")?
+                        .0,
+                    max_priority_fee_per_gas: tx
+                        .max_priority_fee_per_gas
+                        .context("EIP-1559 transaction without a max priority fee per gas")?
+                        .0,
+                    gas_limit: tx.gas.0,
+                    input: tx.input.0,
+                    v: tx.v.0,
+                    r: tx.r.0,
+                    s: tx.s.0,
+                })))
+            }
+            t if t == u64::from(EIP_2930_TX_TYPE) => bail!("EIP-2930 transaction is not supported"),
+            t => bail!("unsupported transaction type: {t}"),
+        }
+    }
+
+    // The raw transactions of the `eth::transaction` tests, and their fee fields in the Lotus JSON
+    // of `eth_getTransactionByHash`.
+    const EIP_1559_RAW_TX: &str = "02f901368304cb2f8201e68459682f008459682f7884023b53a794eb4a9cdb9f42d3a503d580a39b6e3736eb21fffd80b8c4383487be000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000660d4d120000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000003b6261666b726569656f6f75326d36356276376561786e7767656d7562723675787269696867366474646e6c7a663469616f37686c6e6a6d647372750000000000c001a0b9f0afb3fa8821fa414bac6056e613c61a8263ca341b59539096dbbc8600f530a0114a6a032347e132f115accc7664ccc61549be28f5b844c3fc170006feb72f24";
+    const EIP_155_RAW_TX: &str = "f8cc04830406968419ca81cc94d0fb381fc644cdd5d694d35e1afb445527b9244b80b864d5b3d76d00000000000000000000000000000000000000000000000045466fa6fdcb80000000000000000000000000000000000000000000000000000000002e90edd000000000000000000000000000000000000000000000000000000000000001518083099681a0580b1d36c5a8c8c1c550fb45b0a6ff21aaa517be036385541621961b5d873796a055e8447d58d64ebc3038d9882886bbc3b0228c7ac77c71f4e811b97ed3f14b5a";
+
+    fn api_tx_roundtrip(raw_tx: &str) -> serde_json::Value {
+        let raw_tx = hex::decode(raw_tx).unwrap();
+        let tx = parse_eth_transaction(&raw_tx).unwrap();
+        let hash = tx.eth_hash().unwrap();
+        let json = ApiEthTx::from(tx).into_lotus_json_value().unwrap();
+
+        let tx = eth_tx_from_api(ApiEthTx::from_lotus_json(
+            serde_json::from_value(json.clone()).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(tx.rlp_signed_message().unwrap(), raw_tx);
+        assert_eq!(tx.eth_hash().unwrap(), hash);
+        json
+    }
+
+    #[test]
+    fn eip_1559_api_tx_roundtrip() {
+        let json = api_tx_roundtrip(EIP_1559_RAW_TX);
+        assert_eq!(json["type"], "0x2");
+        assert_eq!(json["maxFeePerGas"], "0x59682f78");
+        assert_eq!(json["maxPriorityFeePerGas"], "0x59682f00");
+        assert_eq!(json["accessList"], serde_json::json!([]));
+        assert!(json.get("gasPrice").is_none());
+    }
+
+    #[test]
+    fn eip_155_api_tx_roundtrip() {
+        let json = api_tx_roundtrip(EIP_155_RAW_TX);
+        assert_eq!(json["type"], "0x0");
+        assert_eq!(json["chainId"], "0x4cb2f");
+        assert_eq!(json["gasPrice"], "0x40696");
+        assert!(json.get("maxFeePerGas").is_none());
+        assert!(json.get("maxPriorityFeePerGas").is_none());
+    }
+
+    #[test]
+    fn unsupported_api_tx() {
+        let tx = ApiEthTx {
+            r#type: EthUint64(EIP_2930_TX_TYPE.into()),
+            ..Default::default()
+        };
+        assert!(eth_tx_from_api(tx).is_err());
+
+        let tx = ApiEthTx {
+            r#type: EthUint64(EIP_1559_TX_TYPE.into()),
+            max_fee_per_gas: Some(EthBigInt::default()),
+            max_priority_fee_per_gas: Some(EthBigInt::default()),
+            access_list: vec![EthHash::default()],
+            ..Default::default()
+        };
+        assert!(eth_tx_from_api(tx).is_err());
+    }
+}
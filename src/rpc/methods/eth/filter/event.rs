@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::rpc::Arc;
+use crate::rpc::eth::filter::persist::{
+    PersistedEventFilter, PersistedFilter, PersistedFilterKind,
+};
 use crate::rpc::eth::filter::{ActorEventBlock, ParsedFilter, ParsedFilterTipsets};
 use crate::rpc::eth::{CollectedEvent, FilterID, filter::Filter};
 use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
 use ahash::AHashMap as HashMap;
 use anyhow::{Context, Result};
 use parking_lot::RwLock;
@@ -25,6 +29,9 @@ pub struct EventFilter {
     pub max_results: usize,
     // Collected events
     pub collected: Vec<CollectedEvent>,
+    // Epoch up to which the events were polled before the node restarted, the events up to it
+    // are not returned again
+    pub cursor: Option<ChainEpoch>,
 }
 
 impl From<&EventFilter> for ParsedFilter {
@@ -45,6 +52,30 @@ impl Filter for EventFilter {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn persisted(&self) -> Option<PersistedFilter> {
+        let last_collected = self.collected.iter().max_by_key(|event| event.height);
+        let (cursor, cursor_tipset) = match last_collected {
+            Some(event) if self.cursor.is_none_or(|cursor| event.height > cursor) => {
+                (Some(event.height), Some(event.tipset_key.clone()))
+            }
+            _ => (self.cursor, None),
+        };
+        Some(PersistedFilter {
+            id: self.id.clone(),
+            kind: PersistedFilterKind::Event(PersistedEventFilter {
+                tipsets: (&self.tipsets).into(),
+                addresses: self.addresses.clone(),
+                keys: self
+                    .keys_with_codec
+                    .iter()
+                    .map(|(key, values)| (key.clone(), values.clone()))
+                    .collect(),
+            }),
+            cursor,
+            cursor_tipset,
+        })
+    }
 }
 
 /// The `EventFilterManager` structure maintains a set of filters, allowing new filters to be
@@ -73,6 +104,7 @@ impl EventFilterManager {
             keys_with_codec: pf.keys,
             max_results: self.max_filter_results,
             collected: vec![],
+            cursor: None,
         });
 
         self.filters.write().insert(id, filter.clone());
@@ -84,6 +116,11 @@ impl EventFilterManager {
         let mut filters = self.filters.write();
         filters.remove(id)
     }
+
+    /// Adds back a filter installed before the node restarted.
+    pub fn restore(&self, filter: Arc<EventFilter>) {
+        self.filters.write().insert(filter.id.clone(), filter);
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::rpc::Arc;
+use crate::rpc::eth::filter::persist::{PersistedFilter, PersistedFilterKind};
 use crate::rpc::eth::{FilterID, filter::Filter, filter::FilterManager};
 use crate::shim::fvm_shared_latest::clock::ChainEpoch;
 use ahash::AHashMap as HashMap;
@@ -41,6 +42,15 @@ impl Filter for MempoolFilter {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn persisted(&self) -> Option<PersistedFilter> {
+        Some(PersistedFilter {
+            id: self.id.clone(),
+            kind: PersistedFilterKind::Mempool,
+            cursor: self.collected,
+            cursor_tipset: None,
+        })
+    }
 }
 
 /// `MempoolFilterManager` uses a `RwLock` to handle concurrent access to a collection of `MempoolFilter`
@@ -75,6 +85,10 @@ impl FilterManager for MempoolFilterManager {
         let mut filters = self.filters.write();
         filters.remove(id)
     }
+
+    fn restore(&self, filter: Arc<dyn Filter>) {
+        self.filters.write().insert(filter.id().clone(), filter);
+    }
 }
 
 #[cfg(test)]
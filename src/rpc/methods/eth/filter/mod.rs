@@ -16,6 +16,7 @@
 //! - **Mempool Filter**: Monitors the Ethereum mempool for new pending transactions that meet certain criteria.
 pub mod event;
 pub mod mempool;
+mod persist;
 mod store;
 pub mod tipset;
 
@@ -25,29 +26,34 @@ use super::Predefined;
 use super::get_tipset_from_hash;
 use crate::blocks::Tipset;
 use crate::blocks::TipsetKey;
+use crate::chain::ChainStore;
 use crate::chain::index::ResolveNullTipset;
 use crate::cli_shared::cli::EventsConfig;
+use crate::db::SettingsStore;
 use crate::rpc::eth::EVM_WORD_LENGTH;
 use crate::rpc::eth::filter::event::*;
 use crate::rpc::eth::filter::mempool::*;
 use crate::rpc::eth::filter::tipset::*;
 use crate::rpc::eth::types::*;
 use crate::rpc::misc::ActorEventFilter;
+use crate::rpc::misc::EventSchemaRegistry;
 use crate::rpc::reflect::Ctx;
 use crate::rpc::types::{Event, EventEntry};
 use crate::shim::address::Address;
 use crate::shim::clock::ChainEpoch;
 use crate::shim::executor::{Entry, StampedEvent};
-use crate::state_manager::StateEvents;
+use crate::state_manager::{StateManager, logs_bloom};
 use crate::utils::misc::env::env_or_default;
 use ahash::AHashMap as HashMap;
 use anyhow::{Context, Error, anyhow, bail, ensure};
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::IPLD_RAW;
+use persist::*;
 use serde::*;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
+use std::time::Duration;
 use store::*;
 
 /// A trait for filtering events based on predefined conditions.
@@ -78,6 +84,8 @@ pub trait Matcher {
 pub trait FilterManager {
     fn install(&self) -> Result<Arc<dyn Filter>, Error>;
     fn remove(&self, filter_id: &FilterID) -> Option<Arc<dyn Filter>>;
+    /// Adds back a filter installed before the node restarted.
+    fn restore(&self, filter: Arc<dyn Filter>);
 }
 
 /// Handles Ethereum event filters, providing an interface for creating and managing filters.
@@ -89,6 +97,12 @@ pub struct EthEventHandler {
     pub filter_store: Option<Arc<dyn FilterStore>>,
     pub max_filter_results: usize,
     pub max_filter_height_range: ChainEpoch,
+    /// Maximum number of epochs whose changes are replayed to the filters restored after a restart
+    pub max_filter_replay_epochs: ChainEpoch,
+    /// Duration after which a filter that is not polled is uninstalled
+    pub filter_ttl: Duration,
+    /// Schemas the values of the actor events are decoded with
+    pub event_schemas: EventSchemaRegistry,
     event_filter_manager: Option<Arc<EventFilterManager>>,
     tipset_filter_manager: Option<Arc<TipSetFilterManager>>,
     mempool_filter_manager: Option<Arc<MempoolFilterManager>>,
@@ -138,12 +152,88 @@ impl EthEventHandler {
             filter_store,
             max_filter_results,
             max_filter_height_range,
+            max_filter_replay_epochs: config.max_filter_replay_epochs,
+            filter_ttl: Duration::from_secs(config.filter_ttl_secs),
+            event_schemas: EventSchemaRegistry::new(&config.schemas),
             event_filter_manager,
             tipset_filter_manager,
             mempool_filter_manager,
         }
     }
 
+    /// Persists the installed filters in the settings store, restoring first the filters
+    /// persisted before the node restarted. Their changes are replayed from their cursors on the
+    /// chain of the current head, over [`Self::max_filter_replay_epochs`] epochs at most.
+    pub fn with_persisted_filters<DB: Blockstore>(
+        mut self,
+        store: Arc<dyn SettingsStore + Sync + Send>,
+        chain_store: &ChainStore<DB>,
+    ) -> Self {
+        let Some(filter_store) = self.filter_store.clone() else {
+            return self;
+        };
+        let persisted =
+            PersistedFilter::load(store.as_ref(), self.filter_ttl).unwrap_or_else(|e| {
+                tracing::warn!("Failed to load the persisted filters: {e}");
+                vec![]
+            });
+        let head = chain_store.heaviest_tipset();
+        let min_epoch = (head.epoch() - self.max_filter_replay_epochs).max(0);
+        let mut restored_count = 0;
+        for (persisted, last_used) in persisted {
+            let restored =
+                persisted.restore(chain_store, &head, min_epoch, self.max_filter_results);
+            if let Err(e) = filter_store.insert(restored.filter(), last_used) {
+                tracing::warn!("Failed to restore a persisted filter: {e}");
+                continue;
+            }
+            match restored {
+                RestoredFilter::Event(filter) => {
+                    if let Some(manager) = &self.event_filter_manager {
+                        manager.restore(filter);
+                    }
+                }
+                RestoredFilter::TipSet(filter) => {
+                    if let Some(manager) = &self.tipset_filter_manager {
+                        manager.restore(filter);
+                    }
+                }
+                RestoredFilter::Mempool(filter) => {
+                    if let Some(manager) = &self.mempool_filter_manager {
+                        manager.restore(filter);
+                    }
+                }
+            }
+            restored_count += 1;
+        }
+        if restored_count > 0 {
+            tracing::info!("Restored {restored_count} persisted filters");
+        }
+        self.filter_store = Some(PersistentFilterStore::new(filter_store, store));
+        self
+    }
+
+    /// Periodically uninstalls the filters not polled for [`Self::filter_ttl`] and persists the
+    /// changes of the installed filters.
+    pub async fn maintain_filters(self: Arc<Self>) -> anyhow::Result<()> {
+        const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(10);
+
+        let Some(filter_store) = self.filter_store.clone() else {
+            return Ok(());
+        };
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+        loop {
+            interval.tick().await;
+            for filter in filter_store.expired(self.filter_ttl) {
+                tracing::debug!("Uninstalling the expired filter {}", filter.id());
+                if let Err(e) = self.uninstall_filter(filter) {
+                    tracing::warn!("Failed to uninstall an expired filter: {e}");
+                }
+            }
+            filter_store.flush();
+        }
+    }
+
     // Installs an eth filter based on given filter spec.
     pub fn eth_new_filter(
         &self,
@@ -274,27 +364,52 @@ impl EthEventHandler {
         spec: Option<&impl Matcher>,
         skip_event: SkipEvent,
         collected_events: &mut Vec<CollectedEvent>,
+    ) -> anyhow::Result<()> {
+        Self::collect_tipset_events(
+            &ctx.state_manager,
+            tipset,
+            spec,
+            skip_event,
+            ctx.eth_event_handler.max_filter_results,
+            collected_events,
+        )
+        .await
+    }
+
+    /// Collects the events of the tipset matching the spec, failing if more than `max_results`
+    /// events are collected in total.
+    pub async fn collect_tipset_events<DB: Blockstore + Send + Sync + 'static>(
+        state_manager: &Arc<StateManager<DB>>,
+        tipset: &Tipset,
+        spec: Option<&impl Matcher>,
+        skip_event: SkipEvent,
+        max_results: usize,
+        collected_events: &mut Vec<CollectedEvent>,
     ) -> anyhow::Result<()> {
         let tipset_key = tipset.key().clone();
         let height = tipset.epoch();
 
-        let messages = ctx.chain_store().messages_for_tipset(tipset)?;
+        let messages = state_manager.chain_store().messages_for_tipset(tipset)?;
 
-        let StateEvents { events, .. } = ctx.state_manager.tipset_state_events(tipset).await?;
+        let receipts = state_manager.tipset_message_receipts(tipset).await?;
 
         ensure!(
-            messages.len() == events.len(),
-            "Length of messages ({}) and events ({}) do not match",
+            messages.len() == receipts.len(),
+            "Length of messages ({}) and receipts ({}) do not match",
             messages.len(),
-            events.len(),
+            receipts.len(),
         );
 
         let mut event_count = 0;
-        for (i, (message, events)) in messages.iter().zip(events.into_iter()).enumerate() {
+        for (i, (message, receipt)) in messages.iter().zip(receipts).enumerate() {
+            // The events are loaded one message at a time rather than for the whole tipset
+            let Some(events_root) = receipt.events_root() else {
+                continue;
+            };
+            let events = StampedEvent::get_events(state_manager.blockstore(), &events_root)?;
             for event in events.iter() {
                 let id_addr = Address::new_id(event.emitter());
-                let result = ctx
-                    .state_manager
+                let result = state_manager
                     .resolve_to_deterministic_address(id_addr, tipset)
                     .await
                     .with_context(|| {
@@ -353,7 +468,7 @@ impl EthEventHandler {
                         msg_idx: i as u64,
                         msg_cid: message.cid(),
                     };
-                    if collected_events.len() >= ctx.eth_event_handler.max_filter_results {
+                    if collected_events.len() >= max_results {
                         bail!("filter matches too many events, try a more restricted filter");
                     }
                     collected_events.push(ce);
@@ -370,15 +485,12 @@ impl EthEventHandler {
         ctx: &Ctx<DB>,
         events_root: &Cid,
     ) -> anyhow::Result<Vec<Event>> {
-        let state_events =
-            match StampedEvent::get_events(ctx.chain_store().blockstore(), events_root) {
-                Ok(e) => e,
-                Err(e) => {
-                    return Err(anyhow::anyhow!("load events amt: {}", e));
-                }
-            };
-
-        let chain_events: Vec<Event> = state_events.into_iter().map(Into::into).collect();
+        let mut chain_events = Vec::new();
+        StampedEvent::for_each_event(ctx.chain_store().blockstore(), events_root, |event| {
+            chain_events.push(event.into());
+            Ok(())
+        })
+        .map_err(|e| anyhow::anyhow!("load events amt: {}", e))?;
         Ok(chain_events)
     }
 
@@ -425,6 +537,9 @@ impl EthEventHandler {
                     .chain(&ctx.store())
                     .take_while(|ts| ts.epoch() >= *range.start())
                 {
+                    if !pf.may_match_tipset(ctx, &tipset) {
+                        continue;
+                    }
                     Self::collect_events(ctx, &tipset, Some(pf), skip_event, &mut collected_events)
                         .await?;
                 }
@@ -706,6 +821,32 @@ impl ParsedFilter {
             keys,
         })
     }
+
+    /// Returns `false` if the logs bloom of the tipset shows that none of its events match. Without
+    /// a logs bloom, the events of the tipset have to be checked.
+    fn may_match_tipset<DB: Blockstore>(&self, ctx: &Ctx<DB>, tipset: &Tipset) -> bool {
+        if !ctx.chain_config().enable_indexer {
+            return true;
+        }
+        match ctx.chain_store().get_logs_bloom(tipset.key()) {
+            Ok(Some(bloom)) => self.may_match_bloom(&bloom),
+            _ => true,
+        }
+    }
+
+    fn may_match_bloom(&self, bloom: &ethereum_types::Bloom) -> bool {
+        let match_addr = self.addresses.is_empty()
+            || self
+                .addresses
+                .iter()
+                .any(|address| logs_bloom::may_contain_address(bloom, address));
+        let match_fields = self.keys.values().all(|values| {
+            values
+                .iter()
+                .any(|aeb| logs_bloom::may_contain_value(bloom, &aeb.value))
+        });
+        match_addr && match_fields
+    }
 }
 
 impl Matcher for ParsedFilter {
@@ -784,6 +925,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parsed_filter_may_match_bloom() {
+        let emitter = Address::new_id(1000);
+        let mut bloom = ethereum_types::Bloom::zero();
+        bloom.accrue(ethereum_types::BloomInput::Raw(&emitter.to_bytes()));
+        bloom.accrue(ethereum_types::BloomInput::Raw(b"transfer"));
+
+        let filter = |addresses: Vec<Address>, values: &[&str]| {
+            let mut pf = ParsedFilter::new_with_tipset(ParsedFilterTipsets::Range(0..=10));
+            pf.addresses = addresses;
+            if !values.is_empty() {
+                let blocks = values
+                    .iter()
+                    .map(|value| ActorEventBlock {
+                        codec: IPLD_RAW,
+                        value: value.as_bytes().to_vec(),
+                    })
+                    .collect();
+                pf.keys.insert("t1".into(), blocks);
+            }
+            pf
+        };
+
+        assert!(filter(vec![], &[]).may_match_bloom(&bloom));
+        assert!(filter(vec![emitter], &["transfer"]).may_match_bloom(&bloom));
+        assert!(filter(vec![Address::new_id(1), emitter], &[]).may_match_bloom(&bloom));
+        assert!(filter(vec![], &["approval", "transfer"]).may_match_bloom(&bloom));
+        assert!(!filter(vec![Address::new_id(1)], &[]).may_match_bloom(&bloom));
+        assert!(!filter(vec![emitter], &["approval"]).may_match_bloom(&bloom));
+    }
+
     #[test]
     fn test_empty_address_list() {
         let empty_list_spec = EthFilterSpec {
@@ -0,0 +1,427 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Persistence of the installed filters in the settings store, for them to survive a restart of
+//! the node. Each filter is persisted with its cursor, the epoch up to which its changes were
+//! polled, so that the first `eth_getFilterChanges` after a restart returns the changes of the
+//! epochs missed while the node was down, up to a configurable number of epochs.
+//!
+//! Each filter is stored under its own key, along with when it was last polled so that the
+//! filters whose TTL expired while the node was down are not restored. The changes are written
+//! by [`PersistentFilterStore::flush`], called periodically, rather than on every poll.
+
+use super::event::EventFilter;
+use super::mempool::MempoolFilter;
+use super::store::{Filter, FilterStore, is_expired};
+use super::tipset::TipSetFilter;
+use super::{ActorEventBlock, ParsedFilterTipsets};
+use crate::blocks::{Tipset, TipsetKey};
+use crate::chain::ChainStore;
+use crate::chain::index::ResolveNullTipset;
+use crate::db::{SettingsStore, SettingsStoreExt as _, setting_keys::ETH_FILTERS_KEY};
+use crate::rpc::eth::{EthHash, FilterID};
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use ahash::AHashMap as HashMap;
+use anyhow::Result;
+use fvm_ipld_blockstore::Blockstore;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedFilter {
+    pub id: FilterID,
+    pub kind: PersistedFilterKind,
+    /// Epoch up to which the changes of the filter were polled
+    pub cursor: Option<ChainEpoch>,
+    /// Key of the tipset at the cursor, to tell whether it was reverted while the node was down
+    #[serde(with = "crate::lotus_json")]
+    pub cursor_tipset: Option<TipsetKey>,
+}
+
+/// A persisted filter with the Unix timestamp, in seconds, it was last polled at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PersistedFilterEntry {
+    filter: PersistedFilter,
+    last_used: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PersistedFilterKind {
+    Event(PersistedEventFilter),
+    TipSet,
+    Mempool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedEventFilter {
+    pub(crate) tipsets: PersistedFilterTipsets,
+    #[serde(with = "crate::lotus_json")]
+    pub(crate) addresses: Vec<Address>,
+    pub(crate) keys: BTreeMap<String, Vec<ActorEventBlock>>,
+}
+
+/// A filter restored after a restart, to be added back to its manager and to the filter store
+pub enum RestoredFilter {
+    Event(Arc<EventFilter>),
+    TipSet(Arc<TipSetFilter>),
+    Mempool(Arc<MempoolFilter>),
+}
+
+impl RestoredFilter {
+    pub fn filter(&self) -> Arc<dyn Filter> {
+        match self {
+            Self::Event(filter) => filter.clone(),
+            Self::TipSet(filter) => filter.clone(),
+            Self::Mempool(filter) => filter.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PersistedFilterTipsets {
+    Range { from: ChainEpoch, to: ChainEpoch },
+    Hash(EthHash),
+    Key(#[serde(with = "crate::lotus_json")] TipsetKey),
+}
+
+impl From<&ParsedFilterTipsets> for PersistedFilterTipsets {
+    fn from(tipsets: &ParsedFilterTipsets) -> Self {
+        match tipsets {
+            ParsedFilterTipsets::Range(range) => Self::Range {
+                from: *range.start(),
+                to: *range.end(),
+            },
+            ParsedFilterTipsets::Hash(hash) => Self::Hash(hash.clone()),
+            ParsedFilterTipsets::Key(tsk) => Self::Key(tsk.clone()),
+        }
+    }
+}
+
+impl From<PersistedFilterTipsets> for ParsedFilterTipsets {
+    fn from(tipsets: PersistedFilterTipsets) -> Self {
+        match tipsets {
+            PersistedFilterTipsets::Range { from, to } => Self::Range(from..=to),
+            PersistedFilterTipsets::Hash(hash) => Self::Hash(hash),
+            PersistedFilterTipsets::Key(tsk) => Self::Key(tsk),
+        }
+    }
+}
+
+fn filter_key(id: &FilterID) -> String {
+    format!("{ETH_FILTERS_KEY}/{id}")
+}
+
+impl PersistedFilter {
+    /// Loads the persisted filters with when they were last polled, deleting the ones not
+    /// polled for `ttl`.
+    pub fn load(store: &dyn SettingsStore, ttl: Duration) -> Result<Vec<(Self, SystemTime)>> {
+        let prefix = format!("{ETH_FILTERS_KEY}/");
+        let now = SystemTime::now();
+        let mut filters = vec![];
+        for key in store.setting_keys()? {
+            if !key.starts_with(&prefix) {
+                continue;
+            }
+            let Some(PersistedFilterEntry { filter, last_used }) = store.read_obj(&key)? else {
+                continue;
+            };
+            let last_used = SystemTime::UNIX_EPOCH + Duration::from_secs(last_used);
+            if is_expired(last_used, now, ttl) {
+                store.delete(&key)?;
+            } else {
+                filters.push((filter, last_used));
+            }
+        }
+        Ok(filters)
+    }
+
+    pub fn save(&self, last_used: SystemTime, store: &dyn SettingsStore) -> Result<()> {
+        let last_used = last_used
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        store.write_obj(
+            &filter_key(&self.id),
+            &PersistedFilterEntry {
+                filter: self.clone(),
+                last_used,
+            },
+        )
+    }
+
+    pub fn delete(id: &FilterID, store: &dyn SettingsStore) -> Result<()> {
+        store.delete(&filter_key(id))
+    }
+
+    /// Restores the filter on the chain of `head`. The cursor is rewound past the tipsets
+    /// reverted since it was persisted, and moved forward to `min_epoch` at least, the oldest
+    /// epoch whose changes are replayed.
+    pub fn restore<DB: Blockstore>(
+        self,
+        chain_store: &ChainStore<DB>,
+        head: &Tipset,
+        min_epoch: ChainEpoch,
+        max_results: usize,
+    ) -> RestoredFilter {
+        let cursor = self.cursor.map(|cursor| {
+            rewind_cursor(
+                chain_store,
+                head,
+                cursor,
+                self.cursor_tipset.as_ref(),
+                min_epoch,
+            )
+        });
+        match self.kind {
+            PersistedFilterKind::Event(PersistedEventFilter {
+                tipsets,
+                addresses,
+                keys,
+            }) => RestoredFilter::Event(Arc::new(EventFilter {
+                id: self.id,
+                tipsets: tipsets.into(),
+                addresses,
+                keys_with_codec: keys.into_iter().collect(),
+                max_results,
+                collected: vec![],
+                cursor,
+            })),
+            PersistedFilterKind::TipSet => RestoredFilter::TipSet(Arc::new(TipSetFilter {
+                id: self.id,
+                max_results,
+                collected: cursor,
+            })),
+            PersistedFilterKind::Mempool => RestoredFilter::Mempool(Arc::new(MempoolFilter {
+                id: self.id,
+                max_results,
+                collected: cursor,
+            })),
+        }
+    }
+}
+
+/// Returns the most recent epoch, not after `cursor`, whose tipset is still on the chain of
+/// `head`, and no older than `min_epoch`.
+fn rewind_cursor<DB: Blockstore>(
+    chain_store: &ChainStore<DB>,
+    head: &Tipset,
+    cursor: ChainEpoch,
+    cursor_tipset: Option<&TipsetKey>,
+    min_epoch: ChainEpoch,
+) -> ChainEpoch {
+    let cursor = cursor.min(head.epoch());
+    let Some(tsk) = cursor_tipset else {
+        return cursor.max(min_epoch);
+    };
+    let chain_index = chain_store.chain_index();
+    let Ok(Some(ts)) = chain_index.load_tipset(tsk) else {
+        return min_epoch;
+    };
+    chain_index
+        .chain(ts)
+        .take_while(|ts| ts.epoch() >= min_epoch)
+        .filter(|ts| ts.epoch() <= head.epoch())
+        .find(|ts| {
+            chain_index
+                .tipset_by_height(ts.epoch(), head.clone(), ResolveNullTipset::TakeOlder)
+                .is_ok_and(|on_chain| on_chain.key() == ts.key())
+        })
+        .map(|ts| ts.epoch())
+        .unwrap_or(min_epoch)
+}
+
+/// A [`FilterStore`] persisting the installed filters in the settings store. The filters added,
+/// polled or removed are recorded and written on the next [`FilterStore::flush`], so that a
+/// filter polled several times in between is written once.
+pub struct PersistentFilterStore {
+    inner: Arc<dyn FilterStore>,
+    settings: Arc<dyn SettingsStore + Sync + Send>,
+    /// Filters changed since the last flush, `None` for the removed ones
+    pending: Mutex<HashMap<FilterID, Option<PersistedFilter>>>,
+    /// Serializes the flushes, for a filter not to be overwritten with an older state
+    flush_lock: Mutex<()>,
+}
+
+impl PersistentFilterStore {
+    pub fn new(
+        inner: Arc<dyn FilterStore>,
+        settings: Arc<dyn SettingsStore + Sync + Send>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            inner,
+            settings,
+            pending: Mutex::new(HashMap::new()),
+            flush_lock: Mutex::new(()),
+        })
+    }
+
+    fn changed(&self, filter: &dyn Filter) {
+        if let Some(persisted) = filter.persisted() {
+            self.pending
+                .lock()
+                .insert(filter.id().clone(), Some(persisted));
+        }
+    }
+}
+
+impl FilterStore for PersistentFilterStore {
+    fn insert(&self, filter: Arc<dyn Filter>, last_used: SystemTime) -> Result<()> {
+        self.inner.insert(filter.clone(), last_used)?;
+        self.changed(filter.as_ref());
+        Ok(())
+    }
+
+    fn get(&self, id: &FilterID) -> Result<Arc<dyn Filter>> {
+        self.inner.get(id)
+    }
+
+    fn update(&self, filter: Arc<dyn Filter>) {
+        self.inner.update(filter.clone());
+        self.changed(filter.as_ref());
+    }
+
+    fn remove(&self, id: &FilterID) -> Option<Arc<dyn Filter>> {
+        let removed = self.inner.remove(id);
+        if removed.is_some() {
+            self.pending.lock().insert(id.clone(), None);
+        }
+        removed
+    }
+
+    fn filters(&self) -> Vec<Arc<dyn Filter>> {
+        self.inner.filters()
+    }
+
+    fn last_used(&self, id: &FilterID) -> Option<SystemTime> {
+        self.inner.last_used(id)
+    }
+
+    fn flush(&self) {
+        let _guard = self.flush_lock.lock();
+        let pending = std::mem::take(&mut *self.pending.lock());
+        for (id, filter) in pending {
+            let result = match filter {
+                Some(filter) => filter.save(
+                    self.last_used(&id).unwrap_or_else(SystemTime::now),
+                    self.settings.as_ref(),
+                ),
+                None => PersistedFilter::delete(&id, self.settings.as_ref()),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to persist the filter {id}: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{CachingBlockHeader, Chain4U, chain4u};
+    use crate::db::MemoryDB;
+    use crate::networks::ChainConfig;
+    use crate::rpc::eth::filter::store::MemFilterStore;
+
+    const TTL: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn persist_and_restore_filters() {
+        let db = Arc::new(MemoryDB::default());
+        let c4u = Chain4U::with_blockstore(db.clone());
+        chain4u! {
+            in c4u;
+            [genesis] -> [b_1] -> [b_2] -> [b_3] -> head @ [b_4]
+        };
+        chain4u! {
+            from [b_2] in c4u;
+            [fork_3] -> fork_head @ [fork_4]
+        };
+        let cs = ChainStore::new(
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            Arc::new(ChainConfig::default()),
+            CachingBlockHeader::new(genesis.clone()),
+        )
+        .unwrap();
+
+        let store = PersistentFilterStore::new(MemFilterStore::new(10), db.clone());
+        let event_filter = Arc::new(EventFilter {
+            id: FilterID::new().unwrap(),
+            tipsets: ParsedFilterTipsets::Range(0..=-1),
+            addresses: vec![Address::new_id(1234)],
+            keys_with_codec: HashMap::new(),
+            max_results: 10,
+            collected: vec![],
+            cursor: None,
+        });
+        let tipset_filter = Arc::new(TipSetFilter {
+            id: FilterID::new().unwrap(),
+            max_results: 10,
+            collected: Some(1),
+        });
+        store.add(event_filter.clone()).unwrap();
+        store.add(tipset_filter.clone()).unwrap();
+        // The filters are only written on flush
+        assert!(PersistedFilter::load(db.as_ref(), TTL).unwrap().is_empty());
+        store.flush();
+        let persisted = PersistedFilter::load(db.as_ref(), TTL).unwrap();
+        assert_eq!(persisted.len(), 2);
+
+        // The cursor of a filter polled on a reverted tipset is rewound to the fork point
+        let mut persisted = event_filter.persisted().unwrap();
+        persisted.cursor = Some(fork_4.epoch);
+        persisted.cursor_tipset = Some(fork_head.key().clone());
+        let RestoredFilter::Event(restored) = persisted.restore(&cs, head, 0, 10) else {
+            panic!("an event filter is expected");
+        };
+        assert_eq!(restored.id, event_filter.id);
+        assert_eq!(restored.addresses, event_filter.addresses);
+        assert_eq!(restored.cursor, Some(b_2.epoch));
+
+        // and moved forward to the oldest epoch replayed
+        let RestoredFilter::TipSet(restored) =
+            tipset_filter.persisted().unwrap().restore(&cs, head, 3, 10)
+        else {
+            panic!("a tipset filter is expected");
+        };
+        assert_eq!(restored.collected, Some(3));
+
+        store.remove(&event_filter.id).unwrap();
+        store.flush();
+        let persisted = PersistedFilter::load(db.as_ref(), TTL).unwrap();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].0.id, tipset_filter.id);
+    }
+
+    #[test]
+    fn expired_filters_are_not_restored() {
+        let db = Arc::new(MemoryDB::default());
+        let store = PersistentFilterStore::new(MemFilterStore::new(10), db.clone());
+        let stale = Arc::new(TipSetFilter {
+            id: FilterID::new().unwrap(),
+            max_results: 10,
+            collected: None,
+        });
+        let fresh = Arc::new(TipSetFilter {
+            id: FilterID::new().unwrap(),
+            max_results: 10,
+            collected: None,
+        });
+        store
+            .insert(stale.clone(), SystemTime::now() - 2 * TTL)
+            .unwrap();
+        store.add(fresh.clone()).unwrap();
+        store.flush();
+
+        let persisted = PersistedFilter::load(db.as_ref(), TTL).unwrap();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].0.id, fresh.id);
+        // and the expired filter is deleted from the store
+        assert!(!SettingsStore::exists(db.as_ref(), &filter_key(&stale.id)).unwrap());
+    }
+}
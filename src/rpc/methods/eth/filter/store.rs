@@ -1,6 +1,7 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use super::persist::PersistedFilter;
 use crate::rpc::Arc;
 use crate::rpc::eth::FilterID;
 use ahash::AHashMap as HashMap;
@@ -8,6 +9,7 @@ use anyhow::Result;
 use anyhow::anyhow;
 use parking_lot::RwLock;
 use std::any::Any;
+use std::time::{Duration, SystemTime};
 
 /// This trait should be implemented by any filter that needs to be identified
 /// and managed. It provide methods to retrieve the unique identifier for
@@ -15,20 +17,56 @@ use std::any::Any;
 pub trait Filter: Send + Sync + std::fmt::Debug {
     fn id(&self) -> &FilterID;
     fn as_any(&self) -> &dyn Any;
+    /// Returns the form of the filter persisted across restarts, if it is persisted
+    fn persisted(&self) -> Option<PersistedFilter> {
+        None
+    }
 }
 
 /// The `FilterStore` trait provides the necessary interface for storing and managing filters.
 pub trait FilterStore: Send + Sync {
-    fn add(&self, filter: Arc<dyn Filter>) -> Result<()>;
+    fn add(&self, filter: Arc<dyn Filter>) -> Result<()> {
+        self.insert(filter, SystemTime::now())
+    }
+    /// Adds a filter last polled at `last_used`, e.g. a filter restored after a restart.
+    fn insert(&self, filter: Arc<dyn Filter>, last_used: SystemTime) -> Result<()>;
     fn get(&self, id: &FilterID) -> Result<Arc<dyn Filter>>;
     fn update(&self, filter: Arc<dyn Filter>);
     fn remove(&self, id: &FilterID) -> Option<Arc<dyn Filter>>;
+    fn filters(&self) -> Vec<Arc<dyn Filter>>;
+    /// Returns when the filter was last added, polled or updated.
+    fn last_used(&self, id: &FilterID) -> Option<SystemTime>;
+    /// Returns the filters not polled for `ttl`, to be uninstalled.
+    fn expired(&self, ttl: Duration) -> Vec<Arc<dyn Filter>> {
+        let now = SystemTime::now();
+        self.filters()
+            .into_iter()
+            .filter(|filter| {
+                self.last_used(filter.id())
+                    .is_some_and(|last_used| is_expired(last_used, now, ttl))
+            })
+            .collect()
+    }
+    /// Writes the changes made since the last flush, if the store is persistent.
+    fn flush(&self) {}
+}
+
+/// Returns whether a filter last used at `last_used` has expired at `now`.
+pub fn is_expired(last_used: SystemTime, now: SystemTime, ttl: Duration) -> bool {
+    now.duration_since(last_used)
+        .is_ok_and(|elapsed| elapsed > ttl)
+}
+
+#[derive(Debug)]
+struct StoredFilter {
+    filter: Arc<dyn Filter>,
+    last_used: SystemTime,
 }
 
 #[derive(Debug)]
 pub struct MemFilterStore {
     max: usize,
-    filters: RwLock<HashMap<FilterID, Arc<dyn Filter>>>,
+    filters: RwLock<HashMap<FilterID, StoredFilter>>,
 }
 
 impl MemFilterStore {
@@ -41,7 +79,7 @@ impl MemFilterStore {
 }
 
 impl FilterStore for MemFilterStore {
-    fn add(&self, filter: Arc<dyn Filter>) -> Result<()> {
+    fn insert(&self, filter: Arc<dyn Filter>, last_used: SystemTime) -> Result<()> {
         let mut filters = self.filters.write();
 
         if filters.len() == self.max {
@@ -50,27 +88,46 @@ impl FilterStore for MemFilterStore {
         if filters.contains_key(filter.id()) {
             return Err(anyhow::Error::msg("Filter already registered"));
         }
-        filters.insert(filter.id().clone(), filter);
+        filters.insert(filter.id().clone(), StoredFilter { filter, last_used });
         Ok(())
     }
 
     fn get(&self, id: &FilterID) -> Result<Arc<dyn Filter>> {
-        let filters = self.filters.read();
-        filters
-            .get(id)
-            .cloned()
-            .ok_or_else(|| anyhow!("filter not found"))
+        let mut filters = self.filters.write();
+        let stored = filters
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("filter not found"))?;
+        stored.last_used = SystemTime::now();
+        Ok(stored.filter.clone())
     }
 
     fn update(&self, filter: Arc<dyn Filter>) {
         let mut filters = self.filters.write();
 
-        filters.insert(filter.id().clone(), filter);
+        filters.insert(
+            filter.id().clone(),
+            StoredFilter {
+                filter,
+                last_used: SystemTime::now(),
+            },
+        );
     }
 
     fn remove(&self, id: &FilterID) -> Option<Arc<dyn Filter>> {
         let mut filters = self.filters.write();
-        filters.remove(id)
+        filters.remove(id).map(|stored| stored.filter)
+    }
+
+    fn filters(&self) -> Vec<Arc<dyn Filter>> {
+        self.filters
+            .read()
+            .values()
+            .map(|stored| stored.filter.clone())
+            .collect()
+    }
+
+    fn last_used(&self, id: &FilterID) -> Option<SystemTime> {
+        self.filters.read().get(id).map(|stored| stored.last_used)
     }
 }
 
@@ -122,4 +179,29 @@ mod tests {
         });
         assert!(store.add(filter3.clone()).is_err());
     }
+
+    #[test]
+    fn test_expired_filters() {
+        let store = MemFilterStore::new(2);
+        let ttl = Duration::from_secs(60);
+
+        let stale = Arc::new(TestFilter {
+            id: FilterID::new().unwrap(),
+        });
+        let fresh = Arc::new(TestFilter {
+            id: FilterID::new().unwrap(),
+        });
+        store
+            .insert(stale.clone(), SystemTime::now() - 2 * ttl)
+            .unwrap();
+        store.add(fresh.clone()).unwrap();
+
+        let expired = store.expired(ttl);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id(), &stale.id);
+
+        // Polling a filter keeps it alive
+        store.get(&stale.id).unwrap();
+        assert!(store.expired(ttl).is_empty());
+    }
 }
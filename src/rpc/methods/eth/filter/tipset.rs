@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::rpc::Arc;
+use crate::rpc::eth::filter::persist::{PersistedFilter, PersistedFilterKind};
 use crate::rpc::eth::{FilterID, filter::Filter, filter::FilterManager};
 use crate::shim::fvm_shared_latest::clock::ChainEpoch;
 use ahash::AHashMap as HashMap;
@@ -39,6 +40,15 @@ impl Filter for TipSetFilter {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn persisted(&self) -> Option<PersistedFilter> {
+        Some(PersistedFilter {
+            id: self.id.clone(),
+            kind: PersistedFilterKind::TipSet,
+            cursor: self.collected,
+            cursor_tipset: None,
+        })
+    }
 }
 
 /// The `TipSetFilterManager` structure maintains a set of filters that operate on TipSets,
@@ -75,6 +85,10 @@ impl FilterManager for TipSetFilterManager {
         let mut filters = self.filters.write();
         filters.remove(id)
     }
+
+    fn restore(&self, filter: Arc<dyn Filter>) {
+        self.filters.write().insert(filter.id().clone(), filter);
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,131 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! The signer mode, an opt-in bridge that lets FEVM development tools, like Hardhat, use the
+//! Forest wallet as their signing backend. It is enabled with `FOREST_ETH_SIGNER=1`, then:
+//! - `eth_accounts` lists the delegated addresses of the wallet, and requires an admin token;
+//! - `eth_sendTransaction` signs the given transaction with the key of its sender, and pushes it
+//!   to the message pool. It always requires an admin token.
+
+use anyhow::{Context as _, ensure};
+
+use super::types::EthAddress;
+use crate::eth::{EthChainId, EthEip1559TxArgsBuilder};
+use crate::key_management::{KeyInfo, KeyStore, list_addrs};
+use crate::message::SignedMessage;
+use crate::shim::address::Protocol;
+use crate::shim::crypto::SignatureType;
+use crate::shim::message::Message;
+use crate::utils::misc::env::is_env_truthy;
+
+/// Returns `true` if the signer mode is enabled.
+pub fn is_enabled() -> bool {
+    is_env_truthy("FOREST_ETH_SIGNER")
+}
+
+pub fn ensure_enabled() -> anyhow::Result<()> {
+    ensure!(
+        is_enabled(),
+        "the signer mode is disabled, set FOREST_ETH_SIGNER=1 to enable it"
+    );
+    Ok(())
+}
+
+/// Returns the Ethereum addresses of the delegated keys of the wallet.
+pub fn accounts(keystore: &KeyStore) -> anyhow::Result<Vec<EthAddress>> {
+    list_addrs(keystore)?
+        .iter()
+        .filter(|addr| addr.protocol() == Protocol::Delegated)
+        .map(EthAddress::from_filecoin_address)
+        .collect()
+}
+
+/// Signs a message from a delegated address as an EIP-1559 transaction, which is how Ethereum
+/// accounts sign their messages.
+pub fn sign_message(
+    key_info: &KeyInfo,
+    message: &Message,
+    eth_chain_id: EthChainId,
+) -> anyhow::Result<SignedMessage> {
+    ensure!(
+        *key_info.key_type() == SignatureType::Delegated,
+        "{} is not the address of a delegated key",
+        message.from
+    );
+    let tx = EthEip1559TxArgsBuilder::default()
+        .chain_id(eth_chain_id)
+        .unsigned_message(message)?
+        .build()
+        .context("failed to build the transaction")?;
+    let signature = crate::key_management::sign(
+        SignatureType::Delegated,
+        key_info.private_key(),
+        &tx.rlp_unsigned_message()?,
+    )?;
+    tx.with_signature(&signature)?
+        .get_signed_message(message.from, eth_chain_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::{EVMMethod, EthTx, parse_eth_transaction};
+    use crate::key_management::{Key, KeyStoreConfig, generate_key};
+    use crate::message::Message as _;
+    use crate::networks::calibnet;
+    use crate::rpc::eth::types::EthCallMessage;
+    use crate::shim::econ::TokenAmount;
+    use std::str::FromStr as _;
+
+    fn import_key(keystore: &mut KeyStore, sig_type: SignatureType) -> Key {
+        let key = generate_key(sig_type).unwrap();
+        keystore
+            .put(&format!("wallet-{}", key.address), key.key_info.clone())
+            .unwrap();
+        key
+    }
+
+    #[test]
+    fn signer_accounts() {
+        let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        import_key(&mut keystore, SignatureType::Secp256k1);
+        import_key(&mut keystore, SignatureType::Bls);
+        let delegated = import_key(&mut keystore, SignatureType::Delegated);
+        assert_eq!(
+            accounts(&keystore).unwrap(),
+            vec![EthAddress::from_filecoin_address(&delegated.address).unwrap()]
+        );
+    }
+
+    #[test]
+    fn signer_sign_message() {
+        let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let key = import_key(&mut keystore, SignatureType::Delegated);
+        let mut message = Message::try_from(EthCallMessage {
+            from: Some(EthAddress::from_filecoin_address(&key.address).unwrap()),
+            to: Some(EthAddress::from_str("0xeb4a9cdb9f42d3a503d580a39b6e3736eb21fffd").unwrap()),
+            data: Some(vec![0x38, 0x34, 0x87, 0xbe].into()),
+            ..Default::default()
+        })
+        .unwrap();
+        message.sequence = 7;
+        message.gas_limit = 1_000_000;
+        message.gas_fee_cap = TokenAmount::from_atto(150);
+        message.gas_premium = TokenAmount::from_atto(100);
+        assert_eq!(message.method_num(), EVMMethod::InvokeContract as u64);
+
+        let smsg = sign_message(&key.key_info, &message, calibnet::ETH_CHAIN_ID).unwrap();
+        assert_eq!(smsg.message(), &message);
+
+        // The signature of the transaction recovers to the signing address.
+        let tx = EthTx::from_signed_message(calibnet::ETH_CHAIN_ID, &smsg).unwrap();
+        let recovered = parse_eth_transaction(&tx.rlp_signed_message().unwrap())
+            .unwrap()
+            .get_signed_message(calibnet::ETH_CHAIN_ID)
+            .unwrap();
+        assert_eq!(recovered, smsg);
+
+        let secp = import_key(&mut keystore, SignatureType::Secp256k1);
+        assert!(sign_message(&secp.key_info, &message, calibnet::ETH_CHAIN_ID).is_err());
+    }
+}
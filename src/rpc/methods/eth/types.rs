@@ -3,6 +3,9 @@
 
 use super::*;
 use crate::rpc::eth::pubsub_trait::LogFilter;
+use crate::shim::address::DelegatedNamespace;
+use crate::state_manager::StorageOverride;
+use ahash::HashMap;
 use anyhow::ensure;
 use get_size2::GetSize;
 use ipld_core::serde::SerdeError;
@@ -154,7 +157,7 @@ impl EthAddress {
                 let result: Result<DelegatedAddress, _> = payload.try_into();
                 if let Ok(f4_addr) = result {
                     let namespace = f4_addr.namespace();
-                    if namespace != FilecoinAddress::ETHEREUM_ACCOUNT_MANAGER_ACTOR.id()? {
+                    if namespace != DelegatedNamespace::EAM.actor_id {
                         bail!("invalid address {addr}");
                     }
                     let eth_addr: EthAddress = f4_addr.subaddress().try_into()?;
@@ -298,6 +301,14 @@ pub struct GasReward {
     pub premium: TokenAmount,
 }
 
+/// An entry of the access list of an EIP-2930 or EIP-1559 transaction.
+#[derive(PartialEq, Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthAccessListEntry {
+    pub address: EthAddress,
+    pub storage_keys: Vec<EthHash>,
+}
+
 #[derive(PartialEq, Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EthCallMessage {
@@ -312,14 +323,39 @@ pub struct EthCallMessage {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub gas_price: Option<EthBigInt>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_fee_per_gas: Option<EthBigInt>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_priority_fee_per_gas: Option<EthBigInt>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     pub value: Option<EthBigInt>,
     // Some clients use `input`, others use `data`. We have to support both.
     #[serde(alias = "input", skip_serializing_if = "Option::is_none", default)]
     pub data: Option<EthBytes>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub r#type: Option<EthUint64>,
+    // The FEVM has no warm storage, access lists are accepted but don't affect the execution.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub access_list: Option<Vec<EthAccessListEntry>>,
 }
 lotus_json_with_self!(EthCallMessage);
 
 impl EthCallMessage {
+    /// Checks that the fee cap isn't below the premium, as Lotus does for messages. Like in
+    /// Lotus, the fees themselves and the transaction type don't affect calls and gas estimations.
+    pub fn validate_fees(&self) -> anyhow::Result<()> {
+        if let (Some(max_fee), Some(max_priority_fee)) =
+            (&self.max_fee_per_gas, &self.max_priority_fee_per_gas)
+        {
+            ensure!(
+                max_priority_fee.0 <= max_fee.0,
+                "maxFeePerGas ({}) less than maxPriorityFeePerGas ({})",
+                max_fee.0,
+                max_priority_fee.0
+            );
+        }
+        Ok(())
+    }
+
     pub fn convert_data_to_message_params(data: EthBytes) -> anyhow::Result<RawBytes> {
         if data.0.is_empty() {
             Ok(RawBytes::new(data.0))
@@ -334,6 +370,7 @@ impl EthCallMessage {
 impl TryFrom<EthCallMessage> for Message {
     type Error = anyhow::Error;
     fn try_from(tx: EthCallMessage) -> Result<Self, Self::Error> {
+        tx.validate_fees()?;
         let from = match &tx.from {
             Some(addr) if addr != &EthAddress::default() => {
                 // The from address must be translatable to an f4 address.
@@ -376,6 +413,64 @@ impl TryFrom<EthCallMessage> for Message {
     }
 }
 
+/// Overrides of the state of an account for the duration of an `eth_call`, in the format of
+/// the `stateOverrides` parameter of Geth.
+#[derive(PartialEq, Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthAccountOverride {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub balance: Option<EthBigInt>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub nonce: Option<EthUint64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub code: Option<EthBytes>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state: Option<HashMap<EthHash, EthHash>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state_diff: Option<HashMap<EthHash, EthHash>>,
+}
+lotus_json_with_self!(EthAccountOverride);
+
+#[derive(PartialEq, Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EthStateOverrides(pub HashMap<EthAddress, EthAccountOverride>);
+lotus_json_with_self!(EthStateOverrides);
+
+impl TryFrom<EthStateOverrides> for StateOverrides {
+    type Error = anyhow::Error;
+    fn try_from(overrides: EthStateOverrides) -> Result<Self, Self::Error> {
+        overrides
+            .0
+            .into_iter()
+            .map(|(address, account)| {
+                let address = address.to_filecoin_address()?;
+                let slots = |slots: HashMap<EthHash, EthHash>| {
+                    slots
+                        .into_iter()
+                        .map(|(slot, value)| (slot.0.0, value.0.0))
+                        .collect()
+                };
+                let storage = match (account.state, account.state_diff) {
+                    (Some(_), Some(_)) => {
+                        bail!("both the state and the state diff of {address} are overridden")
+                    }
+                    (Some(state), None) => Some(StorageOverride::Replace(slots(state))),
+                    (None, Some(state_diff)) => Some(StorageOverride::Diff(slots(state_diff))),
+                    (None, None) => None,
+                };
+                Ok((
+                    address,
+                    ActorStateOverride {
+                        balance: account.balance.map(|balance| balance.0.into()),
+                        sequence: account.nonce.map(|nonce| nonce.0),
+                        code: account.code.map(|code| code.0),
+                        storage,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
 #[derive(
     PartialEq,
     Eq,
@@ -401,7 +496,9 @@ impl GetSize for EthHash {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+#[derive(
+    Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Clone, derive_more::Display,
+)]
 pub struct FilterID(EthHash);
 
 lotus_json_with_self!(FilterID);
@@ -570,7 +667,9 @@ impl PartialEq for EthFilterResult {
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[derive(
+    Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema, GetSize,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EthCallTraceAction {
     pub call_type: String,
@@ -581,7 +680,9 @@ pub struct EthCallTraceAction {
     pub input: EthBytes,
 }
 
-#[derive(Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[derive(
+    Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema, GetSize,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EthCreateTraceAction {
     pub from: EthAddress,
@@ -590,7 +691,7 @@ pub struct EthCreateTraceAction {
     pub init: EthBytes,
 }
 
-#[derive(Eq, Hash, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema, GetSize)]
 #[serde(untagged)]
 pub enum TraceAction {
     Call(EthCallTraceAction),
@@ -603,14 +704,18 @@ impl Default for TraceAction {
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[derive(
+    Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema, GetSize,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EthCallTraceResult {
     pub gas_used: EthUint64,
     pub output: EthBytes,
 }
 
-#[derive(Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[derive(
+    Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema, GetSize,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EthCreateTraceResult {
     pub address: Option<EthAddress>,
@@ -618,7 +723,7 @@ pub struct EthCreateTraceResult {
     pub code: EthBytes,
 }
 
-#[derive(Eq, Hash, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema, GetSize)]
 #[serde(untagged)]
 pub enum TraceResult {
     Call(EthCallTraceResult),
@@ -631,7 +736,9 @@ impl Default for TraceResult {
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[derive(
+    Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema, GetSize,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EthTrace {
     pub r#type: String,
@@ -643,7 +750,9 @@ pub struct EthTrace {
     pub error: Option<String>,
 }
 
-#[derive(Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[derive(
+    Eq, Hash, PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema, GetSize,
+)]
 #[serde(rename_all = "camelCase")]
 pub struct EthBlockTrace {
     #[serde(flatten)]
@@ -752,6 +861,7 @@ impl EthTrace {
 mod tests {
     use super::*;
     use base64::{Engine as _, prelude::BASE64_STANDARD};
+    use std::collections::BTreeMap;
 
     #[test]
     fn get_bytecode_return_roundtrip() {
@@ -788,6 +898,45 @@ mod tests {
         assert_eq!(BASE64_STANDARD.encode(&*params).as_str(), "RUR7eINB");
     }
 
+    #[test]
+    fn test_eth_call_message_validate_fees() {
+        let call: EthCallMessage = serde_json::from_value(serde_json::json!({
+            "to": "0xeb4a9cdb9f42d3a503d580a39b6e3736eb21fffd",
+            "type": "0x2",
+            "maxFeePerGas": "0x59682f78",
+            "maxPriorityFeePerGas": "0x59682f00",
+            "accessList": [{
+                "address": "0xeb4a9cdb9f42d3a503d580a39b6e3736eb21fffd",
+                "storageKeys": []
+            }],
+        }))
+        .unwrap();
+        call.validate_fees().unwrap();
+        assert!(Message::try_from(call.clone()).is_ok());
+
+        // Like Lotus, the transaction type and the legacy gas price are ignored
+        let ignored = [
+            EthCallMessage {
+                gas_price: Some(EthBigInt(1.into())),
+                ..call.clone()
+            },
+            EthCallMessage {
+                r#type: Some(EthUint64(3)),
+                ..call.clone()
+            },
+        ];
+        for call in ignored {
+            call.validate_fees().unwrap();
+        }
+
+        let invalid = EthCallMessage {
+            max_priority_fee_per_gas: Some(EthBigInt(1500000121.into())),
+            ..call
+        };
+        assert!(invalid.validate_fees().is_err());
+        assert!(Message::try_from(invalid).is_err());
+    }
+
     #[test]
     fn test_eth_address_from_pub_key() {
         // Uncompressed pub key secp256k1)
@@ -804,4 +953,52 @@ mod tests {
         let result = EthAddress::eth_address_from_pub_key(&pubkey).unwrap();
         assert_eq!(result, expected_eth_address);
     }
+
+    #[test]
+    fn test_state_overrides() {
+        let address = "0xeb1d0c87b7e33d0ab44a397b675f0897295491c2";
+        let overrides: EthStateOverrides = serde_json::from_value(serde_json::json!({
+            address: { "balance": "0x64", "nonce": "0x2" }
+        }))
+        .unwrap();
+        let overrides = StateOverrides::try_from(overrides).unwrap();
+        assert_eq!(
+            overrides,
+            StateOverrides::from([(
+                EthAddress::from_str(address)
+                    .unwrap()
+                    .to_filecoin_address()
+                    .unwrap(),
+                ActorStateOverride {
+                    balance: Some(TokenAmount::from_atto(100)),
+                    sequence: Some(2),
+                    ..Default::default()
+                },
+            )])
+        );
+
+        let slot = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let overrides: EthStateOverrides = serde_json::from_value(serde_json::json!({
+            address: { "code": "0x6080", "stateDiff": { slot: slot } }
+        }))
+        .unwrap();
+        let overrides = StateOverrides::try_from(overrides).unwrap();
+        let mut one = [0; 32];
+        one[31] = 1;
+        assert_eq!(
+            overrides.into_values().next().unwrap(),
+            ActorStateOverride {
+                code: Some(vec![0x60, 0x80]),
+                storage: Some(StorageOverride::Diff(BTreeMap::from([(one, one)]))),
+                ..Default::default()
+            }
+        );
+
+        // The state replaces the storage, the state diff updates it, they can't be combined.
+        let overrides: EthStateOverrides = serde_json::from_value(serde_json::json!({
+            address: { "state": { slot: slot }, "stateDiff": { slot: slot } }
+        }))
+        .unwrap();
+        assert!(StateOverrides::try_from(overrides).is_err());
+    }
 }
@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::types::{EthAddress, EthBytes};
+use crate::chain::ChainStore;
 use crate::rpc::state::{MessageTrace, ReturnTrace};
-use crate::shim::address::Address as FilecoinAddress;
+use crate::shim::address::{Address as FilecoinAddress, Protocol};
 use crate::shim::fvm_shared_latest::IDENTITY_HASH;
-use crate::shim::state_tree::StateTree;
+use crate::shim::state_tree::{ActorState, StateTree};
 use ahash::{HashMap, HashMapExt};
 
 use crate::rpc::eth::{EVM_WORD_LENGTH, EthUint64};
@@ -59,6 +60,33 @@ pub fn lookup_eth_address<DB: Blockstore>(
     Ok(Some(EthAddress::from_actor_id(id_addr)))
 }
 
+/// Gets the actor of an Ethereum address in the given state. The actor IDs of delegated addresses
+/// are indexed in the chain store as the tipset states are computed, so that the lookups read the
+/// actor directly instead of resolving its address through the address map of the init actor.
+pub fn get_eth_actor<DB: Blockstore>(
+    chain_store: &ChainStore<DB>,
+    state: &StateTree<impl Blockstore>,
+    eth_address: &EthAddress,
+) -> Result<Option<ActorState>> {
+    let addr = eth_address.to_filecoin_address()?;
+    if addr.protocol() != Protocol::Delegated
+        || matches!(state, StateTree::FvmV2(_) | StateTree::V0(_))
+    {
+        return state.get_actor(&addr);
+    }
+
+    // The indexed ID is only used if its actor still has the delegated address, it might not
+    // exist yet in older states or have been assigned to another address by a reorg.
+    if let Some(id) = chain_store.get_eth_address_actor_id(eth_address)?
+        && let Some(actor) = state.get_actor(&FilecoinAddress::new_id(id))?
+        && actor.delegated_address.as_ref().map(FilecoinAddress::from) == Some(addr)
+    {
+        return Ok(Some(actor));
+    }
+
+    state.get_actor(&addr)
+}
+
 /// Decodes the payload using the given codec.
 pub fn decode_payload(payload: &RawBytes, codec: u64) -> Result<EthBytes> {
     match codec {
@@ -552,7 +552,7 @@ impl RpcMethod<1> for Finalize {
                     ctx.chain_store(),
                     None,
                     &ctx.chain_store().genesis_tipset(),
-                    ctx.chain_config().block_delay_secs,
+                    ctx.chain_config(),
                 )?;
                 let ts = Arc::new(Tipset::from(fts));
                 ctx.chain_store().put_tipset(&ts)?;
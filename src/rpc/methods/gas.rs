@@ -15,7 +15,7 @@ use crate::shim::{
     econ::{BLOCK_GAS_LIMIT, TokenAmount},
     message::Message,
 };
-use crate::state_manager::StateLookupPolicy;
+use crate::state_manager::{StateLookupPolicy, StateOverrides};
 use anyhow::{Context, Result};
 use enumflags2::BitFlags;
 use fvm_ipld_blockstore::Blockstore;
@@ -26,6 +26,11 @@ use rand_distr::{Distribution, Normal};
 use std::ops::Add;
 
 const MIN_GAS_PREMIUM: f64 = 100000.0;
+/// Minimum number of tipsets sampled to estimate the gas premium, so that the inclusion
+/// percentiles are meaningful for short targets.
+const MIN_PREMIUM_SAMPLE_TIPSETS: u64 = 20;
+/// Probability of inclusion within the targeted number of epochs.
+const INCLUSION_CONFIDENCE: f64 = 0.95;
 
 /// Estimate the fee cap
 pub enum GasEstimateFeeCap {}
@@ -97,6 +102,30 @@ impl RpcMethod<4> for GasEstimateGasPremium {
     }
 }
 
+/// Estimate the gas premium to be included within a number of epochs
+pub enum ForestGasEstimateGasPremium {}
+impl RpcMethod<2> for ForestGasEstimateGasPremium {
+    const NAME: &'static str = "Forest.GasEstimateGasPremium";
+    const PARAM_NAMES: [&'static str; 2] = ["inclusionEpochs", "tipsetKey"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the estimated gas premium for a message to be included within the given number of epochs, from the lowest premiums included in the recent tipsets.",
+    );
+
+    type Params = (u64, ApiTipsetKey);
+    type Ok = String;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (inclusion_epochs, tsk): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        estimate_gas_premium_for_inclusion(&ctx, inclusion_epochs, &tsk)
+            .await
+            .map(|n| TokenAmount::to_string(&n))
+    }
+}
+
 #[derive(Clone)]
 struct GasMeta {
     pub price: TokenAmount,
@@ -140,8 +169,58 @@ pub async fn estimate_gas_premium<DB: Blockstore>(
         ts = pts;
     }
 
-    let mut premium = compute_gas_premium(prices, blocks as u64);
+    let premium = compute_gas_premium(prices, blocks as u64);
+    Ok(adjust_gas_premium(premium, nblocksincl)?)
+}
+
+/// Estimates the gas premium for a message to be included within `nblocksincl` epochs, from the
+/// premiums that got messages included in the recent tipsets.
+pub async fn estimate_gas_premium_for_inclusion<DB: Blockstore>(
+    data: &Ctx<DB>,
+    mut nblocksincl: u64,
+    ApiTipsetKey(ts_key): &ApiTipsetKey,
+) -> Result<TokenAmount, ServerError> {
+    if nblocksincl == 0 {
+        nblocksincl = 1;
+    }
 
+    // Lowest premium included in each of the sampled tipsets
+    let mut inclusion_premiums = Vec::new();
+
+    let mut ts = data
+        .chain_store()
+        .load_required_tipset_or_heaviest(ts_key)?;
+
+    for _ in 0..nblocksincl
+        .saturating_mul(2)
+        .max(MIN_PREMIUM_SAMPLE_TIPSETS)
+    {
+        if ts.epoch() == 0 {
+            break;
+        }
+        let pts = data.chain_index().load_required_tipset(ts.parents())?;
+        let msgs =
+            crate::chain::messages_for_tipset_with_cache(data.store(), &pts, &data.msgs_in_tipset)?;
+        let prices = msgs
+            .iter()
+            .map(|msg| GasMeta {
+                price: msg.message().gas_premium(),
+                limit: msg.message().gas_limit(),
+            })
+            .collect();
+        inclusion_premiums.push(compute_gas_premium(
+            prices,
+            pts.block_headers().len() as u64,
+        ));
+        ts = pts;
+    }
+
+    let premium = premium_for_inclusion(inclusion_premiums, nblocksincl);
+    Ok(adjust_gas_premium(premium, nblocksincl)?)
+}
+
+/// Raises the estimated premium to the minimum premium, and adds some noise to it.
+fn adjust_gas_premium(mut premium: TokenAmount, nblocksincl: u64) -> anyhow::Result<TokenAmount> {
     if premium < TokenAmount::from_atto(MIN_GAS_PREMIUM as u64) {
         premium = TokenAmount::from_atto(match nblocksincl {
             1 => (MIN_GAS_PREMIUM * 2.0) as u64,
@@ -164,6 +243,27 @@ pub async fn estimate_gas_premium<DB: Blockstore>(
     Ok(premium)
 }
 
+/// Returns the premium that clears enough of the sampled tipsets for a message paying it to be
+/// included within `nblocksincl` epochs with a probability of [`INCLUSION_CONFIDENCE`]. Assuming
+/// independent epochs, the premium must clear a share `1 - (1 - confidence)^(1 / nblocksincl)` of
+/// the tipsets, i.e. 95% of them to be included in the next epoch, and 26% of them to be included
+/// within 10 epochs.
+fn premium_for_inclusion(
+    mut inclusion_premiums: Vec<TokenAmount>,
+    nblocksincl: u64,
+) -> TokenAmount {
+    if inclusion_premiums.is_empty() {
+        return TokenAmount::zero();
+    }
+    inclusion_premiums.sort();
+    let share = 1.0 - (1.0 - INCLUSION_CONFIDENCE).powf((nblocksincl.max(1) as f64).recip());
+    let cleared = (share * inclusion_premiums.len() as f64).ceil() as usize;
+    inclusion_premiums
+        .get(cleared.clamp(1, inclusion_premiums.len()) - 1)
+        .cloned()
+        .unwrap_or_default()
+}
+
 // logic taken from here <https://github.com/filecoin-project/lotus/blob/v1.34.3/node/impl/gasutils/gasutils.go#L302>
 fn compute_gas_premium(mut prices: Vec<GasMeta>, blocks: u64) -> TokenAmount {
     prices.sort_by(|a, b| b.price.cmp(&a.price));
@@ -261,6 +361,7 @@ impl GasEstimateGasLimit {
                 &mut chain_msg,
                 &prior_messages,
                 Some(ts.clone()),
+                &StateOverrides::default(),
                 trace_config,
                 StateLookupPolicy::Enabled,
             )
@@ -540,6 +641,30 @@ mod tests {
         assert!(result_3_blocks <= result_1_block);
     }
 
+    #[test]
+    fn test_premium_for_inclusion() {
+        let premiums = (1..=20).map(TokenAmount::from_atto).collect_vec();
+        // Clears 19 of the 20 tipsets
+        assert_eq!(
+            premium_for_inclusion(premiums.clone(), 1),
+            TokenAmount::from_atto(19)
+        );
+        // Clears 6 of the 20 tipsets
+        assert_eq!(
+            premium_for_inclusion(premiums.clone(), 10),
+            TokenAmount::from_atto(6)
+        );
+        // Longer targets are cheaper, down to the lowest premium
+        assert!(
+            premium_for_inclusion(premiums.clone(), 3) < premium_for_inclusion(premiums.clone(), 2)
+        );
+        assert_eq!(
+            premium_for_inclusion(premiums, 1000),
+            TokenAmount::from_atto(1)
+        );
+        assert_eq!(premium_for_inclusion(vec![], 1), TokenAmount::zero());
+    }
+
     // Helper function to create a test message with gas parameters
     fn create_test_message(gas_limit: u64, gas_fee_cap: u64, gas_premium: u64) -> Message {
         Message {
@@ -1,6 +1,12 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod event_schema;
+
+pub use event_schema::{
+    DecodedActorEvent, DecodedEventEntry, EventFieldType, EventSchema, EventSchemaRegistry,
+};
+
 use std::collections::BTreeMap;
 
 use cid::Cid;
@@ -34,23 +40,91 @@ impl RpcMethod<1> for GetActorEventsRaw {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (filter,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        if let Some(filter) = filter {
-            let parsed_filter = ParsedFilter::from_actor_event_filter(
-                ctx.chain_store().heaviest_tipset().epoch(),
-                ctx.eth_event_handler.max_filter_height_range,
-                filter,
-            )?;
-            let events = ctx
-                .eth_event_handler
-                .get_events_for_parsed_filter(&ctx, &parsed_filter, SkipEvent::Never)
-                .await?;
-            Ok(events.into_iter().map(|ce| ce.into()).collect())
-        } else {
-            Ok(vec![])
-        }
+        get_actor_events(&ctx, filter).await
+    }
+}
+
+pub enum ForestGetActorEventsDecoded {}
+impl RpcMethod<1> for ForestGetActorEventsDecoded {
+    const NAME: &'static str = "Forest.GetActorEventsDecoded";
+    const PARAM_NAMES: [&'static str; 1] = ["eventFilter"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the actor events that match the given filter, as `Filecoin.GetActorEventsRaw` does, with the values of their entries decoded according to the registered event schemas.",
+    );
+
+    type Params = (Option<ActorEventFilter>,);
+    type Ok = Vec<DecodedActorEvent>;
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (filter,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let events = get_actor_events(&ctx, filter).await?;
+        let schemas = &ctx.eth_event_handler.event_schemas;
+        Ok(events.into_iter().map(|it| schemas.decode(it)).collect())
     }
 }
 
+pub enum ForestEventSchemaRegister {}
+impl RpcMethod<1> for ForestEventSchemaRegister {
+    const NAME: &'static str = "Forest.EventSchemaRegister";
+    const PARAM_NAMES: [&'static str; 1] = ["schema"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Write;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Registers a schema of the actor events of an emitter, of a type, or of both, replacing the one with the same emitter and type. Registered schemas are kept until the node restarts, persistent ones are configured in the `events.schemas` section of the configuration.",
+    );
+
+    type Params = (EventSchema,);
+    type Ok = ();
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (schema,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        ctx.eth_event_handler.event_schemas.register(schema)?;
+        Ok(())
+    }
+}
+
+pub enum ForestEventSchemaList {}
+impl RpcMethod<0> for ForestEventSchemaList {
+    const NAME: &'static str = "Forest.EventSchemaList";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the registered schemas of the actor events.");
+
+    type Params = ();
+    type Ok = Vec<EventSchema>;
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(ctx.eth_event_handler.event_schemas.schemas())
+    }
+}
+
+async fn get_actor_events(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    filter: Option<ActorEventFilter>,
+) -> Result<Vec<ActorEvent>, ServerError> {
+    let Some(filter) = filter else {
+        return Ok(vec![]);
+    };
+    let parsed_filter = ParsedFilter::from_actor_event_filter(
+        ctx.chain_store().heaviest_tipset().epoch(),
+        ctx.eth_event_handler.max_filter_height_range,
+        filter,
+    )?;
+    let events = ctx
+        .eth_event_handler
+        .get_events_for_parsed_filter(ctx, &parsed_filter, SkipEvent::Never)
+        .await?;
+    Ok(events.into_iter().map(|ce| ce.into()).collect())
+}
+
 #[derive(Clone, JsonSchema, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ActorEventFilter {
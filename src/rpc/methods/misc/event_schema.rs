@@ -0,0 +1,355 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Schemas of the actor events, mapping the keys of their entries to the types their values are
+//! decoded as. A schema applies to the events of an emitter, to the events of a type, i.e. with
+//! the given value of their `$type` entry as the built-in actors emit, or to both. The most
+//! specific schema of an event is used, and the entries it does not cover are decoded according
+//! to their codec only.
+
+use std::collections::BTreeMap;
+
+use cid::Cid;
+use fvm_ipld_encoding::{BytesDe, CBOR, DAG_CBOR, IPLD_RAW};
+use fvm_shared4::bigint::bigint_ser::BigIntDe;
+use ipld_core::ipld::Ipld;
+use num::BigInt;
+use num::bigint::Sign;
+use parking_lot::RwLock;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::ActorEvent;
+use crate::lotus_json::{HasLotusJson as _, LotusJson, lotus_json_with_self};
+use crate::rpc::types::EventEntry;
+use crate::shim::{address::Address, clock::ChainEpoch};
+
+/// Key of the entry holding the type of the events of the built-in actors
+const EVENT_TYPE_KEY: &str = "$type";
+
+/// Type the value of an event entry is decoded as
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFieldType {
+    /// Any IPLD value in its JSON form, or the raw bytes in hexadecimal
+    Ipld,
+    String,
+    /// Signed integer of up to 64 bits
+    Int,
+    /// Integer of any size, e.g. a token amount, or an unsigned big-endian integer such as an
+    /// EVM word if raw, as a decimal string
+    BigInt,
+    /// Filecoin address, or Ethereum address if raw, possibly left-padded to 32 bytes
+    Address,
+    Cid,
+    Bool,
+    /// Bytes in hexadecimal
+    Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSchema {
+    /// Emitter of the events the schema applies to, as reported in the events, any emitter if
+    /// omitted
+    #[serde(
+        default,
+        with = "crate::lotus_json",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "LotusJson<Option<Address>>")]
+    pub emitter: Option<Address>,
+    /// Type of the events the schema applies to, i.e. the value of their `$type` entry, any type
+    /// if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    /// Types of the values of the entries, by key
+    pub fields: BTreeMap<String, EventFieldType>,
+}
+
+lotus_json_with_self!(EventSchema);
+
+impl EventSchema {
+    fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.emitter.is_some() || self.event_type.is_some(),
+            "an event schema must apply to an emitter, to an event type, or to both"
+        );
+        Ok(())
+    }
+
+    fn matches(&self, emitter: &Address, event_type: Option<&str>) -> bool {
+        self.emitter.as_ref().is_none_or(|it| it == emitter)
+            && self
+                .event_type
+                .as_deref()
+                .is_none_or(|it| Some(it) == event_type)
+    }
+
+    fn specificity(&self) -> u8 {
+        u8::from(self.emitter.is_some()) * 2 + u8::from(self.event_type.is_some())
+    }
+}
+
+/// An actor event with the values of its entries decoded
+#[derive(Debug, PartialEq, Clone, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedActorEvent {
+    /// Value of the `$type` entry of the event, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    pub entries: Vec<DecodedEventEntry>,
+    pub emitter: LotusJson<Address>,
+    pub reverted: bool,
+    pub height: ChainEpoch,
+    pub tipset_key: LotusJson<crate::blocks::TipsetKey>,
+    pub msg_cid: LotusJson<Cid>,
+}
+
+lotus_json_with_self!(DecodedActorEvent);
+
+#[derive(Debug, PartialEq, Clone, JsonSchema, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedEventEntry {
+    pub flags: u64,
+    pub key: String,
+    /// The decoded value, `null` if it could not be decoded
+    pub value: Value,
+    /// Why the value could not be decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Event schemas registered in the node
+#[derive(Debug, Default)]
+pub struct EventSchemaRegistry {
+    schemas: RwLock<Vec<EventSchema>>,
+}
+
+impl EventSchemaRegistry {
+    /// Creates a registry of the configured schemas, skipping the invalid ones.
+    pub fn new(schemas: &[EventSchema]) -> Self {
+        let registry = Self::default();
+        for schema in schemas {
+            if let Err(e) = registry.register(schema.clone()) {
+                tracing::warn!("Skipping the event schema {schema:?}: {e}");
+            }
+        }
+        registry
+    }
+
+    /// Registers a schema, replacing the one with the same emitter and event type, if any.
+    pub fn register(&self, schema: EventSchema) -> anyhow::Result<()> {
+        schema.validate()?;
+        let mut schemas = self.schemas.write();
+        schemas.retain(|it| it.emitter != schema.emitter || it.event_type != schema.event_type);
+        schemas.push(schema);
+        Ok(())
+    }
+
+    pub fn schemas(&self) -> Vec<EventSchema> {
+        self.schemas.read().clone()
+    }
+
+    /// Decodes the entries of an event with its most specific schema.
+    pub fn decode(&self, event: ActorEvent) -> DecodedActorEvent {
+        let event_type = event
+            .entries
+            .iter()
+            .find(|it| it.key == EVENT_TYPE_KEY)
+            .and_then(|it| decode_value(it, EventFieldType::String).ok())
+            .and_then(|it| it.as_str().map(String::from));
+        let fields = self
+            .schemas
+            .read()
+            .iter()
+            .filter(|it| it.matches(&event.emitter.0, event_type.as_deref()))
+            .max_by_key(|it| it.specificity())
+            .map(|it| it.fields.clone())
+            .unwrap_or_default();
+        let entries = event
+            .entries
+            .iter()
+            .map(|entry| {
+                let field_type = fields
+                    .get(&entry.key)
+                    .copied()
+                    .unwrap_or(EventFieldType::Ipld);
+                let (value, error) = match decode_value(entry, field_type) {
+                    Ok(value) => (value, None),
+                    Err(e) => (Value::Null, Some(e.to_string())),
+                };
+                DecodedEventEntry {
+                    flags: entry.flags,
+                    key: entry.key.clone(),
+                    value,
+                    error,
+                }
+            })
+            .collect();
+        DecodedActorEvent {
+            event_type,
+            entries,
+            emitter: event.emitter,
+            reverted: event.reverted,
+            height: event.height,
+            tipset_key: event.tipset_key,
+            msg_cid: event.msg_cid,
+        }
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> Value {
+    Value::String(format!("0x{}", hex::encode(bytes)))
+}
+
+/// Decodes the value of an entry, according to its codec, as the field type.
+fn decode_value(entry: &EventEntry, field_type: EventFieldType) -> anyhow::Result<Value> {
+    let bytes = entry.value.0.as_slice();
+    match entry.codec {
+        CBOR | DAG_CBOR => decode_cbor(bytes, field_type),
+        IPLD_RAW => decode_raw(bytes, field_type),
+        codec => anyhow::bail!("unsupported codec {codec:#x}"),
+    }
+}
+
+fn decode_cbor(bytes: &[u8], field_type: EventFieldType) -> anyhow::Result<Value> {
+    use fvm_ipld_encoding::from_slice;
+    Ok(match field_type {
+        EventFieldType::Ipld => from_slice::<Ipld>(bytes)?.into_lotus_json_value()?,
+        EventFieldType::String => Value::String(from_slice(bytes)?),
+        EventFieldType::Int => Value::from(from_slice::<i64>(bytes)?),
+        EventFieldType::BigInt => Value::String(from_slice::<BigIntDe>(bytes)?.0.to_string()),
+        EventFieldType::Address => Value::String(from_slice::<Address>(bytes)?.to_string()),
+        EventFieldType::Cid => Value::String(from_slice::<Cid>(bytes)?.to_string()),
+        EventFieldType::Bool => Value::Bool(from_slice(bytes)?),
+        EventFieldType::Bytes => hex_bytes(&from_slice::<BytesDe>(bytes)?.0),
+    })
+}
+
+fn decode_raw(bytes: &[u8], field_type: EventFieldType) -> anyhow::Result<Value> {
+    Ok(match field_type {
+        EventFieldType::Ipld | EventFieldType::Bytes => hex_bytes(bytes),
+        EventFieldType::String => Value::String(String::from_utf8(bytes.to_vec())?),
+        EventFieldType::Int => {
+            let value = BigInt::from_signed_bytes_be(bytes);
+            Value::from(
+                i64::try_from(&value).map_err(|_| {
+                    anyhow::anyhow!("{value} does not fit in a signed 64-bit integer")
+                })?,
+            )
+        }
+        EventFieldType::BigInt => {
+            Value::String(BigInt::from_bytes_be(Sign::Plus, bytes).to_string())
+        }
+        EventFieldType::Address => match (bytes.len(), bytes.split_at_checked(12)) {
+            (20, _) => hex_bytes(bytes),
+            (32, Some((padding, address))) if padding.iter().all(|&it| it == 0) => {
+                hex_bytes(address)
+            }
+            (len, _) => anyhow::bail!("{len} bytes are not an Ethereum address"),
+        },
+        EventFieldType::Cid => Value::String(Cid::try_from(bytes)?.to_string()),
+        EventFieldType::Bool => Value::Bool(bytes.iter().any(|&it| it != 0)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::TipsetKey;
+    use crate::shim::econ::TokenAmount;
+
+    fn cbor_entry(key: &str, value: impl Serialize) -> EventEntry {
+        EventEntry {
+            flags: 3,
+            key: key.into(),
+            codec: CBOR,
+            value: LotusJson(fvm_ipld_encoding::to_vec(&value).unwrap()),
+        }
+    }
+
+    fn event(emitter: Address, entries: Vec<EventEntry>) -> ActorEvent {
+        ActorEvent {
+            entries,
+            emitter: LotusJson(emitter),
+            reverted: false,
+            height: 10,
+            tipset_key: LotusJson(TipsetKey::from(nunny::vec![Cid::default()])),
+            msg_cid: LotusJson(Cid::default()),
+        }
+    }
+
+    #[test]
+    fn decode_with_the_most_specific_schema() {
+        let miner = Address::new_id(1000);
+        let registry = EventSchemaRegistry::new(&[EventSchema {
+            emitter: None,
+            event_type: Some("sector-activated".into()),
+            fields: [
+                ("miner".to_string(), EventFieldType::Int),
+                ("amount".to_string(), EventFieldType::BigInt),
+            ]
+            .into(),
+        }]);
+        registry
+            .register(EventSchema {
+                emitter: Some(miner),
+                event_type: Some("sector-activated".into()),
+                fields: [("miner".to_string(), EventFieldType::Address)].into(),
+            })
+            .unwrap();
+        // A schema must apply to something
+        assert!(
+            registry
+                .register(EventSchema {
+                    emitter: None,
+                    event_type: None,
+                    fields: Default::default(),
+                })
+                .is_err()
+        );
+        assert_eq!(registry.schemas().len(), 2);
+
+        let entries = vec![
+            cbor_entry(EVENT_TYPE_KEY, "sector-activated"),
+            cbor_entry("miner", 1000u64),
+            cbor_entry("amount", TokenAmount::from_atto(u128::MAX)),
+            cbor_entry("sector", 7u64),
+        ];
+        let decoded = registry.decode(event(Address::new_id(1001), entries.clone()));
+        assert_eq!(decoded.event_type.as_deref(), Some("sector-activated"));
+        assert_eq!(decoded.entries[1].value, Value::from(1000));
+        assert_eq!(decoded.entries[2].value, Value::from(u128::MAX.to_string()));
+        // Entries without a field type are decoded as IPLD
+        assert_eq!(
+            decoded.entries[3].value,
+            serde_json::json!({"/": {"int": "7"}})
+        );
+
+        // The schema of the emitter takes precedence, and decoding errors are reported
+        let decoded = registry.decode(event(miner, entries));
+        assert_eq!(decoded.entries[1].value, Value::Null);
+        assert!(decoded.entries[1].error.is_some());
+    }
+
+    #[test]
+    fn decode_raw_values() {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&[0xab; 20]);
+        assert_eq!(
+            decode_raw(&word, EventFieldType::Address).unwrap(),
+            Value::from(format!("0x{}", "ab".repeat(20)))
+        );
+        assert_eq!(
+            decode_raw(&[1, 0], EventFieldType::BigInt).unwrap(),
+            Value::from("256")
+        );
+        assert_eq!(
+            decode_raw(&[0xff], EventFieldType::Int).unwrap(),
+            Value::from(-1)
+        );
+        assert!(decode_raw(&[0xab; 12], EventFieldType::Address).is_err());
+    }
+}
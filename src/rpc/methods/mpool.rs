@@ -1,20 +1,29 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::gas::estimate_message_gas;
-use crate::lotus_json::NotNullVec;
+use super::gas::{estimate_gas_premium, estimate_gas_premium_for_inclusion, estimate_message_gas};
+use crate::lotus_json::{LotusJson, NotNullVec, lotus_json_with_self};
 use crate::message::SignedMessage;
 use crate::rpc::error::ServerError;
 use crate::rpc::types::{ApiTipsetKey, MessageSendSpec};
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod};
+use crate::shim::actors::power;
 use crate::shim::{
     address::{Address, Protocol},
+    econ::TokenAmount,
     message::Message,
 };
 use ahash::{HashSet, HashSetExt as _};
 use cid::Cid;
 use enumflags2::BitFlags;
 use fvm_ipld_blockstore::Blockstore;
+use num::{BigInt, ToPrimitive as _, Zero as _};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Numbers of blocks within which the premium targets of [`ForestMpoolEstimateWins`] aim to get
+/// messages included.
+const PREMIUM_TARGET_BLOCKS: [u64; 4] = [1, 5, 10, 20];
 
 /// Gets next nonce for the specified sender.
 pub enum MpoolGetNonce {}
@@ -134,6 +143,87 @@ impl RpcMethod<2> for MpoolSelect {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PremiumTarget {
+    /// Number of blocks within which messages are expected to be included
+    pub blocks: u64,
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub gas_premium: TokenAmount,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct WinEstimate {
+    /// Quality-adjusted power of the network
+    #[schemars(with = "LotusJson<BigInt>")]
+    #[serde(with = "crate::lotus_json")]
+    pub network_power: BigInt,
+    pub power_share: f64,
+    pub expected_wins_per_epoch: f64,
+    /// Probability of winning at least one block in an epoch
+    pub win_probability: f64,
+    pub expected_wins_per_day: f64,
+    /// Probabilities of winning 0, 1, ... blocks in a tipset
+    pub win_count_probabilities: Vec<f64>,
+    pub premium_targets: Vec<PremiumTarget>,
+}
+lotus_json_with_self!(WinEstimate);
+
+/// Estimate the wins of a miner from its power, with the block probability model of the message
+/// selection
+pub enum ForestMpoolEstimateWins {}
+impl RpcMethod<2> for ForestMpoolEstimateWins {
+    const NAME: &'static str = "Forest.MpoolEstimateWins";
+    const PARAM_NAMES: [&'static str; 2] = ["minerPower", "tipsetKey"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the expected block wins of a miner with the given quality-adjusted power, and the gas premiums to get messages included within a number of blocks.",
+    );
+
+    type Params = (BigInt, ApiTipsetKey);
+    type Ok = WinEstimate;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (miner_power, tsk): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let ts = ctx.chain_store().load_required_tipset_or_heaviest(&tsk.0)?;
+        let power_state: power::State = ctx.state_manager.get_actor_state(&ts)?;
+        let network_power = power_state.total_power().quality_adj_power;
+        let power_share = if network_power.is_zero() {
+            0.0
+        } else {
+            miner_power.to_f64().unwrap_or_default() / network_power.to_f64().unwrap_or(f64::MAX)
+        };
+
+        let model = &ctx.mpool.get_config().block_probability;
+        let expected_wins_per_epoch = model.expected_wins_per_epoch(power_share);
+        let win_count_probabilities = model.win_count_probabilities(power_share);
+        let epochs_per_day = 24 * 60 * 60 / ctx.chain_config().block_delay_secs.max(1);
+
+        let mut premium_targets = Vec::with_capacity(PREMIUM_TARGET_BLOCKS.len());
+        for blocks in PREMIUM_TARGET_BLOCKS {
+            premium_targets.push(PremiumTarget {
+                blocks,
+                gas_premium: estimate_gas_premium(&ctx, blocks, &tsk).await?,
+            });
+        }
+
+        Ok(WinEstimate {
+            network_power,
+            power_share,
+            expected_wins_per_epoch,
+            win_probability: 1.0 - win_count_probabilities.first().copied().unwrap_or(1.0),
+            expected_wins_per_day: expected_wins_per_epoch * epochs_per_day as f64,
+            win_count_probabilities,
+            premium_targets,
+        })
+    }
+}
+
 /// Add `SignedMessage` to `mpool`, return message CID
 pub enum MpoolPush {}
 impl RpcMethod<1> for MpoolPush {
@@ -172,14 +262,80 @@ impl RpcMethod<1> for MpoolBatchPush {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (messages,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        let mut cids = vec![];
-        for msg in messages {
-            cids.push(ctx.mpool.as_ref().push(msg).await?);
-        }
+        let cids = ctx.mpool.as_ref().batch_push(messages).await?;
         Ok(cids)
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct MessagePrototype {
+    #[schemars(with = "LotusJson<Message>")]
+    #[serde(with = "crate::lotus_json")]
+    pub message: Message,
+    /// Whether the nonce of the message is meant to be the next nonce of the sender
+    pub valid_nonce: bool,
+}
+lotus_json_with_self!(MessagePrototype);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct MessageCheckStatus {
+    #[schemars(with = "LotusJson<Cid>")]
+    #[serde(with = "crate::lotus_json")]
+    pub cid: Cid,
+    pub code: u8,
+    #[serde(rename = "OK")]
+    pub ok: bool,
+    pub err: String,
+    /// Values the message was checked against
+    pub hint: serde_json::Value,
+}
+lotus_json_with_self!(MessageCheckStatus);
+
+/// Check messages without adding them to `mpool`, return the results of the checks of each message
+pub enum MpoolCheckMessages {}
+impl RpcMethod<1> for MpoolCheckMessages {
+    const NAME: &'static str = "Filecoin.MpoolCheckMessages";
+    const PARAM_NAMES: [&'static str; 1] = ["messagePrototypes"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Checks the size, validity, gas, nonce and balance of the given messages, without adding them to the message pool.",
+    );
+
+    type Params = (Vec<MessagePrototype>,);
+    type Ok = Vec<Vec<MessageCheckStatus>>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (prototypes,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let msgs = prototypes
+            .into_iter()
+            .map(|it| (it.message, it.valid_nonce))
+            .collect::<Vec<_>>();
+        let results = ctx.mpool.check_messages(&msgs)?;
+        Ok(msgs
+            .iter()
+            .zip(results)
+            .map(|((msg, _), checks)| {
+                let cid = msg.cid();
+                checks
+                    .into_iter()
+                    .map(|check| MessageCheckStatus {
+                        cid,
+                        code: check.code as u8,
+                        ok: check.ok(),
+                        err: check.err.unwrap_or_default(),
+                        hint: check.hint,
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
 /// Add `SignedMessage` from untrusted source to `mpool`, return message CID
 pub enum MpoolPushUntrusted {}
 impl RpcMethod<1> for MpoolPushUntrusted {
@@ -197,9 +353,6 @@ impl RpcMethod<1> for MpoolPushUntrusted {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (message,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        // Lotus implements a few extra sanity checks that we skip. We skip them
-        // because those checks aren't used for messages received from peers and
-        // therefore aren't safety critical.
         let cid = ctx.mpool.as_ref().push_untrusted(message).await?;
         Ok(cid)
     }
@@ -222,8 +375,8 @@ impl RpcMethod<1> for MpoolBatchPushUntrusted {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (messages,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        // Alias of MpoolBatchPush.
-        MpoolBatchPush::handle(ctx, (messages,)).await
+        let cids = ctx.mpool.as_ref().batch_push_untrusted(messages).await?;
+        Ok(cids)
     }
 }
 
@@ -242,7 +395,7 @@ impl RpcMethod<2> for MpoolPushMessage {
 
     async fn handle(
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
-        (message, send_spec): Self::Params,
+        (mut message, send_spec): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         let from = message.from;
 
@@ -258,6 +411,13 @@ impl RpcMethod<2> for MpoolPushMessage {
             )
             .into());
         }
+        if message.gas_premium.is_zero() {
+            // Unlike `GasEstimateMessageGas`, which follows Lotus, the premium is the one that
+            // got messages included within as many epochs in the recent tipsets.
+            message.set_gas_premium(
+                estimate_gas_premium_for_inclusion(&ctx, 10, &Default::default()).await?,
+            );
+        }
         let mut message =
             estimate_message_gas(&ctx, message, send_spec, Default::default()).await?;
         if message.gas_premium > message.gas_fee_cap {
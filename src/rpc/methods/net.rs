@@ -8,8 +8,9 @@ pub use types::*;
 use std::any::Any;
 use std::str::FromStr;
 
-use crate::libp2p::{NetRPCMethods, NetworkMessage, PeerId};
+use crate::libp2p::{NetRPCMethods, NetworkMessage, PeerId, bandwidth::BANDWIDTH};
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError};
+use ahash::HashMap;
 use anyhow::{Context as _, Result};
 use cid::multibase;
 use enumflags2::BitFlags;
@@ -245,6 +246,66 @@ impl RpcMethod<0> for NetAutoNatStatus {
     }
 }
 
+pub enum NetBandwidthStats {}
+impl RpcMethod<0> for NetBandwidthStats {
+    const NAME: &'static str = "Filecoin.NetBandwidthStats";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the bytes transferred and the transfer rates of the node.");
+
+    type Params = ();
+    type Ok = BandwidthStats;
+
+    async fn handle(_: Ctx<impl Any>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(BANDWIDTH.totals().into())
+    }
+}
+
+pub enum NetBandwidthStatsByPeer {}
+impl RpcMethod<0> for NetBandwidthStatsByPeer {
+    const NAME: &'static str = "Filecoin.NetBandwidthStatsByPeer";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the bytes transferred and the transfer rates of every peer.");
+
+    type Params = ();
+    type Ok = HashMap<String, BandwidthStats>;
+
+    async fn handle(_: Ctx<impl Any>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(BANDWIDTH
+            .by_peer()
+            .into_iter()
+            .map(|(peer_id, stats)| (peer_id.to_string(), stats.into()))
+            .collect())
+    }
+}
+
+pub enum NetBandwidthStatsByProtocol {}
+impl RpcMethod<0> for NetBandwidthStatsByProtocol {
+    const NAME: &'static str = "Filecoin.NetBandwidthStatsByProtocol";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the bytes transferred and the transfer rates of every request-response protocol and gossipsub topic.",
+    );
+
+    type Params = ();
+    type Ok = HashMap<String, BandwidthStats>;
+
+    async fn handle(_: Ctx<impl Any>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(BANDWIDTH
+            .by_protocol()
+            .into_iter()
+            .map(|(protocol, stats)| (protocol, stats.into()))
+            .collect())
+    }
+}
+
 pub enum NetVersion {}
 impl RpcMethod<0> for NetVersion {
     const NAME: &'static str = "Filecoin.NetVersion";
@@ -89,3 +89,32 @@ impl From<libp2p::autonat::NatStatus> for NatStatusResult {
         }
     }
 }
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, JsonSchema, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct BandwidthStats {
+    pub total_in: u64,
+    pub total_out: u64,
+    /// Bytes per second received.
+    pub rate_in: f64,
+    /// Bytes per second sent.
+    pub rate_out: f64,
+}
+lotus_json_with_self!(BandwidthStats);
+
+impl From<crate::libp2p::bandwidth::Stats> for BandwidthStats {
+    fn from(stats: crate::libp2p::bandwidth::Stats) -> Self {
+        let crate::libp2p::bandwidth::Stats {
+            total_in,
+            total_out,
+            rate_in,
+            rate_out,
+        } = stats;
+        Self {
+            total_in,
+            total_out,
+            rate_in,
+            rate_out,
+        }
+    }
+}
@@ -4,6 +4,7 @@
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{
+    db::disk_space::{self, DiskSpaceLevel},
     lotus_json::lotus_json_with_self,
     rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError},
 };
@@ -41,6 +42,14 @@ impl RpcMethod<0> for NodeStatus {
 
         let chain_finality = ctx.chain_config().policy.chain_finality;
 
+        node_status.disk_status = disk_space::status().map(|status| NodeDiskStatus {
+            available_bytes: status.available_bytes,
+            total_bytes: status.total_bytes,
+            level: status.level,
+            exports_paused: status.exports_paused(),
+            sync_paused: status.sync_paused(),
+        });
+
         node_status.sync_status.epoch = head.epoch() as u64;
         node_status.sync_status.behind = behind;
 
@@ -91,10 +100,24 @@ pub struct NodeChainStatus {
 }
 lotus_json_with_self!(NodeChainStatus);
 
+/// Free space of the database volume.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Default, Clone, JsonSchema)]
+pub struct NodeDiskStatus {
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    pub level: DiskSpaceLevel,
+    pub exports_paused: bool,
+    pub sync_paused: bool,
+}
+lotus_json_with_self!(NodeDiskStatus);
+
 #[derive(Debug, Deserialize, Default, Serialize, Clone, JsonSchema, PartialEq)]
 pub struct NodeStatusResult {
     pub sync_status: NodeSyncStatus,
     pub peer_status: NodePeerStatus,
     pub chain_status: NodeChainStatus,
+    /// Forest extension, absent if the disk space monitor is disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_status: Option<NodeDiskStatus>,
 }
 lotus_json_with_self!(NodeStatusResult);
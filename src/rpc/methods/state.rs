@@ -11,10 +11,12 @@ use crate::chain::index::ResolveNullTipset;
 use crate::cid_collections::CidHashSet;
 use crate::eth::EthChainId;
 use crate::interpreter::{MessageCallbackCtx, VMTrace};
+use crate::ipld::selector::{Selector, walk_dag};
 use crate::libp2p::NetworkMessage;
 use crate::lotus_json::{LotusJson, lotus_json_with_self};
 use crate::networks::ChainConfig;
-use crate::rpc::registry::actors_reg::load_and_serialize_actor_state;
+use crate::rpc::registry::actors_reg::{ActorRegistry, load_and_serialize_actor_state};
+use crate::rpc::registry::methods_reg;
 use crate::shim::actors::market::DealState;
 use crate::shim::actors::market::ext::MarketStateExt as _;
 use crate::shim::actors::miner::ext::DeadlineExt;
@@ -35,15 +37,17 @@ use crate::shim::address::Payload;
 use crate::shim::machine::BuiltinActorManifest;
 use crate::shim::message::{Message, MethodNum};
 use crate::shim::sector::{SectorNumber, SectorSize};
-use crate::shim::state_tree::{ActorID, StateTree};
+use crate::shim::state_tree::{ActorID, StateTree, get_actor_with_proof, map_actors};
 use crate::shim::{
     address::Address, clock::ChainEpoch, deal::DealID, econ::TokenAmount, executor::Receipt,
     state_tree::ActorState, version::NetworkVersion,
 };
 use crate::state_manager::{
-    MarketBalance, StateManager, StateOutput, circulating_supply::GenesisInfo, utils::structured,
+    MarketBalance, StateManager, StateOutput, circulating_supply::GenesisInfo,
+    reward_metrics::RewardMetrics, utils::structured,
 };
 use crate::utils::db::car_stream::{CarBlock, CarWriter};
+use crate::utils::db::car_v2::write_car_v2;
 use crate::{
     beacon::BeaconEntry,
     rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError, types::*},
@@ -322,6 +326,37 @@ impl RpcMethod<2> for StateGetActorV2 {
     }
 }
 
+pub enum StateGetActorProof {}
+
+impl RpcMethod<2> for StateGetActorProof {
+    const NAME: &'static str = "Forest.StateGetActorProof";
+    const PARAM_NAMES: [&'static str; 2] = ["address", "tipsetKey"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the specified actor along with a Merkle proof of it, or of its absence, under the parent state root of the tipset.",
+    );
+
+    type Params = (Address, ApiTipsetKey);
+    type Ok = ActorProof;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (address, ApiTipsetKey(tsk)): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let ts = ctx.chain_store().load_required_tipset_or_heaviest(&tsk)?;
+        let (actor, blocks) = get_actor_with_proof(ctx.store(), ts.parent_state(), &address)?;
+        Ok(ActorProof {
+            state_root: *ts.parent_state(),
+            actor,
+            blocks: blocks
+                .into_iter()
+                .map(|(cid, data)| ProofBlock { cid, data })
+                .collect(),
+        })
+    }
+}
+
 pub enum StateGetID {}
 
 impl RpcMethod<2> for StateGetID {
@@ -1220,6 +1255,60 @@ impl RpcMethod<4> for StateWaitMsg {
     }
 }
 
+/// Maximum number of epochs of a [`ForestStateRewardMetrics`] query, a day
+const MAX_REWARD_METRICS_EPOCHS: ChainEpoch = 2880;
+
+pub enum ForestStateRewardMetrics {}
+
+impl RpcMethod<2> for ForestStateRewardMetrics {
+    const NAME: &'static str = "Forest.StateRewardMetrics";
+    const PARAM_NAMES: [&'static str; 2] = ["fromEpoch", "toEpoch"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the baseline power, block reward, smoothed reward estimate and total mined reward of the reward actor, in the parent state of every tipset from `fromEpoch` to `toEpoch`. The metrics are recorded as the tipsets are validated when the indexer is enabled, and read from the state otherwise.",
+    );
+
+    type Params = (ChainEpoch, ChainEpoch);
+    type Ok = Vec<ApiRewardMetrics>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (from_epoch, to_epoch): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        if from_epoch > to_epoch {
+            return Err(ServerError::invalid_params(
+                format!("fromEpoch {from_epoch} is after toEpoch {to_epoch}"),
+                None,
+            ));
+        }
+        if to_epoch - from_epoch >= MAX_REWARD_METRICS_EPOCHS {
+            return Err(ServerError::invalid_params(
+                format!("at most {MAX_REWARD_METRICS_EPOCHS} epochs can be queried at once"),
+                None,
+            ));
+        }
+        let to_ts = ctx.chain_index().tipset_by_height(
+            to_epoch,
+            ctx.chain_store().heaviest_tipset(),
+            ResolveNullTipset::TakeOlder,
+        )?;
+        let mut results = Vec::new();
+        for ts in to_ts
+            .chain(ctx.store())
+            .take_while(|ts| ts.epoch() >= from_epoch)
+        {
+            let metrics = match ctx.chain_store().get_reward_metrics(ts.key())? {
+                Some(metrics) => metrics,
+                None => RewardMetrics::load(&ctx.store_owned(), ts.parent_state())?,
+            };
+            results.push(ApiRewardMetrics::new(ts.epoch(), ts.key().clone(), metrics));
+        }
+        results.reverse();
+        Ok(results)
+    }
+}
+
 /// Searches for a message in the chain, and returns its receipt and the tipset where it was executed.
 /// See <https://github.com/filecoin-project/lotus/blob/master/documentation/en/api-methods-v1-stable.md#StateSearchMsg>
 pub enum StateSearchMsg {}
@@ -1478,6 +1567,73 @@ impl RpcMethod<2> for StateFetchRoot {
     }
 }
 
+pub enum ForestStateExportDag {}
+
+impl RpcMethod<1> for ForestStateExportDag {
+    const NAME: &'static str = "Forest.StateExportDag";
+    const PARAM_NAMES: [&'static str; 1] = ["params"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Exports the blocks of the sub-DAG of a root CID matched by an IPLD selector, or by a path and a depth, to a CAR file. Returns the number of blocks exported.",
+    );
+
+    type Params = (ForestStateExportDagParams,);
+    type Ok = u64;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (params,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let ForestStateExportDagParams {
+            root,
+            selector,
+            path,
+            depth,
+            output_path,
+            car_v2,
+        } = params;
+        let selector = match selector {
+            Some(_) if path.is_some() || depth.is_some() => {
+                return Err(anyhow::anyhow!(
+                    "a selector cannot be combined with a path or a depth"
+                )
+                .into());
+            }
+            Some(selector) => selector,
+            None => Selector::from_path(path.as_deref().unwrap_or_default(), depth),
+        };
+
+        let (car_tx, car_rx) = flume::bounded(100);
+        let file = tokio::fs::File::create(&output_path).await?;
+        let car_handle = tokio::spawn(async move {
+            let blocks = car_rx.stream().map(Ok);
+            if car_v2 {
+                write_car_v2(nonempty![root], blocks, tokio::io::BufWriter::new(file)).await
+            } else {
+                blocks
+                    .forward(CarWriter::new_carv1(nonempty![root], file)?)
+                    .await
+            }
+        });
+
+        let db = ctx.store_owned();
+        let walk = tokio::task::spawn_blocking(move || {
+            let mut exported = 0;
+            walk_dag(&db, root, selector, |cid, data| {
+                car_tx.send(CarBlock { cid, data })?;
+                exported += 1;
+                Ok(())
+            })?;
+            anyhow::Ok(exported)
+        })
+        .await?;
+        // The sender is dropped with the walk, which lets the writer finish.
+        car_handle.await??;
+        Ok(walk?)
+    }
+}
+
 pub enum ForestStateCompute {}
 
 impl RpcMethod<2> for ForestStateCompute {
@@ -1862,24 +2018,37 @@ impl RpcMethod<1> for StateVMCirculatingSupplyInternal {
 
 pub enum StateListMiners {}
 
-impl RpcMethod<1> for StateListMiners {
+impl RpcMethod<3> for StateListMiners {
     const NAME: &'static str = "Filecoin.StateListMiners";
-    const PARAM_NAMES: [&'static str; 1] = ["tipsetKey"];
+    const N_REQUIRED_PARAMS: usize = 1;
+    const PARAM_NAMES: [&'static str; 3] = ["tipsetKey", "offset", "limit"];
     const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
     const PERMISSION: Permission = Permission::Read;
-    const DESCRIPTION: Option<&'static str> =
-        Some("Returns the addresses of every miner with claimed power in the Power Actor.");
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the addresses of every miner with claimed power in the Power Actor, optionally only the `limit` miners starting at index `offset`.",
+    );
 
-    type Params = (ApiTipsetKey,);
+    type Params = (ApiTipsetKey, Option<u64>, Option<u64>);
     type Ok = Vec<Address>;
 
     async fn handle(
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
-        (ApiTipsetKey(tsk),): Self::Params,
+        (ApiTipsetKey(tsk), offset, limit): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         let ts = ctx.chain_store().load_required_tipset_or_heaviest(&tsk)?;
         let state: power::State = ctx.state_manager.get_actor_state(&ts)?;
-        let miners = state.list_all_miners(ctx.store())?;
+        let offset = usize::try_from(offset.unwrap_or_default()).context("offset is too large")?;
+        let limit = limit
+            .map(usize::try_from)
+            .transpose()
+            .context("limit is too large")?
+            .unwrap_or(usize::MAX);
+        let miners = state
+            .list_all_miners(ctx.store())?
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect();
         Ok(miners)
     }
 }
@@ -1901,14 +2070,13 @@ impl RpcMethod<1> for StateListActors {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (ApiTipsetKey(tsk),): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        let mut actors = vec![];
         let ts = ctx.chain_store().load_required_tipset_or_heaviest(&tsk)?;
-        let state_tree = ctx.state_manager.get_state_tree(ts.parent_state())?;
-        state_tree.for_each(|addr, _state| {
-            actors.push(addr);
-            Ok(())
-        })?;
-        Ok(actors)
+        // Listed in state tree order, as Lotus does.
+        Ok(map_actors(
+            ctx.store(),
+            ts.parent_state(),
+            |addr, _state| Ok(addr),
+        )?)
     }
 }
 
@@ -2588,6 +2756,12 @@ impl RpcMethod<3> for StateListMessages {
             return Ok(vec![]);
         }
 
+        if ctx.chain_config().enable_indexer
+            && let Some(out) = list_indexed_messages(&ctx, &from_to, &ts, max_height)?
+        {
+            return Ok(out);
+        }
+
         let mut out = Vec::new();
         let mut cur_ts = ts.clone();
 
@@ -2612,6 +2786,49 @@ impl RpcMethod<3> for StateListMessages {
     }
 }
 
+/// Lists the messages matching the filter with the index of messages by address, if it covers
+/// the epochs from `ts` down to `max_height`.
+fn list_indexed_messages<DB: Blockstore>(
+    ctx: &Ctx<DB>,
+    from_to: &MessageFilter,
+    ts: &Tipset,
+    max_height: ChainEpoch,
+) -> anyhow::Result<Option<Vec<Cid>>> {
+    let chain_store = ctx.chain_store();
+    let candidates = match (&from_to.from, &from_to.to) {
+        (Some(from), Some(to)) => {
+            let Some(sent) = chain_store.get_address_messages(from, ts, max_height)? else {
+                return Ok(None);
+            };
+            let Some(received) = chain_store.get_address_messages(to, ts, max_height)? else {
+                return Ok(None);
+            };
+            let received: HashSet<_> = received.into_iter().collect();
+            sent.into_iter()
+                .filter(|cid| received.contains(cid))
+                .collect::<Vec<_>>()
+        }
+        (Some(address), None) | (None, Some(address)) => {
+            let Some(messages) = chain_store.get_address_messages(address, ts, max_height)? else {
+                return Ok(None);
+            };
+            messages
+        }
+        (None, None) => return Ok(None),
+    };
+    // The index lists the messages of both the senders and the recipients, e.g. a message sent
+    // back from `to` to `from` is listed for both addresses too.
+    Ok(Some(
+        candidates
+            .into_iter()
+            .filter(|cid| {
+                crate::chain::get_chain_message(ctx.store(), cid)
+                    .is_ok_and(|msg| from_to.matches(msg.message()))
+            })
+            .collect(),
+    ))
+}
+
 pub enum StateGetClaim {}
 
 impl RpcMethod<3> for StateGetClaim {
@@ -3255,6 +3472,37 @@ impl std::fmt::Display for StateActorCodeCidsOutput {
     }
 }
 
+pub enum ForestStateActorMethods {}
+
+impl RpcMethod<1> for ForestStateActorMethods {
+    const NAME: &'static str = "Forest.StateActorMethods";
+    const PARAM_NAMES: [&'static str; 1] = ["actorCode"];
+    const API_PATHS: BitFlags<ApiPaths> = ApiPaths::all();
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the number, name and parameter type of the known methods of the builtin actor with the given code.",
+    );
+
+    type Params = (Cid,);
+    type Ok = Vec<ApiActorMethod>;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (code,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        // Fails on unknown actor codes
+        ActorRegistry::get_actor_details_from_code(&code)?;
+        Ok(methods_reg::actor_methods(&code)
+            .into_iter()
+            .map(|(num, info)| ApiActorMethod {
+                num,
+                name: info.name.to_owned(),
+                params: info.params.map(str::to_owned),
+            })
+            .collect())
+    }
+}
+
 pub enum StateActorInfo {}
 
 impl RpcMethod<0> for StateActorInfo {
@@ -3304,3 +3552,63 @@ impl RpcMethod<0> for StateActorInfo {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{BlockSpec, ChainHarness};
+
+    #[tokio::test]
+    async fn state_list_messages_from_index() {
+        let harness = ChainHarness::with_chain_config(ChainConfig {
+            enable_indexer: true,
+            ..Default::default()
+        });
+        let message = |from, to| Message {
+            from: Address::new_id(from),
+            to: Address::new_id(to),
+            ..Default::default()
+        };
+        let (msg1, msg2, msg3) = (message(2, 1), message(3, 1), message(1, 2));
+        for msg in [&msg1, &msg2, &msg3] {
+            let ts = harness
+                .produce(
+                    0,
+                    [BlockSpec {
+                        bls_messages: vec![msg.clone()],
+                        ..Default::default()
+                    }],
+                )
+                .unwrap()
+                .into_tipset();
+            harness.chain_store.put_address_messages(&ts).unwrap();
+        }
+        let head = harness.head();
+        // The index covers the listed epochs
+        assert!(
+            harness
+                .chain_store
+                .get_address_messages(&Address::new_id(1), &head, 1)
+                .unwrap()
+                .is_some()
+        );
+
+        let ctx = harness.rpc_state();
+        let list = |from, to| {
+            let filter = MessageFilter {
+                from: from.map(Address::new_id),
+                to: to.map(Address::new_id),
+            };
+            StateListMessages::handle(ctx.clone(), (filter, ApiTipsetKey(None), 1))
+        };
+        assert_eq!(
+            list(None, Some(1)).await.unwrap(),
+            vec![msg2.cid(), msg1.cid()]
+        );
+        assert_eq!(list(Some(3), None).await.unwrap(), vec![msg2.cid()]);
+        // The messages between two addresses are listed in one direction only
+        assert_eq!(list(Some(2), Some(1)).await.unwrap(), vec![msg1.cid()]);
+        assert_eq!(list(Some(1), Some(2)).await.unwrap(), vec![msg3.cid()]);
+        assert_eq!(list(Some(3), Some(2)).await.unwrap(), vec![]);
+    }
+}
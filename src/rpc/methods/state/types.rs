@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::blocks::TipsetKey;
+use crate::ipld::selector::Selector;
 use crate::lotus_json::{LotusJson, lotus_json_with_self};
 use crate::message::Message as _;
 use crate::shim::executor::ApplyRet;
@@ -11,14 +12,17 @@ use crate::shim::{
     econ::TokenAmount,
     error::ExitCode,
     executor::Receipt,
-    message::Message,
-    state_tree::{ActorID, ActorState},
+    message::{Message, MethodNum},
+    sector::StoragePower,
+    state_tree::{ActorID, ActorState, verify_actor_proof},
 };
+use crate::state_manager::reward_metrics::RewardMetrics;
 use cid::Cid;
 use fvm_ipld_encoding::RawBytes;
 use num::Zero as _;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -275,3 +279,117 @@ pub struct SectorLocation {
     pub partition: u64,
 }
 lotus_json_with_self!(SectorLocation);
+
+/// Merkle proof of an actor under a state root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ActorProof {
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Cid>")]
+    pub state_root: Cid,
+    /// The actor, or `null` if the proof is of its absence
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Option<ActorState>>")]
+    pub actor: Option<ActorState>,
+    /// Blocks read from the state root to look up the actor
+    pub blocks: Vec<ProofBlock>,
+}
+lotus_json_with_self!(ActorProof);
+
+impl ActorProof {
+    /// Checks the proof against its state root, and returns the actor it proves.
+    pub fn verify(&self, address: &Address) -> anyhow::Result<Option<ActorState>> {
+        let actor = verify_actor_proof(
+            &self.state_root,
+            address,
+            self.blocks
+                .iter()
+                .map(|block| (block.cid, block.data.clone())),
+        )?;
+        anyhow::ensure!(actor == self.actor, "the actor doesn't match its proof");
+        Ok(actor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProofBlock {
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Cid>")]
+    pub cid: Cid,
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Vec<u8>>")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ForestStateExportDagParams {
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Cid>")]
+    pub root: Cid,
+    /// IPLD selector in its JSON representation, exclusive with `path` and `depth`
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub selector: Option<Selector>,
+    /// `/`-separated fields or list indices leading to the exported sub-DAG
+    pub path: Option<String>,
+    /// Number of IPLD nodes the sub-DAG is explored below the path, all if unset
+    pub depth: Option<u64>,
+    pub output_path: PathBuf,
+    /// Write a CARv2 file with an index of the blocks, instead of a CARv1 file
+    #[serde(default)]
+    pub car_v2: bool,
+}
+lotus_json_with_self!(ForestStateExportDagParams);
+
+/// Reward actor metrics in the parent state of a tipset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiRewardMetrics {
+    pub epoch: ChainEpoch,
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<TipsetKey>")]
+    pub tipset_key: TipsetKey,
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<StoragePower>")]
+    pub baseline_power: StoragePower,
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    pub this_epoch_reward: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    pub reward_estimate: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    pub total_mined: TokenAmount,
+}
+lotus_json_with_self!(ApiRewardMetrics);
+
+impl ApiRewardMetrics {
+    pub fn new(epoch: ChainEpoch, tipset_key: TipsetKey, metrics: RewardMetrics) -> Self {
+        let RewardMetrics {
+            baseline_power,
+            this_epoch_reward,
+            reward_estimate,
+            total_mined,
+        } = metrics;
+        Self {
+            epoch,
+            tipset_key,
+            baseline_power,
+            this_epoch_reward,
+            reward_estimate,
+            total_mined,
+        }
+    }
+}
+
+/// Method of a builtin actor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiActorMethod {
+    pub num: MethodNum,
+    pub name: String,
+    /// Name of the parameter type, `null` if the method takes no parameters
+    pub params: Option<String>,
+}
+lotus_json_with_self!(ApiActorMethod);
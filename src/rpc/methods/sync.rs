@@ -134,7 +134,7 @@ impl RpcMethod<1> for SyncSubmitBlock {
                 ctx.chain_store(),
                 ctx.bad_blocks.as_ref().map(AsRef::as_ref),
                 &genesis_ts,
-                ctx.chain_config().block_delay_secs,
+                ctx.chain_config(),
             )
             .context("failed to validate the tipset")?;
 
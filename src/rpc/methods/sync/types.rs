@@ -51,8 +51,12 @@ lotus_json_with_self!(SnapshotProgressState);
 pub struct SnapshotProgressTracker(Arc<parking_lot::RwLock<SnapshotProgressState>>);
 
 impl SnapshotProgressTracker {
-    /// Initializes the snapshot progress tracker and returns a callback function that updates the tracker
-    pub fn create_callback(&self) -> Option<Arc<dyn Fn(String) + Send + Sync>> {
+    /// Initializes the snapshot progress tracker and returns a callback function that updates the
+    /// tracker with the progress of the given import stage
+    pub fn create_callback(
+        &self,
+        stage: &'static str,
+    ) -> Option<Arc<dyn Fn(String) + Send + Sync>> {
         let snapshot_progress_tracker = self.0.clone();
 
         // Set the snapshot progress tracker to in progress state only
@@ -60,20 +64,33 @@ impl SnapshotProgressTracker {
         {
             let mut tracker = snapshot_progress_tracker.write();
             *tracker = SnapshotProgressState::InProgress {
-                message: "Loading progress...".to_string(),
+                message: format!("{stage}: loading progress..."),
             };
         }
 
         Some(Arc::new(move |msg: String| {
             snapshot_progress_tracker
                 .write()
-                .set_in_progress(msg.clone());
+                .set_in_progress(format!("{stage}: {msg}"));
         }))
     }
 
-    /// Sets the snapshot progress state to completed, once the snapshot download is finished
+    /// Sets the snapshot progress state to in progress, at the given import stage. Local snapshots
+    /// do not need to be downloaded and keep the not required state.
+    pub fn set_stage(&self, stage: &str) {
+        let mut tracker = self.0.write();
+        if !tracker.is_not_required() {
+            tracker.set_in_progress(stage.to_string());
+        }
+    }
+
+    /// Sets the snapshot progress state to completed, once the snapshot import is finished. Local
+    /// snapshots do not need to be downloaded and keep the not required state.
     pub fn completed(&self) {
-        self.0.write().set_completed();
+        let mut tracker = self.0.write();
+        if !tracker.is_not_required() {
+            tracker.set_completed();
+        }
     }
 
     /// Sets the snapshot progress state to not required, if downloading the snapshot is not required
@@ -5,6 +5,7 @@ use crate::metrics;
 use jsonrpsee::MethodResponse;
 use jsonrpsee::core::middleware::{Batch, Notification};
 use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use prometheus_client::metrics::gauge::Gauge;
 use tower::Layer;
 
 // State-less jsonrpcsee layer for measuring RPC metrics
@@ -30,19 +31,45 @@ impl<S> RecordMetrics<S> {
         F: Future<Output = MethodResponse>,
     {
         let method = metrics::RpcMethodLabel { method };
+        metrics::RPC_METHOD_CALLS.get_or_create(&method).inc();
+        let _in_flight = InFlight::new(&method);
         let start_time = std::time::Instant::now();
         let resp = future.await;
         metrics::RPC_METHOD_TIME
             .get_or_create(&method)
             // Observe the elapsed time in milliseconds
             .observe(start_time.elapsed().as_secs_f64() * 1000.0);
-        if resp.is_error() {
+        if let Some(code) = resp.as_error_code() {
             metrics::RPC_METHOD_FAILURE.get_or_create(&method).inc();
+            metrics::RPC_METHOD_ERRORS
+                .get_or_create(&metrics::RpcMethodErrorLabel {
+                    method: method.method,
+                    code,
+                })
+                .inc();
         }
         resp
     }
 }
 
+/// Counts a call in the in-flight gauge of its method until it completes, or is cancelled, e.g.
+/// when the client disconnects.
+struct InFlight(Gauge);
+
+impl InFlight {
+    fn new(method: &metrics::RpcMethodLabel) -> Self {
+        let gauge = metrics::RPC_METHOD_IN_FLIGHT.get_or_create(method).clone();
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
 impl<S> RpcServiceT for RecordMetrics<S>
 where
     S: RpcServiceT<MethodResponse = MethodResponse, NotificationResponse = MethodResponse>
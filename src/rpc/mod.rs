@@ -8,8 +8,11 @@ mod client;
 mod filter_layer;
 mod filter_list;
 pub mod json_validator;
+mod limits_layer;
 mod log_layer;
 mod metrics_layer;
+mod origin_policy;
+mod proxy_layer;
 mod request;
 mod segregation_layer;
 mod set_extension_layer;
@@ -25,7 +28,12 @@ use filter_layer::FilterLayer;
 pub use filter_list::FilterList;
 use futures::FutureExt as _;
 use jsonrpsee::server::ServerConfig;
+use limits_layer::LimitsLayer;
+pub use limits_layer::{RpcLimitsConfig, RpcMethodLimits};
 use log_layer::LogLayer;
+pub use origin_policy::OriginPolicy;
+use proxy_layer::ProxyLayer;
+pub use proxy_layer::RpcUpstream;
 use reflect::Ctx;
 pub use reflect::{ApiPaths, Permission, RpcMethod, RpcMethodExt};
 pub use request::Request;
@@ -35,7 +43,7 @@ use set_extension_layer::SetExtensionLayer;
 mod error;
 mod reflect;
 use ahash::HashMap;
-mod registry;
+pub(crate) mod registry;
 pub mod types;
 
 pub use methods::*;
@@ -59,6 +67,8 @@ macro_rules! for_each_rpc_method {
         // auth vertical
         $callback!($crate::rpc::auth::AuthNew);
         $callback!($crate::rpc::auth::AuthVerify);
+        $callback!($crate::rpc::auth::ForestAuthList);
+        $callback!($crate::rpc::auth::ForestAuthRevoke);
 
         // beacon vertical
         $callback!($crate::rpc::beacon::BeaconGetEntry);
@@ -80,6 +90,7 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::chain::ChainGetTipSet);
         $callback!($crate::rpc::chain::ChainGetTipSetV2);
         $callback!($crate::rpc::chain::ChainGetTipSetAfterHeight);
+        $callback!($crate::rpc::chain::ChainGetTipSetAtHeight);
         $callback!($crate::rpc::chain::ChainGetTipSetByHeight);
         $callback!($crate::rpc::chain::ChainHasObj);
         $callback!($crate::rpc::chain::ChainHead);
@@ -91,6 +102,7 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::chain::ForestChainExportDiff);
         $callback!($crate::rpc::chain::ForestChainExportStatus);
         $callback!($crate::rpc::chain::ForestChainExportCancel);
+        $callback!($crate::rpc::chain::ForestChainCheckpointAdd);
         $callback!($crate::rpc::chain::ChainGetTipsetByParentState);
 
         // common vertical
@@ -160,12 +172,14 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::eth::Web3ClientVersion);
         $callback!($crate::rpc::eth::EthSendRawTransaction);
         $callback!($crate::rpc::eth::EthSendRawTransactionUntrusted);
+        $callback!($crate::rpc::eth::EthSendTransaction);
 
         // gas vertical
         $callback!($crate::rpc::gas::GasEstimateFeeCap);
         $callback!($crate::rpc::gas::GasEstimateGasLimit);
         $callback!($crate::rpc::gas::GasEstimateGasPremium);
         $callback!($crate::rpc::gas::GasEstimateMessageGas);
+        $callback!($crate::rpc::gas::ForestGasEstimateGasPremium);
 
         // market vertical
         $callback!($crate::rpc::market::MarketAddBalance);
@@ -177,12 +191,14 @@ macro_rules! for_each_rpc_method {
         // mpool vertical
         $callback!($crate::rpc::mpool::MpoolBatchPush);
         $callback!($crate::rpc::mpool::MpoolBatchPushUntrusted);
+        $callback!($crate::rpc::mpool::MpoolCheckMessages);
         $callback!($crate::rpc::mpool::MpoolGetNonce);
         $callback!($crate::rpc::mpool::MpoolPending);
         $callback!($crate::rpc::mpool::MpoolPush);
         $callback!($crate::rpc::mpool::MpoolPushMessage);
         $callback!($crate::rpc::mpool::MpoolPushUntrusted);
         $callback!($crate::rpc::mpool::MpoolSelect);
+        $callback!($crate::rpc::mpool::ForestMpoolEstimateWins);
 
         // msig vertical
         $callback!($crate::rpc::msig::MsigGetAvailableBalance);
@@ -194,6 +210,9 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::net::NetAddrsListen);
         $callback!($crate::rpc::net::NetAgentVersion);
         $callback!($crate::rpc::net::NetAutoNatStatus);
+        $callback!($crate::rpc::net::NetBandwidthStats);
+        $callback!($crate::rpc::net::NetBandwidthStatsByPeer);
+        $callback!($crate::rpc::net::NetBandwidthStatsByProtocol);
         $callback!($crate::rpc::net::NetConnect);
         $callback!($crate::rpc::net::NetDisconnect);
         $callback!($crate::rpc::net::NetFindPeer);
@@ -216,8 +235,10 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::state::StateCompute);
         $callback!($crate::rpc::state::StateDealProviderCollateralBounds);
         $callback!($crate::rpc::state::StateFetchRoot);
+        $callback!($crate::rpc::state::ForestStateExportDag);
         $callback!($crate::rpc::state::StateGetActor);
         $callback!($crate::rpc::state::StateGetActorV2);
+        $callback!($crate::rpc::state::StateGetActorProof);
         $callback!($crate::rpc::state::StateGetID);
         $callback!($crate::rpc::state::StateGetAllAllocations);
         $callback!($crate::rpc::state::StateGetAllClaims);
@@ -261,9 +282,11 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::state::StateNetworkName);
         $callback!($crate::rpc::state::StateNetworkVersion);
         $callback!($crate::rpc::state::StateActorInfo);
+        $callback!($crate::rpc::state::ForestStateActorMethods);
         $callback!($crate::rpc::state::StateReadState);
         $callback!($crate::rpc::state::StateDecodeParams);
         $callback!($crate::rpc::state::StateReplay);
+        $callback!($crate::rpc::state::ForestStateRewardMetrics);
         $callback!($crate::rpc::state::StateSearchMsg);
         $callback!($crate::rpc::state::StateSearchMsgLimited);
         $callback!($crate::rpc::state::StateSectorExpiration);
@@ -327,6 +350,9 @@ macro_rules! for_each_rpc_method {
 
         // misc
         $callback!($crate::rpc::misc::GetActorEventsRaw);
+        $callback!($crate::rpc::misc::ForestGetActorEventsDecoded);
+        $callback!($crate::rpc::misc::ForestEventSchemaRegister);
+        $callback!($crate::rpc::misc::ForestEventSchemaList);
     };
 }
 pub(crate) use for_each_rpc_method;
@@ -441,6 +467,7 @@ use crate::rpc::metrics_layer::MetricsLayer;
 use crate::{chain_sync::network_context::SyncNetworkContext, key_management::KeyStore};
 
 use crate::blocks::FullTipset;
+use anyhow::Context as _;
 use fvm_ipld_blockstore::Blockstore;
 use jsonrpsee::{
     Methods,
@@ -452,6 +479,7 @@ use std::env;
 use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio_util::either::Either;
 use tower::Service;
 
 use crate::rpc::sync::SnapshotProgressState;
@@ -460,7 +488,7 @@ use openrpc_types::{self, ParamStructure};
 pub const DEFAULT_PORT: u16 = 2345;
 
 /// Request timeout read from environment variables
-static DEFAULT_REQUEST_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
+pub(crate) static DEFAULT_REQUEST_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
     env::var("FOREST_RPC_DEFAULT_TIMEOUT")
         .ok()
         .and_then(|it| Duration::from_secs(it.parse().ok()?).into())
@@ -476,9 +504,53 @@ static DEFAULT_MAX_CONNECTIONS: LazyLock<u32> = LazyLock::new(|| {
         .unwrap_or(1000)
 });
 
+/// Default size limit of the requests, the default of `jsonrpsee` (10 MiB) is not enough for
+/// methods like `Filecoin.StateMinerActiveSectors`.
 const MAX_REQUEST_BODY_SIZE: u32 = 64 * 1024 * 1024;
 const MAX_RESPONSE_BODY_SIZE: u32 = MAX_REQUEST_BODY_SIZE;
 
+/// Serves the entries of a batch one by one, with the `call` and `notification` of the service,
+/// for the layers that handle each call on its own rather than the batch as a whole.
+fn batch_one_by_one<'a, S>(
+    service: &S,
+    batch: jsonrpsee::core::middleware::Batch<'a>,
+    max_response_body_size: usize,
+) -> impl Future<Output = jsonrpsee::MethodResponse> + Send + 'a
+where
+    S: jsonrpsee::server::middleware::rpc::RpcServiceT<
+            MethodResponse = jsonrpsee::MethodResponse,
+            NotificationResponse = jsonrpsee::MethodResponse,
+        >,
+{
+    use futures::future::{Either, join_all, ready};
+    use jsonrpsee::MethodResponse;
+    use jsonrpsee::core::middleware::BatchEntry;
+    use jsonrpsee::core::server::BatchResponseBuilder;
+
+    let entries = batch
+        .into_iter()
+        .map(|entry| match entry {
+            Ok(BatchEntry::Call(req)) => Either::Left(service.call(req).map(Some)),
+            Ok(BatchEntry::Notification(n)) => {
+                Either::Right(Either::Left(service.notification(n).map(|_| None)))
+            }
+            Err(err) => {
+                let (err, id) = err.into_parts();
+                Either::Right(Either::Right(ready(Some(MethodResponse::error(id, err)))))
+            }
+        })
+        .collect::<Vec<_>>();
+    async move {
+        let mut builder = BatchResponseBuilder::new_with_limit(max_response_body_size);
+        for response in join_all(entries).await.into_iter().flatten() {
+            if let Err(too_big) = builder.append(response) {
+                return too_big;
+            }
+        }
+        MethodResponse::from_batch(builder.finish())
+    }
+}
+
 /// This is where you store persistent data, or at least access to stateful
 /// data.
 pub struct RPCState<DB> {
@@ -537,16 +609,43 @@ struct PerConnection<RpcMiddleware, HttpMiddleware> {
     keystore: Arc<RwLock<KeyStore>>,
 }
 
+/// Settings of the RPC server, the defaults serve all the methods over plain TCP only.
+#[derive(Default)]
+pub struct RpcServerConfig {
+    /// Serves the TCP connections over TLS
+    pub tls: Option<tokio_rustls::TlsAcceptor>,
+    /// Unix socket listener, served along the TCP listener
+    pub socket: Option<tokio::net::UnixListener>,
+    pub filter_list: FilterList,
+    /// Node the unsupported methods are proxied to
+    pub upstream: Option<RpcUpstream>,
+    pub origin_policy: OriginPolicy,
+    pub limits: RpcLimitsConfig,
+}
+
+/// Serves the RPC methods on the TCP listener, and on the unix socket listener of the config if
+/// there is one.
 pub async fn start_rpc<DB>(
     state: RPCState<DB>,
     rpc_listener: tokio::net::TcpListener,
     stop_handle: StopHandle,
-    filter_list: Option<FilterList>,
+    config: RpcServerConfig,
 ) -> anyhow::Result<()>
 where
     DB: Blockstore + Send + Sync + 'static,
 {
-    let filter_list = filter_list.unwrap_or_default();
+    let RpcServerConfig {
+        tls,
+        socket: rpc_socket,
+        filter_list,
+        upstream,
+        origin_policy,
+        limits,
+    } = config;
+    let upstream = upstream.map(Arc::new);
+    if let Some(upstream) = &upstream {
+        tracing::info!("Proxying unsupported RPC methods to {}", upstream.url());
+    }
     // `Arc` is needed because we will share the state between two modules
     let state = Arc::new(state);
     let keystore = state.keystore.clone();
@@ -557,6 +656,10 @@ where
         let state_clone = state.clone();
         move |params| chain::chain_notify(params, &state_clone)
     })?;
+    pubsub_module.register_channel(chain::ACTOR_CODE_CHANGES, {
+        let state_clone = state.clone();
+        move |params| chain::actor_code_changes(params, &state_clone)
+    })?;
 
     for module in modules.values_mut() {
         // register eth subscription APIs
@@ -572,9 +675,8 @@ where
         svc_builder: Server::builder()
             .set_config(
                 ServerConfig::builder()
-                    // Default size (10 MiB) is not enough for methods like `Filecoin.StateMinerActiveSectors`
-                    .max_request_body_size(MAX_REQUEST_BODY_SIZE)
-                    .max_response_body_size(MAX_RESPONSE_BODY_SIZE)
+                    .max_request_body_size(limits.server_max_request_body_bytes())
+                    .max_response_body_size(limits.server_max_response_body_bytes())
                     .max_connections(*DEFAULT_MAX_CONNECTIONS)
                     .set_id_provider(RandomHexStringIdProvider::new())
                     .build(),
@@ -582,6 +684,7 @@ where
             .set_http_middleware(
                 tower::ServiceBuilder::new()
                     .layer(CompressionLayer::new())
+                    .layer(origin_policy.cors_layer())
                     // Mark the `Authorization` request header as sensitive so it doesn't show in logs
                     .layer(SetSensitiveRequestHeadersLayer::new(std::iter::once(
                         http::header::AUTHORIZATION,
@@ -590,18 +693,28 @@ where
             .to_service_builder(),
         keystore,
     };
+    let limits = Arc::new(limits);
     tracing::info!("Ready for RPC connections");
     loop {
         let sock = tokio::select! {
         res = rpc_listener.accept() => {
             match res {
-              Ok((stream, _remote_addr)) => stream,
+              Ok((stream, _remote_addr)) => Either::Left(stream),
               Err(e) => {
                 tracing::error!("failed to accept v4 connection: {:?}", e);
                 continue;
               }
             }
           }
+        res = accept_unix(rpc_socket.as_ref()) => {
+            match res {
+              Ok(stream) => Either::Right(stream),
+              Err(e) => {
+                tracing::error!("failed to accept unix socket connection: {:?}", e);
+                continue;
+              }
+            }
+          }
           _ = per_conn.stop_handle.clone().shutdown() => break,
         };
 
@@ -609,8 +722,24 @@ where
             let methods = methods.clone();
             let per_conn = per_conn.clone();
             let filter_list = filter_list.clone();
+            let upstream = upstream.clone();
+            let origin_policy = origin_policy.clone();
+            let limits = limits.clone();
             move |req| {
                 let is_websocket = jsonrpsee::server::ws::is_upgrade_request(&req);
+                if is_websocket && !origin_policy.allows(req.headers().get(http::header::ORIGIN)) {
+                    tracing::debug!(
+                        "Rejected WebSocket connection from origin {:?}",
+                        req.headers().get(http::header::ORIGIN)
+                    );
+                    return async move {
+                        Ok(http::Response::builder()
+                            .status(http::StatusCode::FORBIDDEN)
+                            .body(Default::default())
+                            .unwrap_or_else(|_| http::Response::new(Default::default())))
+                    }
+                    .boxed();
+                }
                 let path = if let Ok(p) = ApiPaths::from_uri(req.uri()) {
                     p
                 } else {
@@ -633,8 +762,15 @@ where
                 let headers = req.headers().clone();
                 let rpc_middleware = RpcServiceBuilder::new()
                     .layer(SetExtensionLayer { path })
-                    .layer(SegregationLayer)
                     .layer(FilterLayer::new(filter_list.clone()))
+                    // Before the proxy, so that the proxied calls are limited too
+                    .layer(LimitsLayer::new(limits.clone()))
+                    .layer(ProxyLayer {
+                        upstream: upstream.clone(),
+                        headers: headers.clone(),
+                        keystore: keystore.clone(),
+                    })
+                    .layer(SegregationLayer)
                     .layer(validation_layer::JsonValidationLayer)
                     .layer(AuthLayer {
                         headers,
@@ -685,16 +821,84 @@ where
             }
         });
 
-        tokio::spawn(jsonrpsee::server::serve_with_graceful_shutdown(
-            sock,
-            svc,
-            stop_handle.clone().shutdown(),
-        ));
+        let shutdown = stop_handle.clone().shutdown();
+        match (sock, tls.clone()) {
+            (Either::Left(stream), Some(acceptor)) => {
+                // The handshake is done in the connection task, so that it doesn't hold up the
+                // other connections.
+                tokio::spawn(async move {
+                    let stream = match tokio::time::timeout(
+                        crate::utils::net::TLS_HANDSHAKE_TIMEOUT,
+                        acceptor.accept(stream),
+                    )
+                    .await
+                    {
+                        Ok(Ok(stream)) => stream,
+                        Ok(Err(e)) => {
+                            tracing::debug!("TLS handshake failed: {e}");
+                            return;
+                        }
+                        Err(_) => {
+                            tracing::debug!("TLS handshake timed out");
+                            return;
+                        }
+                    };
+                    let _ = jsonrpsee::server::serve_with_graceful_shutdown(stream, svc, shutdown)
+                        .await;
+                });
+            }
+            (sock, _) => {
+                tokio::spawn(jsonrpsee::server::serve_with_graceful_shutdown(
+                    sock, svc, shutdown,
+                ));
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Accepts a connection on the unix socket listener, or never resolves if there is none.
+async fn accept_unix(
+    listener: Option<&tokio::net::UnixListener>,
+) -> std::io::Result<tokio::net::UnixStream> {
+    match listener {
+        Some(listener) => Ok(listener.accept().await?.0),
+        None => std::future::pending().await,
+    }
+}
+
+/// Binds a unix socket listener to the path, replacing the stale socket of a previous run. The
+/// socket is only accessible to the user and the group of the node.
+pub fn bind_rpc_socket(path: &std::path::Path) -> anyhow::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{FileTypeExt as _, PermissionsExt as _};
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        anyhow::ensure!(
+            metadata.file_type().is_socket(),
+            "{} exists and is not a socket",
+            path.display()
+        );
+        std::fs::remove_file(path)?;
+    }
+    // The socket is bound in a private directory, out of the reach of others until its
+    // permissions are set, then moved into place.
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let private_dir = tempfile::Builder::new()
+        .prefix(".rpc-socket")
+        .permissions(std::fs::Permissions::from_mode(0o700))
+        .tempdir_in(parent)?;
+    let private_path = private_dir.path().join("rpc.sock");
+    let listener = tokio::net::UnixListener::bind(&private_path)
+        .with_context(|| format!("unable to listen on RPC socket {}", path.display()))?;
+    std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o660))?;
+    std::fs::rename(&private_path, path)?;
+    Ok(listener)
+}
+
 fn create_modules<DB>(state: Arc<RPCState<DB>>) -> HashMap<ApiPaths, RpcModule<RPCState<DB>>>
 where
     DB: Blockstore + Send + Sync + 'static,
@@ -806,7 +1010,7 @@ mod tests {
     use tokio::task::JoinSet;
 
     // To update RPC specs:
-    // `cargo test --lib -- rpc::tests::openrpc`
+    // `cargo test --lib -- rpc::tests::openrpc rpc::tests::rpc_method_permissions`
     // `cargo insta review`
 
     #[test]
@@ -829,6 +1033,27 @@ mod tests {
         insta::assert_yaml_snapshot!(path.path(), spec);
     }
 
+    // Permissions are not part of the OpenRPC specs, but loosening them is a breaking change too.
+    #[test]
+    fn rpc_method_permissions() {
+        let mut permissions = std::collections::BTreeMap::new();
+        macro_rules! insert {
+            ($ty:ty) => {
+                for name in std::iter::once(<$ty>::NAME).chain(<$ty>::NAME_ALIAS) {
+                    if let Some(permission) = permissions.insert(name, <$ty>::PERMISSION) {
+                        assert_eq!(
+                            permission,
+                            <$ty>::PERMISSION,
+                            "all versions of {name} must require the same permission"
+                        );
+                    }
+                }
+            };
+        }
+        for_each_rpc_method!(insert);
+        insta::assert_yaml_snapshot!(permissions);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_rpc_server() {
         let chain = NetworkChain::Calibnet;
@@ -841,7 +1066,7 @@ mod tests {
         let shutdown_send = state.shutdown.clone();
         let jwt_read_permissions = vec!["read".to_owned()];
         let jwt_read = super::methods::auth::AuthNew::create_token(
-            &state.keystore.read(),
+            &mut state.keystore.write(),
             chrono::Duration::hours(1),
             jwt_read_permissions.clone(),
         )
@@ -855,7 +1080,23 @@ mod tests {
 
         // Start an RPC server
 
-        let handle = tokio::spawn(start_rpc(state, rpc_listener, stop_handle, None));
+        let socket_dir = tempfile::tempdir().unwrap();
+        let socket_path = socket_dir.path().join("rpc.sock");
+        let rpc_socket = bind_rpc_socket(&socket_path).unwrap();
+        let permissions = std::fs::metadata(&socket_path).unwrap().permissions();
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&permissions) & 0o777,
+            0o660
+        );
+        let handle = tokio::spawn(start_rpc(
+            state,
+            rpc_listener,
+            stop_handle,
+            RpcServerConfig {
+                socket: Some(rpc_socket),
+                ..Default::default()
+            },
+        ));
 
         // Send a few http requests
 
@@ -888,6 +1129,16 @@ mod tests {
                 .unwrap(),
         );
 
+        let response = super::methods::auth::AuthVerify::call(&client, (jwt_read.clone(),))
+            .await
+            .unwrap();
+        assert_eq!(response, jwt_read_permissions);
+
+        // Send a request over the unix socket
+
+        let client =
+            Client::from_url("http://localhost/".parse().unwrap()).with_socket(socket_path);
+
         let response = super::methods::auth::AuthVerify::call(&client, (jwt_read,))
             .await
             .unwrap();
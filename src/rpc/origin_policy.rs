@@ -0,0 +1,85 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Origins of the browser-based applications allowed to call the RPC, through CORS for HTTP and
+//! through the `Origin` header of the upgrade requests for WebSocket. No browser is allowed by
+//! default. Clients that don't send an `Origin` header, i.e. that are not browsers, are not
+//! restricted.
+
+use http::{HeaderValue, Method, header};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Allows any origin.
+const ANY_ORIGIN: &str = "*";
+
+#[derive(Clone, Debug, Default)]
+pub struct OriginPolicy {
+    any: bool,
+    origins: Vec<HeaderValue>,
+}
+
+impl OriginPolicy {
+    /// Creates a policy allowing the origins, e.g. `http://localhost:3000`, or any origin if one
+    /// is `*`.
+    pub fn new(origins: &[String]) -> anyhow::Result<Self> {
+        let any = origins.iter().any(|origin| origin == ANY_ORIGIN);
+        let origins = origins
+            .iter()
+            .filter(|origin| *origin != ANY_ORIGIN)
+            .map(|origin| {
+                let origin = origin.trim_end_matches('/');
+                anyhow::ensure!(
+                    origin.starts_with("http://") || origin.starts_with("https://"),
+                    "invalid origin {origin}, expected a scheme and a host, e.g. http://localhost:3000"
+                );
+                Ok(HeaderValue::from_str(origin)?)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { any, origins })
+    }
+
+    /// Returns `true` if a request with the `Origin` header is allowed.
+    pub fn allows(&self, origin: Option<&HeaderValue>) -> bool {
+        match origin {
+            Some(origin) => self.any || self.origins.contains(origin),
+            None => true,
+        }
+    }
+
+    /// Returns the CORS layer of the HTTP requests.
+    pub fn cors_layer(&self) -> CorsLayer {
+        let allow_origin = if self.any {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(self.origins.clone())
+        };
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::POST])
+            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin_policy() {
+        let localhost = HeaderValue::from_static("http://localhost:3000");
+        let other = HeaderValue::from_static("https://example.com");
+
+        let policy = OriginPolicy::default();
+        assert!(policy.allows(None));
+        assert!(!policy.allows(Some(&localhost)));
+
+        let policy = OriginPolicy::new(&["http://localhost:3000/".into()]).unwrap();
+        assert!(policy.allows(Some(&localhost)));
+        assert!(!policy.allows(Some(&other)));
+
+        let policy = OriginPolicy::new(&["*".into()]).unwrap();
+        assert!(policy.allows(Some(&other)));
+
+        assert!(OriginPolicy::new(&["localhost:3000".into()]).is_err());
+    }
+}
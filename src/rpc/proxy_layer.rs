@@ -0,0 +1,395 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Middleware layer for the RPC gateway mode.
+//!
+//! Calls to methods that Forest does not support, as well as calls to methods that are explicitly
+//! configured to be proxied, are forwarded to an upstream Lotus or Forest node. Everything else is
+//! served locally.
+//!
+//! The calls are forwarded with the credentials of the upstream, so the caller must be granted the
+//! permission the method requires locally, or the admin permission for the methods Forest does not
+//! know about.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use ahash::HashSet;
+use futures::future::Either;
+use http::{HeaderMap, header::AUTHORIZATION};
+use jsonrpsee::MethodResponse;
+use jsonrpsee::core::ClientError;
+use jsonrpsee::core::middleware::{Batch, Notification};
+use jsonrpsee::server::ResponsePayload;
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::types::ErrorObject;
+use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
+use parking_lot::RwLock;
+use tower::Layer;
+use url::Url;
+
+use super::{
+    ApiPaths, Client, MAX_RESPONSE_BODY_SIZE, Permission, auth_layer, batch_one_by_one,
+    segregation_layer,
+};
+use crate::key_management::KeyStore;
+use crate::metrics;
+
+/// Upstream node that RPC calls are proxied to.
+pub struct RpcUpstream {
+    client: Client,
+    /// Methods that are always proxied, even if they are supported locally.
+    methods: HashSet<String>,
+}
+
+impl RpcUpstream {
+    pub fn new(url: Url, methods: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            client: Client::from_url(url),
+            methods: methods.into_iter().collect(),
+        }
+    }
+
+    /// Returns the upstream URL, without credentials.
+    pub fn url(&self) -> &Url {
+        self.client.base_url()
+    }
+
+    async fn forward(&self, path: ApiPaths, req: jsonrpsee::types::Request<'_>) -> MethodResponse {
+        let method_name = req.method_name().to_owned();
+        tracing::debug!("Proxying {method_name} to {}", self.url());
+        metrics::RPC_METHOD_PROXIED
+            .get_or_create(&metrics::RpcMethodLabel {
+                method: method_name.clone(),
+            })
+            .inc();
+
+        let params = match req.params().as_str() {
+            Some(params) => match serde_json::from_str(params) {
+                Ok(params) => params,
+                Err(e) => {
+                    return MethodResponse::error(
+                        req.id(),
+                        ErrorObject::owned(
+                            INTERNAL_ERROR_CODE,
+                            format!("invalid parameters: {e}"),
+                            None::<()>,
+                        ),
+                    );
+                }
+            },
+            None => serde_json::Value::Null,
+        };
+        let request = crate::rpc::Request::<serde_json::Value> {
+            method_name: method_name.into(),
+            params,
+            result_type: PhantomData,
+            api_paths: path.into(),
+            timeout: *super::DEFAULT_REQUEST_TIMEOUT,
+        };
+        match self.client.call(request).await {
+            Ok(result) => MethodResponse::response(
+                req.id(),
+                ResponsePayload::success(result),
+                MAX_RESPONSE_BODY_SIZE as usize,
+            ),
+            Err(ClientError::Call(e)) => MethodResponse::error(req.id(), e),
+            Err(e) => MethodResponse::error(
+                req.id(),
+                ErrorObject::owned(
+                    INTERNAL_ERROR_CODE,
+                    format!("upstream request failed: {e}"),
+                    None::<()>,
+                ),
+            ),
+        }
+    }
+}
+
+/// JSON-RPC middleware layer for proxying RPC calls to an upstream node.
+#[derive(Clone)]
+pub(super) struct ProxyLayer {
+    pub upstream: Option<Arc<RpcUpstream>>,
+    pub headers: HeaderMap,
+    pub keystore: Arc<RwLock<KeyStore>>,
+}
+
+impl<S> Layer<S> for ProxyLayer {
+    type Service = Proxy<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Proxy {
+            upstream: self.upstream.clone(),
+            headers: self.headers.clone(),
+            keystore: self.keystore.clone(),
+            service,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Proxy<S> {
+    upstream: Option<Arc<RpcUpstream>>,
+    headers: HeaderMap,
+    keystore: Arc<RwLock<KeyStore>>,
+    service: S,
+}
+
+/// Where a call is routed to, see [`Proxy::route`]
+enum Route {
+    Local,
+    Upstream(Arc<RpcUpstream>),
+    Unauthorized,
+}
+
+impl<S> Proxy<S> {
+    /// Returns whether the call should be forwarded to the upstream, served locally, or rejected
+    /// because the caller is not allowed to call the method upstream.
+    fn route(&self, path: Option<&ApiPaths>, method_name: &str) -> Route {
+        let (Some(upstream), Some(path)) = (&self.upstream, path) else {
+            return Route::Local;
+        };
+        let required = if segregation_layer::is_supported(path, method_name) {
+            if !upstream.methods.contains(method_name) {
+                return Route::Local;
+            }
+            match auth_layer::required_permission(method_name) {
+                Some(required) => required,
+                None => return Route::Local,
+            }
+        } else {
+            Permission::Admin
+        };
+        match auth_layer::check_permission(
+            &self.keystore,
+            self.headers.get(AUTHORIZATION),
+            required,
+        ) {
+            Ok(true) => Route::Upstream(upstream.clone()),
+            _ => {
+                tracing::warn!("Unauthorized attempt to proxy method {method_name}");
+                Route::Unauthorized
+            }
+        }
+    }
+}
+
+impl<S> RpcServiceT for Proxy<S>
+where
+    S: RpcServiceT<
+            MethodResponse = MethodResponse,
+            NotificationResponse = MethodResponse,
+            BatchResponse = MethodResponse,
+        > + Send
+        + Sync
+        + Clone
+        + 'static,
+{
+    type MethodResponse = S::MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(
+        &self,
+        req: jsonrpsee::types::Request<'a>,
+    ) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+        let path = req.extensions().get::<ApiPaths>().copied();
+        match (path, self.route(path.as_ref(), req.method_name())) {
+            (Some(path), Route::Upstream(upstream)) => {
+                Either::Right(Either::Left(
+                    async move { upstream.forward(path, req).await },
+                ))
+            }
+            (_, Route::Unauthorized) => Either::Right(Either::Right(async move {
+                MethodResponse::error(
+                    req.id(),
+                    ErrorObject::borrowed(
+                        http::StatusCode::UNAUTHORIZED.as_u16() as _,
+                        "Unauthorized",
+                        None,
+                    ),
+                )
+            })),
+            _ => Either::Left(self.service.call(req)),
+        }
+    }
+
+    fn batch<'a>(&self, batch: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+        if self.upstream.is_none() {
+            return Either::Left(self.service.batch(batch));
+        }
+        // The calls of the batch are routed one by one, as some may be served upstream.
+        Either::Right(batch_one_by_one(
+            self,
+            batch,
+            MAX_RESPONSE_BODY_SIZE as usize,
+        ))
+    }
+
+    fn notification<'a>(
+        &self,
+        n: Notification<'a>,
+    ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+        self.service.notification(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{ADMIN, JWT_IDENTIFIER, READ, create_token, generate_priv_key};
+    use crate::rpc::{RpcMethod as _, chain::ChainHead, wallet::WalletNew};
+    use futures::future::ready;
+    use http::HeaderValue;
+    use jsonrpsee::types::Id;
+
+    const UNSUPPORTED_METHOD: &str = "Filecoin.Cthulhu";
+
+    fn proxy(upstream_methods: &[&str]) -> (Proxy<()>, Vec<u8>) {
+        let mut keystore = KeyStore::new(crate::KeyStoreConfig::Memory).unwrap();
+        let key_info = generate_priv_key();
+        keystore.put(JWT_IDENTIFIER, key_info.clone()).unwrap();
+        // Nothing listens on this port, the calls proxied to it fail
+        let upstream = RpcUpstream::new(
+            "http://127.0.0.1:1/".parse().unwrap(),
+            upstream_methods.iter().map(ToString::to_string),
+        );
+        let proxy = Proxy {
+            upstream: Some(Arc::new(upstream)),
+            headers: HeaderMap::new(),
+            keystore: Arc::new(RwLock::new(keystore)),
+            service: (),
+        };
+        (proxy, key_info.private_key().clone())
+    }
+
+    fn with_token(mut proxy: Proxy<()>, key: &[u8], perms: &[&str]) -> Proxy<()> {
+        let token = create_token(
+            perms.iter().map(ToString::to_string).collect(),
+            key,
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        proxy.headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        proxy
+    }
+
+    #[test]
+    fn unauthenticated_callers_are_not_proxied() {
+        let (proxy, _) = proxy(&[WalletNew::NAME, ChainHead::NAME]);
+        let path = Some(&ApiPaths::V1);
+        assert!(matches!(
+            proxy.route(path, UNSUPPORTED_METHOD),
+            Route::Unauthorized
+        ));
+        assert!(matches!(
+            proxy.route(path, WalletNew::NAME),
+            Route::Unauthorized
+        ));
+        // Read methods configured to be proxied don't require a token
+        assert!(matches!(
+            proxy.route(path, ChainHead::NAME),
+            Route::Upstream(_)
+        ));
+    }
+
+    #[test]
+    fn read_only_callers_are_not_proxied() {
+        let (proxy, key) = proxy(&[WalletNew::NAME]);
+        let proxy = with_token(proxy, &key, READ);
+        let path = Some(&ApiPaths::V1);
+        assert!(matches!(
+            proxy.route(path, UNSUPPORTED_METHOD),
+            Route::Unauthorized
+        ));
+        assert!(matches!(
+            proxy.route(path, WalletNew::NAME),
+            Route::Unauthorized
+        ));
+        assert!(matches!(proxy.route(path, ChainHead::NAME), Route::Local));
+    }
+
+    #[test]
+    fn admin_callers_are_proxied() {
+        let (proxy, key) = proxy(&[WalletNew::NAME]);
+        let proxy = with_token(proxy, &key, ADMIN);
+        let path = Some(&ApiPaths::V1);
+        assert!(matches!(
+            proxy.route(path, UNSUPPORTED_METHOD),
+            Route::Upstream(_)
+        ));
+        assert!(matches!(
+            proxy.route(path, WalletNew::NAME),
+            Route::Upstream(_)
+        ));
+    }
+
+    /// Local service answering every call with the name of the method
+    #[derive(Clone)]
+    struct Local;
+
+    impl RpcServiceT for Local {
+        type MethodResponse = MethodResponse;
+        type NotificationResponse = MethodResponse;
+        type BatchResponse = MethodResponse;
+
+        fn call<'a>(
+            &self,
+            req: jsonrpsee::types::Request<'a>,
+        ) -> impl Future<Output = Self::MethodResponse> + Send + 'a {
+            let method_name = req.method_name().to_owned();
+            ready(MethodResponse::response(
+                req.id(),
+                ResponsePayload::success(method_name),
+                usize::MAX,
+            ))
+        }
+
+        fn batch<'a>(&self, _: Batch<'a>) -> impl Future<Output = Self::BatchResponse> + Send + 'a {
+            async { unreachable!("the calls of a batch are routed one by one") }
+        }
+
+        fn notification<'a>(
+            &self,
+            _: Notification<'a>,
+        ) -> impl Future<Output = Self::NotificationResponse> + Send + 'a {
+            ready(MethodResponse::notification())
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_calls_are_routed() {
+        let (proxy, key) = proxy(&[]);
+        let proxy = with_token(proxy, &key, ADMIN);
+        let proxy = Proxy {
+            upstream: proxy.upstream,
+            headers: proxy.headers,
+            keystore: proxy.keystore,
+            service: Local,
+        };
+        let mut batch = Batch::new();
+        for (i, method_name) in [ChainHead::NAME, UNSUPPORTED_METHOD]
+            .into_iter()
+            .enumerate()
+        {
+            let mut req =
+                jsonrpsee::types::Request::owned(method_name.into(), None, Id::Number(i as u64));
+            req.extensions_mut().insert(ApiPaths::V1);
+            batch.push(req);
+        }
+        let response = proxy.batch(batch).await;
+        let responses: Vec<serde_json::Value> =
+            serde_json::from_str(response.as_json().get()).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"], ChainHead::NAME);
+        assert!(
+            responses[1]["error"]["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("upstream request failed")
+        );
+    }
+}
@@ -28,6 +28,12 @@ macro_rules! register_evm_version {
             Method::GetStorageAt as MethodNum,
             GetStorageAtParams::deserialize_params,
         );
+        $registry.register_method_info(
+            $code_cid,
+            Method::GetStorageAt as MethodNum,
+            "GetStorageAt",
+            Some("GetStorageAtParams"),
+        );
 
         register_actor_methods!(
             $registry,
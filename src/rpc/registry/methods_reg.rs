@@ -4,7 +4,7 @@
 use crate::lotus_json::HasLotusJson;
 use crate::rpc::registry::actors_reg::{ACTOR_REGISTRY, ActorRegistry};
 use crate::shim::machine::BuiltinActor;
-use crate::shim::message::MethodNum;
+use crate::shim::message::{METHOD_SEND, MethodNum};
 use ahash::{HashMap, HashMapExt};
 use anyhow::{Context, Result, bail};
 use cid::Cid;
@@ -19,15 +19,27 @@ static METHOD_REGISTRY: LazyLock<MethodRegistry> =
 
 type ParamDeserializerFn = Box<dyn Fn(&[u8]) -> Result<Value> + Send + Sync>;
 
+/// Name and parameter type of a builtin actor method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodInfo {
+    /// Name of the method, e.g. `PublishStorageDeals`
+    pub name: &'static str,
+    /// Name of the parameter type of the method, `None` if it takes no parameters
+    pub params: Option<&'static str>,
+}
+
 pub struct MethodRegistry {
     // (code_cid, method_num) -> method param deserializer
     deserializers: HashMap<(Cid, MethodNum), ParamDeserializerFn>,
+    // (code_cid, method_num) -> method name and param type
+    methods: HashMap<(Cid, MethodNum), MethodInfo>,
 }
 
 impl MethodRegistry {
     fn new() -> Self {
         Self {
             deserializers: HashMap::new(),
+            methods: HashMap::new(),
         }
     }
 
@@ -53,6 +65,35 @@ impl MethodRegistry {
             .insert((code_cid, method_num), boxed_deserializer);
     }
 
+    /// Records the name and the parameter type of a method. The name is the last segment of the
+    /// path of the method, e.g. `PublishStorageDeals` for `Method::PublishStorageDeals`.
+    pub(crate) fn register_method_info(
+        &mut self,
+        code_cid: Cid,
+        method_num: MethodNum,
+        method: &'static str,
+        params: Option<&'static str>,
+    ) {
+        let name = method.rsplit("::").next().unwrap_or(method).trim();
+        self.methods
+            .insert((code_cid, method_num), MethodInfo { name, params });
+    }
+
+    fn method_info(&self, code_cid: &Cid, method_num: MethodNum) -> Option<MethodInfo> {
+        self.methods.get(&(*code_cid, method_num)).copied()
+    }
+
+    fn actor_methods(&self, code_cid: &Cid) -> Vec<(MethodNum, MethodInfo)> {
+        let mut methods: Vec<_> = self
+            .methods
+            .iter()
+            .filter(|((cid, _), _)| cid == code_cid)
+            .map(|(&(_, method_num), &info)| (method_num, info))
+            .collect();
+        methods.sort_by_key(|(method_num, _)| *method_num);
+        methods
+    }
+
     fn deserialize_params(
         &self,
         code_cid: &Cid,
@@ -77,6 +118,8 @@ impl MethodRegistry {
         };
 
         for (&cid, &(actor_type, version)) in ACTOR_REGISTRY.iter() {
+            // Every actor accepts plain value transfers
+            self.register_method_info(cid, METHOD_SEND, "Send", None);
             match actor_type {
                 BuiltinActor::Account => {
                     account::register_account_actor_methods(self, cid, version)
@@ -115,6 +158,17 @@ pub fn deserialize_params(
     METHOD_REGISTRY.deserialize_params(code_cid, method_num, params_bytes)
 }
 
+/// Returns the name and the parameter type of a builtin actor method, `None` if the actor or the
+/// method is unknown.
+pub fn method_info(code_cid: &Cid, method_num: MethodNum) -> Option<MethodInfo> {
+    METHOD_REGISTRY.method_info(code_cid, method_num)
+}
+
+/// Returns the known methods of a builtin actor, ordered by method number.
+pub fn actor_methods(code_cid: &Cid) -> Vec<(MethodNum, MethodInfo)> {
+    METHOD_REGISTRY.actor_methods(code_cid)
+}
+
 macro_rules! register_actor_methods {
     // Handle an empty params case
     ($registry:expr, $code_cid:expr, [
@@ -134,6 +188,12 @@ macro_rules! register_actor_methods {
                     }
                 },
             );
+            $registry.register_method_info(
+                $code_cid,
+                $method as MethodNum,
+                stringify!($method),
+                None,
+            );
         )*
     };
 
@@ -146,6 +206,12 @@ macro_rules! register_actor_methods {
                 $method as MethodNum,
                 |bytes| -> anyhow::Result<$param_type> { Ok(fvm_ipld_encoding::from_slice(bytes)?) },
             );
+            $registry.register_method_info(
+                $code_cid,
+                $method as MethodNum,
+                stringify!($method),
+                Some(stringify!($param_type)),
+            );
         )*
     };
 }
@@ -321,6 +387,30 @@ mod test {
         assert!(result3.is_err());
     }
 
+    #[test]
+    fn test_method_info() {
+        let market_cid = get_real_actor_cid(BuiltinActor::Market, ActorVersion::V16).unwrap();
+        let publish_storage_deals = method_info(&market_cid, 4).unwrap();
+        assert_eq!(publish_storage_deals.name, "PublishStorageDeals");
+        assert_eq!(
+            publish_storage_deals.params,
+            Some("PublishStorageDealsParams")
+        );
+        assert_eq!(method_info(&market_cid, METHOD_SEND).unwrap().name, "Send");
+        assert_eq!(
+            method_info(&market_cid, 1),
+            Some(MethodInfo {
+                name: "Constructor",
+                params: None
+            })
+        );
+        assert_eq!(method_info(&create_test_cid(b"unknown"), 4), None);
+
+        let methods = actor_methods(&market_cid);
+        assert!(methods.is_sorted_by_key(|(method_num, _)| *method_num));
+        assert!(methods.contains(&(4, publish_storage_deals)));
+    }
+
     #[test]
     fn test_system_actor_deserialize_params_cbor_null() {
         let system_cid = get_real_actor_cid(BuiltinActor::System, ActorVersion::V16)
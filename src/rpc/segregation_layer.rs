@@ -34,6 +34,7 @@ static VERSION_METHODS_MAPPINGS: LazyLock<HashMap<ApiPaths, HashSet<&'static str
             for_each_rpc_method!(insert);
 
             supported.insert(crate::rpc::chain::CHAIN_NOTIFY);
+            supported.insert(crate::rpc::chain::ACTOR_CODE_CHANGES);
             supported.insert(crate::rpc::channel::CANCEL_METHOD_NAME);
 
             map.insert(version, supported);
@@ -42,6 +43,13 @@ static VERSION_METHODS_MAPPINGS: LazyLock<HashMap<ApiPaths, HashSet<&'static str
         map
     });
 
+/// Returns `true` if the method is served locally on the given API path.
+pub(super) fn is_supported(path: &ApiPaths, method_name: &str) -> bool {
+    VERSION_METHODS_MAPPINGS
+        .get(path)
+        .is_some_and(|set| set.contains(method_name))
+}
+
 /// JSON-RPC middleware layer for segregating RPC methods by the versions they support.
 #[derive(Clone, Default)]
 pub(super) struct SegregationLayer;
@@ -61,11 +69,7 @@ pub(super) struct SegregationService<S> {
 
 impl<S> SegregationService<S> {
     fn check<'a>(&self, path: Option<&ApiPaths>, method_name: &str) -> Result<(), ErrorObject<'a>> {
-        let supported = path
-            .and_then(|p| VERSION_METHODS_MAPPINGS.get(p))
-            .map(|set| set.contains(method_name))
-            .unwrap_or(false);
-        if supported {
+        if path.is_some_and(|p| is_supported(p, method_name)) {
             Ok(())
         } else {
             Err(ErrorObject::borrowed(
@@ -1,4 +1,6 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub type CreateParams = fil_actor_eam_state::v16::CreateParams;
+pub type CreateReturn = fil_actor_eam_state::v16::CreateReturn;
 pub type CreateExternalReturn = fil_actor_eam_state::v16::CreateExternalReturn;
@@ -45,10 +45,74 @@ impl State {
         })
     }
 
+    /// Creates the state of a contract in the given actors version.
+    pub fn new(
+        version: u64,
+        bytecode: Cid,
+        bytecode_hash: [u8; 32],
+        contract_state: Cid,
+        nonce: u64,
+    ) -> anyhow::Result<Self> {
+        macro_rules! new_state {
+            ($version:ident $(, $transient_data:ident)?) => {
+                State::from(fil_actor_evm_state::$version::State {
+                    bytecode,
+                    bytecode_hash: bytecode_hash.into(),
+                    contract_state,
+                    $($transient_data: None,)?
+                    nonce,
+                    tombstone: None,
+                })
+            };
+        }
+        Ok(match version {
+            10 => new_state!(v10),
+            11 => new_state!(v11),
+            12 => new_state!(v12),
+            13 => new_state!(v13),
+            14 => new_state!(v14),
+            15 => new_state!(v15),
+            16 => new_state!(v16, transient_data),
+            17 => new_state!(v17, transient_data),
+            _ => anyhow::bail!("unsupported EVM actor version {version}"),
+        })
+    }
+
     pub fn nonce(&self) -> u64 {
         delegate_state!(self.nonce)
     }
 
+    /// Root of the storage of the contract.
+    pub fn contract_state(&self) -> Cid {
+        delegate_state!(self.contract_state)
+    }
+
+    pub fn set_contract_state(&mut self, contract_state: Cid) {
+        macro_rules! set_contract_state {
+            ($($version:ident),*) => {
+                match self {
+                    $(State::$version(st) => st.contract_state = contract_state,)*
+                }
+            };
+        }
+        set_contract_state!(V10, V11, V12, V13, V14, V15, V16, V17)
+    }
+
+    /// Replaces the bytecode of the contract.
+    pub fn set_bytecode(&mut self, bytecode: Cid, bytecode_hash: [u8; 32]) {
+        macro_rules! set_bytecode {
+            ($($version:ident),*) => {
+                match self {
+                    $(State::$version(st) => {
+                        st.bytecode = bytecode;
+                        st.bytecode_hash = bytecode_hash.into();
+                    })*
+                }
+            };
+        }
+        set_bytecode!(V10, V11, V12, V13, V14, V15, V16, V17)
+    }
+
     pub fn is_alive(&self) -> bool {
         delegate_state!(self.tombstone.is_none())
     }
@@ -42,6 +42,11 @@ impl State {
         })
     }
 
+    /// The ID the next actor created is assigned.
+    pub fn next_id(&self) -> u64 {
+        delegate_state!(self.next_id)
+    }
+
     pub fn into_network_name(self) -> String {
         delegate_state!(self.network_name)
     }
@@ -378,7 +378,7 @@ where
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct DealProposal {
     #[serde(rename = "PieceCID")]
@@ -396,6 +396,28 @@ pub struct DealProposal {
     pub client_collateral: TokenAmount,
 }
 
+#[cfg(test)]
+impl quickcheck::Arbitrary for DealProposal {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        use quickcheck::Arbitrary as _;
+
+        Self {
+            piece_cid: Cid::arbitrary(g),
+            // `PaddedPieceSize` is a foreign type
+            piece_size: PaddedPieceSize(u64::arbitrary(g)),
+            verified_deal: bool::arbitrary(g),
+            client: Address::arbitrary(g),
+            provider: Address::arbitrary(g),
+            label: String::arbitrary(g),
+            start_epoch: ChainEpoch::arbitrary(g),
+            end_epoch: ChainEpoch::arbitrary(g),
+            storage_price_per_epoch: TokenAmount::arbitrary(g),
+            provider_collateral: TokenAmount::arbitrary(g),
+            client_collateral: TokenAmount::arbitrary(g),
+        }
+    }
+}
+
 impl TryFrom<&fil_actor_market_state::v9::DealProposal> for DealProposal {
     type Error = anyhow::Error;
 
@@ -741,7 +763,8 @@ where
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct DealState {
     pub sector_start_epoch: ChainEpoch, // -1 if not yet included in proven sector
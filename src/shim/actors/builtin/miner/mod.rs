@@ -856,7 +856,8 @@ impl MinerInfo {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct MinerPower {
     pub miner_power: Claim,
     pub total_power: Claim,
@@ -1210,6 +1211,7 @@ impl From<fil_actor_miner_state::v17::SectorOnChainInfo> for SectorOnChainInfo {
 /// "Deadline" refers to the window during which proofs may be submitted.
 /// Windows are non-overlapping ranges [Open, Close), but the challenge epoch for a window occurs
 /// before the window opens.
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct DeadlineInfo {
@@ -3,7 +3,6 @@
 
 pub mod ext;
 
-use crate::list_miners_for_state;
 use crate::shim::{
     actors::{FilterEstimate, convert::*},
     address::Address,
@@ -11,6 +10,7 @@ use crate::shim::{
     runtime::Policy,
     sector::StoragePower,
 };
+use crate::{list_claims_for_state, list_miners_for_state};
 use fvm_ipld_blockstore::Blockstore;
 use serde::{Deserialize, Serialize};
 use spire_enum::prelude::delegated_enum;
@@ -151,6 +151,73 @@ impl State {
         }
     }
 
+    /// Returns the power claim of every miner that has claimed power in the power actor
+    pub fn list_all_claims<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<(Address, Claim)>> {
+        match self {
+            State::V8(st) => list_claims_for_state!(st, store, v8),
+            State::V9(st) => list_claims_for_state!(st, store, v9),
+            State::V10(st) => list_claims_for_state!(st, store, v10),
+            State::V11(st) => list_claims_for_state!(st, store, v11),
+            State::V12(st) => {
+                let claims = st.load_claims(store)?;
+                let mut miner_claims = Vec::new();
+                claims.for_each(|addr, claim| {
+                    miner_claims.push((addr.into(), claim.clone().into()));
+                    Ok(())
+                })?;
+                Ok(miner_claims)
+            }
+            State::V13(st) => {
+                let claims = st.load_claims(store)?;
+                let mut miner_claims = Vec::new();
+                claims.for_each(|addr, claim| {
+                    miner_claims.push((addr.into(), claim.clone().into()));
+                    Ok(())
+                })?;
+                Ok(miner_claims)
+            }
+            State::V14(st) => {
+                let claims = st.load_claims(store)?;
+                let mut miner_claims = Vec::new();
+                claims.for_each(|addr, claim| {
+                    miner_claims.push((addr.into(), claim.clone().into()));
+                    Ok(())
+                })?;
+                Ok(miner_claims)
+            }
+            State::V15(st) => {
+                let claims = st.load_claims(store)?;
+                let mut miner_claims = Vec::new();
+                claims.for_each(|addr, claim| {
+                    miner_claims.push((addr.into(), claim.clone().into()));
+                    Ok(())
+                })?;
+                Ok(miner_claims)
+            }
+            State::V16(st) => {
+                let claims = st.load_claims(store)?;
+                let mut miner_claims = Vec::new();
+                claims.for_each(|addr, claim| {
+                    miner_claims.push((addr.into(), claim.clone().into()));
+                    Ok(())
+                })?;
+                Ok(miner_claims)
+            }
+            State::V17(st) => {
+                let claims = st.load_claims(store)?;
+                let mut miner_claims = Vec::new();
+                claims.for_each(|addr, claim| {
+                    miner_claims.push((addr.into(), claim.clone().into()));
+                    Ok(())
+                })?;
+                Ok(miner_claims)
+            }
+        }
+    }
+
     /// Returns the total power claim.
     pub fn total_power(&self) -> Claim {
         delegate_state!(self => |st| Claim {
@@ -264,6 +331,7 @@ impl State {
     }
 }
 
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct Claim {
     /// Sum of raw byte power for a miner's sectors.
@@ -375,3 +443,22 @@ macro_rules! list_miners_for_state {
         Ok(miners)
     }};
 }
+
+#[macro_export]
+macro_rules! list_claims_for_state {
+    ($state:ident, $store:ident, $version:ident) => {{
+        let claims = fil_actors_shared::$version::make_map_with_root::<
+            _,
+            fil_actor_power_state::$version::Claim,
+        >(&$state.claims, $store)?;
+        let mut miner_claims = Vec::new();
+        claims.for_each(|bytes, claim| {
+            miner_claims.push((
+                Address::from_bytes(bytes).expect("Cannot get address from bytes"),
+                Claim::from(claim.clone()),
+            ));
+            Ok(())
+        })?;
+        Ok(miner_claims)
+    }};
+}
@@ -34,6 +34,9 @@ pub const ADDRESS: Address = Address::new_id(2);
 /// Reward actor method.
 pub type Method = fil_actor_reward_state::v8::Method;
 
+/// Number of fractional bits of the fixed-point filter estimates
+const FILTER_ESTIMATE_PRECISION: u32 = 128;
+
 /// Reward actor state.
 #[delegated_enum(impl_conversions)]
 #[derive(Serialize, Debug)]
@@ -91,6 +94,18 @@ impl State {
         delegate_state!(self.this_epoch_baseline_power.borrow())
     }
 
+    /// The block reward of this state's epoch.
+    pub fn this_epoch_reward(&self) -> TokenAmount {
+        delegate_state!(self.this_epoch_reward.clone().into())
+    }
+
+    /// The smoothed estimate of the block reward at this state's epoch.
+    pub fn this_epoch_reward_estimate(&self) -> TokenAmount {
+        delegate_state!(self => |st| TokenAmount::from_atto(
+            &st.this_epoch_reward_smoothed.position >> FILTER_ESTIMATE_PRECISION
+        ))
+    }
+
     pub fn pre_commit_deposit_for_power(
         &self,
         network_qa_power: FilterEstimate,
@@ -3,6 +3,8 @@
 
 use std::{fmt::Display, str::FromStr};
 
+use anyhow::Context as _;
+
 use data_encoding::Encoding;
 use data_encoding_macro::new_encoding;
 use fvm_shared2::address::Address as Address_v2;
@@ -13,6 +15,7 @@ pub use fvm_shared4::address::{Error, Network, PAYLOAD_HASH_LEN, Payload, Protoc
 use get_size2::GetSize;
 use integer_encoding::VarInt;
 use num_traits::FromPrimitive;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::sync::{
     LazyLock,
@@ -99,6 +102,67 @@ mod network_guard_impl {
     }
 }
 
+/// Namespace of delegated (`f4`) addresses. Delegated addresses are managed by the namespace
+/// actor, e.g. `f410f...` addresses are managed by the Ethereum Address Manager (ID 10).
+///
+/// Namespaces are identified by a human-readable prefix, see [`Address::to_namespaced_string`].
+/// The Ethereum Address Manager is always registered, other address classes can be added with
+/// [`DelegatedNamespace::register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DelegatedNamespace {
+    /// ID of the namespace actor
+    pub actor_id: u64,
+    /// Human-readable prefix of the namespace
+    pub prefix: &'static str,
+}
+
+static DELEGATED_NAMESPACES: LazyLock<RwLock<Vec<DelegatedNamespace>>> =
+    LazyLock::new(|| RwLock::new(vec![DelegatedNamespace::EAM]));
+
+impl DelegatedNamespace {
+    /// The Ethereum Address Manager namespace.
+    pub const EAM: Self = Self {
+        actor_id: 10,
+        prefix: "eth",
+    };
+
+    /// Registers a namespace. Registering the same namespace again is a no-op, but neither the
+    /// actor ID nor the prefix may be reused by a different namespace.
+    pub fn register(namespace: Self) -> anyhow::Result<()> {
+        let mut namespaces = DELEGATED_NAMESPACES.write();
+        if let Some(existing) = namespaces
+            .iter()
+            .find(|ns| ns.actor_id == namespace.actor_id || ns.prefix == namespace.prefix)
+        {
+            anyhow::ensure!(
+                *existing == namespace,
+                "delegated namespace {namespace:?} conflicts with {existing:?}"
+            );
+            return Ok(());
+        }
+        namespaces.push(namespace);
+        Ok(())
+    }
+
+    /// Looks up a registered namespace by the ID of its actor.
+    pub fn from_actor_id(actor_id: u64) -> Option<Self> {
+        DELEGATED_NAMESPACES
+            .read()
+            .iter()
+            .find(|ns| ns.actor_id == actor_id)
+            .copied()
+    }
+
+    /// Looks up a registered namespace by its prefix.
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        DELEGATED_NAMESPACES
+            .read()
+            .iter()
+            .find(|ns| ns.prefix == prefix)
+            .copied()
+    }
+}
+
 /// A Filecoin address is an identifier that refers to an actor in the Filecoin state. All actors
 /// (miner actors, the storage market actor, account actors) have an address. This address encodes
 /// information about the network to which an actor belongs, the specific type of address encoding,
@@ -181,6 +245,40 @@ impl Address {
     pub fn from_bytes(bz: &[u8]) -> Result<Self, Error> {
         Address_latest::from_bytes(bz).map(Address)
     }
+
+    /// Returns the registered namespace of a delegated address, if any.
+    pub fn delegated_namespace(&self) -> Option<DelegatedNamespace> {
+        match self.payload() {
+            Payload::Delegated(addr) => DelegatedNamespace::from_actor_id(addr.namespace()),
+            _ => None,
+        }
+    }
+
+    /// Formats a delegated address in a registered namespace as `<prefix>:0x<subaddress>`, e.g.
+    /// `eth:0x...` for Ethereum addresses.
+    pub fn to_namespaced_string(&self) -> Option<String> {
+        let namespace = self.delegated_namespace()?;
+        match self.payload() {
+            Payload::Delegated(addr) => Some(format!(
+                "{}:0x{}",
+                namespace.prefix,
+                hex::encode(addr.subaddress())
+            )),
+            _ => None,
+        }
+    }
+
+    /// Parses a delegated address formatted by [`Address::to_namespaced_string`].
+    pub fn from_namespaced_str(s: &str) -> anyhow::Result<Self> {
+        let (prefix, subaddress) = s
+            .split_once(':')
+            .with_context(|| format!("missing namespace prefix in {s}"))?;
+        let namespace = DelegatedNamespace::from_prefix(prefix)
+            .with_context(|| format!("unknown delegated namespace {prefix}"))?;
+        let subaddress = hex::decode(subaddress.strip_prefix("0x").unwrap_or(subaddress))
+            .with_context(|| format!("invalid subaddress in {s}"))?;
+        Ok(Self::new_delegated(namespace.actor_id, &subaddress)?)
+    }
 }
 
 impl FromStr for Address {
@@ -460,3 +558,51 @@ fn inherit_global_network() {
     .unwrap();
     CurrentNetwork::set_global(outer_network);
 }
+
+#[test]
+fn eam_namespace() {
+    assert_eq!(
+        Address::ETHEREUM_ACCOUNT_MANAGER_ACTOR.id().unwrap(),
+        DelegatedNamespace::EAM.actor_id
+    );
+    let address = Address::new_delegated(10, &[0xab; 20]).unwrap();
+    assert_eq!(address.delegated_namespace(), Some(DelegatedNamespace::EAM));
+    let namespaced = address.to_namespaced_string().unwrap();
+    assert_eq!(namespaced, format!("eth:0x{}", "ab".repeat(20)));
+    assert_eq!(Address::from_namespaced_str(&namespaced).unwrap(), address);
+    assert_eq!(Address::new_id(10).delegated_namespace(), None);
+}
+
+#[test]
+fn register_delegated_namespace() {
+    let namespace = DelegatedNamespace {
+        actor_id: 1234,
+        prefix: "test",
+    };
+    let address = Address::new_delegated(1234, b"subaddress").unwrap();
+    assert!(Address::from_namespaced_str("test:0x00").is_err());
+
+    DelegatedNamespace::register(namespace).unwrap();
+    // Idempotent
+    DelegatedNamespace::register(namespace).unwrap();
+    assert_eq!(address.delegated_namespace(), Some(namespace));
+    assert_eq!(
+        Address::from_namespaced_str(&address.to_namespaced_string().unwrap()).unwrap(),
+        address
+    );
+    // Conflicts
+    assert!(
+        DelegatedNamespace::register(DelegatedNamespace {
+            actor_id: 1234,
+            prefix: "other",
+        })
+        .is_err()
+    );
+    assert!(
+        DelegatedNamespace::register(DelegatedNamespace {
+            actor_id: 10,
+            prefix: "test",
+        })
+        .is_err()
+    );
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::trace::ExecutionEvent;
+use crate::db::{BlockKind, with_block_kind};
 use crate::shim::{
     econ::TokenAmount, fvm_shared_latest::ActorID, fvm_shared_latest::error::ExitCode,
 };
@@ -135,41 +136,66 @@ impl Receipt {
         receipts: &Cid,
         i: u64,
     ) -> anyhow::Result<Option<Self>> {
-        // Try Receipt_v4 first. (Receipt_v4 and Receipt_v3 are identical, use v4 here)
-        if let Ok(amt) = Amtv0::load(receipts, db)
-            && let Ok(receipts) = amt.get(i)
-        {
-            return Ok(receipts.cloned().map(Receipt::V4));
-        }
+        with_block_kind(BlockKind::Receipt, || {
+            // Try Receipt_v4 first. (Receipt_v4 and Receipt_v3 are identical, use v4 here)
+            if let Ok(amt) = Amtv0::load(receipts, db)
+                && let Ok(receipts) = amt.get(i)
+            {
+                return Ok(receipts.cloned().map(Receipt::V4));
+            }
 
-        // Fallback to Receipt_v2.
-        let amt = Amtv0::load(receipts, db)?;
-        let receipts = amt.get(i)?;
-        Ok(receipts.cloned().map(Receipt::V2))
+            // Fallback to Receipt_v2.
+            let amt = Amtv0::load(receipts, db)?;
+            let receipts = amt.get(i)?;
+            Ok(receipts.cloned().map(Receipt::V2))
+        })
     }
 
     pub fn get_receipts(db: &impl Blockstore, receipts_cid: Cid) -> anyhow::Result<Vec<Receipt>> {
-        let mut receipts = Vec::new();
-
-        // Try Receipt_v4 first. (Receipt_v4 and Receipt_v3 are identical, use v4 here)
-        if let Ok(amt) = Amtv0::<fvm_shared4::receipt::Receipt, _>::load(&receipts_cid, db) {
-            amt.for_each(|_, receipt| {
-                receipts.push(Receipt::V4(receipt.clone()));
-                Ok(())
-            })?;
-        } else {
-            // Fallback to Receipt_v2.
-            let amt = Amtv0::<fvm_shared2::receipt::Receipt, _>::load(&receipts_cid, db)?;
-            amt.for_each(|_, receipt| {
-                receipts.push(Receipt::V2(receipt.clone()));
-                Ok(())
-            })?;
-        }
+        Self::get_receipts_ranged(db, receipts_cid, 0, None)
+    }
+
+    /// Loads at most `limit` receipts starting at index `start`. Only the nodes of the AMT that
+    /// hold the range are loaded.
+    pub fn get_receipts_ranged(
+        db: &impl Blockstore,
+        receipts_cid: Cid,
+        start: u64,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Vec<Receipt>> {
+        let mut receipts = vec![];
+
+        with_block_kind(BlockKind::Receipt, || {
+            // Try Receipt_v4 first. (Receipt_v4 and Receipt_v3 are identical, use v4 here)
+            if let Ok(amt) = Amtv0::<fvm_shared4::receipt::Receipt, _>::load(&receipts_cid, db) {
+                receipts.reserve(ranged_len(amt.count(), start, limit));
+                amt.for_each_ranged(Some(start), limit, |_, receipt| {
+                    receipts.push(Receipt::V4(receipt.clone()));
+                    Ok(())
+                })?;
+            } else {
+                // Fallback to Receipt_v2.
+                let amt = Amtv0::<fvm_shared2::receipt::Receipt, _>::load(&receipts_cid, db)?;
+                receipts.reserve(ranged_len(amt.count(), start, limit));
+                amt.for_each_ranged(Some(start), limit, |_, receipt| {
+                    receipts.push(Receipt::V2(receipt.clone()));
+                    Ok(())
+                })?;
+            }
+            anyhow::Ok(())
+        })?;
 
         Ok(receipts)
     }
 }
 
+/// Number of the entries of an AMT of `count` entries in the range of at most `limit` entries
+/// starting at index `start`.
+fn ranged_len(count: u64, start: u64, limit: Option<usize>) -> usize {
+    let len = usize::try_from(count.saturating_sub(start)).unwrap_or(usize::MAX);
+    limit.map_or(len, |limit| limit.min(len))
+}
+
 #[delegated_enum(impl_conversions)]
 #[derive(Clone, Debug)]
 pub enum Entry {
@@ -258,23 +284,31 @@ impl StampedEvent {
         events_root: &Cid,
     ) -> anyhow::Result<Vec<StampedEvent>> {
         let mut events = Vec::new();
+        Self::for_each_event(db, events_root, |event| {
+            events.push(event);
+            Ok(())
+        })?;
+        Ok(events)
+    }
 
+    /// Calls `f` on the events of the events AMT root CID one at a time, in the order they are
+    /// stored in the AMT, without keeping the loaded nodes of the AMT in memory.
+    pub fn for_each_event<DB: Blockstore>(
+        db: &DB,
+        events_root: &Cid,
+        mut f: impl FnMut(StampedEvent) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
         // Try StampedEvent_v4 first (StampedEvent_v4 and StampedEvent_v3 are identical, use v4 here)
-        if let Ok(amt) = Amt::<StampedEvent_v4, _>::load(events_root, db) {
-            amt.for_each_cacheless(|_, event| {
-                events.push(StampedEvent::V4(event.clone()));
-                Ok(())
-            })?;
-        } else {
-            // Fallback to StampedEvent_v3
-            let amt = Amt::<StampedEvent_v3, _>::load(events_root, db)?;
-            amt.for_each_cacheless(|_, event| {
-                events.push(StampedEvent::V3(event.clone()));
-                Ok(())
-            })?;
-        }
-
-        Ok(events)
+        with_block_kind(BlockKind::Event, || {
+            if let Ok(amt) = Amt::<StampedEvent_v4, _>::load(events_root, db) {
+                amt.for_each_cacheless(|_, event| f(StampedEvent::V4(event.clone())))?;
+            } else {
+                // Fallback to StampedEvent_v3
+                let amt = Amt::<StampedEvent_v3, _>::load(events_root, db)?;
+                amt.for_each_cacheless(|_, event| f(StampedEvent::V3(event.clone())))?;
+            }
+            Ok(())
+        })
     }
 }
 
@@ -353,4 +387,38 @@ mod tests {
         .unwrap();
         assert_eq!(encoded, encoded2);
     }
+
+    #[test]
+    fn get_receipts_ranged() {
+        let db = crate::db::MemoryDB::default();
+        let receipts = (0..10u64)
+            .map(|gas_used| Receipt_v4 {
+                exit_code: fvm_shared4::error::ExitCode::new(0),
+                return_data: RawBytes::default(),
+                gas_used,
+                events_root: None,
+            })
+            .collect::<Vec<_>>();
+        let receipts_root = Amtv0::new_from_iter(&db, receipts).unwrap();
+
+        let gas_used =
+            |receipts: Vec<Receipt>| receipts.iter().map(Receipt::gas_used).collect::<Vec<_>>();
+        assert_eq!(
+            gas_used(Receipt::get_receipts(&db, receipts_root).unwrap()),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            gas_used(Receipt::get_receipts_ranged(&db, receipts_root, 3, Some(4)).unwrap()),
+            vec![3, 4, 5, 6]
+        );
+        assert_eq!(
+            gas_used(Receipt::get_receipts_ranged(&db, receipts_root, 8, Some(4)).unwrap()),
+            vec![8, 9]
+        );
+        assert!(
+            Receipt::get_receipts_ranged(&db, receipts_root, 10, None)
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
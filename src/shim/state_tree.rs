@@ -9,6 +9,7 @@ use crate::{
 use crate::{
     networks::{ACTOR_BUNDLES_METADATA, ActorBundleMetadata},
     shim::actors::account,
+    utils::multihash::prelude::*,
 };
 use anyhow::{Context as _, anyhow, bail};
 use cid::Cid;
@@ -25,10 +26,13 @@ pub use fvm3::state_tree::{ActorState as ActorStateV3, StateTree as StateTreeV3}
 pub use fvm4::state_tree::{
     ActorState as ActorStateV4, ActorState as ActorState_latest, StateTree as StateTreeV4,
 };
+use ipld_core::ipld::Ipld;
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use spire_enum::prelude::delegated_enum;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 #[derive(
@@ -346,6 +350,17 @@ where
         }
     }
 
+    /// Registers a new address with the init actor and returns the ID assigned to it.
+    pub fn register_new_address(&mut self, addr: &Address) -> anyhow::Result<ActorID> {
+        match self {
+            StateTree::FvmV3(st) => Ok(st.register_new_address(&addr.into())?),
+            StateTree::FvmV4(st) => Ok(st.register_new_address(&addr.into())?),
+            StateTree::FvmV2(_) | StateTree::V0(_) => {
+                bail!("StateTree::register_new_address not supported on old state trees")
+            }
+        }
+    }
+
     /// Returns the public key type of
     /// address(`BLS`/`SECP256K1`) of an actor identified by `addr`,
     /// or its delegated address.
@@ -409,6 +424,167 @@ where
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 pub struct ActorState(ActorState_latest);
 
+/// Calls `f` on every actor of the state tree at `state_root`. Unlike [`StateTree::for_each`],
+/// the actors HAMT is sharded by key prefix, i.e. by the pointers of its root node, across the
+/// rayon thread pool, so `f` is called concurrently and in no particular order. The state tree
+/// is only read.
+///
+/// State trees older than version 3 use a legacy HAMT encoding and are iterated sequentially.
+pub fn for_each_actor<S, F>(store: &S, state_root: &Cid, f: F) -> anyhow::Result<()>
+where
+    S: Blockstore + Sync,
+    F: Fn(Address, &ActorState) -> anyhow::Result<()> + Sync,
+{
+    map_actors(store, state_root, f).map(drop)
+}
+
+/// Calls `f` on every actor of the state tree at `state_root` like [`for_each_actor`], and
+/// returns the values it returned in the order [`StateTree::for_each`] visits the actors.
+pub fn map_actors<S, T, F>(store: &S, state_root: &Cid, f: F) -> anyhow::Result<Vec<T>>
+where
+    S: Blockstore + Sync,
+    T: Send,
+    F: Fn(Address, &ActorState) -> anyhow::Result<T> + Sync,
+{
+    let root = store
+        .get_cbor::<StateRoot>(state_root)
+        .ok()
+        .flatten()
+        .filter(|root| StateTreeVersion::from(root.version) >= StateTreeVersion::V3);
+    let Some(StateRoot {
+        version, actors, ..
+    }) = root
+    else {
+        let mut values = vec![];
+        StateTree::new_from_root(Arc::new(store), state_root)?.for_each(|address, actor| {
+            values.push(f(address, actor)?);
+            Ok(())
+        })?;
+        return Ok(values);
+    };
+    // Actors gained a delegated address in version 5.
+    let has_delegated_address = StateTreeVersion::from(version) >= StateTreeVersion::V5;
+    let decode_actor = |value: Ipld| -> anyhow::Result<ActorState> {
+        if has_delegated_address {
+            Ok(ipld_core::serde::from_ipld::<ActorStateV4>(value)?.into())
+        } else {
+            Ok(ipld_core::serde::from_ipld::<ActorStateV2>(value)?.into())
+        }
+    };
+    // The shards are collected in the order of the pointers of the root node.
+    let shards = hamt_node_pointers(store, &actors)?
+        .into_par_iter()
+        .map(|pointer| {
+            let mut values = vec![];
+            for_each_hamt_entry(store, pointer, &mut |key, value| {
+                values.push(f(Address::from_bytes(&key)?, &decode_actor(value)?)?);
+                Ok(())
+            })?;
+            Ok(values)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(shards.into_iter().flatten().collect())
+}
+
+/// Loads the pointers of a HAMT node, which is encoded as `[bitfield, pointers]`.
+fn hamt_node_pointers(store: &impl Blockstore, cid: &Cid) -> anyhow::Result<Vec<Ipld>> {
+    match store
+        .get_cbor::<Ipld>(cid)?
+        .with_context(|| format!("HAMT node not found: {cid}"))?
+    {
+        Ipld::List(mut node) if node.len() == 2 => match node.pop() {
+            Some(Ipld::List(pointers)) => Ok(pointers),
+            _ => bail!("invalid HAMT node pointers: {cid}"),
+        },
+        _ => bail!("invalid HAMT node: {cid}"),
+    }
+}
+
+/// Walks the HAMT entries under `pointer`, which is either a link to a child node or a bucket of
+/// `[key, value]` pairs.
+fn for_each_hamt_entry(
+    store: &impl Blockstore,
+    pointer: Ipld,
+    f: &mut impl FnMut(Vec<u8>, Ipld) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    match pointer {
+        Ipld::Link(cid) => hamt_node_pointers(store, &cid)?
+            .into_iter()
+            .try_for_each(|pointer| for_each_hamt_entry(store, pointer, f)),
+        Ipld::List(bucket) => bucket.into_iter().try_for_each(|entry| {
+            let Ipld::List(entry) = entry else {
+                bail!("invalid HAMT bucket entry");
+            };
+            let Ok([Ipld::Bytes(key), value]) = <[Ipld; 2]>::try_from(entry) else {
+                bail!("invalid HAMT bucket entry");
+            };
+            f(key, value)
+        }),
+        _ => bail!("invalid HAMT pointer"),
+    }
+}
+
+/// Blockstore recording the blocks read through it.
+struct RecordingBlockstore<'a, S> {
+    inner: &'a S,
+    read: parking_lot::Mutex<BTreeMap<Cid, Vec<u8>>>,
+}
+
+impl<S: Blockstore> Blockstore for RecordingBlockstore<'_, S> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let block = self.inner.get(k)?;
+        if let Some(block) = &block {
+            self.read.lock().insert(*k, block.clone());
+        }
+        Ok(block)
+    }
+
+    fn put_keyed(&self, _: &Cid, _: &[u8]) -> anyhow::Result<()> {
+        bail!("proofs are generated from a read-only state tree")
+    }
+}
+
+/// Looks up the actor of the address under the state root, and returns it along with the blocks
+/// read to look it up, i.e. the state root, the HAMT nodes on the paths to the address and its ID
+/// address, and the top-level object of the actor state. Those blocks form a Merkle proof of the
+/// actor, or of its absence, which can be checked with [`verify_actor_proof`].
+pub fn get_actor_with_proof<S: Blockstore>(
+    store: &S,
+    state_root: &Cid,
+    addr: &Address,
+) -> anyhow::Result<(Option<ActorState>, Vec<(Cid, Vec<u8>)>)> {
+    let recorder = Arc::new(RecordingBlockstore {
+        inner: store,
+        read: Default::default(),
+    });
+    let actor = StateTree::new_from_root(recorder.clone(), state_root)?.get_actor(addr)?;
+    if let Some(actor) = &actor {
+        recorder.get(&actor.state)?;
+    }
+    let blocks = recorder.read.lock().clone().into_iter().collect();
+    Ok((actor, blocks))
+}
+
+/// Looks up the actor of the address under the state root from the blocks of a proof only, after
+/// checking that every block matches its CID. Fails if the proof lacks a block on the path to the
+/// actor.
+pub fn verify_actor_proof(
+    state_root: &Cid,
+    addr: &Address,
+    blocks: impl IntoIterator<Item = (Cid, Vec<u8>)>,
+) -> anyhow::Result<Option<ActorState>> {
+    let store = fvm_ipld_blockstore::MemoryBlockstore::new();
+    for (cid, block) in blocks {
+        let hash = MultihashCode::try_from(cid.hash().code())?.digest(&block);
+        anyhow::ensure!(hash == *cid.hash(), "block {cid} doesn't match its CID");
+        store.put_keyed(&cid, &block)?;
+    }
+    StateTree::new_from_root(Arc::new(store), state_root)
+        .context("incomplete proof of the state root")?
+        .get_actor(addr)
+        .context("incomplete proof of the actor")
+}
+
 impl ActorState {
     pub fn new(
         code: Cid,
@@ -528,13 +704,13 @@ impl From<ActorState> for ActorStateV4 {
 
 #[cfg(test)]
 mod tests {
-    use super::StateTree;
+    use super::*;
     use crate::blocks::CachingBlockHeader;
+    use crate::db::MemoryDB;
     use crate::db::car::AnyCar;
     use crate::networks::{calibnet, mainnet};
     use crate::shim::actors::init;
-    use cid::Cid;
-    use std::sync::Arc;
+    use parking_lot::Mutex;
 
     // refactored from `StateManager::get_network_name`
     fn get_network_name(car: &'static [u8], genesis_cid: Cid) -> String {
@@ -564,4 +740,84 @@ mod tests {
             "testnetnet"
         );
     }
+
+    fn assert_for_each_actor_matches(version: StateTreeVersion) {
+        let store = Arc::new(MemoryDB::default());
+        let mut state_tree = StateTree::new(store.clone(), version).unwrap();
+        // Enough actors for the HAMT to have more than one level.
+        for id in 0..500 {
+            let actor = ActorState::new(
+                Cid::default(),
+                Cid::default(),
+                TokenAmount::from_atto(id),
+                id,
+                None,
+            );
+            state_tree.set_actor(&Address::new_id(id), actor).unwrap();
+        }
+        let root = state_tree.flush().unwrap();
+
+        let mut expected = vec![];
+        state_tree
+            .for_each(|address, actor| {
+                expected.push((address, actor.clone()));
+                Ok(())
+            })
+            .unwrap();
+        let actors = Mutex::new(vec![]);
+        for_each_actor(&store, &root, |address, actor| {
+            actors.lock().push((address, actor.clone()));
+            Ok(())
+        })
+        .unwrap();
+        // `map_actors` keeps the state tree order.
+        let mapped =
+            map_actors(&store, &root, |address, actor| Ok((address, actor.clone()))).unwrap();
+        assert_eq!(mapped, expected);
+
+        let mut actors = actors.into_inner();
+        actors.sort_by_key(|(address, _)| address.id().unwrap());
+        expected.sort_by_key(|(address, _)| address.id().unwrap());
+        assert_eq!(expected.len(), 500);
+        assert_eq!(actors, expected);
+    }
+
+    #[test]
+    fn for_each_actor_v4() {
+        assert_for_each_actor_matches(StateTreeVersion::V4);
+    }
+
+    #[test]
+    fn for_each_actor_v5() {
+        assert_for_each_actor_matches(StateTreeVersion::V5);
+    }
+
+    #[test]
+    fn verify_actor_proofs() {
+        let store = MemoryDB::default();
+        let state = store.put_cbor_default(&"actor state").unwrap();
+        let mut state_tree = StateTree::new(Arc::new(&store), StateTreeVersion::V5).unwrap();
+        for id in 0..500 {
+            let actor =
+                ActorState::new(Cid::default(), state, TokenAmount::from_atto(id), id, None);
+            state_tree.set_actor(&Address::new_id(id), actor).unwrap();
+        }
+        let root = state_tree.flush().unwrap();
+
+        let address = Address::new_id(42);
+        let (actor, blocks) = get_actor_with_proof(&store, &root, &address).unwrap();
+        assert_eq!(actor.as_ref().unwrap().sequence, 42);
+        assert!(blocks.iter().any(|(cid, _)| *cid == state));
+        assert_eq!(
+            verify_actor_proof(&root, &address, blocks.clone()).unwrap(),
+            actor
+        );
+        // Proofs missing a block on the path are rejected,
+        let incomplete = blocks.iter().filter(|(cid, _)| *cid != root).cloned();
+        assert!(verify_actor_proof(&root, &address, incomplete).is_err());
+        // as well as tampered blocks.
+        let mut tampered = blocks;
+        tampered.last_mut().unwrap().1.push(0);
+        assert!(verify_actor_proof(&root, &address, tampered).is_err());
+    }
 }
@@ -0,0 +1,221 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Changes of actor code `CIDs` between the parent states of consecutive tipsets. They are
+//! observed to report the migrations of the built-in actors at network upgrades, and the code
+//! changes of watched actors, e.g. a placeholder that became a contract.
+
+use std::fmt;
+use std::sync::Arc;
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use serde::{Deserialize, Serialize};
+
+use crate::blocks::Tipset;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::state_tree::StateTree;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "Type")]
+pub enum ActorCodeChange {
+    /// The built-in actors were migrated to another actor bundle, as done by network upgrades.
+    #[serde(rename_all = "PascalCase")]
+    Migration {
+        epoch: ChainEpoch,
+        /// Version of the new actor bundle, if it is a known bundle.
+        version: Option<String>,
+        #[serde(with = "crate::lotus_json")]
+        old_system_code: Cid,
+        #[serde(with = "crate::lotus_json")]
+        new_system_code: Cid,
+    },
+    /// The code of a watched actor changed. The old code is `None` if the actor was created, and
+    /// the new code is `None` if it was deleted.
+    #[serde(rename_all = "PascalCase")]
+    Actor {
+        epoch: ChainEpoch,
+        #[serde(with = "crate::lotus_json")]
+        address: Address,
+        #[serde(with = "crate::lotus_json")]
+        old_code: Option<Cid>,
+        #[serde(with = "crate::lotus_json")]
+        new_code: Option<Cid>,
+    },
+}
+
+impl fmt::Display for ActorCodeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Migration {
+                epoch,
+                version,
+                old_system_code,
+                new_system_code,
+            } => write!(
+                f,
+                "built-in actors migrated at epoch {epoch} to bundle {} (system actor code {old_system_code} -> {new_system_code})",
+                version.as_deref().unwrap_or("unknown")
+            ),
+            Self::Actor {
+                epoch,
+                address,
+                old_code,
+                new_code,
+            } => {
+                let code = |code: &Option<Cid>| match code {
+                    Some(code) => code.to_string(),
+                    None => "none".into(),
+                };
+                write!(
+                    f,
+                    "code of actor {address} changed at epoch {epoch}: {} -> {}",
+                    code(old_code),
+                    code(new_code)
+                )
+            }
+        }
+    }
+}
+
+/// Returns the actor code changes of the parent state of `tipset` over the parent state of its
+/// parent tipset, i.e. the changes made by the execution of its parent tipset, and by the
+/// migration that may have run before it.
+pub fn tipset_changes<DB: Blockstore>(
+    db: &Arc<DB>,
+    tipset: &Tipset,
+    watched: &[Address],
+) -> anyhow::Result<Vec<ActorCodeChange>> {
+    if tipset.epoch() == 0 {
+        return Ok(vec![]);
+    }
+    let parent = Tipset::load_required(db, tipset.parents())?;
+    changes(
+        db,
+        tipset.epoch(),
+        parent.parent_state(),
+        tipset.parent_state(),
+        watched,
+    )
+}
+
+/// Returns the actor code changes of the state `new_state` over the state `old_state`.
+pub fn changes<DB: Blockstore>(
+    db: &Arc<DB>,
+    epoch: ChainEpoch,
+    old_state: &Cid,
+    new_state: &Cid,
+    watched: &[Address],
+) -> anyhow::Result<Vec<ActorCodeChange>> {
+    let mut changes = vec![];
+    if old_state == new_state {
+        return Ok(changes);
+    }
+    let old_tree = StateTree::new_from_root(db.clone(), old_state)?;
+    let new_tree = StateTree::new_from_root(db.clone(), new_state)?;
+
+    // The system actor is migrated along with all the other built-in actors.
+    if let (Some(old_system), Some(new_system)) = (
+        old_tree.get_actor(&Address::SYSTEM_ACTOR)?,
+        new_tree.get_actor(&Address::SYSTEM_ACTOR)?,
+    ) && old_system.code != new_system.code
+    {
+        changes.push(ActorCodeChange::Migration {
+            epoch,
+            version: new_tree
+                .get_actor_bundle_metadata()
+                .ok()
+                .map(|metadata| metadata.version.clone()),
+            old_system_code: old_system.code,
+            new_system_code: new_system.code,
+        });
+    }
+
+    for address in watched {
+        let old_code = old_tree.get_actor(address)?.map(|actor| actor.code);
+        let new_code = new_tree.get_actor(address)?.map(|actor| actor.code);
+        if old_code != new_code {
+            changes.push(ActorCodeChange::Actor {
+                epoch,
+                address: *address,
+                old_code,
+                new_code,
+            });
+        }
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::shim::state_tree::{ActorState, StateTreeVersion};
+    use crate::utils::multihash::prelude::*;
+    use fvm_ipld_encoding::IPLD_RAW;
+
+    fn code(name: &str) -> Cid {
+        Cid::new_v1(IPLD_RAW, MultihashCode::Identity.digest(name.as_bytes()))
+    }
+
+    fn state(db: &Arc<MemoryDB>, actors: &[(Address, Cid)]) -> Cid {
+        let mut tree = StateTree::new(db.clone(), StateTreeVersion::V5).unwrap();
+        for (address, code) in actors {
+            tree.set_actor(address, ActorState::new_empty(*code, None))
+                .unwrap();
+        }
+        tree.flush().unwrap()
+    }
+
+    #[test]
+    fn actor_code_changes() {
+        let db = Arc::new(MemoryDB::default());
+        let watched = Address::new_id(1000);
+        let created = Address::new_id(1001);
+        let old_state = state(
+            &db,
+            &[
+                (Address::SYSTEM_ACTOR, code("system/v1")),
+                (watched, code("placeholder")),
+            ],
+        );
+        assert!(
+            changes(&db, 10, &old_state, &old_state, &[watched])
+                .unwrap()
+                .is_empty()
+        );
+
+        let new_state = state(
+            &db,
+            &[
+                (Address::SYSTEM_ACTOR, code("system/v2")),
+                (watched, code("evm")),
+                (created, code("account")),
+            ],
+        );
+        assert_eq!(
+            changes(&db, 10, &old_state, &new_state, &[watched, created]).unwrap(),
+            vec![
+                ActorCodeChange::Migration {
+                    epoch: 10,
+                    version: None,
+                    old_system_code: code("system/v1"),
+                    new_system_code: code("system/v2"),
+                },
+                ActorCodeChange::Actor {
+                    epoch: 10,
+                    address: watched,
+                    old_code: Some(code("placeholder")),
+                    new_code: Some(code("evm")),
+                },
+                ActorCodeChange::Actor {
+                    epoch: 10,
+                    address: created,
+                    old_code: None,
+                    new_code: Some(code("account")),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Watcher of the balances and nonces of configured addresses, e.g. the control addresses of a
+//! storage provider. They are read from the parent state of every new head and exported as
+//! Prometheus gauges, and a webhook is optionally invoked when a balance drops below a threshold.
+
+use std::collections::HashSet;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use anyhow::Context as _;
+use fvm_ipld_blockstore::Blockstore;
+use num::ToPrimitive as _;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::{family::Family, gauge::Gauge};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::StateManager;
+use crate::blocks::Tipset;
+use crate::chain::HeadChange;
+use crate::cli::humantoken::{self, TokenAmountPretty as _};
+use crate::shim::address::{Address, StrictAddress};
+use crate::shim::clock::ChainEpoch;
+use crate::shim::econ::TokenAmount;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Balance watcher configuration exposed in Forest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct BalanceWatcherConfig {
+    /// Addresses whose balances and nonces are watched, the watcher is disabled when empty
+    pub addresses: Vec<String>,
+    /// Balance under which the webhook is invoked, e.g. `10 FIL`
+    pub low_balance_threshold: Option<String>,
+    /// URL the low balance alerts are posted to, as JSON
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct AddressLabel {
+    address: String,
+}
+
+static WATCHED_BALANCE: LazyLock<Family<AddressLabel, Gauge<f64, AtomicU64>>> =
+    LazyLock::new(|| {
+        let metric = Family::default();
+        crate::metrics::default_registry().register(
+            "watched_address_balance_fil",
+            "Balance of the watched addresses, in FIL",
+            metric.clone(),
+        );
+        metric
+    });
+static WATCHED_NONCE: LazyLock<Family<AddressLabel, Gauge>> = LazyLock::new(|| {
+    let metric = Family::default();
+    crate::metrics::default_registry().register(
+        "watched_address_nonce",
+        "Nonce of the watched addresses",
+        metric.clone(),
+    );
+    metric
+});
+
+/// Payload posted to the webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct LowBalanceAlert {
+    #[serde(with = "crate::lotus_json")]
+    address: Address,
+    epoch: ChainEpoch,
+    #[serde(with = "crate::lotus_json")]
+    balance: TokenAmount,
+    #[serde(with = "crate::lotus_json")]
+    threshold: TokenAmount,
+}
+
+pub struct BalanceWatcher {
+    addresses: Vec<Address>,
+    threshold: Option<TokenAmount>,
+    webhook_url: Option<reqwest::Url>,
+    /// Addresses under the threshold, which are only alerted again once they went back above it
+    below_threshold: HashSet<Address>,
+}
+
+impl BalanceWatcher {
+    /// Returns the watcher of the configuration, or `None` if no address is watched.
+    pub fn new(config: &BalanceWatcherConfig) -> anyhow::Result<Option<Self>> {
+        if config.addresses.is_empty() {
+            return Ok(None);
+        }
+        let addresses = config
+            .addresses
+            .iter()
+            .map(|address| {
+                address
+                    .parse::<StrictAddress>()
+                    .map(Address::from)
+                    .with_context(|| format!("invalid watched address {address}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let threshold = config
+            .low_balance_threshold
+            .as_deref()
+            .map(humantoken::parse)
+            .transpose()
+            .context("invalid low balance threshold")?;
+        let webhook_url = config
+            .webhook_url
+            .as_deref()
+            .map(reqwest::Url::parse)
+            .transpose()
+            .context("invalid webhook URL")?;
+        Ok(Some(Self {
+            addresses,
+            threshold,
+            webhook_url,
+            below_threshold: HashSet::new(),
+        }))
+    }
+
+    /// Updates the gauges of the watched addresses from the new heads, and alerts the low
+    /// balances, until the publisher of the head changes is closed.
+    pub async fn run<DB: Blockstore>(mut self, state_manager: Arc<StateManager<DB>>) {
+        let http = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let mut receiver = state_manager.chain_store().publisher().subscribe();
+        loop {
+            let ts = match receiver.recv().await {
+                Ok(HeadChange::Apply(ts)) => ts,
+                // Only the balances of the latest head matter.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            };
+            for alert in self.check(&state_manager, &ts) {
+                tracing::warn!(
+                    "Balance of {} is {}, under the threshold of {}",
+                    alert.address,
+                    alert.balance.pretty(),
+                    alert.threshold.pretty()
+                );
+                if let Some(url) = &self.webhook_url
+                    && let Err(e) = post_alert(&http, url.clone(), &alert).await
+                {
+                    tracing::warn!("Failed to invoke the low balance webhook: {e:#}");
+                }
+            }
+        }
+    }
+
+    /// Reads the actors of the watched addresses from the parent state of the tipset, and returns
+    /// the alerts of the balances which dropped below the threshold.
+    fn check<DB: Blockstore>(
+        &mut self,
+        state_manager: &StateManager<DB>,
+        ts: &Tipset,
+    ) -> Vec<LowBalanceAlert> {
+        let state_tree = match state_manager.get_state_tree(ts.parent_state()) {
+            Ok(state_tree) => state_tree,
+            Err(e) => {
+                tracing::warn!("Failed to load the state of tipset {}: {e}", ts.key());
+                return vec![];
+            }
+        };
+        let mut alerts = vec![];
+        for address in self.addresses.clone() {
+            let (balance, nonce) = match state_tree.get_actor(&address) {
+                Ok(Some(actor)) => (TokenAmount::from(&actor.balance), actor.sequence),
+                // Addresses without an actor yet have no funds.
+                Ok(None) => (TokenAmount::default(), 0),
+                Err(e) => {
+                    tracing::warn!("Failed to load the actor of {address}: {e}");
+                    continue;
+                }
+            };
+            let label = AddressLabel {
+                address: address.to_string(),
+            };
+            WATCHED_BALANCE
+                .get_or_create(&label)
+                .set(balance.atto().to_f64().unwrap_or(f64::NAN) / 1e18);
+            WATCHED_NONCE.get_or_create(&label).set(nonce as _);
+            if let Some(threshold) = self.update_threshold(address, &balance) {
+                alerts.push(LowBalanceAlert {
+                    address,
+                    epoch: ts.epoch(),
+                    balance,
+                    threshold,
+                });
+            }
+        }
+        alerts
+    }
+
+    /// Returns the threshold if the balance of the address just dropped below it.
+    fn update_threshold(&mut self, address: Address, balance: &TokenAmount) -> Option<TokenAmount> {
+        let threshold = self.threshold.as_ref()?;
+        if balance >= threshold {
+            self.below_threshold.remove(&address);
+            None
+        } else if self.below_threshold.insert(address) {
+            Some(threshold.clone())
+        } else {
+            None
+        }
+    }
+}
+
+async fn post_alert(
+    http: &reqwest::Client,
+    url: reqwest::Url,
+    alert: &LowBalanceAlert,
+) -> anyhow::Result<()> {
+    http.post(url)
+        .json(alert)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_once_below_threshold() {
+        let mut watcher = BalanceWatcher::new(&BalanceWatcherConfig {
+            addresses: vec!["f01234".into()],
+            low_balance_threshold: Some("10 FIL".into()),
+            webhook_url: None,
+        })
+        .unwrap()
+        .unwrap();
+        let address = watcher.addresses[0];
+        let fil = |n| TokenAmount::from_whole(n);
+        assert_eq!(watcher.update_threshold(address, &fil(20)), None);
+        assert_eq!(watcher.update_threshold(address, &fil(5)), Some(fil(10)));
+        assert_eq!(watcher.update_threshold(address, &fil(4)), None);
+        assert_eq!(watcher.update_threshold(address, &fil(10)), None);
+        assert_eq!(watcher.update_threshold(address, &fil(9)), Some(fil(10)));
+    }
+
+    #[test]
+    fn disabled_without_addresses() {
+        assert!(
+            BalanceWatcher::new(&BalanceWatcherConfig::default())
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            BalanceWatcher::new(&BalanceWatcherConfig {
+                addresses: vec!["not an address".into()],
+                ..Default::default()
+            })
+            .is_err()
+        );
+    }
+}
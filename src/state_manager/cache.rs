@@ -1,11 +1,15 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 use crate::blocks::TipsetKey;
+use crate::db::{SettingsStore, SettingsStoreExt as _, setting_keys::TIPSET_STATES_KEY};
 use crate::shim::executor::Receipt;
-use crate::state_manager::{DEFAULT_TIPSET_CACHE_SIZE, StateEvents};
+use crate::state_manager::{StateEvents, StateOutputValue, TIPSET_STATE_CACHE_SIZE};
 use crate::utils::cache::{LruValueConstraints, SizeTrackingLruCache};
+use cid::Cid;
 use nonzero_ext::nonzero;
 use parking_lot::Mutex as SyncMutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::future::Future;
 use std::num::NonZeroUsize;
 use std::pin::Pin;
@@ -45,7 +49,7 @@ enum CacheLookupStatus<V> {
 
 impl<V: LruValueConstraints> TipsetStateCache<V> {
     pub fn new(cache_identifier: &str) -> Self {
-        Self::with_size(cache_identifier, DEFAULT_TIPSET_CACHE_SIZE)
+        Self::with_size(cache_identifier, *TIPSET_STATE_CACHE_SIZE)
     }
 
     pub fn with_size(cache_identifier: &str, cache_size: NonZeroUsize) -> Self {
@@ -136,6 +140,70 @@ impl<V: LruValueConstraints> TipsetStateCache<V> {
     }
 }
 
+/// Maximum number of computed tipset states that are persisted.
+const MAX_PERSISTED_TIPSET_STATES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PersistedTipsetState {
+    tipset_key: TipsetKey,
+    state_root: Cid,
+    receipt_root: Cid,
+}
+
+/// The most recently computed tipset states, persisted in the settings store so that they are
+/// not computed again after a restart. The states of the tipsets of the chain but the head are
+/// also found in the headers of their children, see [`super::StateManager::populate_cache`].
+pub(crate) struct PersistedTipsetStates {
+    store: Arc<dyn SettingsStore + Sync + Send>,
+    /// Most recently computed first
+    states: SyncMutex<VecDeque<PersistedTipsetState>>,
+}
+
+impl PersistedTipsetStates {
+    pub fn load(store: Arc<dyn SettingsStore + Sync + Send>) -> Self {
+        let states = store
+            .read_obj(TIPSET_STATES_KEY)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Ignoring the persisted tipset states: {e}");
+                None
+            })
+            .unwrap_or_default();
+        Self {
+            store,
+            states: SyncMutex::new(states),
+        }
+    }
+
+    pub fn get(&self, key: &TipsetKey) -> Option<StateOutputValue> {
+        self.states
+            .lock()
+            .iter()
+            .find(|state| &state.tipset_key == key)
+            .map(|state| StateOutputValue {
+                state_root: state.state_root,
+                receipt_root: state.receipt_root,
+            })
+    }
+
+    pub fn insert(&self, key: TipsetKey, value: &StateOutputValue) {
+        let mut states = self.states.lock();
+        states.retain(|state| state.tipset_key != key);
+        states.push_front(PersistedTipsetState {
+            tipset_key: key,
+            state_root: value.state_root,
+            receipt_root: value.receipt_root,
+        });
+        states.truncate(MAX_PERSISTED_TIPSET_STATES);
+        self.save(&states);
+    }
+
+    fn save(&self, states: &VecDeque<PersistedTipsetState>) {
+        if let Err(e) = self.store.write_obj(TIPSET_STATES_KEY, states) {
+            tracing::warn!("Failed to persist the tipset states: {e}");
+        }
+    }
+}
+
 // Type alias for the compute function for receipts
 type ComputeReceiptFn =
     Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = anyhow::Result<Vec<Receipt>>> + Send>> + Send>;
@@ -311,6 +379,32 @@ mod tests {
         TipsetKey::from(nunny::vec![cid])
     }
 
+    #[test]
+    fn test_persisted_tipset_states() {
+        let store = Arc::new(crate::db::MemoryDB::default());
+        let value = |i| StateOutputValue {
+            state_root: *create_test_tipset_key(i).into_cids().first(),
+            receipt_root: Cid::default(),
+        };
+
+        let states = PersistedTipsetStates::load(store.clone());
+        for i in 0..(MAX_PERSISTED_TIPSET_STATES as u64 + 1) {
+            states.insert(create_test_tipset_key(i), &value(i));
+        }
+
+        // The states survive a restart, the oldest one was dropped.
+        let states = PersistedTipsetStates::load(store);
+        assert!(states.get(&create_test_tipset_key(0)).is_none());
+        let last = MAX_PERSISTED_TIPSET_STATES as u64;
+        assert_eq!(
+            states
+                .get(&create_test_tipset_key(last))
+                .map(|it| it.state_root),
+            Some(value(last).state_root)
+        );
+        assert!(states.get(&create_test_tipset_key(1)).is_some());
+    }
+
     fn create_test_receipt(i: u64) -> Vec<Receipt> {
         vec![Receipt::V4(fvm_shared4::receipt::Receipt {
             exit_code: fvm_shared4::error::ExitCode::new(0),
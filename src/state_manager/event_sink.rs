@@ -0,0 +1,398 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Sink posting the chain events, i.e. the new heads, the reorgs and the actor events matching a
+//! filter, as JSON to the HTTP endpoints of the configuration, so that lightweight integrations
+//! don't need to maintain a WebSocket client. Every endpoint has a queue of its own, and failed
+//! posts are retried with exponential backoff.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use backon::{ExponentialBuilder, Retryable as _};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::IPLD_RAW;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use super::StateManager;
+use crate::blocks::{Tipset, TipsetKey};
+use crate::chain::HeadChange;
+use crate::lotus_json::LotusJson;
+use crate::rpc::chain::{PathChange, impl_chain_get_path};
+use crate::rpc::eth::filter::{EthEventHandler, ParsedFilter, SkipEvent};
+use crate::rpc::misc::{ActorEvent, ActorEventBlock, ActorEventFilter};
+use crate::shim::address::{Address, StrictAddress};
+use crate::shim::clock::ChainEpoch;
+use crate::utils::net::global_http_client;
+
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of events queued for an endpoint, the newer events are dropped when it is full.
+const ENDPOINT_QUEUE_CAPACITY: usize = 1024;
+
+/// Event sink configuration exposed in Forest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct EventSinkConfig {
+    /// HTTP endpoints the chain events are posted to, the sink is disabled when empty
+    pub endpoints: Vec<EventSinkEndpointConfig>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct EventSinkEndpointConfig {
+    /// URL the events are posted to
+    pub url: String,
+    /// Post the new heads
+    pub heads: bool,
+    /// Post the reorgs, with the keys of the reverted and the applied tipsets
+    pub reorgs: bool,
+    /// Post the actor events matching this filter, none are posted when unset
+    pub actor_events: Option<ActorEventSinkFilter>,
+    /// Maximum number of retries of a failed post
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub max_retries: usize,
+    /// Delay before the first retry of a failed post, in milliseconds, doubled at every retry
+    pub retry_delay_ms: u64,
+}
+
+impl Default for EventSinkEndpointConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            heads: true,
+            reorgs: true,
+            actor_events: None,
+            max_retries: 5,
+            retry_delay_ms: 1000,
+        }
+    }
+}
+
+/// Filter of the actor events posted to an endpoint.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct ActorEventSinkFilter {
+    /// Emitters of the events, events of any emitter match when empty
+    pub addresses: Vec<String>,
+    /// Accepted values of the entries of the events, hex-encoded, keyed by entry key. An event
+    /// matches if it has an entry with one of the accepted values for every key.
+    pub fields: BTreeMap<String, Vec<String>>,
+}
+
+impl ActorEventSinkFilter {
+    fn parse(&self) -> anyhow::Result<ParsedFilter> {
+        let addresses = self
+            .addresses
+            .iter()
+            .map(|address| {
+                address
+                    .parse::<StrictAddress>()
+                    .map(|address| LotusJson(Address::from(address)))
+                    .with_context(|| format!("invalid event emitter address {address}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let fields = self
+            .fields
+            .iter()
+            .map(|(key, values)| {
+                let values = values
+                    .iter()
+                    .map(|value| {
+                        let bytes =
+                            hex::decode(value.trim_start_matches("0x")).with_context(|| {
+                                format!("invalid value {value} of event entry {key}")
+                            })?;
+                        Ok(ActorEventBlock {
+                            codec: IPLD_RAW,
+                            value: LotusJson(bytes),
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                Ok((key.clone(), values))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        ParsedFilter::from_actor_event_filter(
+            ChainEpoch::MAX,
+            ChainEpoch::MAX,
+            ActorEventFilter {
+                addresses,
+                fields,
+                from_height: None,
+                to_height: None,
+                tipset_key: None,
+            },
+        )
+    }
+}
+
+/// Payload posted to the endpoints.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "Type")]
+enum ChainEvent {
+    #[serde(rename_all = "PascalCase")]
+    Head {
+        #[serde(with = "crate::lotus_json")]
+        tipset: Tipset,
+    },
+    #[serde(rename_all = "PascalCase")]
+    Reorg {
+        #[serde(with = "crate::lotus_json")]
+        reverted: Vec<TipsetKey>,
+        #[serde(with = "crate::lotus_json")]
+        applied: Vec<TipsetKey>,
+    },
+    /// Actor events of a tipset, reverted if the tipset was reverted.
+    #[serde(rename_all = "PascalCase")]
+    ActorEvents {
+        epoch: ChainEpoch,
+        events: Vec<ActorEvent>,
+    },
+}
+
+struct Endpoint {
+    url: reqwest::Url,
+    heads: bool,
+    reorgs: bool,
+    actor_events: Option<ParsedFilter>,
+    max_retries: usize,
+    retry_delay: Duration,
+}
+
+impl Endpoint {
+    fn new(config: &EventSinkEndpointConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            url: reqwest::Url::parse(&config.url)
+                .with_context(|| format!("invalid event sink URL {}", config.url))?,
+            heads: config.heads,
+            reorgs: config.reorgs,
+            actor_events: config
+                .actor_events
+                .as_ref()
+                .map(ActorEventSinkFilter::parse)
+                .transpose()?,
+            max_retries: config.max_retries,
+            retry_delay: Duration::from_millis(config.retry_delay_ms),
+        })
+    }
+
+    /// Returns the events of the path from the previous head to the new one.
+    async fn events<DB: Blockstore + Send + Sync + 'static>(
+        &self,
+        state_manager: &Arc<StateManager<DB>>,
+        path: &[PathChange],
+    ) -> Vec<ChainEvent> {
+        let mut events = vec![];
+        let (reverted, applied): (Vec<_>, Vec<_>) = path
+            .iter()
+            .partition(|change| matches!(change, PathChange::Revert(_)));
+        if self.reorgs && !reverted.is_empty() {
+            let keys = |changes: &[&PathChange]| {
+                changes
+                    .iter()
+                    .map(|(PathChange::Revert(ts) | PathChange::Apply(ts))| ts.key().clone())
+                    .collect()
+            };
+            events.push(ChainEvent::Reorg {
+                reverted: keys(&reverted),
+                applied: keys(&applied),
+            });
+        }
+        for change in path {
+            let (ts, reverted) = match change {
+                PathChange::Revert(ts) => (ts, true),
+                PathChange::Apply(ts) => {
+                    if self.heads {
+                        events.push(ChainEvent::Head { tipset: ts.clone() });
+                    }
+                    (ts, false)
+                }
+            };
+            if let Some(filter) = &self.actor_events {
+                match actor_events(state_manager, ts, filter, reverted).await {
+                    Ok(actor_events) if actor_events.is_empty() => {}
+                    Ok(actor_events) => events.push(ChainEvent::ActorEvents {
+                        epoch: ts.epoch(),
+                        events: actor_events,
+                    }),
+                    Err(e) => {
+                        tracing::warn!("Failed to collect the events of tipset {}: {e}", ts.key())
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    /// Posts the queued events in order, retrying the failed posts.
+    async fn deliver(self: Arc<Self>, mut events: mpsc::Receiver<ChainEvent>) {
+        while let Some(event) = events.recv().await {
+            let post = || async {
+                global_http_client()
+                    .post(self.url.clone())
+                    .json(&event)
+                    .timeout(POST_TIMEOUT)
+                    .send()
+                    .await?
+                    .error_for_status()
+            };
+            if let Err(e) = post
+                .retry(
+                    ExponentialBuilder::default()
+                        .with_min_delay(self.retry_delay)
+                        .with_max_times(self.max_retries),
+                )
+                .await
+            {
+                tracing::warn!("Failed to post a chain event to {}: {e}", self.url);
+            }
+        }
+    }
+}
+
+async fn actor_events<DB: Blockstore + Send + Sync + 'static>(
+    state_manager: &Arc<StateManager<DB>>,
+    ts: &Tipset,
+    filter: &ParsedFilter,
+    reverted: bool,
+) -> anyhow::Result<Vec<ActorEvent>> {
+    let mut collected = vec![];
+    EthEventHandler::collect_tipset_events(
+        state_manager,
+        ts,
+        Some(filter),
+        SkipEvent::Never,
+        usize::MAX,
+        &mut collected,
+    )
+    .await?;
+    Ok(collected
+        .into_iter()
+        .map(|mut event| {
+            event.reverted = reverted;
+            event.into()
+        })
+        .collect())
+}
+
+pub struct EventSink {
+    endpoints: Vec<Endpoint>,
+}
+
+impl EventSink {
+    /// Returns the sink of the configuration, or `None` if it has no endpoint.
+    pub fn new(config: &EventSinkConfig) -> anyhow::Result<Option<Self>> {
+        if config.endpoints.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            endpoints: config
+                .endpoints
+                .iter()
+                .map(Endpoint::new)
+                .collect::<anyhow::Result<_>>()?,
+        }))
+    }
+
+    /// Starts the deliveries to the endpoints, and queues the events of the head changes for them,
+    /// until the publisher of the head changes is closed.
+    pub async fn run<DB: Blockstore + Send + Sync + 'static>(
+        self,
+        state_manager: Arc<StateManager<DB>>,
+    ) {
+        let endpoints = self
+            .endpoints
+            .into_iter()
+            .map(|endpoint| {
+                let endpoint = Arc::new(endpoint);
+                let (sender, receiver) = mpsc::channel(ENDPOINT_QUEUE_CAPACITY);
+                tokio::spawn(endpoint.clone().deliver(receiver));
+                (endpoint, sender)
+            })
+            .collect::<Vec<_>>();
+        let mut receiver = state_manager.chain_store().publisher().subscribe();
+        let mut head = state_manager.chain_store().heaviest_tipset();
+        loop {
+            let ts = match receiver.recv().await {
+                Ok(HeadChange::Apply(ts)) => ts,
+                // The missed heads are covered by the path from the previous head to the next one.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            };
+            let path = match impl_chain_get_path(state_manager.chain_store(), head.key(), ts.key())
+            {
+                Ok(path) => path,
+                Err(e) => {
+                    tracing::warn!("Failed to get the path to the new head {}: {e}", ts.key());
+                    vec![PathChange::Apply(ts.clone())]
+                }
+            };
+            head = ts;
+            for (endpoint, sender) in &endpoints {
+                for event in endpoint.events(&state_manager, &path).await {
+                    if sender.try_send(event).is_err() {
+                        tracing::warn!(
+                            "Event queue of {} is full, dropping chain events",
+                            endpoint.url
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::CachingBlockHeader;
+
+    #[test]
+    fn parse_endpoints() {
+        let endpoint = Endpoint::new(&EventSinkEndpointConfig {
+            url: "http://localhost:8080/events".into(),
+            actor_events: Some(ActorEventSinkFilter {
+                addresses: vec!["f01234".into()],
+                fields: BTreeMap::from_iter([("t1".into(), vec!["0xdeadbeef".into()])]),
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+        let filter = endpoint.actor_events.unwrap();
+        assert_eq!(filter.addresses, vec![Address::new_id(1234)]);
+        assert_eq!(filter.keys.len(), 1);
+
+        assert!(
+            Endpoint::new(&EventSinkEndpointConfig {
+                url: "not a url".into(),
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            EventSink::new(&EventSinkConfig::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn reorg_payload() {
+        let tipset = Tipset::from(CachingBlockHeader::default());
+        let event = ChainEvent::Reorg {
+            reverted: vec![tipset.key().clone()],
+            applied: vec![],
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["Type"], "Reorg");
+        assert_eq!(json["Reverted"].as_array().unwrap().len(), 1);
+        assert_eq!(json["Applied"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Bloom filters of the events of tipsets. They are computed along with the tipset states, so
+//! that log queries over wide epoch ranges can skip the tipsets whose events can't match without
+//! loading them.
+
+use std::sync::Arc;
+
+use ahash::HashSet;
+use cid::Cid;
+use ethereum_types::{Bloom, BloomInput};
+use fvm_ipld_blockstore::Blockstore;
+
+use crate::shim::address::Address;
+use crate::shim::executor::StampedEvent;
+use crate::shim::state_tree::StateTree;
+
+/// Computes the bloom filter of the emitters and entry values of the events of a tipset.
+///
+/// Events are matched against the address their emitter resolves to, in the parent state of the
+/// tipset or, for the actors created by the tipset, in its computed state. Both the ID address
+/// and the resolved address of the emitters are added.
+pub fn compute<DB: Blockstore>(
+    db: &Arc<DB>,
+    parent_state: &Cid,
+    state_root: &Cid,
+    events: &[Vec<StampedEvent>],
+) -> anyhow::Result<Bloom> {
+    let mut bloom = Bloom::zero();
+    if events.iter().all(Vec::is_empty) {
+        return Ok(bloom);
+    }
+    let parent_state = StateTree::new_from_root(db.clone(), parent_state)?;
+    let state = StateTree::new_from_root(db.clone(), state_root)?;
+    let mut emitters = HashSet::default();
+    for event in events.iter().flatten() {
+        if emitters.insert(event.emitter()) {
+            let id = Address::new_id(event.emitter());
+            bloom.accrue(BloomInput::Raw(&id.to_bytes()));
+            if let Ok(resolved) = parent_state
+                .resolve_to_deterministic_addr(db, id)
+                .or_else(|_| state.resolve_to_deterministic_addr(db, id))
+            {
+                bloom.accrue(BloomInput::Raw(&resolved.to_bytes()));
+            }
+        }
+        for entry in event.event().entries() {
+            bloom.accrue(BloomInput::Raw(entry.value()));
+        }
+    }
+    Ok(bloom)
+}
+
+/// Returns `false` if no event emitted by `address` was added to the bloom filter.
+pub fn may_contain_address(bloom: &Bloom, address: &Address) -> bool {
+    bloom.contains_input(BloomInput::Raw(&address.to_bytes()))
+}
+
+/// Returns `false` if no event entry of the given value was added to the bloom filter.
+pub fn may_contain_value(bloom: &Bloom, value: &[u8]) -> bool {
+    bloom.contains_input(BloomInput::Raw(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::shim::state_tree::StateTreeVersion;
+
+    fn event(emitter: u64, value: &[u8]) -> StampedEvent {
+        StampedEvent::V4(fvm_shared4::event::StampedEvent {
+            emitter,
+            event: fvm_shared4::event::ActorEvent {
+                entries: vec![fvm_shared4::event::Entry {
+                    flags: fvm_shared4::event::Flags::FLAG_INDEXED_ALL,
+                    key: "t1".into(),
+                    codec: fvm_ipld_encoding::IPLD_RAW,
+                    value: value.to_vec(),
+                }],
+            },
+        })
+    }
+
+    #[test]
+    fn logs_bloom() {
+        let db = Arc::new(MemoryDB::default());
+        let state_root = StateTree::new(db.clone(), StateTreeVersion::V5)
+            .unwrap()
+            .flush()
+            .unwrap();
+
+        let empty = compute(&db, &state_root, &state_root, &[vec![], vec![]]).unwrap();
+        assert_eq!(empty, Bloom::zero());
+
+        // The emitters are not in the state tree, only their ID addresses are added.
+        let events = [
+            vec![event(1000, b"transfer")],
+            vec![event(1001, b"approval")],
+        ];
+        let bloom = compute(&db, &state_root, &state_root, &events).unwrap();
+        assert!(may_contain_address(&bloom, &Address::new_id(1000)));
+        assert!(may_contain_address(&bloom, &Address::new_id(1001)));
+        assert!(may_contain_value(&bloom, b"transfer"));
+        assert!(may_contain_value(&bloom, b"approval"));
+        assert!(!may_contain_address(&bloom, &Address::new_id(1002)));
+        assert!(!may_contain_value(&bloom, b"deposit"));
+    }
+}
@@ -4,10 +4,16 @@
 #[cfg(test)]
 mod tests;
 
+pub mod actor_code;
+pub mod balance_watcher;
 mod cache;
 pub mod chain_rand;
 pub mod circulating_supply;
 mod errors;
+pub mod event_sink;
+pub mod logs_bloom;
+pub mod reward_metrics;
+mod state_overrides;
 pub mod utils;
 
 pub use self::errors::*;
@@ -19,6 +25,10 @@ use crate::chain::{
     ChainStore, HeadChange,
     index::{ChainIndex, ResolveNullTipset},
 };
+use crate::db::{
+    BlockKind, BlockstoreWithMemoryOverlay, SettingsStore, StateProvenance, StateProvenanceStore,
+    receipts_marker, with_block_kind, with_optional_block_kind,
+};
 use crate::interpreter::{
     ApplyResult, BlockMessages, CalledAt, ExecutionContext, IMPLICIT_MESSAGE_GAS_LIMIT, VM,
     resolve_to_key_addr,
@@ -27,6 +37,7 @@ use crate::interpreter::{MessageCallbackCtx, VMTrace};
 use crate::lotus_json::{LotusJson, lotus_json_with_self};
 use crate::message::{ChainMessage, Message as MessageTrait, SignedMessage};
 use crate::networks::ChainConfig;
+use crate::rpc::eth::types::EthAddress;
 use crate::rpc::state::{ApiInvocResult, InvocResult, MessageGasCost};
 use crate::rpc::types::{MiningBaseInfo, SectorOnChainInfo};
 use crate::shim::actors::init::{self, State};
@@ -53,15 +64,16 @@ use crate::shim::{
     version::NetworkVersion,
 };
 use crate::state_manager::cache::{
-    DisabledTipsetDataCache, EnabledTipsetDataCache, TipsetReceiptEventCacheHandler,
-    TipsetStateCache,
+    DisabledTipsetDataCache, EnabledTipsetDataCache, PersistedTipsetStates,
+    TipsetReceiptEventCacheHandler, TipsetStateCache,
 };
 use crate::state_manager::chain_rand::draw_randomness;
-use crate::state_migration::run_state_migrations;
+use crate::state_migration::{has_state_migrations, run_state_migrations};
 use crate::utils::get_size::{
     GetSize, vec_heap_size_helper, vec_with_stack_only_item_heap_size_helper,
 };
-use ahash::{HashMap, HashMapExt};
+use crate::utils::misc::memory_budget::size_from_env_or_memory_budget;
+use ahash::HashMap;
 use anyhow::{Context as _, bail, ensure};
 use bls_signatures::{PublicKey as BlsPublicKey, Serialize as _};
 use chain_rand::ChainRand;
@@ -72,24 +84,36 @@ use fil_actor_verifreg_state::v13::ClaimID;
 use fil_actors_shared::fvm_ipld_amt::{Amt, Amtv0};
 use fil_actors_shared::fvm_ipld_bitfield::BitField;
 use fil_actors_shared::v12::runtime::DomainSeparationTag;
-use futures::{FutureExt, channel::oneshot, select};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::to_vec;
 use fvm_shared4::crypto::signature::SECP_SIG_LEN;
 use itertools::Itertools as _;
 use nonzero_ext::nonzero;
-use num::BigInt;
-use num_traits::identities::Zero;
 use rayon::prelude::ParallelBridge;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+pub use state_overrides::{ActorStateOverride, StateOverrides, StorageOverride};
 use std::ops::RangeInclusive;
 use std::time::Duration;
-use std::{num::NonZeroUsize, sync::Arc};
-use tokio::sync::{RwLock, broadcast::error::RecvError};
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, LazyLock},
+};
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{error, info, instrument, trace, warn};
 
-const DEFAULT_TIPSET_CACHE_SIZE: NonZeroUsize = nonzero!(1024usize);
+/// Number of tipset states kept in cache, 1024 at the reference memory budget. It can be
+/// overridden with `FOREST_STATE_CACHE_SIZE`.
+static TIPSET_STATE_CACHE_SIZE: LazyLock<NonZeroUsize> = LazyLock::new(|| {
+    const DEFAULT_TIPSET_STATE_CACHE_SIZE: NonZeroUsize = nonzero!(1024usize);
+    NonZeroUsize::new(size_from_env_or_memory_budget(
+        "FOREST_STATE_CACHE_SIZE",
+        DEFAULT_TIPSET_STATE_CACHE_SIZE.get(),
+        128,
+        1 << 16,
+    ))
+    .unwrap_or(DEFAULT_TIPSET_STATE_CACHE_SIZE)
+});
 pub const EVENTS_AMT_BITWIDTH: u32 = 5;
 
 /// Intermediary for retrieving state objects and updating actor states.
@@ -164,6 +188,10 @@ pub struct StateManager<DB> {
     cs: Arc<ChainStore<DB>>,
     /// This is a cache which indexes tipsets to their calculated state output (state root, receipt root).
     cache: TipsetStateCache<StateOutputValue>,
+    /// The most recently computed tipset states, persisted across restarts.
+    persisted_states: Option<PersistedTipsetStates>,
+    /// Store of the tipsets the computed state roots originate from.
+    state_provenance: Option<Arc<dyn StateProvenanceStore + Sync + Send>>,
     beacon: Arc<crate::beacon::BeaconSchedule>,
     engine: Arc<MultiEngine>,
     /// Handler for caching/retrieving tipset events and receipts.
@@ -198,12 +226,34 @@ where
         Ok(Self {
             cs,
             cache: TipsetStateCache::new("state_output"), // For StateOutputValue
+            persisted_states: None,
+            state_provenance: None,
             beacon,
             engine,
             receipt_event_cache_handler: cache_handler,
         })
     }
 
+    /// Persists the most recently computed tipset states in the given settings store, so that
+    /// they are not computed again after a restart.
+    pub fn with_persisted_tipset_states(
+        mut self,
+        store: Arc<dyn SettingsStore + Sync + Send>,
+    ) -> Self {
+        self.persisted_states = Some(PersistedTipsetStates::load(store));
+        self
+    }
+
+    /// Records the tipset each computed state root originates from in the given store, see
+    /// [`StateProvenance`].
+    pub fn with_state_provenance(
+        mut self,
+        store: Arc<dyn StateProvenanceStore + Sync + Send>,
+    ) -> Self {
+        self.state_provenance = Some(store);
+        self
+    }
+
     /// Returns the currently tracked heaviest tipset.
     pub fn heaviest_tipset(&self) -> Tipset {
         self.chain_store().heaviest_tipset()
@@ -267,7 +317,7 @@ where
             .chain_index()
             .chain(self.heaviest_tipset())
             .tuple_windows()
-            .take(DEFAULT_TIPSET_CACHE_SIZE.into())
+            .take(TIPSET_STATE_CACHE_SIZE.get())
         {
             let key = parent.key();
             let state_root = child.min_ticket_block().state_root;
@@ -494,14 +544,33 @@ where
                     return Ok(state_from_child);
                 }
 
+                if let Some(persisted_state) = self.persisted_tipset_state(key) {
+                    return Ok(persisted_state);
+                }
+
                 trace!("Computing state for tipset at epoch {}", tipset.epoch());
                 let state_output = self
                     .compute_tipset_state(tipset.clone(), NO_CALLBACK, VMTrace::NotTraced)
                     .await?;
 
                 self.update_cache_with_state_output(key, &state_output);
+                if self.chain_config().enable_indexer {
+                    self.index_logs_bloom(tipset, &state_output);
+                    self.index_reward_metrics(tipset);
+                    self.index_actor_eth_addresses(tipset, &state_output);
+                }
+
+                if let Err(e) = self.record_state_provenance(tipset, &state_output.state_root) {
+                    warn!(
+                        "Failed to record the provenance of state {}: {e:#}",
+                        state_output.state_root
+                    );
+                }
 
                 let ts_state = state_output.into();
+                if let Some(persisted_states) = &self.persisted_states {
+                    persisted_states.insert(key.clone(), &ts_state);
+                }
 
                 Ok(ts_state)
             })
@@ -509,6 +578,100 @@ where
             .map(StateOutput::from)
     }
 
+    fn record_state_provenance(&self, tipset: &Tipset, state_root: &Cid) -> anyhow::Result<()> {
+        let Some(store) = &self.state_provenance else {
+            return Ok(());
+        };
+        // The genesis state is not computed from a parent state.
+        let migration = tipset.epoch() > 0 && {
+            let parent = self.chain_index().load_required_tipset(tipset.parents())?;
+            has_state_migrations(parent.epoch()..tipset.epoch(), self.chain_config())
+        };
+        store.write_state_provenance(
+            state_root,
+            &StateProvenance {
+                epoch: tipset.epoch(),
+                tipset_key: tipset.key().clone(),
+                migration,
+            },
+        )
+    }
+
+    /// Returns the persisted state of a tipset, unless its state tree or its receipts are no
+    /// longer in the blockstore.
+    fn persisted_tipset_state(&self, key: &TipsetKey) -> Option<StateOutputValue> {
+        let state = self.persisted_states.as_ref()?.get(key)?;
+        let has = |cid| self.blockstore().has(cid).unwrap_or(false);
+        (has(&state.state_root) && has(&state.receipt_root)).then_some(state)
+    }
+
+    /// Indexes the bloom filter of the events of a computed tipset, for log queries to skip the
+    /// tipset if it can't match.
+    fn index_logs_bloom(&self, tipset: &Tipset, state_output: &StateOutput) {
+        if let Err(e) = logs_bloom::compute(
+            self.blockstore(),
+            tipset.parent_state(),
+            &state_output.state_root,
+            &state_output.events,
+        )
+        .and_then(|bloom| Ok(self.chain_store().put_logs_bloom(tipset.key(), &bloom)?))
+        {
+            warn!(
+                "Failed to index the logs bloom of tipset {}: {e}",
+                tipset.key()
+            );
+        }
+    }
+
+    /// Indexes the delegated Ethereum addresses of the actors created by a computed tipset, see
+    /// [`ChainStore::get_eth_address_actor_id`]. The init actor assigns the actor IDs in
+    /// sequence, the new actors are those between its next IDs in the parent and computed states.
+    fn index_actor_eth_addresses(&self, tipset: &Tipset, state_output: &StateOutput) {
+        let index = || -> anyhow::Result<()> {
+            let parent = self.get_state_tree(tipset.parent_state())?;
+            let state = self.get_state_tree(&state_output.state_root)?;
+            let first_id = parent.get_actor_state::<State>()?.next_id();
+            let next_id = state.get_actor_state::<State>()?.next_id();
+            for id in first_id..next_id {
+                let eth_address = state
+                    .get_actor(&Address::new_id(id))?
+                    .and_then(|actor| actor.delegated_address)
+                    .and_then(|addr| EthAddress::from_filecoin_address(&addr.into()).ok());
+                self.chain_store().put_actor_eth_address(
+                    id,
+                    eth_address.as_ref(),
+                    tipset.epoch(),
+                )?;
+            }
+            Ok(())
+        };
+        if let Err(e) = index() {
+            warn!(
+                "Failed to index the Ethereum addresses of the actors created by tipset {}: {e}",
+                tipset.key()
+            );
+        }
+    }
+
+    /// Indexes the reward actor metrics of the parent state of a computed tipset, so that their
+    /// history outlives the states.
+    fn index_reward_metrics(&self, tipset: &Tipset) {
+        if let Err(e) =
+            reward_metrics::RewardMetrics::load(self.blockstore(), tipset.parent_state()).and_then(
+                |metrics| {
+                    Ok(self
+                        .chain_store()
+                        .put_reward_metrics(tipset.key(), &metrics)?)
+                },
+            )
+        {
+            warn!(
+                "Failed to index the reward metrics of tipset {}: {e}",
+                tipset.key()
+            );
+        }
+    }
+
     /// update the receipt and events caches
     fn update_cache_with_state_output(&self, key: &TipsetKey, state_output: &StateOutput) {
         if !state_output.events.is_empty() || !state_output.events_roots.is_empty() {
@@ -683,6 +846,7 @@ where
         self: &Arc<Self>,
         tipset: Option<Tipset>,
         msg: Message,
+        state_overrides: &StateOverrides,
         state_lookup: StateLookupPolicy,
     ) -> anyhow::Result<ApiInvocResult> {
         let ts = tipset.unwrap_or_else(|| self.heaviest_tipset());
@@ -707,7 +871,14 @@ where
         };
 
         let (_invoc_res, apply_ret, duration) = self
-            .call_with_gas(&mut chain_msg, &[], Some(ts), VMTrace::Traced, state_lookup)
+            .call_with_gas(
+                &mut chain_msg,
+                &[],
+                Some(ts),
+                state_overrides,
+                VMTrace::Traced,
+                state_lookup,
+            )
             .await?;
         Ok(ApiInvocResult {
             msg_cid: msg.cid(),
@@ -720,13 +891,14 @@ where
         })
     }
 
-    /// Computes message on the given [Tipset] state, after applying other
-    /// messages and returns the values computed in the VM.
+    /// Computes message on the given [Tipset] state, with the given actor state
+    /// overrides, after applying other messages and returns the values computed in the VM.
     pub async fn call_with_gas(
         self: &Arc<Self>,
         message: &mut ChainMessage,
         prior_messages: &[ChainMessage],
         tipset: Option<Tipset>,
+        state_overrides: &StateOverrides,
         trace_config: VMTrace,
         state_lookup: StateLookupPolicy,
     ) -> Result<(InvocResult, ApplyRet, Duration), Error> {
@@ -735,6 +907,12 @@ where
             .tipset_state(&ts, state_lookup)
             .await
             .map_err(|e| Error::Other(format!("Could not load tipset state: {e}")))?;
+        // The overrides and the message are applied on an in-memory overlay of the store, for
+        // nothing to be written to the node store.
+        let store = Arc::new(BlockstoreWithMemoryOverlay::new(self.blockstore_owned()));
+        let st = state_overrides::apply_state_overrides(store.clone(), st, state_overrides)
+            .map_err(|e| Error::Other(format!("Could not apply state overrides: {e}")))?;
+        let chain_index = Arc::new(self.chain_index().with_db(store.clone()));
         let chain_rand = self.chain_rand(ts.clone());
 
         // Since we're simulating a future message, pretend we're applying it in the
@@ -751,13 +929,9 @@ where
                     epoch,
                     rand: Box::new(chain_rand),
                     base_fee: ts.block_headers().first().parent_base_fee.clone(),
-                    circ_supply: genesis_info.get_vm_circulating_supply(
-                        epoch,
-                        self.blockstore(),
-                        &st,
-                    )?,
+                    circ_supply: genesis_info.get_vm_circulating_supply(epoch, &store, &st)?,
                     chain_config: self.chain_config().clone(),
-                    chain_index: self.chain_index().clone(),
+                    chain_index,
                     timestamp: ts.min_timestamp(),
                 },
                 &self.engine,
@@ -805,10 +979,22 @@ where
                 CalledAt::Applied | CalledAt::Reward
                     if api_invoc_result.is_none() && ctx.cid == mcid =>
                 {
+                    let msg_rct = ctx.apply_ret.msg_receipt();
+                    // The replay halts before the events of the tipset are stored, the events of
+                    // the message are stored so that they can be fetched from its receipt. They
+                    // are not referenced by the tipset, and are left untagged.
+                    if let Some(events_root) = msg_rct.events_root() {
+                        store_events(
+                            self.blockstore(),
+                            None,
+                            &ctx.apply_ret.events(),
+                            &events_root,
+                        )?;
+                    }
                     api_invoc_result = Some(ApiInvocResult {
                         msg_cid: ctx.message.cid(),
                         msg: ctx.message.message().clone(),
-                        msg_rct: Some(ctx.apply_ret.msg_receipt()),
+                        msg_rct: Some(msg_rct),
                         error: ctx.apply_ret.failure_info().unwrap_or_default(),
                         duration: ctx.duration.as_nanos().clamp(0, u64::MAX as u128) as u64,
                         gas_cost: MessageGasCost::new(ctx.message.message(), ctx.apply_ret)?,
@@ -839,50 +1025,14 @@ where
         base_tipset: &Tipset,
         lookback_tipset: &Tipset,
     ) -> anyhow::Result<bool, Error> {
-        let hmp =
-            self.miner_has_min_power(&self.chain_config().policy, address, lookback_tipset)?;
-        let version = self.get_network_version(base_tipset.epoch());
-
-        if version <= NetworkVersion::V3 {
-            return Ok(hmp);
-        }
-
-        if !hmp {
-            return Ok(false);
-        }
-
-        let actor = self
-            .get_actor(&Address::POWER_ACTOR, *base_tipset.parent_state())?
-            .ok_or_else(|| Error::state("Power actor address could not be resolved"))?;
-
-        let power_state = power::State::load(self.blockstore(), actor.code, actor.state)?;
-
-        let actor = self
-            .get_actor(address, *base_tipset.parent_state())?
-            .ok_or_else(|| Error::state("Miner actor address could not be resolved"))?;
-
-        let miner_state = miner::State::load(self.blockstore(), actor.code, actor.state)?;
-
-        // Non-empty power claim.
-        let claim = power_state
-            .miner_power(self.blockstore(), address)?
-            .ok_or_else(|| Error::Other("Could not get claim".to_string()))?;
-        if claim.quality_adj_power <= BigInt::zero() {
-            return Ok(false);
-        }
-
-        // No fee debt.
-        if !miner_state.fee_debt().is_zero() {
-            return Ok(false);
-        }
-
-        // No active consensus faults.
-        let info = miner_state.info(self.blockstore())?;
-        if base_tipset.epoch() <= info.consensus_fault_elapsed {
-            return Ok(false);
-        }
-
-        Ok(true)
+        Ok(utils::miner_eligible_to_mine(
+            self.blockstore(),
+            &self.chain_config().policy,
+            self.get_network_version(base_tipset.epoch()),
+            address,
+            base_tipset,
+            lookback_tipset.parent_state(),
+        )?)
     }
 
     /// Conceptually, a [`Tipset`] consists of _blocks_ which share an _epoch_.
@@ -1059,6 +1209,10 @@ where
         let mut current_actor_state = self
             .get_required_actor(&message_from_address, *current.parent_state())
             .map_err(Error::state)?;
+        // The sender nonce has not moved past the message yet, so it cannot have been executed.
+        if current_actor_state.sequence <= message_sequence {
+            return Ok(None);
+        }
         let message_from_id = self.lookup_required_id(&message_from_address, &current)?;
 
         while current.epoch() >= lookback_max_epoch {
@@ -1119,9 +1273,54 @@ where
             _ => 0,
         };
 
+        if let Some((tipset, receipt)) =
+            self.search_for_indexed_message(&current, message, allow_replaced)?
+            && tipset.epoch() >= lookback_max_epoch
+        {
+            return Ok(Some((tipset, receipt)));
+        }
+
         self.check_search(current, message, lookback_max_epoch, allow_replaced)
     }
 
+    /// Looks the message up in the message inclusion index and returns its receipt if the
+    /// indexed tipset is still part of the chain ending in `head`.
+    fn search_for_indexed_message(
+        &self,
+        head: &Tipset,
+        message: &ChainMessage,
+        allow_replaced: bool,
+    ) -> Result<Option<(Tipset, Receipt)>, Error> {
+        let (inclusion_tsk, inclusion_epoch) =
+            match self.chain_store().get_message_inclusion(&message.cid()) {
+                Ok(Some(inclusion)) => inclusion,
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    trace!("message inclusion index unavailable: {e}");
+                    return Ok(None);
+                }
+            };
+        // The message has been included but not yet executed.
+        if inclusion_epoch >= head.epoch() {
+            return Ok(None);
+        }
+        let execution_tipset = self
+            .chain_index()
+            .tipset_by_height(
+                inclusion_epoch + 1,
+                head.clone(),
+                ResolveNullTipset::TakeNewer,
+            )
+            .map_err(Error::other)?;
+        // The indexed tipset has been reverted.
+        if execution_tipset.parents() != &inclusion_tsk {
+            return Ok(None);
+        }
+        Ok(self
+            .tipset_executed_message(&execution_tipset, message, allow_replaced)?
+            .map(|receipt| (execution_tipset, receipt)))
+    }
+
     /// Returns a message receipt from a given tipset and message CID.
     pub fn get_receipt(&self, tipset: Tipset, msg: Cid) -> Result<Receipt, Error> {
         let m = crate::chain::get_chain_message(self.blockstore(), &msg)
@@ -1144,6 +1343,11 @@ where
     /// backwards in the chain to see if this has already happened. It
     /// guarantees that the message has been on chain for at least
     /// confidence epochs without being reverted before returning.
+    ///
+    /// Head changes are subscribed to before the initial search so that no
+    /// tipset applied in the meantime is missed. When the tipset holding the
+    /// message is no longer part of the heaviest chain (e.g. after a reorg),
+    /// the search is started over from the new head.
     pub async fn wait_for_message(
         self: &Arc<Self>,
         msg_cid: Cid,
@@ -1152,121 +1356,100 @@ where
         allow_replaced: Option<bool>,
     ) -> Result<(Option<Tipset>, Option<Receipt>), Error> {
         let mut subscriber = self.cs.publisher().subscribe();
-        let (sender, mut receiver) = oneshot::channel::<()>();
         let message = crate::chain::get_chain_message(self.blockstore(), &msg_cid)
             .map_err(|err| Error::Other(format!("failed to load message {err:}")))?;
-        let current_tipset = self.heaviest_tipset();
-        let maybe_message_receipt =
-            self.tipset_executed_message(&current_tipset, &message, true)?;
-        if let Some(r) = maybe_message_receipt {
-            return Ok((Some(current_tipset.clone()), Some(r)));
-        }
-
-        let mut candidate_tipset: Option<Tipset> = None;
-        let mut candidate_receipt: Option<Receipt> = None;
-
-        let sm_cloned = Arc::clone(self);
+        let allow_replaced = allow_replaced.unwrap_or(true);
 
-        let message_for_task = message.clone();
-        let height_of_head = current_tipset.epoch();
-        let task = tokio::task::spawn(async move {
-            let back_tuple = sm_cloned.search_back_for_message(
-                current_tipset,
-                &message_for_task,
+        let mut head = self.heaviest_tipset();
+        let mut candidate = self
+            .search_back_for_message_async(
+                head.clone(),
+                message.clone(),
                 look_back_limit,
                 allow_replaced,
-            )?;
-            sender
-                .send(())
-                .map_err(|e| Error::Other(format!("Could not send to channel {e:?}")))?;
-            Ok::<_, Error>(back_tuple)
-        });
+            )
+            .await?;
 
-        let reverts: Arc<RwLock<HashMap<TipsetKey, bool>>> = Arc::new(RwLock::new(HashMap::new()));
-        let block_revert = reverts.clone();
-        let sm_cloned = Arc::clone(self);
-
-        // Wait for message to be included in head change.
-        let mut subscriber_poll = tokio::task::spawn(async move {
-            loop {
-                match subscriber.recv().await {
-                    Ok(subscriber) => match subscriber {
-                        HeadChange::Apply(tipset) => {
-                            if candidate_tipset
-                                .as_ref()
-                                .map(|s| tipset.epoch() >= s.epoch() + confidence)
-                                .unwrap_or_default()
-                            {
-                                return Ok((candidate_tipset, candidate_receipt));
-                            }
-                            let poll_receiver = receiver.try_recv();
-                            if let Ok(Some(_)) = poll_receiver {
-                                block_revert
-                                    .write()
-                                    .await
-                                    .insert(tipset.key().to_owned(), true);
-                            }
-
-                            let maybe_receipt =
-                                sm_cloned.tipset_executed_message(&tipset, &message, true)?;
-                            if let Some(receipt) = maybe_receipt {
-                                if confidence == 0 {
-                                    return Ok((Some(tipset), Some(receipt)));
-                                }
-                                candidate_tipset = Some(tipset);
-                                candidate_receipt = Some(receipt)
-                            }
-                        }
-                    },
-                    Err(RecvError::Lagged(i)) => {
-                        warn!(
-                            "wait for message head change subscriber lagged, skipped {} events",
-                            i
-                        );
-                    }
-                    Err(RecvError::Closed) => break,
-                }
-            }
-            Ok((None, None))
-        })
-        .fuse();
-
-        // Search backwards for message.
-        let mut search_back_poll = tokio::task::spawn(async move {
-            let back_tuple = task.await.map_err(|e| {
-                Error::Other(format!("Could not search backwards for message {e}"))
-            })??;
-            if let Some((back_tipset, back_receipt)) = back_tuple {
-                let should_revert = *reverts
-                    .read()
-                    .await
-                    .get(back_tipset.key())
-                    .unwrap_or(&false);
-                let larger_height_of_head = height_of_head >= back_tipset.epoch() + confidence;
-                if !should_revert && larger_height_of_head {
-                    return Ok::<_, Error>((Some(back_tipset), Some(back_receipt)));
-                }
-                return Ok((None, None));
+        loop {
+            if let Some((tipset, receipt)) = &candidate
+                && head.epoch() >= tipset.epoch() + confidence
+            {
+                return Ok((Some(tipset.clone()), Some(receipt.clone())));
             }
-            Ok((None, None))
-        })
-        .fuse();
 
-        // Await on first future to finish.
-        loop {
-            select! {
-                res = subscriber_poll => {
-                    return res?
+            let previous_head = head;
+            head = match subscriber.recv().await {
+                Ok(HeadChange::Apply(tipset)) => tipset,
+                Err(RecvError::Lagged(i)) => {
+                    warn!(
+                        "wait for message head change subscriber lagged, skipped {} events",
+                        i
+                    );
+                    self.heaviest_tipset()
                 }
-                res = search_back_poll => {
-                    if let Ok((Some(ts), Some(rct))) = res? {
-                        return Ok((Some(ts), Some(rct)));
-                    }
+                Err(RecvError::Closed) => {
+                    return Err(Error::Other(format!(
+                        "head change channel closed while waiting for message {msg_cid}"
+                    )));
                 }
+            };
+
+            if let Some((tipset, _)) = &candidate
+                && !self.is_tipset_in_chain(tipset, &head)?
+            {
+                info!(
+                    "message {msg_cid} executed at epoch {} has been reverted, searching again",
+                    tipset.epoch()
+                );
+                candidate = None;
+            }
+
+            if candidate.is_none() {
+                candidate = if head.parents() == previous_head.key() {
+                    // The head advanced by one tipset, only the new head needs to be checked.
+                    self.tipset_executed_message(&head, &message, allow_replaced)?
+                        .map(|receipt| (head.clone(), receipt))
+                } else {
+                    // Either a reorg happened or head changes have been skipped.
+                    self.search_back_for_message_async(
+                        head.clone(),
+                        message.clone(),
+                        look_back_limit,
+                        allow_replaced,
+                    )
+                    .await?
+                };
             }
         }
     }
 
+    /// Runs [`Self::search_back_for_message`] on the blocking thread pool.
+    async fn search_back_for_message_async(
+        self: &Arc<Self>,
+        current: Tipset,
+        message: ChainMessage,
+        look_back_limit: Option<ChainEpoch>,
+        allow_replaced: bool,
+    ) -> Result<Option<(Tipset, Receipt)>, Error> {
+        let this = Arc::clone(self);
+        tokio::task::spawn_blocking(move || {
+            this.search_back_for_message(current, &message, look_back_limit, Some(allow_replaced))
+        })
+        .await?
+    }
+
+    /// Returns `true` if `tipset` is part of the chain ending in `head`.
+    fn is_tipset_in_chain(&self, tipset: &Tipset, head: &Tipset) -> Result<bool, Error> {
+        if tipset.epoch() > head.epoch() {
+            return Ok(false);
+        }
+        let tipset_at_epoch = self
+            .chain_index()
+            .tipset_by_height(tipset.epoch(), head.clone(), ResolveNullTipset::TakeOlder)
+            .map_err(Error::other)?;
+        Ok(tipset_at_epoch.key() == tipset.key())
+    }
+
     pub async fn search_for_message(
         &self,
         from: Option<Tipset>,
@@ -2018,27 +2201,33 @@ where
         let (receipts, events, events_roots) =
             vm.apply_block_messages(&block_messages, epoch, callback)?;
 
+        // The receipts and events of a tipset computed again are already referenced, they are
+        // written untagged not to be referenced twice, see `receipts_marker`.
+        let (marker, marker_data) = receipts_marker(tipset.key())?;
+        let written = chain_index.db().has(&marker)?;
+        let kind = |kind| (!written).then_some(kind);
+
         // step 5: construct receipt root from receipts
-        let receipt_root = Amtv0::new_from_iter(chain_index.db(), receipts)?;
+        let receipt_root = with_optional_block_kind(kind(BlockKind::Receipt), || {
+            Amtv0::new_from_iter(chain_index.db(), receipts)
+        })?;
 
         // step 6: store events AMTs in the blockstore
         for (msg_events, events_root) in events.iter().zip(events_roots.iter()) {
-            if let Some(event_root) = events_root {
-                // Store the events AMT - the root CID should match the one computed by FVM
-                let derived_event_root = Amt::new_from_iter_with_bit_width(
+            if let Some(events_root) = events_root {
+                store_events(
                     chain_index.db(),
-                    EVENTS_AMT_BITWIDTH,
-                    msg_events.iter(),
-                )
-                .map_err(|e| Error::Other(format!("failed to store events AMT: {e}")))?;
-
-                // Verify the stored root matches the FVM-computed root
-                ensure!(
-                    derived_event_root.eq(event_root),
-                    "Events AMT root mismatch: derived={derived_event_root}, actual={event_root}."
-                );
+                    kind(BlockKind::Event),
+                    msg_events,
+                    events_root,
+                )?;
             }
         }
+        if !written {
+            with_block_kind(BlockKind::Receipt, || {
+                chain_index.db().put_keyed(&marker, &marker_data)
+            })?;
+        }
 
         let state_root = vm.flush()?;
 
@@ -2051,6 +2240,28 @@ where
     })
 }
 
+/// Stores the events AMT of a message, so that its events can be retrieved from the events root
+/// of its receipt, e.g. with `Filecoin.ChainGetEvents`.
+fn store_events<DB: Blockstore>(
+    db: &DB,
+    kind: Option<BlockKind>,
+    events: &[StampedEvent],
+    events_root: &Cid,
+) -> anyhow::Result<()> {
+    // The root CID should match the one computed by FVM
+    let derived_events_root = with_optional_block_kind(kind, || {
+        Amt::new_from_iter_with_bit_width(db, EVENTS_AMT_BITWIDTH, events.iter())
+    })
+    .map_err(|e| anyhow::anyhow!("failed to store events AMT: {e}"))?;
+
+    // Verify the stored root matches the FVM-computed root
+    ensure!(
+        derived_events_root.eq(events_root),
+        "Events AMT root mismatch: derived={derived_events_root}, actual={events_root}."
+    );
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn compute_state<DB>(
     _height: ChainEpoch,
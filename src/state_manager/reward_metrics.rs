@@ -0,0 +1,104 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Metrics of the reward actor. They are recorded along with the tipset states, so that their
+//! history can be queried over wide epoch ranges without loading the states, which may have been
+//! garbage collected since.
+
+use std::sync::Arc;
+
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::*;
+
+use crate::shim::{
+    actors::reward, econ::TokenAmount, fvm_shared_latest::bigint::bigint_ser, sector::StoragePower,
+    state_tree::StateTree,
+};
+
+/// Metrics of the reward actor in a state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct RewardMetrics {
+    /// Baseline power the network is targeting
+    #[serde(with = "bigint_ser")]
+    pub baseline_power: StoragePower,
+    /// Block reward of the epoch
+    pub this_epoch_reward: TokenAmount,
+    /// Smoothed estimate of the block reward
+    pub reward_estimate: TokenAmount,
+    /// Total reward paid out to the miners so far
+    pub total_mined: TokenAmount,
+}
+
+impl RewardMetrics {
+    /// Loads the metrics of the reward actor in the state.
+    pub fn load<DB: Blockstore>(db: &Arc<DB>, state_root: &Cid) -> anyhow::Result<Self> {
+        let state: reward::State =
+            StateTree::new_from_root(Arc::clone(db), state_root)?.get_actor_state()?;
+        Ok(Self {
+            baseline_power: state.this_epoch_baseline_power().clone(),
+            this_epoch_reward: state.this_epoch_reward(),
+            reward_estimate: state.this_epoch_reward_estimate(),
+            total_mined: state.into_total_storage_power_reward(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::networks::ACTOR_BUNDLES_METADATA;
+    use crate::shim::{
+        address::Address,
+        machine::BuiltinActor,
+        state_tree::{ActorState, StateTreeVersion},
+    };
+    use crate::utils::db::CborStoreExt as _;
+    use num::{Signed as _, Zero as _};
+
+    #[test]
+    fn load_and_store_reward_metrics() {
+        let reward_code = ACTOR_BUNDLES_METADATA
+            .values()
+            .filter(|metadata| metadata.actor_major_version().ok() == Some(17))
+            .flat_map(|metadata| metadata.manifest.builtin_actors())
+            .find(|(actor_type, _)| *actor_type == BuiltinActor::Reward)
+            .map(|(_, cid)| cid)
+            .unwrap();
+        let db = Arc::new(MemoryDB::default());
+        let reward_state = fil_actor_reward_state::v17::State::new(StoragePower::from(1u64 << 50));
+        let mut state_tree = StateTree::new(db.clone(), StateTreeVersion::V5).unwrap();
+        state_tree
+            .set_actor(
+                &Address::REWARD_ACTOR,
+                ActorState::new(
+                    reward_code,
+                    db.put_cbor_default(&reward_state).unwrap(),
+                    TokenAmount::default(),
+                    0,
+                    None,
+                ),
+            )
+            .unwrap();
+        let state_root = state_tree.flush().unwrap();
+
+        let metrics = RewardMetrics::load(&db, &state_root).unwrap();
+        assert_eq!(
+            metrics.baseline_power,
+            reward_state.this_epoch_baseline_power
+        );
+        assert_eq!(
+            metrics.this_epoch_reward,
+            TokenAmount::from(reward_state.this_epoch_reward.clone())
+        );
+        assert!(metrics.reward_estimate.is_positive());
+        assert!(metrics.total_mined.is_zero());
+
+        let bytes = fvm_ipld_encoding::to_vec(&metrics).unwrap();
+        assert_eq!(
+            fvm_ipld_encoding::from_slice::<RewardMetrics>(&bytes).unwrap(),
+            metrics
+        );
+    }
+}
@@ -0,0 +1,372 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Overrides of the state of actors, applied to the state a message is called on, like the state
+//! overrides of `eth_call` in Geth. Actors that do not exist are created, and the code and the
+//! storage of EVM actors can be replaced.
+
+use crate::shim::actors::{
+    BuiltinActor, EVMActorStateLoad as _, account, evm, is_ethaccount_actor, is_evm_actor,
+    is_placeholder_actor,
+};
+use crate::shim::address::{Address, Protocol};
+use crate::shim::econ::TokenAmount;
+use crate::shim::state_tree::{ActorState, StateTree};
+use crate::utils::db::CborStoreExt as _;
+use crate::utils::multihash::prelude::*;
+use anyhow::{Context as _, bail, ensure};
+use cid::Cid;
+use fil_actor_evm_state::evm_shared::v17::uint::U256;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::IPLD_RAW;
+use fvm_ipld_kamt::{AsHashedKey, Config as KamtConfig, Kamt};
+use get_size2::GetSize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Overrides of the state of an actor, applied to the state a message is called on.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ActorStateOverride {
+    pub balance: Option<TokenAmount>,
+    pub sequence: Option<u64>,
+    /// EVM bytecode, the actor is turned into an EVM actor running it
+    pub code: Option<Vec<u8>>,
+    /// Storage of the EVM actor
+    pub storage: Option<StorageOverride>,
+}
+
+/// Overrides of the storage slots of an EVM actor, keyed by slot.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StorageOverride {
+    /// Replaces the whole storage
+    Replace(BTreeMap<[u8; 32], [u8; 32]>),
+    /// Replaces the given slots only
+    Diff(BTreeMap<[u8; 32], [u8; 32]>),
+}
+
+impl GetSize for ActorStateOverride {
+    fn get_heap_size(&self) -> usize {
+        self.balance.as_ref().map_or(0, GetSize::get_heap_size)
+            + self.code.as_ref().map_or(0, Vec::capacity)
+            + self.storage.as_ref().map_or(0, GetSize::get_heap_size)
+    }
+}
+
+impl GetSize for StorageOverride {
+    fn get_heap_size(&self) -> usize {
+        let (Self::Replace(slots) | Self::Diff(slots)) = self;
+        slots.len() * std::mem::size_of::<([u8; 32], [u8; 32])>()
+    }
+}
+
+/// Overrides of the state of actors, keyed by address.
+pub type StateOverrides = BTreeMap<Address, ActorStateOverride>;
+
+/// Parameters of the KAMT holding the storage of EVM actors, see
+/// <https://github.com/filecoin-project/builtin-actors/blob/v17.0.0/actors/evm/src/interpreter/system.rs>
+const STORAGE_KAMT_CONFIG: KamtConfig = KamtConfig {
+    min_data_depth: 0,
+    bit_width: 5,
+    max_array_width: 1,
+};
+
+/// Slots of the storage of EVM actors are keyed by their big-endian bytes.
+struct StorageSlotHash;
+
+impl AsHashedKey<U256, 32> for StorageSlotHash {
+    fn as_hashed_key(key: &U256) -> Cow<'_, [u8; 32]> {
+        let mut bytes = [0; 32];
+        key.to_big_endian(&mut bytes);
+        Cow::Owned(bytes)
+    }
+}
+
+type StorageKamt<BS> = Kamt<BS, U256, U256, StorageSlotHash>;
+
+/// Returns the root of the given state with the actor state overrides applied. The store is
+/// written to, it should be an in-memory overlay of the node store for the overrides not to be
+/// persisted.
+pub fn apply_state_overrides<BS: Blockstore>(
+    store: Arc<BS>,
+    state_root: Cid,
+    state_overrides: &StateOverrides,
+) -> anyhow::Result<Cid> {
+    if state_overrides.is_empty() {
+        return Ok(state_root);
+    }
+    let mut state_tree = StateTree::new_from_root(store, &state_root)?;
+    for (address, state_override) in state_overrides {
+        let mut actor = match state_tree.get_actor(address)? {
+            Some(actor) => actor,
+            None => create_actor(&mut state_tree, address)?,
+        };
+        if let Some(balance) = &state_override.balance {
+            actor.balance = balance.clone().into();
+        }
+        if let Some(sequence) = state_override.sequence {
+            actor.sequence = sequence;
+        }
+        if let Some(code) = &state_override.code {
+            set_code(&state_tree, address, &mut actor, code)?;
+        }
+        if let Some(storage) = &state_override.storage {
+            set_storage(state_tree.store(), address, &mut actor, storage)?;
+        }
+        state_tree.set_actor(address, actor)?;
+    }
+    state_tree.flush()
+}
+
+/// Creates the actor of an address that is not in the state, an account actor for a public
+/// key address and an Ethereum account actor for a delegated address.
+fn create_actor<BS: Blockstore>(
+    state_tree: &mut StateTree<BS>,
+    address: &Address,
+) -> anyhow::Result<ActorState> {
+    let manifest = &state_tree.get_actor_bundle_metadata()?.manifest;
+    let actor = match address.protocol() {
+        Protocol::Delegated => ActorState::new(
+            manifest.get(BuiltinActor::EthAccount)?,
+            fil_actors_shared::v10::runtime::EMPTY_ARR_CID,
+            TokenAmount::default(),
+            0,
+            Some(*address),
+        ),
+        Protocol::Secp256k1 | Protocol::BLS => {
+            let state = state_tree
+                .store()
+                .put_cbor_default(&account::State::default_latest_version(address.into()))?;
+            ActorState::new(
+                manifest.get(BuiltinActor::Account)?,
+                state,
+                TokenAmount::default(),
+                0,
+                None,
+            )
+        }
+        Protocol::ID | Protocol::Actor => bail!("actor {address} not found"),
+    };
+    state_tree.register_new_address(address)?;
+    Ok(actor)
+}
+
+/// Replaces the bytecode of an EVM actor, or turns an Ethereum account or a placeholder into
+/// an EVM actor with an empty storage.
+fn set_code<BS: Blockstore>(
+    state_tree: &StateTree<BS>,
+    address: &Address,
+    actor: &mut ActorState,
+    code: &[u8],
+) -> anyhow::Result<()> {
+    let store = state_tree.store();
+    let bytecode = Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(code));
+    store.put_keyed(&bytecode, code)?;
+    let bytecode_hash = keccak_hash::keccak(code).0;
+    let state = if is_evm_actor(&actor.code) {
+        let mut state = evm::State::load(store, actor.code, actor.state)?;
+        state.set_bytecode(bytecode, bytecode_hash);
+        state
+    } else if is_ethaccount_actor(&actor.code) || is_placeholder_actor(&actor.code) {
+        let metadata = state_tree.get_actor_bundle_metadata()?;
+        let storage = StorageKamt::new_with_config(store, STORAGE_KAMT_CONFIG).flush()?;
+        let state = evm::State::new(
+            metadata.actor_major_version()?,
+            bytecode,
+            bytecode_hash,
+            storage,
+            // The nonce of a contract starts at 1, see EIP-161
+            1,
+        )?;
+        actor.code = metadata.manifest.get(BuiltinActor::EVM)?;
+        state
+    } else {
+        bail!("the code of the non-EVM actor {address} cannot be overridden");
+    };
+    actor.state = store.put_cbor_default(&state)?;
+    Ok(())
+}
+
+/// Overrides the storage slots of an EVM actor. A zero value clears a slot.
+fn set_storage<BS: Blockstore>(
+    store: &BS,
+    address: &Address,
+    actor: &mut ActorState,
+    storage: &StorageOverride,
+) -> anyhow::Result<()> {
+    ensure!(
+        is_evm_actor(&actor.code),
+        "the storage of the non-EVM actor {address} cannot be overridden"
+    );
+    let mut state = evm::State::load(store, actor.code, actor.state)?;
+    let (mut kamt, slots) = match storage {
+        StorageOverride::Replace(slots) => (
+            StorageKamt::new_with_config(store, STORAGE_KAMT_CONFIG),
+            slots,
+        ),
+        StorageOverride::Diff(slots) => (
+            StorageKamt::load_with_config(&state.contract_state(), store, STORAGE_KAMT_CONFIG)
+                .context("failed to load the contract storage")?,
+            slots,
+        ),
+    };
+    for (slot, value) in slots {
+        let slot = U256::from_big_endian(slot);
+        let value = U256::from_big_endian(value);
+        if value.is_zero() {
+            kamt.delete(&slot)?;
+        } else {
+            kamt.set(slot, value)?;
+        }
+    }
+    state.set_contract_state(kamt.flush()?);
+    actor.state = store.put_cbor_default(&state)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{BlockstoreWithMemoryOverlay, MemoryDB};
+    use crate::networks::ACTOR_BUNDLES_METADATA;
+    use crate::shim::actors::{init, system};
+    use crate::shim::state_tree::StateTreeVersion;
+    use fil_actors_shared::v11::{builtin::HAMT_BIT_WIDTH, make_empty_map};
+
+    /// A state with the system and init actors of the latest actors bundle
+    fn new_state(store: &Arc<MemoryDB>) -> Cid {
+        let manifest = &ACTOR_BUNDLES_METADATA
+            .values()
+            .max_by_key(|metadata| metadata.actor_major_version().unwrap_or_default())
+            .unwrap()
+            .manifest;
+        let mut state_tree = StateTree::new(store.clone(), StateTreeVersion::V5).unwrap();
+        let system_state = store
+            .put_cbor_default(&system::State::default_latest_version(
+                manifest.source_cid(),
+            ))
+            .unwrap();
+        let address_map = make_empty_map::<_, u64>(store, HAMT_BIT_WIDTH)
+            .flush()
+            .unwrap();
+        let init_state = store
+            .put_cbor_default(&init::State::default_latest_version(
+                address_map,
+                1000,
+                "test".into(),
+            ))
+            .unwrap();
+        for (address, code, state) in [
+            (Address::SYSTEM_ACTOR, manifest.get_system(), system_state),
+            (Address::INIT_ACTOR, manifest.get_init(), init_state),
+        ] {
+            state_tree
+                .set_actor(
+                    &address,
+                    ActorState::new(code, state, TokenAmount::default(), 0, None),
+                )
+                .unwrap();
+        }
+        state_tree.flush().unwrap()
+    }
+
+    #[test]
+    fn overrides_are_not_persisted() {
+        let db = Arc::new(MemoryDB::default());
+        let state_root = new_state(&db);
+        let address = Address::new_delegated(10, &[1; 20]).unwrap();
+        let overlay = Arc::new(BlockstoreWithMemoryOverlay::new(db.clone()));
+        let overrides = StateOverrides::from([(
+            address,
+            ActorStateOverride {
+                balance: Some(TokenAmount::from_atto(100)),
+                ..Default::default()
+            },
+        )]);
+
+        let overridden = apply_state_overrides(overlay.clone(), state_root, &overrides).unwrap();
+        assert!(!db.has(&overridden).unwrap());
+        let actor = StateTree::new_from_root(overlay, &overridden)
+            .unwrap()
+            .get_actor(&address)
+            .unwrap()
+            .unwrap();
+        assert!(is_ethaccount_actor(&actor.code));
+        assert_eq!(
+            TokenAmount::from(&actor.balance),
+            TokenAmount::from_atto(100)
+        );
+        assert!(
+            StateTree::new_from_root(db, &state_root)
+                .unwrap()
+                .get_actor(&address)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn override_code_and_storage() {
+        let db = Arc::new(MemoryDB::default());
+        let state_root = new_state(&db);
+        let store = Arc::new(BlockstoreWithMemoryOverlay::new(db));
+        let address = Address::new_delegated(10, &[2; 20]).unwrap();
+        let slot = |i: u8| {
+            let mut slot = [0; 32];
+            slot[31] = i;
+            slot
+        };
+        let overrides = StateOverrides::from([(
+            address,
+            ActorStateOverride {
+                code: Some(vec![0x60, 0x80]),
+                storage: Some(StorageOverride::Replace(BTreeMap::from([
+                    (slot(1), slot(10)),
+                    (slot(2), slot(20)),
+                ]))),
+                ..Default::default()
+            },
+        )]);
+        let state_root = apply_state_overrides(store.clone(), state_root, &overrides).unwrap();
+
+        let storage = |state_root: Cid| {
+            let actor = StateTree::new_from_root(store.clone(), &state_root)
+                .unwrap()
+                .get_actor(&address)
+                .unwrap()
+                .unwrap();
+            assert!(is_evm_actor(&actor.code));
+            let state = evm::State::load(&store, actor.code, actor.state).unwrap();
+            let kamt =
+                StorageKamt::load_with_config(&state.contract_state(), &store, STORAGE_KAMT_CONFIG)
+                    .unwrap();
+            [1, 2, 3].map(|i| {
+                kamt.get(&U256::from_big_endian(&slot(i)))
+                    .unwrap()
+                    .copied()
+                    .unwrap_or_default()
+            })
+        };
+        assert_eq!(
+            storage(state_root),
+            [U256::from(10_u64), U256::from(20_u64), U256::zero()]
+        );
+
+        // A diff keeps the other slots, a zero value clears a slot
+        let overrides = StateOverrides::from([(
+            address,
+            ActorStateOverride {
+                storage: Some(StorageOverride::Diff(BTreeMap::from([
+                    (slot(1), [0; 32]),
+                    (slot(3), slot(30)),
+                ]))),
+                ..Default::default()
+            },
+        )]);
+        let state_root = apply_state_overrides(store.clone(), state_root, &overrides).unwrap();
+        assert_eq!(
+            storage(state_root),
+            [U256::zero(), U256::from(20_u64), U256::from(30_u64)]
+        );
+    }
+}
@@ -8,6 +8,7 @@ use crate::db::MemoryDB;
 use crate::networks::ChainConfig;
 use crate::shim::clock::ChainEpoch;
 use crate::shim::executor::{Receipt, StampedEvent};
+use crate::test_utils::{BlockSpec, ChainHarness};
 use crate::utils::db::CborStoreExt;
 use crate::utils::multihash::MultihashCode;
 use cid::Cid;
@@ -488,6 +489,25 @@ fn test_events_store_and_retrieve_basic() {
     assert_eq!(retrieved_events[2].emitter(), 1002);
 }
 
+#[test]
+fn test_store_events_checks_events_root() {
+    let events = vec![
+        StampedEvent::V4(create_raw_event_v4(1000, "event1")),
+        StampedEvent::V4(create_raw_event_v4(1001, "event2")),
+    ];
+    let events_root =
+        Amt::new_from_iter_with_bit_width(&MemoryDB::default(), EVENTS_AMT_BITWIDTH, events.iter())
+            .unwrap();
+
+    let db = MemoryDB::default();
+    store_events(&db, Some(BlockKind::Event), &events, &events_root).unwrap();
+    assert_eq!(
+        StampedEvent::get_events(&db, &events_root).unwrap().len(),
+        2
+    );
+    store_events(&db, None, &events[..1], &events_root).unwrap_err();
+}
+
 #[test]
 fn test_events_entries_are_preserved_when_duplicates_are_stored() {
     let db = MemoryDB::default();
@@ -622,3 +642,140 @@ fn test_identical_events_produce_same_root() {
     assert_eq!(retrieved_events[0].emitter(), 1000);
     assert_eq!(retrieved_events[1].emitter(), 1001);
 }
+
+const WAIT_SENDER: u64 = 100;
+
+/// A message of [`WAIT_SENDER`], and a block including it.
+fn wait_message() -> (Message, BlockSpec) {
+    let message = Message {
+        from: Address::new_id(WAIT_SENDER),
+        to: Address::new_id(WAIT_SENDER + 1),
+        sequence: 0,
+        ..Default::default()
+    };
+    let block = BlockSpec {
+        bls_messages: vec![message.clone()],
+        ..Default::default()
+    };
+    (message, block)
+}
+
+/// Sets the nonce of [`WAIT_SENDER`] in the state of the next produced blocks.
+fn set_wait_sender_nonce(harness: &ChainHarness, sequence: u64) {
+    harness
+        .update_state(|state| {
+            state.set_actor(
+                &Address::new_id(WAIT_SENDER),
+                ActorState::new(
+                    create_dummy_cid(0),
+                    create_dummy_cid(0),
+                    TokenAmount::default(),
+                    sequence,
+                    None,
+                ),
+            )
+        })
+        .unwrap();
+}
+
+fn wait_receipt(gas_used: u64) -> Receipt {
+    Receipt::V4(fvm_shared4::receipt::Receipt {
+        exit_code: fvm_shared4::error::ExitCode::new(0),
+        return_data: fvm_ipld_encoding::RawBytes::default(),
+        gas_used,
+        events_root: None,
+    })
+}
+
+/// Includes the message in a tipset, and executes it in the next one with a receipt using
+/// `gas_used`. Returns the executing tipset.
+fn include_and_execute(harness: &ChainHarness, block: BlockSpec, gas_used: u64) -> Tipset {
+    set_wait_sender_nonce(harness, 0);
+    harness.produce(0, [block]).unwrap();
+    set_wait_sender_nonce(harness, 1);
+    harness
+        .set_parent_receipts([wait_receipt(gas_used)])
+        .unwrap();
+    harness.produce_empty(1).unwrap();
+    harness.head()
+}
+
+/// Waits for the spawned wait to process the head changes. The tests run with the clock paused,
+/// which only advances when all the tasks are idle, so the sleep ends once the wait is idle again.
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_message_with_confidence() {
+    let harness = ChainHarness::new();
+    let (message, block) = wait_message();
+    let executed = include_and_execute(&harness, block, 42);
+
+    let wait = tokio::spawn({
+        let state_manager = harness.state_manager.clone();
+        async move {
+            state_manager
+                .wait_for_message(message.cid(), 2, None, None)
+                .await
+        }
+    });
+    settle().await;
+    harness.produce_empty(1).unwrap();
+    settle().await;
+    // The message has only been on chain for one epoch.
+    assert!(!wait.is_finished());
+
+    harness.produce_empty(1).unwrap();
+    let (tipset, receipt) = wait.await.unwrap().unwrap();
+    assert_eq!(tipset.unwrap(), executed);
+    assert_eq!(receipt.unwrap().gas_used(), 42);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_message_searches_again_after_reorg() {
+    let harness = ChainHarness::new();
+    set_wait_sender_nonce(&harness, 0);
+    harness.produce_empty(1).unwrap();
+    let fork_point = harness.head();
+    let (message, block) = wait_message();
+    let reverted = include_and_execute(&harness, block.clone(), 42);
+
+    let wait = tokio::spawn({
+        let state_manager = harness.state_manager.clone();
+        async move {
+            state_manager
+                .wait_for_message(message.cid(), 2, None, None)
+                .await
+        }
+    });
+    settle().await;
+
+    // Revert the tipsets including and executing the message.
+    harness
+        .chain_store
+        .set_heaviest_tipset(fork_point.clone())
+        .unwrap();
+    settle().await;
+    assert!(!wait.is_finished());
+
+    // The message is included again, by another miner, on the new fork.
+    let executed = include_and_execute(
+        &harness,
+        BlockSpec {
+            miner: Some(Address::new_id(2000)),
+            ..block
+        },
+        7,
+    );
+    assert_ne!(executed, reverted);
+    assert_eq!(executed.epoch(), reverted.epoch());
+    harness.produce_empty(1).unwrap();
+    settle().await;
+    assert!(!wait.is_finished());
+
+    harness.produce_empty(1).unwrap();
+    let (tipset, receipt) = wait.await.unwrap().unwrap();
+    assert_eq!(tipset.unwrap(), executed);
+    assert_eq!(receipt.unwrap().gas_used(), 7);
+}
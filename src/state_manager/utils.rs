@@ -2,22 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::MinerActorStateLoad as _;
-use crate::shim::actors::miner;
+use crate::blocks::Tipset;
+use crate::shim::actors::{miner, power};
 use crate::shim::{
     actors::{is_account_actor, is_ethaccount_actor, is_placeholder_actor},
-    address::{Address, Payload},
+    address::{Address, DelegatedNamespace, Payload},
     randomness::Randomness,
+    runtime::Policy,
     sector::{ExtendedSectorInfo, RegisteredPoStProof, RegisteredSealProof},
-    state_tree::ActorState,
+    state_tree::{ActorState, StateTree},
     version::NetworkVersion,
 };
 use crate::state_manager::{StateManager, errors::*};
 use crate::utils::encoding::prover_id_from_u64;
+use anyhow::Context as _;
 use cid::Cid;
 use fil_actors_shared::filecoin_proofs_api::post;
 use fil_actors_shared::fvm_ipld_bitfield::BitField;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::bytes_32;
+use num::BigInt;
+use num_traits::Zero as _;
+use std::sync::Arc;
 
 impl<DB> StateManager<DB>
 where
@@ -110,6 +116,46 @@ where
     }
 }
 
+/// Checks the eligibility of a miner to mine a block on the base tipset: it meets the minimum
+/// power in the lookback state and, from network version 4, has power, no fee debt and no
+/// active consensus fault in the state of the base tipset.
+pub fn miner_eligible_to_mine<DB: Blockstore>(
+    db: &Arc<DB>,
+    policy: &Policy,
+    network_version: NetworkVersion,
+    address: &Address,
+    base_tipset: &Tipset,
+    lookback_state: &Cid,
+) -> anyhow::Result<bool> {
+    let lookback_power: power::State =
+        StateTree::new_from_root(db.clone(), lookback_state)?.get_actor_state()?;
+    let hmp = lookback_power.miner_nominal_power_meets_consensus_minimum(policy, db, address)?;
+    if network_version <= NetworkVersion::V3 || !hmp {
+        return Ok(hmp);
+    }
+
+    let state_tree = StateTree::new_from_root(db.clone(), base_tipset.parent_state())?;
+    let power_state: power::State = state_tree.get_actor_state()?;
+    let miner_state: miner::State = state_tree.get_actor_state_from_address(address)?;
+
+    // Non-empty power claim.
+    let claim = power_state
+        .miner_power(db, address)?
+        .context("Could not get claim")?;
+    if claim.quality_adj_power <= BigInt::zero() {
+        return Ok(false);
+    }
+
+    // No fee debt.
+    if !miner_state.fee_debt().is_zero() {
+        return Ok(false);
+    }
+
+    // No active consensus faults.
+    let info = miner_state.info(db)?;
+    Ok(base_tipset.epoch() > info.consensus_fault_elapsed)
+}
+
 pub fn is_valid_for_sending(network_version: NetworkVersion, actor: &ActorState) -> bool {
     // Comments from Lotus:
     // Before nv18 (Hygge), we only supported built-in account actors as senders.
@@ -150,7 +196,7 @@ pub fn is_valid_for_sending(network_version: NetworkVersion, actor: &ActorState)
         .expect("unfallible")
         .payload()
     {
-        address.namespace() == Address::ETHEREUM_ACCOUNT_MANAGER_ACTOR.id().unwrap()
+        address.namespace() == DelegatedNamespace::EAM.actor_id
     } else {
         false
     }
@@ -10,7 +10,10 @@ use super::{
     verifier::MigrationVerifier,
 };
 use crate::cid_collections::CidHashMap;
-use crate::shim::{clock::ChainEpoch, state_tree::StateTree};
+use crate::shim::{
+    clock::ChainEpoch,
+    state_tree::{StateTree, for_each_actor},
+};
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use nonzero_ext::nonzero;
@@ -169,41 +172,48 @@ impl<BS: Blockstore + Send + Sync> StateMigration<BS> {
         // very cheap; ~200ms on mainnet. The alternative is to collect the deferred migrations
         // into a separate collection, which would increase the memory footprint of the migration.
         tracing::info!("Processing deferred migrations");
-        let mut job_counter = 0;
-        actors_in_clone.lock().for_each(|address, state| {
-            job_counter += 1;
-            let migrator = self
-                .migrations
-                .get(&state.code)
-                .cloned()
-                .unwrap_or_else(|| panic!("migration failed with state code: {}", state.code));
-
-            if !migrator.is_deferred() {
-                return Ok(());
-            }
+        let actors_in_root = actors_in_clone.lock().flush()?;
+        let actors_out = Mutex::new(actors_out);
+        let job_counter = AtomicU64::new(0);
+        pool.install(|| {
+            for_each_actor(store, &actors_in_root, |address, state| {
+                job_counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let migrator = self
+                    .migrations
+                    .get(&state.code)
+                    .cloned()
+                    .unwrap_or_else(|| panic!("migration failed with state code: {}", state.code));
+
+                if !migrator.is_deferred() {
+                    return Ok(());
+                }
 
-            let job = MigrationJob {
-                address,
-                actor_state: state.clone(),
-                actor_migration: migrator,
-            };
-            let job_output = job.run(store, prior_epoch, cache.clone())?;
-            if let Some(MigrationJobOutput {
-                address,
-                actor_state,
-            }) = job_output
-            {
-                actors_out
-                    .set_actor(&address, actor_state)
-                    .unwrap_or_else(|e| {
-                        panic!(
-                            "Failed setting new actor state at given address: {address}, Reason: {e}"
-                        )
-                    });
-            }
+                let job = MigrationJob {
+                    address,
+                    actor_state: state.clone(),
+                    actor_migration: migrator,
+                };
+                let job_output = job.run(store, prior_epoch, cache.clone())?;
+                if let Some(MigrationJobOutput {
+                    address,
+                    actor_state,
+                }) = job_output
+                {
+                    actors_out
+                        .lock()
+                        .set_actor(&address, actor_state)
+                        .unwrap_or_else(|e| {
+                            panic!(
+                                "Failed setting new actor state at given address: {address}, Reason: {e}"
+                            )
+                        });
+                }
 
-            Ok(())
+                Ok(())
+            })
         })?;
+        let mut actors_out = actors_out.into_inner();
+        let job_counter = job_counter.into_inner();
         tracing::info!("Processed {job_counter} deferred migrations");
 
         // execute post migration actions, e.g., create new actors
@@ -89,6 +89,16 @@ where
     }
 }
 
+/// Returns whether a state migration runs at any of the epochs.
+pub fn has_state_migrations(
+    epochs: std::ops::Range<ChainEpoch>,
+    chain_config: &ChainConfig,
+) -> bool {
+    get_migrations::<crate::db::MemoryDB>(&chain_config.network)
+        .iter()
+        .any(|(height, _)| epochs.contains(&chain_config.epoch(*height)))
+}
+
 /// Run state migrations
 pub fn run_state_migrations<DB>(
     epoch: ChainEpoch,
@@ -97,14 +107,8 @@ pub fn run_state_migrations<DB>(
     parent_state: &Cid,
 ) -> anyhow::Result<Option<Cid>>
 where
-    DB: Blockstore + Send + Sync,
+    DB: Blockstore + Send + Sync + 'static,
 {
-    // ~10MB RAM per 10k buffer
-    let db_write_buffer = match std::env::var("FOREST_STATE_MIGRATION_DB_WRITE_BUFFER") {
-        Ok(v) => v.parse().ok(),
-        _ => None,
-    }
-    .unwrap_or(10000);
     let mappings = get_migrations(&chain_config.network);
 
     // Make sure bundle is defined.
@@ -128,9 +132,9 @@ where
         if epoch == chain_config.epoch(height) {
             tracing::info!("Running {height} migration at epoch {epoch}");
             let start_time = std::time::Instant::now();
-            let db = Arc::new(BlockstoreWithWriteBuffer::new_with_capacity(
+            let db = Arc::new(BlockstoreWithWriteBuffer::new(
                 db.clone(),
-                db_write_buffer,
+                chain_config.write_buffer,
             ));
             let new_state = migrate(chain_config, &db, parent_state, epoch)?;
             let elapsed = start_time.elapsed();
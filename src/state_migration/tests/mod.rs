@@ -7,7 +7,7 @@ use crate::{
     daemon::bundle::load_actor_bundles,
     networks::{ChainConfig, Height, NetworkChain},
     shim::state_tree::StateRoot,
-    state_migration::run_state_migrations,
+    state_migration::{has_state_migrations, run_state_migrations},
 };
 use cid::Cid;
 use futures::{AsyncWriteExt, TryStreamExt};
@@ -124,3 +124,13 @@ async fn test_state_migration(
 
     assert_eq!(new_state, Some(expected_new_state));
 }
+
+#[test]
+fn test_has_state_migrations() {
+    let chain_config = ChainConfig::calibnet();
+    let shark = chain_config.epoch(Height::Shark);
+    assert!(has_state_migrations(shark..shark + 1, &chain_config));
+    // The migration also runs when its epoch is a null round.
+    assert!(has_state_migrations(shark - 2..shark + 3, &chain_config));
+    assert!(!has_state_migrations(shark + 1..shark + 2, &chain_config));
+}
@@ -0,0 +1,350 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! An in-memory chain for integration tests, see [`ChainHarness`].
+
+use std::sync::Arc;
+
+use cid::Cid;
+use fil_actors_shared::fvm_ipld_amt::Amtv0 as Amt;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use crate::blocks::{
+    Block, CachingBlockHeader, FullTipset, RawBlockHeader, Ticket, Tipset, VRFProof,
+};
+use crate::chain::ChainStore;
+use crate::chain_sync::network_context::SyncNetworkContext;
+use crate::chain_sync::{SyncStatusReport, TipsetValidator};
+use crate::db::MemoryDB;
+use crate::key_management::{KeyStore, KeyStoreConfig};
+use crate::libp2p::{NetworkMessage, PeerManager};
+use crate::message::SignedMessage;
+use crate::message_pool::{MessagePool, MpoolRpcProvider};
+use crate::networks::ChainConfig;
+use crate::rpc::RPCState;
+use crate::rpc::eth::filter::EthEventHandler;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::executor::Receipt;
+use crate::shim::message::Message;
+use crate::shim::state_tree::{StateTree, StateTreeVersion};
+use crate::state_manager::StateManager;
+
+/// A [`ChainStore`], a [`StateManager`] and a [`MessagePool`] over a [`MemoryDB`], with a
+/// programmable block producer. It lets higher-level features, like the sync logic or the RPC
+/// methods, be tested without snapshots or network access.
+///
+/// The produced blocks are not executed. They carry the state root set with
+/// [`ChainHarness::update_state`], which starts as an empty state tree, and empty receipts unless
+/// set with [`ChainHarness::set_parent_receipts`].
+///
+/// Must be created within a `tokio` runtime, which runs the tasks of the message pool.
+pub struct ChainHarness {
+    pub chain_store: Arc<ChainStore<MemoryDB>>,
+    pub state_manager: Arc<StateManager<MemoryDB>>,
+    pub mpool: Arc<MessagePool<MpoolRpcProvider<MemoryDB>>>,
+    /// Receives the messages that the message pool publishes to the network.
+    pub network_rx: flume::Receiver<NetworkMessage>,
+    network_send: flume::Sender<NetworkMessage>,
+    /// State root of the next produced blocks.
+    state_root: Mutex<Cid>,
+    /// Receipts root of the next produced tipset only.
+    parent_receipts: Mutex<Option<Cid>>,
+    empty_receipts_root: Cid,
+    _services: JoinSet<anyhow::Result<()>>,
+}
+
+/// A block for the [`ChainHarness`] to produce.
+#[derive(Debug, Clone, Default)]
+pub struct BlockSpec {
+    /// Miner of the block. Defaults to a distinct ID address for every block of a tipset.
+    pub miner: Option<Address>,
+    pub bls_messages: Vec<Message>,
+    pub secp_messages: Vec<SignedMessage>,
+}
+
+impl BlockSpec {
+    /// A block of the given messages, the BLS signed ones are included unsigned.
+    pub fn with_messages(messages: impl IntoIterator<Item = SignedMessage>) -> Self {
+        let mut spec = Self::default();
+        for message in messages {
+            if message.is_bls() {
+                spec.bls_messages.push(message.message);
+            } else {
+                spec.secp_messages.push(message);
+            }
+        }
+        spec
+    }
+}
+
+impl Default for ChainHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainHarness {
+    pub fn new() -> Self {
+        Self::with_chain_config(ChainConfig::default())
+    }
+
+    pub fn with_chain_config(chain_config: ChainConfig) -> Self {
+        let db = Arc::new(MemoryDB::default());
+        let state_root = StateTree::new(db.clone(), StateTreeVersion::V5)
+            .and_then(|mut state| state.flush())
+            .expect("failed to create the genesis state tree");
+        let empty_receipts_root = Amt::new_from_iter(&db, Vec::<Receipt>::new())
+            .expect("failed to create the receipts root");
+        let genesis = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::new_id(0),
+            state_root,
+            message_receipts: empty_receipts_root,
+            messages: TipsetValidator::compute_msg_root(&db, &[], &[])
+                .expect("failed to create the messages root"),
+            timestamp: 7777,
+            ..Default::default()
+        });
+        crate::chain::persist_objects(&db, std::iter::once(&genesis))
+            .expect("failed to persist the genesis");
+
+        let chain_store = Arc::new(
+            ChainStore::new(
+                db.clone(),
+                db.clone(),
+                db,
+                Arc::new(chain_config),
+                genesis.clone(),
+            )
+            .expect("failed to create the chain store"),
+        );
+        chain_store
+            .set_heaviest_tipset(Tipset::from(genesis))
+            .expect("failed to set the genesis as head");
+        let state_manager = Arc::new(
+            StateManager::new(chain_store.clone()).expect("failed to create the state manager"),
+        );
+
+        let (network_send, network_rx) = flume::unbounded();
+        let mut services = JoinSet::new();
+        let mpool = MessagePool::new(
+            MpoolRpcProvider::new(chain_store.publisher().clone(), state_manager.clone()),
+            network_send.clone(),
+            Default::default(),
+            state_manager.chain_config().clone(),
+            &mut services,
+        )
+        .expect("failed to create the message pool");
+
+        Self {
+            chain_store,
+            state_manager,
+            mpool: Arc::new(mpool),
+            network_rx,
+            network_send,
+            state_root: Mutex::new(state_root),
+            parent_receipts: Default::default(),
+            empty_receipts_root,
+            _services: services,
+        }
+    }
+
+    pub fn db(&self) -> &Arc<MemoryDB> {
+        self.chain_store.blockstore()
+    }
+
+    pub fn genesis(&self) -> Tipset {
+        self.chain_store.genesis_tipset()
+    }
+
+    pub fn head(&self) -> Tipset {
+        self.chain_store.heaviest_tipset()
+    }
+
+    /// Modifies the state tree that the next produced blocks carry, for instance to fund the
+    /// senders of messages.
+    pub fn update_state(
+        &self,
+        update: impl FnOnce(&mut StateTree<MemoryDB>) -> anyhow::Result<()>,
+    ) -> anyhow::Result<Cid> {
+        let mut state_root = self.state_root.lock();
+        let mut state = StateTree::new_from_root(self.db().clone(), &state_root)?;
+        update(&mut state)?;
+        *state_root = state.flush()?;
+        Ok(*state_root)
+    }
+
+    /// Sets the receipts of the messages of the head, which the next produced tipset carries.
+    pub fn set_parent_receipts(
+        &self,
+        receipts: impl IntoIterator<Item = Receipt>,
+    ) -> anyhow::Result<Cid> {
+        let root = Amt::new_from_iter(self.db(), receipts.into_iter().collect::<Vec<_>>())?;
+        *self.parent_receipts.lock() = Some(root);
+        Ok(root)
+    }
+
+    /// Produces a tipset of the given blocks on top of the head, after the given number of null
+    /// rounds, and makes it the new head.
+    pub fn produce(
+        &self,
+        null_rounds: ChainEpoch,
+        blocks: impl IntoIterator<Item = BlockSpec>,
+    ) -> anyhow::Result<FullTipset> {
+        let parent = self.head();
+        let epoch = parent.epoch() + 1 + null_rounds;
+        let timestamp = parent.min_timestamp()
+            + (1 + null_rounds) as u64 * self.state_manager.chain_config().block_delay_secs as u64;
+        let state_root = *self.state_root.lock();
+        let message_receipts = self
+            .parent_receipts
+            .lock()
+            .take()
+            .unwrap_or(self.empty_receipts_root);
+        let blocks = blocks
+            .into_iter()
+            .enumerate()
+            .map(|(i, spec)| {
+                let header = CachingBlockHeader::new(RawBlockHeader {
+                    miner_address: spec
+                        .miner
+                        .unwrap_or_else(|| Address::new_id(1000 + i as u64)),
+                    ticket: Some(Ticket::new(VRFProof::new(
+                        format!("{epoch}/{i}").into_bytes(),
+                    ))),
+                    parents: parent.key().clone(),
+                    weight: parent.weight() + 1,
+                    epoch,
+                    state_root,
+                    message_receipts,
+                    messages: TipsetValidator::compute_msg_root(
+                        self.db(),
+                        &spec.bls_messages,
+                        &spec.secp_messages,
+                    )?,
+                    timestamp,
+                    parent_base_fee: parent.min_ticket_block().parent_base_fee.clone(),
+                    ..Default::default()
+                });
+                Ok(Block {
+                    header,
+                    bls_messages: spec.bls_messages,
+                    secp_messages: spec.secp_messages,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let tipset = FullTipset::new(blocks)?;
+        tipset.persist(self.db())?;
+        self.chain_store
+            .set_heaviest_tipset(tipset.clone().into_tipset())?;
+        Ok(tipset)
+    }
+
+    /// Produces tipsets of the blocks returned by `producer` for every epoch, and returns them.
+    pub fn produce_with(
+        &self,
+        count: usize,
+        mut producer: impl FnMut(&Self, ChainEpoch) -> Vec<BlockSpec>,
+    ) -> anyhow::Result<Vec<FullTipset>> {
+        (0..count)
+            .map(|_| {
+                let blocks = producer(self, self.head().epoch() + 1);
+                self.produce(0, blocks)
+            })
+            .collect()
+    }
+
+    /// Produces tipsets of a single empty block.
+    pub fn produce_empty(&self, count: usize) -> anyhow::Result<Vec<FullTipset>> {
+        self.produce_with(count, |_, _| vec![BlockSpec::default()])
+    }
+
+    /// Returns a block of the messages pending in the message pool.
+    pub fn pending_block(&self) -> anyhow::Result<BlockSpec> {
+        let (pending, _) = self.mpool.pending()?;
+        Ok(BlockSpec::with_messages(pending))
+    }
+
+    /// Returns an RPC state over the chain, to test RPC methods.
+    pub fn rpc_state(&self) -> Arc<RPCState<MemoryDB>> {
+        let sync_network_context = SyncNetworkContext::new(
+            self.network_send.clone(),
+            Arc::new(PeerManager::default()),
+            self.state_manager.blockstore_owned(),
+        );
+        Arc::new(RPCState {
+            state_manager: self.state_manager.clone(),
+            keystore: Arc::new(RwLock::new(
+                KeyStore::new(KeyStoreConfig::Memory).expect("failed to create the keystore"),
+            )),
+            mpool: self.mpool.clone(),
+            bad_blocks: Some(Default::default()),
+            msgs_in_tipset: Default::default(),
+            sync_status: Arc::new(RwLock::new(SyncStatusReport::init())),
+            eth_event_handler: Arc::new(EthEventHandler::new()),
+            sync_network_context,
+            start_time: chrono::Utc::now(),
+            shutdown: mpsc::channel(1).0, // dummy for tests
+            tipset_send: flume::unbounded().0,
+            snapshot_progress_tracker: Default::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcMethod as _;
+    use crate::rpc::chain::{ChainGetBlockMessages, ChainHead};
+    use crate::test_utils::construct_messages;
+
+    #[tokio::test]
+    async fn chain_harness() {
+        let harness = ChainHarness::new();
+        assert_eq!(harness.head(), harness.genesis());
+
+        let tipsets = harness.produce_empty(3).unwrap();
+        assert_eq!(tipsets.len(), 3);
+        assert_eq!(harness.head().epoch(), 3);
+        assert_eq!(harness.head().parents(), tipsets[1].key());
+
+        // A tipset of two blocks, after two null rounds.
+        let (bls_message, secp_message) = construct_messages();
+        let tipset = harness
+            .produce(
+                2,
+                [
+                    BlockSpec {
+                        bls_messages: vec![bls_message],
+                        ..Default::default()
+                    },
+                    BlockSpec {
+                        secp_messages: vec![secp_message],
+                        ..Default::default()
+                    },
+                ],
+            )
+            .unwrap();
+        assert_eq!(tipset.epoch(), 6);
+        assert_eq!(tipset.blocks().len(), 2);
+
+        let ctx = harness.rpc_state();
+        let head = ChainHead::handle(ctx.clone(), ()).await.unwrap();
+        assert_eq!(head, harness.head());
+        for block in tipset.blocks() {
+            let messages = ChainGetBlockMessages::handle(ctx.clone(), (*block.cid(),))
+                .await
+                .unwrap();
+            assert_eq!(messages.bls_msg, block.bls_messages);
+            assert_eq!(messages.secp_msg, block.secp_messages);
+        }
+        let messages = tipset
+            .blocks()
+            .iter()
+            .map(|block| block.bls_messages.len() + block.secp_messages.len())
+            .sum::<usize>();
+        assert_eq!(messages, 2);
+    }
+}
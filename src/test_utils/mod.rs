@@ -1,6 +1,10 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+// Relies on test-only defaults of the block header.
+#[cfg(test)]
+mod chain_harness;
+
 use std::str::FromStr;
 
 use crate::{
@@ -15,6 +19,9 @@ use crate::{
 };
 use base64::{Engine, prelude::BASE64_STANDARD};
 
+#[cfg(test)]
+pub use chain_harness::{BlockSpec, ChainHarness};
+
 /// Returns a Ticket to be used for testing
 pub fn construct_ticket() -> Ticket {
     let vrf_result = VRFProof::new(BASE64_STANDARD.decode("lmRJLzDpuVA7cUELHTguK9SFf+IVOaySG8t/0IbVeHHm3VwxzSNhi1JStix7REw6Apu6rcJQV1aBBkd39gQGxP8Abzj8YXH+RdSD5RV50OJHi35f3ixR0uhkY6+G08vV").unwrap());
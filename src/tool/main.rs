@@ -22,16 +22,25 @@ where
     match cmd {
         Subcommand::Backup(cmd) => cmd.run(),
         Subcommand::Benchmark(cmd) => cmd.run().await,
+        Subcommand::State(cmd) => cmd.run().await,
         Subcommand::StateMigration(cmd) => cmd.run().await,
         Subcommand::Snapshot(cmd) => cmd.run().await,
         Subcommand::Fetch(cmd) => cmd.run().await,
         Subcommand::Archive(cmd) => cmd.run().await,
         Subcommand::DB(cmd) => cmd.run().await,
+        Subcommand::Export(cmd) => cmd.run().await,
         Subcommand::Index(cmd) => cmd.run().await,
         Subcommand::Car(cmd) => cmd.run().await,
         Subcommand::Api(cmd) => cmd.run().await,
         Subcommand::Net(cmd) => cmd.run().await,
+        Subcommand::LightClient(cmd) => cmd.run(client).await,
+        Subcommand::Address(cmd) => cmd.run(client).await,
+        Subcommand::Hamt(cmd) => cmd.run(client).await,
+        Subcommand::Amt(cmd) => cmd.run(client).await,
+        Subcommand::Message(cmd) => cmd.run(client).await,
         Subcommand::Shed(cmd) => cmd.run(client).await,
+        Subcommand::Test(cmd) => cmd.run().await,
+        Subcommand::TestVectors(cmd) => cmd.run().await,
         Subcommand::Completion(cmd) => cmd.run(&mut std::io::stdout()),
     }
 }
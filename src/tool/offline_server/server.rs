@@ -234,7 +234,7 @@ where
     let mut terminate = signal(SignalKind::terminate())?;
     let (stop_handle, server_handle) = stop_channel();
     let result = tokio::select! {
-        ret = start_rpc(state, rpc_listener,stop_handle, None) => ret,
+        ret = start_rpc(state, rpc_listener, stop_handle, Default::default()) => ret,
         _ = ctrl_c() => {
             info!("Keyboard interrupt.");
             Ok(())
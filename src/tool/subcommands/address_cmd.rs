@@ -0,0 +1,95 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::str::FromStr as _;
+
+use crate::rpc::eth::types::EthAddress;
+use crate::rpc::types::ApiTipsetKey;
+use crate::rpc::{self, prelude::*};
+use crate::shim::address::{Address, CurrentNetwork, Network, Protocol};
+use crate::shim::clock::ChainEpoch;
+use anyhow::Context as _;
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+pub enum AddressCommands {
+    /// Convert an address between its Filecoin and Ethereum forms
+    Convert {
+        /// A Filecoin address (e.g. `f01234` or `f410f...`), an Ethereum address (`0x...`) or a
+        /// delegated address with a namespace prefix (e.g. `eth:0x...`)
+        address: String,
+        /// Resolve the ID and robust addresses using the state of a running node
+        #[arg(long)]
+        resolve: bool,
+        /// Epoch of the tipset to resolve the addresses at (the chain head if omitted)
+        #[arg(long, requires = "resolve")]
+        epoch: Option<ChainEpoch>,
+    },
+}
+
+impl AddressCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Convert {
+                address,
+                resolve,
+                epoch,
+            } => {
+                let address = if address.starts_with("0x") {
+                    EthAddress::from_str(&address)
+                        .map_err(|e| anyhow::anyhow!("invalid Ethereum address {address}: {e}"))?
+                        .to_filecoin_address()?
+                } else if address.contains(':') {
+                    Address::from_namespaced_str(&address)?
+                } else {
+                    // Print addresses with the same network prefix as the input.
+                    if address.starts_with('t') {
+                        CurrentNetwork::set(Network::Testnet);
+                    }
+                    Address::from_str(&address)
+                        .with_context(|| format!("invalid Filecoin address {address}"))?
+                };
+                println!("Filecoin address: {address}");
+                if let Some(namespaced) = address.to_namespaced_string() {
+                    println!("Delegated address: {namespaced}");
+                }
+
+                let mut eth_address = EthAddress::from_filecoin_address(&address).ok();
+                if resolve {
+                    let tsk = match epoch {
+                        Some(epoch) => Some(
+                            ChainGetTipSetByHeight::call(&client, (epoch, ApiTipsetKey(None)))
+                                .await?
+                                .key()
+                                .clone(),
+                        ),
+                        None => None,
+                    };
+                    let id_address =
+                        StateLookupID::call(&client, (address, ApiTipsetKey(tsk.clone()))).await?;
+                    println!("ID address: {id_address}");
+                    // Built-in actors do not have a robust address.
+                    match StateLookupRobustAddress::call(&client, (id_address, ApiTipsetKey(tsk)))
+                        .await
+                        .ok()
+                        .filter(|robust| robust.protocol() != Protocol::ID)
+                    {
+                        Some(robust_address) => println!("Robust address: {robust_address}"),
+                        None => println!("Robust address: none"),
+                    }
+                    if eth_address.is_none() {
+                        eth_address = EthAddress::from_filecoin_address(&id_address).ok();
+                    }
+                }
+                match eth_address {
+                    Some(eth_address) => println!("Ethereum address: {:#x}", eth_address.0),
+                    None if resolve => println!("Ethereum address: none"),
+                    None => println!(
+                        "Ethereum address: unknown, use `--resolve` to look up the ID address"
+                    ),
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+use clap::Subcommand;
+use fil_actors_shared::fvm_ipld_amt::Amt;
+
+use super::hamt_cmd::{BlockSource, ValueSchema};
+use crate::ipld::Ipld;
+use crate::rpc;
+
+#[derive(Debug, Subcommand)]
+pub enum AmtCommands {
+    /// Print the indices and values of an AMT, whose bit width is read from its root
+    Dump {
+        /// Root CID of the AMT
+        root: Cid,
+        /// Type of the values
+        #[arg(long, value_enum, default_value_t = ValueSchema::Ipld)]
+        value_schema: ValueSchema,
+        #[command(flatten)]
+        source: BlockSource,
+    },
+}
+
+impl AmtCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Dump {
+                root,
+                value_schema,
+                source,
+            } => {
+                let store = source.open(client)?;
+                tokio::task::spawn_blocking(move || {
+                    let amt = Amt::<Ipld, _>::load(&root, &store)?;
+                    amt.for_each(|index, value| {
+                        println!("{index}: {}", value_schema.decode(value)?);
+                        Ok(())
+                    })?;
+                    anyhow::Ok(())
+                })
+                .await?
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod api_compare_tests;
+mod corpus;
 pub(crate) mod generate_test_snapshot;
 mod report;
 mod state_decode_params_tests;
@@ -16,6 +17,7 @@ use crate::lotus_json::HasLotusJson;
 use crate::networks::NetworkChain;
 use crate::rpc::{self, ApiPaths, eth::types::*, prelude::*};
 use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
 use crate::tool::offline_server::start_offline_server;
 use crate::tool::subcommands::api_cmd::stateful_tests::TestTransaction;
 use crate::tool::subcommands::api_cmd::test_snapshot::{Index, Payload};
@@ -95,6 +97,9 @@ pub enum ApiCommands {
     /// If snapshot files are provided,
     /// these files will be used to generate additional tests.
     ///
+    /// If a corpus file is provided, its calls are made instead of the built-in tests,
+    /// and the responses of both nodes must be identical.
+    ///
     /// Example output:
     /// ```markdown
     /// | RPC Method                        | Forest              | Lotus         |
@@ -137,6 +142,15 @@ pub enum ApiCommands {
         #[command(flatten)]
         create_tests_args: CreateTestsArgs,
 
+        /// JSON file of RPC calls, `[{ "method": ..., "params": [...] }, ...]`, to make on both
+        /// nodes instead of the built-in tests. The `$epoch` and `$tipset_key` placeholders in
+        /// the parameters are replaced by the epoch and key of the tipset at `--epoch`.
+        #[arg(long)]
+        corpus: Option<PathBuf>,
+        /// Epoch of the tipset to run the corpus at. Defaults to the head of the Lotus node.
+        #[arg(long, requires = "corpus")]
+        epoch: Option<ChainEpoch>,
+
         /// Specify a directory to which the RPC tests are dumped
         #[arg(long)]
         dump_dir: Option<PathBuf>,
@@ -295,6 +309,8 @@ impl ApiCommands {
                 run_ignored,
                 max_concurrent_requests,
                 create_tests_args,
+                corpus,
+                epoch,
                 dump_dir,
                 test_criteria_overrides,
                 report_dir,
@@ -303,7 +319,12 @@ impl ApiCommands {
             } => {
                 let forest = Arc::new(rpc::Client::from_url(forest));
                 let lotus = Arc::new(rpc::Client::from_url(lotus));
-                let tests = api_compare_tests::create_tests(create_tests_args.clone()).await?;
+                let tests = if let Some(corpus) = corpus {
+                    corpus::create_corpus_tests(corpus::read_corpus(&corpus)?, &lotus, epoch)
+                        .await?
+                } else {
+                    api_compare_tests::create_tests(create_tests_args.clone()).await?
+                };
 
                 api_compare_tests::run_tests(
                     tests,
@@ -590,7 +590,8 @@ fn chain_tests_with_tipset<DB: Blockstore>(
             RpcTest::identity(ChainGetBlock::request((block_cid,))?),
             RpcTest::identity(ChainGetBlockMessages::request((block_cid,))?),
             RpcTest::identity(ChainGetParentMessages::request((block_cid,))?),
-            RpcTest::identity(ChainGetParentReceipts::request((block_cid,))?),
+            // The range parameters of `ChainGetParentReceipts` are not supported in Lotus
+            RpcTest::identity(ChainGetParentReceipts::request((block_cid, None, None))?),
             RpcTest::identity(ChainStatObj::request((block.messages, None))?),
             RpcTest::identity(ChainStatObj::request((
                 block.messages,
@@ -683,6 +684,9 @@ fn net_tests() -> Vec<RpcTest> {
         RpcTest::basic(NetInfo::request(()).unwrap())
             .ignore("Not implemented in Lotus. Why do we even have this method?"),
         RpcTest::basic(NetAutoNatStatus::request(()).unwrap()),
+        RpcTest::basic(NetBandwidthStats::request(()).unwrap()),
+        RpcTest::basic(NetBandwidthStatsByPeer::request(()).unwrap()),
+        RpcTest::basic(NetBandwidthStatsByProtocol::request(()).unwrap()),
         RpcTest::identity(NetVersion::request(()).unwrap()),
         RpcTest::identity(NetProtectAdd::request((vec![PeerId::random().to_string()],)).unwrap()),
         RpcTest::identity(
@@ -928,7 +932,7 @@ fn state_tests_with_tipset<DB: Blockstore>(
             tipset.key().into(),
         ))?),
         RpcTest::identity(StateNetworkVersion::request((tipset.key().into(),))?),
-        RpcTest::identity(StateListMiners::request((tipset.key().into(),))?),
+        RpcTest::identity(StateListMiners::request((tipset.key().into(), None, None))?),
         RpcTest::identity(StateListActors::request((tipset.key().into(),))?),
         RpcTest::identity(MsigGetAvailableBalance::request((
             Address::new_id(18101), // msig address id
@@ -1466,6 +1470,7 @@ fn eth_tests() -> Vec<RpcTest> {
                     (
                         msg.clone(),
                         BlockNumberOrHash::from_predefined(Predefined::Latest),
+                        None,
                     ),
                     use_alias,
                 )
@@ -1479,7 +1484,11 @@ fn eth_tests() -> Vec<RpcTest> {
             ] {
                 tests.push(RpcTest::identity(
                     EthCallV2::request_with_alias(
-                        (msg.clone(), ExtBlockNumberOrHash::PredefinedBlock(tag)),
+                        (
+                            msg.clone(),
+                            ExtBlockNumberOrHash::PredefinedBlock(tag),
+                            None,
+                        ),
                         use_alias,
                     )
                     .unwrap(),
@@ -1573,8 +1582,12 @@ fn eth_call_api_err_tests(epoch: i64) -> Vec<RpcTest> {
             ..EthCallMessage::default()
         };
 
-        let eth_call_request =
-            EthCall::request((msg.clone(), BlockNumberOrHash::from_block_number(epoch))).unwrap();
+        let eth_call_request = EthCall::request((
+            msg.clone(),
+            BlockNumberOrHash::from_block_number(epoch),
+            None,
+        ))
+        .unwrap();
 
         tests.push(
             RpcTest::identity(eth_call_request)
@@ -1582,7 +1595,8 @@ fn eth_call_api_err_tests(epoch: i64) -> Vec<RpcTest> {
         );
 
         let eth_call_v2_request =
-            EthCallV2::request((msg, ExtBlockNumberOrHash::from_block_number(epoch))).unwrap();
+            EthCallV2::request((msg, ExtBlockNumberOrHash::from_block_number(epoch), None))
+                .unwrap();
 
         tests.push(
             RpcTest::identity(eth_call_v2_request)
@@ -0,0 +1,157 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! A corpus of RPC calls for `forest-tool api compare --corpus`. Every call is made with
+//! identical parameters on both nodes, and the responses must be identical.
+//!
+//! The corpus is a JSON array of entries:
+//! ```json
+//! [
+//!   { "method": "Filecoin.ChainGetTipSetByHeight", "params": ["$epoch", null] },
+//!   { "method": "Filecoin.StateListMiners", "params": ["$tipset_key"], "unordered": true },
+//!   { "method": "Filecoin.ChainHead", "api_path": "v0" }
+//! ]
+//! ```
+//! The `$epoch` and `$tipset_key` placeholders are replaced by the epoch and the key of the
+//! tipset the comparison runs at.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_with::{DisplayFromStr, serde_as};
+
+use super::api_compare_tests::{PolicyOnRejected, RpcTest, SortPolicy};
+use crate::blocks::TipsetKey;
+use crate::lotus_json::HasLotusJson as _;
+use crate::rpc::{self, ApiPaths, prelude::*, types::ApiTipsetKey};
+use crate::shim::clock::ChainEpoch;
+
+const EPOCH_PLACEHOLDER: &str = "$epoch";
+const TIPSET_KEY_PLACEHOLDER: &str = "$tipset_key";
+
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CorpusEntry {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    /// API version to call the method on, `v1` when unset.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub api_path: Option<ApiPaths>,
+    /// Compare the responses regardless of the order of arrays and maps.
+    #[serde(default)]
+    pub unordered: bool,
+}
+
+pub fn read_corpus(path: &Path) -> anyhow::Result<Vec<CorpusEntry>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read corpus {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("invalid corpus {}", path.display()))
+}
+
+/// Creates the tests of a corpus at the tipset of `epoch`, or at the head of the `lotus` node.
+pub async fn create_corpus_tests(
+    corpus: Vec<CorpusEntry>,
+    lotus: &rpc::Client,
+    epoch: Option<ChainEpoch>,
+) -> anyhow::Result<Vec<RpcTest>> {
+    let tipset = match epoch {
+        Some(epoch) => ChainGetTipSetByHeight::call(lotus, (epoch, ApiTipsetKey(None)))
+            .await
+            .with_context(|| format!("failed to get the tipset at epoch {epoch}"))?,
+        None => ChainHead::call(lotus, ()).await?,
+    };
+    corpus
+        .into_iter()
+        .map(|entry| corpus_test(entry, tipset.epoch(), tipset.key()))
+        .collect()
+}
+
+fn corpus_test(
+    CorpusEntry {
+        method,
+        mut params,
+        api_path,
+        unordered,
+    }: CorpusEntry,
+    epoch: ChainEpoch,
+    tipset_key: &TipsetKey,
+) -> anyhow::Result<RpcTest> {
+    resolve_placeholders(&mut params, epoch, tipset_key)?;
+    Ok(RpcTest {
+        request: rpc::Request {
+            method_name: method.into(),
+            params,
+            result_type: Default::default(),
+            api_paths: api_path.unwrap_or(ApiPaths::V1).into(),
+            timeout: *rpc::DEFAULT_REQUEST_TIMEOUT,
+        },
+        check_syntax: std::sync::Arc::new(|_| true),
+        check_semantics: std::sync::Arc::new(|forest, lotus| forest == lotus),
+        ignore: None,
+        policy_on_rejected: PolicyOnRejected::PassWithIdenticalError,
+        sort_policy: unordered.then_some(SortPolicy::All),
+    })
+}
+
+fn resolve_placeholders(
+    params: &mut Value,
+    epoch: ChainEpoch,
+    tipset_key: &TipsetKey,
+) -> anyhow::Result<()> {
+    match params {
+        Value::String(s) if s == EPOCH_PLACEHOLDER => *params = epoch.into(),
+        Value::String(s) if s == TIPSET_KEY_PLACEHOLDER => {
+            *params = tipset_key.clone().into_lotus_json_value()?
+        }
+        Value::Array(values) => {
+            for value in values {
+                resolve_placeholders(value, epoch, tipset_key)?;
+            }
+        }
+        Value::Object(values) => {
+            for value in values.values_mut() {
+                resolve_placeholders(value, epoch, tipset_key)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_and_resolve_corpus() {
+        let corpus: Vec<CorpusEntry> = serde_json::from_value(json!([
+            { "method": "Filecoin.ChainGetTipSetByHeight", "params": ["$epoch", "$tipset_key"] },
+            { "method": "Filecoin.ChainHead", "api_path": "v0", "unordered": true },
+        ]))
+        .unwrap();
+        assert_eq!(corpus[1].api_path, Some(ApiPaths::V0));
+        assert!(corpus[1].unordered);
+
+        let tipset_key = TipsetKey::from(nunny::vec![cid::Cid::default()]);
+        let test = corpus_test(corpus[0].clone(), 42, &tipset_key).unwrap();
+        assert_eq!(
+            test.request.params,
+            json!([42, tipset_key.clone().into_lotus_json_value().unwrap()])
+        );
+        assert_eq!(test.request.api_path().unwrap(), ApiPaths::V1);
+        assert!(test.sort_policy.is_none());
+
+        assert!(
+            serde_json::from_value::<Vec<CorpusEntry>>(
+                json!([{ "method": "Filecoin.ChainHead", "param": [] }])
+            )
+            .is_err()
+        );
+    }
+}
@@ -271,6 +271,10 @@ impl<T: SettingsStore> SettingsStore for ReadOpsTrackingStore<T> {
         Ok(result.is_some())
     }
 
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.inner.delete(key)
+    }
+
     fn setting_keys(&self) -> anyhow::Result<Vec<String>> {
         self.inner.setting_keys()
     }
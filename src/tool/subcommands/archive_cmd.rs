@@ -703,7 +703,7 @@ async fn merge_f3_snapshot(filecoin: PathBuf, f3: PathBuf, output: PathBuf) -> a
             crate::db::car::forest::new_encoder(DEFAULT_FOREST_CAR_COMPRESSION_LEVEL)?;
         snap_meta_block.write(&mut encoder)?;
         anyhow::Ok((
-            vec![snap_meta_block.cid],
+            vec![(snap_meta_block.cid, 0)],
             crate::db::car::forest::finalize_frame(
                 DEFAULT_FOREST_CAR_COMPRESSION_LEVEL,
                 &mut encoder,
@@ -717,7 +717,7 @@ async fn merge_f3_snapshot(filecoin: PathBuf, f3: PathBuf, output: PathBuf) -> a
         f3_data.seek(SeekFrom::Start(0))?;
         encoder.write_car_block(f3_cid, f3_data_len, &mut f3_data)?;
         anyhow::Ok((
-            vec![f3_cid],
+            vec![(f3_cid, 0)],
             crate::db::car::forest::finalize_frame(
                 DEFAULT_FOREST_CAR_COMPRESSION_LEVEL,
                 &mut encoder,
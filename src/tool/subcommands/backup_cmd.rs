@@ -1,18 +1,31 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use anyhow::bail;
+use anyhow::{Context as _, bail};
 use clap::Subcommand;
 use std::{
     fs::File,
+    io::{Cursor, Read, Write},
     path::{Path, PathBuf},
 };
 
-use crate::{cli_shared::read_config, networks::NetworkChain};
+use crate::{
+    cli_shared::{cli::project_config_path, read_config},
+    key_management::{decrypt_with_passphrase, encrypt_with_passphrase},
+    networks::NetworkChain,
+};
+
+/// Environment variable holding the passphrase of encrypted backups.
+const FOREST_BACKUP_PASSPHRASE_ENV: &str = "FOREST_BACKUP_PASSPHRASE";
+/// Header of encrypted backups, followed by the archive encrypted like the encrypted keystore.
+const ENCRYPTED_BACKUP_HEADER: &[u8] = b"forest-encrypted-backup\n";
+/// Path of the daemon configuration in the backup archives, outside of the data directory.
+const CONFIG_ENTRY: &str = ".forest-backup/config.toml";
 
 #[derive(Subcommand)]
 pub enum BackupCommands {
-    /// Create a backup of the node. By default, only the peer-to-peer key-pair and key-store are backed up.
+    /// Create a backup of the node. By default, only the peer-to-peer key-pair, the key-store,
+    /// which holds the JWT secret, and the configuration file are backed up.
     /// The node must be offline.
     Create {
         /// Path to the output backup file if not using the default
@@ -27,12 +40,20 @@ pub enum BackupCommands {
         /// Disables backing up the key-store
         #[arg(long)]
         no_keystore: bool,
+        /// Disables backing up the configuration file
+        #[arg(long)]
+        no_config: bool,
         /// Backs up the blockstore for the specified chain. If not provided, it will not be backed up.
         #[arg(long)]
         backup_chain: Option<NetworkChain>,
         /// Include proof parameters in the backup
         #[arg(long)]
         include_proof_params: bool,
+        /// Encrypt the backup with a passphrase, read from `FOREST_BACKUP_PASSPHRASE` or prompted.
+        /// Encrypted backups are built in memory, so they can't include the chain or the proof
+        /// parameters.
+        #[arg(long, conflicts_with_all = ["all", "backup_chain", "include_proof_params"])]
+        encrypt: bool,
         /// Optional TOML file containing forest daemon configuration. If not provided, the default configuration will be used.
         #[arg(short, long)]
         daemon_config: Option<PathBuf>,
@@ -42,8 +63,12 @@ pub enum BackupCommands {
         /// Path to the backup file
         backup_file: PathBuf,
         /// Optional TOML file containing forest daemon configuration. If not provided, the default configuration will be used.
+        /// The configuration file of the backup is restored to this path.
         #[arg(short, long)]
         daemon_config: Option<PathBuf>,
+        /// Disables restoring the configuration file
+        #[arg(long)]
+        no_config: bool,
         /// Force restore even if files already exist
         /// WARNING: This will overwrite existing files.
         #[arg(long)]
@@ -59,11 +84,14 @@ impl BackupCommands {
                 all,
                 no_keypair,
                 no_keystore,
+                no_config,
                 backup_chain,
                 include_proof_params,
+                encrypt,
                 daemon_config,
             } => {
-                let (_, config) = read_config(daemon_config.as_ref(), backup_chain.clone())?;
+                let (config_path, config) =
+                    read_config(daemon_config.as_ref(), backup_chain.clone())?;
 
                 let data_dir = &config.client.data_dir;
 
@@ -81,13 +109,17 @@ impl BackupCommands {
                         include_proof_params,
                     )?
                 };
+                let config_path = config_path
+                    .filter(|_| !no_config)
+                    .map(|path| path.to_path_buf().clone());
 
                 let backup_file_path = if let Some(backup_file) = backup_file {
                     backup_file
                 } else {
                     let path = PathBuf::from(format!(
-                        "forest-backup-{}.tar",
-                        chrono::Utc::now().format("%Y-%m-%d_%H-%M-%S")
+                        "forest-backup-{}.tar{}",
+                        chrono::Utc::now().format("%Y-%m-%d_%H-%M-%S"),
+                        if encrypt { ".enc" } else { "" }
                     ));
                     if path.exists() {
                         bail!("Backup file already exists at {}", path.display());
@@ -95,7 +127,26 @@ impl BackupCommands {
                     path
                 };
 
-                archive_entries(data_dir, backup_entries, &backup_file_path)?;
+                if encrypt {
+                    let passphrase = read_passphrase(true)?;
+                    let mut archive = vec![];
+                    archive_entries(
+                        data_dir,
+                        backup_entries,
+                        config_path.as_deref(),
+                        &mut archive,
+                    )?;
+                    let mut encrypted = ENCRYPTED_BACKUP_HEADER.to_vec();
+                    encrypted.extend(encrypt_with_passphrase(&passphrase, &archive)?);
+                    std::fs::write(&backup_file_path, encrypted)?;
+                } else {
+                    archive_entries(
+                        data_dir,
+                        backup_entries,
+                        config_path.as_deref(),
+                        File::create(&backup_file_path)?,
+                    )?;
+                }
                 println!("Backup complete: {}", backup_file_path.display());
 
                 Ok(())
@@ -103,12 +154,20 @@ impl BackupCommands {
             BackupCommands::Restore {
                 backup_file,
                 daemon_config,
+                no_config,
                 force,
             } => {
-                let (_, config) = read_config(daemon_config.as_ref(), None)?;
+                let backup = Backup::open(&backup_file)?;
+                // The configuration is restored first, as it sets the data directory.
+                let restored_config = if no_config {
+                    None
+                } else {
+                    restore_config(&backup, daemon_config.clone(), force)?
+                };
+                let (_, config) = read_config(restored_config.or(daemon_config).as_ref(), None)?;
                 let data_dir = &config.client.data_dir;
 
-                extract_entries(data_dir, &backup_file, force)?;
+                extract_entries(data_dir, &backup, force)?;
                 println!("Restore complete");
 
                 Ok(())
@@ -117,12 +176,101 @@ impl BackupCommands {
     }
 }
 
-fn extract_entries(data_dir: &Path, backup_file: &Path, force: bool) -> anyhow::Result<()> {
-    let backup_file = File::open(backup_file)?;
-    let mut archive = tar::Archive::new(backup_file);
+/// Reads the passphrase of encrypted backups from the environment, or prompts for it.
+fn read_passphrase(confirm: bool) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var(FOREST_BACKUP_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let term = dialoguer::console::Term::stderr();
+    // `dialoguer::Password` doesn't fail if the terminal is not a tty.
+    anyhow::ensure!(
+        term.is_term(),
+        "cannot read the backup passphrase from a non-terminal, set {FOREST_BACKUP_PASSPHRASE_ENV}"
+    );
+    let mut prompt = dialoguer::Password::new()
+        .with_prompt("Enter the passphrase of the backup")
+        .allow_empty_password(false);
+    if confirm {
+        prompt = prompt.with_confirmation(
+            "Confirm passphrase",
+            "Error: the passphrases do not match. Try again or press Ctrl+C to abort.",
+        );
+    }
+    Ok(prompt.interact_on(&term)?)
+}
+
+/// A backup archive, decrypted in memory if it's encrypted.
+enum Backup {
+    Plain(PathBuf),
+    Decrypted(Vec<u8>),
+}
+
+impl Backup {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut header = vec![0; ENCRYPTED_BACKUP_HEADER.len()];
+        let mut file = File::open(path)?;
+        let is_encrypted =
+            file.read_exact(&mut header).is_ok() && header == ENCRYPTED_BACKUP_HEADER;
+        if !is_encrypted {
+            return Ok(Self::Plain(path.to_path_buf()));
+        }
+        let mut encrypted = vec![];
+        file.read_to_end(&mut encrypted)?;
+        let archive = decrypt_with_passphrase(&read_passphrase(false)?, &encrypted)
+            .context("failed to decrypt the backup, is the passphrase correct?")?;
+        Ok(Self::Decrypted(archive))
+    }
+
+    fn archive(&self) -> anyhow::Result<tar::Archive<Box<dyn Read + '_>>> {
+        let reader: Box<dyn Read + '_> = match self {
+            Self::Plain(path) => Box::new(File::open(path)?),
+            Self::Decrypted(archive) => Box::new(Cursor::new(archive)),
+        };
+        Ok(tar::Archive::new(reader))
+    }
+}
+
+/// Restores the configuration file of the backup, if it has one, to the given path, or to the
+/// default path. Returns the path of the restored configuration file.
+fn restore_config(
+    backup: &Backup,
+    daemon_config: Option<PathBuf>,
+    force: bool,
+) -> anyhow::Result<Option<PathBuf>> {
+    let mut archive = backup.archive()?;
+    for file in archive.entries()? {
+        let mut file = file?;
+        if file.path()? != Path::new(CONFIG_ENTRY) {
+            continue;
+        }
+        let path = daemon_config
+            .or_else(|| std::env::var("FOREST_CONFIG_PATH").ok().map(PathBuf::from))
+            .or_else(project_config_path)
+            .context("no path to restore the configuration file to, use --daemon-config")?;
+        if path.exists() && !force {
+            bail!(
+                "File already exists at {}. Use --force to overwrite.",
+                path.display()
+            );
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        println!("Restoring {}", path.display());
+        file.unpack(&path)?;
+        return Ok(Some(path));
+    }
+    Ok(None)
+}
+
+fn extract_entries(data_dir: &Path, backup: &Backup, force: bool) -> anyhow::Result<()> {
+    let mut archive = backup.archive()?;
     for file in archive.entries()? {
         let mut file = file?;
         let path = file.path()?;
+        if path == Path::new(CONFIG_ENTRY) {
+            continue;
+        }
         let path = data_dir.join(path);
         if path.exists() && !force {
             bail!(
@@ -143,10 +291,15 @@ fn extract_entries(data_dir: &Path, backup_file: &Path, force: bool) -> anyhow::
 fn archive_entries(
     data_dir: &PathBuf,
     backup_entries: Vec<PathBuf>,
-    backup_file_path: &Path,
+    config_path: Option<&Path>,
+    writer: impl Write,
 ) -> anyhow::Result<()> {
-    let backup_file = File::create(backup_file_path)?;
-    let mut archive = tar::Builder::new(backup_file);
+    let mut archive = tar::Builder::new(writer);
+    // The configuration goes first, as it's restored first.
+    if let Some(config_path) = config_path {
+        println!("Adding {} to backup", config_path.display());
+        archive.append_path_with_name(config_path, CONFIG_ENTRY)?;
+    }
     for entry in backup_entries {
         let entry_canonicalized = entry.canonicalize()?;
         let name = entry.strip_prefix(data_dir)?;
@@ -234,6 +387,7 @@ fn validate_and_add_entries(
 #[cfg(test)]
 mod test {
     use itertools::Itertools;
+    use serial_test::serial;
     use tempfile::TempDir;
     use walkdir::WalkDir;
 
@@ -410,10 +564,11 @@ mod test {
         let data_dir = temp_dir.path().to_path_buf();
 
         let backup_file = tempfile::Builder::new().suffix(".tar").tempfile().unwrap();
-        archive_entries(&data_dir, entries.clone(), backup_file.path()).unwrap();
+        archive_entries(&data_dir, entries.clone(), None, backup_file.as_file()).unwrap();
 
         let restore_dir = tempfile::tempdir().unwrap();
-        extract_entries(restore_dir.path(), backup_file.path(), true).unwrap();
+        let backup = Backup::open(backup_file.path()).unwrap();
+        extract_entries(restore_dir.path(), &backup, true).unwrap();
 
         // get all entries recursively
         let get_entries_recurse = |dir| {
@@ -430,4 +585,56 @@ mod test {
         assert!(restored.len() > entries.len());
         itertools::assert_equal(original.iter(), restored.iter());
     }
+
+    #[test]
+    fn config_roundtrip() {
+        let (temp_dir, entries) = create_test_data();
+        let data_dir = temp_dir.path().to_path_buf();
+        let config_dir = tempfile::tempdir().unwrap();
+        let config_path = config_dir.path().join("config.toml");
+        std::fs::write(&config_path, "[client]\n").unwrap();
+
+        let mut archive = vec![];
+        archive_entries(&data_dir, entries, Some(&config_path), &mut archive).unwrap();
+        let backup = Backup::Decrypted(archive);
+
+        let restored_path = config_dir.path().join("restored.toml");
+        assert_eq!(
+            restore_config(&backup, Some(restored_path.clone()), false).unwrap(),
+            Some(restored_path.clone())
+        );
+        assert_eq!(
+            std::fs::read_to_string(&restored_path).unwrap(),
+            "[client]\n"
+        );
+        // The configuration is not overwritten without `--force`.
+        assert!(restore_config(&backup, Some(restored_path.clone()), false).is_err());
+
+        // The configuration is not restored to the data directory.
+        let restore_dir = tempfile::tempdir().unwrap();
+        extract_entries(restore_dir.path(), &backup, false).unwrap();
+        assert!(!restore_dir.path().join(CONFIG_ENTRY).exists());
+    }
+
+    #[test]
+    #[serial]
+    fn encrypted_backup_roundtrip() {
+        let (temp_dir, entries) = create_test_data();
+        let data_dir = temp_dir.path().to_path_buf();
+
+        let mut archive = vec![];
+        archive_entries(&data_dir, entries, None, &mut archive).unwrap();
+        let mut encrypted = ENCRYPTED_BACKUP_HEADER.to_vec();
+        encrypted.extend(encrypt_with_passphrase("passphrase", &archive).unwrap());
+        let backup_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(backup_file.path(), &encrypted).unwrap();
+
+        unsafe { std::env::set_var(FOREST_BACKUP_PASSPHRASE_ENV, "passphrase") };
+        let backup = Backup::open(backup_file.path());
+        unsafe { std::env::remove_var(FOREST_BACKUP_PASSPHRASE_ENV) };
+        let Backup::Decrypted(decrypted) = backup.unwrap() else {
+            panic!("the backup should be decrypted");
+        };
+        assert_eq!(decrypted, archive);
+    }
 }
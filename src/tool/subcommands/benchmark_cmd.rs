@@ -1,14 +1,28 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use crate::Config;
+use crate::beacon::BeaconSchedule;
 use crate::blocks::{Tipset, TipsetKey};
-use crate::db::car::ManyCar;
+use crate::daemon::bundle::load_actor_bundles;
 use crate::db::car::forest::DEFAULT_FOREST_CAR_FRAME_SIZE;
+use crate::db::car::{AnyCar, ManyCar, RandomAccessFileReader};
+use crate::interpreter::VMTrace;
 use crate::ipld::{stream_chain, stream_graph};
+use crate::message_pool::testing::{Corpus, CorpusConfig, Simulation};
+use crate::networks::{ChainConfig, NetworkChain};
+use crate::shim::address::CurrentNetwork;
 use crate::shim::clock::ChainEpoch;
+use crate::shim::executor::Receipt;
+use crate::shim::fvm_shared_latest::address::Network;
+use crate::shim::machine::GLOBAL_MULTI_ENGINE;
+use crate::shim::state_tree::StateTree;
+use crate::state_manager::{NO_CALLBACK, StateOutput, apply_block_messages};
 use crate::utils::db::car_stream::{CarBlock, CarStream};
 use crate::utils::encoding::extract_cids;
+use crate::utils::io::Mmap;
 use crate::utils::multihash::MultihashCode;
+use crate::utils::proofs_api::ensure_proof_params_downloaded;
 use crate::utils::stream::par_buffer;
 use crate::{
     chain::{
@@ -25,9 +39,11 @@ use fvm_ipld_encoding::DAG_CBOR;
 use human_repr::HumanCount as _;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
+use positioned_io::RandomAccessFile;
+use rand::seq::SliceRandom as _;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::{
     fs::File,
     io::{AsyncWrite, AsyncWriteExt, BufReader},
@@ -50,6 +66,15 @@ pub enum BenchmarkCommands {
         #[arg(long)]
         inspect: bool,
     },
+    /// Random block reads from a CAR archive, with file I/O, with a memory map, and with the
+    /// zero-copy read path of the memory map (`.forest.car.zst` only)
+    CarReads {
+        /// Snapshot input file (`.car.`, `.forest.car.zst`)
+        snapshot_file: PathBuf,
+        /// Number of blocks to read
+        #[arg(long, default_value_t = 100_000)]
+        blocks: usize,
+    },
     /// Depth-first traversal of the Filecoin graph
     GraphTraversal {
         /// Snapshot input files (`.car.`, `.car.zst`, `.forest.car.zst`)
@@ -92,6 +117,57 @@ pub enum BenchmarkCommands {
         #[arg(long, default_value = "parity")]
         db: DbType,
     },
+    /// Re-execute the tipsets of an epoch range, checking the computed state roots against the
+    /// headers and reporting per-epoch execution timings
+    ReplayRange {
+        /// Snapshot input files (`.car.`, `.car.zst`, `.forest.car.zst`)
+        #[arg(required = true)]
+        snapshot_files: Vec<PathBuf>,
+        /// First epoch to re-execute
+        #[arg(long)]
+        from: ChainEpoch,
+        /// Last epoch to re-execute. Its child tipset must be in the snapshot.
+        #[arg(long)]
+        to: ChainEpoch,
+    },
+    /// Select the messages of blocks from synthetic message pools, checking the invariants of the
+    /// selection and reporting the selection timings
+    MpoolSelect {
+        /// Number of sending actors
+        #[arg(long, default_value_t = 1000)]
+        actors: usize,
+        /// Maximum number of pending messages per actor
+        #[arg(long, default_value_t = 50)]
+        messages_per_actor: u64,
+        /// Ticket quality of the miner, the greedy selection is used above 0.84
+        #[arg(long, default_value_t = 0.9)]
+        ticket_quality: f64,
+        /// Number of message pools to generate
+        #[arg(long, default_value_t = 10)]
+        rounds: u64,
+        /// Seed of the first message pool
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Execute the last tipsets of a snapshot, reporting the message and gas throughput
+    ExecuteTipsets {
+        /// Snapshot input files (`.car.`, `.car.zst`, `.forest.car.zst`)
+        #[arg(required = true)]
+        snapshot_files: Vec<PathBuf>,
+        /// Number of epochs to execute, before the head of the snapshot
+        #[arg(long, default_value_t = 10)]
+        epochs: ChainEpochDelta,
+    },
+    /// Traverse the state tree at the head of a snapshot and look up random actors in it,
+    /// reporting the reads per second
+    StateRead {
+        /// Snapshot input files (`.car.`, `.car.zst`, `.forest.car.zst`)
+        #[arg(required = true)]
+        snapshot_files: Vec<PathBuf>,
+        /// Number of random actor lookups
+        #[arg(long, default_value_t = 100_000)]
+        lookups: usize,
+    },
 }
 
 impl BenchmarkCommands {
@@ -104,6 +180,10 @@ impl BenchmarkCommands {
                 true => benchmark_car_streaming_inspect(snapshot_files).await,
                 false => benchmark_car_streaming(snapshot_files).await,
             },
+            Self::CarReads {
+                snapshot_file,
+                blocks,
+            } => benchmark_car_reads(snapshot_file, blocks).await,
             Self::GraphTraversal { snapshot_files } => {
                 benchmark_graph_traversal(snapshot_files).await
             }
@@ -123,6 +203,26 @@ impl BenchmarkCommands {
                     .await
             }
             Self::Blockstore { snapshot_file, db } => benchmark_blockstore(snapshot_file, db).await,
+            Self::ReplayRange {
+                snapshot_files,
+                from,
+                to,
+            } => benchmark_replay_range(snapshot_files, from, to).await,
+            Self::MpoolSelect {
+                actors,
+                messages_per_actor,
+                ticket_quality,
+                rounds,
+                seed,
+            } => benchmark_mpool_select(actors, messages_per_actor, ticket_quality, rounds, seed),
+            Self::ExecuteTipsets {
+                snapshot_files,
+                epochs,
+            } => benchmark_execute_tipsets(snapshot_files, epochs).await,
+            Self::StateRead {
+                snapshot_files,
+                lookups,
+            } => benchmark_state_read(snapshot_files, lookups).await,
         }
     }
 }
@@ -168,6 +268,54 @@ async fn benchmark_car_streaming_inspect(input: Vec<PathBuf>) -> anyhow::Result<
     Ok(())
 }
 
+// Read the same blocks of a CAR file, in random order, through each read path and measure how
+// long it takes.
+async fn benchmark_car_reads(snapshot: PathBuf, n_blocks: usize) -> anyhow::Result<()> {
+    let mut cids: Vec<Cid> = CarStream::new_from_path(&snapshot)
+        .await?
+        .map_ok(|block| block.cid)
+        .take(n_blocks)
+        .try_collect()
+        .await?;
+    cids.shuffle(&mut crate::utils::rand::forest_rng());
+
+    time_car_reads("file-io", RandomAccessFile::open(&snapshot)?, &cids)?;
+    time_car_reads(
+        "mmap",
+        Mmap::map_path(&snapshot)?.with_zero_copy(false),
+        &cids,
+    )?;
+    time_car_reads(
+        "mmap-zero-copy",
+        Mmap::map_path(&snapshot)?.with_zero_copy(true),
+        &cids,
+    )?;
+    Ok(())
+}
+
+fn time_car_reads(
+    read_path: &str,
+    reader: impl RandomAccessFileReader,
+    cids: &[Cid],
+) -> anyhow::Result<()> {
+    let car = AnyCar::new(reader)?;
+    let start = Instant::now();
+    let mut bytes = 0;
+    for cid in cids {
+        bytes += car
+            .get(cid)?
+            .with_context(|| format!("block {cid} is missing"))?
+            .len();
+    }
+    println!(
+        "{read_path}: read {} blocks ({}), took {}",
+        cids.len(),
+        bytes.human_count_bytes(),
+        humantime::format_duration(start.elapsed())
+    );
+    Ok(())
+}
+
 // Open a set of CAR files as a block store and do a DFS traversal of all
 // reachable nodes.
 async fn benchmark_graph_traversal(input: Vec<PathBuf>) -> anyhow::Result<()> {
@@ -312,6 +460,295 @@ async fn benchmark_blockstore_traversal(
     Ok(())
 }
 
+// Block store of the snapshots, with everything needed to execute their tipsets.
+struct Executor {
+    store: Arc<ManyCar>,
+    heaviest: Tipset,
+    genesis_timestamp: u64,
+    chain_index: Arc<ChainIndex<Arc<ManyCar>>>,
+    chain_config: Arc<ChainConfig>,
+    beacon: Arc<BeaconSchedule>,
+}
+
+impl Executor {
+    async fn open(input: Vec<PathBuf>) -> anyhow::Result<Self> {
+        let store = Arc::new(open_store(input)?);
+        let heaviest = store.heaviest_tipset()?;
+        let genesis = heaviest.genesis(&store)?;
+        let network = NetworkChain::from_genesis_or_devnet_placeholder(genesis.cid());
+        let chain_config = Arc::new(ChainConfig::from_chain(&network));
+        if chain_config.is_testnet() {
+            CurrentNetwork::set_global(Network::Testnet);
+        }
+        // Bundles are required when doing state migrations.
+        load_actor_bundles(&store, &network).await?;
+        crate::utils::proofs_api::maybe_set_proofs_parameter_cache_dir_env(
+            &Config::default().client.data_dir,
+        );
+        ensure_proof_params_downloaded().await?;
+
+        let chain_index = Arc::new(ChainIndex::new(store.clone()));
+        let beacon = Arc::new(chain_config.get_beacon_schedule(genesis.timestamp));
+        Ok(Self {
+            store,
+            heaviest,
+            genesis_timestamp: genesis.timestamp,
+            chain_index,
+            chain_config,
+            beacon,
+        })
+    }
+
+    /// Returns the tipsets in `from..=to` in order, paired with their children, which hold the
+    /// roots to check their execution against.
+    fn tipsets(&self, from: ChainEpoch, to: ChainEpoch) -> anyhow::Result<Vec<(Tipset, Tipset)>> {
+        anyhow::ensure!(
+            to < self.heaviest.epoch(),
+            "--to {to} must be before the head of the snapshot at epoch {}",
+            self.heaviest.epoch()
+        );
+        let end = self
+            .chain_index
+            .tipset_by_height(to + 1, self.heaviest.clone(), ResolveNullTipset::TakeNewer)
+            .with_context(|| format!("couldn't get a tipset after height {to}"))?;
+        let mut tipsets = self
+            .chain_index
+            .chain(end)
+            .take_while(|tipset| tipset.epoch() >= from)
+            .tuple_windows()
+            .collect_vec();
+        tipsets.reverse();
+        Ok(tipsets)
+    }
+
+    fn execute(&self, tipset: Tipset) -> anyhow::Result<StateOutput> {
+        let epoch = tipset.epoch();
+        apply_block_messages(
+            self.genesis_timestamp,
+            self.chain_index.clone(),
+            self.chain_config.clone(),
+            self.beacon.clone(),
+            &GLOBAL_MULTI_ENGINE,
+            tipset,
+            NO_CALLBACK,
+            VMTrace::NotTraced,
+        )
+        .with_context(|| format!("couldn't compute the state of epoch {epoch}"))
+    }
+}
+
+// Re-execute the tipsets in `from..=to` in order, and compare the state and receipt roots with
+// the ones in the headers of their children. Unlike `snapshot validate`, tipsets are executed
+// sequentially so that the timings are not skewed by concurrent executions, and divergences are
+// reported without stopping the replay.
+async fn benchmark_replay_range(
+    input: Vec<PathBuf>,
+    from: ChainEpoch,
+    to: ChainEpoch,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(from <= to, "--from {from} is after --to {to}");
+    let executor = Executor::open(input).await?;
+    let tipsets = executor.tipsets(from, to)?;
+
+    let mut timings = vec![];
+    let mut divergences = vec![];
+    for (child, parent) in tipsets {
+        let epoch = parent.epoch();
+        let start = Instant::now();
+        let StateOutput {
+            state_root,
+            receipt_root,
+            ..
+        } = executor.execute(parent)?;
+        let elapsed = start.elapsed();
+        timings.push((epoch, elapsed));
+
+        let expected_state_root = *child.parent_state();
+        let expected_receipt_root = child.min_ticket_block().message_receipts;
+        if (state_root, receipt_root) == (expected_state_root, expected_receipt_root) {
+            println!("epoch {epoch}: {}", humantime::format_duration(elapsed));
+        } else {
+            println!(
+                "epoch {epoch}: {} DIVERGED, state root {state_root} (expected {expected_state_root}), receipt root {receipt_root} (expected {expected_receipt_root})",
+                humantime::format_duration(elapsed)
+            );
+            divergences.push(epoch);
+        }
+    }
+
+    print_timing_stats(&timings);
+    anyhow::ensure!(
+        divergences.is_empty(),
+        "{} diverging epoch(s): {}",
+        divergences.len(),
+        divergences.iter().join(", ")
+    );
+    Ok(())
+}
+
+// Execute the last `epochs` tipsets before the head of the snapshot, and measure how many
+// messages and how much gas are executed per second.
+async fn benchmark_execute_tipsets(
+    input: Vec<PathBuf>,
+    epochs: ChainEpochDelta,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(epochs > 0, "--epochs must be positive");
+    let executor = Executor::open(input).await?;
+    let to = executor.heaviest.epoch() - 1;
+    let tipsets = executor.tipsets(to - epochs + 1, to)?;
+
+    let mut timings = vec![];
+    let (mut total_messages, mut total_gas) = (0, 0);
+    for (_, parent) in tipsets {
+        let epoch = parent.epoch();
+        let start = Instant::now();
+        let StateOutput { receipt_root, .. } = executor.execute(parent)?;
+        let elapsed = start.elapsed();
+        timings.push((epoch, elapsed));
+
+        let receipts = Receipt::get_receipts(&executor.store, receipt_root)?;
+        let gas: u64 = receipts.iter().map(Receipt::gas_used).sum();
+        println!(
+            "epoch {epoch}: {} messages, {gas} gas in {}",
+            receipts.len(),
+            humantime::format_duration(elapsed)
+        );
+        total_messages += receipts.len();
+        total_gas += gas;
+    }
+
+    print_timing_stats(&timings);
+    let total = timings
+        .iter()
+        .map(|(_, elapsed)| *elapsed)
+        .sum::<Duration>()
+        .as_secs_f64();
+    if total > 0.0 {
+        println!(
+            "throughput: {:.0} msg/s, {:.0} gas/s",
+            total_messages as f64 / total,
+            total_gas as f64 / total
+        );
+    }
+    Ok(())
+}
+
+// Traverse the state tree at the head of the snapshot, then look up random actors in it, and
+// measure how many reads are done per second.
+async fn benchmark_state_read(input: Vec<PathBuf>, lookups: usize) -> anyhow::Result<()> {
+    let store = Arc::new(open_store(input)?);
+    let heaviest = store.heaviest_tipset()?;
+    let state_root = *heaviest.parent_state();
+
+    let state_tree = StateTree::new_from_root(store.clone(), &state_root)?;
+    let mut addresses = vec![];
+    let start = Instant::now();
+    state_tree.for_each(|address, _| {
+        addresses.push(address);
+        Ok(())
+    })?;
+    let elapsed = start.elapsed();
+    println!(
+        "traversed {} actors in {} ({:.0} actors/s)",
+        addresses.len(),
+        humantime::format_duration(elapsed),
+        addresses.len() as f64 / elapsed.as_secs_f64()
+    );
+    anyhow::ensure!(!addresses.is_empty(), "the state tree has no actors");
+
+    // A fresh state tree, so that the lookups don't hit the actors cached by the traversal.
+    let state_tree = StateTree::new_from_root(store.clone(), &state_root)?;
+    let mut rng = crate::utils::rand::forest_rng();
+    let sample = (0..lookups)
+        .map(|_| {
+            addresses
+                .choose(&mut rng)
+                .copied()
+                .expect("addresses is not empty")
+        })
+        .collect_vec();
+    let start = Instant::now();
+    for address in &sample {
+        state_tree
+            .get_actor(address)?
+            .with_context(|| format!("actor {address} not found"))?;
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "looked up {lookups} random actors in {} ({:.0} lookups/s)",
+        humantime::format_duration(elapsed),
+        lookups as f64 / elapsed.as_secs_f64()
+    );
+    Ok(())
+}
+
+// Select messages from synthetic message pools and measure how long the selection takes.
+fn benchmark_mpool_select(
+    actors: usize,
+    messages_per_actor: u64,
+    ticket_quality: f64,
+    rounds: u64,
+    seed: u64,
+) -> anyhow::Result<()> {
+    let mut services = tokio::task::JoinSet::new();
+    let mut timings = vec![];
+    for seed in seed..seed + rounds {
+        let corpus = Corpus::generate(&CorpusConfig {
+            actors,
+            max_messages_per_actor: messages_per_actor,
+            seed,
+            ..Default::default()
+        });
+        let simulation = Simulation::new(corpus, &mut services)?;
+        let start = Instant::now();
+        let selected = simulation.select(ticket_quality)?;
+        let elapsed = start.elapsed();
+        anyhow::ensure!(
+            simulation.select(ticket_quality)? == selected,
+            "selection of message pool {seed} is not deterministic"
+        );
+        let stats = simulation
+            .corpus
+            .check(&selected)
+            .with_context(|| format!("invalid selection of message pool {seed}"))?;
+        println!(
+            "pool {seed}: selected {} of {} messages, {} gas, revenue {} in {}",
+            stats.messages,
+            simulation.corpus.messages.len(),
+            stats.gas_limit,
+            stats.revenue,
+            humantime::format_duration(elapsed)
+        );
+        // Timings are reported per pool, reusing the per-epoch statistics.
+        timings.push((seed as ChainEpoch, elapsed));
+    }
+    print_timing_stats(&timings);
+    Ok(())
+}
+
+fn print_timing_stats(timings: &[(ChainEpoch, Duration)]) {
+    let Some((slowest_epoch, slowest)) = timings.iter().max_by_key(|(_, elapsed)| *elapsed) else {
+        println!("no tipsets executed");
+        return;
+    };
+    let mut sorted = timings.iter().map(|(_, elapsed)| *elapsed).collect_vec();
+    sorted.sort();
+    let percentile = |p: usize| {
+        sorted
+            .get((sorted.len() - 1) * p / 100)
+            .copied()
+            .unwrap_or_default()
+    };
+    let total: Duration = sorted.iter().sum();
+    let format =
+        |d: Duration| humantime::format_duration(Duration::from_millis(d.as_millis() as u64));
+    println!("executed {} tipsets in {}", sorted.len(), format(total));
+    println!("mean:   {}", format(total / sorted.len() as u32));
+    println!("median: {}", format(percentile(50)));
+    println!("p95:    {}", format(percentile(95)));
+    println!("max:    {} (epoch {slowest_epoch})", format(*slowest));
+}
+
 // Sink with attached progress indicator
 fn indicatif_sink(task: &'static str) -> impl AsyncWrite {
     let sink = tokio::io::sink();
@@ -332,13 +769,26 @@ fn indicatif_sink(task: &'static str) -> impl AsyncWrite {
 // CAR.zst files have to be decompressed). Show a progress indicator and clear
 // it when done.
 fn open_store(input: Vec<PathBuf>) -> anyhow::Result<ManyCar> {
-    let pb = indicatif::ProgressBar::new_spinner().with_style(
-        indicatif::ProgressStyle::with_template("{spinner} opening block store")
-            .expect("indicatif template must be valid"),
+    let total = input
+        .iter()
+        .map(|file| {
+            std::fs::metadata(file)
+                .map(|it| it.len())
+                .unwrap_or_default()
+        })
+        .sum();
+    let pb = indicatif::ProgressBar::new(total).with_style(
+        indicatif::ProgressStyle::with_template(
+            "{spinner} opening block store {bytes}/{total_bytes} ({binary_bytes_per_sec}, {eta})",
+        )
+        .expect("indicatif template must be valid"),
     );
     pb.enable_steady_tick(std::time::Duration::from_secs_f32(0.1));
 
-    let store = ManyCar::try_from(input).context("couldn't read input CAR file")?;
+    let store = ManyCar::default();
+    store
+        .read_only_files_with_progress(input.into_iter(), |n| pb.inc(n))
+        .context("couldn't read input CAR file")?;
 
     pb.finish_and_clear();
 
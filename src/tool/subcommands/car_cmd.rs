@@ -203,7 +203,7 @@ mod tests {
             |(cids, bytes)| {
                 (
                     cids.into_iter()
-                        .filter(|cid| !ignored_cids.contains(cid))
+                        .filter(|(cid, _)| !ignored_cids.contains(cid))
                         .collect(),
                     bytes,
                 )
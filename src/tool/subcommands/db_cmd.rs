@@ -5,8 +5,8 @@ use std::path::PathBuf;
 
 use crate::cli::subcommands::prompt_confirm;
 use crate::cli_shared::{chain_path, read_config};
-use crate::db::BlockstoreWithWriteBuffer;
 use crate::db::db_engine::{db_root, open_db};
+use crate::db::parity_db::ParityDb;
 use crate::networks::NetworkChain;
 use crate::utils::db::car_stream::CarStream;
 use clap::Subcommand;
@@ -53,6 +53,29 @@ pub enum DBCommands {
         #[arg(long)]
         skip_validation: bool,
     },
+    /// Compact the database, rewriting its columns and rebuilding their indexes. The node must be
+    /// stopped.
+    Compact {
+        /// Optional TOML file containing forest daemon configuration
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Optional chain, will override the chain section of configuration file if used
+        #[arg(long)]
+        chain: Option<NetworkChain>,
+    },
+    /// Check the integrity of the database, rehashing its blocks against their CIDs and reporting
+    /// the orphaned columns. The node must be stopped.
+    Check {
+        /// Optional TOML file containing forest daemon configuration
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Optional chain, will override the chain section of configuration file if used
+        #[arg(long)]
+        chain: Option<NetworkChain>,
+        /// Number of blocks to check in each column, all of them if unset
+        #[arg(long)]
+        sample: Option<usize>,
+    },
 }
 
 impl DBCommands {
@@ -106,8 +129,6 @@ impl DBCommands {
                 db,
                 skip_validation: no_validation,
             } => {
-                const DB_WRITE_BUFFER_CAPACITY: usize = 10000;
-
                 let db_root_path = if let Some(db) = db {
                     db
                 } else {
@@ -115,10 +136,10 @@ impl DBCommands {
                     db_root(&chain_path(&config))?
                 };
                 println!("Opening parity-db at {}", db_root_path.display());
-                let db_writer = BlockstoreWithWriteBuffer::new_with_capacity(
-                    open_db(db_root_path, &Default::default())?,
-                    DB_WRITE_BUFFER_CAPACITY,
-                );
+                // Blocks are written in batches, each committed with a single write.
+                const BATCH_SIZE: usize = 10000;
+
+                let db = open_db(db_root_path, &Default::default())?;
 
                 let pb = ProgressBar::new_spinner().with_style(
                     ProgressStyle::with_template("{spinner} {msg}")
@@ -127,22 +148,67 @@ impl DBCommands {
                 pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
                 let mut total = 0;
+                let mut batch = Vec::with_capacity(BATCH_SIZE);
                 for snap in snapshot_files {
                     let mut car = CarStream::new_from_path(&snap).await?;
                     while let Some(b) = car.try_next().await? {
                         if !no_validation {
                             b.validate()?;
                         }
-                        db_writer.put_keyed(&b.cid, &b.data)?;
-                        total += 1;
-                        let text = format!("{total} blocks imported");
-                        pb.set_message(text);
+                        batch.push((b.cid, b.data));
+                        if batch.len() >= BATCH_SIZE {
+                            total += batch.len();
+                            db.put_many_keyed(batch.drain(..))?;
+                            pb.set_message(format!("{total} blocks imported"));
+                        }
                     }
                 }
-                drop(db_writer);
+                total += batch.len();
+                db.put_many_keyed(batch)?;
+                pb.set_message(format!("{total} blocks imported"));
                 pb.finish();
                 Ok(())
             }
+            Self::Compact { config, chain } => {
+                use human_repr::HumanCount as _;
+
+                let (_, config) = read_config(config.as_ref(), chain.clone())?;
+                let dir = db_root(&chain_path(&config))?;
+                let size_before = fs_extra::dir::get_size(&dir).unwrap_or_default();
+                println!("Compacting {}", dir.display());
+                ParityDb::compact(&dir, &Default::default())?;
+                let size_after = fs_extra::dir::get_size(&dir).unwrap_or_default();
+                println!(
+                    "Database size: {} -> {}",
+                    size_before.human_count_bytes(),
+                    size_after.human_count_bytes()
+                );
+                Ok(())
+            }
+            Self::Check {
+                config,
+                chain,
+                sample,
+            } => {
+                let (_, config) = read_config(config.as_ref(), chain.clone())?;
+                let dir = db_root(&chain_path(&config))?;
+                println!("Checking {}", dir.display());
+                let orphaned = ParityDb::orphaned_columns(&dir)?;
+                if !orphaned.is_empty() {
+                    println!("Orphaned columns: {orphaned:?}");
+                }
+                let db = open_db(dir, &Default::default())?;
+                let mut corrupted = 0;
+                for column in db.check_integrity(sample)? {
+                    println!(
+                        "{}: {} blocks checked, {} corrupted",
+                        column.column, column.checked, column.corrupted
+                    );
+                    corrupted += column.corrupted;
+                }
+                anyhow::ensure!(corrupted == 0, "found {corrupted} corrupted blocks");
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,546 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Export of the chain history to Parquet files, to be loaded into analytics engines such as
+//! DuckDB or Spark. The schemas of the tables are stable: columns may be appended, but are never
+//! renamed, retyped or removed.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use clap::Subcommand;
+use fvm_ipld_blockstore::Blockstore;
+use itertools::Itertools as _;
+use parquet::{
+    basic::{Compression, ZstdLevel},
+    data_type::{ByteArray, ByteArrayType, Int64Type},
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+
+use crate::blocks::{CachingBlockHeader, Tipset};
+use crate::chain::index::{ChainIndex, ResolveNullTipset};
+use crate::db::car::ManyCar;
+use crate::interpreter::BlockMessages;
+use crate::message::ChainMessage;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::executor::Receipt;
+
+/// Schema of `block_headers.parquet`, one row per block. The parents are comma-separated CIDs.
+const BLOCK_HEADERS_SCHEMA: &str = "
+message block_headers {
+    REQUIRED INT64 epoch;
+    REQUIRED BYTE_ARRAY cid (UTF8);
+    REQUIRED BYTE_ARRAY miner (UTF8);
+    REQUIRED BYTE_ARRAY parents (UTF8);
+    REQUIRED BYTE_ARRAY parent_weight (UTF8);
+    REQUIRED BYTE_ARRAY parent_state_root (UTF8);
+    REQUIRED BYTE_ARRAY parent_message_receipts (UTF8);
+    REQUIRED BYTE_ARRAY messages (UTF8);
+    REQUIRED INT64 timestamp (UINT_64);
+    REQUIRED BYTE_ARRAY parent_base_fee (UTF8);
+    REQUIRED INT64 win_count;
+}";
+
+/// Schema of `messages.parquet`, one row per message executed in a tipset, in execution order.
+/// The amounts are in attoFIL.
+const MESSAGES_SCHEMA: &str = "
+message messages {
+    REQUIRED INT64 epoch;
+    REQUIRED INT64 index;
+    REQUIRED BYTE_ARRAY cid (UTF8);
+    REQUIRED BYTE_ARRAY from (UTF8);
+    REQUIRED BYTE_ARRAY to (UTF8);
+    REQUIRED INT64 nonce (UINT_64);
+    REQUIRED BYTE_ARRAY value (UTF8);
+    REQUIRED INT64 method (UINT_64);
+    REQUIRED BYTE_ARRAY params;
+    REQUIRED INT64 gas_limit (UINT_64);
+    REQUIRED BYTE_ARRAY gas_fee_cap (UTF8);
+    REQUIRED BYTE_ARRAY gas_premium (UTF8);
+}";
+
+/// Schema of `receipts.parquet`, one row per message of `messages.parquet` with the same epoch
+/// and index. The receipts of a tipset are recorded in its child, so there are none for the
+/// messages of the heaviest tipset.
+const RECEIPTS_SCHEMA: &str = "
+message receipts {
+    REQUIRED INT64 epoch;
+    REQUIRED INT64 index;
+    REQUIRED BYTE_ARRAY message_cid (UTF8);
+    REQUIRED INT64 exit_code;
+    REQUIRED BYTE_ARRAY return;
+    REQUIRED INT64 gas_used (UINT_64);
+    OPTIONAL BYTE_ARRAY events_root (UTF8);
+}";
+
+/// Number of rows of a table buffered before they are written as a row group
+const ROW_GROUP_SIZE: usize = 100_000;
+
+#[derive(Debug, Subcommand)]
+pub enum ExportCommands {
+    /// Export the block headers, messages and receipts of an epoch range to
+    /// `block_headers.parquet`, `messages.parquet` and `receipts.parquet` files
+    Analytics {
+        /// First epoch of the range
+        #[arg(long)]
+        from: ChainEpoch,
+        /// Last epoch of the range, the heaviest epoch of the snapshots if omitted
+        #[arg(long)]
+        to: Option<ChainEpoch>,
+        /// Directory to write the Parquet files to
+        #[arg(long)]
+        out: PathBuf,
+        /// Snapshot files to read the chain from
+        #[arg(long, required = true, num_args = 1..)]
+        snapshot_files: Vec<PathBuf>,
+    },
+}
+
+impl ExportCommands {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Analytics {
+                from,
+                to,
+                out,
+                snapshot_files,
+            } => {
+                let store = Arc::new(ManyCar::try_from(snapshot_files)?);
+                let heaviest = store.heaviest_tipset()?;
+                let to = to.unwrap_or(heaviest.epoch());
+                anyhow::ensure!(from <= to, "the range {from}..={to} is empty");
+                std::fs::create_dir_all(&out)?;
+                let summary =
+                    tokio::task::spawn_blocking(move || export(&store, heaviest, from, to, &out))
+                        .await??;
+                println!(
+                    "Exported {} block headers, {} messages and {} receipts",
+                    summary.block_headers, summary.messages, summary.receipts
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Number of rows written to each table
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ExportSummary {
+    block_headers: usize,
+    messages: usize,
+    receipts: usize,
+}
+
+/// Exports the tipsets of the chain of `heaviest` in the epoch range to the `out` directory.
+fn export<DB: Blockstore>(
+    db: &Arc<DB>,
+    heaviest: Tipset,
+    from: ChainEpoch,
+    to: ChainEpoch,
+    out: &Path,
+) -> anyhow::Result<ExportSummary> {
+    let mut headers =
+        TableWriter::create(&out.join("block_headers.parquet"), BLOCK_HEADERS_SCHEMA)?;
+    let mut messages = TableWriter::create(&out.join("messages.parquet"), MESSAGES_SCHEMA)?;
+    let mut receipts = TableWriter::create(&out.join("receipts.parquet"), RECEIPTS_SCHEMA)?;
+    let mut header_rows = BlockHeaderRows::default();
+    let mut message_rows = MessageRows::default();
+    let mut receipt_rows = ReceiptRows::default();
+
+    let chain_index = ChainIndex::new(db.clone());
+    // Starts from the child of the last tipset of the range, which holds its receipts
+    let start = if to < heaviest.epoch() {
+        chain_index.tipset_by_height(to + 1, heaviest, ResolveNullTipset::TakeNewer)?
+    } else {
+        heaviest
+    };
+    let mut child: Option<Tipset> = None;
+    for ts in chain_index.chain(start) {
+        if ts.epoch() > to {
+            child = Some(ts);
+            continue;
+        }
+        if ts.epoch() < from {
+            break;
+        }
+        for header in ts.block_headers() {
+            header_rows.push(header);
+        }
+        let chain_messages = BlockMessages::for_tipset(db, &ts)?
+            .into_iter()
+            .flat_map(|it| it.messages)
+            .collect_vec();
+        if let Some(child) = &child {
+            let ts_receipts = Receipt::get_receipts(db, *child.parent_message_receipts())?;
+            anyhow::ensure!(
+                ts_receipts.len() == chain_messages.len(),
+                "{} receipts for the {} messages of the tipset at epoch {}",
+                ts_receipts.len(),
+                chain_messages.len(),
+                ts.epoch()
+            );
+            for (index, (message, receipt)) in chain_messages.iter().zip(&ts_receipts).enumerate() {
+                receipt_rows.push(ts.epoch(), index, message, receipt);
+            }
+        }
+        for (index, message) in chain_messages.iter().enumerate() {
+            message_rows.push(ts.epoch(), index, message);
+        }
+
+        if header_rows.len() >= ROW_GROUP_SIZE {
+            headers.write_row_group(header_rows.take_columns())?;
+        }
+        if message_rows.len() >= ROW_GROUP_SIZE {
+            messages.write_row_group(message_rows.take_columns())?;
+        }
+        if receipt_rows.len() >= ROW_GROUP_SIZE {
+            receipts.write_row_group(receipt_rows.take_columns())?;
+        }
+        child = Some(ts);
+    }
+    headers.write_row_group(header_rows.take_columns())?;
+    messages.write_row_group(message_rows.take_columns())?;
+    receipts.write_row_group(receipt_rows.take_columns())?;
+    Ok(ExportSummary {
+        block_headers: headers.close()?,
+        messages: messages.close()?,
+        receipts: receipts.close()?,
+    })
+}
+
+/// A column of a table, in the order of the fields of its schema
+enum Column {
+    Int64(Vec<i64>),
+    Bytes(Vec<ByteArray>),
+    OptionalBytes(Vec<Option<ByteArray>>),
+}
+
+/// Writer of a Parquet file, one row group at a time
+struct TableWriter {
+    writer: SerializedFileWriter<File>,
+    rows: usize,
+}
+
+impl TableWriter {
+    fn create(path: &Path, schema: &str) -> anyhow::Result<Self> {
+        let schema = Arc::new(parse_message_type(schema)?);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let file =
+            File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        Ok(Self {
+            writer: SerializedFileWriter::new(file, schema, Arc::new(properties))?,
+            rows: 0,
+        })
+    }
+
+    /// Writes a row group of the columns, unless they are empty.
+    fn write_row_group(&mut self, columns: Vec<Column>) -> anyhow::Result<()> {
+        let rows = match columns.first() {
+            Some(Column::Int64(values)) => values.len(),
+            Some(Column::Bytes(values)) => values.len(),
+            Some(Column::OptionalBytes(values)) => values.len(),
+            None => 0,
+        };
+        if rows == 0 {
+            return Ok(());
+        }
+        let mut row_group = self.writer.next_row_group()?;
+        let mut columns = columns.into_iter();
+        while let Some(mut column_writer) = row_group.next_column()? {
+            match columns.next().context("missing column")? {
+                Column::Int64(values) => {
+                    column_writer
+                        .typed::<Int64Type>()
+                        .write_batch(&values, None, None)?;
+                }
+                Column::Bytes(values) => {
+                    column_writer
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, None, None)?;
+                }
+                Column::OptionalBytes(values) => {
+                    let definition_levels = values
+                        .iter()
+                        .map(|it| i16::from(it.is_some()))
+                        .collect_vec();
+                    let values = values.into_iter().flatten().collect_vec();
+                    column_writer.typed::<ByteArrayType>().write_batch(
+                        &values,
+                        Some(&definition_levels),
+                        None,
+                    )?;
+                }
+            }
+            column_writer.close()?;
+        }
+        row_group.close()?;
+        self.rows += rows;
+        Ok(())
+    }
+
+    /// Writes the footer of the file, returning the number of rows written.
+    fn close(self) -> anyhow::Result<usize> {
+        self.writer.close()?;
+        Ok(self.rows)
+    }
+}
+
+fn string(value: impl ToString) -> ByteArray {
+    ByteArray::from(value.to_string().into_bytes())
+}
+
+#[derive(Default)]
+struct BlockHeaderRows {
+    epoch: Vec<i64>,
+    cid: Vec<ByteArray>,
+    miner: Vec<ByteArray>,
+    parents: Vec<ByteArray>,
+    parent_weight: Vec<ByteArray>,
+    parent_state_root: Vec<ByteArray>,
+    parent_message_receipts: Vec<ByteArray>,
+    messages: Vec<ByteArray>,
+    timestamp: Vec<i64>,
+    parent_base_fee: Vec<ByteArray>,
+    win_count: Vec<i64>,
+}
+
+impl BlockHeaderRows {
+    fn push(&mut self, header: &CachingBlockHeader) {
+        self.epoch.push(header.epoch);
+        self.cid.push(string(header.cid()));
+        self.miner.push(string(header.miner_address));
+        self.parents
+            .push(string(header.parents.to_cids().into_iter().join(",")));
+        self.parent_weight.push(string(&header.weight));
+        self.parent_state_root.push(string(header.state_root));
+        self.parent_message_receipts
+            .push(string(header.message_receipts));
+        self.messages.push(string(header.messages));
+        self.timestamp.push(header.timestamp as i64);
+        self.parent_base_fee
+            .push(string(header.parent_base_fee.atto()));
+        self.win_count.push(
+            header
+                .election_proof
+                .as_ref()
+                .map(|it| it.win_count)
+                .unwrap_or_default(),
+        );
+    }
+
+    fn len(&self) -> usize {
+        self.epoch.len()
+    }
+
+    fn take_columns(&mut self) -> Vec<Column> {
+        let rows = std::mem::take(self);
+        vec![
+            Column::Int64(rows.epoch),
+            Column::Bytes(rows.cid),
+            Column::Bytes(rows.miner),
+            Column::Bytes(rows.parents),
+            Column::Bytes(rows.parent_weight),
+            Column::Bytes(rows.parent_state_root),
+            Column::Bytes(rows.parent_message_receipts),
+            Column::Bytes(rows.messages),
+            Column::Int64(rows.timestamp),
+            Column::Bytes(rows.parent_base_fee),
+            Column::Int64(rows.win_count),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct MessageRows {
+    epoch: Vec<i64>,
+    index: Vec<i64>,
+    cid: Vec<ByteArray>,
+    from: Vec<ByteArray>,
+    to: Vec<ByteArray>,
+    nonce: Vec<i64>,
+    value: Vec<ByteArray>,
+    method: Vec<i64>,
+    params: Vec<ByteArray>,
+    gas_limit: Vec<i64>,
+    gas_fee_cap: Vec<ByteArray>,
+    gas_premium: Vec<ByteArray>,
+}
+
+impl MessageRows {
+    // The unsigned integers are stored as their bit patterns, as per their `UINT_64` annotation.
+    fn push(&mut self, epoch: ChainEpoch, index: usize, chain_message: &ChainMessage) {
+        let message = chain_message.message();
+        self.epoch.push(epoch);
+        self.index.push(index as i64);
+        self.cid.push(string(chain_message.cid()));
+        self.from.push(string(message.from));
+        self.to.push(string(message.to));
+        self.nonce.push(message.sequence as i64);
+        self.value.push(string(message.value.atto()));
+        self.method.push(message.method_num as i64);
+        self.params
+            .push(ByteArray::from(message.params.bytes().to_vec()));
+        self.gas_limit.push(message.gas_limit as i64);
+        self.gas_fee_cap.push(string(message.gas_fee_cap.atto()));
+        self.gas_premium.push(string(message.gas_premium.atto()));
+    }
+
+    fn len(&self) -> usize {
+        self.epoch.len()
+    }
+
+    fn take_columns(&mut self) -> Vec<Column> {
+        let rows = std::mem::take(self);
+        vec![
+            Column::Int64(rows.epoch),
+            Column::Int64(rows.index),
+            Column::Bytes(rows.cid),
+            Column::Bytes(rows.from),
+            Column::Bytes(rows.to),
+            Column::Int64(rows.nonce),
+            Column::Bytes(rows.value),
+            Column::Int64(rows.method),
+            Column::Bytes(rows.params),
+            Column::Int64(rows.gas_limit),
+            Column::Bytes(rows.gas_fee_cap),
+            Column::Bytes(rows.gas_premium),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct ReceiptRows {
+    epoch: Vec<i64>,
+    index: Vec<i64>,
+    message_cid: Vec<ByteArray>,
+    exit_code: Vec<i64>,
+    return_data: Vec<ByteArray>,
+    gas_used: Vec<i64>,
+    events_root: Vec<Option<ByteArray>>,
+}
+
+impl ReceiptRows {
+    fn push(
+        &mut self,
+        epoch: ChainEpoch,
+        index: usize,
+        chain_message: &ChainMessage,
+        receipt: &Receipt,
+    ) {
+        self.epoch.push(epoch);
+        self.index.push(index as i64);
+        self.message_cid.push(string(chain_message.cid()));
+        self.exit_code.push(receipt.exit_code().value().into());
+        self.return_data
+            .push(ByteArray::from(receipt.return_data().bytes().to_vec()));
+        self.gas_used.push(receipt.gas_used() as i64);
+        self.events_root.push(receipt.events_root().map(string));
+    }
+
+    fn len(&self) -> usize {
+        self.epoch.len()
+    }
+
+    fn take_columns(&mut self) -> Vec<Column> {
+        let rows = std::mem::take(self);
+        vec![
+            Column::Int64(rows.epoch),
+            Column::Int64(rows.index),
+            Column::Bytes(rows.message_cid),
+            Column::Int64(rows.exit_code),
+            Column::Bytes(rows.return_data),
+            Column::Int64(rows.gas_used),
+            Column::OptionalBytes(rows.events_root),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shim::{address::Address, econ::TokenAmount, message::Message};
+    use parquet::file::reader::{FileReader as _, SerializedFileReader};
+    use parquet::record::RowAccessor as _;
+
+    #[test]
+    fn write_messages_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.parquet");
+        let message = |sequence| {
+            ChainMessage::Unsigned(
+                Message {
+                    from: Address::new_id(1000),
+                    to: Address::new_id(1001),
+                    sequence,
+                    value: TokenAmount::from_atto(u128::MAX),
+                    method_num: u64::MAX,
+                    ..Default::default()
+                }
+                .into(),
+            )
+        };
+        let mut table = TableWriter::create(&path, MESSAGES_SCHEMA).unwrap();
+        let mut rows = MessageRows::default();
+        rows.push(10, 0, &message(1));
+        rows.push(10, 1, &message(2));
+        table.write_row_group(rows.take_columns()).unwrap();
+        assert_eq!(rows.len(), 0);
+        rows.push(11, 0, &message(3));
+        table.write_row_group(rows.take_columns()).unwrap();
+        // Empty row groups are skipped
+        table.write_row_group(rows.take_columns()).unwrap();
+        assert_eq!(table.close().unwrap(), 3);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect_vec();
+        assert_eq!(rows[2].get_long(0).unwrap(), 11);
+        assert_eq!(
+            rows[2].get_string(2).unwrap(),
+            &message(3).cid().to_string()
+        );
+        assert_eq!(rows[2].get_ulong(5).unwrap(), 3);
+        assert_eq!(rows[2].get_string(6).unwrap(), &u128::MAX.to_string());
+        assert_eq!(rows[2].get_ulong(7).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn write_optional_column() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("receipts.parquet");
+        let mut table = TableWriter::create(&path, RECEIPTS_SCHEMA).unwrap();
+        let mut rows = ReceiptRows::default();
+        let message = ChainMessage::Unsigned(Message::default().into());
+        for events_root in [None, Some(message.cid())] {
+            let receipt = Receipt::V4(fvm_shared4::receipt::Receipt {
+                exit_code: fvm_shared4::error::ExitCode::OK,
+                return_data: Default::default(),
+                gas_used: 42,
+                events_root,
+            });
+            rows.push(1, 0, &message, &receipt);
+        }
+        table.write_row_group(rows.take_columns()).unwrap();
+        assert_eq!(table.close().unwrap(), 2);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect_vec();
+        assert!(rows[0].get_string(6).is_err());
+        assert_eq!(rows[1].get_string(6).unwrap(), &message.cid().to_string());
+        assert_eq!(rows[1].get_ulong(5).unwrap(), 42);
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cid::Cid;
+use clap::Subcommand;
+use fil_actors_shared::fvm_ipld_hamt::{BytesKey, Hamt};
+use fvm_ipld_blockstore::Blockstore;
+use serde::de::DeserializeOwned;
+
+use crate::chain_sync::light_client::RemoteBlockstore;
+use crate::db::MemoryDB;
+use crate::db::car::ManyCar;
+use crate::ipld::Ipld;
+use crate::lotus_json::HasLotusJson;
+use crate::rpc;
+use crate::shim::actors::{power, verifreg};
+use crate::shim::address::Address;
+use crate::shim::econ::TokenAmount;
+use crate::shim::state_tree::ActorState;
+
+#[derive(Debug, Subcommand)]
+pub enum HamtCommands {
+    /// Print the keys and values of a HAMT
+    Dump {
+        /// Root CID of the HAMT
+        root: Cid,
+        /// Bit width of the HAMT
+        #[arg(long, default_value_t = 5)]
+        bitwidth: u32,
+        /// Type of the keys
+        #[arg(long, value_enum, default_value_t = KeySchema::Bytes)]
+        key_schema: KeySchema,
+        /// Type of the values
+        #[arg(long, value_enum, default_value_t = ValueSchema::Ipld)]
+        value_schema: ValueSchema,
+        #[command(flatten)]
+        source: BlockSource,
+    },
+}
+
+impl HamtCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Dump {
+                root,
+                bitwidth,
+                key_schema,
+                value_schema,
+                source,
+            } => {
+                let store = source.open(client)?;
+                tokio::task::spawn_blocking(move || {
+                    let hamt =
+                        Hamt::<_, Ipld, BytesKey>::load_with_bit_width(&root, &store, bitwidth)?;
+                    hamt.for_each(|key, value| {
+                        println!(
+                            "{}: {}",
+                            key_schema.decode(key)?,
+                            value_schema.decode(value)?
+                        );
+                        Ok(())
+                    })?;
+                    anyhow::Ok(())
+                })
+                .await?
+            }
+        }
+    }
+}
+
+/// Encoding of the keys of a HAMT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum KeySchema {
+    /// Raw bytes, printed in hexadecimal
+    Bytes,
+    /// Address, e.g. of the state tree or of the balance tables
+    Address,
+    /// Unsigned varint, e.g. actor or allocation IDs
+    Uint,
+    /// CID, e.g. of pending deal proposals
+    Cid,
+}
+
+impl KeySchema {
+    pub fn decode(self, key: &[u8]) -> anyhow::Result<String> {
+        Ok(match self {
+            Self::Bytes => hex::encode(key),
+            Self::Address => Address::from_bytes(key)?.to_string(),
+            Self::Uint => fil_actors_shared::v17::parse_uint_key(key)?.to_string(),
+            Self::Cid => Cid::try_from(key)?.to_string(),
+        })
+    }
+}
+
+/// Decoder of the values of builtin-actor collections, printing them in their Lotus JSON form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ValueSchema {
+    /// Any value, printed as IPLD
+    Ipld,
+    /// Actor of the state tree
+    #[value(name = "system/actor")]
+    Actor,
+    /// Balance of a balance table
+    #[value(name = "market/balance")]
+    Balance,
+    /// Deal proposal of the market actor
+    #[value(name = "market/proposal")]
+    DealProposal,
+    /// Sector of a miner
+    #[value(name = "miner/sector")]
+    Sector,
+    /// Power claim of a miner
+    #[value(name = "power/claim")]
+    PowerClaim,
+    /// Allocation of the verified registry
+    #[value(name = "verifreg/allocation")]
+    Allocation,
+    /// Claim of the verified registry
+    #[value(name = "verifreg/claim")]
+    Claim,
+}
+
+impl ValueSchema {
+    pub fn decode(self, value: &Ipld) -> anyhow::Result<String> {
+        let json = match self {
+            Self::Ipld => serde_json::to_string(&value.clone().into_lotus_json())?,
+            Self::Actor => lotus_json::<ActorState>(value)?,
+            Self::Balance => lotus_json::<TokenAmount>(value)?,
+            Self::DealProposal => lotus_json::<fil_actor_market_state::v17::DealProposal>(value)?,
+            Self::Sector => {
+                serde_json::to_string(&rpc::types::SectorOnChainInfo::from(from_ipld::<
+                    fil_actor_miner_state::v17::SectorOnChainInfo,
+                >(
+                    value
+                )?))?
+            }
+            Self::PowerClaim => serde_json::to_string(
+                &power::Claim::from(from_ipld::<fil_actor_power_state::v17::Claim>(value)?)
+                    .into_lotus_json(),
+            )?,
+            Self::Allocation => serde_json::to_string(
+                &verifreg::Allocation::from(
+                    &from_ipld::<fil_actor_verifreg_state::v17::Allocation>(value)?,
+                )
+                .into_lotus_json(),
+            )?,
+            Self::Claim => lotus_json::<verifreg::Claim>(value)?,
+        };
+        Ok(json)
+    }
+}
+
+fn from_ipld<T: DeserializeOwned>(value: &Ipld) -> anyhow::Result<T> {
+    Ok(fvm_ipld_encoding::from_slice(&fvm_ipld_encoding::to_vec(
+        value,
+    )?)?)
+}
+
+fn lotus_json<T: HasLotusJson + DeserializeOwned>(value: &Ipld) -> anyhow::Result<String> {
+    Ok(serde_json::to_string(
+        &from_ipld::<T>(value)?.into_lotus_json(),
+    )?)
+}
+
+/// Where the blocks of the collections are read from.
+#[derive(Debug, clap::Args)]
+pub struct BlockSource {
+    /// Snapshot files to read the blocks from, instead of the running node
+    #[arg(long, num_args = 1..)]
+    snapshot_files: Vec<PathBuf>,
+}
+
+impl BlockSource {
+    /// Opens the store, which reads blocks synchronously and must be used from a blocking task.
+    pub fn open(self, client: rpc::Client) -> anyhow::Result<SourceStore> {
+        Ok(if self.snapshot_files.is_empty() {
+            SourceStore::Node(RemoteBlockstore::new(Arc::new(client)))
+        } else {
+            SourceStore::Snapshots(ManyCar::try_from(self.snapshot_files)?)
+        })
+    }
+}
+
+pub enum SourceStore {
+    Node(RemoteBlockstore),
+    Snapshots(ManyCar<MemoryDB>),
+}
+
+impl Blockstore for SourceStore {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        match self {
+            Self::Node(store) => store.get(k),
+            Self::Snapshots(store) => store.get(k),
+        }
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        match self {
+            Self::Node(store) => store.put_keyed(k, block),
+            Self::Snapshots(store) => store.put_keyed(k, block),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_keys() {
+        let address = Address::new_id(1234);
+        assert_eq!(
+            KeySchema::Address.decode(&address.to_bytes()).unwrap(),
+            address.to_string()
+        );
+        assert_eq!(
+            KeySchema::Uint
+                .decode(&fil_actors_shared::v17::u64_key(42))
+                .unwrap(),
+            "42"
+        );
+        assert_eq!(KeySchema::Bytes.decode(&[0xab, 0x01]).unwrap(), "ab01");
+    }
+
+    #[test]
+    fn decode_values() {
+        let amount = TokenAmount::from_atto(42);
+        let ipld: Ipld =
+            fvm_ipld_encoding::from_slice(&fvm_ipld_encoding::to_vec(&amount).unwrap()).unwrap();
+        assert_eq!(ValueSchema::Balance.decode(&ipld).unwrap(), "\"42\"");
+        assert!(ValueSchema::Actor.decode(&ipld).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use cid::Cid;
+use jsonrpsee::RpcModule;
+use jsonrpsee::types::ErrorObjectOwned;
+use nunny::Vec as NonEmpty;
+use parking_lot::RwLock;
+
+use crate::blocks::{Tipset, TipsetKey};
+use crate::chain_sync::light_client::{HeaderVerifier, LightClient, RemoteBlockstore};
+use crate::lotus_json::HasLotusJson as _;
+use crate::networks::{ChainConfig, NetworkChain};
+use crate::rpc::{self, RpcMethod as _, prelude::*};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Follow the chain of a node as a light client, verifying the block headers without executing
+/// messages, and serve the verified heads over RPC
+#[derive(Debug, clap::Args)]
+pub struct LightClientCommand {
+    /// CIDs of the blocks of the trusted tipset the verification starts from
+    #[arg(long, num_args = 1.., required = true)]
+    anchor: Vec<Cid>,
+    /// Filecoin network chain
+    #[arg(long, default_value = "mainnet")]
+    chain: NetworkChain,
+    /// Address the `Filecoin.ChainHead` and `Filecoin.ChainGetFinalizedTipSet` methods are
+    /// served on
+    #[arg(long, default_value = "127.0.0.1:2346")]
+    listen: SocketAddr,
+}
+
+/// Verified head and finalized tipset
+type Heads = Arc<RwLock<(Tipset, Tipset)>>;
+
+impl LightClientCommand {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        let client = Arc::new(client);
+        let chain_config = Arc::new(ChainConfig::from_chain(&self.chain));
+        let anchor = TipsetKey::from(
+            NonEmpty::new(self.anchor).map_err(|_| anyhow::anyhow!("anchor cannot be empty"))?,
+        );
+        let light_client = {
+            let client = client.clone();
+            tokio::task::spawn_blocking(move || {
+                let store = Arc::new(RemoteBlockstore::new(client));
+                let anchor = Tipset::load_required(&store, &anchor)?;
+                let genesis = anchor.genesis(&store)?;
+                let verifier = HeaderVerifier::new(store, chain_config, genesis.timestamp);
+                anyhow::Ok(LightClient::new(verifier, anchor))
+            })
+            .await??
+        };
+        let anchor = light_client.head().clone();
+        println!(
+            "Verifying the chain from tipset {} at epoch {}",
+            anchor.key(),
+            anchor.epoch()
+        );
+        let heads: Heads = Arc::new(RwLock::new((anchor.clone(), anchor)));
+
+        let server = jsonrpsee::server::Server::builder()
+            .build(self.listen)
+            .await
+            .with_context(|| format!("failed to listen on {}", self.listen))?;
+        let handle = server.start(rpc_module(heads.clone())?);
+        println!("Serving the verified heads on {}", self.listen);
+
+        let light_client = Arc::new(parking_lot::Mutex::new(light_client));
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let head = match ChainHead::call(&client, ()).await {
+                Ok(head) => head,
+                Err(e) => {
+                    tracing::warn!("Failed to get the chain head: {e}");
+                    continue;
+                }
+            };
+            let light_client = light_client.clone();
+            let heads = heads.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut light_client = light_client.lock();
+                match light_client.apply(head) {
+                    Ok(true) => tracing::info!(
+                        "Verified head {} at epoch {}",
+                        light_client.head().key(),
+                        light_client.head().epoch()
+                    ),
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Failed to verify the chain head: {e:#}"),
+                }
+                *heads.write() = (
+                    light_client.head().clone(),
+                    light_client.finalized().clone(),
+                );
+            })
+            .await?;
+            if handle.is_stopped() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn rpc_module(heads: Heads) -> anyhow::Result<RpcModule<Heads>> {
+    let mut module = RpcModule::new(heads);
+    module.register_method(ChainHead::NAME, |_, heads, _| {
+        Result::<_, ErrorObjectOwned>::Ok(heads.read().0.clone().into_lotus_json())
+    })?;
+    module.register_method(ChainGetFinalizedTipset::NAME, |_, heads, _| {
+        Result::<_, ErrorObjectOwned>::Ok(heads.read().1.clone().into_lotus_json())
+    })?;
+    Ok(module)
+}
@@ -0,0 +1,170 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use cid::Cid;
+use clap::Subcommand;
+use serde::Serialize;
+
+use crate::db::car::ManyCar;
+use crate::message::SignedMessage;
+use crate::rpc::registry::{
+    actors_reg::ActorRegistry,
+    methods_reg::{deserialize_params, method_info},
+};
+use crate::rpc::types::ApiTipsetKey;
+use crate::rpc::{self, prelude::*};
+use crate::shim::crypto::Signature;
+use crate::shim::message::Message;
+use crate::shim::state_tree::StateTree;
+
+#[derive(Debug, Subcommand)]
+pub enum MessageCommands {
+    /// Decode a message, and its parameters according to the type of the receiving actor, e.g. to
+    /// inspect a message before signing it
+    Decode {
+        /// The CBOR encoding of a message or of a signed message, in hexadecimal or base64
+        message: String,
+        /// Snapshot files to look the receiving actor up in, instead of the running node
+        #[arg(long, num_args = 1..)]
+        snapshot_files: Vec<PathBuf>,
+    },
+}
+
+impl MessageCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Decode {
+                message,
+                snapshot_files,
+            } => {
+                let (message, signature) = decode_message(&message)?;
+                let code = if snapshot_files.is_empty() {
+                    StateGetActor::call(&client, (message.to, ApiTipsetKey(None)))
+                        .await?
+                        .map(|actor| actor.code)
+                } else {
+                    let store = Arc::new(ManyCar::try_from(snapshot_files)?);
+                    let ts = store.heaviest_tipset()?;
+                    StateTree::new_from_root(store, ts.parent_state())?
+                        .get_actor(&message.to)?
+                        .map(|actor| actor.code)
+                }
+                .with_context(|| format!("receiving actor {} not found", message.to))?;
+                let decoded = DecodedMessage::new(message, signature, &code)?;
+                println!("{}", serde_json::to_string_pretty(&decoded)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct DecodedMessage {
+    #[serde(with = "crate::lotus_json")]
+    cid: Cid,
+    #[serde(with = "crate::lotus_json")]
+    message: Message,
+    #[serde(with = "crate::lotus_json", skip_serializing_if = "Option::is_none")]
+    signature: Option<Signature>,
+    actor_type: &'static str,
+    actor_version: u8,
+    /// The name of the method, if it is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    method_name: Option<&'static str>,
+    /// The parameters in their Lotus JSON form, `null` if there are none
+    params: serde_json::Value,
+}
+
+impl DecodedMessage {
+    fn new(message: Message, signature: Option<Signature>, code: &Cid) -> anyhow::Result<Self> {
+        let (actor_type, actor_version) = ActorRegistry::get_actor_details_from_code(code)?;
+        let method_name = method_info(code, message.method_num).map(|info| info.name);
+        let params = if message.params.bytes().is_empty() {
+            serde_json::Value::Null
+        } else {
+            deserialize_params(code, message.method_num, message.params.bytes())?
+                .unwrap_or_default()
+        };
+        let cid = match &signature {
+            Some(signature) => {
+                SignedMessage::new_unchecked(message.clone(), signature.clone()).cid()
+            }
+            None => message.cid(),
+        };
+        Ok(Self {
+            cid,
+            message,
+            signature,
+            actor_type: actor_type.name(),
+            actor_version: actor_version as u8,
+            method_name,
+            params,
+        })
+    }
+}
+
+/// Decodes a message, or a signed message, from its CBOR encoding in hexadecimal or base64.
+fn decode_message(input: &str) -> anyhow::Result<(Message, Option<Signature>)> {
+    let input = input.trim();
+    let bytes = match hex::decode(input.strip_prefix("0x").unwrap_or(input)) {
+        Ok(bytes) => bytes,
+        Err(_) => BASE64_STANDARD
+            .decode(input)
+            .context("the message is neither hexadecimal nor base64")?,
+    };
+    if let Ok(signed) = fvm_ipld_encoding::from_slice::<SignedMessage>(&bytes) {
+        return Ok((signed.message, Some(signed.signature)));
+    }
+    let message = fvm_ipld_encoding::from_slice(&bytes)
+        .context("the input is neither a message nor a signed message")?;
+    Ok((message, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::ACTOR_BUNDLES_METADATA;
+    use crate::shim::address::Address;
+    use crate::shim::machine::BuiltinActor;
+    use fvm_ipld_encoding::RawBytes;
+
+    #[test]
+    fn decode_miner_message() {
+        let miner_code = ACTOR_BUNDLES_METADATA
+            .values()
+            .filter(|metadata| metadata.actor_major_version().ok() == Some(17))
+            .flat_map(|metadata| metadata.manifest.builtin_actors())
+            .find(|(actor_type, _)| *actor_type == BuiltinActor::Miner)
+            .map(|(_, cid)| cid)
+            .unwrap();
+        let message = Message {
+            to: Address::new_id(1000),
+            from: Address::new_id(1001),
+            method_num: fil_actor_miner_state::v17::Method::ChangePeerID as u64,
+            params: RawBytes::serialize(fil_actor_miner_state::v17::ChangePeerIDParams {
+                new_id: vec![1, 2, 3],
+            })
+            .unwrap(),
+            ..Default::default()
+        };
+        let bytes = fvm_ipld_encoding::to_vec(&message).unwrap();
+        for input in [hex::encode(&bytes), BASE64_STANDARD.encode(&bytes)] {
+            let (decoded, signature) = decode_message(&input).unwrap();
+            assert_eq!(decoded, message);
+            assert!(signature.is_none());
+        }
+
+        let decoded = DecodedMessage::new(message.clone(), None, &miner_code).unwrap();
+        assert_eq!(decoded.cid, message.cid());
+        assert_eq!(decoded.actor_type, "storageminer");
+        assert_eq!(decoded.actor_version, 17);
+        assert_eq!(decoded.method_name, Some("ChangePeerID"));
+        assert!(decoded.params.is_object());
+    }
+}
@@ -1,18 +1,27 @@
 // Copyright 2019-2026 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod address_cmd;
+mod amt_cmd;
 pub(crate) mod api_cmd;
 pub(crate) mod archive_cmd;
 mod backup_cmd;
 mod benchmark_cmd;
 mod car_cmd;
 mod db_cmd;
+mod export_cmd;
 mod fetch_params_cmd;
+mod hamt_cmd;
 mod index_cmd;
+mod light_client_cmd;
+mod message_cmd;
 mod net_cmd;
 mod shed_cmd;
 mod snapshot_cmd;
+mod state_cmd;
 mod state_migration_cmd;
+mod test_cmd;
+mod test_vectors_cmd;
 
 use crate::cli_shared::cli::*;
 use crate::cli_shared::cli::{CompletionCommand, HELP_MESSAGE};
@@ -41,6 +50,10 @@ pub enum Subcommand {
     #[command(subcommand)]
     Benchmark(benchmark_cmd::BenchmarkCommands),
 
+    /// Inspect the state roots computed by the node, and the power table of snapshots
+    #[command(subcommand)]
+    State(state_cmd::StateCommands),
+
     /// State migration tools
     #[command(subcommand)]
     StateMigration(state_migration_cmd::StateMigrationCommands),
@@ -61,6 +74,10 @@ pub enum Subcommand {
     #[command(subcommand)]
     DB(db_cmd::DBCommands),
 
+    /// Export the chain history for analytics
+    #[command(subcommand)]
+    Export(export_cmd::ExportCommands),
+
     /// Index database management
     #[command(subcommand)]
     Index(index_cmd::IndexCommands),
@@ -77,9 +94,36 @@ pub enum Subcommand {
     #[command(subcommand)]
     Net(net_cmd::NetCommands),
 
+    /// Follow a node as a light client, verifying headers
+    LightClient(light_client_cmd::LightClientCommand),
+
+    /// Address conversion utilities
+    #[command(subcommand)]
+    Address(address_cmd::AddressCommands),
+
+    /// Decode the keys and values of HAMTs
+    #[command(subcommand)]
+    Hamt(hamt_cmd::HamtCommands),
+
+    /// Decode the indices and values of AMTs
+    #[command(subcommand)]
+    Amt(amt_cmd::AmtCommands),
+
+    /// Decode messages
+    #[command(subcommand)]
+    Message(message_cmd::MessageCommands),
+
     /// Miscellaneous, semver-exempt commands for developer use.
     #[command(subcommand)]
     Shed(shed_cmd::ShedCommands),
 
+    /// Round-trip tests of the chain types
+    #[command(subcommand)]
+    Test(test_cmd::TestCommands),
+
+    /// Run Filecoin conformance test vectors
+    #[command(subcommand)]
+    TestVectors(test_vectors_cmd::TestVectorsCommands),
+
     Completion(CompletionCommand),
 }
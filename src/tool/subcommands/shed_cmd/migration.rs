@@ -9,7 +9,7 @@ use cid::Cid;
 use clap::Args;
 use itertools::Itertools;
 
-use crate::db::{BlockstoreWithWriteBuffer, Either};
+use crate::db::{BlockstoreWithWriteBuffer, Either, WriteBufferConfig};
 use crate::utils::db::CborStoreExt;
 use crate::{
     blocks::CachingBlockHeader,
@@ -38,8 +38,11 @@ pub struct MigrateStateCommand {
     #[arg(long, required = true)]
     chain: NetworkChain,
     /// Size of database write buffer, use 0 to disable write buffer
-    #[arg(long, default_value_t = 10000)]
+    #[arg(long, default_value_t = WriteBufferConfig::default().max_blocks)]
     db_write_buffer: usize,
+    /// Maximum total size of the blocks in the database write buffer, in bytes
+    #[arg(long, default_value_t = WriteBufferConfig::default().max_bytes)]
+    db_write_buffer_bytes: usize,
 }
 
 impl MigrateStateCommand {
@@ -50,6 +53,7 @@ impl MigrateStateCommand {
             db,
             chain,
             db_write_buffer,
+            db_write_buffer_bytes,
         } = self;
         let db = {
             let db = if let Some(db) = db {
@@ -60,9 +64,13 @@ impl MigrateStateCommand {
             };
             let db = load_db(&db)?;
             Arc::new(if db_write_buffer > 0 {
-                Either::Left(BlockstoreWithWriteBuffer::new_with_capacity(
+                Either::Left(BlockstoreWithWriteBuffer::new(
                     db,
-                    db_write_buffer,
+                    WriteBufferConfig {
+                        max_blocks: db_write_buffer,
+                        max_bytes: db_write_buffer_bytes,
+                        ..Default::default()
+                    },
                 ))
             } else {
                 Either::Right(db)
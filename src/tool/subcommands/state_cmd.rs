@@ -0,0 +1,182 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use cid::Cid;
+use clap::Subcommand;
+use serde::Serialize;
+
+use crate::chain::index::{ChainIndex, ResolveNullTipset};
+use crate::cli_shared::{chain_path, read_config};
+use crate::db::StateProvenanceStore as _;
+use crate::db::car::ManyCar;
+use crate::db::db_engine::{db_root, open_db};
+use crate::networks::NetworkChain;
+use crate::shim::actors::power;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::sector::StoragePower;
+use crate::shim::state_tree::StateTree;
+
+#[derive(Debug, Subcommand)]
+pub enum StateCommands {
+    /// Show the tipset whose computation produced a state root. Only the state roots computed
+    /// by the node are tracked, and the node must be stopped.
+    Provenance {
+        /// State root CID
+        root: Cid,
+        /// Filecoin network chain
+        #[arg(long, required = true)]
+        chain: NetworkChain,
+        /// Optional path to the database folder that powers a Forest node
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Export the raw and quality-adjusted power of every miner, from the claims of the power
+    /// actor in a snapshot
+    PowerTable {
+        /// Epoch of the power table, the heaviest epoch of the snapshots if omitted. A null epoch
+        /// resolves to the closest earlier tipset.
+        #[arg(long)]
+        epoch: Option<ChainEpoch>,
+        /// Snapshot files to read the power table from
+        #[arg(long, required = true, num_args = 1..)]
+        snapshot_files: Vec<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = PowerTableFormat::Csv)]
+        format: PowerTableFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PowerTableFormat {
+    /// One `miner,raw_byte_power,quality_adj_power` line per miner, after a header line
+    Csv,
+    /// An array of the power claims in their Lotus JSON form
+    Json,
+}
+
+impl StateCommands {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Provenance { root, chain, db } => {
+                let db_root_path = if let Some(db) = db {
+                    db
+                } else {
+                    let (_, config) = read_config(None, Some(chain))?;
+                    db_root(&chain_path(&config))?
+                };
+                let db = open_db(db_root_path, &Default::default())?;
+                let provenance = db
+                    .read_state_provenance(&root)?
+                    .with_context(|| format!("no provenance recorded for state root {root}"))?;
+                println!("State root: {root}");
+                println!("Epoch:      {}", provenance.epoch);
+                println!("Tipset:     {}", provenance.tipset_key);
+                println!("Migration:  {}", provenance.migration);
+                Ok(())
+            }
+            Self::PowerTable {
+                epoch,
+                snapshot_files,
+                format,
+            } => {
+                let store = Arc::new(ManyCar::try_from(snapshot_files)?);
+                let heaviest = store.heaviest_tipset()?;
+                let ts = match epoch {
+                    Some(epoch) => ChainIndex::new(store.clone()).tipset_by_height(
+                        epoch,
+                        heaviest,
+                        ResolveNullTipset::TakeOlder,
+                    )?,
+                    None => heaviest,
+                };
+                let state: power::State =
+                    StateTree::new_from_root(store.clone(), ts.parent_state())?
+                        .get_actor_state()?;
+                let table = power_table(state.list_all_claims(&store)?);
+                let mut stdout = std::io::stdout().lock();
+                match format {
+                    PowerTableFormat::Csv => write_csv(&mut stdout, &table)?,
+                    PowerTableFormat::Json => {
+                        serde_json::to_writer_pretty(&mut stdout, &table)?;
+                        writeln!(stdout)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct PowerTableEntry {
+    #[serde(with = "crate::lotus_json")]
+    miner: Address,
+    #[serde(with = "crate::lotus_json")]
+    raw_byte_power: StoragePower,
+    #[serde(with = "crate::lotus_json")]
+    quality_adj_power: StoragePower,
+}
+
+/// Sorts the claims by decreasing quality-adjusted power, then by miner address.
+fn power_table(claims: Vec<(Address, power::Claim)>) -> Vec<PowerTableEntry> {
+    let mut table: Vec<_> = claims
+        .into_iter()
+        .map(|(miner, claim)| PowerTableEntry {
+            miner,
+            raw_byte_power: claim.raw_byte_power,
+            quality_adj_power: claim.quality_adj_power,
+        })
+        .collect();
+    table.sort_by(|a, b| {
+        b.quality_adj_power
+            .cmp(&a.quality_adj_power)
+            .then_with(|| a.miner.cmp(&b.miner))
+    });
+    table
+}
+
+fn write_csv(mut writer: impl std::io::Write, table: &[PowerTableEntry]) -> anyhow::Result<()> {
+    writeln!(writer, "miner,raw_byte_power,quality_adj_power")?;
+    for entry in table {
+        writeln!(
+            writer,
+            "{},{},{}",
+            entry.miner, entry.raw_byte_power, entry.quality_adj_power
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_table_csv() {
+        let claim = |raw: u64, qa: u64| power::Claim {
+            raw_byte_power: raw.into(),
+            quality_adj_power: qa.into(),
+        };
+        let table = power_table(vec![
+            (Address::new_id(1002), claim(10, 10)),
+            (Address::new_id(1001), claim(10, 100)),
+            (Address::new_id(1000), claim(10, 10)),
+        ]);
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &table).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "miner,raw_byte_power,quality_adj_power\n\
+             f01001,10,100\n\
+             f01000,10,10\n\
+             f01002,10,10\n"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use futures::TryStreamExt as _;
+
+use crate::blocks::CachingBlockHeader;
+use crate::message::SignedMessage;
+use crate::shim::message::Message;
+use crate::utils::db::car_stream::CarStream;
+use crate::utils::encoding::roundtrip::roundtrip_block;
+
+#[derive(Debug, Subcommand)]
+pub enum TestCommands {
+    /// Check that the block headers and messages of snapshots round-trip through `CBOR` and
+    /// Lotus JSON
+    Roundtrip {
+        /// Snapshot files. A snapshot can be a plain CAR, a zstd compressed CAR or a
+        /// `.forest.car.zst` file
+        #[arg(required = true)]
+        snapshot_files: Vec<PathBuf>,
+    },
+}
+
+impl TestCommands {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Roundtrip { snapshot_files } => {
+                let mut checked = BTreeMap::<&str, usize>::new();
+                let mut failed = 0;
+                for path in snapshot_files {
+                    let mut stream = CarStream::new_from_path(&path).await?;
+                    while let Some(block) = stream.try_next().await? {
+                        if block.cid.codec() != fvm_ipld_encoding::DAG_CBOR {
+                            continue;
+                        }
+                        match roundtrip_chain_block(&block.data) {
+                            Ok(Some(kind)) => *checked.entry(kind).or_default() += 1,
+                            Ok(None) => {}
+                            Err(e) => {
+                                failed += 1;
+                                println!("FAIL {} in {}: {e:#}", block.cid, path.display());
+                            }
+                        }
+                    }
+                }
+
+                for (kind, count) in checked {
+                    println!("{count} {kind} checked");
+                }
+                anyhow::ensure!(failed == 0, "{failed} round-trip(s) failed");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Checks the round-trips of a block as the chain type it decodes as, and returns the name of
+/// that type. The encodings of the chain types don't overlap, a block decodes as at most one of
+/// them.
+fn roundtrip_chain_block(data: &[u8]) -> anyhow::Result<Option<&'static str>> {
+    if roundtrip_block::<CachingBlockHeader>(data)? {
+        return Ok(Some("block headers"));
+    }
+    if roundtrip_block::<SignedMessage>(data)? {
+        return Ok(Some("signed messages"));
+    }
+    if roundtrip_block::<Message>(data)? {
+        return Ok(Some("messages"));
+    }
+    Ok(None)
+}
@@ -0,0 +1,511 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Runner of the Filecoin conformance test vectors, see
+//! <https://github.com/filecoin-project/test-vectors>. The vectors of the `message` and `tipset`
+//! classes are executed through the interpreter, and their receipts and post-state roots are
+//! compared with the expected ones.
+
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use cid::Cid;
+use clap::Subcommand;
+use fil_actors_shared::fvm_ipld_amt::Amtv0 as Amt;
+use flate2::read::GzDecoder;
+use num::{BigInt, FromPrimitive as _};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use walkdir::WalkDir;
+
+use crate::blocks::{RawBlockHeader, Tipset};
+use crate::chain::index::ChainIndex;
+use crate::daemon::bundle::load_actor_bundles;
+use crate::db::MemoryDB;
+use crate::interpreter::{BlockMessages, ExecutionContext, VM, VMTrace};
+use crate::message::ChainMessage;
+use crate::networks::{ChainConfig, NetworkChain};
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::econ::{TOTAL_FILECOIN, TokenAmount};
+use crate::shim::executor::Receipt;
+use crate::shim::externs::Rand;
+use crate::shim::machine::GLOBAL_MULTI_ENGINE;
+use crate::shim::message::Message;
+use crate::shim::version::NetworkVersion;
+use crate::state_manager::NO_CALLBACK;
+use crate::utils::db::car_util::load_car;
+use crate::utils::encoding::from_slice_with_fallback;
+
+/// Base fee of the message vectors that don't set one, in `attoFIL`. Same as in `ref-fvm`.
+const DEFAULT_BASE_FEE: u64 = 100;
+
+/// Randomness returned for the requests that the vectors did not record. Same as in `ref-fvm`.
+const DEFAULT_RANDOMNESS: [u8; 32] = *b"i_am_random_____i_am_random_____";
+
+#[derive(Debug, Subcommand)]
+pub enum TestVectorsCommands {
+    /// Execute conformance test vectors through the interpreter, and report their divergences
+    /// from the expected receipts and state roots
+    Run {
+        /// Test vector files, or directories that are searched for test vector files
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+impl TestVectorsCommands {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Run { paths } => {
+                // The state trees of the vectors reference the actors of the builtin bundles.
+                let db = Arc::new(MemoryDB::default());
+                load_actor_bundles(&db, &NetworkChain::Mainnet).await?;
+
+                let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+                for path in paths.iter().flat_map(|path| vector_files(path)) {
+                    match run_vector(&db, &path).await {
+                        Ok(outcomes) => {
+                            for (variant, outcome) in outcomes {
+                                let name = format!("{} ({variant})", path.display());
+                                match outcome {
+                                    Outcome::Pass => {
+                                        passed += 1;
+                                        println!("PASS {name}");
+                                    }
+                                    Outcome::Skip(reason) => {
+                                        skipped += 1;
+                                        println!("SKIP {name}: {reason}");
+                                    }
+                                    Outcome::Fail(divergences) => {
+                                        failed += 1;
+                                        println!("FAIL {name}");
+                                        for divergence in divergences {
+                                            println!("  {divergence}");
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            failed += 1;
+                            println!("FAIL {}: {e:#}", path.display());
+                        }
+                    }
+                }
+
+                println!("{passed} passed, {failed} failed, {skipped} skipped");
+                anyhow::ensure!(failed == 0, "{failed} test vector(s) failed");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns the JSON files under a path, in a stable order.
+fn vector_files(path: &Path) -> Vec<PathBuf> {
+    WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "json")
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Pass,
+    Skip(String),
+    Fail(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Class {
+    Message,
+    Tipset,
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestVector {
+    class: Class,
+    #[serde(default)]
+    selector: Option<ahash::HashMap<String, String>>,
+    /// Gzipped CAR of the pre- and post-state trees.
+    #[serde(with = "crate::lotus_json::base64_standard")]
+    car: Vec<u8>,
+    preconditions: Preconditions,
+    #[serde(default)]
+    apply_messages: Vec<ApplyMessage>,
+    #[serde(default)]
+    apply_tipsets: Vec<ApplyTipset>,
+    postconditions: Postconditions,
+    #[serde(default)]
+    randomness: Vec<RandomnessMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateTree {
+    #[serde(with = "crate::lotus_json")]
+    root_cid: Cid,
+}
+
+#[derive(Debug, Deserialize)]
+struct Preconditions {
+    state_tree: StateTree,
+    basefee: Option<f64>,
+    circ_supply: Option<f64>,
+    #[serde(default)]
+    variants: Vec<Variant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Variant {
+    id: String,
+    epoch: ChainEpoch,
+    nv: NetworkVersion,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApplyMessage {
+    #[serde(with = "crate::lotus_json")]
+    bytes: Vec<u8>,
+    epoch_offset: Option<ChainEpoch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApplyTipset {
+    epoch_offset: ChainEpoch,
+    basefee: f64,
+    blocks: Vec<Block>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Block {
+    #[serde(with = "crate::lotus_json")]
+    miner_addr: Address,
+    win_count: i64,
+    #[serde(with = "crate::lotus_json")]
+    messages: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Postconditions {
+    state_tree: StateTree,
+    #[serde(default)]
+    receipts: Vec<ExpectedReceipt>,
+    #[serde(default, with = "crate::lotus_json")]
+    receipts_roots: Option<Vec<Cid>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpectedReceipt {
+    exit_code: u32,
+    #[serde(rename = "return", with = "crate::lotus_json")]
+    return_data: Vec<u8>,
+    gas_used: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RandomnessKind {
+    Chain,
+    Beacon,
+}
+
+/// A recorded randomness request. The domain separation tag and the entropy are mixed in by
+/// the FVM, only the kind and the epoch of the request select the recorded value.
+#[derive(Debug, Clone, Deserialize)]
+struct RandomnessMatch {
+    on: (RandomnessKind, IgnoredAny, ChainEpoch, IgnoredAny),
+    #[serde(with = "crate::lotus_json::base64_standard")]
+    ret: Vec<u8>,
+}
+
+/// Replays the randomness recorded in a vector.
+struct ReplayingRand(Vec<RandomnessMatch>);
+
+impl ReplayingRand {
+    fn lookup(&self, kind: RandomnessKind, round: ChainEpoch) -> anyhow::Result<[u8; 32]> {
+        match self.0.iter().find(|m| m.on.0 == kind && m.on.2 == round) {
+            Some(m) => m.ret.as_slice().try_into().with_context(|| {
+                format!("recorded {kind:?} randomness at epoch {round} is not 32 bytes")
+            }),
+            None => Ok(DEFAULT_RANDOMNESS),
+        }
+    }
+}
+
+impl Rand for ReplayingRand {
+    fn get_chain_randomness(&self, round: ChainEpoch) -> anyhow::Result<[u8; 32]> {
+        self.lookup(RandomnessKind::Chain, round)
+    }
+
+    fn get_beacon_randomness(&self, round: ChainEpoch) -> anyhow::Result<[u8; 32]> {
+        self.lookup(RandomnessKind::Beacon, round)
+    }
+}
+
+/// Converts a token amount of a vector, which is encoded as a JSON number, like `ref-fvm` does.
+fn token_amount(value: f64) -> anyhow::Result<TokenAmount> {
+    BigInt::from_f64(value)
+        .map(TokenAmount::from_atto)
+        .with_context(|| format!("invalid token amount {value}"))
+}
+
+/// The vectors carry no chain, a synthetic tipset at the epoch of the execution stands in for
+/// the head.
+fn synthetic_tipset(epoch: ChainEpoch, state_root: Cid) -> Tipset {
+    Tipset::from(RawBlockHeader {
+        miner_address: Address::new_id(0),
+        ticket: None,
+        election_proof: None,
+        beacon_entries: vec![],
+        winning_post_proof: vec![],
+        parents: nunny::vec![Cid::default()].into(),
+        weight: BigInt::default(),
+        epoch,
+        state_root,
+        message_receipts: Cid::default(),
+        messages: Cid::default(),
+        bls_aggregate: None,
+        timestamp: 0,
+        signature: None,
+        fork_signal: 0,
+        parent_base_fee: TokenAmount::default(),
+    })
+}
+
+async fn run_vector(db: &Arc<MemoryDB>, path: &Path) -> anyhow::Result<Vec<(String, Outcome)>> {
+    let vector: TestVector =
+        serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(path)?))
+            .context("invalid test vector")?;
+
+    let skip = |reason: &str| {
+        let variants = vector.preconditions.variants.iter().map(|v| v.id.clone());
+        Ok(variants
+            .map(|id| (id, Outcome::Skip(reason.into())))
+            .collect())
+    };
+    if matches!(vector.class, Class::Unsupported) {
+        return skip("unsupported vector class");
+    }
+    if vector
+        .selector
+        .as_ref()
+        .is_some_and(|selector| selector.get("chaos_actor").is_some_and(|v| v == "true"))
+    {
+        return skip("requires the chaos actor");
+    }
+
+    let mut car = vec![];
+    GzDecoder::new(vector.car.as_slice())
+        .read_to_end(&mut car)
+        .context("invalid vector CAR")?;
+    load_car(db.as_ref(), std::io::Cursor::new(car)).await?;
+
+    let mut outcomes = vec![];
+    for variant in &vector.preconditions.variants {
+        let outcome = if variant.nv < NetworkVersion::V16 {
+            Outcome::Skip(format!("network version {} predates the FVM", variant.nv))
+        } else {
+            match run_variant(db, &vector, variant) {
+                Ok(divergences) if divergences.is_empty() => Outcome::Pass,
+                Ok(divergences) => Outcome::Fail(divergences),
+                Err(e) => Outcome::Fail(vec![format!("{e:#}")]),
+            }
+        };
+        outcomes.push((variant.id.clone(), outcome));
+    }
+    Ok(outcomes)
+}
+
+/// Executes a variant of a vector, and returns its divergences from the postconditions.
+fn run_variant(
+    db: &Arc<MemoryDB>,
+    vector: &TestVector,
+    variant: &Variant,
+) -> anyhow::Result<Vec<String>> {
+    let chain_config = Arc::new(ChainConfig {
+        genesis_network: variant.nv,
+        height_infos: Default::default(),
+        ..ChainConfig::mainnet()
+    });
+    let chain_index = Arc::new(ChainIndex::new(db.clone()));
+    let circ_supply = match vector.preconditions.circ_supply {
+        Some(circ_supply) => token_amount(circ_supply)?,
+        None => TOTAL_FILECOIN.clone(),
+    };
+    let create_vm = |state_tree_root, epoch, base_fee| {
+        VM::new(
+            ExecutionContext {
+                heaviest_tipset: synthetic_tipset(epoch, state_tree_root),
+                state_tree_root,
+                epoch,
+                rand: Box::new(ReplayingRand(vector.randomness.clone())),
+                base_fee,
+                circ_supply: circ_supply.clone(),
+                chain_config: chain_config.clone(),
+                chain_index: chain_index.clone(),
+                timestamp: 0,
+            },
+            &GLOBAL_MULTI_ENGINE,
+            VMTrace::NotTraced,
+        )
+    };
+
+    let mut state_root = vector.preconditions.state_tree.root_cid;
+    let mut receipts: Vec<Receipt> = vec![];
+    let mut receipts_roots = vec![];
+    match vector.class {
+        Class::Message => {
+            let base_fee = match vector.preconditions.basefee {
+                Some(base_fee) => token_amount(base_fee)?,
+                None => TokenAmount::from_atto(DEFAULT_BASE_FEE),
+            };
+            for apply in &vector.apply_messages {
+                let message: Message = from_slice_with_fallback(&apply.bytes)?;
+                let epoch = variant.epoch + apply.epoch_offset.unwrap_or_default();
+                let (receipt, root) = stacker::grow(64 << 20, || -> anyhow::Result<_> {
+                    let mut vm = create_vm(state_root, epoch, base_fee.clone())?;
+                    let (ret, _) = vm.apply_message(&ChainMessage::Unsigned(message))?;
+                    Ok((ret.msg_receipt(), vm.flush()?))
+                })?;
+                receipts.push(receipt);
+                state_root = root;
+            }
+        }
+        Class::Tipset => {
+            let mut parent_epoch = variant.epoch;
+            for tipset in &vector.apply_tipsets {
+                let epoch = variant.epoch + tipset.epoch_offset;
+                let base_fee = token_amount(tipset.basefee)?;
+                let block_messages = tipset
+                    .blocks
+                    .iter()
+                    .map(|block| {
+                        Ok(BlockMessages {
+                            miner: block.miner_addr,
+                            messages: block
+                                .messages
+                                .iter()
+                                .map(|bytes| from_slice_with_fallback(bytes))
+                                .collect::<Result<_, _>>()?,
+                            win_count: block.win_count,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let (tipset_receipts, root) = stacker::grow(64 << 20, || -> anyhow::Result<_> {
+                    let mut root = state_root;
+                    // Only cron runs at the null rounds since the parent.
+                    for null_epoch in parent_epoch + 1..epoch {
+                        let mut vm = create_vm(root, null_epoch, base_fee.clone())?;
+                        vm.run_cron(null_epoch, NO_CALLBACK)?;
+                        root = vm.flush()?;
+                    }
+                    let mut vm = create_vm(root, epoch, base_fee.clone())?;
+                    let (receipts, ..) =
+                        vm.apply_block_messages(&block_messages, epoch, NO_CALLBACK)?;
+                    Ok((receipts, vm.flush()?))
+                })?;
+                receipts_roots.push(Amt::new_from_iter(db.as_ref(), tipset_receipts.clone())?);
+                receipts.extend(tipset_receipts);
+                state_root = root;
+                parent_epoch = epoch;
+            }
+        }
+        Class::Unsupported => unreachable!("unsupported vectors are skipped"),
+    }
+
+    Ok(divergences(
+        &vector.postconditions,
+        &receipts,
+        &receipts_roots,
+        state_root,
+    ))
+}
+
+fn divergences(
+    expected: &Postconditions,
+    receipts: &[Receipt],
+    receipts_roots: &[Cid],
+    state_root: Cid,
+) -> Vec<String> {
+    let mut divergences = vec![];
+    if receipts.len() != expected.receipts.len() {
+        divergences.push(format!(
+            "expected {} receipts, got {}",
+            expected.receipts.len(),
+            receipts.len()
+        ));
+    }
+    for (i, (actual, expected)) in receipts.iter().zip(&expected.receipts).enumerate() {
+        if actual.exit_code().value() != expected.exit_code {
+            divergences.push(format!(
+                "receipt {i}: expected exit code {}, got {}",
+                expected.exit_code,
+                actual.exit_code().value()
+            ));
+        }
+        if actual.return_data().bytes() != expected.return_data.as_slice() {
+            divergences.push(format!(
+                "receipt {i}: expected return data 0x{}, got 0x{}",
+                hex::encode(&expected.return_data),
+                hex::encode(actual.return_data().bytes())
+            ));
+        }
+        if actual.gas_used() != expected.gas_used {
+            divergences.push(format!(
+                "receipt {i}: expected {} gas used, got {}",
+                expected.gas_used,
+                actual.gas_used()
+            ));
+        }
+    }
+    if let Some(expected_roots) = &expected.receipts_roots {
+        for (i, (actual, expected)) in receipts_roots.iter().zip(expected_roots).enumerate() {
+            if actual != expected {
+                divergences.push(format!(
+                    "tipset {i}: expected receipts root {expected}, got {actual}"
+                ));
+            }
+        }
+    }
+    if state_root != expected.state_tree.root_cid {
+        divergences.push(format!(
+            "expected state root {}, got {state_root}",
+            expected.state_tree.root_cid
+        ));
+    }
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, prelude::BASE64_STANDARD};
+
+    #[test]
+    fn replaying_rand() {
+        let randomness: Vec<RandomnessMatch> = serde_json::from_value(serde_json::json!([
+            { "on": ["chain", 1, 10, "AAAA"], "ret": BASE64_STANDARD.encode([1; 32]) },
+            { "on": ["beacon", 1, 10, "AAAA"], "ret": BASE64_STANDARD.encode([2; 32]) },
+            { "on": ["chain", 1, 20, null], "ret": BASE64_STANDARD.encode([3; 16]) },
+        ]))
+        .unwrap();
+        let rand = ReplayingRand(randomness);
+        assert_eq!(rand.get_chain_randomness(10).unwrap(), [1; 32]);
+        assert_eq!(rand.get_beacon_randomness(10).unwrap(), [2; 32]);
+        // Unrecorded requests get the default randomness.
+        assert_eq!(rand.get_beacon_randomness(11).unwrap(), DEFAULT_RANDOMNESS);
+        // Malformed recorded randomness is an error.
+        assert!(rand.get_chain_randomness(20).is_err());
+    }
+}
@@ -18,7 +18,7 @@ use parking_lot::RwLock;
 use prometheus_client::{
     collector::Collector,
     encoding::{DescriptorEncoder, EncodeMetric},
-    metrics::gauge::Gauge,
+    metrics::{counter::Counter, gauge::Gauge},
     registry::Unit,
 };
 
@@ -45,6 +45,8 @@ where
     cache_id: usize,
     cache_name: Cow<'static, str>,
     cache: Arc<RwLock<LruCache<K, V>>>,
+    /// Number of entries evicted to make room for new ones
+    evictions: Counter,
 }
 
 impl<K, V> SizeTrackingLruCache<K, V>
@@ -62,6 +64,7 @@ where
         Self {
             cache_id: ID_GENERATOR.fetch_add(1, Ordering::Relaxed),
             cache_name,
+            evictions: Default::default(),
             #[allow(clippy::disallowed_methods)]
             cache: Arc::new(RwLock::new(
                 capacity
@@ -101,7 +104,15 @@ where
     }
 
     pub fn push(&self, k: K, v: V) -> Option<V> {
-        self.cache.write().insert(k, v)
+        let mut cache = self.cache.write();
+        if !cache.contains_key(&k) && cache.len() >= cache.capacity() {
+            self.evictions.inc();
+        }
+        cache.insert(k, v)
+    }
+
+    pub fn evictions(&self) -> u64 {
+        self.evictions.get()
     }
 
     pub fn contains<Q>(&self, k: &Q) -> bool
@@ -209,7 +220,46 @@ where
             )?;
             cap.encode(cap_metric_encoder)?;
         }
+        {
+            let evictions_metric_name = format!("{}_{}_evictions", self.cache_name, self.cache_id);
+            let evictions_metric_help = format!(
+                "Number of entries evicted from LruCache {}_{}",
+                self.cache_name, self.cache_id
+            );
+            let evictions_metric_encoder = encoder.encode_descriptor(
+                &evictions_metric_name,
+                &evictions_metric_help,
+                None,
+                self.evictions.metric_type(),
+            )?;
+            self.evictions.encode(evictions_metric_encoder)?;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nonzero_ext::nonzero;
+
+    #[test]
+    fn count_evictions() {
+        let cache = SizeTrackingLruCache::<u64, u64>::new_without_metrics_registry(
+            "test".into(),
+            nonzero!(2_usize),
+        );
+        cache.push(1, 1);
+        cache.push(2, 2);
+        // Replacing an entry does not evict anything
+        cache.push(2, 3);
+        assert_eq!(cache.evictions(), 0);
+        cache.push(3, 3);
+        assert_eq!(cache.evictions(), 1);
+        assert!(!cache.contains(&1));
+        // The counter is shared by the clones registered as metric collectors
+        cache.clone().push(4, 4);
+        assert_eq!(cache.evictions(), 2);
+    }
+}
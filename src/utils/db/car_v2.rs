@@ -0,0 +1,227 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! # CARv2 indexes
+//!
+//! CARv2 files wrap a CARv1 payload between a fixed-size header and an optional
+//! index of the blocks of the payload. The index maps the multihash digests of
+//! the blocks to the offsets of their varint frames in the payload.
+//!
+//! ```text
+//! ┌──────┬─────────┬──────────────┬─────┐
+//! │pragma│v2 header│CARv1 payload │index│
+//! └──────┴─────────┴──────────────┴─────┘
+//! ```
+//!
+//! Only the `IndexSorted` and `MultihashIndexSorted` index formats are
+//! supported, the latter being the one written by `go-car`.
+//!
+//! See <https://ipld.io/specs/transport/car/carv2/>
+
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind::InvalidData, Read, SeekFrom};
+use std::pin::pin;
+
+use byteorder::{LittleEndian, ReadBytesExt as _, WriteBytesExt as _};
+use bytes::{BufMut as _, Bytes, BytesMut};
+use cid::Cid;
+use futures::{Stream, TryStreamExt as _};
+use integer_encoding::{VarInt as _, VarIntReader as _};
+use nunny::Vec as NonEmpty;
+use tokio::io::{AsyncSeek, AsyncSeekExt as _, AsyncWrite, AsyncWriteExt as _};
+use tokio_util::codec::Encoder as _;
+
+use super::car_stream::{CarBlock, CarV1Header, CarV2Header, uvi_bytes};
+
+/// <https://ipld.io/specs/transport/car/carv2/#pragma>, without its length prefix
+pub const CAR_V2_PRAGMA: [u8; 10] = [0xa1, 0x67, 0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x02];
+/// Length of the pragma and of the header, i.e. the offset of the CARv1 payload
+/// written by [`write_car_v2`]
+const CAR_V2_DATA_OFFSET: u64 = 1 + CAR_V2_PRAGMA.len() as u64 + 40;
+/// <https://ipld.io/specs/transport/car/carv2/#format-0x0400-indexsorted>
+const INDEX_SORTED: u64 = 0x0400;
+/// <https://ipld.io/specs/transport/car/carv2/#format-0x0401-multihashindexsorted>
+const MULTIHASH_INDEX_SORTED: u64 = 0x0401;
+
+/// Reads the offsets of the block frames in the CARv1 payload from a CARv2
+/// index, or returns `None` if the index format is not supported.
+///
+/// Note that `go-car` does not index the blocks with identity CIDs by default.
+pub fn read_index_offsets(mut reader: impl Read) -> io::Result<Option<Vec<u64>>> {
+    let codec: u64 = reader.read_varint()?;
+    let mut offsets = vec![];
+    match codec {
+        INDEX_SORTED => read_sorted_offsets(&mut reader, &mut offsets)?,
+        MULTIHASH_INDEX_SORTED => {
+            let num_codes = read_count(&mut reader)?;
+            for _ in 0..num_codes {
+                let _multihash_code = reader.read_u64::<LittleEndian>()?;
+                read_sorted_offsets(&mut reader, &mut offsets)?;
+            }
+        }
+        _ => return Ok(None),
+    }
+    Ok(Some(offsets))
+}
+
+/// Reads the buckets of an `IndexSorted` index, one per digest width.
+fn read_sorted_offsets(mut reader: impl Read, offsets: &mut Vec<u64>) -> io::Result<()> {
+    let num_buckets = read_count(&mut reader)?;
+    for _ in 0..num_buckets {
+        // Width of the entries, the digest followed by the offset
+        let width = u64::from(reader.read_u32::<LittleEndian>()?);
+        let len = u64::try_from(reader.read_i64::<LittleEndian>()?)
+            .map_err(|e| io::Error::new(InvalidData, e))?;
+        if width <= 8 || len % width != 0 {
+            return Err(io::Error::new(
+                InvalidData,
+                format!("invalid index bucket of width {width} and length {len}"),
+            ));
+        }
+        let mut digest = vec![0; usize::try_from(width - 8).map_err(io::Error::other)?];
+        for _ in 0..len / width {
+            reader.read_exact(&mut digest)?;
+            offsets.push(reader.read_u64::<LittleEndian>()?);
+        }
+    }
+    Ok(())
+}
+
+fn read_count(mut reader: impl Read) -> io::Result<u32> {
+    u32::try_from(reader.read_i32::<LittleEndian>()?).map_err(|e| io::Error::new(InvalidData, e))
+}
+
+/// Writes the blocks to a CARv2 file with a `MultihashIndexSorted` index, for
+/// interoperability with tools that expect indexed CAR files, e.g. IPFS. The
+/// header is written last, as it holds the length of the payload.
+pub async fn write_car_v2(
+    roots: NonEmpty<Cid>,
+    blocks: impl Stream<Item = io::Result<CarBlock>>,
+    mut writer: impl AsyncWrite + AsyncSeek + Unpin,
+) -> io::Result<()> {
+    let start = writer.stream_position().await?;
+    // Reserve the space of the header
+    writer.write_all(&[0; CAR_V2_DATA_OFFSET as usize]).await?;
+
+    let mut header_frame = BytesMut::new();
+    uvi_bytes().encode(
+        Bytes::from(fvm_ipld_encoding::to_vec(&CarV1Header {
+            roots,
+            version: 1,
+        })?),
+        &mut header_frame,
+    )?;
+    writer.write_all(&header_frame).await?;
+    let mut data_size = header_frame.len() as u64;
+
+    // Digests of the blocks and the offsets of their frames, by multihash code
+    // and digest length.
+    let mut index: BTreeMap<u64, BTreeMap<usize, Vec<(Vec<u8>, u64)>>> = BTreeMap::new();
+    let mut frame = BytesMut::new().writer();
+    let mut blocks = pin!(blocks);
+    while let Some(block) = blocks.try_next().await? {
+        let hash = block.cid.hash();
+        index
+            .entry(hash.code())
+            .or_default()
+            .entry(hash.digest().len())
+            .or_default()
+            .push((hash.digest().to_vec(), data_size));
+        frame.get_mut().clear();
+        block.write(&mut frame)?;
+        writer.write_all(frame.get_ref()).await?;
+        data_size += frame.get_ref().len() as u64;
+    }
+    writer.write_all(&encode_index(index)?).await?;
+    let end = writer.stream_position().await?;
+
+    let header = CarV2Header {
+        characteristics: [0; 16],
+        data_offset: CAR_V2_DATA_OFFSET as i64,
+        data_size: data_size as i64,
+        index_offset: (CAR_V2_DATA_OFFSET + data_size) as i64,
+    };
+    writer.seek(SeekFrom::Start(start)).await?;
+    writer.write_all(&encode_header(&header)?).await?;
+    writer.seek(SeekFrom::Start(end)).await?;
+    writer.flush().await
+}
+
+/// <https://ipld.io/specs/transport/car/carv2/#header>
+fn encode_header(header: &CarV2Header) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(CAR_V2_DATA_OFFSET as usize);
+    bytes.push(CAR_V2_PRAGMA.len() as u8);
+    bytes.extend_from_slice(&CAR_V2_PRAGMA);
+    bytes.extend_from_slice(&header.characteristics);
+    bytes.write_i64::<LittleEndian>(header.data_offset)?;
+    bytes.write_i64::<LittleEndian>(header.data_size)?;
+    bytes.write_i64::<LittleEndian>(header.index_offset)?;
+    Ok(bytes)
+}
+
+/// Encodes a `MultihashIndexSorted` index, with the codes and the buckets
+/// sorted, and the entries of each bucket sorted by digest.
+fn encode_index(index: BTreeMap<u64, BTreeMap<usize, Vec<(Vec<u8>, u64)>>>) -> io::Result<Vec<u8>> {
+    let mut bytes = MULTIHASH_INDEX_SORTED.encode_var_vec();
+    bytes.write_i32::<LittleEndian>(index.len() as i32)?;
+    for (code, buckets) in index {
+        bytes.write_u64::<LittleEndian>(code)?;
+        bytes.write_i32::<LittleEndian>(buckets.len() as i32)?;
+        for (digest_len, mut entries) in buckets {
+            entries.sort_unstable();
+            let width = digest_len + 8;
+            bytes.write_u32::<LittleEndian>(width as u32)?;
+            bytes.write_i64::<LittleEndian>((width * entries.len()) as i64)?;
+            for (digest, offset) in entries {
+                bytes.extend_from_slice(&digest);
+                bytes.write_u64::<LittleEndian>(offset)?;
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_on;
+    use crate::db::car::PlainCar;
+    use crate::utils::db::car_stream::CarStream;
+    use fvm_ipld_blockstore::Blockstore as _;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn car_v2_roundtrip(blocks: nunny::Vec<CarBlock>) -> anyhow::Result<()> {
+        let roots = nunny::vec![blocks.first().cid];
+        let mut car = io::Cursor::new(vec![]);
+        block_on(write_car_v2(
+            roots.clone(),
+            futures::stream::iter(blocks.clone().into_iter().map(Ok)),
+            &mut car,
+        ))?;
+        let car = car.into_inner();
+
+        // The embedded index covers all the blocks
+        let header_v2 = crate::db::car::plain::read_v2_header(car.as_slice())?
+            .expect("a CARv2 header is written");
+        let index_offset = usize::try_from(header_v2.index_offset)?;
+        let offsets = read_index_offsets(&car[index_offset..])?.expect("a supported index");
+        assert_eq!(offsets.len(), blocks.len());
+
+        let plain = PlainCar::new(car.clone())?;
+        assert_eq!(plain.version(), 2);
+        assert_eq!(plain.head_tipset_key(), &roots);
+        for block in &blocks {
+            assert_eq!(plain.get(&block.cid)?.as_ref(), Some(&block.data));
+        }
+
+        let streamed: Vec<CarBlock> = block_on(async {
+            CarStream::new(io::Cursor::new(car))
+                .await?
+                .try_collect()
+                .await
+        })?;
+        assert_eq!(streamed, blocks.into_iter().collect::<Vec<_>>());
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod car_stream;
 pub mod car_util;
+pub mod car_v2;
 
 use crate::utils::multihash::prelude::*;
 use anyhow::Context as _;
@@ -0,0 +1,204 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Structural limits of untrusted CBOR input. The `serde` decoders are recursive, so a deeply
+//! nested item from a malicious peer could exhaust the stack. [`check_cbor_limits`] walks the
+//! item iteratively and rejects it before it is decoded.
+
+use std::sync::LazyLock;
+
+use anyhow::{bail, ensure};
+
+/// Default maximum nesting depth of arrays and maps. Filecoin data structures are shallow, a
+/// HAMT or AMT node for instance is nested only a few levels deep.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 128;
+
+/// Maximum nesting depth of arrays and maps, can be overridden with the
+/// `FOREST_CBOR_MAX_NESTING_DEPTH` environment variable.
+pub static MAX_NESTING_DEPTH: LazyLock<usize> = LazyLock::new(|| {
+    std::env::var("FOREST_CBOR_MAX_NESTING_DEPTH")
+        .ok()
+        .and_then(|it| it.parse().ok())
+        .unwrap_or(DEFAULT_MAX_NESTING_DEPTH)
+});
+
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+const INDEFINITE: u8 = 31;
+
+/// Checks that the first CBOR item of `bytes` nests arrays and maps at most `max_depth` levels
+/// deep, and that the declared lengths of its strings, arrays and maps fit in the input, so
+/// that a decoder neither recurses nor pre-allocates beyond the size of the input. Trailing
+/// bytes are left to the decoder.
+pub fn check_cbor_limits(bytes: &[u8], max_depth: usize) -> anyhow::Result<()> {
+    let mut reader = Reader { bytes, pos: 0 };
+    // The number of items left in every open array or map, `None` for indefinite lengths.
+    let mut open: Vec<Option<u64>> = Vec::new();
+    loop {
+        let (major, info) = reader.header()?;
+        match major {
+            MAJOR_SIMPLE if info == INDEFINITE => {
+                let Some(None) = open.pop() else {
+                    bail!("unexpected break at offset {}", reader.pos - 1);
+                };
+            }
+            MAJOR_BYTES | MAJOR_TEXT => {
+                let Some(len) = reader.argument(info)? else {
+                    bail!("indefinite-length strings are not supported");
+                };
+                reader.skip(len)?;
+            }
+            MAJOR_ARRAY | MAJOR_MAP => {
+                let items = match reader.argument(info)? {
+                    Some(len) if major == MAJOR_MAP => Some(
+                        len.checked_mul(2)
+                            .ok_or_else(|| anyhow::anyhow!("map length {len} overflows"))?,
+                    ),
+                    len => len,
+                };
+                if items != Some(0) {
+                    ensure!(
+                        open.len() < max_depth,
+                        "nesting depth exceeds the maximum of {max_depth}"
+                    );
+                    if let Some(items) = items {
+                        // Every item takes at least one byte.
+                        ensure!(
+                            items <= reader.remaining(),
+                            "{items} items declared at offset {} but only {} bytes left",
+                            reader.pos,
+                            reader.remaining()
+                        );
+                    }
+                    open.push(items);
+                    continue;
+                }
+            }
+            // The tagged item follows.
+            MAJOR_TAG => {
+                reader.argument(info)?;
+                continue;
+            }
+            // Integers and simple values
+            _ => {
+                reader.argument(info)?;
+            }
+        }
+        // An item is complete, close the arrays and maps it completes.
+        loop {
+            match open.last_mut() {
+                None => return Ok(()),
+                Some(Some(items)) => {
+                    *items -= 1;
+                    if *items > 0 {
+                        break;
+                    }
+                    open.pop();
+                }
+                Some(None) => break,
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn remaining(&self) -> u64 {
+        (self.bytes.len() - self.pos) as u64
+    }
+
+    fn read<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let Some(bytes) = self.bytes.get(self.pos..self.pos + N) else {
+            bail!("unexpected end of input at offset {}", self.pos);
+        };
+        self.pos += N;
+        Ok(bytes.try_into()?)
+    }
+
+    fn skip(&mut self, n: u64) -> anyhow::Result<()> {
+        ensure!(
+            n <= self.remaining(),
+            "{n} bytes declared at offset {} but only {} bytes left",
+            self.pos,
+            self.remaining()
+        );
+        self.pos += n as usize;
+        Ok(())
+    }
+
+    /// Reads the major type and the additional information of an item.
+    fn header(&mut self) -> anyhow::Result<(u8, u8)> {
+        let [byte] = self.read()?;
+        Ok((byte >> 5, byte & 0x1f))
+    }
+
+    /// Reads the argument of an item, `None` for indefinite lengths.
+    fn argument(&mut self, info: u8) -> anyhow::Result<Option<u64>> {
+        Ok(Some(match info {
+            0..=23 => info.into(),
+            24 => u8::from_be_bytes(self.read()?).into(),
+            25 => u16::from_be_bytes(self.read()?).into(),
+            26 => u32::from_be_bytes(self.read()?).into(),
+            27 => u64::from_be_bytes(self.read()?),
+            INDEFINITE => return Ok(None),
+            _ => bail!("invalid additional information {info}"),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::encoding::from_slice_with_fallback;
+    use ipld_core::ipld::Ipld;
+
+    fn nested_arrays(depth: usize) -> Vec<u8> {
+        let mut bytes = vec![0x81; depth];
+        bytes.push(0x01);
+        bytes
+    }
+
+    #[test]
+    fn nesting_depth() {
+        assert!(check_cbor_limits(&nested_arrays(128), 128).is_ok());
+        assert!(check_cbor_limits(&nested_arrays(129), 128).is_err());
+        // Indefinite-length arrays count as well.
+        let mut indefinite = vec![0x9f; 4];
+        indefinite.extend([0xff; 4]);
+        assert!(check_cbor_limits(&indefinite, 4).is_ok());
+        assert!(check_cbor_limits(&indefinite, 3).is_err());
+        // Empty arrays and maps do not nest.
+        assert!(check_cbor_limits(&[0x82, 0x80, 0xa0], 1).is_ok());
+
+        // Deep enough to overflow the stack of a recursive decoder.
+        let err = from_slice_with_fallback::<Ipld>(&nested_arrays(1_000_000)).unwrap_err();
+        assert!(err.to_string().contains("nesting depth"), "{err}");
+    }
+
+    #[test]
+    fn declared_lengths() {
+        let value = Ipld::List(vec![
+            Ipld::Map([("a".to_owned(), Ipld::Bytes(vec![1, 2, 3]))].into()),
+            Ipld::Link(cid::Cid::default()),
+            Ipld::Float(1.5),
+            Ipld::Integer(-1_000_000),
+        ]);
+        let bytes = serde_ipld_dagcbor::to_vec(&value).unwrap();
+        assert!(check_cbor_limits(&bytes, 2).is_ok());
+        assert!(check_cbor_limits(&bytes[..bytes.len() - 1], 2).is_err());
+
+        // An array of 2^32 items, a byte string of 2^32 bytes
+        assert!(check_cbor_limits(&[0x9a, 0xff, 0xff, 0xff, 0xff, 0x01], 128).is_err());
+        assert!(check_cbor_limits(&[0x5a, 0xff, 0xff, 0xff, 0xff, 0x01], 128).is_err());
+        // Stray break
+        assert!(check_cbor_limits(&[0x81, 0xff], 128).is_err());
+    }
+}
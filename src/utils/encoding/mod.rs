@@ -7,7 +7,9 @@ use fil_actors_shared::filecoin_proofs_api::ProverId;
 use fvm_ipld_encoding::strict_bytes::{Deserialize, Serialize};
 use serde::{Deserializer, Serializer, de, ser};
 
+mod cbor_limits;
 mod fallback_de_ipld_dagcbor;
+pub use cbor_limits::{MAX_NESTING_DEPTH, check_cbor_limits};
 
 /// Limit the the number of bytes that are used for pre-allocating `Vec<Cid>`s. This follows what `serde` is
 /// doing internally with `serde::private::size_hint::cautious()`.
@@ -23,9 +25,21 @@ fn size_hint_cautious_cid(size_hint: usize) -> usize {
 /// support strings with invalid UTF-8 bytes. On a failure, it
 /// will retry the operation using the fallback that will de-serialize
 /// strings with invalid UTF-8 bytes as bytes.
+///
+/// The input is checked with [`check_cbor_limits`] against [`MAX_NESTING_DEPTH`] first, so it is
+/// safe to call on untrusted input.
 pub fn from_slice_with_fallback<'a, T: serde::de::Deserialize<'a>>(
     bytes: &'a [u8],
 ) -> anyhow::Result<T> {
+    from_slice_with_fallback_and_max_depth(bytes, *MAX_NESTING_DEPTH)
+}
+
+/// See [`from_slice_with_fallback`], with an explicit maximum nesting depth.
+pub fn from_slice_with_fallback_and_max_depth<'a, T: serde::de::Deserialize<'a>>(
+    bytes: &'a [u8],
+    max_depth: usize,
+) -> anyhow::Result<T> {
+    check_cbor_limits(bytes, max_depth)?;
     match serde_ipld_dagcbor::from_slice(bytes) {
         Ok(v) => Ok(v),
         Err(err) => fallback_de_ipld_dagcbor::from_slice(bytes).map_err(|fallback_err| {
@@ -39,6 +53,8 @@ pub fn from_slice_with_fallback<'a, T: serde::de::Deserialize<'a>>(
 mod cid_de_cbor;
 pub use cid_de_cbor::extract_cids;
 
+pub mod roundtrip;
+
 /// `serde_bytes` with max length check
 pub mod serde_byte_array {
     use super::*;
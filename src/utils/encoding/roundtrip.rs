@@ -0,0 +1,167 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Round-trip checks of the encodings of the chain types, `CBOR` ↔ struct ↔ Lotus JSON. They run
+//! on random values in the unit tests, and on the blocks of snapshots with
+//! `forest-tool test roundtrip`.
+
+use std::any::type_name;
+use std::fmt::Debug;
+
+use anyhow::ensure;
+use serde::{Serialize, de::DeserializeOwned};
+
+use super::from_slice_with_fallback;
+use crate::lotus_json::HasLotusJson;
+
+/// Checks that a value is unchanged by a `CBOR` round-trip, and that it is re-encoded to the
+/// same bytes.
+pub fn cbor_roundtrip<T>(value: &T) -> anyhow::Result<()>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let bytes = fvm_ipld_encoding::to_vec(value)?;
+    let decoded: T = from_slice_with_fallback(&bytes)?;
+    ensure!(
+        &decoded == value,
+        "CBOR round-trip of {} changed {value:?} into {decoded:?}",
+        type_name::<T>()
+    );
+    ensure!(
+        fvm_ipld_encoding::to_vec(&decoded)? == bytes,
+        "CBOR re-encoding of {} differs",
+        type_name::<T>()
+    );
+    Ok(())
+}
+
+/// Checks that a value is unchanged by a Lotus JSON round-trip, and that it is re-encoded to the
+/// same JSON.
+pub fn lotus_json_roundtrip<T>(value: &T) -> anyhow::Result<()>
+where
+    T: HasLotusJson + Clone + PartialEq + Debug,
+{
+    let json = value.clone().into_lotus_json_value()?;
+    let decoded = T::from_lotus_json(serde_json::from_value(json.clone())?);
+    ensure!(
+        &decoded == value,
+        "Lotus JSON round-trip of {} changed {value:?} into {decoded:?}",
+        type_name::<T>()
+    );
+    ensure!(
+        decoded.into_lotus_json_value()? == json,
+        "Lotus JSON re-encoding of {} differs: {json}",
+        type_name::<T>()
+    );
+    Ok(())
+}
+
+/// Checks both the `CBOR` and the Lotus JSON round-trips of a value.
+pub fn roundtrip<T>(value: &T) -> anyhow::Result<()>
+where
+    T: Serialize + DeserializeOwned + HasLotusJson + Clone + PartialEq + Debug,
+{
+    cbor_roundtrip(value)?;
+    lotus_json_roundtrip(value)
+}
+
+/// Decodes a `CBOR` block as a `T`, and checks that it is re-encoded to the same bytes and that
+/// it round-trips. Returns `false` if the block is not a `T`.
+pub fn roundtrip_block<T>(data: &[u8]) -> anyhow::Result<bool>
+where
+    T: Serialize + DeserializeOwned + HasLotusJson + Clone + PartialEq + Debug,
+{
+    let Ok(value) = from_slice_with_fallback::<T>(data) else {
+        return Ok(false);
+    };
+    ensure!(
+        fvm_ipld_encoding::to_vec(&value)? == data,
+        "CBOR re-encoding of {} differs from the block",
+        type_name::<T>()
+    );
+    roundtrip(&value)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{CachingBlockHeader, FullTipset, GossipBlock};
+    use crate::libp2p::chain_exchange::{CompactedMessages, TipsetBundle};
+    use crate::message::SignedMessage;
+    use crate::shim::actors::market::{DealProposal, DealState};
+    use crate::shim::actors::miner::{DeadlineInfo, MinerPower};
+    use crate::shim::actors::power::Claim;
+    use crate::shim::message::Message;
+    use quickcheck_macros::quickcheck;
+
+    #[quickcheck]
+    fn block_header_roundtrip(header: CachingBlockHeader) {
+        roundtrip(&header).unwrap();
+    }
+
+    #[quickcheck]
+    fn gossip_block_roundtrip(block: GossipBlock) {
+        roundtrip(&block).unwrap();
+    }
+
+    #[quickcheck]
+    fn message_roundtrip(message: Message) {
+        roundtrip(&message).unwrap();
+    }
+
+    #[quickcheck]
+    fn signed_message_roundtrip(message: SignedMessage) {
+        roundtrip(&message).unwrap();
+    }
+
+    #[quickcheck]
+    fn compacted_messages_roundtrip(messages: CompactedMessages) {
+        cbor_roundtrip(&messages).unwrap();
+    }
+
+    #[quickcheck]
+    fn tipset_bundle_roundtrip(bundle: TipsetBundle) {
+        cbor_roundtrip(&bundle).unwrap();
+    }
+
+    #[quickcheck]
+    fn full_tipset_roundtrip(fts: FullTipset) {
+        // Full tipsets are encoded as tipset bundles on the wire.
+        let bundle = TipsetBundle::from(fts.clone());
+        cbor_roundtrip(&bundle).unwrap();
+        assert_eq!(FullTipset::try_from(bundle).unwrap(), fts);
+    }
+
+    #[quickcheck]
+    fn deal_proposal_roundtrip(proposal: DealProposal) {
+        cbor_roundtrip(&proposal).unwrap();
+    }
+
+    #[quickcheck]
+    fn deal_state_roundtrip(state: DealState) {
+        cbor_roundtrip(&state).unwrap();
+    }
+
+    #[quickcheck]
+    fn deadline_info_roundtrip(info: DeadlineInfo) {
+        roundtrip(&info).unwrap();
+    }
+
+    #[quickcheck]
+    fn miner_power_roundtrip(power: MinerPower) {
+        roundtrip(&power).unwrap();
+    }
+
+    #[quickcheck]
+    fn power_claim_roundtrip(claim: Claim) {
+        roundtrip(&claim).unwrap();
+    }
+
+    #[quickcheck]
+    fn roundtrip_block_of_another_type(message: Message) {
+        let bytes = fvm_ipld_encoding::to_vec(&message).unwrap();
+        assert!(roundtrip_block::<Message>(&bytes).unwrap());
+        assert!(!roundtrip_block::<CachingBlockHeader>(&bytes).unwrap());
+    }
+}
@@ -9,33 +9,51 @@ use positioned_io::{RandomAccessFile, ReadAt, Size};
 use crate::utils::misc::env::is_env_truthy;
 
 /// Wrapper type of [`memmap2::Mmap`] that implements [`ReadAt`] and [`Size`]
-pub struct Mmap(memmap2::Mmap);
+pub struct Mmap {
+    inner: memmap2::Mmap,
+    zero_copy: bool,
+}
 
 impl Mmap {
     pub fn map(file: impl MmapAsRawDesc) -> io::Result<Self> {
-        Ok(Self(unsafe { memmap2::Mmap::map(file)? }))
+        Ok(Self {
+            inner: unsafe { memmap2::Mmap::map(file)? },
+            zero_copy: should_use_zero_copy(),
+        })
     }
 
     pub fn map_path(path: impl AsRef<Path>) -> io::Result<Self> {
         Self::map(&fs::File::open(path.as_ref())?)
     }
+
+    /// Enables or disables the zero-copy read path, see [`Mmap::zero_copy_bytes`].
+    pub fn with_zero_copy(mut self, zero_copy: bool) -> Self {
+        self.zero_copy = zero_copy;
+        self
+    }
+
+    /// Returns the mapped bytes if the zero-copy read path is enabled, so that the blocks can be
+    /// decoded in place instead of being copied out with [`ReadAt::read_at`] first.
+    pub fn zero_copy_bytes(&self) -> Option<&[u8]> {
+        self.zero_copy.then_some(&self.inner[..])
+    }
 }
 
 impl ReadAt for Mmap {
     #[allow(clippy::indexing_slicing)]
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         let start = pos as usize;
-        if start >= self.0.len() {
+        if start >= self.inner.len() {
             // This matches the behaviour for seeking past the end of a file
             return Ok(0);
         }
         let end = start + buf.len();
-        if end <= self.0.len() {
-            buf.copy_from_slice(&self.0[start..end]);
+        if end <= self.inner.len() {
+            buf.copy_from_slice(&self.inner[start..end]);
             Ok(buf.len())
         } else {
-            let len = self.0.len() - start;
-            buf[..len].copy_from_slice(&self.0[start..]);
+            let len = self.inner.len() - start;
+            buf[..len].copy_from_slice(&self.inner[start..]);
             Ok(len)
         }
     }
@@ -43,7 +61,7 @@ impl ReadAt for Mmap {
 
 impl Size for Mmap {
     fn size(&self) -> io::Result<Option<u64>> {
-        Ok(Some(self.0.len() as _))
+        Ok(Some(self.inner.len() as _))
     }
 }
 
@@ -87,6 +105,12 @@ fn should_use_file_io() -> bool {
     is_env_truthy("FOREST_CAR_LOADER_FILE_IO")
 }
 
+fn should_use_zero_copy() -> bool {
+    // Copy the blocks out of the map by default, read them in place when
+    // `FOREST_CAR_LOADER_ZERO_COPY` is set to `1` or `true`
+    is_env_truthy("FOREST_CAR_LOADER_ZERO_COPY")
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -101,6 +125,10 @@ mod tests {
         let mmap = Mmap::map(&fs::File::open(&tmp)?)?;
 
         assert_eq!(mmap.size()?.unwrap_or_default() as usize, bytes.len());
+        let mmap = mmap.with_zero_copy(false);
+        assert_eq!(mmap.zero_copy_bytes(), None);
+        let mmap = mmap.with_zero_copy(true);
+        assert_eq!(mmap.zero_copy_bytes(), Some(&bytes[..]));
 
         let mut buffer = [0; 128];
         for pos in 0..bytes.len() {
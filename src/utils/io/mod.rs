@@ -12,7 +12,7 @@ use std::{
     path::Path,
 };
 
-pub use mmap::EitherMmapOrRandomAccessFile;
+pub use mmap::{EitherMmapOrRandomAccessFile, Mmap};
 pub use progress_log::WithProgress;
 pub use writer_checksum::*;
 
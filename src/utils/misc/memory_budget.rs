@@ -0,0 +1,122 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Memory budget of the process, from which the caches and buffers are sized instead of fixed
+//! constants. The default sizes of the caches and buffers were tuned for
+//! [`REFERENCE_MEMORY_BUDGET`], which is also the default budget, and are scaled linearly from it
+//! when the budget is set with `--memory-budget`. The sizes of the main caches can also be set
+//! explicitly with environment variables.
+
+use std::num::NonZeroUsize;
+use std::sync::OnceLock;
+
+use anyhow::Context as _;
+
+const GIB: u64 = 1 << 30;
+/// Memory budget the default sizes of the caches and buffers are tuned for.
+pub const REFERENCE_MEMORY_BUDGET: u64 = 8 * GIB;
+
+static MEMORY_BUDGET: OnceLock<u64> = OnceLock::new();
+
+/// Overrides the memory budget. It has to be called before any cache or buffer is sized, later
+/// overrides are ignored.
+pub fn set_memory_budget(bytes: u64) {
+    if MEMORY_BUDGET.set(bytes).is_err() {
+        tracing::warn!(
+            "Memory budget is already set to {}, ignoring the override",
+            human_bytes::human_bytes(memory_budget() as f64)
+        );
+    }
+}
+
+/// Returns the memory budget of the process, in bytes, [`REFERENCE_MEMORY_BUDGET`] unless it was
+/// overridden.
+pub fn memory_budget() -> u64 {
+    *MEMORY_BUDGET.get_or_init(|| REFERENCE_MEMORY_BUDGET)
+}
+
+/// Scales a size tuned for [`REFERENCE_MEMORY_BUDGET`] to the memory budget, within `min..=max`.
+pub fn scale_to_memory_budget(reference: usize, min: usize, max: usize) -> usize {
+    scale(reference, memory_budget(), min, max)
+}
+
+/// Returns the size set with the environment variable `env_key`, or else scales a size tuned for
+/// [`REFERENCE_MEMORY_BUDGET`] to the memory budget, within `min..=max`.
+pub fn size_from_env_or_memory_budget(
+    env_key: &str,
+    reference: usize,
+    min: usize,
+    max: usize,
+) -> usize {
+    if let Ok(value) = std::env::var(env_key) {
+        match value.parse::<NonZeroUsize>() {
+            Ok(size) => {
+                tracing::info!("{env_key} is set to {size}");
+                return size.get();
+            }
+            Err(_) => tracing::error!(
+                "Failed to parse {env_key}={value}, value should be a positive integer"
+            ),
+        }
+    }
+    scale_to_memory_budget(reference, min, max)
+}
+
+fn scale(reference: usize, budget: u64, min: usize, max: usize) -> usize {
+    let scaled = reference as u128 * budget as u128 / REFERENCE_MEMORY_BUDGET as u128;
+    usize::try_from(scaled)
+        .unwrap_or(usize::MAX)
+        .clamp(min, max)
+}
+
+/// Parses a memory size, in bytes or with a binary unit suffix, e.g. `8GiB` or `512M`.
+pub fn parse_memory_size(s: &str) -> anyhow::Result<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value
+        .parse()
+        .with_context(|| format!("invalid memory size: {s}"))?;
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kib" => 10,
+        "m" | "mib" => 20,
+        "g" | "gib" => 30,
+        "t" | "tib" => 40,
+        _ => anyhow::bail!("invalid memory size unit: {unit}"),
+    };
+    value
+        .checked_mul(1 << shift)
+        .with_context(|| format!("memory size is too large: {s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_sizes() {
+        assert_eq!(scale(1000, REFERENCE_MEMORY_BUDGET, 0, usize::MAX), 1000);
+        assert_eq!(
+            scale(1000, 2 * REFERENCE_MEMORY_BUDGET, 0, usize::MAX),
+            2000
+        );
+        assert_eq!(scale(1000, REFERENCE_MEMORY_BUDGET / 4, 0, usize::MAX), 250);
+        assert_eq!(
+            scale(1000, REFERENCE_MEMORY_BUDGET / 4, 500, usize::MAX),
+            500
+        );
+        assert_eq!(scale(1000, 4 * REFERENCE_MEMORY_BUDGET, 0, 3000), 3000);
+    }
+
+    #[test]
+    fn parse_memory_sizes() {
+        assert_eq!(parse_memory_size("1024").unwrap(), 1024);
+        assert_eq!(parse_memory_size("512M").unwrap(), 512 << 20);
+        assert_eq!(parse_memory_size("8GiB").unwrap(), 8 * GIB);
+        assert_eq!(parse_memory_size("2 tib").unwrap(), 2 << 40);
+        assert!(parse_memory_size("8GB").is_err());
+        assert!(parse_memory_size("GiB").is_err());
+        assert!(parse_memory_size("99999999999T").is_err());
+    }
+}
@@ -8,6 +8,7 @@ pub use adaptive_value_provider::*;
 mod logo;
 pub use logo::*;
 pub mod env;
+pub mod memory_budget;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, strum::EnumString)]
 #[strum(serialize_all = "kebab-case")]
@@ -3,6 +3,8 @@
 
 mod download_file;
 pub use download_file::*;
+mod tls;
+pub use tls::*;
 
 use crate::utils::io::WithProgress;
 use crate::utils::reqwest_resume;
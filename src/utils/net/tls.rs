@@ -0,0 +1,211 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! TLS termination of the RPC and metrics endpoints, so that they can be exposed without a
+//! reverse proxy.
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio::time::error::Elapsed;
+use tokio_rustls::rustls::{
+    self, RootCertStore, ServerConfig,
+    crypto::CryptoProvider,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject as _},
+    server::WebPkiClientVerifier,
+};
+use tokio_rustls::{TlsAcceptor, server::TlsStream};
+
+use crate::cli_shared::cli::TlsConfig;
+
+/// Time given to clients to complete the handshake.
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of handshakes in progress, no connection is accepted beyond it.
+const MAX_PENDING_TLS_HANDSHAKES: usize = 256;
+
+type Handshake = (
+    Result<io::Result<TlsStream<TcpStream>>, Elapsed>,
+    SocketAddr,
+);
+
+/// Creates a TLS acceptor with the certificate and the key of the configuration, which requires
+/// client certificates if the configuration has certificate authorities for them.
+pub fn tls_acceptor(config: &TlsConfig) -> anyhow::Result<TlsAcceptor> {
+    // Both `ring` and `aws-lc-rs` are enabled in the dependency graph, so the provider can't be
+    // inferred.
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let certs = read_certs(&config.cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(&config.key_path)
+        .with_context(|| format!("failed to read TLS key {}", config.key_path.display()))?;
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match &config.client_ca_path {
+        Some(path) => builder.with_client_cert_verifier(client_verifier(path, provider)?),
+        None => builder.with_no_client_auth(),
+    };
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .context("invalid TLS certificate or key")?;
+    // WebSocket upgrades are only supported over HTTP/1.1.
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+fn client_verifier(
+    path: &Path,
+    provider: Arc<CryptoProvider>,
+) -> anyhow::Result<Arc<dyn rustls::server::danger::ClientCertVerifier>> {
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(path)? {
+        roots.add(cert)?;
+    }
+    Ok(WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?)
+}
+
+fn read_certs(path: &Path) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("failed to read TLS certificates {}", path.display()))?;
+    anyhow::ensure!(!certs.is_empty(), "no certificate in {}", path.display());
+    Ok(certs)
+}
+
+/// TCP listener for `axum` that completes the TLS handshakes of the connections it accepts.
+/// The handshakes run in their own tasks, so that a slow client does not hold back the others.
+/// Connections that fail the handshake are dropped.
+pub struct TlsListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshakes: JoinSet<Handshake>,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, acceptor: TlsAcceptor) -> Self {
+        Self {
+            listener,
+            acceptor,
+            handshakes: JoinSet::new(),
+        }
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            tokio::select! {
+                (stream, addr) = axum::serve::Listener::accept(&mut self.listener),
+                    if self.handshakes.len() < MAX_PENDING_TLS_HANDSHAKES =>
+                {
+                    let acceptor = self.acceptor.clone();
+                    self.handshakes.spawn(async move {
+                        let handshake =
+                            tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream));
+                        (handshake.await, addr)
+                    });
+                }
+                Some(handshake) = self.handshakes.join_next() => match handshake {
+                    Ok((Ok(Ok(stream)), addr)) => return (stream, addr),
+                    Ok((Ok(Err(e)), addr)) => {
+                        tracing::debug!("TLS handshake with {addr} failed: {e}")
+                    }
+                    Ok((Err(_), addr)) => tracing::debug!("TLS handshake with {addr} timed out"),
+                    Err(e) => tracing::debug!("TLS handshake task failed: {e}"),
+                },
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.listener.local_addr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_tls_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::write(&cert_path, cert.pem()).unwrap();
+        std::fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+
+        let mut config = TlsConfig {
+            cert_path: cert_path.clone(),
+            key_path: key_path.clone(),
+            client_ca_path: None,
+        };
+        tls_acceptor(&config).unwrap();
+        // The certificate is its own authority.
+        config.client_ca_path = Some(cert_path.clone());
+        tls_acceptor(&config).unwrap();
+
+        config.key_path = cert_path;
+        assert!(tls_acceptor(&config).is_err());
+        config.key_path = dir.path().join("missing.pem");
+        assert!(tls_acceptor(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn stalled_handshake_does_not_block_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::write(&cert_path, cert.pem()).unwrap();
+        std::fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+        let acceptor = tls_acceptor(&TlsConfig {
+            cert_path,
+            key_path,
+            client_ca_path: None,
+        })
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut listener = TlsListener::new(listener, acceptor);
+
+        // A client that never sends its hello
+        let _stalled = TcpStream::connect(addr).await.unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+        let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            connector
+                .connect("localhost".try_into().unwrap(), stream)
+                .await
+                .unwrap()
+        });
+
+        let (_, peer) = tokio::time::timeout(
+            Duration::from_secs(5),
+            axum::serve::Listener::accept(&mut listener),
+        )
+        .await
+        .expect("the handshake should not wait for the stalled client");
+        let client = client.await.unwrap();
+        assert_eq!(peer, client.get_ref().0.local_addr().unwrap());
+    }
+}
@@ -3,6 +3,8 @@
 
 mod parameters;
 mod paramfetch;
+mod verification_pool;
 
 pub use parameters::maybe_set_proofs_parameter_cache_dir_env;
 pub use paramfetch::{SectorSizeOpt, ensure_proof_params_downloaded, get_params_default};
+pub use verification_pool::verify_on_pool;
@@ -0,0 +1,103 @@
+// Copyright 2019-2026 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! A dedicated thread pool for proof verification. Verifying proofs is CPU heavy, and on the
+//! threads of the runtime it would starve the RPC and gossip handling during the sync of
+//! proof-heavy epochs.
+
+use std::{panic::AssertUnwindSafe, sync::LazyLock, time::Instant};
+
+use anyhow::Context as _;
+use prometheus_client::metrics::{gauge::Gauge, histogram::Histogram};
+
+use crate::metrics::HistogramTimerExt as _;
+
+static PROOF_VERIFICATION_QUEUE_LENGTH: LazyLock<Gauge> = LazyLock::new(|| {
+    let metric = Gauge::default();
+    crate::metrics::default_registry().register(
+        "proof_verification_queue_length",
+        "Number of proof verifications waiting for a thread of the proof verification pool",
+        metric.clone(),
+    );
+    metric
+});
+
+static PROOF_VERIFICATION_QUEUE_TIME: LazyLock<Histogram> = LazyLock::new(|| {
+    let metric = crate::metrics::default_histogram();
+    crate::metrics::default_registry().register(
+        "proof_verification_queue_time",
+        "Duration proof verifications wait for a thread of the proof verification pool",
+        metric.clone(),
+    );
+    metric
+});
+
+static PROOF_VERIFICATION_TIME: LazyLock<Histogram> = LazyLock::new(|| {
+    let metric = crate::metrics::default_histogram();
+    crate::metrics::default_registry().register(
+        "proof_verification_time",
+        "Duration of proof verifications",
+        metric.clone(),
+    );
+    metric
+});
+
+static POOL: LazyLock<rayon::ThreadPool> = LazyLock::new(|| {
+    let num_threads = std::env::var("FOREST_PROOF_VERIFICATION_THREADS")
+        .ok()
+        .and_then(|it| it.parse().ok())
+        // Don't use all CPU, otherwise the verification will starve the rest of the system.
+        .unwrap_or_else(|| num_cpus::get() / 2)
+        .max(1);
+    rayon::ThreadPoolBuilder::new()
+        .thread_name(|id| format!("proof verification thread: {id}"))
+        .num_threads(num_threads)
+        .build()
+        .expect("failed to build the proof verification thread pool")
+});
+
+/// Runs a proof verification on the proof verification pool, and waits for its result without
+/// blocking the runtime. The verifications are queued while all the threads of the pool are
+/// busy. The parallel iterators of the verification also run on the pool.
+pub async fn verify_on_pool<T, F>(verify: F) -> anyhow::Result<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let queued_at = Instant::now();
+    PROOF_VERIFICATION_QUEUE_LENGTH.inc();
+    POOL.spawn(move || {
+        PROOF_VERIFICATION_QUEUE_LENGTH.dec();
+        PROOF_VERIFICATION_QUEUE_TIME.observe(queued_at.elapsed().as_secs_f64());
+        let result = {
+            let _timer = PROOF_VERIFICATION_TIME.start_timer();
+            std::panic::catch_unwind(AssertUnwindSafe(verify))
+        };
+        // The receiver is gone if the caller was cancelled.
+        let _ = tx.send(result);
+    });
+    rx.await
+        .context("proof verification pool is gone")?
+        .map_err(|_| anyhow::anyhow!("proof verification panicked"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn verify_on_pool_result() {
+        let thread_name = verify_on_pool(|| std::thread::current().name().map(str::to_owned))
+            .await
+            .unwrap();
+        assert!(
+            thread_name.is_some_and(|name| name.starts_with("proof verification thread")),
+            "verification did not run on the pool"
+        );
+
+        // A panicking verification fails instead of bringing the pool down.
+        assert!(verify_on_pool(|| panic!("invalid proof")).await.is_err());
+        assert_eq!(verify_on_pool(|| 42).await.unwrap(), 42);
+    }
+}